@@ -0,0 +1,178 @@
+//! Leaving the service: deactivation and scheduled deletion of an account.
+//!
+//! - `deactivate` hides the account (user directory, API keys) and signs the
+//!   user out everywhere. Signing in again reactivates it (`reactivate_on_login`).
+//! - `schedule_deletion` deactivates the account and sets its deletion date to
+//!   now + grace period. Signing in during the grace period cancels the deletion.
+//! - `purge_due`, run periodically by the server, deletes the accounts whose
+//!   grace period is over. Their messages stay in the conversations, and their
+//!   tasks, comments and task activity stay too, all attributed to the
+//!   `user:deleted` placeholder ("deleted user"). Assignments, watches, project
+//!   memberships, notifications and the calendar feed are dropped.
+//!
+//! Env:
+//! - ACCOUNT_DELETION_GRACE_DAYS (default 30)
+//! - ACCOUNT_PURGE_INTERVAL_SECONDS (default 3600)
+
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::error::AuthError;
+use crate::interfaces::repositories::account_deletion::AccountDeletionRepository;
+use crate::interfaces::repositories::audit_log::AuditLogRepository;
+use crate::models::entities::audit_event::{AuditAction, AuditEvent};
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct AccountDeletionService {
+    deletions: Arc<dyn AccountDeletionRepository>,
+    audit: Arc<dyn AuditLogRepository>,
+    grace_period: Duration,
+    purge_interval: std::time::Duration,
+}
+
+impl AccountDeletionService {
+    pub fn new(
+        deletions: Arc<dyn AccountDeletionRepository>,
+        audit: Arc<dyn AuditLogRepository>,
+        grace_period: Duration,
+        purge_interval: std::time::Duration,
+    ) -> Self {
+        Self {
+            deletions,
+            audit,
+            grace_period,
+            purge_interval,
+        }
+    }
+
+    /// Builds the service reading ACCOUNT_DELETION_GRACE_DAYS and
+    /// ACCOUNT_PURGE_INTERVAL_SECONDS.
+    pub fn from_env(
+        deletions: Arc<dyn AccountDeletionRepository>,
+        audit: Arc<dyn AuditLogRepository>,
+    ) -> Self {
+        let grace_days = env::var("ACCOUNT_DELETION_GRACE_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+        let interval_secs = env::var("ACCOUNT_PURGE_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(3600);
+        Self::new(
+            deletions,
+            audit,
+            Duration::days(grace_days),
+            std::time::Duration::from_secs(interval_secs),
+        )
+    }
+
+    /// How often the server should call `purge_due`.
+    pub fn purge_interval(&self) -> std::time::Duration {
+        self.purge_interval
+    }
+
+    /// Deactivates the account without scheduling its deletion.
+    pub async fn deactivate(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        let updated = users
+            .deactivate(&user_id, None)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!("Account deactivated by username={}", user.username);
+        Ok(updated)
+    }
+
+    /// Deactivates the account and schedules its deletion after the grace period.
+    pub async fn schedule_deletion(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        let delete_at = Utc::now() + self.grace_period;
+        let updated = users
+            .deactivate(&user_id, Some(delete_at))
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+
+        info!(
+            "Account deletion scheduled for username={} at {}",
+            user.username, delete_at
+        );
+        self.audit_event(
+            AuditAction::AccountDeletionScheduled,
+            &user_id,
+            format!("{} scheduled for {}", user.username, delete_at.to_rfc3339()),
+        )
+        .await;
+        Ok(updated)
+    }
+
+    /// Deletes every account whose grace period is over; returns how many.
+    /// A failing account is logged and retried on the next run.
+    pub async fn purge_due(&self) -> usize {
+        let due = match self.deletions.find_due(Utc::now()).await {
+            Ok(due) => due,
+            Err(e) => {
+                error!("Could not list accounts due for deletion: {:?}", e);
+                return 0;
+            }
+        };
+
+        let mut purged = 0;
+        for user_id in due {
+            let subject = user_id.id.to_raw();
+            if let Err(e) = self.deletions.purge(user_id).await {
+                error!("Could not delete account {}: {:?}", subject, e);
+                continue;
+            }
+            info!("Account {} deleted after its grace period", subject);
+            self.audit_event(
+                AuditAction::AccountDeleted,
+                &subject,
+                "grace period over".to_string(),
+            )
+            .await;
+            purged += 1;
+        }
+        purged
+    }
+
+    async fn audit_event(&self, action: AuditAction, subject: &str, detail: String) {
+        // The state change already happened; a failed audit write is only logged
+        if let Err(e) = self
+            .audit
+            .record(AuditEvent::new(action, subject, None, Some(detail)))
+            .await
+        {
+            error!("Could not write audit event: {:?}", e);
+        }
+    }
+}
+
+/// Reactivates a deactivated account once its owner has fully signed in again,
+/// cancelling a scheduled deletion. Other accounts are returned unchanged.
+pub async fn reactivate_on_login(users: &dyn UserDataTrait, user: User) -> Result<User, AuthError> {
+    if !user.is_deactivated() {
+        return Ok(user);
+    }
+    let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+    let reactivated = users.reactivate(&user_id).await.ok_or_else(|| {
+        warn!("Could not reactivate username={}", user.username);
+        AuthError::DatabaseError
+    })?;
+    info!(
+        "Account reactivated by sign-in: username={}",
+        reactivated.username
+    );
+    Ok(reactivated)
+}
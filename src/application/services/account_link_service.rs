@@ -0,0 +1,308 @@
+//! Linking and unlinking wallets and external identities on one account.
+//!
+//! Wallet linking is a challenge-response:
+//! 1. `wallet_challenge` stores a random nonce bound to the user and the address
+//!    (as a `wallet_link` one-time token) and returns the message to sign;
+//! 2. `link_wallet` consumes the nonce and checks the EIP-191 signature of the
+//!    same message. A wallet already attached to another account is refused
+//!    (`WalletAlreadyLinked`); the accounts can be merged instead.
+//!
+//! Signing in with a wallet uses the same kind of challenge (`login_challenge`,
+//! then `verify_login`), issued for the address alone because the account may
+//! not exist yet. The login handler resolves the account afterwards.
+//!
+//! External identities are linked through the OIDC flow
+//! (`OidcService::start_link`). Unlinking either kind of credential is refused
+//! when it is the last way to sign in to the account.
+//!
+//! Env:
+//! - WALLET_CHALLENGE_TTL_SECONDS (default 300)
+
+use chrono::Duration;
+use log::{error, info, warn};
+use serde::Serialize;
+use std::env;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::infrastructure::auth::wallet::{normalize_address, verify_personal_sign};
+use crate::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+use crate::models::entities::user::User;
+use crate::models::entities::wallet::LinkedWallet;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Challenge handed to the client for signing
+#[derive(Debug, Serialize)]
+pub struct WalletChallenge {
+    /// Lower-case address being linked or signed in with
+    pub wallet: String,
+    /// Nonce to send back with the signature
+    pub nonce: String,
+    /// Exact text to sign with `personal_sign`
+    pub message: String,
+    /// Seconds until the challenge expires
+    pub expires_in: i64,
+}
+
+pub struct AccountLinkService {
+    token_repo: Arc<dyn OneTimeTokenRepository>,
+    challenge_ttl: Duration,
+}
+
+impl AccountLinkService {
+    pub fn new(token_repo: Arc<dyn OneTimeTokenRepository>, challenge_ttl: Duration) -> Self {
+        Self {
+            token_repo,
+            challenge_ttl,
+        }
+    }
+
+    /// Builds the service reading WALLET_CHALLENGE_TTL_SECONDS.
+    pub fn from_env(token_repo: Arc<dyn OneTimeTokenRepository>) -> Self {
+        let ttl_secs = env::var("WALLET_CHALLENGE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(300);
+        Self::new(token_repo, Duration::seconds(ttl_secs))
+    }
+
+    /// Issues a challenge for linking `wallet` to `user`.
+    pub async fn wallet_challenge(
+        &self,
+        user: &User,
+        wallet: &str,
+    ) -> Result<WalletChallenge, AuthError> {
+        let user_id = user.id.clone().ok_or(AuthError::UserNotFound)?;
+        let wallet = normalize_wallet(wallet)?;
+        if let Err(e) = self
+            .token_repo
+            .purge_expired(TokenPurpose::WalletLink)
+            .await
+        {
+            warn!("Could not purge expired wallet challenges: {:?}", e);
+        }
+
+        let (nonce, _) = new_opaque_token();
+        self.token_repo
+            .create(OneTimeToken::new(
+                user_id,
+                TokenPurpose::WalletLink,
+                challenge_hash(&wallet, &nonce),
+                self.challenge_ttl,
+            ))
+            .await
+            .map_err(db_error)?;
+
+        Ok(WalletChallenge {
+            message: challenge_message(user, &wallet, &nonce),
+            wallet,
+            nonce,
+            expires_in: self.challenge_ttl.num_seconds(),
+        })
+    }
+
+    /// Links `wallet` after checking the signed challenge.
+    pub async fn link_wallet(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        wallet: &str,
+        nonce: &str,
+        signature: &str,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        let wallet = normalize_wallet(wallet)?;
+
+        // The challenge is spent even if the signature turns out to be wrong
+        self.token_repo
+            .consume(TokenPurpose::WalletLink, &challenge_hash(&wallet, nonce))
+            .await
+            .map_err(db_error)?
+            .filter(|t| user.id.as_ref() == Some(&t.user_id))
+            .ok_or(AuthError::InvalidOrExpiredToken)?;
+        verify_personal_sign(&wallet, &challenge_message(user, &wallet, nonce), signature)
+            .map_err(|e| {
+                warn!("Wallet link rejected for username={}: {}", user.username, e);
+                AuthError::InvalidWallet(e.to_string())
+            })?;
+
+        if let Some(owner) = users.find_user_by_wallet(&wallet).await {
+            if owner.id != user.id {
+                warn!(
+                    "Wallet {} already belongs to username={}",
+                    wallet, owner.username
+                );
+                return Err(AuthError::WalletAlreadyLinked);
+            }
+        }
+
+        let mut wallets: Vec<LinkedWallet> = user
+            .wallets
+            .iter()
+            .filter(|w| w.address != wallet)
+            .cloned()
+            .collect();
+        wallets.push(LinkedWallet::verified(&wallet));
+        let primary = user.wallet.clone().or_else(|| Some(wallet.clone()));
+
+        let updated = users
+            .set_wallets(&user_id, primary, wallets)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!("Wallet {} linked to username={}", wallet, user.username);
+        Ok(updated)
+    }
+
+    /// Issues a challenge for signing in with `wallet`.
+    pub async fn login_challenge(&self, wallet: &str) -> Result<WalletChallenge, AuthError> {
+        let wallet = normalize_wallet(wallet)?;
+        if let Err(e) = self
+            .token_repo
+            .purge_expired(TokenPurpose::WalletLogin)
+            .await
+        {
+            warn!("Could not purge expired wallet login challenges: {:?}", e);
+        }
+
+        let (nonce, _) = new_opaque_token();
+        self.token_repo
+            .create(OneTimeToken::new(
+                Thing::from(("wallet", wallet.as_str())),
+                TokenPurpose::WalletLogin,
+                challenge_hash(&wallet, &nonce),
+                self.challenge_ttl,
+            ))
+            .await
+            .map_err(db_error)?;
+
+        Ok(WalletChallenge {
+            message: login_message(&wallet, &nonce),
+            wallet,
+            nonce,
+            expires_in: self.challenge_ttl.num_seconds(),
+        })
+    }
+
+    /// Consumes a login challenge and checks its signature.
+    ///
+    /// # Returns
+    /// The normalized address, proven to be controlled by the caller.
+    pub async fn verify_login(
+        &self,
+        wallet: &str,
+        nonce: &str,
+        signature: &str,
+    ) -> Result<String, AuthError> {
+        let wallet = normalize_wallet(wallet)?;
+
+        // The challenge is spent even if the signature turns out to be wrong
+        self.token_repo
+            .consume(TokenPurpose::WalletLogin, &challenge_hash(&wallet, nonce))
+            .await
+            .map_err(db_error)?
+            .ok_or(AuthError::InvalidOrExpiredToken)?;
+        verify_personal_sign(&wallet, &login_message(&wallet, nonce), signature).map_err(|e| {
+            warn!("Wallet login rejected for {}: {}", wallet, e);
+            AuthError::InvalidWallet(e.to_string())
+        })?;
+        Ok(wallet)
+    }
+
+    /// Detaches a wallet; the primary wallet passes to the next linked one.
+    pub async fn unlink_wallet(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        wallet: &str,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        let wallet = normalize_wallet(wallet)?;
+        if !user.has_wallet(&wallet) {
+            return Err(AuthError::CredentialNotFound);
+        }
+        if user.login_method_count() <= 1 {
+            return Err(AuthError::LastLoginMethod);
+        }
+
+        let wallets: Vec<LinkedWallet> = user
+            .wallets
+            .iter()
+            .filter(|w| w.address != wallet)
+            .cloned()
+            .collect();
+        let primary = match &user.wallet {
+            Some(primary) if *primary != wallet => Some(primary.clone()),
+            _ => wallets.first().map(|w| w.address.clone()),
+        };
+
+        let updated = users
+            .set_wallets(&user_id, primary, wallets)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!("Wallet {} unlinked from username={}", wallet, user.username);
+        Ok(updated)
+    }
+
+    /// Detaches the external identity `subject` at `provider`.
+    pub async fn unlink_identity(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        provider: &str,
+        subject: &str,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        if !user.has_identity(provider, subject) {
+            return Err(AuthError::CredentialNotFound);
+        }
+        if user.login_method_count() <= 1 {
+            return Err(AuthError::LastLoginMethod);
+        }
+
+        let identities = user
+            .identities
+            .iter()
+            .filter(|i| !i.matches(provider, subject))
+            .cloned()
+            .collect();
+        let updated = users
+            .set_identities(&user_id, identities)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!(
+            "Identity ({}) unlinked from username={}",
+            provider, user.username
+        );
+        Ok(updated)
+    }
+}
+
+/// Text the wallet signs; binds the nonce to the account and the address.
+pub fn challenge_message(user: &User, wallet: &str, nonce: &str) -> String {
+    format!(
+        "Link wallet {} to Chasqui account {}.\n\nNonce: {}",
+        wallet, user.username, nonce
+    )
+}
+
+/// Text the wallet signs to sign in; distinct from the link message, so a
+/// signature for one cannot be replayed as the other.
+pub fn login_message(wallet: &str, nonce: &str) -> String {
+    format!("Sign in to Chasqui with wallet {}.\n\nNonce: {}", wallet, nonce)
+}
+
+fn challenge_hash(wallet: &str, nonce: &str) -> String {
+    hash_opaque_token(&format!("{}:{}", wallet, nonce))
+}
+
+fn normalize_wallet(wallet: &str) -> Result<String, AuthError> {
+    normalize_address(wallet).map_err(|e| AuthError::InvalidWallet(e.to_string()))
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("Wallet challenge repository error: {:?}", e);
+    AuthError::DatabaseError
+}
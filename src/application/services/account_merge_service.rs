@@ -0,0 +1,151 @@
+//! Merging a duplicate account into a primary one.
+//!
+//! A person who registered with e-mail and later signed in with a wallet ends
+//! up with two accounts. Merging moves everything from the duplicate (source)
+//! into the primary (target) and deletes the source, in one transaction:
+//! - wallets and external identities are added to the target;
+//! - a password and e-mail are carried over only if the target has none;
+//! - conversation memberships, sent messages and read receipts are reassigned;
+//! - created, assigned and watched tasks, task comments and activity, project
+//!   ownership and memberships and notifications are reassigned;
+//! - the calendar feed of the source is kept only if the target has none;
+//! - API keys of the source are revoked.
+//!
+//! Roles, 2FA settings and the profile of the target are kept as they are, so a
+//! merge never grants the target new permissions.
+//!
+//! Self-service merges prove control of the source with a valid access token
+//! of that account (`source_from_token`); admins can merge any two accounts.
+//! Every merge is recorded in the audit log.
+
+use log::{error, info, warn};
+use std::sync::Arc;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::jwt::validate_token;
+use crate::interfaces::repositories::account_merge::AccountMergeRepository;
+use crate::interfaces::repositories::audit_log::AuditLogRepository;
+use crate::models::entities::audit_event::{AuditAction, AuditEvent};
+use crate::models::entities::user::User;
+use crate::models::entities::wallet::LinkedWallet;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct AccountMergeService {
+    merges: Arc<dyn AccountMergeRepository>,
+    audit: Arc<dyn AuditLogRepository>,
+}
+
+impl AccountMergeService {
+    pub fn new(
+        merges: Arc<dyn AccountMergeRepository>,
+        audit: Arc<dyn AuditLogRepository>,
+    ) -> Self {
+        Self { merges, audit }
+    }
+
+    /// Resolves the account a (non-revoked) access token was issued for.
+    pub async fn source_from_token(
+        &self,
+        users: &dyn UserDataTrait,
+        token: &str,
+    ) -> Result<User, AuthError> {
+        let claims = validate_token(token).map_err(|e| {
+            warn!("Merge rejected: invalid source token: {}", e);
+            AuthError::InvalidOrExpiredToken
+        })?;
+        users
+            .find_user_by_id(&claims.sub)
+            .await
+            .filter(|u| u.token_version == claims.ver && !u.blocked)
+            .ok_or(AuthError::InvalidOrExpiredToken)
+    }
+
+    /// Merges `source` into `target` on behalf of `actor` and returns the
+    /// updated target.
+    pub async fn merge(
+        &self,
+        source: &User,
+        target: &User,
+        actor: &User,
+    ) -> Result<User, AuthError> {
+        let (source_id, target_id) = match (&source.id, &target.id) {
+            (Some(source_id), Some(target_id)) => (source_id.clone(), target_id.clone()),
+            _ => return Err(AuthError::UserNotFound),
+        };
+        if source_id == target_id {
+            return Err(AuthError::InvalidMerge(
+                "cannot merge an account into itself".to_string(),
+            ));
+        }
+        if source.bot || target.bot {
+            return Err(AuthError::InvalidMerge(
+                "bot accounts cannot be merged".to_string(),
+            ));
+        }
+
+        let merged = merged_credentials(source, target);
+        self.merges
+            .merge(source_id.clone(), merged.clone())
+            .await
+            .map_err(|e| {
+                error!("Account merge failed: {:?}", e);
+                AuthError::DatabaseError
+            })?;
+
+        info!(
+            "Account {} merged into {} by {}",
+            source.username, target.username, actor.username
+        );
+        let event = AuditEvent::new(
+            AuditAction::AccountMerged,
+            &target_id.to_string(),
+            None,
+            Some(format!(
+                "{} ({}) merged by {}",
+                source_id, source.username, actor.username
+            )),
+        );
+        // The merge already happened; a failed audit write is only logged
+        if let Err(e) = self.audit.record(event).await {
+            error!("Could not write audit event: {:?}", e);
+        }
+        Ok(merged)
+    }
+}
+
+// The target with the source's wallets and identities added, and the source's
+// password / e-mail where the target has none
+fn merged_credentials(source: &User, target: &User) -> User {
+    let mut merged = target.clone();
+
+    for address in source.wallet_addresses() {
+        if merged.has_wallet(&address) {
+            continue;
+        }
+        let linked = source
+            .wallets
+            .iter()
+            .find(|w| w.address == address)
+            .cloned()
+            .unwrap_or_else(|| LinkedWallet::unverified(&address));
+        merged.wallets.push(linked);
+    }
+    if merged.wallet.is_none() {
+        merged.wallet = merged.wallets.first().map(|w| w.address.clone());
+    }
+
+    for identity in &source.identities {
+        if !merged.has_identity(&identity.provider, &identity.subject) {
+            merged.identities.push(identity.clone());
+        }
+    }
+
+    if merged.password.is_none() {
+        merged.password = source.password.clone();
+    }
+    if merged.email.is_none() && source.email.is_some() {
+        merged.email = source.email.clone();
+        merged.email_verified = source.email_verified;
+    }
+    merged
+}
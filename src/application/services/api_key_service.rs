@@ -0,0 +1,174 @@
+//! Personal API keys and bot credentials.
+//!
+//! Keys are created for the caller itself or, by an admin, for a bot account.
+//! Each scope must be a permission the key's user currently holds, optionally
+//! restricted to one conversation (`message:create` on `conversation:<uuid>`).
+//! At request time the effective permissions are the intersection of the key
+//! scopes and the user's current roles, so demoting a user also narrows its keys.
+//!
+//! The full key (`chq_<prefix>_<secret>`) is returned once by `create`; only its
+//! SHA-256 digest is stored.
+
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::interfaces::repositories::api_key::ApiKeyRepository;
+use crate::models::entities::api_key::{is_api_key, ApiKey, ApiKeyScope, API_KEY_PREFIX};
+use crate::models::entities::user::User;
+
+/// Hex characters of the public key prefix
+const PREFIX_LEN: usize = 8;
+
+pub struct ApiKeyService {
+    keys: Arc<dyn ApiKeyRepository>,
+}
+
+impl ApiKeyService {
+    pub fn new(keys: Arc<dyn ApiKeyRepository>) -> Self {
+        Self { keys }
+    }
+
+    /// Creates a key acting as `owner`.
+    ///
+    /// # Returns
+    /// The stored key and the plaintext key, which is not retrievable later.
+    pub async fn create(
+        &self,
+        owner: &User,
+        created_by: &User,
+        name: &str,
+        scopes: Vec<ApiKeyScope>,
+        expires_in_days: Option<i64>,
+    ) -> Result<(ApiKey, String), AuthError> {
+        let (owner_id, creator_id) = match (owner.id.clone(), created_by.id.clone()) {
+            (Some(owner_id), Some(creator_id)) => (owner_id, creator_id),
+            _ => return Err(AuthError::UserNotFound),
+        };
+        validate_scopes(owner, &scopes)?;
+        let ttl = match expires_in_days {
+            Some(days) if days <= 0 => {
+                return Err(AuthError::InvalidApiKeyScope(
+                    "expires_in_days must be positive".to_string(),
+                ))
+            }
+            Some(days) => Some(Duration::days(days)),
+            None => None,
+        };
+
+        let (lookup, _) = new_opaque_token();
+        let (secret, _) = new_opaque_token();
+        let prefix = format!("{}{}", API_KEY_PREFIX, &lookup[..PREFIX_LEN]);
+        let plaintext = format!("{}_{}", prefix, secret);
+
+        let key = ApiKey::new(
+            owner_id,
+            creator_id,
+            name.trim().to_string(),
+            prefix,
+            hash_opaque_token(&plaintext),
+            scopes,
+            ttl,
+        );
+        if !key.is_valid() {
+            return Err(AuthError::InvalidApiKeyScope(
+                "name must be 1-64 characters".to_string(),
+            ));
+        }
+
+        let key = self.keys.create(key).await.map_err(db_error)?;
+        info!(
+            "API key {} created for {} by {}",
+            key.prefix, owner.username, created_by.username
+        );
+        Ok((key, plaintext))
+    }
+
+    /// Resolves a presented key; rejects unknown, expired and revoked keys.
+    pub async fn authenticate(&self, presented: &str) -> Result<ApiKey, AuthError> {
+        if !is_api_key(presented) {
+            return Err(AuthError::InvalidApiKey);
+        }
+        let key = self
+            .keys
+            .find_by_hash(&hash_opaque_token(presented))
+            .await
+            .map_err(db_error)?
+            .ok_or(AuthError::InvalidApiKey)?;
+
+        let now = Utc::now();
+        if !key.is_active_at(now) {
+            warn!("Inactive API key {} presented", key.prefix);
+            return Err(AuthError::InvalidApiKey);
+        }
+        if let Some(id) = key.id.clone() {
+            // Usage tracking never blocks the request
+            if let Err(e) = self.keys.touch(id, now).await {
+                error!("Could not record API key use: {:?}", e);
+            }
+        }
+        Ok(key)
+    }
+
+    /// Keys acting as `user_id`, newest first.
+    pub async fn list(&self, user_id: Thing) -> Result<Vec<ApiKey>, AuthError> {
+        self.keys.list_for_user(user_id).await.map_err(db_error)
+    }
+
+    /// Revokes a key of `caller` (any key if `caller` is an admin).
+    pub async fn revoke(&self, key_id: &str, caller: &User) -> Result<(), AuthError> {
+        let key_id = Thing::from(("api_key", key_id));
+        let key = self
+            .keys
+            .find(key_id.clone())
+            .await
+            .map_err(db_error)?
+            .ok_or(AuthError::ApiKeyNotFound)?;
+        if caller.id.as_ref() != Some(&key.user_id) && !caller.is_admin() {
+            return Err(AuthError::ApiKeyNotFound);
+        }
+        self.keys
+            .revoke(key_id, Utc::now())
+            .await
+            .map_err(db_error)?;
+        info!("API key {} revoked by {}", key.prefix, caller.username);
+        Ok(())
+    }
+}
+
+// Each scope must be held by the owner; conversation ids are `conversation:<id>`
+fn validate_scopes(owner: &User, scopes: &[ApiKeyScope]) -> Result<(), AuthError> {
+    if scopes.is_empty() {
+        return Err(AuthError::InvalidApiKeyScope(
+            "at least one scope is required".to_string(),
+        ));
+    }
+    for scope in scopes {
+        if !owner.has_permission(scope.permission) {
+            return Err(AuthError::InvalidApiKeyScope(format!(
+                "{} does not hold {}",
+                owner.username, scope.permission
+            )));
+        }
+        if let Some(conversation_id) = &scope.conversation_id {
+            let valid = conversation_id
+                .strip_prefix("conversation:")
+                .is_some_and(|id| !id.is_empty());
+            if !valid {
+                return Err(AuthError::InvalidApiKeyScope(format!(
+                    "invalid conversation_id {}",
+                    conversation_id
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("API key repository error: {:?}", e);
+    AuthError::DatabaseError
+}
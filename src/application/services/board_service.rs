@@ -0,0 +1,664 @@
+//! Projects and Kanban boards built on tasks.
+//!
+//! - Projects are visible to their owner and members; only the owner adds members.
+//! - Boards belong to a project. Every member can view them, edit their columns
+//!   and add, move or remove cards. Putting a task on a board requires seeing
+//!   the task, and shows it to the project members.
+//! - Columns are mapped to task statuses: moving a card into a column whose
+//!   status differs from the task's updates the task through `TaskService` (its
+//!   ownership rules and the blocker gate apply). The card is placed first; if
+//!   the task cannot take the status, the placement is undone and the task
+//!   error is returned.
+//! - A task whose status changes elsewhere (PATCH, the `/task` command,
+//!   recurrence) has its card shown in the first column of the new status, so
+//!   the board never contradicts the task.
+//! - Cards are ordered within a column by fractional index keys, so a move
+//!   rewrites only the moved card.
+//!
+//! Concurrency: every change is computed from the current board and stored only
+//! if the board version has not moved since (compare-and-set). When the client
+//! sends the `version` it saw, a stale version answers `VersionConflict`;
+//! otherwise the change is recomputed on the newer board, up to `MAX_ATTEMPTS`
+//! times. Moves name their neighbour cards rather than indexes, so they keep
+//! their meaning on a newer board.
+//!
+//! Changes are published (`with_events`) to the project members.
+
+use chrono::Utc;
+use log::{error, info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::application::services::task_service::{TaskAccess, TaskService};
+use crate::error::BoardError;
+use crate::interfaces::repositories::board::BoardRepository;
+use crate::models::entities::board::{
+    AddCardRequest, AddProjectMemberRequest, Board, BoardCard, BoardColumn, BoardEvent,
+    BoardEventKind, BoardView, CardPlacement, CardView, ColumnSpec, ColumnView, CreateBoardRequest,
+    CreateProjectRequest, Project, SetColumnsRequest, MAX_BOARD_COLUMNS,
+};
+use crate::models::entities::fractional_index::key_between;
+use crate::models::entities::task::{TaskStatus, UpdateTaskRequest};
+use crate::models::entities::user::User;
+use crate::models::traits::board_event_sink::BoardEventSink;
+use crate::models::traits::task_data_trait::TaskDataTrait;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Attempts at storing a change while other changes keep winning
+const MAX_ATTEMPTS: usize = 3;
+
+/// A card after a change, with the board version it produced
+#[derive(Debug, Clone, Serialize)]
+pub struct CardUpdate {
+    pub board_id: String,
+    pub version: u64,
+    pub card: BoardCard,
+}
+
+pub struct BoardService {
+    boards: Arc<dyn BoardRepository>,
+    tasks: Arc<TaskService>,
+    events: Option<Arc<dyn BoardEventSink>>,
+}
+
+impl BoardService {
+    pub fn new(boards: Arc<dyn BoardRepository>, tasks: Arc<TaskService>) -> Self {
+        Self {
+            boards,
+            tasks,
+            events: None,
+        }
+    }
+
+    /// Publishes board changes to `events` (the chat server in production).
+    pub fn with_events(mut self, events: Arc<dyn BoardEventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Creates a project owned by `owner`.
+    pub async fn create_project(
+        &self,
+        users: &dyn UserDataTrait,
+        owner: &User,
+        req: CreateProjectRequest,
+    ) -> Result<Project, BoardError> {
+        req.validate()
+            .map_err(|e| BoardError::InvalidBoard(e.to_string()))?;
+        let name = required_name(&req.name)?;
+        let owner_id = owner.id_string().ok_or(BoardError::DatabaseError)?;
+        let mut project = Project::new(name, owner_id);
+        for member in req.members {
+            if !project.is_member(&member) {
+                check_user(users, &member).await?;
+                project.members.push(member);
+            }
+        }
+        let created = self
+            .boards
+            .create_project(project)
+            .await
+            .map_err(db_error)?;
+        info!(
+            "Project {} created by username={}",
+            created.uuid, owner.username
+        );
+        Ok(created)
+    }
+
+    /// Projects `user` owns or is a member of.
+    pub async fn list_projects(&self, user: &User) -> Result<Vec<Project>, BoardError> {
+        let user_id = user.id_string().ok_or(BoardError::DatabaseError)?;
+        self.boards
+            .find_projects_by_user(&user_id)
+            .await
+            .map_err(db_error)
+    }
+
+    /// Adds a member to a project owned by `user`.
+    pub async fn add_member(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        project_id: &str,
+        req: AddProjectMemberRequest,
+    ) -> Result<Project, BoardError> {
+        let mut project = self.member_project(user, project_id).await?;
+        if user.id_string().as_deref() != Some(project.owner.as_str()) {
+            return Err(BoardError::NotAllowed);
+        }
+        if project.is_member(&req.user_id) {
+            return Ok(project);
+        }
+        check_user(users, &req.user_id).await?;
+        self.boards
+            .add_project_member(project_id, &req.user_id)
+            .await
+            .map_err(db_error)?;
+        info!("User {} added to project {}", req.user_id, project_id);
+        project.members.push(req.user_id);
+        Ok(project)
+    }
+
+    /// Creates a board in a project `user` takes part in.
+    pub async fn create_board(
+        &self,
+        user: &User,
+        project_id: &str,
+        req: CreateBoardRequest,
+    ) -> Result<Board, BoardError> {
+        req.validate()
+            .map_err(|e| BoardError::InvalidBoard(e.to_string()))?;
+        let name = required_name(&req.name)?;
+        let project = self.member_project(user, project_id).await?;
+        let columns = if req.columns.is_empty() {
+            Vec::new()
+        } else {
+            build_columns(&[], &req.columns)?
+        };
+        let board = self
+            .boards
+            .create_board(Board::new(project.uuid, name, columns))
+            .await
+            .map_err(db_error)?;
+        info!("Board {} created in project {}", board.uuid, project_id);
+        Ok(board)
+    }
+
+    /// Boards of a project `user` takes part in.
+    pub async fn list_boards(
+        &self,
+        user: &User,
+        project_id: &str,
+    ) -> Result<Vec<Board>, BoardError> {
+        self.member_project(user, project_id).await?;
+        self.boards
+            .find_boards_by_project(project_id)
+            .await
+            .map_err(db_error)
+    }
+
+    /// The board with its columns and cards in order; cards of deleted tasks are left out.
+    pub async fn get_board(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        board_id: &str,
+    ) -> Result<BoardView, BoardError> {
+        let (_, board) = self.member_board(user, board_id).await?;
+        let task_ids = board.cards.iter().map(|c| c.task_id.clone()).collect();
+        let found = tasks
+            .find_tasks(task_ids)
+            .await
+            .ok_or(BoardError::DatabaseError)?;
+        let statuses = found.iter().map(|t| (t.uuid.clone(), t.status)).collect();
+        let columns = board
+            .columns
+            .iter()
+            .map(|column| ColumnView {
+                column: column.clone(),
+                cards: board
+                    .column_cards(&column.id, &statuses)
+                    .into_iter()
+                    .filter_map(|card| {
+                        found
+                            .iter()
+                            .find(|t| t.uuid == card.task_id)
+                            .map(|task| CardView {
+                                card: card.clone(),
+                                task: task.clone(),
+                            })
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(BoardView {
+            uuid: board.uuid,
+            project_id: board.project_id,
+            name: board.name,
+            version: board.version,
+            columns,
+        })
+    }
+
+    /// Replaces the columns of a board; columns holding cards cannot be removed.
+    pub async fn set_columns(
+        &self,
+        user: &User,
+        board_id: &str,
+        req: SetColumnsRequest,
+    ) -> Result<Board, BoardError> {
+        req.validate()
+            .map_err(|e| BoardError::InvalidBoard(e.to_string()))?;
+        let (project, _) = self.member_board(user, board_id).await?;
+        let (board, ()) = self
+            .commit(board_id, req.version, |board| {
+                let columns = build_columns(&board.columns, &req.columns)?;
+                if board
+                    .cards
+                    .iter()
+                    .any(|card| !columns.iter().any(|c| c.id == card.column_id))
+                {
+                    return Err(BoardError::ColumnNotEmpty);
+                }
+                board.columns = columns;
+                Ok(())
+            })
+            .await?;
+        info!(
+            "Board {} columns changed by username={}",
+            board_id, user.username
+        );
+        self.publish(BoardEventKind::ColumnsChanged, &project, &board, None);
+        Ok(board)
+    }
+
+    /// Puts a task `user` can see on the board.
+    pub async fn add_card(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        access: TaskAccess,
+        board_id: &str,
+        req: AddCardRequest,
+    ) -> Result<CardUpdate, BoardError> {
+        let (project, board) = self.member_board(user, board_id).await?;
+        let task = self.tasks.get(tasks, user, access, &req.task_id).await?;
+        if board.cards.iter().any(|c| c.task_id == task.uuid) {
+            return Err(BoardError::TaskAlreadyOnBoard);
+        }
+        let placement = req.placement;
+        let column = match &placement.column_id {
+            Some(column_id) => board.column(column_id).ok_or(BoardError::ColumnNotFound)?,
+            None => board
+                .columns
+                .iter()
+                .find(|c| c.status == task.status)
+                .or(board.columns.first())
+                .ok_or(BoardError::ColumnNotFound)?,
+        }
+        .clone();
+        let statuses = self.task_statuses(tasks, &board).await?;
+        place(&board, &statuses, &column.id, None, &placement)?;
+
+        let (board, (card, column)) = self
+            .commit(board_id, placement.version, |board| {
+                if board.cards.iter().any(|c| c.task_id == task.uuid) {
+                    return Err(BoardError::TaskAlreadyOnBoard);
+                }
+                let column = board
+                    .column(&column.id)
+                    .ok_or(BoardError::ColumnNotFound)?
+                    .clone();
+                let card = BoardCard {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    task_id: task.uuid.clone(),
+                    column_id: column.id.clone(),
+                    position: place(board, &statuses, &column.id, None, &placement)?,
+                };
+                board.cards.push(card.clone());
+                Ok((card, column))
+            })
+            .await?;
+        if column.status != task.status {
+            if let Err(e) = self
+                .set_task_status(tasks, users, user, access, &task.uuid, &column)
+                .await
+            {
+                self.undo(board_id, &card, |board| {
+                    board.cards.retain(|c| c.id != card.id);
+                })
+                .await;
+                return Err(e);
+            }
+        }
+        info!("Task {} added to board {}", task.uuid, board_id);
+        self.publish(BoardEventKind::CardAdded, &project, &board, Some(&card));
+        Ok(CardUpdate {
+            board_id: board.uuid,
+            version: board.version,
+            card,
+        })
+    }
+
+    /// Moves a card within its column or to another one (between `after` / `before`).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn move_card(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        access: TaskAccess,
+        board_id: &str,
+        card_id: &str,
+        placement: CardPlacement,
+    ) -> Result<CardUpdate, BoardError> {
+        let (project, board) = self.member_board(user, board_id).await?;
+        let card = board.card(card_id).ok_or(BoardError::CardNotFound)?;
+        let statuses = self.task_statuses(tasks, &board).await?;
+        let column_id = placement
+            .column_id
+            .clone()
+            .unwrap_or_else(|| board.shown_column(card, &statuses).to_string());
+        board.column(&column_id).ok_or(BoardError::ColumnNotFound)?;
+        place(&board, &statuses, &column_id, Some(card_id), &placement)?;
+
+        let (board, (card, previous, status_change)) = self
+            .commit(board_id, placement.version, |board| {
+                let column = board
+                    .column(&column_id)
+                    .ok_or(BoardError::ColumnNotFound)?
+                    .clone();
+                let position = place(board, &statuses, &column_id, Some(card_id), &placement)?;
+                let previous = board.card(card_id).ok_or(BoardError::CardNotFound)?.clone();
+                // Reordering within columns of the task's status leaves the task
+                // alone; cards of deleted tasks only move
+                let status_change = statuses
+                    .get(&previous.task_id)
+                    .is_some_and(|status| *status != column.status)
+                    .then_some(column);
+                let card = board
+                    .cards
+                    .iter_mut()
+                    .find(|c| c.id == card_id)
+                    .ok_or(BoardError::CardNotFound)?;
+                card.column_id = column_id.clone();
+                card.position = position;
+                Ok((card.clone(), previous, status_change))
+            })
+            .await?;
+        if let Some(column) = status_change {
+            if let Err(e) = self
+                .set_task_status(tasks, users, user, access, &card.task_id, &column)
+                .await
+            {
+                self.undo(board_id, &card, |board| {
+                    if let Some(moved) = board.cards.iter_mut().find(|c| c.id == card.id) {
+                        moved.column_id = previous.column_id.clone();
+                        moved.position = previous.position.clone();
+                    }
+                })
+                .await;
+                return Err(e);
+            }
+        }
+        info!(
+            "Card {} moved on board {} by username={}",
+            card_id, board_id, user.username
+        );
+        self.publish(BoardEventKind::CardMoved, &project, &board, Some(&card));
+        Ok(CardUpdate {
+            board_id: board.uuid,
+            version: board.version,
+            card,
+        })
+    }
+
+    /// Takes a card off the board (the task is kept).
+    pub async fn remove_card(
+        &self,
+        user: &User,
+        board_id: &str,
+        card_id: &str,
+    ) -> Result<CardUpdate, BoardError> {
+        let (project, _) = self.member_board(user, board_id).await?;
+        let (board, card) = self
+            .commit(board_id, None, |board| {
+                let index = board
+                    .cards
+                    .iter()
+                    .position(|c| c.id == card_id)
+                    .ok_or(BoardError::CardNotFound)?;
+                Ok(board.cards.remove(index))
+            })
+            .await?;
+        info!("Card {} removed from board {}", card_id, board_id);
+        self.publish(BoardEventKind::CardRemoved, &project, &board, Some(&card));
+        Ok(CardUpdate {
+            board_id: board.uuid,
+            version: board.version,
+            card,
+        })
+    }
+
+    // Applies `change` to the current board and stores it if nobody else did meanwhile
+    async fn commit<T>(
+        &self,
+        board_id: &str,
+        expected_version: Option<u64>,
+        mut change: impl FnMut(&mut Board) -> Result<T, BoardError>,
+    ) -> Result<(Board, T), BoardError> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut board = self
+                .boards
+                .find_board(board_id)
+                .await
+                .map_err(db_error)?
+                .ok_or(BoardError::BoardNotFound)?;
+            if expected_version.is_some_and(|v| v != board.version) {
+                return Err(BoardError::VersionConflict);
+            }
+            let version = board.version;
+            let result = change(&mut board)?;
+            board.version = version + 1;
+            board.updated_at = Utc::now();
+            if let Some(saved) = self
+                .boards
+                .save_board(board, version)
+                .await
+                .map_err(db_error)?
+            {
+                return Ok((saved, result));
+            }
+            info!(
+                "Board {} changed concurrently (attempt {}/{})",
+                board_id, attempt, MAX_ATTEMPTS
+            );
+            if expected_version.is_some() {
+                break;
+            }
+        }
+        warn!("Board {} change gave up after conflicts", board_id);
+        Err(BoardError::VersionConflict)
+    }
+
+    // Reverts a card change whose task update failed, unless the card was
+    // changed again meanwhile
+    async fn undo(&self, board_id: &str, card: &BoardCard, mut revert: impl FnMut(&mut Board)) {
+        let result = self
+            .commit(board_id, None, |board| {
+                if board.card(&card.id) == Some(card) {
+                    revert(board);
+                }
+                Ok(())
+            })
+            .await;
+        if let Err(e) = result {
+            error!(
+                "Card {} on board {} could not be reverted: {:?}",
+                card.id, board_id, e
+            );
+        }
+    }
+
+    // Current status of the tasks on `board`, by task UUID
+    async fn task_statuses(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        board: &Board,
+    ) -> Result<HashMap<String, TaskStatus>, BoardError> {
+        let task_ids = board.cards.iter().map(|c| c.task_id.clone()).collect();
+        let found = tasks
+            .find_tasks(task_ids)
+            .await
+            .ok_or(BoardError::DatabaseError)?;
+        Ok(found.into_iter().map(|t| (t.uuid, t.status)).collect())
+    }
+
+    // Moves the task of a card to the status of `column`
+    async fn set_task_status(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        access: TaskAccess,
+        task_id: &str,
+        column: &BoardColumn,
+    ) -> Result<(), BoardError> {
+        let patch = UpdateTaskRequest {
+            status: Some(column.status),
+            ..UpdateTaskRequest::default()
+        };
+        self.tasks
+            .update(tasks, users, user, access, task_id, patch)
+            .await?;
+        Ok(())
+    }
+
+    fn publish(
+        &self,
+        kind: BoardEventKind,
+        project: &Project,
+        board: &Board,
+        card: Option<&BoardCard>,
+    ) {
+        if let Some(events) = &self.events {
+            events.publish(BoardEvent {
+                kind,
+                board: board.clone(),
+                card: card.cloned(),
+                audience: project.audience(),
+            });
+        }
+    }
+
+    // Loads a project, hiding it from non-members
+    async fn member_project(&self, user: &User, project_id: &str) -> Result<Project, BoardError> {
+        let project = self
+            .boards
+            .find_project(project_id)
+            .await
+            .map_err(db_error)?
+            .ok_or(BoardError::ProjectNotFound)?;
+        match user.id_string() {
+            Some(user_id) if project.is_member(&user_id) => Ok(project),
+            _ => Err(BoardError::ProjectNotFound),
+        }
+    }
+
+    // Loads a board and its project, hiding them from non-members
+    async fn member_board(
+        &self,
+        user: &User,
+        board_id: &str,
+    ) -> Result<(Project, Board), BoardError> {
+        let board = self
+            .boards
+            .find_board(board_id)
+            .await
+            .map_err(db_error)?
+            .ok_or(BoardError::BoardNotFound)?;
+        let project = self
+            .member_project(user, &board.project_id)
+            .await
+            .map_err(|_| BoardError::BoardNotFound)?;
+        Ok((project, board))
+    }
+}
+
+// Fractional index key for a card placed in `column_id` after / before a neighbour
+// (at the end by default); `moving` is left out of the column
+fn place(
+    board: &Board,
+    statuses: &HashMap<String, TaskStatus>,
+    column_id: &str,
+    moving: Option<&str>,
+    placement: &CardPlacement,
+) -> Result<String, BoardError> {
+    let cards: Vec<&BoardCard> = board
+        .column_cards(column_id, statuses)
+        .into_iter()
+        .filter(|c| Some(c.id.as_str()) != moving)
+        .collect();
+    let index_of = |id: &String| {
+        cards
+            .iter()
+            .position(|c| c.id == *id)
+            .ok_or(BoardError::CardNotFound)
+    };
+    let index = match (&placement.after, &placement.before) {
+        (Some(after), _) => index_of(after)? + 1,
+        (None, Some(before)) => index_of(before)?,
+        (None, None) => cards.len(),
+    };
+    let low = index.checked_sub(1).map(|i| cards[i].position.as_str());
+    let high = cards.get(index).map(|c| c.position.as_str());
+    // Equal neighbour keys (never written by this service) fall back to "after low"
+    key_between(low, high)
+        .or_else(|| key_between(low, None))
+        .ok_or_else(|| {
+            error!("Board {} has malformed card positions", board.uuid);
+            BoardError::DatabaseError
+        })
+}
+
+// Columns in the requested order, keeping the IDs of existing ones
+fn build_columns(
+    existing: &[BoardColumn],
+    specs: &[ColumnSpec],
+) -> Result<Vec<BoardColumn>, BoardError> {
+    if specs.is_empty() || specs.len() > MAX_BOARD_COLUMNS {
+        return Err(BoardError::InvalidBoard(format!(
+            "a board needs between 1 and {} columns",
+            MAX_BOARD_COLUMNS
+        )));
+    }
+    let mut columns: Vec<BoardColumn> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let name = required_name(&spec.name)?;
+        let id = match &spec.id {
+            Some(id) => {
+                if !existing.iter().any(|c| c.id == *id) {
+                    return Err(BoardError::ColumnNotFound);
+                }
+                if columns.iter().any(|c| c.id == *id) {
+                    return Err(BoardError::InvalidBoard(format!(
+                        "column {} listed twice",
+                        id
+                    )));
+                }
+                id.clone()
+            }
+            None => uuid::Uuid::new_v4().to_string(),
+        };
+        columns.push(BoardColumn {
+            id,
+            name,
+            status: spec.status,
+        });
+    }
+    Ok(columns)
+}
+
+fn required_name(name: &str) -> Result<String, BoardError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(BoardError::InvalidBoard("name required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+async fn check_user(users: &dyn UserDataTrait, user_id: &str) -> Result<(), BoardError> {
+    match users.find_user_by_id(user_id).await {
+        Some(user) if !user.is_deactivated() => Ok(()),
+        _ => Err(BoardError::UserNotFound),
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> BoardError {
+    error!("Board repository error: {:?}", e);
+    BoardError::DatabaseError
+}
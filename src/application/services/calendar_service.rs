@@ -0,0 +1,191 @@
+//! Calendar subscriptions and `.ics` imports of tasks.
+//!
+//! - `create_token` gives the caller a secret feed token (replacing any
+//!   previous one); `revoke_token` removes it. Only the token's SHA-256 digest
+//!   is stored.
+//! - `feed` answers the token's owner's visible tasks with a due date as an
+//!   iCalendar document (the first `CALENDAR_MAX_TASKS` due dates from
+//!   `FEED_LOOKBACK_DAYS` ago on), so calendar apps can subscribe without
+//!   signing in. Blocked and deactivated accounts
+//!   get no feed.
+//! - `import` creates one task per `VTODO` / `VEVENT` of an uploaded file, with
+//!   the same rules as `POST /api/tasks`; entries that cannot become a task
+//!   are reported as skipped and do not stop the import.
+//!
+//! Env:
+//! - CALENDAR_MAX_TASKS (default 500)
+//! - CALENDAR_MAX_IMPORT (default 500): Entries accepted per import
+
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::application::services::task_service::{TaskAccess, TaskService};
+use crate::error::TaskError;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::infrastructure::calendar::ics::{read_calendar, write_calendar, IcsComponent};
+use crate::interfaces::repositories::calendar_feed::CalendarFeedRepository;
+use crate::models::entities::calendar::{
+    CalendarFeed, CalendarFeedToken, ImportReport, SkippedEntry,
+};
+use crate::models::entities::user::User;
+use crate::models::traits::task_data_trait::TaskDataTrait;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Path of the feed, completed with `?token=`
+pub const CALENDAR_FEED_PATH: &str = "/api/tasks/calendar.ics";
+
+/// Days of past due dates a feed still shows
+pub const FEED_LOOKBACK_DAYS: i64 = 30;
+
+pub struct CalendarService {
+    feeds: Arc<dyn CalendarFeedRepository>,
+    tasks: Arc<TaskService>,
+    max_tasks: usize,
+    max_import: usize,
+}
+
+impl CalendarService {
+    pub fn new(
+        feeds: Arc<dyn CalendarFeedRepository>,
+        tasks: Arc<TaskService>,
+        max_tasks: usize,
+        max_import: usize,
+    ) -> Self {
+        Self {
+            feeds,
+            tasks,
+            max_tasks,
+            max_import,
+        }
+    }
+
+    /// Builds the service reading CALENDAR_MAX_TASKS and CALENDAR_MAX_IMPORT.
+    pub fn from_env(feeds: Arc<dyn CalendarFeedRepository>, tasks: Arc<TaskService>) -> Self {
+        let read = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        Self::new(
+            feeds,
+            tasks,
+            read("CALENDAR_MAX_TASKS", 500),
+            read("CALENDAR_MAX_IMPORT", 500),
+        )
+    }
+
+    /// Creates a feed token for `user`, replacing the previous one.
+    pub async fn create_token(&self, user: &User) -> Result<CalendarFeedToken, TaskError> {
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        let (token, token_hash) = new_opaque_token();
+        self.feeds
+            .upsert(CalendarFeed {
+                user_id,
+                token_hash,
+                created_at: Utc::now(),
+            })
+            .await
+            .map_err(db_error)?;
+        info!("Calendar feed token created for username={}", user.username);
+        Ok(CalendarFeedToken {
+            url: format!("{}?token={}", CALENDAR_FEED_PATH, token),
+            token,
+        })
+    }
+
+    /// Revokes the feed token of `user`, if any.
+    pub async fn revoke_token(&self, user: &User) -> Result<(), TaskError> {
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        self.feeds.delete(&user_id).await.map_err(db_error)?;
+        info!("Calendar feed token revoked for username={}", user.username);
+        Ok(())
+    }
+
+    /// The iCalendar document of the feed `token`.
+    pub async fn feed(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        token: &str,
+        component: IcsComponent,
+    ) -> Result<String, TaskError> {
+        if token.is_empty() {
+            return Err(TaskError::InvalidFeedToken);
+        }
+        let feed = self
+            .feeds
+            .find_by_token_hash(&hash_opaque_token(token))
+            .await
+            .map_err(db_error)?
+            .ok_or(TaskError::InvalidFeedToken)?;
+        let user = users
+            .find_user_by_id(&feed.user_id)
+            .await
+            .ok_or(TaskError::InvalidFeedToken)?;
+        if user.blocked || user.is_deactivated() {
+            warn!(
+                "Calendar feed of unavailable account username={}",
+                user.username
+            );
+            return Err(TaskError::InvalidFeedToken);
+        }
+        let since = Utc::now() - Duration::days(FEED_LOOKBACK_DAYS);
+        let due = self
+            .tasks
+            .due_tasks(tasks, &user, since, self.max_tasks)
+            .await?;
+        let name = format!("Chasqui tasks ({})", user.username);
+        Ok(write_calendar(&name, &due, component))
+    }
+
+    /// Creates a task for every `VTODO` / `VEVENT` of the iCalendar `text`.
+    pub async fn import(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        text: &str,
+    ) -> Result<ImportReport, TaskError> {
+        let entries = read_calendar(text).map_err(TaskError::InvalidCalendar)?;
+        if entries.len() > self.max_import {
+            return Err(TaskError::InvalidCalendar(format!(
+                "at most {} entries per import",
+                self.max_import
+            )));
+        }
+
+        let mut report = ImportReport::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let created = match entry.request {
+                Ok(req) => self.tasks.create(tasks, users, user, TaskAccess::default(), req).await,
+                Err(reason) => Err(TaskError::InvalidCalendar(reason)),
+            };
+            match created {
+                Ok(task) => report.created.push(task),
+                // A broken store would fail every entry alike
+                Err(TaskError::DatabaseError) => return Err(TaskError::DatabaseError),
+                Err(e) => report.skipped.push(SkippedEntry {
+                    index,
+                    summary: entry.summary,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        info!(
+            "Calendar import by username={}: {} created, {} skipped",
+            user.username,
+            report.created.len(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> TaskError {
+    error!("Calendar feed storage error: {:?}", e);
+    TaskError::DatabaseError
+}
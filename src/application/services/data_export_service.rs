@@ -0,0 +1,204 @@
+//! Personal data export (`GET /api/me/export`).
+//!
+//! Collects everything the service stores about a user and packs it as a ZIP
+//! archive of JSON files:
+//! - `profile.json`: account, linked credentials and profile (no password hash,
+//!   no 2FA secret);
+//! - `conversations.json`: the conversations the user takes part in;
+//! - `messages.json`: every message the user sent, oldest first.
+//!
+//! Ids are plain strings (`user:<uuid>`, `conversation:<uuid>`, ...).
+
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::AuthError;
+use crate::interfaces::repositories::conversation::ConversationRepository;
+use crate::interfaces::repositories::message::MessageRepository;
+use crate::models::entities::conversation::{Conversation, ConversationType};
+use crate::models::entities::identity::ExternalIdentity;
+use crate::models::entities::message::{Message, MessageType};
+use crate::models::entities::profile::UserProfile;
+use crate::models::entities::user::User;
+use crate::models::entities::wallet::LinkedWallet;
+
+/// Everything exported for one user
+#[derive(Debug, Serialize)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub profile: ExportedProfile,
+    pub conversations: Vec<ExportedConversation>,
+    pub messages: Vec<ExportedMessage>,
+}
+
+/// The account itself
+#[derive(Debug, Serialize)]
+pub struct ExportedProfile {
+    pub id: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub wallet: Option<String>,
+    pub wallets: Vec<LinkedWallet>,
+    pub identities: Vec<ExternalIdentity>,
+    pub roles: Vec<String>,
+    pub bot: bool,
+    pub mfa_enabled: bool,
+    #[serde(flatten)]
+    pub profile: UserProfile,
+}
+
+/// A conversation the user takes part in
+#[derive(Debug, Serialize)]
+pub struct ExportedConversation {
+    pub id: String,
+    pub conversation_type: ConversationType,
+    pub name: Option<String>,
+    pub participants: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A message the user sent
+#[derive(Debug, Serialize)]
+pub struct ExportedMessage {
+    pub id: String,
+    pub conversation_id: String,
+    pub content: String,
+    pub message_type: MessageType,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct DataExportService {
+    conversations: Arc<dyn ConversationRepository>,
+    messages: Arc<dyn MessageRepository>,
+}
+
+impl DataExportService {
+    pub fn new(
+        conversations: Arc<dyn ConversationRepository>,
+        messages: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            conversations,
+            messages,
+        }
+    }
+
+    /// Gathers the data of `user`.
+    pub async fn export(&self, user: &User) -> Result<AccountExport, AuthError> {
+        let user_id = user.id.clone().ok_or(AuthError::UserNotFound)?;
+        let conversations = self
+            .conversations
+            .find_by_user(user_id.clone())
+            .await
+            .map_err(db_error)?;
+        let messages = self
+            .messages
+            .find_by_sender(user_id)
+            .await
+            .map_err(db_error)?;
+
+        Ok(AccountExport {
+            exported_at: Utc::now(),
+            profile: ExportedProfile::from(user),
+            conversations: conversations.into_iter().map(Into::into).collect(),
+            messages: messages.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Gathers the data of `user` as a ZIP archive.
+    pub async fn export_zip(&self, user: &User) -> Result<Vec<u8>, AuthError> {
+        let export = self.export(user).await?;
+        to_zip(&export).map_err(|e| {
+            error!("Could not build export archive: {}", e);
+            AuthError::DatabaseError
+        })
+    }
+}
+
+/// Packs an export as `profile.json`, `conversations.json` and `messages.json`.
+pub fn to_zip(export: &AccountExport) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let files = [
+        ("profile.json", serde_json::to_vec_pretty(&export.profile)?),
+        (
+            "conversations.json",
+            serde_json::to_vec_pretty(&export.conversations)?,
+        ),
+        (
+            "messages.json",
+            serde_json::to_vec_pretty(&export.messages)?,
+        ),
+    ];
+    for (name, contents) in files {
+        zip.start_file(name, options)?;
+        zip.write_all(&contents)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+impl From<&User> for ExportedProfile {
+    fn from(user: &User) -> Self {
+        ExportedProfile {
+            id: user.id.as_ref().map(thing_string).unwrap_or_default(),
+            username: user.username.clone(),
+            email: user.email.clone(),
+            email_verified: user.email_verified,
+            wallet: user.wallet.clone(),
+            wallets: user.wallets.clone(),
+            identities: user.identities.clone(),
+            roles: user.role_names(),
+            bot: user.bot,
+            mfa_enabled: user.is_mfa_enabled(),
+            profile: user.profile.clone(),
+        }
+    }
+}
+
+impl From<Conversation> for ExportedConversation {
+    fn from(conversation: Conversation) -> Self {
+        ExportedConversation {
+            id: conversation
+                .id
+                .as_ref()
+                .map(thing_string)
+                .unwrap_or_default(),
+            conversation_type: conversation.conversation_type,
+            name: conversation.name,
+            participants: conversation.participants.iter().map(thing_string).collect(),
+            created_at: conversation.created_at,
+            updated_at: conversation.updated_at,
+        }
+    }
+}
+
+impl From<Message> for ExportedMessage {
+    fn from(message: Message) -> Self {
+        ExportedMessage {
+            id: message.id.as_ref().map(thing_string).unwrap_or_default(),
+            conversation_id: thing_string(&message.conversation_id),
+            content: message.content,
+            message_type: message.message_type,
+            created_at: message.created_at,
+        }
+    }
+}
+
+// `table:id` without the `⟨ ⟩` SurrealDB adds around UUIDs
+fn thing_string(thing: &Thing) -> String {
+    format!("{}:{}", thing.tb, thing.id.to_raw())
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("Data export repository error: {:?}", e);
+    AuthError::DatabaseError
+}
@@ -0,0 +1,222 @@
+//! E-mail verification for traditional (email + password) registration.
+//!
+//! Flow:
+//! 1. After `register`, `send_verification` issues an `email_verification`
+//!    one-time token and e-mails a link to `GET /api/auth/verify-email?token=`.
+//! 2. `verify` consumes the token and sets `User::email_verified`.
+//! 3. `resend` issues a new link at most once per cooldown window; unknown or
+//!    already verified addresses are accepted silently.
+//!
+//! Enforcement is configurable through `EmailVerificationPolicy`; wallet users
+//! have no e-mail and are always exempt.
+//!
+//! Env:
+//! - EMAIL_VERIFICATION_REQUIRED_FOR: comma list of `login`, `chat` (default empty)
+//! - EMAIL_VERIFICATION_TTL_SECONDS (default 86400)
+//! - EMAIL_VERIFICATION_RESEND_SECONDS (default 60)
+//! - PUBLIC_API_URL (default `http://localhost:8080`), used to build the link
+
+use chrono::{Duration, Utc};
+use log::{debug, error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::infrastructure::mail::{Mailer, OutgoingEmail};
+use crate::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Which actions require a verified e-mail address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailVerificationPolicy {
+    /// Reject password login until verified
+    pub require_for_login: bool,
+    /// Reject sending chat messages until verified
+    pub require_for_chat: bool,
+}
+
+impl EmailVerificationPolicy {
+    /// Parses a comma separated list such as `"login,chat"`.
+    pub fn parse(value: &str) -> Self {
+        let mut policy = Self::default();
+        for item in value.split(',').map(|s| s.trim().to_lowercase()) {
+            match item.as_str() {
+                "login" => policy.require_for_login = true,
+                "chat" => policy.require_for_chat = true,
+                "" => {}
+                other => warn!("Unknown EMAIL_VERIFICATION_REQUIRED_FOR entry: {}", other),
+            }
+        }
+        policy
+    }
+
+    /// Whether `user` may log in under this policy.
+    pub fn allows_login(&self, user: &User) -> bool {
+        !self.require_for_login || user.is_email_verified_or_exempt()
+    }
+
+    /// Whether `user` may send chat messages under this policy.
+    pub fn allows_chat(&self, user: &User) -> bool {
+        !self.require_for_chat || user.is_email_verified_or_exempt()
+    }
+}
+
+pub struct EmailVerificationService {
+    token_repo: Arc<dyn OneTimeTokenRepository>,
+    mailer: Arc<dyn Mailer>,
+    policy: EmailVerificationPolicy,
+    ttl: Duration,
+    resend_cooldown: Duration,
+    api_url: String,
+}
+
+impl EmailVerificationService {
+    pub fn new(
+        token_repo: Arc<dyn OneTimeTokenRepository>,
+        mailer: Arc<dyn Mailer>,
+        policy: EmailVerificationPolicy,
+        ttl: Duration,
+        resend_cooldown: Duration,
+        api_url: String,
+    ) -> Self {
+        Self {
+            token_repo,
+            mailer,
+            policy,
+            ttl,
+            resend_cooldown,
+            api_url,
+        }
+    }
+
+    /// Builds the service from the EMAIL_VERIFICATION_* and PUBLIC_API_URL variables.
+    pub fn from_env(
+        token_repo: Arc<dyn OneTimeTokenRepository>,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        let seconds = |name: &str, default: i64| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(default)
+        };
+        let policy = EmailVerificationPolicy::parse(
+            &env::var("EMAIL_VERIFICATION_REQUIRED_FOR").unwrap_or_default(),
+        );
+        let api_url =
+            env::var("PUBLIC_API_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+        Self::new(
+            token_repo,
+            mailer,
+            policy,
+            Duration::seconds(seconds("EMAIL_VERIFICATION_TTL_SECONDS", 86400)),
+            Duration::seconds(seconds("EMAIL_VERIFICATION_RESEND_SECONDS", 60)),
+            api_url,
+        )
+    }
+
+    /// The enforcement policy in effect.
+    pub fn policy(&self) -> EmailVerificationPolicy {
+        self.policy
+    }
+
+    /// Issues a verification token for `user` and e-mails the link.
+    pub async fn send_verification(&self, user: &User) -> Result<(), AuthError> {
+        let (user_id, email) = match (&user.id, &user.email) {
+            (Some(id), Some(email)) => (id.clone(), email.clone()),
+            _ => return Err(AuthError::UserNotFound),
+        };
+
+        self.token_repo
+            .invalidate_for_user(user_id.clone(), TokenPurpose::EmailVerification)
+            .await
+            .map_err(db_error)?;
+
+        let (plain, hash) = new_opaque_token();
+        let token = OneTimeToken::new(user_id, TokenPurpose::EmailVerification, hash, self.ttl);
+        self.token_repo.create(token).await.map_err(db_error)?;
+
+        let link = format!(
+            "{}/api/auth/verify-email?token={}",
+            self.api_url.trim_end_matches('/'),
+            plain
+        );
+        let body = format!(
+            "Hola {},\n\nConfirma tu correo abriendo este enlace (válido por {} horas):\n\n{}\n",
+            user.username,
+            self.ttl.num_hours(),
+            link
+        );
+        self.mailer
+            .send(OutgoingEmail {
+                to: email,
+                subject: "Confirma tu correo".to_string(),
+                body,
+            })
+            .await
+            .map_err(|e| {
+                error!("Verification mail failed: {}", e);
+                AuthError::MailDeliveryFailed
+            })?;
+
+        info!("Verification e-mail issued for username={}", user.username);
+        Ok(())
+    }
+
+    /// Consumes the token and marks the user's e-mail as verified.
+    pub async fn verify(&self, users: &dyn UserDataTrait, token: &str) -> Result<User, AuthError> {
+        let consumed = self
+            .token_repo
+            .consume(TokenPurpose::EmailVerification, &hash_opaque_token(token.trim()))
+            .await
+            .map_err(db_error)?
+            .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+        let user_id = consumed.user_id.id.to_raw();
+        let user = users
+            .mark_email_verified(&user_id)
+            .await
+            .ok_or(AuthError::UserNotFound)?;
+        info!("E-mail verified for username={}", user.username);
+        Ok(user)
+    }
+
+    /// Re-sends the verification link unless one was issued within the cooldown.
+    ///
+    /// Unknown and already verified addresses succeed without sending anything.
+    pub async fn resend(&self, users: &dyn UserDataTrait, email: &str) -> Result<(), AuthError> {
+        let user = match users.find_user_by_email(email.trim()).await {
+            Some(user) if !user.email_verified => user,
+            _ => {
+                debug!("Verification resend skipped: unknown or already verified");
+                return Ok(());
+            }
+        };
+        let user_id = user.id.clone().ok_or(AuthError::UserNotFound)?;
+
+        let latest = self
+            .token_repo
+            .find_latest_for_user(user_id, TokenPurpose::EmailVerification)
+            .await
+            .map_err(db_error)?;
+        if let Some(latest) = latest {
+            if Utc::now() - latest.created_at < self.resend_cooldown {
+                warn!(
+                    "Verification resend throttled for username={}",
+                    user.username
+                );
+                return Ok(());
+            }
+        }
+
+        self.send_verification(&user).await
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("One-time token repository error: {:?}", e);
+    AuthError::DatabaseError
+}
@@ -0,0 +1,242 @@
+//! Brute-force protection of the password login.
+//!
+//! Every failed password login is counted twice: under the account
+//! (`account:user:<uuid>` when the e-mail or username belongs to one, so both
+//! share a single budget; otherwise `account:<identifier>` as typed, normalized)
+//! and under the client address (`ip:<address>`). Then:
+//! - each account failure delays the next attempt (base delay, doubling, capped);
+//! - `max_account_failures` within the window lock the identifier out;
+//! - `max_ip_failures` within the window lock the address out (password spraying).
+//!
+//! Attempts while delayed or locked are refused before the password is checked,
+//! with `TooManyLoginAttempts` (429 + Retry-After). Unknown accounts are counted
+//! and locked exactly like existing ones, so responses never reveal whether an
+//! account exists. A successful login clears the account counter only; the IP
+//! counter keeps running so one valid account cannot reset it.
+//!
+//! Lockouts and unlocks (expiry or admin) are written to the audit log.
+//!
+//! Env:
+//! - LOGIN_MAX_FAILURES (default 5)
+//! - LOGIN_MAX_FAILURES_PER_IP (default 50)
+//! - LOGIN_LOCKOUT_SECONDS (default 900)
+//! - LOGIN_THROTTLE_BASE_SECONDS (default 1)
+//! - LOGIN_THROTTLE_MAX_SECONDS (default 60)
+//! - LOGIN_FAILURE_WINDOW_SECONDS (default 900)
+
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::error::AuthError;
+use crate::interfaces::repositories::audit_log::AuditLogRepository;
+use crate::interfaces::repositories::login_throttle::LoginThrottleRepository;
+use crate::models::entities::audit_event::{AuditAction, AuditEvent};
+use crate::models::entities::login_throttle::{ThrottleKind, ThrottlePolicy};
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct LoginThrottleService {
+    throttles: Arc<dyn LoginThrottleRepository>,
+    audit: Arc<dyn AuditLogRepository>,
+    policy: ThrottlePolicy,
+}
+
+impl LoginThrottleService {
+    pub fn new(
+        throttles: Arc<dyn LoginThrottleRepository>,
+        audit: Arc<dyn AuditLogRepository>,
+        policy: ThrottlePolicy,
+    ) -> Self {
+        Self {
+            throttles,
+            audit,
+            policy,
+        }
+    }
+
+    /// Builds the service reading the LOGIN_* variables.
+    pub fn from_env(
+        throttles: Arc<dyn LoginThrottleRepository>,
+        audit: Arc<dyn AuditLogRepository>,
+    ) -> Self {
+        let defaults = ThrottlePolicy::default();
+        let read = |name: &str| env::var(name).ok().and_then(|v| v.parse::<i64>().ok());
+        let policy = ThrottlePolicy {
+            max_account_failures: read("LOGIN_MAX_FAILURES")
+                .map(|v| v as u32)
+                .unwrap_or(defaults.max_account_failures),
+            max_ip_failures: read("LOGIN_MAX_FAILURES_PER_IP")
+                .map(|v| v as u32)
+                .unwrap_or(defaults.max_ip_failures),
+            lockout: read("LOGIN_LOCKOUT_SECONDS")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.lockout),
+            base_delay: read("LOGIN_THROTTLE_BASE_SECONDS")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.base_delay),
+            max_delay: read("LOGIN_THROTTLE_MAX_SECONDS")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.max_delay),
+            window: read("LOGIN_FAILURE_WINDOW_SECONDS")
+                .map(Duration::seconds)
+                .unwrap_or(defaults.window),
+        };
+        Self::new(throttles, audit, policy)
+    }
+
+    /// The account value a login identifier is throttled under: the user id of the
+    /// account it names, or the identifier itself for unknown accounts.
+    pub fn account_subject(user: Option<&User>, identifier: &str) -> String {
+        user.and_then(User::id_string)
+            .map(|id| format!("user:{}", id))
+            .unwrap_or_else(|| identifier.to_string())
+    }
+
+    /// Resolves an e-mail or username like the password login does and returns
+    /// its `account_subject` (used by the admin unlock).
+    pub async fn resolve_account(users: &dyn UserDataTrait, identifier: &str) -> String {
+        let identifier = identifier.trim();
+        let user = match users.find_user_by_email(identifier).await {
+            Some(user) => Some(user),
+            None => users.find_user_by_username(identifier).await,
+        };
+        Self::account_subject(user.as_ref(), identifier)
+    }
+
+    /// Refuses the attempt if the account or the address is delayed or locked.
+    /// Lockouts that ran out are cleared (and audited) here.
+    pub async fn check(&self, identifier: &str, ip: &str) -> Result<(), AuthError> {
+        let now = Utc::now();
+        let mut blocked: Option<DateTime<Utc>> = None;
+
+        for (kind, key) in keys(identifier, ip) {
+            let throttle = match self.throttles.find(&key).await.map_err(db_error)? {
+                Some(throttle) => throttle,
+                None => continue,
+            };
+            if throttle.lock_expired_at(now) {
+                self.throttles.clear(&key).await.map_err(db_error)?;
+                info!("Login lockout expired for {}", key);
+                self.audit(AuditAction::LoginUnlocked, &key, ip, "lockout expired")
+                    .await;
+                continue;
+            }
+            if let Some(until) = throttle.blocked_until(&self.policy, kind, now) {
+                blocked = blocked.max(Some(until));
+            }
+        }
+
+        match blocked {
+            Some(until) => {
+                warn!(
+                    "Login attempt throttled for {}",
+                    ThrottleKind::Account.key(identifier)
+                );
+                Err(AuthError::TooManyLoginAttempts(retry_after_secs(
+                    until, now,
+                )))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a failed attempt; locks (and audits) keys that reach their limit.
+    pub async fn record_failure(&self, identifier: &str, ip: &str) -> Result<(), AuthError> {
+        for (kind, key) in keys(identifier, ip) {
+            let throttle = self
+                .throttles
+                .record_failure(&key, self.policy.window)
+                .await
+                .map_err(db_error)?;
+            let now = Utc::now();
+            if throttle.failures >= self.policy.max_failures(kind) && !throttle.is_locked_at(now) {
+                let until = now + self.policy.lockout;
+                self.throttles.lock(&key, until).await.map_err(db_error)?;
+                warn!(
+                    "Login locked for {} after {} failures (until {})",
+                    key, throttle.failures, until
+                );
+                let detail = format!(
+                    "{} failed attempts, locked until {}",
+                    throttle.failures, until
+                );
+                self.audit(AuditAction::LoginLocked, &key, ip, &detail)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the account counter after a successful login.
+    pub async fn record_success(&self, identifier: &str) -> Result<(), AuthError> {
+        self.throttles
+            .clear(&ThrottleKind::Account.key(identifier))
+            .await
+            .map_err(db_error)
+    }
+
+    /// Lifts the lockout of an account identifier or address (admin action).
+    ///
+    /// # Returns
+    /// `true` if a counter existed and was cleared.
+    pub async fn unlock(
+        &self,
+        kind: ThrottleKind,
+        value: &str,
+        admin_username: &str,
+    ) -> Result<bool, AuthError> {
+        let key = kind.key(value);
+        let existing = self.throttles.find(&key).await.map_err(db_error)?;
+        if existing.is_none() {
+            return Ok(false);
+        }
+        self.throttles.clear(&key).await.map_err(db_error)?;
+        info!("Login lockout for {} lifted by {}", key, admin_username);
+        let detail = format!("lifted by {}", admin_username);
+        self.audit_event(AuditEvent::new(
+            AuditAction::LoginUnlocked,
+            &key,
+            None,
+            Some(detail),
+        ))
+        .await;
+        Ok(true)
+    }
+
+    async fn audit(&self, action: AuditAction, subject: &str, ip: &str, detail: &str) {
+        self.audit_event(AuditEvent::new(
+            action,
+            subject,
+            Some(ip.to_string()),
+            Some(detail.to_string()),
+        ))
+        .await;
+    }
+
+    // Audit failures are logged but never block the login flow
+    async fn audit_event(&self, event: AuditEvent) {
+        if let Err(e) = self.audit.record(event).await {
+            error!("Could not write audit event: {:?}", e);
+        }
+    }
+}
+
+fn keys(identifier: &str, ip: &str) -> [(ThrottleKind, String); 2] {
+    [
+        (ThrottleKind::Account, ThrottleKind::Account.key(identifier)),
+        (ThrottleKind::Ip, ThrottleKind::Ip.key(ip)),
+    ]
+}
+
+// Whole seconds, rounded up, at least 1
+fn retry_after_secs(until: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    let millis = (until - now).num_milliseconds().max(1);
+    (millis + 999) / 1000
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("Login throttle repository error: {:?}", e);
+    AuthError::DatabaseError
+}
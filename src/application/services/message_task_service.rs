@@ -0,0 +1,221 @@
+//! Turning chat messages into tasks.
+//!
+//! `create_from_message` (REST `POST /api/messages/{id}/task`, and the
+//! `/task <title>` chat command):
+//! - checks that the caller takes part in the message's conversation and that
+//!   the message is not linked to a task yet;
+//! - creates a task in that conversation referencing the message (name: the
+//!   given one, else the first line of the message);
+//! - links the message to the task and posts a `system` message announcing it.
+//!   Linking only succeeds on a message without a task, so of two concurrent
+//!   requests one wins; the other deletes its task and answers
+//!   `MessageAlreadyLinked`.
+//!
+//! `with_tasks` turns stored messages into client payloads carrying the current
+//! name and status of their linked task.
+
+use log::{error, info, warn};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+use crate::application::services::task_service::{TaskAccess, TaskService};
+use crate::error::TaskError;
+use crate::interfaces::repositories::conversation::ConversationRepository;
+use crate::interfaces::repositories::message::MessageRepository;
+use crate::models::entities::message::{LinkedTask, Message, MessagePayload, MessageType};
+use crate::models::entities::task::{
+    AddTaskRequest, CreateTaskFromMessageRequest, Task, MAX_TASK_NAME_LENGTH,
+};
+use crate::models::entities::user::User;
+use crate::models::traits::task_data_trait::TaskDataTrait;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Prefix of the chat command that turns a message into a task
+pub const TASK_COMMAND: &str = "/task";
+
+/// Result of turning a message into a task
+#[derive(Debug, Clone)]
+pub struct MessageTask {
+    pub task: Task,
+    /// The `system` message announcing the task
+    pub announcement: Message,
+}
+
+pub struct MessageTaskService {
+    tasks: Arc<TaskService>,
+    messages: Arc<dyn MessageRepository>,
+    conversations: Arc<dyn ConversationRepository>,
+}
+
+impl MessageTaskService {
+    pub fn new(
+        tasks: Arc<TaskService>,
+        messages: Arc<dyn MessageRepository>,
+        conversations: Arc<dyn ConversationRepository>,
+    ) -> Self {
+        Self {
+            tasks,
+            messages,
+            conversations,
+        }
+    }
+
+    /// The message `message_id` (UUID), if it exists.
+    pub async fn find_message(&self, message_id: &str) -> Result<Option<Message>, TaskError> {
+        self.messages
+            .find_by_id(Thing::from(("message", message_id)))
+            .await
+            .map_err(db_error)
+    }
+
+    /// Creates a task from the message `message_id` (UUID) on behalf of `creator`.
+    pub async fn create_from_message(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        creator: &User,
+        message_id: &str,
+        req: CreateTaskFromMessageRequest,
+    ) -> Result<MessageTask, TaskError> {
+        let creator_id = creator.id.clone().ok_or(TaskError::DatabaseError)?;
+        let message_thing = Thing::from(("message", message_id));
+        let message = self
+            .messages
+            .find_by_id(message_thing.clone())
+            .await
+            .map_err(db_error)?
+            .ok_or(TaskError::MessageNotFound)?;
+        let conversation = self
+            .conversations
+            .find_by_id(message.conversation_id.clone())
+            .await
+            .map_err(db_error)?;
+        // Messages of other conversations are hidden, not forbidden
+        if !conversation.is_some_and(|c| c.has_participant(&creator_id)) {
+            return Err(TaskError::MessageNotFound);
+        }
+        if message.task_id.is_some() {
+            return Err(TaskError::MessageAlreadyLinked);
+        }
+
+        let (task_name, description) = match req.task_name {
+            Some(name) => (name, req.description),
+            None => {
+                let first_line = message.content.trim().lines().next().unwrap_or_default();
+                let name: String = first_line
+                    .chars()
+                    .take(MAX_TASK_NAME_LENGTH as usize)
+                    .collect();
+                let whole = message.content.trim();
+                let description = req
+                    .description
+                    .or_else(|| (whole != name).then(|| whole.to_string()));
+                (name, description)
+            }
+        };
+        let task = self
+            .tasks
+            .create(
+                tasks,
+                users,
+                creator,
+                TaskAccess::default(),
+                AddTaskRequest {
+                    task_name,
+                    description,
+                    priority: req.priority,
+                    assignee: req.assignee,
+                    due_date: req.due_date,
+                    conversation_id: Some(message.conversation_id.id.to_raw()),
+                    source_message_id: Some(message_id.to_string()),
+                    ..AddTaskRequest::default()
+                },
+            )
+            .await?;
+
+        let linked = self
+            .messages
+            .link_task(message_thing, task.uuid.clone())
+            .await
+            .map_err(db_error)?;
+        if !linked {
+            // Another request linked the message first; drop the task made here
+            warn!(
+                "Message {} was linked meanwhile, deleting task {}",
+                message_id, task.uuid
+            );
+            self.tasks
+                .delete(tasks, creator, TaskAccess::default(), &task.uuid)
+                .await?;
+            return Err(TaskError::MessageAlreadyLinked);
+        }
+        let mut announcement = Message::new(
+            message.conversation_id.clone(),
+            creator_id,
+            format!("{} created task \"{}\"", creator.username, task.task_name),
+            Some(MessageType::System),
+        );
+        announcement.task_id = Some(task.uuid.clone());
+        let announcement = self.messages.create(announcement).await.map_err(db_error)?;
+
+        info!(
+            "Task {} created from message {} by username={}",
+            task.uuid, message_id, creator.username
+        );
+        Ok(MessageTask { task, announcement })
+    }
+
+    /// Attaches the current name and status of each message's linked task.
+    pub async fn with_tasks(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        messages: Vec<Message>,
+    ) -> Vec<MessagePayload> {
+        let mut uuids: Vec<String> = messages.iter().filter_map(|m| m.task_id.clone()).collect();
+        uuids.sort();
+        uuids.dedup();
+        let linked = tasks.find_tasks(uuids).await.unwrap_or_else(|| {
+            error!("Could not load the tasks linked to messages");
+            Vec::new()
+        });
+        messages
+            .into_iter()
+            .map(|message| {
+                let task = message
+                    .task_id
+                    .as_ref()
+                    .and_then(|uuid| linked.iter().find(|t| &t.uuid == uuid))
+                    .map(LinkedTask::from);
+                MessagePayload { message, task }
+            })
+            .collect()
+    }
+}
+
+/// The title of a `/task <title>` chat command; `Some("")` if it has none,
+/// `None` for other messages.
+pub fn task_command(content: &str) -> Option<&str> {
+    let rest = content.trim_start().strip_prefix(TASK_COMMAND)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim())
+}
+
+fn db_error(e: surrealdb::Error) -> TaskError {
+    error!("Message task repository error: {:?}", e);
+    TaskError::DatabaseError
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_command() {
+        assert_eq!(task_command("/task Fix the build"), Some("Fix the build"));
+        assert_eq!(task_command("  /task"), Some(""));
+        assert_eq!(task_command("/tasks are fun"), None);
+        assert_eq!(task_command("see /task"), None);
+    }
+}
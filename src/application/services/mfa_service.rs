@@ -0,0 +1,273 @@
+//! TOTP two-factor authentication (RFC 6238).
+//!
+//! Enrollment:
+//! 1. `start_enrollment` generates a secret, stores it as pending and returns the
+//!    `otpauth://` provisioning URI for the authenticator app.
+//! 2. `confirm_enrollment` checks a first code, enables 2FA and returns the
+//!    recovery codes. Only their SHA-256 digests are stored, so they are shown once.
+//!
+//! Login (every flow: password, wallet and external providers):
+//! 1. After the credential check, `issue_mfa_token` returns a short-lived JWT that is
+//!    not accepted as an access token.
+//! 2. `complete_login` exchanges it, together with a TOTP or recovery code, for the
+//!    real JWT. Codes cannot be replayed. After `MFA_MAX_FAILED_ATTEMPTS` wrong codes
+//!    the user's tokens are revoked, which also kills the pending `mfa_token`, so the
+//!    password step must be repeated. Attempts are counted in the database before
+//!    the code is checked, so codes sent in parallel share the same budget.
+//!
+//! Env:
+//! - TOTP_ISSUER (default `Chasqui`), shown by authenticator apps
+//! - MFA_TOKEN_TTL_SECONDS (default 300)
+//! - MFA_MAX_FAILED_ATTEMPTS (default 5)
+
+use chrono::Utc;
+use log::{error, info, warn};
+use rand::RngCore;
+use serde::Serialize;
+use std::env;
+
+use crate::application::services::account_deletion_service::reactivate_on_login;
+use crate::error::AuthError;
+use crate::infrastructure::auth::jwt::{
+    generate_mfa_token, generate_token_for_user, validate_mfa_token,
+};
+use crate::infrastructure::auth::opaque_token::hash_opaque_token;
+use crate::infrastructure::auth::totp;
+use crate::models::entities::totp::TotpSettings;
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Number of recovery codes generated on confirmation
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Data the client needs to register the secret in an authenticator app
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollment {
+    /// Base32 secret, for manual entry
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code
+    pub otpauth_uri: String,
+}
+
+pub struct MfaService {
+    issuer: String,
+    mfa_token_ttl_secs: usize,
+    max_failed_attempts: u32,
+}
+
+impl MfaService {
+    pub fn new(issuer: String, mfa_token_ttl_secs: usize, max_failed_attempts: u32) -> Self {
+        Self {
+            issuer,
+            mfa_token_ttl_secs,
+            max_failed_attempts,
+        }
+    }
+
+    /// Builds the service reading TOTP_ISSUER, MFA_TOKEN_TTL_SECONDS and MFA_MAX_FAILED_ATTEMPTS.
+    pub fn from_env() -> Self {
+        let issuer = env::var("TOTP_ISSUER").unwrap_or_else(|_| "Chasqui".to_string());
+        let ttl = env::var("MFA_TOKEN_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(300);
+        let max_failed = env::var("MFA_MAX_FAILED_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(5);
+        Self::new(issuer, ttl, max_failed)
+    }
+
+    /// Lifetime of the intermediate `mfa_token`, in seconds.
+    pub fn mfa_token_ttl_secs(&self) -> usize {
+        self.mfa_token_ttl_secs
+    }
+
+    /// Stores a new pending secret (replacing any unconfirmed one).
+    pub async fn start_enrollment(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+    ) -> Result<TotpEnrollment, AuthError> {
+        if user.is_mfa_enabled() {
+            return Err(AuthError::MfaAlreadyEnabled);
+        }
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+
+        let secret = totp::generate_secret();
+        users
+            .set_totp(&user_id, Some(TotpSettings::pending(secret.clone())))
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+
+        let account = user.email.as_deref().unwrap_or(&user.username);
+        let otpauth_uri = totp::provisioning_uri(&self.issuer, account, &secret);
+        info!("2FA enrollment started for username={}", user.username);
+        Ok(TotpEnrollment {
+            secret,
+            otpauth_uri,
+        })
+    }
+
+    /// Enables 2FA if `code` matches the pending secret; returns the plaintext recovery codes.
+    pub async fn confirm_enrollment(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        code: &str,
+    ) -> Result<Vec<String>, AuthError> {
+        let mut settings = match &user.totp {
+            Some(t) if t.enabled => return Err(AuthError::MfaAlreadyEnabled),
+            Some(t) => t.clone(),
+            None => return Err(AuthError::MfaNotEnabled),
+        };
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+
+        let step = totp::verify_code(&settings.secret, code, unix_now())
+            .ok_or(AuthError::InvalidMfaCode)?;
+
+        let codes = generate_recovery_codes();
+        settings.enable(step, codes.iter().map(|c| hash_recovery_code(c)).collect());
+        users
+            .set_totp(&user_id, Some(settings))
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+
+        info!("2FA enabled for username={}", user.username);
+        Ok(codes)
+    }
+
+    /// Disables 2FA after checking a current TOTP or recovery code.
+    pub async fn disable(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        code: &str,
+    ) -> Result<(), AuthError> {
+        let mut settings = match &user.totp {
+            Some(t) if t.enabled => t.clone(),
+            _ => return Err(AuthError::MfaNotEnabled),
+        };
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+
+        if !check_code(&mut settings, code) {
+            warn!("2FA disable rejected for username={}", user.username);
+            return Err(AuthError::InvalidMfaCode);
+        }
+        users
+            .set_totp(&user_id, None)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+
+        info!("2FA disabled for username={}", user.username);
+        Ok(())
+    }
+
+    /// Issues the intermediate token returned by `login` when 2FA is enabled.
+    pub fn issue_mfa_token(&self, user: &User) -> Result<String, AuthError> {
+        generate_mfa_token(user, self.mfa_token_ttl_secs).map_err(|e| {
+            error!("MFA token generation failed: {}", e);
+            AuthError::DatabaseError
+        })
+    }
+
+    /// Exchanges an `mfa_token` plus a TOTP or recovery code for an access token.
+    pub async fn complete_login(
+        &self,
+        users: &dyn UserDataTrait,
+        mfa_token: &str,
+        code: &str,
+    ) -> Result<String, AuthError> {
+        let claims = validate_mfa_token(mfa_token).map_err(|e| {
+            warn!("MFA token rejected: {}", e);
+            AuthError::InvalidOrExpiredToken
+        })?;
+        let user = users
+            .find_user_by_id(&claims.sub)
+            .await
+            .ok_or(AuthError::InvalidOrExpiredToken)?;
+        if user.token_version != claims.ver {
+            warn!("Stale MFA token for username={}", user.username);
+            return Err(AuthError::InvalidOrExpiredToken);
+        }
+        let mut settings = match &user.totp {
+            Some(t) if t.enabled => t.clone(),
+            _ => return Err(AuthError::MfaNotEnabled),
+        };
+
+        // The attempt is counted atomically before the code is checked, so codes
+        // sent in parallel cannot exceed the limit; a correct code resets it.
+        let attempts = users
+            .increment_totp_failures(&claims.sub, claims.ver)
+            .await
+            .ok_or(AuthError::InvalidOrExpiredToken)?;
+        if attempts > self.max_failed_attempts {
+            warn!("2FA attempt over the limit for username={}", user.username);
+            return Err(AuthError::InvalidMfaCode);
+        }
+        if !check_code(&mut settings, code) {
+            if attempts == self.max_failed_attempts {
+                warn!(
+                    "Too many 2FA failures for username={}; revoking tokens",
+                    user.username
+                );
+                users
+                    .lock_out_totp(&claims.sub)
+                    .await
+                    .ok_or(AuthError::DatabaseError)?;
+            } else {
+                warn!("Invalid 2FA code for username={}", user.username);
+            }
+            return Err(AuthError::InvalidMfaCode);
+        }
+
+        let user = users
+            .set_totp(&claims.sub, Some(settings))
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        let user = reactivate_on_login(users, user).await?;
+        let token = generate_token_for_user(&user).map_err(|e| {
+            error!("Token generation failed: {}", e);
+            AuthError::DatabaseError
+        })?;
+        info!("2FA login success for username={}", user.username);
+        Ok(token)
+    }
+}
+
+/// Accepts a 6-digit TOTP code (not replayed) or an unused recovery code.
+fn check_code(settings: &mut TotpSettings, code: &str) -> bool {
+    let code = code.trim();
+    if code.len() == totp::DIGITS as usize && code.chars().all(|c| c.is_ascii_digit()) {
+        totp::verify_code(&settings.secret, code, unix_now())
+            .is_some_and(|step| settings.accept_step(step))
+    } else {
+        settings.take_recovery_code(&hash_recovery_code(code))
+    }
+}
+
+/// Random `xxxxx-xxxxx` hex codes.
+fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 5];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{}-{}", &hex[..5], &hex[5..])
+        })
+        .collect()
+}
+
+/// Digest of a recovery code, ignoring case, dashes and spaces.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hash_opaque_token(&normalized)
+}
+
+fn unix_now() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
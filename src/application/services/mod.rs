@@ -4,7 +4,28 @@
 //! business logic and use cases.
 //!
 //! # Module Structure
+//! - `account_deletion_service`: Self-service deactivation and scheduled account deletion
+//! - `account_link_service`: Linking and unlinking wallets and external identities
+//! - `account_merge_service`: Merging a duplicate account into a primary one
+//! - `api_key_service`: Scoped API keys for integrations and bot accounts
+//! - `board_service`: Projects and Kanban boards with status-mapped columns
+//! - `calendar_service`: iCalendar feed of task due dates and `.ics` imports
+//! - `data_export_service`: Personal data export (ZIP archive of JSON files)
 //! - `data_trait_executor`: Implementation of data processing and execution logic
+//! - `email_verification_service`: E-mail verification links and enforcement policy
+//! - `message_task_service`: Turning chat messages into linked tasks
+//! - `mfa_service`: TOTP two-factor enrollment and two-step login
+//! - `notification_service`: Live delivery and storage of notifications for offline users
+//! - `oidc_service`: External OAuth2/OIDC logins and account linking
+//! - `login_throttle_service`: Login brute-force throttling, lockouts and audit events
+//! - `password_policy`: Length and breached-list rules for new passwords
+//! - `password_reset_service`: Password reset via e-mailed one-time token
+//! - `profile_service`: Profile updates and password / e-mail changes
+//! - `role_service`: Role catalog administration and built-in role seeding
+//! - `task_reminder_service`: Periodic job sending the reminders of task due dates
+//! - `task_service`: Task creation, partial updates and deletion
+//! - `user_directory_service`: Paginated user directory search
+//! - `ws_ticket_service`: Single-use tickets for the chat WebSocket handshake
 //!
 //! # Usage
 //! ```rust,ignore
@@ -17,6 +38,27 @@
 //! - Execute core application logic
 //! - Handle service-level operations
 
+pub mod account_deletion_service;
+pub mod account_link_service;
+pub mod account_merge_service;
+pub mod api_key_service;
+pub mod board_service;
+pub mod calendar_service;
 pub mod conversation_service;
+pub mod data_export_service;
 pub mod data_trait_executor;
+pub mod email_verification_service;
+pub mod login_throttle_service;
 pub mod message_service;
+pub mod message_task_service;
+pub mod mfa_service;
+pub mod notification_service;
+pub mod oidc_service;
+pub mod password_policy;
+pub mod password_reset_service;
+pub mod profile_service;
+pub mod role_service;
+pub mod task_reminder_service;
+pub mod task_service;
+pub mod user_directory_service;
+pub mod ws_ticket_service;
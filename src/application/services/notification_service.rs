@@ -0,0 +1,88 @@
+//! Notifications for one user, delivered live or kept until they are read.
+//!
+//! - `notify` pushes the notification to the user's WebSocket sessions; if
+//!   the user has none it is stored, to be listed when they come back.
+//! - `list` returns the stored notifications of the caller, newest first
+//!   (`unread_only` skips those marked read).
+//! - `mark_read` marks one of the caller's notifications read; other users'
+//!   notifications answer `NotificationNotFound`.
+
+use chrono::Utc;
+use log::{error, info};
+use std::sync::Arc;
+
+use crate::error::TaskError;
+use crate::interfaces::repositories::notification::NotificationRepository;
+use crate::models::entities::notification::Notification;
+use crate::models::entities::user::User;
+use crate::models::traits::notification_sink::NotificationSink;
+
+pub struct NotificationService {
+    notifications: Arc<dyn NotificationRepository>,
+    sink: Arc<dyn NotificationSink>,
+}
+
+impl NotificationService {
+    pub fn new(
+        notifications: Arc<dyn NotificationRepository>,
+        sink: Arc<dyn NotificationSink>,
+    ) -> Self {
+        Self {
+            notifications,
+            sink,
+        }
+    }
+
+    /// Delivers `notification` live, or stores it if its user is offline.
+    /// Returns false only if it could be neither delivered nor stored.
+    pub async fn notify(&self, notification: Notification) -> bool {
+        let user_id = notification.user_id.clone();
+        if self.sink.deliver(&user_id, notification.payload()).await {
+            return true;
+        }
+        match self.notifications.create(notification).await {
+            Ok(stored) => {
+                info!(
+                    "Notification {} kept for offline user {}",
+                    stored.uuid, user_id
+                );
+                true
+            }
+            Err(e) => {
+                error!(
+                    "Could not store a notification for user {}: {:?}",
+                    user_id, e
+                );
+                false
+            }
+        }
+    }
+
+    /// Stored notifications of `user`, newest first.
+    pub async fn list(
+        &self,
+        user: &User,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, TaskError> {
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        self.notifications
+            .find_for_user(&user_id, unread_only)
+            .await
+            .map_err(db_error)
+    }
+
+    /// Marks the notification `uuid` of `user` read.
+    pub async fn mark_read(&self, user: &User, uuid: &str) -> Result<Notification, TaskError> {
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        self.notifications
+            .mark_read(&user_id, uuid, Utc::now())
+            .await
+            .map_err(db_error)?
+            .ok_or(TaskError::NotificationNotFound)
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> TaskError {
+    error!("Notification storage error: {:?}", e);
+    TaskError::DatabaseError
+}
@@ -0,0 +1,333 @@
+//! External login ("Sign in with Google/GitHub/...") via OAuth2 / OpenID Connect.
+//!
+//! Flow:
+//! 1. `start_login` creates a random `state`, `nonce` and PKCE verifier, stores them
+//!    (only the SHA-256 of `state`) as an `oidc_state` row and returns the provider's
+//!    authorization URL with the `state`. The handler also sets `state` as an
+//!    HttpOnly cookie (`OIDC_STATE_COOKIE`), binding the login to that browser.
+//! 2. `complete_login` requires the callback `state` to match the cookie, so a
+//!    victim cannot be made to finish a login or link started by someone else.
+//!    It then consumes the row (single use, expiring), exchanges the code
+//!    with the verifier and resolves the local account:
+//!    - an account already linked to the identity (provider + `sub`) logs in;
+//!    - otherwise the provider must assert a verified e-mail, and an account with
+//!      that verified e-mail gets the identity linked;
+//!    - a password account whose e-mail was never verified is not linked
+//!      (`AccountLinkConflict`), so pre-registering someone else's address cannot
+//!      capture their external login;
+//!    - otherwise a new account without password is created.
+//!
+//! `start_link` starts the same flow for a signed-in user; its callback attaches
+//! the identity to that user instead (`AccountLinkConflict` if another account
+//! already owns it), without requiring a provider e-mail.
+//!
+//! Env:
+//! - OIDC_PROVIDERS and per-provider settings (see `infrastructure::auth::oidc`)
+//! - OIDC_STATE_TTL_SECONDS (default 600)
+
+use chrono::Duration;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::oidc::{
+    new_pkce_pair, random_url_safe, ExternalProfile, OidcClient, OidcError, OidcProviderConfig,
+};
+use crate::infrastructure::auth::opaque_token::hash_opaque_token;
+use crate::interfaces::repositories::oidc_state::OidcStateRepository;
+use crate::models::entities::identity::ExternalIdentity;
+use crate::models::entities::oidc_state::OidcLoginState;
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Cookie holding the `state` of the external login started by this browser
+pub const OIDC_STATE_COOKIE: &str = "chasqui_oidc_state";
+
+/// Where to send the browser, and the `state` to bind to it
+#[derive(Debug, Clone)]
+pub struct OidcAuthorization {
+    pub url: String,
+    pub state: String,
+}
+
+pub struct OidcService {
+    providers: HashMap<String, OidcClient>,
+    state_repo: Arc<dyn OidcStateRepository>,
+    state_ttl: Duration,
+}
+
+impl OidcService {
+    pub fn new(
+        configs: Vec<OidcProviderConfig>,
+        state_repo: Arc<dyn OidcStateRepository>,
+        state_ttl: Duration,
+    ) -> Self {
+        let providers = configs
+            .into_iter()
+            .map(|c| (c.name.clone(), OidcClient::new(c)))
+            .collect();
+        Self {
+            providers,
+            state_repo,
+            state_ttl,
+        }
+    }
+
+    /// Builds the service from OIDC_PROVIDERS and OIDC_STATE_TTL_SECONDS.
+    pub fn from_env(state_repo: Arc<dyn OidcStateRepository>) -> Self {
+        let ttl_secs = env::var("OIDC_STATE_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(600);
+        let configs = OidcProviderConfig::all_from_env();
+        info!(
+            "OIDC providers configured: {:?}",
+            configs.iter().map(|c| c.name.as_str()).collect::<Vec<_>>()
+        );
+        Self::new(configs, state_repo, Duration::seconds(ttl_secs))
+    }
+
+    /// Names of the configured providers, sorted.
+    pub fn provider_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.providers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// How long a started login can be completed.
+    pub fn state_ttl(&self) -> Duration {
+        self.state_ttl
+    }
+
+    /// Stores a pending login and returns the provider's authorization URL.
+    pub async fn start_login(&self, provider: &str) -> Result<OidcAuthorization, AuthError> {
+        self.start(provider, None).await
+    }
+
+    /// Like `start_login`, but the callback links the identity to `user`.
+    pub async fn start_link(
+        &self,
+        provider: &str,
+        user: &User,
+    ) -> Result<OidcAuthorization, AuthError> {
+        let user_id = user.id.clone().ok_or(AuthError::UserNotFound)?;
+        self.start(provider, Some(user_id)).await
+    }
+
+    async fn start(
+        &self,
+        provider: &str,
+        link_user_id: Option<Thing>,
+    ) -> Result<OidcAuthorization, AuthError> {
+        let client = self
+            .providers
+            .get(provider)
+            .ok_or(AuthError::UnknownProvider)?;
+
+        if let Err(e) = self.state_repo.purge_expired().await {
+            warn!("Could not purge expired OIDC states: {:?}", e);
+        }
+
+        let state = random_url_safe(32);
+        let nonce = random_url_safe(32);
+        let (verifier, challenge) = new_pkce_pair();
+        let mut pending = OidcLoginState::new(
+            provider,
+            hash_opaque_token(&state),
+            verifier,
+            nonce.clone(),
+            self.state_ttl,
+        );
+        if let Some(user_id) = link_user_id {
+            pending = pending.with_link_user(user_id);
+        }
+        self.state_repo
+            .create(pending)
+            .await
+            .map_err(db_error)?;
+
+        let url = client
+            .authorization_url(&state, &nonce, &challenge)
+            .await
+            .map_err(provider_error)?;
+        Ok(OidcAuthorization { url, state })
+    }
+
+    /// Completes the callback and returns the local user (found, linked or created).
+    /// `browser_state` is the `OIDC_STATE_COOKIE` of the browser that made the
+    /// callback request.
+    pub async fn complete_login(
+        &self,
+        users: &dyn UserDataTrait,
+        provider: &str,
+        code: &str,
+        state: &str,
+        browser_state: Option<&str>,
+    ) -> Result<User, AuthError> {
+        let client = self
+            .providers
+            .get(provider)
+            .ok_or(AuthError::UnknownProvider)?;
+
+        // Checked before consuming, so a forged callback cannot spend the state
+        if browser_state != Some(state) {
+            warn!("OIDC callback rejected: state not started by this browser");
+            return Err(AuthError::InvalidOrExpiredToken);
+        }
+
+        let pending = self
+            .state_repo
+            .consume(&hash_opaque_token(state))
+            .await
+            .map_err(db_error)?
+            .filter(|p| p.provider == provider)
+            .ok_or_else(|| {
+                warn!("OIDC callback rejected: unknown, expired or reused state");
+                AuthError::InvalidOrExpiredToken
+            })?;
+
+        let profile = client
+            .exchange_code(code, &pending.code_verifier, &pending.nonce)
+            .await
+            .map_err(provider_error)?;
+
+        match pending.link_user_id {
+            Some(user_id) => self.link_user(users, provider, profile, &user_id).await,
+            None => self.resolve_user(users, provider, profile).await,
+        }
+    }
+
+    async fn link_user(
+        &self,
+        users: &dyn UserDataTrait,
+        provider: &str,
+        profile: ExternalProfile,
+        user_id: &Thing,
+    ) -> Result<User, AuthError> {
+        let user_id = user_id.id.to_raw();
+        let user = users
+            .find_user_by_id(&user_id)
+            .await
+            .filter(|u| !u.blocked)
+            .ok_or(AuthError::UserNotFound)?;
+
+        if let Some(owner) = users
+            .find_user_by_identity(provider, &profile.subject)
+            .await
+        {
+            if owner.id == user.id {
+                return Ok(owner);
+            }
+            warn!(
+                "OIDC identity ({}) already belongs to username={}",
+                provider, owner.username
+            );
+            return Err(AuthError::AccountLinkConflict);
+        }
+
+        let email = profile.email.filter(|_| profile.email_verified);
+        let identity = ExternalIdentity::new(provider, &profile.subject, email);
+        let linked = users
+            .link_identity(&user_id, identity)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!(
+            "OIDC identity ({}) linked to username={} on request",
+            provider, linked.username
+        );
+        Ok(linked)
+    }
+
+    async fn resolve_user(
+        &self,
+        users: &dyn UserDataTrait,
+        provider: &str,
+        profile: ExternalProfile,
+    ) -> Result<User, AuthError> {
+        if let Some(user) = users
+            .find_user_by_identity(provider, &profile.subject)
+            .await
+        {
+            info!("OIDC login ({}) for username={}", provider, user.username);
+            return Ok(user);
+        }
+
+        let email = match (&profile.email, profile.email_verified) {
+            (Some(email), true) => email.clone(),
+            _ => {
+                warn!(
+                    "OIDC login ({}) rejected: provider e-mail not verified",
+                    provider
+                );
+                return Err(AuthError::EmailNotVerified);
+            }
+        };
+        let identity = ExternalIdentity::new(provider, &profile.subject, Some(email.clone()));
+
+        if let Some(user) = users.find_user_by_verified_email(&email).await {
+            let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+            let linked = users
+                .link_identity(&user_id, identity)
+                .await
+                .ok_or(AuthError::DatabaseError)?;
+            info!(
+                "OIDC identity ({}) linked to username={}",
+                provider, linked.username
+            );
+            return Ok(linked);
+        }
+
+        if users.find_user_by_email(&email).await.is_some() {
+            warn!(
+                "OIDC login ({}) not linked: local account e-mail is unverified",
+                provider
+            );
+            return Err(AuthError::AccountLinkConflict);
+        }
+
+        let username = username_from_profile(&profile, &email);
+        let user = User::new_from_identity(username, Some(email), true, identity);
+        let created = users.add_user(user).await.ok_or(AuthError::DatabaseError)?;
+        info!(
+            "OIDC login ({}) created username={}",
+            provider, created.username
+        );
+        Ok(created)
+    }
+}
+
+// `<preferred username or e-mail local part>_<4 hex>`, like wallet usernames
+fn username_from_profile(profile: &ExternalProfile, email: &str) -> String {
+    let base: String = profile
+        .preferred_username
+        .as_deref()
+        .unwrap_or_else(|| email.split('@').next().unwrap_or("user"))
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .take(24)
+        .collect::<String>()
+        .to_lowercase();
+    let base = if base.is_empty() {
+        "user".to_string()
+    } else {
+        base
+    };
+    format!("{}_{}", base, &Uuid::new_v4().to_string()[0..4])
+}
+
+fn provider_error(e: OidcError) -> AuthError {
+    warn!("OIDC provider error: {}", e);
+    match e {
+        OidcError::InvalidIdToken(_) => AuthError::InvalidIdToken,
+        other => AuthError::IdentityProviderError(other.to_string()),
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("OIDC state repository error: {:?}", e);
+    AuthError::DatabaseError
+}
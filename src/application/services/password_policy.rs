@@ -0,0 +1,133 @@
+//! Password policy applied when a password is chosen (registration, reset).
+//!
+//! Rules:
+//! - length between `min_length` and `max_length` characters;
+//! - not contained in the breached-password list, if one is configured.
+//!
+//! The breached list is a text file with one entry per line, either a plaintext
+//! password or a SHA-1 hex digest optionally followed by `:count` (the format of
+//! the Have I Been Pwned downloads). Only the SHA-1 digests are kept in memory.
+//! The file is read once when the policy is built.
+//!
+//! Env:
+//! - PASSWORD_MIN_LENGTH (default 8)
+//! - PASSWORD_MAX_LENGTH (default 128)
+//! - PASSWORD_BREACHED_LIST_FILE (optional path)
+
+use log::{error, info};
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+
+use crate::error::AuthError;
+
+/// Default minimum accepted password length
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Default maximum accepted password length
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    breached: HashSet<[u8; 20]>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::new(MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH)
+    }
+}
+
+impl PasswordPolicy {
+    /// Policy with length limits only.
+    pub fn new(min_length: usize, max_length: usize) -> Self {
+        Self {
+            min_length,
+            max_length,
+            breached: HashSet::new(),
+        }
+    }
+
+    /// Adds the entries of a breached-password list (see module docs).
+    pub fn with_breached_list(mut self, contents: &str) -> Self {
+        for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let digest = sha1_hex_prefix(line).unwrap_or_else(|| sha1(line));
+            self.breached.insert(digest);
+        }
+        self
+    }
+
+    /// Builds the policy from PASSWORD_MIN_LENGTH, PASSWORD_MAX_LENGTH and
+    /// PASSWORD_BREACHED_LIST_FILE. An unreadable list file is logged and ignored.
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(default)
+        };
+        let policy = Self::new(
+            read("PASSWORD_MIN_LENGTH", MIN_PASSWORD_LENGTH),
+            read("PASSWORD_MAX_LENGTH", MAX_PASSWORD_LENGTH),
+        );
+
+        match env::var("PASSWORD_BREACHED_LIST_FILE") {
+            Ok(path) if !path.is_empty() => match fs::read_to_string(&path) {
+                Ok(contents) => {
+                    let policy = policy.with_breached_list(&contents);
+                    info!(
+                        "Breached password list loaded: {} entries",
+                        policy.breached.len()
+                    );
+                    policy
+                }
+                Err(e) => {
+                    error!("Could not read PASSWORD_BREACHED_LIST_FILE {}: {}", path, e);
+                    policy
+                }
+            },
+            _ => policy,
+        }
+    }
+
+    /// Returns `WeakPassword` with the reason if `password` breaks a rule.
+    pub fn check(&self, password: &str) -> Result<(), AuthError> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(AuthError::WeakPassword(format!(
+                "password must be at least {} characters",
+                self.min_length
+            )));
+        }
+        if length > self.max_length {
+            return Err(AuthError::WeakPassword(format!(
+                "password must be at most {} characters",
+                self.max_length
+            )));
+        }
+        if self.breached.contains(&sha1(password)) {
+            return Err(AuthError::WeakPassword(
+                "password appears in a list of breached passwords".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn sha1(value: &str) -> [u8; 20] {
+    Sha1::digest(value.as_bytes()).into()
+}
+
+// `<40 hex chars>` or `<40 hex chars>:<count>`
+fn sha1_hex_prefix(line: &str) -> Option<[u8; 20]> {
+    let hex = line.split(':').next()?;
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut digest = [0u8; 20];
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(digest)
+}
@@ -0,0 +1,174 @@
+//! Password reset via e-mailed one-time token.
+//!
+//! Flow:
+//! 1. `request_reset` issues a random secret, stores only its SHA-256 digest as a
+//!    `password_reset` one-time token and e-mails a reset link. Unknown e-mails
+//!    and mail delivery failures (logged) are accepted silently so the endpoint
+//!    cannot be used to enumerate accounts.
+//! 2. `reset_password` checks the new password against the `PasswordPolicy`,
+//!    consumes the token (single use, expiring), hashes the new password via
+//!    `password::hash_password` and bumps the user's token version, which revokes
+//!    every session issued before the reset.
+//!
+//! Env:
+//! - PASSWORD_RESET_TTL_SECONDS (default 3600)
+//! - APP_BASE_URL (default `http://localhost:3000`), used to build the link
+
+use chrono::Duration;
+use log::{debug, error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::application::services::password_policy::PasswordPolicy;
+use crate::error::AuthError;
+use crate::infrastructure::auth::password::hash_password;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::infrastructure::mail::{Mailer, OutgoingEmail};
+use crate::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct PasswordResetService {
+    token_repo: Arc<dyn OneTimeTokenRepository>,
+    mailer: Arc<dyn Mailer>,
+    policy: Arc<PasswordPolicy>,
+    ttl: Duration,
+    base_url: String,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        token_repo: Arc<dyn OneTimeTokenRepository>,
+        mailer: Arc<dyn Mailer>,
+        policy: Arc<PasswordPolicy>,
+        ttl: Duration,
+        base_url: String,
+    ) -> Self {
+        Self {
+            token_repo,
+            mailer,
+            policy,
+            ttl,
+            base_url,
+        }
+    }
+
+    /// Builds the service reading PASSWORD_RESET_TTL_SECONDS and APP_BASE_URL.
+    pub fn from_env(
+        token_repo: Arc<dyn OneTimeTokenRepository>,
+        mailer: Arc<dyn Mailer>,
+        policy: Arc<PasswordPolicy>,
+    ) -> Self {
+        let ttl_secs = env::var("PASSWORD_RESET_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(3600);
+        let base_url =
+            env::var("APP_BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        Self::new(
+            token_repo,
+            mailer,
+            policy,
+            Duration::seconds(ttl_secs),
+            base_url,
+        )
+    }
+
+    /// Issues a reset token and e-mails it. Succeeds even if no account matches.
+    pub async fn request_reset(
+        &self,
+        users: &dyn UserDataTrait,
+        email: &str,
+    ) -> Result<(), AuthError> {
+        let email = email.trim().to_string();
+        let user = match users.find_user_by_email(&email).await {
+            Some(user) => user,
+            None => {
+                debug!("Password reset requested for unknown e-mail");
+                return Ok(());
+            }
+        };
+        let user_id = match &user.id {
+            Some(id) => id.clone(),
+            None => return Err(AuthError::UserNotFound),
+        };
+
+        // Only the newest link should work
+        self.token_repo
+            .invalidate_for_user(user_id.clone(), TokenPurpose::PasswordReset)
+            .await
+            .map_err(db_error)?;
+
+        let (plain, hash) = new_opaque_token();
+        let token = OneTimeToken::new(user_id, TokenPurpose::PasswordReset, hash, self.ttl);
+        self.token_repo.create(token).await.map_err(db_error)?;
+
+        let link = format!(
+            "{}/reset-password?token={}",
+            self.base_url.trim_end_matches('/'),
+            plain
+        );
+        let body = format!(
+            "Hola {},\n\nPara restablecer tu contraseña abre este enlace (válido por {} minutos):\n\n{}\n\nSi no solicitaste el cambio, ignora este correo.\n",
+            user.username,
+            self.ttl.num_minutes(),
+            link
+        );
+        // A delivery error must look like an unknown address to the caller
+        match self
+            .mailer
+            .send(OutgoingEmail {
+                to: email,
+                subject: "Restablecer contraseña".to_string(),
+                body,
+            })
+            .await
+        {
+            Ok(()) => info!("Password reset issued for username={}", user.username),
+            Err(e) => error!(
+                "Password reset mail failed for username={}: {}",
+                user.username, e
+            ),
+        }
+        Ok(())
+    }
+
+    /// Consumes the token and sets a new password, revoking existing sessions.
+    pub async fn reset_password(
+        &self,
+        users: &dyn UserDataTrait,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AuthError> {
+        self.policy.check(new_password)?;
+
+        let consumed = self
+            .token_repo
+            .consume(TokenPurpose::PasswordReset, &hash_opaque_token(token.trim()))
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| {
+                warn!("Password reset rejected: invalid, expired or used token");
+                AuthError::InvalidOrExpiredToken
+            })?;
+
+        let hashed = hash_password(new_password).map_err(|e| {
+            error!("Password reset hashing failed: {}", e);
+            AuthError::DatabaseError
+        })?;
+
+        let user_id = consumed.user_id.id.to_raw();
+        users
+            .update_password(&user_id, &hashed)
+            .await
+            .ok_or(AuthError::UserNotFound)?;
+
+        info!("Password reset completed for user {}", user_id);
+        Ok(())
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("One-time token repository error: {:?}", e);
+    AuthError::DatabaseError
+}
@@ -0,0 +1,122 @@
+//! Self-service profile and credential changes.
+//!
+//! - `update_profile` merges a partial profile (empty strings clear fields).
+//! - `change_password` and `change_email` require the current password. A new
+//!   password revokes every token issued before (the handler returns a fresh
+//!   one); a new e-mail address starts unverified.
+//!
+//! Accounts without a local password (wallet, external login, bots) cannot use
+//! the credential flows; they can set a password through the reset flow.
+
+use log::{error, info, warn};
+use std::sync::Arc;
+use validator::ValidateEmail;
+
+use crate::application::services::password_policy::PasswordPolicy;
+use crate::error::AuthError;
+use crate::infrastructure::auth::password::{hash_password, verify_password};
+use crate::models::entities::profile::UserProfile;
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct ProfileService {
+    policy: Arc<PasswordPolicy>,
+}
+
+impl ProfileService {
+    pub fn new(policy: Arc<PasswordPolicy>) -> Self {
+        Self { policy }
+    }
+
+    /// Applies a partial profile update and returns the updated user.
+    pub async fn update_profile(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        patch: UserProfile,
+    ) -> Result<User, AuthError> {
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+        let mut profile = user.profile.clone();
+        profile.merge(patch);
+        if profile == user.profile {
+            return Ok(user.clone());
+        }
+        users
+            .update_profile(&user_id, profile)
+            .await
+            .ok_or(AuthError::DatabaseError)
+    }
+
+    /// Sets a new password after checking the current one; revokes older tokens.
+    pub async fn change_password(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<User, AuthError> {
+        check_current_password(user, current_password)?;
+        self.policy.check(new_password)?;
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+
+        let hashed = hash_password(new_password).map_err(|e| {
+            error!("Password change hashing failed: {}", e);
+            AuthError::DatabaseError
+        })?;
+        let updated = users
+            .update_password(&user_id, &hashed)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+
+        info!("Password changed for username={}", user.username);
+        Ok(updated)
+    }
+
+    /// Sets a new, unverified e-mail address after checking the current password.
+    pub async fn change_email(
+        &self,
+        users: &dyn UserDataTrait,
+        user: &User,
+        current_password: &str,
+        new_email: &str,
+    ) -> Result<User, AuthError> {
+        check_current_password(user, current_password)?;
+        let new_email = new_email.trim();
+        if !new_email.validate_email() {
+            return Err(AuthError::InvalidEmail);
+        }
+        if user.email.as_deref() == Some(new_email) {
+            return Ok(user.clone());
+        }
+        let user_id = user.id_string().ok_or(AuthError::UserNotFound)?;
+
+        let taken = match users.find_user_by_email(new_email).await {
+            Some(existing) => Some(existing),
+            None => users.find_user_by_verified_email(new_email).await,
+        };
+        if taken.is_some_and(|existing| existing.id != user.id) {
+            warn!(
+                "E-mail change rejected for username={}: address in use",
+                user.username
+            );
+            return Err(AuthError::EmailAlreadyInUse);
+        }
+
+        let updated = users
+            .update_email(&user_id, new_email)
+            .await
+            .ok_or(AuthError::DatabaseError)?;
+        info!("E-mail changed for username={}", user.username);
+        Ok(updated)
+    }
+}
+
+fn check_current_password(user: &User, password: &str) -> Result<(), AuthError> {
+    let hash = user.password.as_deref().ok_or(AuthError::PasswordNotSet)?;
+    if verify_password(password, hash) {
+        Ok(())
+    } else {
+        warn!("Wrong current password for username={}", user.username);
+        Err(AuthError::InvalidCurrentPassword)
+    }
+}
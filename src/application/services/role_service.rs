@@ -0,0 +1,143 @@
+use log::{error, info, warn};
+use std::sync::Arc;
+
+use crate::error::RoleError;
+use crate::interfaces::repositories::role::RoleRepository;
+use crate::models::entities::role::{roles, Permission, Role};
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+pub struct RoleService {
+    role_repo: Arc<dyn RoleRepository>,
+}
+
+impl RoleService {
+    pub fn new(role_repo: Arc<dyn RoleRepository>) -> Self {
+        Self { role_repo }
+    }
+
+    pub async fn list_roles(&self) -> Result<Vec<Role>, RoleError> {
+        self.role_repo.find_all().await.map_err(db_error)
+    }
+
+    pub async fn get_role(&self, name: &str) -> Result<Role, RoleError> {
+        self.role_repo
+            .find_by_name(name)
+            .await
+            .map_err(db_error)?
+            .ok_or(RoleError::RoleNotFound)
+    }
+
+    pub async fn create_role(
+        &self,
+        name: &str,
+        description: &str,
+        permissions: &[Permission],
+    ) -> Result<Role, RoleError> {
+        let role = Role::new(name, description).with_permissions(permissions);
+        if !role.is_valid() {
+            return Err(RoleError::InvalidRole(format!("invalid role name: {}", name)));
+        }
+
+        // Role names are unique
+        if self.role_repo.find_by_name(name).await.map_err(db_error)?.is_some() {
+            return Err(RoleError::RoleAlreadyExists);
+        }
+
+        let created = self.role_repo.create(role).await.map_err(db_error)?;
+        info!("Role created: {}", created.name);
+        Ok(created)
+    }
+
+    /// Updates description and/or permissions, then refreshes the copy embedded
+    /// in every user holding the role (revoking their current tokens).
+    pub async fn update_role(
+        &self,
+        name: &str,
+        description: Option<String>,
+        permissions: Option<Vec<Permission>>,
+    ) -> Result<Role, RoleError> {
+        if name == "admin" {
+            return Err(RoleError::BuiltinRoleProtected);
+        }
+
+        let mut role = self.get_role(name).await?;
+        if let Some(description) = description {
+            role.description = description;
+        }
+        if let Some(permissions) = permissions {
+            role.permissions = permissions;
+        }
+
+        let updated = self.role_repo.update(role).await.map_err(db_error)?;
+        let affected = self.role_repo.sync_users(&updated).await.map_err(db_error)?;
+        info!("Role updated: {} ({} users refreshed)", updated.name, affected);
+        Ok(updated)
+    }
+
+    /// Deletes a custom role and revokes it from every user holding it.
+    pub async fn delete_role(&self, name: &str) -> Result<(), RoleError> {
+        let role = self.get_role(name).await?;
+        if role.is_builtin() {
+            return Err(RoleError::BuiltinRoleProtected);
+        }
+
+        let affected = self
+            .role_repo
+            .remove_from_users(&role.name)
+            .await
+            .map_err(db_error)?;
+        self.role_repo.delete(&role.name).await.map_err(db_error)?;
+        info!("Role deleted: {} ({} users updated)", role.name, affected);
+        Ok(())
+    }
+
+    /// Rejects replacing the roles of `user` with `next` when that removes `admin`
+    /// from the last user holding it, which would lock every operator out of the
+    /// RBAC API.
+    pub async fn ensure_admin_remains(
+        users: &dyn UserDataTrait,
+        user: &User,
+        next: &[Role],
+    ) -> Result<(), RoleError> {
+        if !user.has_role("admin") || next.iter().any(|r| r.name == "admin") {
+            return Ok(());
+        }
+        let admins = users
+            .count_users_with_role("admin")
+            .await
+            .ok_or(RoleError::DatabaseError)?;
+        if admins <= 1 {
+            warn!("Refused to revoke admin from the last admin username={}", user.username);
+            return Err(RoleError::LastAdmin);
+        }
+        Ok(())
+    }
+
+    /// Creates the built-in roles that are missing from the `role` table.
+    ///
+    /// Existing roles are left untouched. Returns the names of the roles created.
+    pub async fn seed_builtin_roles(&self) -> Result<Vec<String>, RoleError> {
+        let mut seeded = Vec::new();
+        for role in roles::builtin() {
+            if self
+                .role_repo
+                .find_by_name(&role.name)
+                .await
+                .map_err(db_error)?
+                .is_some()
+            {
+                continue;
+            }
+            let created = self.role_repo.create(role).await.map_err(db_error)?;
+            info!("Seeded built-in role: {}", created.name);
+            seeded.push(created.name);
+        }
+        Ok(seeded)
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> RoleError {
+    error!("Role repository error: {:?}", e);
+    RoleError::DatabaseError
+}
@@ -0,0 +1,93 @@
+//! Reminders of task due dates, sent by a periodic job.
+//!
+//! A task's `reminders` are minutes before its due date. On every run the
+//! reminders whose time has come are sent to the assignee (or, without one,
+//! the creator) as a `TaskReminder` notification and marked sent, so each is
+//! sent once per due date. They are marked before sending, on the version that
+//! was read: if the task changed meanwhile (a new due date re-arms them) the
+//! run skips it and the next one works from the new task. When several are reached at once (the server was
+//! down, or the due date moved closer) a single notification is sent, for the
+//! latest of them. Done tasks are not reminded.
+//!
+//! Env:
+//! - REMINDER_INTERVAL_SECONDS (default 60)
+
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use std::env;
+use std::sync::Arc;
+
+use crate::application::services::notification_service::NotificationService;
+use crate::models::entities::notification::Notification;
+use crate::models::entities::task::MAX_REMINDER_MINUTES;
+use crate::models::traits::task_data_trait::TaskDataTrait;
+
+pub struct TaskReminderService {
+    notifications: Arc<NotificationService>,
+    interval: std::time::Duration,
+}
+
+impl TaskReminderService {
+    pub fn new(notifications: Arc<NotificationService>, interval: std::time::Duration) -> Self {
+        Self {
+            notifications,
+            interval,
+        }
+    }
+
+    /// Builds the service reading REMINDER_INTERVAL_SECONDS.
+    pub fn from_env(notifications: Arc<NotificationService>) -> Self {
+        let interval_secs = env::var("REMINDER_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(60);
+        Self::new(notifications, std::time::Duration::from_secs(interval_secs))
+    }
+
+    /// How often the server should call `run_due`.
+    pub fn interval(&self) -> std::time::Duration {
+        self.interval
+    }
+
+    /// Sends the reminders due at `now`; returns how many notifications were sent.
+    pub async fn run_due(&self, tasks: &dyn TaskDataTrait, now: DateTime<Utc>) -> usize {
+        // No reminder is earlier than MAX_REMINDER_MINUTES before its due date
+        let horizon = now + Duration::minutes(i64::from(MAX_REMINDER_MINUTES));
+        let Some(candidates) = tasks.find_tasks_with_reminders(horizon).await else {
+            error!("Could not list tasks with reminders");
+            return 0;
+        };
+
+        let mut sent = 0;
+        for task in candidates {
+            let due = task.due_reminders(now);
+            let Some(latest) = due.iter().min().copied() else {
+                continue;
+            };
+            let Some(recipient) = task.reminder_recipient().map(str::to_string) else {
+                continue;
+            };
+            let mut reminded = task.reminded.clone();
+            reminded.extend(due);
+            if !tasks
+                .set_reminded(task.uuid.clone(), reminded, task.version)
+                .await
+            {
+                warn!("Task {} changed meanwhile; reminder skipped", task.uuid);
+                continue;
+            }
+            let notification = Notification::task_reminder(recipient.clone(), &task, latest);
+            if !self.notifications.notify(notification).await {
+                error!("Could not send the reminder of task {}", task.uuid);
+                continue;
+            }
+            info!(
+                "Task {} reminder ({} minutes before) sent to user {}",
+                task.uuid, latest, recipient
+            );
+            sent += 1;
+        }
+        sent
+    }
+}
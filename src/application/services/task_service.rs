@@ -0,0 +1,1297 @@
+//! Task creation, listing, partial updates and deletion, with ownership rules.
+//!
+//! - A task belongs to its creator and may belong to a conversation.
+//! - Seeing a task: its creator, its assignee, the participants of its
+//!   conversation, and holders of `task:read`.
+//! - Changing a task: its creator, its assignee, and holders of `task:update`.
+//! - Deleting a task: its creator and holders of `task:delete`.
+//!
+//! Tasks the caller cannot see answer `NoTaskFoundWithId`; tasks the caller can
+//! see but not change answer `NotAllowed`.
+//!
+//! - `list` returns one page of the visible tasks, filtered by `status`,
+//!   `assignee`, `due_before` and `q` (case-insensitive substring of the name or
+//!   description) and ordered by `sort` (`created_at` by default; `updated_at`,
+//!   `due_date`, `priority`; `-` prefix for descending). Ties are broken by UUID
+//!   and the cursor is the (sort key, UUID) of the last task of a page.
+//!
+//! - `create` validates the request, records the caller as creator and checks
+//!   that the assignee exists and that the caller takes part in the conversation.
+//! - `update` applies a validated partial update (`PATCH`): absent fields are
+//!   kept, `null` clears `description` / `assignee` / `due_date`. Moving the
+//!   status to `done` stamps `completed_at`; reopening clears it.
+//!
+//! Structure:
+//! - A task may have a parent (`parent` on create / `PATCH`); the caller must
+//!   see the parent. Cycles are rejected.
+//! - Checklist items are ordered and edited by whoever may change the task.
+//! - "Depends on" relations block a task: it cannot move to `done` while a
+//!   blocker is open unless the update is forced. Cycles are rejected.
+//! - `progress` counts checked items and finished subtasks; it is recomputed
+//!   on the task and on its parent whenever either changes.
+//!
+//! Tasks carry a version: a change is stored only against the version it was
+//! computed from, otherwise it is recomputed on the newer task, up to
+//! `MAX_ATTEMPTS` times before answering `VersionConflict`.
+//!
+//! Every change is published (`with_events`) as `TaskCreated`, `TaskUpdated` or
+//! `TaskDeleted` to the creator, the assignee and the conversation participants;
+//! updates also reach whoever could see the task before the change.
+//!
+//! Timeline (`with_activity`):
+//! - Creation and changes of status, assignee and due date are recorded.
+//! - Whoever can see a task may comment on it; `@username` mentions users who
+//!   can see the task (others are ignored).
+//! - The creator, assignees, commenters and mentioned users watch the task;
+//!   anyone who can see it may watch or unwatch it. New comments and changes
+//!   are published to the watchers who still take part in the task.
+//!
+//! Recurrence and reminders:
+//! - A recurring task needs a due date, the anchor of its series. Completing
+//!   an occurrence creates the next one (same fields, unchecked checklist,
+//!   same parent) until the rule's `count` or `until` ends the series; the
+//!   next occurrence is created once, even if the task is reopened and
+//!   completed again. A new rule restarts the series at the current due date.
+//! - `reminders` are minutes before the due date; `TaskReminderService` sends
+//!   them. Moving the due date re-arms them.
+//!
+//! Env:
+//! - TASK_PAGE_SIZE (default 20)
+//! - TASK_MAX_PAGE_SIZE (default 100)
+
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::error::TaskError;
+use crate::interfaces::repositories::conversation::ConversationRepository;
+use crate::interfaces::repositories::task_activity::TaskActivityRepository;
+use crate::models::entities::message::LinkedTask;
+use crate::models::entities::pagination::{decode_cursor, Page};
+use crate::models::entities::recurrence::TaskRecurrence;
+use crate::models::entities::task::{
+    check_reminders, normalize_reminders, AddChecklistItemRequest, AddTaskRequest,
+    ChecklistItem, Task, TaskEvent, TaskEventKind, TaskProgress, TaskStatus,
+    UpdateChecklistItemRequest, UpdateTaskRequest,
+};
+use crate::models::entities::task_activity::{
+    mentions, ActivityKind, AddCommentRequest, TaskActivity, TaskComment, TimelineEntry,
+    TimelineEvent,
+};
+use crate::models::entities::user::User;
+use crate::models::traits::task_data_trait::{
+    TaskDataTrait, TaskLinks, TaskSearch, TaskSort, TaskSortField,
+};
+use crate::models::traits::task_event_sink::TaskEventSink;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Longest accepted text search
+const MAX_QUERY_LENGTH: usize = 100;
+
+/// Tries to store a change before giving up on concurrent changes
+const MAX_ATTEMPTS: usize = 3;
+
+/// Access to other users' tasks granted by the caller's permissions
+/// (`task:read`, `task:update`, `task:delete`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskAccess {
+    pub read_all: bool,
+    pub update_all: bool,
+    pub delete_all: bool,
+}
+
+/// Filters and paging of a task listing, as received
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+    pub due_before: Option<String>,
+    pub q: Option<String>,
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A task with its parent, subtasks and blocking relations
+/// (only the related tasks the caller can see)
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskDetail {
+    #[serde(flatten)]
+    pub task: Task,
+    pub parent: Option<LinkedTask>,
+    pub subtasks: Vec<LinkedTask>,
+    /// Tasks that must be done first
+    pub depends_on: Vec<LinkedTask>,
+    /// Tasks waiting for this one
+    pub blocks: Vec<LinkedTask>,
+}
+
+pub struct TaskService {
+    conversations: Arc<dyn ConversationRepository>,
+    events: Option<Arc<dyn TaskEventSink>>,
+    activity: Option<Arc<dyn TaskActivityRepository>>,
+    page_size: usize,
+    max_page_size: usize,
+}
+
+impl TaskService {
+    pub fn new(
+        conversations: Arc<dyn ConversationRepository>,
+        page_size: usize,
+        max_page_size: usize,
+    ) -> Self {
+        Self {
+            conversations,
+            events: None,
+            activity: None,
+            page_size,
+            max_page_size,
+        }
+    }
+
+    /// Publishes task changes to `events` (the chat server in production).
+    pub fn with_events(mut self, events: Arc<dyn TaskEventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Records changes and stores comments in `activity`.
+    pub fn with_activity(mut self, activity: Arc<dyn TaskActivityRepository>) -> Self {
+        self.activity = Some(activity);
+        self
+    }
+
+    /// Builds the service reading TASK_PAGE_SIZE and TASK_MAX_PAGE_SIZE.
+    pub fn from_env(conversations: Arc<dyn ConversationRepository>) -> Self {
+        let read = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        let max_page_size = read("TASK_MAX_PAGE_SIZE", 100);
+        Self::new(
+            conversations,
+            read("TASK_PAGE_SIZE", 20).min(max_page_size),
+            max_page_size,
+        )
+    }
+
+    /// Returns one page of the tasks `user` can see that match `query`.
+    pub async fn list(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        query: TaskQuery,
+    ) -> Result<Page<Task>, TaskError> {
+        let limit = query.limit.unwrap_or(self.page_size);
+        if limit == 0 || limit > self.max_page_size {
+            return Err(TaskError::InvalidQuery(format!(
+                "limit must be between 1 and {}",
+                self.max_page_size
+            )));
+        }
+        let status = match non_blank(query.status) {
+            Some(status) => Some(
+                serde_json::from_value::<TaskStatus>(serde_json::Value::String(status.clone()))
+                    .map_err(|_| TaskError::InvalidQuery(format!("unknown status: {}", status)))?,
+            ),
+            None => None,
+        };
+        let sort = match non_blank(query.sort) {
+            Some(sort) => sort.parse::<TaskSort>().map_err(TaskError::InvalidQuery)?,
+            None => TaskSort::default(),
+        };
+        let due_before = match non_blank(query.due_before) {
+            Some(due) => Some(
+                DateTime::parse_from_rfc3339(&due)
+                    .map_err(|_| {
+                        TaskError::InvalidQuery("due_before must be an RFC 3339 date".to_string())
+                    })?
+                    .with_timezone(&Utc),
+            ),
+            None => None,
+        };
+        let text = non_blank(query.q);
+        if text
+            .as_ref()
+            .is_some_and(|q| q.chars().count() > MAX_QUERY_LENGTH)
+        {
+            return Err(TaskError::InvalidQuery(format!(
+                "q must be at most {} characters",
+                MAX_QUERY_LENGTH
+            )));
+        }
+        let after = match non_blank(query.cursor) {
+            Some(cursor) => Some(
+                decode_cursor(&cursor)
+                    .as_deref()
+                    .and_then(parse_cursor)
+                    .ok_or_else(|| TaskError::InvalidQuery("invalid cursor".to_string()))?,
+            ),
+            None => None,
+        };
+        let visible_to = if access.read_all {
+            None
+        } else {
+            let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+            Some((user_id, self.conversation_ids(user).await?))
+        };
+
+        let search = TaskSearch {
+            visible_to,
+            status,
+            assignee: non_blank(query.assignee),
+            due_before,
+            due_after: None,
+            text,
+            sort,
+            after,
+            // One extra row tells whether another page exists
+            limit: limit + 1,
+        };
+        let rows = tasks
+            .search_tasks(&search)
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        Ok(Page::from_overfetch(rows, limit, |t| {
+            format!("{}:{}", sort.key(t), t.uuid)
+        }))
+    }
+
+    /// Creates a task on behalf of `creator`, under a parent `creator` can see
+    /// with `access`.
+    pub async fn create(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        creator: &User,
+        access: TaskAccess,
+        req: AddTaskRequest,
+    ) -> Result<Task, TaskError> {
+        req.validate()
+            .map_err(|e| TaskError::InvalidTask(e.to_string()))?;
+        let task_name = req.task_name.trim().to_string();
+        if task_name.is_empty() {
+            return Err(TaskError::InvalidTask("task name required".to_string()));
+        }
+        if let Some(assignee) = &req.assignee {
+            check_assignee(users, assignee).await?;
+        }
+        if let Some(conversation_id) = &req.conversation_id {
+            if !self.takes_part(creator, conversation_id).await? {
+                return Err(TaskError::ConversationNotFound);
+            }
+        }
+        if let Some(parent) = &req.parent {
+            self.find_visible(tasks, creator, access, parent).await?;
+        }
+        if let Some(reminders) = &req.reminders {
+            check_reminders(reminders).map_err(TaskError::InvalidTask)?;
+        }
+        if let Some(rule) = &req.recurrence {
+            rule.check().map_err(TaskError::InvalidTask)?;
+            if req.due_date.is_none() {
+                return Err(TaskError::InvalidTask(
+                    "recurrence requires a due date".to_string(),
+                ));
+            }
+        }
+
+        let mut task = Task::new(Uuid::new_v4().to_string(), task_name);
+        task.description = req
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+        task.priority = req.priority.unwrap_or_default();
+        task.assignee = req.assignee;
+        task.creator = creator.id_string();
+        task.conversation_id = req.conversation_id;
+        task.source_message_id = req.source_message_id;
+        task.due_date = req.due_date;
+        task.recurrence = req
+            .recurrence
+            .zip(req.due_date)
+            .map(|(rule, anchor)| TaskRecurrence::new(rule, anchor));
+        task.reminders = normalize_reminders(req.reminders.unwrap_or_default());
+        task.set_status(req.status.unwrap_or_default());
+        for watcher in task.creator.clone().into_iter().chain(task.assignee.clone()) {
+            task.watch(&watcher);
+        }
+
+        let uuid = task.uuid.clone();
+        let created = tasks
+            .add_task(task)
+            .await
+            .ok_or(TaskError::TaskCreationError)?;
+        info!("Task {} created by username={}", uuid, creator.username);
+        if let Some(parent) = req.parent {
+            if !tasks.set_parent(uuid.clone(), Some(parent.clone())).await {
+                // Do not leave a top-level task the caller did not ask for
+                if !tasks.delete_task(uuid.clone()).await {
+                    error!("Task {} could not be removed after its parent failed", uuid);
+                }
+                return Err(TaskError::DatabaseError);
+            }
+            self.refresh_progress(tasks, &parent).await?;
+        }
+        self.publish(TaskEventKind::TaskCreated, &created, &[])
+            .await;
+        self.record(&created, creator, ActivityKind::Created, None, None)
+            .await;
+        Ok(created)
+    }
+
+    /// Applies a partial update to the task `uuid` on behalf of `user`.
+    pub async fn update(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        patch: UpdateTaskRequest,
+    ) -> Result<Task, TaskError> {
+        patch
+            .validate()
+            .map_err(|e| TaskError::InvalidTask(e.to_string()))?;
+        if patch
+            .task_name
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err(TaskError::InvalidTask("task name required".to_string()));
+        }
+
+        let task = self.find_editable(tasks, user, access, uuid).await?;
+        if patch.is_empty() {
+            return Ok(task);
+        }
+        if let Some(Some(assignee)) = &patch.assignee {
+            if task.assignee.as_deref() != Some(assignee.as_str()) {
+                check_assignee(users, assignee).await?;
+            }
+        }
+        if let Some(reminders) = &patch.reminders {
+            check_reminders(reminders).map_err(TaskError::InvalidTask)?;
+        }
+        if let Some(Some(rule)) = &patch.recurrence {
+            rule.check().map_err(TaskError::InvalidTask)?;
+        }
+        let links = tasks
+            .task_links(uuid.to_string())
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        let new_parent = patch.parent.clone().filter(|p| *p != links.parent);
+        if let Some(Some(parent)) = &new_parent {
+            self.find_visible(tasks, user, access, parent).await?;
+            if self
+                .reaches(tasks, parent, uuid, |l| l.parent.into_iter().collect())
+                .await?
+            {
+                return Err(TaskError::DependencyCycle);
+            }
+        }
+        if patch.status == Some(TaskStatus::Done) && task.status != TaskStatus::Done && !patch.force {
+            let open = self.open_blockers(tasks, &links).await?;
+            if !open.is_empty() {
+                info!("Task {} is blocked by {:?}", uuid, open);
+                return Err(TaskError::BlockedBy(open));
+            }
+        }
+
+        let (before, updated) = self
+            .commit(tasks, task, |task| {
+                let assignee = task.assignee.clone();
+                let recurrence = patch.recurrence.clone();
+                task.apply(patch.clone());
+                match recurrence {
+                    // A new rule starts a new series at the current due date
+                    Some(Some(rule)) => {
+                        task.recurrence = task.due_date.map(|due| TaskRecurrence::new(rule, due))
+                    }
+                    Some(None) => task.recurrence = None,
+                    None => {}
+                }
+                if task.recurrence.is_some() && task.due_date.is_none() {
+                    return Err(TaskError::InvalidTask(
+                        "recurrence requires a due date".to_string(),
+                    ));
+                }
+                if let Some(new_assignee) = task.assignee.clone() {
+                    if assignee.as_ref() != Some(&new_assignee) {
+                        task.watch(&new_assignee);
+                    }
+                }
+                Ok(true)
+            })
+            .await?;
+        let completing = updated.status == TaskStatus::Done && before.status != TaskStatus::Done;
+        // A former assignee hears about the change that removed them too
+        self.publish(TaskEventKind::TaskUpdated, &updated, &[&before])
+            .await;
+        self.record_changes(&before, &updated, user).await;
+
+        // Roll the status change up to the parent(s)
+        let parent_now = new_parent.clone().unwrap_or(links.parent.clone());
+        let mut parents: Vec<String> = Vec::new();
+        if let Some(parent) = new_parent {
+            if !tasks.set_parent(uuid.to_string(), parent.clone()).await {
+                return Err(TaskError::DatabaseError);
+            }
+            parents.extend(links.parent.clone());
+            parents.extend(parent);
+        } else if before.status != updated.status {
+            parents.extend(links.parent.clone());
+        }
+        for parent in parents {
+            self.refresh_progress(tasks, &parent).await?;
+        }
+        if completing {
+            return self.spawn_next(tasks, user, updated, parent_now).await;
+        }
+        Ok(updated)
+    }
+
+    /// Deletes the task `uuid` on behalf of `user`.
+    pub async fn delete(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<(), TaskError> {
+        let task = self.find_visible(tasks, user, access, uuid).await?;
+        let is_creator = user.id_string().is_some() && task.creator == user.id_string();
+        if !(access.delete_all || is_creator) {
+            warn!(
+                "Task {} deletion denied for username={}",
+                uuid, user.username
+            );
+            return Err(TaskError::NotAllowed);
+        }
+        let links = tasks
+            .task_links(uuid.to_string())
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        if !tasks.delete_task(uuid.to_string()).await {
+            return Err(TaskError::DatabaseError);
+        }
+        info!("Task {} deleted by username={}", uuid, user.username);
+        if let Some(activity) = &self.activity {
+            if let Err(e) = activity.delete_for_task(uuid).await {
+                error!("Could not delete the timeline of task {}: {:?}", uuid, e);
+            }
+        }
+        self.publish(TaskEventKind::TaskDeleted, &task, &[]).await;
+        if let Some(parent) = links.parent {
+            self.refresh_progress(tasks, &parent).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the task `uuid` if `user` can see it.
+    pub async fn get(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<Task, TaskError> {
+        self.find_visible(tasks, user, access, uuid).await
+    }
+
+    /// The tasks due at or after `since` that `user` can see, earliest due
+    /// date first, at most `limit` of them.
+    pub async fn due_tasks(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Task>, TaskError> {
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        let search = TaskSearch {
+            visible_to: Some((user_id, self.conversation_ids(user).await?)),
+            due_after: Some(since),
+            sort: TaskSort {
+                field: TaskSortField::DueDate,
+                descending: false,
+            },
+            limit,
+            ..TaskSearch::default()
+        };
+        tasks
+            .search_tasks(&search)
+            .await
+            .ok_or(TaskError::DatabaseError)
+    }
+
+    /// Comments on the task `uuid`; mentioned users who can see it start watching it.
+    pub async fn add_comment(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        users: &dyn UserDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        req: AddCommentRequest,
+    ) -> Result<TaskComment, TaskError> {
+        req.validate()
+            .map_err(|e| TaskError::InvalidTask(e.to_string()))?;
+        let text = req.text.trim().to_string();
+        if text.is_empty() {
+            return Err(TaskError::InvalidTask("comment required".to_string()));
+        }
+        let activity = self.activity_repository()?;
+        let task = self.find_visible(tasks, user, access, uuid).await?;
+
+        let mut mentioned = Vec::new();
+        for name in mentions(&text) {
+            match users.find_active_user_by_username(name).await {
+                Some(m) if self.can_see(&m, TaskAccess::default(), &task).await? => {
+                    mentioned.extend(m.id_string())
+                }
+                _ => info!("Task {} comment mentions @{}, who cannot see it", uuid, name),
+            }
+        }
+        let comment = activity
+            .add_comment(TaskComment::new(
+                uuid.to_string(),
+                user,
+                text,
+                mentioned.clone(),
+            ))
+            .await
+            .map_err(db_error)?;
+        info!("Task {} commented by username={}", uuid, user.username);
+
+        let watchers: Vec<String> = user.id_string().into_iter().chain(mentioned).collect();
+        let (_, task) = self
+            .commit(tasks, task, |task| {
+                let mut new_watchers = false;
+                for watcher in &watchers {
+                    new_watchers |= task.watch(watcher);
+                }
+                Ok(new_watchers)
+            })
+            .await?;
+        self.publish_timeline(&task, TimelineEntry::Comment(comment.clone()))
+            .await;
+        Ok(comment)
+    }
+
+    /// Comments and recorded changes of the task `uuid`, oldest first.
+    pub async fn timeline(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<Vec<TimelineEntry>, TaskError> {
+        let activity = self.activity_repository()?;
+        self.find_visible(tasks, user, access, uuid).await?;
+        let mut entries: Vec<TimelineEntry> = activity
+            .find_comments(uuid)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(TimelineEntry::Comment)
+            .collect();
+        entries.extend(
+            activity
+                .find_activity(uuid)
+                .await
+                .map_err(db_error)?
+                .into_iter()
+                .map(TimelineEntry::Activity),
+        );
+        entries.sort_by(|a, b| {
+            a.created_at()
+                .cmp(&b.created_at())
+                .then_with(|| a.uuid().cmp(b.uuid()))
+        });
+        Ok(entries)
+    }
+
+    /// Starts or stops (`watching`) following the timeline of the task `uuid`.
+    pub async fn set_watching(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        watching: bool,
+    ) -> Result<Task, TaskError> {
+        let task = self.find_visible(tasks, user, access, uuid).await?;
+        let user_id = user.id_string().ok_or(TaskError::DatabaseError)?;
+        let (before, task) = self
+            .commit(tasks, task, |task| {
+                if watching {
+                    return Ok(task.watch(&user_id));
+                }
+                let count = task.watchers.len();
+                task.watchers.retain(|w| *w != user_id);
+                Ok(task.watchers.len() != count)
+            })
+            .await?;
+        if before.version != task.version {
+            info!(
+                "Task {} {} by username={}",
+                uuid,
+                if watching { "watched" } else { "unwatched" },
+                user.username
+            );
+        }
+        Ok(task)
+    }
+
+    /// Returns the task `uuid` with its parent, subtasks and blocking relations.
+    pub async fn detail(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<TaskDetail, TaskError> {
+        let task = self.find_visible(tasks, user, access, uuid).await?;
+        let links = tasks
+            .task_links(uuid.to_string())
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        let related: Vec<String> = links
+            .parent
+            .iter()
+            .chain(&links.subtasks)
+            .chain(&links.depends_on)
+            .chain(&links.blocks)
+            .cloned()
+            .collect();
+        let mut visible = Vec::new();
+        for t in tasks
+            .find_tasks(related)
+            .await
+            .ok_or(TaskError::DatabaseError)?
+        {
+            if self.can_see(user, access, &t).await? {
+                visible.push(t);
+            }
+        }
+        let summaries = |uuids: &[String]| -> Vec<LinkedTask> {
+            uuids
+                .iter()
+                .filter_map(|u| visible.iter().find(|t| &t.uuid == u))
+                .map(LinkedTask::from)
+                .collect()
+        };
+        Ok(TaskDetail {
+            parent: summaries(links.parent.as_slice()).into_iter().next(),
+            subtasks: summaries(&links.subtasks),
+            depends_on: summaries(&links.depends_on),
+            blocks: summaries(&links.blocks),
+            task,
+        })
+    }
+
+    /// Makes the task `uuid` wait for `blocker`.
+    pub async fn add_dependency(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        blocker: &str,
+    ) -> Result<TaskDetail, TaskError> {
+        self.find_editable(tasks, user, access, uuid).await?;
+        self.find_visible(tasks, user, access, blocker).await?;
+        if self.reaches(tasks, blocker, uuid, |l| l.depends_on).await? {
+            return Err(TaskError::DependencyCycle);
+        }
+        if !tasks
+            .add_dependency(uuid.to_string(), blocker.to_string())
+            .await
+        {
+            return Err(TaskError::DatabaseError);
+        }
+        info!(
+            "Task {} depends on {} (username={})",
+            uuid, blocker, user.username
+        );
+        self.detail(tasks, user, access, uuid).await
+    }
+
+    /// Removes the relation making the task `uuid` wait for `blocker`.
+    pub async fn remove_dependency(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        blocker: &str,
+    ) -> Result<TaskDetail, TaskError> {
+        self.find_editable(tasks, user, access, uuid).await?;
+        if !tasks
+            .remove_dependency(uuid.to_string(), blocker.to_string())
+            .await
+        {
+            return Err(TaskError::DatabaseError);
+        }
+        self.detail(tasks, user, access, uuid).await
+    }
+
+    /// Inserts a checklist item into the task `uuid`.
+    pub async fn add_checklist_item(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        req: AddChecklistItemRequest,
+    ) -> Result<Task, TaskError> {
+        req.validate()
+            .map_err(|e| TaskError::InvalidTask(e.to_string()))?;
+        let text = non_blank(Some(req.text))
+            .ok_or_else(|| TaskError::InvalidTask("item text required".to_string()))?;
+        let task = self.find_editable(tasks, user, access, uuid).await?;
+        let id = Uuid::new_v4().to_string();
+        self.save_checklist(tasks, task, |task| {
+            let position = req
+                .position
+                .unwrap_or(task.checklist.len())
+                .min(task.checklist.len());
+            task.checklist.insert(
+                position,
+                ChecklistItem {
+                    id: id.clone(),
+                    text: text.clone(),
+                    done: false,
+                },
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    /// Edits, checks or moves a checklist item of the task `uuid`.
+    pub async fn update_checklist_item(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        item_id: &str,
+        req: UpdateChecklistItemRequest,
+    ) -> Result<Task, TaskError> {
+        req.validate()
+            .map_err(|e| TaskError::InvalidTask(e.to_string()))?;
+        let text = match req.text {
+            Some(text) => Some(
+                non_blank(Some(text))
+                    .ok_or_else(|| TaskError::InvalidTask("item text required".to_string()))?,
+            ),
+            None => None,
+        };
+        let task = self.find_editable(tasks, user, access, uuid).await?;
+        self.save_checklist(tasks, task, |task| {
+            let index = task
+                .checklist
+                .iter()
+                .position(|i| i.id == item_id)
+                .ok_or(TaskError::ChecklistItemNotFound)?;
+            let mut item = task.checklist.remove(index);
+            if let Some(text) = &text {
+                item.text = text.clone();
+            }
+            if let Some(done) = req.done {
+                item.done = done;
+            }
+            let position = req.position.unwrap_or(index).min(task.checklist.len());
+            task.checklist.insert(position, item);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Removes a checklist item from the task `uuid`.
+    pub async fn remove_checklist_item(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+        item_id: &str,
+    ) -> Result<Task, TaskError> {
+        let task = self.find_editable(tasks, user, access, uuid).await?;
+        self.save_checklist(tasks, task, |task| {
+            let index = task
+                .checklist
+                .iter()
+                .position(|i| i.id == item_id)
+                .ok_or(TaskError::ChecklistItemNotFound)?;
+            task.checklist.remove(index);
+            Ok(())
+        })
+        .await
+    }
+
+    // Creates the next occurrence of a completed recurring task (once) under the
+    // same parent, and returns the completed task pointing at it
+    async fn spawn_next(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        done: Task,
+        parent: Option<String>,
+    ) -> Result<Task, TaskError> {
+        let Some(recurrence) = done.recurrence.clone() else {
+            return Ok(done);
+        };
+        if recurrence.next_task.is_some() {
+            return Ok(done);
+        }
+        let Some((following, due)) = recurrence.following() else {
+            info!("Task {} ends its series", done.uuid);
+            return Ok(done);
+        };
+
+        let mut next = Task::new(Uuid::new_v4().to_string(), done.task_name.clone());
+        next.description = done.description.clone();
+        next.priority = done.priority;
+        next.assignee = done.assignee.clone();
+        next.creator = done.creator.clone();
+        next.conversation_id = done.conversation_id.clone();
+        next.due_date = Some(due);
+        next.recurrence = Some(following);
+        next.reminders = done.reminders.clone();
+        next.watchers = done.watchers.clone();
+        next.checklist = done
+            .checklist
+            .iter()
+            .map(|item| ChecklistItem {
+                id: Uuid::new_v4().to_string(),
+                text: item.text.clone(),
+                done: false,
+            })
+            .collect();
+        next.progress = TaskProgress::of(&next.checklist, &[]);
+        let created = tasks
+            .add_task(next)
+            .await
+            .ok_or(TaskError::TaskCreationError)?;
+        info!(
+            "Task {} spawned the next occurrence {} due {}",
+            done.uuid, created.uuid, due
+        );
+        if let Some(parent) = parent {
+            if !tasks.set_parent(created.uuid.clone(), Some(parent.clone())).await {
+                return Err(TaskError::DatabaseError);
+            }
+            self.refresh_progress(tasks, &parent).await?;
+        }
+        self.publish(TaskEventKind::TaskCreated, &created, &[])
+            .await;
+        self.record(&created, user, ActivityKind::Created, None, None)
+            .await;
+
+        let (_, done) = self
+            .commit(tasks, done, |task| match &mut task.recurrence {
+                Some(recurrence) if recurrence.next_task.is_none() => {
+                    recurrence.next_task = Some(created.uuid.clone());
+                    Ok(true)
+                }
+                _ => Ok(false),
+            })
+            .await?;
+        Ok(done)
+    }
+
+    // Applies a checklist `change` to the task and stores it with its new progress
+    async fn save_checklist(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        task: Task,
+        mut change: impl FnMut(&mut Task) -> Result<(), TaskError>,
+    ) -> Result<Task, TaskError> {
+        let subtasks = self.subtasks(tasks, &task.uuid).await?;
+        let (_, updated) = self
+            .commit(tasks, task, |task| {
+                change(task)?;
+                task.progress = TaskProgress::of(&task.checklist, &subtasks);
+                task.updated_at = Utc::now();
+                Ok(true)
+            })
+            .await?;
+        self.publish(TaskEventKind::TaskUpdated, &updated, &[])
+            .await;
+        Ok(updated)
+    }
+
+    // Recomputes the progress of the task `uuid`, storing and publishing it if it changed
+    async fn refresh_progress(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        uuid: &str,
+    ) -> Result<(), TaskError> {
+        let Some(task) = tasks.find_task(uuid.to_string()).await else {
+            return Ok(());
+        };
+        let subtasks = self.subtasks(tasks, uuid).await?;
+        let (before, updated) = self
+            .commit(tasks, task, |task| {
+                let progress = TaskProgress::of(&task.checklist, &subtasks);
+                if progress == task.progress {
+                    return Ok(false);
+                }
+                task.progress = progress;
+                Ok(true)
+            })
+            .await?;
+        if before.version != updated.version {
+            self.publish(TaskEventKind::TaskUpdated, &updated, &[])
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn subtasks(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        uuid: &str,
+    ) -> Result<Vec<Task>, TaskError> {
+        let links = tasks
+            .task_links(uuid.to_string())
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        tasks
+            .find_tasks(links.subtasks)
+            .await
+            .ok_or(TaskError::DatabaseError)
+    }
+
+    // Applies `change` to `task` and stores it if nobody else changed the task
+    // meanwhile, otherwise recomputes it on the newer task. `change` answers
+    // false when there is nothing to store; returns the task before and after
+    async fn commit(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        mut task: Task,
+        mut change: impl FnMut(&mut Task) -> Result<bool, TaskError>,
+    ) -> Result<(Task, Task), TaskError> {
+        let uuid = task.uuid.clone();
+        for attempt in 1..=MAX_ATTEMPTS {
+            let before = task.clone();
+            if !change(&mut task)? {
+                return Ok((before, task));
+            }
+            task.version = before.version + 1;
+            if let Some(saved) = tasks
+                .update_task(uuid.clone(), task, before.version)
+                .await
+            {
+                return Ok((before, saved));
+            }
+            task = tasks
+                .find_task(uuid.clone())
+                .await
+                .ok_or(TaskError::NoTaskFoundWithId)?;
+            if task.version == before.version {
+                return Err(TaskError::DatabaseError);
+            }
+            info!(
+                "Task {} changed concurrently (attempt {}/{})",
+                uuid, attempt, MAX_ATTEMPTS
+            );
+        }
+        warn!("Task {} change gave up after conflicts", uuid);
+        Err(TaskError::VersionConflict)
+    }
+
+    // UUIDs of the blockers that are not done yet
+    async fn open_blockers(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        links: &TaskLinks,
+    ) -> Result<Vec<String>, TaskError> {
+        let blockers = tasks
+            .find_tasks(links.depends_on.clone())
+            .await
+            .ok_or(TaskError::DatabaseError)?;
+        Ok(blockers
+            .into_iter()
+            .filter(|t| t.status != TaskStatus::Done)
+            .map(|t| t.uuid)
+            .collect())
+    }
+
+    // True if `target` is `from` or can be reached from it following `next`
+    async fn reaches(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        from: &str,
+        target: &str,
+        next: fn(TaskLinks) -> Vec<String>,
+    ) -> Result<bool, TaskError> {
+        let mut pending = vec![from.to_string()];
+        let mut seen = HashSet::new();
+        while let Some(uuid) = pending.pop() {
+            if uuid == target {
+                return Ok(true);
+            }
+            if !seen.insert(uuid.clone()) {
+                continue;
+            }
+            let links = tasks
+                .task_links(uuid)
+                .await
+                .ok_or(TaskError::DatabaseError)?;
+            pending.extend(next(links));
+        }
+        Ok(false)
+    }
+
+    // Sends a change to the users who can see `task` (or could, in `previous`)
+    async fn publish(&self, kind: TaskEventKind, task: &Task, previous: &[&Task]) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let mut audience = Vec::new();
+        for t in std::iter::once(task).chain(previous.iter().copied()) {
+            audience.extend(t.creator.clone());
+            audience.extend(t.assignee.clone());
+            if let Some(conversation_id) = &t.conversation_id {
+                audience.extend(self.participants(conversation_id).await);
+            }
+        }
+        audience.sort();
+        audience.dedup();
+        events.publish(TaskEvent {
+            kind,
+            task: task.clone(),
+            audience,
+        });
+    }
+
+    // Records the status, assignee and due date changes of an update
+    async fn record_changes(&self, before: &Task, after: &Task, actor: &User) {
+        if before.status != after.status {
+            let (from, to) = (before.status.as_str(), after.status.as_str());
+            self.record(
+                after,
+                actor,
+                ActivityKind::StatusChanged,
+                Some(from.to_string()),
+                Some(to.to_string()),
+            )
+            .await;
+        }
+        if before.assignee != after.assignee {
+            self.record(
+                after,
+                actor,
+                ActivityKind::Reassigned,
+                before.assignee.clone(),
+                after.assignee.clone(),
+            )
+            .await;
+        }
+        if before.due_date != after.due_date {
+            self.record(
+                after,
+                actor,
+                ActivityKind::DueDateChanged,
+                before.due_date.map(|d| d.to_rfc3339()),
+                after.due_date.map(|d| d.to_rfc3339()),
+            )
+            .await;
+        }
+    }
+
+    // Adds an entry to the task history; a failure is logged, not returned,
+    // since the change itself is already stored
+    async fn record(
+        &self,
+        task: &Task,
+        actor: &User,
+        kind: ActivityKind,
+        from: Option<String>,
+        to: Option<String>,
+    ) {
+        let Some(activity) = &self.activity else {
+            return;
+        };
+        let entry = TaskActivity::new(task.uuid.clone(), actor, kind, from, to);
+        match activity.add_activity(entry).await {
+            Ok(entry) => {
+                self.publish_timeline(task, TimelineEntry::Activity(entry))
+                    .await
+            }
+            Err(e) => error!("Could not record activity of task {}: {:?}", task.uuid, e),
+        }
+    }
+
+    // Sends a timeline entry to the watchers who still take part in the task
+    async fn publish_timeline(&self, task: &Task, entry: TimelineEntry) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let mut members: Vec<String> = task.creator.iter().chain(&task.assignee).cloned().collect();
+        if let Some(conversation_id) = &task.conversation_id {
+            members.extend(self.participants(conversation_id).await);
+        }
+        let audience = task
+            .watchers
+            .iter()
+            .filter(|w| members.contains(w))
+            .cloned()
+            .collect();
+        events.publish_timeline(TimelineEvent {
+            task_id: task.uuid.clone(),
+            entry,
+            audience,
+        });
+    }
+
+    fn activity_repository(&self) -> Result<&Arc<dyn TaskActivityRepository>, TaskError> {
+        self.activity.as_ref().ok_or_else(|| {
+            error!("Task activity storage is not configured");
+            TaskError::DatabaseError
+        })
+    }
+
+    // UUIDs of the participants of a conversation; empty if it cannot be read
+    async fn participants(&self, conversation_id: &str) -> Vec<String> {
+        match self
+            .conversations
+            .find_by_id(Thing::from(("conversation", conversation_id)))
+            .await
+        {
+            Ok(Some(conversation)) => conversation
+                .participants
+                .iter()
+                .map(|p| p.id.to_raw())
+                .collect(),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                error!(
+                    "Could not load participants of conversation {}: {:?}",
+                    conversation_id, e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    // Loads a task, hiding the ones the user cannot see
+    async fn find_visible(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<Task, TaskError> {
+        let task = tasks
+            .find_task(uuid.to_string())
+            .await
+            .ok_or(TaskError::NoTaskFoundWithId)?;
+        if self.can_see(user, access, &task).await? {
+            Ok(task)
+        } else {
+            Err(TaskError::NoTaskFoundWithId)
+        }
+    }
+
+    // Loads a task the user may change
+    async fn find_editable(
+        &self,
+        tasks: &dyn TaskDataTrait,
+        user: &User,
+        access: TaskAccess,
+        uuid: &str,
+    ) -> Result<Task, TaskError> {
+        let task = self.find_visible(tasks, user, access, uuid).await?;
+        if access.update_all || involves(&task, user) {
+            Ok(task)
+        } else {
+            warn!("Task {} update denied for username={}", uuid, user.username);
+            Err(TaskError::NotAllowed)
+        }
+    }
+
+    async fn can_see(
+        &self,
+        user: &User,
+        access: TaskAccess,
+        task: &Task,
+    ) -> Result<bool, TaskError> {
+        if access.read_all || involves(task, user) {
+            return Ok(true);
+        }
+        match &task.conversation_id {
+            Some(conversation_id) => self.takes_part(user, conversation_id).await,
+            None => Ok(false),
+        }
+    }
+
+    async fn takes_part(&self, user: &User, conversation_id: &str) -> Result<bool, TaskError> {
+        let user_id = user.id.clone().ok_or(TaskError::DatabaseError)?;
+        let conversation = self
+            .conversations
+            .find_by_id(Thing::from(("conversation", conversation_id)))
+            .await
+            .map_err(db_error)?;
+        Ok(conversation.is_some_and(|c| c.has_participant(&user_id)))
+    }
+
+    async fn conversation_ids(&self, user: &User) -> Result<Vec<String>, TaskError> {
+        let user_id = user.id.clone().ok_or(TaskError::DatabaseError)?;
+        let conversations = self
+            .conversations
+            .find_by_user(user_id)
+            .await
+            .map_err(db_error)?;
+        Ok(conversations
+            .into_iter()
+            .filter_map(|c| c.id.map(|id| id.id.to_raw()))
+            .collect())
+    }
+}
+
+// `<sort key>:<uuid>` of the last task of the previous page
+fn parse_cursor(cursor: &str) -> Option<(i64, String)> {
+    let (key, uuid) = cursor.split_once(':')?;
+    Some((key.parse().ok()?, uuid.to_string()))
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn involves(task: &Task, user: &User) -> bool {
+    user.id_string()
+        .is_some_and(|user_id| task.is_involved(&user_id))
+}
+
+async fn check_assignee(users: &dyn UserDataTrait, assignee: &str) -> Result<(), TaskError> {
+    match users.find_user_by_id(assignee).await {
+        Some(user) if !user.is_deactivated() => Ok(()),
+        _ => {
+            warn!("Task assignee {} does not exist", assignee);
+            Err(TaskError::AssigneeNotFound)
+        }
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> TaskError {
+    error!("Task repository error: {:?}", e);
+    TaskError::DatabaseError
+}
@@ -0,0 +1,111 @@
+//! Searchable, paginated user directory.
+//!
+//! Results are ordered by username and paged with an opaque cursor (the last
+//! username of the previous page). Filters:
+//! - `q`: case-insensitive prefix of the username or display name;
+//! - `wallet`: exact wallet address (compared in lower case).
+//!
+//! Blocked accounts are never listed. Which fields of a user are shown (e-mail
+//! only to admins) is decided by the handler.
+//!
+//! Env:
+//! - USER_DIRECTORY_PAGE_SIZE (default 20)
+//! - USER_DIRECTORY_MAX_PAGE_SIZE (default 100)
+
+use std::env;
+
+use crate::error::AuthError;
+use crate::models::entities::pagination::{decode_cursor, Page};
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::{UserDataTrait, UserSearch};
+
+/// Longest accepted search prefix
+const MAX_QUERY_LENGTH: usize = 64;
+
+/// Filters and paging of a directory request
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryQuery {
+    pub q: Option<String>,
+    pub wallet: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub struct UserDirectoryService {
+    page_size: usize,
+    max_page_size: usize,
+}
+
+impl UserDirectoryService {
+    pub fn new(page_size: usize, max_page_size: usize) -> Self {
+        Self {
+            page_size,
+            max_page_size,
+        }
+    }
+
+    /// Builds the service reading USER_DIRECTORY_PAGE_SIZE and
+    /// USER_DIRECTORY_MAX_PAGE_SIZE.
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        let max_page_size = read("USER_DIRECTORY_MAX_PAGE_SIZE", 100);
+        Self::new(
+            read("USER_DIRECTORY_PAGE_SIZE", 20).min(max_page_size),
+            max_page_size,
+        )
+    }
+
+    /// Returns one page of matching, non-blocked users.
+    pub async fn search(
+        &self,
+        users: &dyn UserDataTrait,
+        query: DirectoryQuery,
+    ) -> Result<Page<User>, AuthError> {
+        let limit = query.limit.unwrap_or(self.page_size);
+        if limit == 0 || limit > self.max_page_size {
+            return Err(AuthError::InvalidSearch(format!(
+                "limit must be between 1 and {}",
+                self.max_page_size
+            )));
+        }
+        let prefix = non_blank(query.q);
+        if prefix
+            .as_ref()
+            .is_some_and(|q| q.chars().count() > MAX_QUERY_LENGTH)
+        {
+            return Err(AuthError::InvalidSearch(format!(
+                "q must be at most {} characters",
+                MAX_QUERY_LENGTH
+            )));
+        }
+        let after = match non_blank(query.cursor) {
+            Some(cursor) => Some(
+                decode_cursor(&cursor)
+                    .ok_or_else(|| AuthError::InvalidSearch("invalid cursor".to_string()))?,
+            ),
+            None => None,
+        };
+
+        let search = UserSearch {
+            prefix,
+            wallet: non_blank(query.wallet).map(|w| w.to_lowercase()),
+            after,
+            // One extra row tells whether another page exists
+            limit: limit + 1,
+        };
+        let rows = users.search_users(&search).await;
+        Ok(Page::from_overfetch(rows, limit, |u| u.username.clone()))
+    }
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
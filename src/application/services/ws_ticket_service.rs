@@ -0,0 +1,95 @@
+//! Short-lived tickets for opening the chat WebSocket.
+//!
+//! Browsers cannot set an `Authorization` header on a WebSocket handshake, and a
+//! JWT in the URL ends up in proxy and access logs. Instead the client calls
+//! `POST /api/ws/ticket` with its bearer token and connects with
+//! `/api/ws/chat?ticket=<ticket>`:
+//! - the ticket is a random secret stored as a `ws_ticket` one-time token (only
+//!   its SHA-256 digest is persisted), bound to the user;
+//! - it expires after a few seconds and is consumed by the handshake, so a
+//!   logged URL is useless.
+//!
+//! Env:
+//! - WS_TICKET_TTL_SECONDS (default 30)
+
+use chrono::Duration;
+use log::{error, warn};
+use serde::Serialize;
+use std::env;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+use crate::error::AuthError;
+use crate::infrastructure::auth::opaque_token::{hash_opaque_token, new_opaque_token};
+use crate::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+
+/// Ticket handed to the client
+#[derive(Debug, Serialize)]
+pub struct WsTicket {
+    /// Opaque secret for the `ticket` query parameter
+    pub ticket: String,
+    /// Seconds until the ticket expires
+    pub expires_in: i64,
+}
+
+pub struct WsTicketService {
+    token_repo: Arc<dyn OneTimeTokenRepository>,
+    ttl: Duration,
+}
+
+impl WsTicketService {
+    pub fn new(token_repo: Arc<dyn OneTimeTokenRepository>, ttl: Duration) -> Self {
+        Self { token_repo, ttl }
+    }
+
+    /// Builds the service reading WS_TICKET_TTL_SECONDS.
+    pub fn from_env(token_repo: Arc<dyn OneTimeTokenRepository>) -> Self {
+        let ttl_secs = env::var("WS_TICKET_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+        Self::new(token_repo, Duration::seconds(ttl_secs))
+    }
+
+    /// Issues a single-use ticket for `user_id`.
+    pub async fn issue(&self, user_id: Thing) -> Result<WsTicket, AuthError> {
+        if let Err(e) = self.token_repo.purge_expired(TokenPurpose::WsTicket).await {
+            warn!("Could not purge expired WebSocket tickets: {:?}", e);
+        }
+
+        let (ticket, hash) = new_opaque_token();
+        self.token_repo
+            .create(OneTimeToken::new(
+                user_id,
+                TokenPurpose::WsTicket,
+                hash,
+                self.ttl,
+            ))
+            .await
+            .map_err(db_error)?;
+
+        Ok(WsTicket {
+            ticket,
+            expires_in: self.ttl.num_seconds(),
+        })
+    }
+
+    /// Consumes a ticket and returns the user it was issued to.
+    pub async fn redeem(&self, ticket: &str) -> Result<Thing, AuthError> {
+        self.token_repo
+            .consume(TokenPurpose::WsTicket, &hash_opaque_token(ticket.trim()))
+            .await
+            .map_err(db_error)?
+            .map(|t| t.user_id)
+            .ok_or_else(|| {
+                warn!("WebSocket ticket rejected: invalid, expired or used");
+                AuthError::InvalidOrExpiredToken
+            })
+    }
+}
+
+fn db_error(e: surrealdb::Error) -> AuthError {
+    error!("One-time token repository error: {:?}", e);
+    AuthError::DatabaseError
+}
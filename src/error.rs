@@ -5,6 +5,9 @@
 //!
 //! # Module Structure
 //! - `task_error`: Task-related error definitions
+//! - `role_error`: Role administration (RBAC) error definitions
+//! - `auth_error`: Account and authentication flow error definitions
+//! - `board_error`: Project and board error definitions
 //!
 //! # Usage
 //! ```rust,ignore
@@ -18,5 +21,11 @@
 //! - Facilitate error reporting
 
 // Import and re-export error types
+pub mod auth_error;
+pub mod board_error;
+pub mod role_error;
 pub mod task_error;
+pub use auth_error::AuthError;
+pub use board_error::BoardError;
+pub use role_error::RoleError;
 pub use task_error::TaskError;
\ No newline at end of file
@@ -0,0 +1,132 @@
+//! Error types and Actix-Web integration for account/authentication flows.
+//!
+//! `AuthError` is returned by the authentication services (password reset, ...)
+//! and their handlers. Like `TaskError`, it implements `ResponseError` so
+//! handlers get a JSON body plus the matching status code.
+//!
+use actix_web::{
+    http::{header, header::ContentType, StatusCode},
+    HttpResponse, ResponseError,
+};
+
+use derive_more::Display;
+use serde::Serialize;
+
+/// Errors that can occur during account and authentication flows.
+#[derive(Debug, Display, Serialize)]
+pub enum AuthError {
+    /// The one-time token is unknown, expired or already used.
+    InvalidOrExpiredToken,
+    /// The new password does not satisfy the password policy.
+    #[display(fmt = "WeakPassword: {}", _0)]
+    WeakPassword(String),
+    /// No user exists for the requested operation.
+    UserNotFound,
+    /// The current password given to confirm a change is wrong.
+    InvalidCurrentPassword,
+    /// The account has no local password (wallet, external or bot login).
+    PasswordNotSet,
+    /// The e-mail address is malformed.
+    InvalidEmail,
+    /// Another account already uses the e-mail address.
+    EmailAlreadyInUse,
+    /// A profile field is invalid.
+    #[display(fmt = "InvalidProfile: {}", _0)]
+    InvalidProfile(String),
+    /// A user directory query (cursor, limit, filters) is invalid.
+    #[display(fmt = "InvalidSearch: {}", _0)]
+    InvalidSearch(String),
+    /// The account was blocked by an administrator.
+    AccountBlocked,
+    /// A wallet address or its signature is invalid.
+    #[display(fmt = "InvalidWallet: {}", _0)]
+    InvalidWallet(String),
+    /// The wallet belongs to another account (merge the accounts instead).
+    WalletAlreadyLinked,
+    /// The wallet or external identity is not linked to the account.
+    CredentialNotFound,
+    /// Removing the credential would leave the account without a way to sign in.
+    LastLoginMethod,
+    /// The accounts cannot be merged.
+    #[display(fmt = "InvalidMerge: {}", _0)]
+    InvalidMerge(String),
+    /// The account's e-mail address must be verified first.
+    EmailNotVerified,
+    /// 2FA is already enabled for the account.
+    MfaAlreadyEnabled,
+    /// 2FA is not enabled (or no enrollment is pending).
+    MfaNotEnabled,
+    /// The TOTP or recovery code is wrong or was already used.
+    InvalidMfaCode,
+    /// No external login provider is configured with that name.
+    UnknownProvider,
+    /// The external provider could not be reached or rejected the exchange.
+    #[display(fmt = "IdentityProviderError: {}", _0)]
+    IdentityProviderError(String),
+    /// The provider's ID token failed validation.
+    InvalidIdToken,
+    /// An account with that e-mail exists but cannot be linked automatically.
+    AccountLinkConflict,
+    /// Too many failed logins for the account or address; retry after N seconds.
+    #[display(fmt = "TooManyLoginAttempts: retry after {}s", _0)]
+    TooManyLoginAttempts(i64),
+    /// The API key is unknown, expired or revoked.
+    InvalidApiKey,
+    /// The API key name or one of its scopes is invalid.
+    #[display(fmt = "InvalidApiKeyScope: {}", _0)]
+    InvalidApiKeyScope(String),
+    /// No API key exists with that id (or it belongs to someone else).
+    ApiKeyNotFound,
+    /// The e-mail could not be delivered.
+    MailDeliveryFailed,
+    /// Failed to read or write the data store.
+    DatabaseError,
+}
+
+// Integrate `AuthError` with Actix-Web error handling.
+impl ResponseError for AuthError {
+    // Render the error as a JSON response with a proper Content-Type header.
+    fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+        let mut builder = HttpResponse::build(self.status_code());
+        builder.insert_header(ContentType::json());
+        if let AuthError::TooManyLoginAttempts(secs) = self {
+            builder.insert_header((header::RETRY_AFTER, secs.to_string()));
+        }
+        builder.json(self)
+    }
+
+    // Map each error variant to its corresponding HTTP status code.
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::InvalidOrExpiredToken => StatusCode::BAD_REQUEST,
+            AuthError::WeakPassword(_) => StatusCode::BAD_REQUEST,
+            AuthError::UserNotFound => StatusCode::NOT_FOUND,
+            AuthError::InvalidCurrentPassword => StatusCode::FORBIDDEN,
+            AuthError::PasswordNotSet => StatusCode::BAD_REQUEST,
+            AuthError::InvalidEmail => StatusCode::BAD_REQUEST,
+            AuthError::EmailAlreadyInUse => StatusCode::CONFLICT,
+            AuthError::InvalidProfile(_) => StatusCode::BAD_REQUEST,
+            AuthError::InvalidSearch(_) => StatusCode::BAD_REQUEST,
+            AuthError::AccountBlocked => StatusCode::FORBIDDEN,
+            AuthError::InvalidWallet(_) => StatusCode::BAD_REQUEST,
+            AuthError::WalletAlreadyLinked => StatusCode::CONFLICT,
+            AuthError::CredentialNotFound => StatusCode::NOT_FOUND,
+            AuthError::LastLoginMethod => StatusCode::BAD_REQUEST,
+            AuthError::InvalidMerge(_) => StatusCode::BAD_REQUEST,
+            AuthError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AuthError::MfaAlreadyEnabled => StatusCode::CONFLICT,
+            AuthError::MfaNotEnabled => StatusCode::BAD_REQUEST,
+            AuthError::InvalidMfaCode => StatusCode::UNAUTHORIZED,
+            AuthError::UnknownProvider => StatusCode::NOT_FOUND,
+            AuthError::IdentityProviderError(_) => StatusCode::BAD_GATEWAY,
+            AuthError::InvalidIdToken => StatusCode::UNAUTHORIZED,
+            AuthError::AccountLinkConflict => StatusCode::CONFLICT,
+            AuthError::TooManyLoginAttempts(_) => StatusCode::TOO_MANY_REQUESTS,
+            AuthError::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            AuthError::InvalidApiKeyScope(_) => StatusCode::BAD_REQUEST,
+            AuthError::ApiKeyNotFound => StatusCode::NOT_FOUND,
+            AuthError::MailDeliveryFailed => StatusCode::BAD_GATEWAY,
+            AuthError::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
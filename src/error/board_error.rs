@@ -0,0 +1,81 @@
+//! Error types and Actix-Web integration for projects and boards.
+//!
+//! `BoardError` follows `TaskError`: each variant maps to a status code and is
+//! rendered as a JSON body. Task rule violations raised while moving cards
+//! (e.g. `BlockedBy`) are passed through as the task error's own response.
+//!
+use actix_web::{
+    http::{header::ContentType, StatusCode},
+    HttpResponse, ResponseError,
+};
+
+use derive_more::Display;
+use serde::Serialize;
+
+use crate::error::TaskError;
+
+/// Project and board errors.
+#[derive(Debug, Display, Serialize)]
+pub enum BoardError {
+    /// The project does not exist or the caller is not a member.
+    ProjectNotFound,
+    /// The board does not exist or the caller is not a member of its project.
+    BoardNotFound,
+    /// The board has no card with the given ID.
+    CardNotFound,
+    /// The board has no column with the given ID.
+    ColumnNotFound,
+    /// The request body failed validation.
+    #[display(fmt = "InvalidBoard: {}", _0)]
+    InvalidBoard(String),
+    /// Only the project owner may do this.
+    NotAllowed,
+    /// The user to add does not exist.
+    UserNotFound,
+    /// The task is already on the board.
+    TaskAlreadyOnBoard,
+    /// Columns holding cards cannot be removed.
+    ColumnNotEmpty,
+    /// The board changed since the given version (or kept changing while retrying).
+    VersionConflict,
+    /// A task rule rejected the change.
+    #[display(fmt = "{}", _0)]
+    Task(TaskError),
+    /// The data store failed while reading or writing a board.
+    DatabaseError,
+}
+
+impl From<TaskError> for BoardError {
+    fn from(e: TaskError) -> Self {
+        BoardError::Task(e)
+    }
+}
+
+// Integrate `BoardError` with Actix-Web error handling.
+impl ResponseError for BoardError {
+    fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+        if let BoardError::Task(e) = self {
+            return e.error_response();
+        }
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .json(self)
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BoardError::ProjectNotFound => StatusCode::NOT_FOUND,
+            BoardError::BoardNotFound => StatusCode::NOT_FOUND,
+            BoardError::CardNotFound => StatusCode::NOT_FOUND,
+            BoardError::ColumnNotFound => StatusCode::BAD_REQUEST,
+            BoardError::InvalidBoard(_) => StatusCode::BAD_REQUEST,
+            BoardError::NotAllowed => StatusCode::FORBIDDEN,
+            BoardError::UserNotFound => StatusCode::BAD_REQUEST,
+            BoardError::TaskAlreadyOnBoard => StatusCode::CONFLICT,
+            BoardError::ColumnNotEmpty => StatusCode::CONFLICT,
+            BoardError::VersionConflict => StatusCode::CONFLICT,
+            BoardError::Task(e) => e.status_code(),
+            BoardError::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
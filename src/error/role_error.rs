@@ -0,0 +1,56 @@
+//! Error types and Actix-Web integration for role administration.
+//!
+//! `RoleError` is returned by `RoleService` and the `/api/roles` handlers. Like
+//! `TaskError`, it implements `ResponseError` so handlers can return it directly
+//! and get a JSON body plus the matching status code.
+//!
+use actix_web::{
+    http::{header::ContentType, StatusCode},
+    HttpResponse, ResponseError,
+};
+
+use derive_more::Display;
+use serde::Serialize;
+
+/// Role-level errors that can occur during RBAC administration.
+#[derive(Debug, Display, Serialize)]
+pub enum RoleError {
+    /// No role exists with the specified name.
+    RoleNotFound,
+    /// A role with the same name already exists.
+    RoleAlreadyExists,
+    /// The role name or one of its permissions is invalid.
+    #[display(fmt = "InvalidRole: {}", _0)]
+    InvalidRole(String),
+    /// Built-in roles cannot be deleted or renamed.
+    BuiltinRoleProtected,
+    /// No user exists with the specified ID.
+    UserNotFound,
+    /// The change would leave no user holding the `admin` role.
+    LastAdmin,
+    /// Failed to read or write the data store.
+    DatabaseError,
+}
+
+// Integrate `RoleError` with Actix-Web error handling.
+impl ResponseError for RoleError {
+    // Render the error as a JSON response with a proper Content-Type header.
+    fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .json(self)
+    }
+
+    // Map each error variant to its corresponding HTTP status code.
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RoleError::RoleNotFound => StatusCode::NOT_FOUND,
+            RoleError::RoleAlreadyExists => StatusCode::CONFLICT,
+            RoleError::InvalidRole(_) => StatusCode::BAD_REQUEST,
+            RoleError::BuiltinRoleProtected => StatusCode::FORBIDDEN,
+            RoleError::UserNotFound => StatusCode::NOT_FOUND,
+            RoleError::LastAdmin => StatusCode::CONFLICT,
+            RoleError::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -22,11 +22,45 @@ use serde::Serialize;
 #[derive(Debug, Display, Serialize)]
 pub enum TaskError {
     /// No tasks were found in the data store.
-    NoTasksFound = 0,
+    NoTasksFound,
     /// Failed to create a new task due to an internal error.
-    TaskCreationError = 1,
+    TaskCreationError,
     /// No task exists with the specified ID.
-    NoTaskFoundWithId = 2,
+    NoTaskFoundWithId,
+    /// The request body failed validation.
+    #[display(fmt = "InvalidTask: {}", _0)]
+    InvalidTask(String),
+    /// The listing filters, sort or cursor are malformed.
+    #[display(fmt = "InvalidQuery: {}", _0)]
+    InvalidQuery(String),
+    /// The assignee is not an existing user.
+    AssigneeNotFound,
+    /// The caller can see the task but may not change it.
+    NotAllowed,
+    /// The conversation does not exist or the caller does not take part in it.
+    ConversationNotFound,
+    /// The message does not exist or the caller does not take part in its conversation.
+    MessageNotFound,
+    /// The message is already linked to a task.
+    MessageAlreadyLinked,
+    /// The parent or dependency would make a task (indirectly) depend on itself.
+    DependencyCycle,
+    /// The task cannot be done while these blocking tasks (UUIDs) are open.
+    #[display(fmt = "BlockedBy: {}", "_0.join(\", \")")]
+    BlockedBy(Vec<String>),
+    /// The task has no checklist item with the given ID.
+    ChecklistItemNotFound,
+    /// The caller has no notification with the given ID.
+    NotificationNotFound,
+    /// The uploaded calendar could not be read.
+    #[display(fmt = "InvalidCalendar: {}", _0)]
+    InvalidCalendar(String),
+    /// The calendar feed token is unknown or was revoked.
+    InvalidFeedToken,
+    /// The task kept changing concurrently while the change was being stored.
+    VersionConflict,
+    /// The data store failed while reading or writing a task.
+    DatabaseError,
 }
 
 // Integrate `TaskError` with Actix-Web error handling.
@@ -44,6 +78,21 @@ impl ResponseError for TaskError {
             TaskError::NoTasksFound => StatusCode::NOT_FOUND,
             TaskError::TaskCreationError => StatusCode::INTERNAL_SERVER_ERROR,
             TaskError::NoTaskFoundWithId => StatusCode::NOT_FOUND,
+            TaskError::InvalidTask(_) => StatusCode::BAD_REQUEST,
+            TaskError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            TaskError::AssigneeNotFound => StatusCode::BAD_REQUEST,
+            TaskError::NotAllowed => StatusCode::FORBIDDEN,
+            TaskError::ConversationNotFound => StatusCode::BAD_REQUEST,
+            TaskError::MessageNotFound => StatusCode::NOT_FOUND,
+            TaskError::MessageAlreadyLinked => StatusCode::CONFLICT,
+            TaskError::DependencyCycle => StatusCode::CONFLICT,
+            TaskError::BlockedBy(_) => StatusCode::CONFLICT,
+            TaskError::ChecklistItemNotFound => StatusCode::NOT_FOUND,
+            TaskError::NotificationNotFound => StatusCode::NOT_FOUND,
+            TaskError::InvalidCalendar(_) => StatusCode::BAD_REQUEST,
+            TaskError::InvalidFeedToken => StatusCode::UNAUTHORIZED,
+            TaskError::VersionConflict => StatusCode::CONFLICT,
+            TaskError::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
\ No newline at end of file
@@ -1,17 +1,25 @@
-//! Authentication helpers: password hashing/verification and JWT creation.
+//! Authentication helpers: JWT creation and validation.
 //!
 //! This module provides utility functions to:
-//! - Hash and verify passwords using `bcrypt`.
 //! - Generate JSON Web Tokens (JWT) with a configurable expiration.
+//! - Embed the user's persisted roles, permissions and token version in the claims.
+//! - Issue the short-lived `mfa_token` of the two-step (TOTP) login.
+//!
+//! Tokens are signed with the key cached by `keys` (HS256 with SECRET_KEY by
+//! default, or RS256/EdDSA with a `kid` header) and validated against any of its
+//! verification keys, which allows rotating keys without logging everyone out.
 //!
 //! Security notes:
-//! - SECRET_KEY debe estar definido en el entorno (es obligatorio). Si falta, se retorna error.
+//! - En modo HS256 SECRET_KEY debe estar definido en el entorno (es obligatorio). Si falta, se retorna error.
 //! - Ajusta JWT_EXP_SECONDS (segundos) para cambiar la expiración del token.
-//! - Ajusta BCRYPT_COST para controlar el “work factor” del hash.
+//! - El hash de contraseñas (Argon2id, migración desde bcrypt) vive en `password`.
 //!
-use bcrypt::{hash, verify, DEFAULT_COST};
 use jsonwebtoken::errors::{Error, ErrorKind};
-use jsonwebtoken::{encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode_header, encode, Header, Validation};
+use crate::infrastructure::auth::keys;
+use crate::models::entities::role::Permission;
+use crate::models::entities::user::User;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -24,6 +32,8 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// - `iat`: Issued-at as a Unix timestamp (seconds since epoch).
 /// - `username`: Username of the user.
 /// - `roles`: List of roles.
+/// - `perms`: Compact permission set derived from the roles (e.g. `admin:all`).
+/// - `ver`: Token version of the user at issue time; stale versions are rejected.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
@@ -31,25 +41,41 @@ pub struct Claims {
     pub iat: usize,
     pub username: String,
     pub roles: Vec<String>,
+    #[serde(default)]
+    pub perms: Vec<String>,
+    #[serde(default)]
+    pub ver: u32,
 }
 
-/// Hash a plaintext password using `bcrypt`.
-///
-/// Returns the password hash on success.
-pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
-    // Permitir configurar el coste via BCRYPT_COST, sino usar DEFAULT_COST
-    let cost = env::var("BCRYPT_COST")
-        .ok()
-        .and_then(|v| v.parse::<u32>().ok())
-        .unwrap_or(DEFAULT_COST);
-    hash(password, cost)
+impl Claims {
+    /// Returns true if the permission set carries `perm` (or `admin:all`).
+    pub fn has_permission(&self, perm: Permission) -> bool {
+        let perm = perm.to_string();
+        let admin = Permission::AdminAll.to_string();
+        self.perms.iter().any(|p| *p == perm || *p == admin)
+    }
 }
 
-/// Verify a plaintext password against a previously stored `bcrypt` hash.
+/// Type marker of the intermediate token issued when 2FA is pending
+pub const MFA_TOKEN_TYPE: &str = "mfa";
+
+/// Claims of the intermediate `mfa_token`.
+///
+/// It proves the password step only: it carries no roles/permissions, so
+/// `validate_token` (which requires them) never accepts it as an access token.
 ///
-/// Returns `true` if the password matches, otherwise `false`.
-pub fn verify_password(password: &str, hash: &str) -> bool {
-    verify(password, hash).unwrap_or(false)
+/// Fields:
+/// - `sub`: the user identifier (UUID).
+/// - `exp` / `iat`: Unix timestamps.
+/// - `ver`: Token version at issue time; revoking tokens also invalidates it.
+/// - `typ`: Always `MFA_TOKEN_TYPE`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub sub: String,
+    pub exp: usize,
+    pub iat: usize,
+    pub ver: u32,
+    pub typ: String,
 }
 
 /// Generate a signed JWT for the given user identifier and metadata.
@@ -61,16 +87,37 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
 /// - `username`: provided username
 /// - `roles`: provided roles
 ///
-/// SECRET_KEY must be set in the environment.
+/// The signing key must be configured (see `keys`).
 pub fn generate_token(user_id: &str, username: &str, roles: &[String]) -> Result<String, Error> {
-    // Leer SECRET_KEY obligatoriamente (sin fallback)
-    let secret = env::var("SECRET_KEY").map_err(|_| Error::from(ErrorKind::InvalidToken))?;
+    encode_claims(user_id, username, roles, &[], 0)
+}
 
-    // Timestamps (seconds since epoch)
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize;
+/// Generate a signed JWT for a persisted user.
+///
+/// Roles, the compact permission set and the token version are taken from the
+/// `User` record, so role changes are reflected in newly issued tokens.
+pub fn generate_token_for_user(user: &User) -> Result<String, Error> {
+    let user_id = user
+        .id_string()
+        .ok_or_else(|| Error::from(ErrorKind::InvalidSubject))?;
+    encode_claims(
+        &user_id,
+        &user.username,
+        &user.role_names(),
+        &user.permission_names(),
+        user.token_version,
+    )
+}
+
+// Build and sign the claims shared by all token generators.
+fn encode_claims(
+    user_id: &str,
+    username: &str,
+    roles: &[String],
+    perms: &[String],
+    ver: u32,
+) -> Result<String, Error> {
+    let now = now_secs();
 
     // Expiración configurable via JWT_EXP_SECONDS, por defecto 24h
     let exp_secs = env::var("JWT_EXP_SECONDS")
@@ -85,23 +132,78 @@ pub fn generate_token(user_id: &str, username: &str, roles: &[String]) -> Result
         iat: now,
         username: username.to_string(),
         roles: roles.to_vec(),
+        perms: perms.to_vec(),
+        ver,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
+    sign(&claims)
 }
 
 /// Validate and decode a signed JWT.
 ///
+/// The key is selected by the `kid` header; its algorithm must match the header.
 /// Returns the claims if the token is valid, otherwise returns an error.
 pub fn validate_token(token: &str) -> Result<Claims, Error> {
-    let secret = env::var("SECRET_KEY").map_err(|_| Error::from(ErrorKind::InvalidToken))?;
-    let decoding_key = DecodingKey::from_secret(secret.as_bytes());
-    let validation = Validation::default();
+    decode_verified(token)
+}
+
+/// Generate the short-lived token returned by `login` when the user has 2FA enabled.
+///
+/// It must be exchanged, together with a TOTP or recovery code, for a real JWT.
+pub fn generate_mfa_token(user: &User, ttl_secs: usize) -> Result<String, Error> {
+    let user_id = user
+        .id_string()
+        .ok_or_else(|| Error::from(ErrorKind::InvalidSubject))?;
+    let now = now_secs();
+    let claims = MfaClaims {
+        sub: user_id,
+        exp: now + ttl_secs,
+        iat: now,
+        ver: user.token_version,
+        typ: MFA_TOKEN_TYPE.to_string(),
+    };
+    sign(&claims)
+}
+
+/// Validate and decode an `mfa_token`; access tokens are rejected.
+pub fn validate_mfa_token(token: &str) -> Result<MfaClaims, Error> {
+    let claims: MfaClaims = decode_verified(token)?;
+    if claims.typ != MFA_TOKEN_TYPE {
+        return Err(Error::from(ErrorKind::InvalidToken));
+    }
+    Ok(claims)
+}
 
-    let token_data = jsonwebtoken::decode::<Claims>(token, &decoding_key, &validation)?;
+// Sign with the active key, announcing its `kid`
+fn sign<T: Serialize>(claims: &T) -> Result<String, Error> {
+    let signing = key_store()?.signing();
+    let mut header = Header::new(signing.algorithm);
+    header.kid = signing.kid.clone();
+    encode(&header, claims, &signing.key)
+}
+
+// Pick the verification key from the header, then check signature and `exp`
+fn decode_verified<T: DeserializeOwned>(token: &str) -> Result<T, Error> {
+    let header = decode_header(token)?;
+    let key = key_store()?
+        .verification_key(header.kid.as_deref(), header.alg)
+        .ok_or_else(|| Error::from(ErrorKind::InvalidSignature))?;
+    let token_data = jsonwebtoken::decode::<T>(token, &key.key, &Validation::new(key.algorithm))?;
     Ok(token_data.claims)
 }
+
+// Claves cacheadas; sin configuración válida (p. ej. falta SECRET_KEY) se retorna error
+fn key_store() -> Result<&'static keys::KeyStore, Error> {
+    keys::current().map_err(|e| {
+        log::error!("JWT keys unavailable: {}", e);
+        Error::from(ErrorKind::InvalidKeyFormat)
+    })
+}
+
+// Timestamps (seconds since epoch)
+fn now_secs() -> usize {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize
+}
@@ -0,0 +1,489 @@
+//! JWT signing and verification keys.
+//!
+//! Keys are loaded once (at startup via `init_from_env`, or lazily on first use)
+//! and cached for the lifetime of the process.
+//!
+//! Modes:
+//! - `HS256` (default): signs and verifies with `SECRET_KEY`; nothing is published.
+//! - `RS256` / `EdDSA`: signs with the private key in `JWT_PRIVATE_KEY_FILE` and sets
+//!   the `kid` header. Other services verify tokens with `GET /.well-known/jwks.json`.
+//!
+//! Rotation: list the public keys of previous (or upcoming) signing keys in
+//! `JWT_VERIFICATION_KEY_FILES`; tokens signed with any of them keep validating
+//! and the keys are published in the JWKS. Once old tokens have expired, remove them.
+//!
+//! Env:
+//! - JWT_ALGORITHM: `HS256` | `RS256` | `EdDSA` (default `HS256`)
+//! - JWT_PRIVATE_KEY_FILE: PEM private key (PKCS#8, or PKCS#1 for RSA)
+//! - JWT_KEY_ID: `kid` of the signing key (default: RFC 7638 thumbprint)
+//! - JWT_VERIFICATION_KEY_FILES: comma separated PEM public keys, optionally `kid=path`
+//! - JWT_ACCEPT_LEGACY_HS256: `true` keeps accepting `SECRET_KEY` tokens without
+//!   `kid` while migrating from HS256 (default `false`)
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use derive_more::Display;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use log::info;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+// DER encoded OIDs
+const OID_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+static KEYS: OnceLock<KeyStore> = OnceLock::new();
+
+/// Errors raised while loading keys.
+#[derive(Debug, Display)]
+pub enum KeyError {
+    /// Missing or inconsistent settings.
+    #[display(fmt = "invalid key configuration: {}", _0)]
+    Config(String),
+    /// A key file could not be read or parsed.
+    #[display(fmt = "invalid key file {}: {}", _0, _1)]
+    File(String, String),
+}
+
+impl std::error::Error for KeyError {}
+
+/// Key used to sign new tokens
+pub struct SigningKey {
+    pub algorithm: Algorithm,
+    /// `kid` header; None for HS256
+    pub kid: Option<String>,
+    pub key: EncodingKey,
+}
+
+/// Key accepted when validating tokens
+pub struct VerificationKey {
+    pub algorithm: Algorithm,
+    pub kid: Option<String>,
+    pub key: DecodingKey,
+    /// Public JWK; None for shared secrets, which are never published
+    pub jwk: Option<Value>,
+}
+
+/// The active signing key plus every accepted verification key
+pub struct KeyStore {
+    signing: SigningKey,
+    verification: Vec<VerificationKey>,
+}
+
+/// Public parameters of an asymmetric key
+#[derive(Debug, Clone, PartialEq)]
+enum PublicKey {
+    /// Big-endian modulus and exponent
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    /// Raw 32-byte public key
+    Ed25519(Vec<u8>),
+}
+
+impl PublicKey {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            PublicKey::Rsa { .. } => Algorithm::RS256,
+            PublicKey::Ed25519(_) => Algorithm::EdDSA,
+        }
+    }
+
+    // Required members only, in lexicographic order (RFC 7638)
+    fn thumbprint_input(&self) -> String {
+        match self {
+            PublicKey::Rsa { n, e } => format!(
+                r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+                URL_SAFE_NO_PAD.encode(e),
+                URL_SAFE_NO_PAD.encode(n)
+            ),
+            PublicKey::Ed25519(x) => format!(
+                r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#,
+                URL_SAFE_NO_PAD.encode(x)
+            ),
+        }
+    }
+
+    /// RFC 7638 JWK thumbprint, used as the default `kid`.
+    fn thumbprint(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.thumbprint_input().as_bytes()))
+    }
+
+    fn decoding_key(&self) -> Result<DecodingKey, String> {
+        match self {
+            PublicKey::Rsa { n, e } => DecodingKey::from_rsa_components(
+                &URL_SAFE_NO_PAD.encode(n),
+                &URL_SAFE_NO_PAD.encode(e),
+            )
+            .map_err(|e| e.to_string()),
+            PublicKey::Ed25519(x) => DecodingKey::from_ed_components(&URL_SAFE_NO_PAD.encode(x))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn jwk(&self, kid: &str) -> Value {
+        match self {
+            PublicKey::Rsa { n, e } => json!({
+                "kty": "RSA",
+                "use": "sig",
+                "alg": "RS256",
+                "kid": kid,
+                "n": URL_SAFE_NO_PAD.encode(n),
+                "e": URL_SAFE_NO_PAD.encode(e),
+            }),
+            PublicKey::Ed25519(x) => json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "use": "sig",
+                "alg": "EdDSA",
+                "kid": kid,
+                "x": URL_SAFE_NO_PAD.encode(x),
+            }),
+        }
+    }
+
+    fn verification_key(&self, kid: String) -> Result<VerificationKey, String> {
+        Ok(VerificationKey {
+            algorithm: self.algorithm(),
+            key: self.decoding_key()?,
+            jwk: Some(self.jwk(&kid)),
+            kid: Some(kid),
+        })
+    }
+}
+
+impl KeyStore {
+    /// Shared-secret (HS256) store.
+    pub fn hmac(secret: &str) -> Self {
+        KeyStore {
+            signing: SigningKey {
+                algorithm: Algorithm::HS256,
+                kid: None,
+                key: EncodingKey::from_secret(secret.as_bytes()),
+            },
+            verification: vec![hmac_verification_key(secret)],
+        }
+    }
+
+    /// Asymmetric store from PEM contents.
+    ///
+    /// # Arguments
+    /// * `private_pem` - Signing key (RSA or Ed25519)
+    /// * `kid` - Signing `kid`; defaults to the key thumbprint
+    /// * `extra_public_pems` - `(kid, pem)` of additional verification keys
+    pub fn from_pems(
+        private_pem: &str,
+        kid: Option<String>,
+        extra_public_pems: &[(Option<String>, String)],
+    ) -> Result<Self, String> {
+        let public = public_key_from_private_pem(private_pem)?;
+        let kid = kid.unwrap_or_else(|| public.thumbprint());
+        let key = match public {
+            PublicKey::Rsa { .. } => EncodingKey::from_rsa_pem(private_pem.as_bytes()),
+            PublicKey::Ed25519(_) => EncodingKey::from_ed_pem(private_pem.as_bytes()),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let mut verification = vec![public.verification_key(kid.clone())?];
+        for (extra_kid, pem) in extra_public_pems {
+            let extra = public_key_from_public_pem(pem)?;
+            let extra_kid = extra_kid.clone().unwrap_or_else(|| extra.thumbprint());
+            if verification
+                .iter()
+                .any(|k| k.kid.as_deref() == Some(&extra_kid))
+            {
+                continue;
+            }
+            verification.push(extra.verification_key(extra_kid)?);
+        }
+
+        Ok(KeyStore {
+            signing: SigningKey {
+                algorithm: public.algorithm(),
+                kid: Some(kid),
+                key,
+            },
+            verification,
+        })
+    }
+
+    /// Also accept HS256 tokens without `kid` signed with `secret` (migration aid).
+    pub fn with_legacy_hmac(mut self, secret: &str) -> Self {
+        self.verification.push(hmac_verification_key(secret));
+        self
+    }
+
+    /// Builds the store described by the JWT_* variables (see module docs).
+    pub fn from_env() -> Result<Self, KeyError> {
+        let algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+        let secret = env::var("SECRET_KEY").ok().filter(|s| !s.is_empty());
+
+        if algorithm.eq_ignore_ascii_case("HS256") {
+            let secret = secret
+                .ok_or_else(|| KeyError::Config("SECRET_KEY is required for HS256".to_string()))?;
+            return Ok(Self::hmac(&secret));
+        }
+
+        let path = env::var("JWT_PRIVATE_KEY_FILE").map_err(|_| {
+            KeyError::Config(format!(
+                "JWT_PRIVATE_KEY_FILE is required for {}",
+                algorithm
+            ))
+        })?;
+        let private_pem =
+            fs::read_to_string(&path).map_err(|e| KeyError::File(path.clone(), e.to_string()))?;
+
+        let mut extra = Vec::new();
+        for entry in env::var("JWT_VERIFICATION_KEY_FILES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            let (kid, file) = match entry.split_once('=') {
+                Some((kid, file)) => (Some(kid.trim().to_string()), file.trim()),
+                None => (None, entry),
+            };
+            let pem = fs::read_to_string(file)
+                .map_err(|e| KeyError::File(file.to_string(), e.to_string()))?;
+            extra.push((kid, pem));
+        }
+
+        let kid = env::var("JWT_KEY_ID").ok().filter(|s| !s.is_empty());
+        let mut store =
+            Self::from_pems(&private_pem, kid, &extra).map_err(|e| KeyError::File(path, e))?;
+
+        let expected = match algorithm.to_ascii_uppercase().as_str() {
+            "RS256" => Algorithm::RS256,
+            "EDDSA" => Algorithm::EdDSA,
+            other => {
+                return Err(KeyError::Config(format!(
+                    "unsupported JWT_ALGORITHM {}",
+                    other
+                )))
+            }
+        };
+        if store.signing.algorithm != expected {
+            return Err(KeyError::Config(format!(
+                "JWT_ALGORITHM is {:?} but the private key is {:?}",
+                expected, store.signing.algorithm
+            )));
+        }
+
+        let accept_legacy = env::var("JWT_ACCEPT_LEGACY_HS256")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+        if let (true, Some(secret)) = (accept_legacy, secret) {
+            store = store.with_legacy_hmac(&secret);
+        }
+        Ok(store)
+    }
+
+    /// The key new tokens are signed with.
+    pub fn signing(&self) -> &SigningKey {
+        &self.signing
+    }
+
+    /// Verification key for a token header (`kid`, `alg`).
+    ///
+    /// Tokens with a `kid` must match a known key; tokens without one are only
+    /// accepted by a shared-secret key. The algorithm must match the key's.
+    pub fn verification_key(&self, kid: Option<&str>, alg: Algorithm) -> Option<&VerificationKey> {
+        self.verification
+            .iter()
+            .find(|k| k.kid.as_deref() == kid && k.algorithm == alg)
+    }
+
+    /// Public JWK Set (`{"keys": [...]}`); shared secrets are never included.
+    pub fn jwks(&self) -> Value {
+        let keys: Vec<Value> = self
+            .verification
+            .iter()
+            .filter_map(|k| k.jwk.clone())
+            .collect();
+        json!({ "keys": keys })
+    }
+}
+
+fn hmac_verification_key(secret: &str) -> VerificationKey {
+    VerificationKey {
+        algorithm: Algorithm::HS256,
+        kid: None,
+        key: DecodingKey::from_secret(secret.as_bytes()),
+        jwk: None,
+    }
+}
+
+/// Loads the keys from the environment and caches them; call once at startup so
+/// a misconfiguration fails fast instead of on the first login.
+pub fn init_from_env() -> Result<&'static KeyStore, KeyError> {
+    if let Some(store) = KEYS.get() {
+        return Ok(store);
+    }
+    let store = KeyStore::from_env()?;
+    info!(
+        "JWT keys loaded: alg={:?} kid={:?} verification_keys={}",
+        store.signing.algorithm,
+        store.signing.kid,
+        store.verification.len()
+    );
+    Ok(KEYS.get_or_init(|| store))
+}
+
+/// The cached key store (loaded from the environment on first use).
+pub fn current() -> Result<&'static KeyStore, KeyError> {
+    init_from_env()
+}
+
+// ---------- PEM / DER parsing ----------
+
+fn pem_to_der(pem: &str) -> Result<(String, Vec<u8>), String> {
+    let label = pem
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("-----BEGIN "))
+        .and_then(|l| l.strip_suffix("-----"))
+        .ok_or("not a PEM file")?
+        .to_string();
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.starts_with("-----"))
+        .collect();
+    let der = STANDARD.decode(body).map_err(|e| e.to_string())?;
+    Ok((label, der))
+}
+
+/// Reads one DER TLV; returns `(tag, content, rest)`.
+fn der_read(input: &[u8]) -> Result<(u8, &[u8], &[u8]), String> {
+    let err = || "malformed DER".to_string();
+    let (&tag, input) = input.split_first().ok_or_else(err)?;
+    let (&first, mut input) = input.split_first().ok_or_else(err)?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || input.len() < count {
+            return Err(err());
+        }
+        let len = input[..count]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        input = &input[count..];
+        len
+    };
+    if input.len() < len {
+        return Err(err());
+    }
+    Ok((tag, &input[..len], &input[len..]))
+}
+
+fn der_expect(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), String> {
+    let (t, content, rest) = der_read(input)?;
+    if t != tag {
+        return Err(format!("unexpected DER tag {:#x}", t));
+    }
+    Ok((content, rest))
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    bytes[start..].to_vec()
+}
+
+// RSAPublicKey ::= SEQUENCE { n, e } or RSAPrivateKey ::= SEQUENCE { version, n, e, ... }
+fn rsa_components(der: &[u8], private: bool) -> Result<PublicKey, String> {
+    let (mut seq, _) = der_expect(der, 0x30)?;
+    if private {
+        seq = der_expect(seq, 0x02)?.1;
+    }
+    let (n, rest) = der_expect(seq, 0x02)?;
+    let (e, _) = der_expect(rest, 0x02)?;
+    Ok(PublicKey::Rsa {
+        n: strip_leading_zeros(n),
+        e: strip_leading_zeros(e),
+    })
+}
+
+// AlgorithmIdentifier ::= SEQUENCE { OID, params }; returns the OID bytes
+fn algorithm_oid(algid: &[u8]) -> Result<&[u8], String> {
+    Ok(der_expect(algid, 0x06)?.0)
+}
+
+fn public_key_from_private_pem(pem: &str) -> Result<PublicKey, String> {
+    let (label, der) = pem_to_der(pem)?;
+    match label.as_str() {
+        "RSA PRIVATE KEY" => rsa_components(&der, true),
+        "PRIVATE KEY" => {
+            // PrivateKeyInfo ::= SEQUENCE { version, AlgorithmIdentifier, OCTET STRING }
+            let (seq, _) = der_expect(&der, 0x30)?;
+            let (_, rest) = der_expect(seq, 0x02)?;
+            let (algid, rest) = der_expect(rest, 0x30)?;
+            let (key, _) = der_expect(rest, 0x04)?;
+            match algorithm_oid(algid)? {
+                oid if oid == OID_RSA => rsa_components(key, true),
+                oid if oid == OID_ED25519 => {
+                    let (seed, _) = der_expect(key, 0x04)?;
+                    let pair =
+                        Ed25519KeyPair::from_seed_unchecked(seed).map_err(|e| e.to_string())?;
+                    Ok(PublicKey::Ed25519(pair.public_key().as_ref().to_vec()))
+                }
+                _ => Err("unsupported key type (use RSA or Ed25519)".to_string()),
+            }
+        }
+        other => Err(format!("unsupported PEM label {}", other)),
+    }
+}
+
+fn public_key_from_public_pem(pem: &str) -> Result<PublicKey, String> {
+    let (label, der) = pem_to_der(pem)?;
+    match label.as_str() {
+        "RSA PUBLIC KEY" => rsa_components(&der, false),
+        "PUBLIC KEY" => {
+            // SubjectPublicKeyInfo ::= SEQUENCE { AlgorithmIdentifier, BIT STRING }
+            let (seq, _) = der_expect(&der, 0x30)?;
+            let (algid, rest) = der_expect(seq, 0x30)?;
+            let (bits, _) = der_expect(rest, 0x03)?;
+            let key = bits.get(1..).ok_or("empty key")?; // skip unused-bits byte
+            match algorithm_oid(algid)? {
+                oid if oid == OID_RSA => rsa_components(key, false),
+                oid if oid == OID_ED25519 => Ok(PublicKey::Ed25519(key.to_vec())),
+                _ => Err("unsupported key type (use RSA or Ed25519)".to_string()),
+            }
+        }
+        other => Err(format!("unsupported PEM label {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc7638_thumbprint() {
+        // RFC 7638, section 3.1
+        let n = URL_SAFE_NO_PAD
+            .decode("0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw")
+            .unwrap();
+        let key = PublicKey::Rsa {
+            n,
+            e: vec![1, 0, 1],
+        };
+        assert_eq!(
+            key.thumbprint(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn test_der_read_long_length() {
+        let mut der = vec![0x04, 0x81, 0x80];
+        der.extend(std::iter::repeat_n(7u8, 0x80));
+        let (tag, content, rest) = der_read(&der).unwrap();
+        assert_eq!(tag, 0x04);
+        assert_eq!(content.len(), 0x80);
+        assert!(rest.is_empty());
+        assert!(der_read(&[0x04, 0x05, 1]).is_err());
+    }
+}
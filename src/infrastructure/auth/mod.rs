@@ -7,4 +7,10 @@
 //! Example import:
 //! `use crate::infrastructure::auth::jwt;`
  
-pub mod jwt; // Expose JWT-related helpers
\ No newline at end of file
+pub mod jwt; // Expose JWT-related helpers
+pub mod keys; // Cached signing/verification keys and the public JWKS
+pub mod oidc; // OAuth2 / OpenID Connect external logins
+pub mod opaque_token; // Hashed one-time secrets (reset/verification links)
+pub mod password; // Argon2id password hashing (verifies legacy bcrypt)
+pub mod totp; // RFC 6238 codes for two-factor authentication
+pub mod wallet; // EIP-191 signatures proving wallet ownership
\ No newline at end of file
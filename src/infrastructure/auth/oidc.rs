@@ -0,0 +1,528 @@
+//! OAuth2 / OpenID Connect client for external logins.
+//!
+//! Supports the authorization-code flow with PKCE (S256) against:
+//! - any OpenID Connect provider (Google, Keycloak, Auth0, ...): endpoints come
+//!   from `{issuer}/.well-known/openid-configuration` and the ID token is
+//!   validated against the provider's JWKS (signature, `iss`, `aud`, `exp`, `nonce`);
+//! - GitHub, which speaks plain OAuth2: the profile and the verified primary
+//!   e-mail are read from its REST API with the access token.
+//!
+//! Discovery metadata and the JWKS are cached per provider; the JWKS is fetched
+//! again once when an ID token carries an unknown `kid` (key rotation).
+//!
+//! Env (one block per name listed in `OIDC_PROVIDERS=google,github`):
+//! - OIDC_<NAME>_CLIENT_ID, OIDC_<NAME>_CLIENT_SECRET (secret optional for public clients)
+//! - OIDC_<NAME>_ISSUER (required for `oidc` providers)
+//! - OIDC_<NAME>_KIND: `oidc` | `github` (default `github` for the name `github`, else `oidc`)
+//! - OIDC_<NAME>_REDIRECT_URI (default `{PUBLIC_API_URL}/api/auth/oidc/<name>/callback`)
+//! - OIDC_<NAME>_SCOPES (default `openid email profile`, GitHub `read:user user:email`)
+//! - OIDC_<NAME>_AUTHORIZATION_ENDPOINT, OIDC_<NAME>_TOKEN_ENDPOINT (override discovery)
+//! - OIDC_<NAME>_API_URL (GitHub only, default `https://api.github.com`)
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use derive_more::Display;
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use log::{debug, info, warn};
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Signature algorithms accepted for ID tokens (never HMAC: the client secret is not a signing key)
+const ALLOWED_ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Allowed clock skew when checking `exp`/`iat`, in seconds
+const CLOCK_SKEW_SECS: u64 = 60;
+
+/// How the provider exposes the user's identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    /// OpenID Connect: discovery + signed ID token
+    Oidc,
+    /// GitHub OAuth2: REST API profile
+    Github,
+}
+
+/// Static configuration of one provider
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Name used in the URL (`/api/auth/oidc/{name}/...`) and stored on linked identities
+    pub name: String,
+    pub kind: ProviderKind,
+    /// Expected `iss` claim and discovery base URL (OIDC only)
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    /// Space separated scopes
+    pub scopes: String,
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    /// REST API base URL (GitHub only)
+    pub api_url: Option<String>,
+}
+
+impl OidcProviderConfig {
+    /// Reads every provider listed in `OIDC_PROVIDERS`; incomplete entries are skipped with a warning.
+    pub fn all_from_env() -> Vec<Self> {
+        let names = env::var("OIDC_PROVIDERS").unwrap_or_default();
+        let public_api_url =
+            env::var("PUBLIC_API_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+        names
+            .split(',')
+            .map(|n| n.trim().to_lowercase())
+            .filter(|n| !n.is_empty())
+            .filter_map(|name| {
+                let config = Self::from_env(&name, &public_api_url);
+                if config.is_none() {
+                    warn!("OIDC provider '{}' is incomplete and was skipped", name);
+                }
+                config
+            })
+            .collect()
+    }
+
+    fn from_env(name: &str, public_api_url: &str) -> Option<Self> {
+        let var = |key: &str| {
+            env::var(format!("OIDC_{}_{}", name.to_uppercase(), key))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        };
+        let kind = match var("KIND").as_deref() {
+            Some("github") => ProviderKind::Github,
+            Some(_) => ProviderKind::Oidc,
+            None if name == "github" => ProviderKind::Github,
+            None => ProviderKind::Oidc,
+        };
+        let client_id = var("CLIENT_ID")?;
+        let redirect_uri = var("REDIRECT_URI").unwrap_or_else(|| {
+            format!(
+                "{}/api/auth/oidc/{}/callback",
+                public_api_url.trim_end_matches('/'),
+                name
+            )
+        });
+
+        let config = match kind {
+            ProviderKind::Oidc => OidcProviderConfig {
+                name: name.to_string(),
+                kind,
+                issuer: var("ISSUER")?.trim_end_matches('/').to_string(),
+                client_id,
+                client_secret: var("CLIENT_SECRET"),
+                redirect_uri,
+                scopes: var("SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+                authorization_endpoint: var("AUTHORIZATION_ENDPOINT"),
+                token_endpoint: var("TOKEN_ENDPOINT"),
+                api_url: None,
+            },
+            ProviderKind::Github => OidcProviderConfig {
+                name: name.to_string(),
+                kind,
+                issuer: var("ISSUER").unwrap_or_else(|| "https://github.com".to_string()),
+                client_id,
+                client_secret: var("CLIENT_SECRET"),
+                redirect_uri,
+                scopes: var("SCOPES").unwrap_or_else(|| "read:user user:email".to_string()),
+                authorization_endpoint: Some(
+                    var("AUTHORIZATION_ENDPOINT")
+                        .unwrap_or_else(|| "https://github.com/login/oauth/authorize".to_string()),
+                ),
+                token_endpoint: Some(
+                    var("TOKEN_ENDPOINT").unwrap_or_else(|| {
+                        "https://github.com/login/oauth/access_token".to_string()
+                    }),
+                ),
+                api_url: Some(
+                    var("API_URL").unwrap_or_else(|| "https://api.github.com".to_string()),
+                ),
+            },
+        };
+        Some(config)
+    }
+}
+
+/// Identity asserted by the provider after a successful code exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalProfile {
+    /// Stable user id at the provider (`sub`)
+    pub subject: String,
+    pub email: Option<String>,
+    /// Whether the provider verified `email`
+    pub email_verified: bool,
+    /// Suggested username (`preferred_username`, GitHub `login`, ...)
+    pub preferred_username: Option<String>,
+}
+
+/// Errors raised while talking to a provider.
+#[derive(Debug, Display)]
+pub enum OidcError {
+    /// Discovery document or JWKS could not be fetched or parsed.
+    #[display(fmt = "discovery failed: {}", _0)]
+    Discovery(String),
+    /// The token endpoint rejected the code or answered garbage.
+    #[display(fmt = "token exchange failed: {}", _0)]
+    TokenExchange(String),
+    /// The ID token failed validation.
+    #[display(fmt = "invalid id token: {}", _0)]
+    InvalidIdToken(String),
+    /// The user profile could not be read.
+    #[display(fmt = "profile request failed: {}", _0)]
+    Profile(String),
+}
+
+impl std::error::Error for OidcError {}
+
+/// Generates a PKCE pair `(code_verifier, code_challenge)` using the S256 method.
+pub fn new_pkce_pair() -> (String, String) {
+    let verifier = random_url_safe(32);
+    let challenge = pkce_challenge(&verifier);
+    (verifier, challenge)
+}
+
+/// S256 code challenge of a verifier: BASE64URL(SHA256(verifier)).
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Random URL-safe string from `bytes` random bytes (state, nonce, verifier).
+pub fn random_url_safe(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    #[serde(default)]
+    nonce: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    // Some providers send "true"/"false" as strings
+    #[serde(default)]
+    email_verified: Option<serde_json::Value>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubUser {
+    id: u64,
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+/// Client for one configured provider
+pub struct OidcClient {
+    config: OidcProviderConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcProviderConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent("chasqui-server")
+            .build()
+            .unwrap_or_default();
+        Self {
+            config,
+            http,
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &OidcProviderConfig {
+        &self.config
+    }
+
+    /// Builds the URL the browser is redirected to.
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String, OidcError> {
+        let endpoint = match &self.config.authorization_endpoint {
+            Some(e) => e.clone(),
+            None => self.metadata().await?.authorization_endpoint,
+        };
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", self.config.client_id.as_str()),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("scope", self.config.scopes.as_str()),
+            ("state", state),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256"),
+        ];
+        if self.config.kind == ProviderKind::Oidc {
+            params.push(("nonce", nonce));
+        }
+        reqwest::Url::parse_with_params(&endpoint, &params)
+            .map(|u| u.to_string())
+            .map_err(|e| OidcError::Discovery(format!("invalid authorization endpoint: {}", e)))
+    }
+
+    /// Exchanges the authorization code and returns the verified profile.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExternalProfile, OidcError> {
+        let endpoint = match &self.config.token_endpoint {
+            Some(e) => e.clone(),
+            None => self.metadata().await?.token_endpoint,
+        };
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response: TokenResponse = self
+            .http
+            .post(&endpoint)
+            .header("Accept", "application/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| OidcError::TokenExchange(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::TokenExchange(e.to_string()))?;
+        if let Some(error) = response.error {
+            return Err(OidcError::TokenExchange(format!(
+                "{} {}",
+                error,
+                response.error_description.unwrap_or_default()
+            )));
+        }
+
+        match self.config.kind {
+            ProviderKind::Oidc => {
+                let id_token = response.id_token.ok_or_else(|| {
+                    OidcError::TokenExchange("response has no id_token".to_string())
+                })?;
+                self.validate_id_token(&id_token, nonce).await
+            }
+            ProviderKind::Github => {
+                let access_token = response.access_token.ok_or_else(|| {
+                    OidcError::TokenExchange("response has no access_token".to_string())
+                })?;
+                self.github_profile(&access_token).await
+            }
+        }
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<ExternalProfile, OidcError> {
+        let header =
+            decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "algorithm {:?} not allowed",
+                header.alg
+            )));
+        }
+
+        let jwk = self.signing_key(header.kid.as_deref()).await?;
+        if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+            return Err(OidcError::InvalidIdToken(
+                "symmetric keys are not accepted".to_string(),
+            ));
+        }
+        let key =
+            DecodingKey::from_jwk(&jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.leeway = CLOCK_SKEW_SECS;
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".to_string()));
+        }
+
+        let email_verified = match &claims.email_verified {
+            Some(serde_json::Value::Bool(b)) => *b,
+            Some(serde_json::Value::String(s)) => s == "true",
+            _ => false,
+        };
+        Ok(ExternalProfile {
+            subject: claims.sub,
+            email: claims.email.map(|e| e.trim().to_lowercase()),
+            email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+
+    // Key matching `kid`; refetches the JWKS once if the key is unknown.
+    async fn signing_key(&self, kid: Option<&str>) -> Result<Jwk, OidcError> {
+        let select = |set: &JwkSet| -> Option<Jwk> {
+            match kid {
+                Some(kid) => set.find(kid).cloned(),
+                None if set.keys.len() == 1 => set.keys.first().cloned(),
+                None => None,
+            }
+        };
+
+        let cached = self.jwks.read().unwrap().as_ref().and_then(select);
+        if let Some(jwk) = cached {
+            return Ok(jwk);
+        }
+
+        let uri = self.metadata().await?.jwks_uri;
+        debug!("Fetching JWKS for provider {}", self.config.name);
+        let set: JwkSet = self
+            .http
+            .get(&uri)
+            .send()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))?;
+        let found = select(&set);
+        *self.jwks.write().unwrap() = Some(set);
+        found.ok_or_else(|| OidcError::InvalidIdToken(format!("unknown key id {:?}", kid)))
+    }
+
+    async fn metadata(&self) -> Result<ProviderMetadata, OidcError> {
+        if let Some(metadata) = self.metadata.read().unwrap().clone() {
+            return Ok(metadata);
+        }
+
+        let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(OidcError::Discovery(format!(
+                "issuer mismatch: {}",
+                metadata.issuer
+            )));
+        }
+        info!("OIDC discovery completed for provider {}", self.config.name);
+        *self.metadata.write().unwrap() = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn github_profile(&self, access_token: &str) -> Result<ExternalProfile, OidcError> {
+        let api = self
+            .config
+            .api_url
+            .as_deref()
+            .unwrap_or("https://api.github.com")
+            .trim_end_matches('/');
+        let user: GithubUser = self
+            .github_get(&format!("{}/user", api), access_token)
+            .await?;
+        let emails: Vec<GithubEmail> = self
+            .github_get(&format!("{}/user/emails", api), access_token)
+            .await?;
+        let primary = emails.into_iter().find(|e| e.primary);
+
+        Ok(ExternalProfile {
+            subject: user.id.to_string(),
+            email: primary.as_ref().map(|e| e.email.trim().to_lowercase()),
+            email_verified: primary.is_some_and(|e| e.verified),
+            preferred_username: Some(user.login),
+        })
+    }
+
+    async fn github_get<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        access_token: &str,
+    ) -> Result<T, OidcError> {
+        self.http
+            .get(url)
+            .bearer_auth(access_token)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| OidcError::Profile(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Profile(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge_rfc7636_vector() {
+        // RFC 7636, Appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_new_pkce_pair_matches() {
+        let (verifier, challenge) = new_pkce_pair();
+        assert!(verifier.len() >= 43);
+        assert_eq!(pkce_challenge(&verifier), challenge);
+    }
+}
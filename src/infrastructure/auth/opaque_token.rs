@@ -0,0 +1,48 @@
+//! Opaque one-time secrets (password reset, e-mail verification, ...).
+//!
+//! The plaintext secret is only ever handed to the user; the database stores
+//! its SHA-256 digest, so a leaked table cannot be replayed.
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Number of random bytes in a generated secret (256 bits).
+const SECRET_BYTES: usize = 32;
+
+/// Generates a URL-safe random secret and returns `(plaintext, sha256_hex)`.
+pub fn new_opaque_token() -> (String, String) {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let plain = to_hex(&bytes);
+    let hash = hash_opaque_token(&plain);
+    (plain, hash)
+}
+
+/// SHA-256 digest (hex) of a plaintext secret, as stored in the database.
+pub fn hash_opaque_token(plain: &str) -> String {
+    to_hex(&Sha256::digest(plain.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_opaque_token_hash_matches() {
+        let (plain, hash) = new_opaque_token();
+        assert_eq!(plain.len(), SECRET_BYTES * 2);
+        assert_eq!(hash, hash_opaque_token(&plain));
+        assert_ne!(plain, hash);
+    }
+
+    #[test]
+    fn test_new_opaque_token_is_random() {
+        let (a, _) = new_opaque_token();
+        let (b, _) = new_opaque_token();
+        assert_ne!(a, b);
+    }
+}
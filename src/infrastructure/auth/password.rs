@@ -0,0 +1,178 @@
+//! Password hashing and verification.
+//!
+//! New hashes use Argon2id and are stored as PHC strings
+//! (`$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`). Verification detects the
+//! algorithm from the stored string, so bcrypt hashes (`$2a$`, `$2b$`, `$2y$`)
+//! created before the switch keep working; `needs_rehash` tells the login flow
+//! to replace them (or Argon2 hashes with outdated parameters) after a
+//! successful check.
+//!
+//! Env (Argon2id parameters, defaults follow the OWASP recommendation):
+//! - ARGON2_MEMORY_KIB (default 19456)
+//! - ARGON2_ITERATIONS (default 2)
+//! - ARGON2_PARALLELISM (default 1)
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use derive_more::Display;
+use rand::rngs::OsRng;
+use std::env;
+use std::sync::OnceLock;
+
+/// Errors raised while hashing a password.
+#[derive(Debug, Display)]
+pub enum PasswordHashError {
+    /// Invalid Argon2 parameters or hashing failure.
+    #[display(fmt = "argon2 error: {}", _0)]
+    Argon2(String),
+}
+
+impl std::error::Error for PasswordHashError {}
+
+/// Algorithm of a stored hash, detected from its prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashScheme {
+    Argon2id,
+    /// Argon2i / Argon2d
+    OtherArgon2,
+    Bcrypt,
+    Unknown,
+}
+
+impl HashScheme {
+    /// Detects the scheme of a PHC / modular-crypt string.
+    pub fn detect(hash: &str) -> Self {
+        if hash.starts_with("$argon2id$") {
+            HashScheme::Argon2id
+        } else if hash.starts_with("$argon2") {
+            HashScheme::OtherArgon2
+        } else if ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|p| hash.starts_with(p))
+        {
+            HashScheme::Bcrypt
+        } else {
+            HashScheme::Unknown
+        }
+    }
+}
+
+// Argon2id parameters configured in the environment
+fn argon2_params() -> Result<Params, PasswordHashError> {
+    let read = |name: &str, default: u32| {
+        env::var(name)
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(default)
+    };
+    Params::new(
+        read("ARGON2_MEMORY_KIB", Params::DEFAULT_M_COST),
+        read("ARGON2_ITERATIONS", Params::DEFAULT_T_COST),
+        read("ARGON2_PARALLELISM", Params::DEFAULT_P_COST),
+        None,
+    )
+    .map_err(|e| PasswordHashError::Argon2(e.to_string()))
+}
+
+/// Hash a plaintext password with Argon2id and a random salt.
+///
+/// Returns the PHC string on success.
+pub fn hash_password(password: &str) -> Result<String, PasswordHashError> {
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params()?);
+    let salt = SaltString::generate(&mut OsRng);
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| PasswordHashError::Argon2(e.to_string()))
+}
+
+/// Verify a plaintext password against a stored Argon2 or bcrypt hash.
+///
+/// Returns `true` if the password matches, otherwise `false` (also for
+/// malformed or unknown hashes).
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    match HashScheme::detect(hash) {
+        HashScheme::Argon2id | HashScheme::OtherArgon2 => PasswordHash::new(hash)
+            .map(|parsed| {
+                // Parameters and variant are read from the PHC string
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false),
+        HashScheme::Bcrypt => bcrypt::verify(password, hash).unwrap_or(false),
+        HashScheme::Unknown => false,
+    }
+}
+
+/// Whether a stored hash should be replaced after a successful login: any
+/// non-Argon2id hash, or Argon2id with parameters different from the configured ones.
+pub fn needs_rehash(hash: &str) -> bool {
+    if HashScheme::detect(hash) != HashScheme::Argon2id {
+        return true;
+    }
+    let (parsed, configured) = match (PasswordHash::new(hash), argon2_params()) {
+        (Ok(parsed), Ok(configured)) => (parsed, configured),
+        _ => return false,
+    };
+    match Params::try_from(&parsed) {
+        Ok(current) => {
+            current.m_cost() != configured.m_cost()
+                || current.t_cost() != configured.t_cost()
+                || current.p_cost() != configured.p_cost()
+        }
+        Err(_) => true,
+    }
+}
+
+/// Spend the time of a real `verify_password` when there is no hash to check
+/// (unknown account), so response timing does not reveal whether it exists.
+pub fn verify_password_dummy(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let dummy =
+        DUMMY_HASH.get_or_init(|| hash_password("chasqui-dummy-password").unwrap_or_default());
+    let _ = verify_password(password, dummy);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_scheme() {
+        assert_eq!(
+            HashScheme::detect("$argon2id$v=19$m=8,t=1,p=1$c2FsdA$aGFzaA"),
+            HashScheme::Argon2id
+        );
+        assert_eq!(
+            HashScheme::detect("$argon2i$v=19$m=8,t=1,p=1$c2FsdA$aGFzaA"),
+            HashScheme::OtherArgon2
+        );
+        assert_eq!(HashScheme::detect("$2b$04$abc"), HashScheme::Bcrypt);
+        assert_eq!(HashScheme::detect("plain"), HashScheme::Unknown);
+    }
+
+    #[test]
+    fn test_bcrypt_hash_verifies_and_needs_rehash() {
+        let legacy = bcrypt::hash("Old$ecret1", 4).unwrap();
+        assert!(verify_password("Old$ecret1", &legacy));
+        assert!(!verify_password("wrong", &legacy));
+        assert!(needs_rehash(&legacy));
+        assert!(!verify_password("Old$ecret1", "not-a-hash"));
+    }
+
+    #[test]
+    fn test_argon2id_with_other_params_needs_rehash() {
+        let salt = SaltString::generate(&mut OsRng);
+        let weak = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(1024, 1, 1, None).unwrap(),
+        )
+        .hash_password(b"pw", &salt)
+        .unwrap()
+        .to_string();
+        assert!(verify_password("pw", &weak));
+        assert!(needs_rehash(&weak));
+    }
+}
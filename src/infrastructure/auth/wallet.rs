@@ -0,0 +1,170 @@
+//! Ethereum wallet ownership proofs (EIP-191 `personal_sign`).
+//!
+//! The client signs a server-issued challenge message with the wallet, and the
+//! server recovers the signing address from the 65-byte `r || s || v` signature:
+//! `keccak256("\x19Ethereum Signed Message:\n" + len(message) + message)`.
+//! The recovered address must equal the claimed one; addresses are compared in
+//! lower case, so EIP-55 checksummed input is accepted.
+
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Errors while checking a wallet signature
+#[derive(Debug, PartialEq, Eq)]
+pub enum WalletError {
+    /// Not a `0x`-prefixed 20-byte hex address
+    InvalidAddress,
+    /// Not a `0x`-prefixed 65-byte hex signature, or not recoverable
+    InvalidSignature,
+    /// The signature is valid but was made by another address
+    AddressMismatch,
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletError::InvalidAddress => write!(f, "invalid wallet address"),
+            WalletError::InvalidSignature => write!(f, "invalid wallet signature"),
+            WalletError::AddressMismatch => write!(f, "signature made by another wallet"),
+        }
+    }
+}
+
+/// True for `0x` followed by 40 hex digits.
+pub fn is_valid_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Lower-case form of a valid address.
+pub fn normalize_address(address: &str) -> Result<String, WalletError> {
+    let address = address.trim();
+    if is_valid_address(address) {
+        Ok(address.to_lowercase())
+    } else {
+        Err(WalletError::InvalidAddress)
+    }
+}
+
+/// Checks that `signature` is `address`'s `personal_sign` of `message`.
+pub fn verify_personal_sign(
+    address: &str,
+    message: &str,
+    signature: &str,
+) -> Result<(), WalletError> {
+    let address = normalize_address(address)?;
+    if recover_address(message, signature)? == address {
+        Ok(())
+    } else {
+        Err(WalletError::AddressMismatch)
+    }
+}
+
+/// Recovers the (lower-case) address that produced a `personal_sign` signature.
+pub fn recover_address(message: &str, signature: &str) -> Result<String, WalletError> {
+    let bytes = signature
+        .trim()
+        .strip_prefix("0x")
+        .and_then(decode_hex)
+        .filter(|b| b.len() == 65)
+        .ok_or(WalletError::InvalidSignature)?;
+
+    let signature =
+        Signature::from_slice(&bytes[..64]).map_err(|_| WalletError::InvalidSignature)?;
+    // Wallets use 27/28 (legacy) or 0/1 for `v`
+    let v = match bytes[64] {
+        27 | 28 => bytes[64] - 27,
+        v => v,
+    };
+    let recovery_id = RecoveryId::from_byte(v).ok_or(WalletError::InvalidSignature)?;
+    let key = VerifyingKey::recover_from_prehash(
+        &personal_message_hash(message),
+        &signature,
+        recovery_id,
+    )
+    .map_err(|_| WalletError::InvalidSignature)?;
+    Ok(address_of(&key))
+}
+
+/// EIP-191 digest of a `personal_sign` message.
+pub fn personal_message_hash(message: &str) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+    hasher.update(message.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Lower-case address of a public key: last 20 bytes of keccak256(x || y).
+pub fn address_of(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let digest = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", to_hex(&digest[12..]))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    fn key_one() -> SigningKey {
+        let mut secret = [0u8; 32];
+        secret[31] = 1;
+        SigningKey::from_slice(&secret).unwrap()
+    }
+
+    fn sign(key: &SigningKey, message: &str) -> String {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&personal_message_hash(message))
+            .unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        format!("0x{}", to_hex(&bytes))
+    }
+
+    #[test]
+    fn test_address_of_known_key() {
+        // Private key 0x..01 is the well-known address 0x7E5F...5Bdf
+        assert_eq!(
+            address_of(key_one().verifying_key()),
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+    }
+
+    #[test]
+    fn test_personal_sign_round_trip() {
+        let signature = sign(&key_one(), "link wallet");
+        let address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
+        assert_eq!(
+            verify_personal_sign(address, "link wallet", &signature),
+            Ok(())
+        );
+        assert_eq!(
+            verify_personal_sign(address, "other message", &signature),
+            Err(WalletError::AddressMismatch)
+        );
+        assert_eq!(
+            verify_personal_sign(address, "link wallet", "0x1234"),
+            Err(WalletError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_personal_sign("0x123", "link wallet", &signature),
+            Err(WalletError::InvalidAddress)
+        );
+    }
+}
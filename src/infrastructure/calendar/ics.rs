@@ -0,0 +1,439 @@
+//! iCalendar (RFC 5545) writing and reading of tasks.
+//!
+//! Writing: every task with a due date becomes a `VEVENT` starting at the due
+//! date, or a `VTODO` due then (status, priority, progress and completion
+//! included). Reminders become `VALARM`s. Occurrences of a recurring task are
+//! separate tasks, so no `RRULE` is written.
+//!
+//! Reading: `VTODO` and `VEVENT` components are turned into `AddTaskRequest`s.
+//! - `SUMMARY` -> name, `DESCRIPTION` -> description
+//! - `DUE` (`VTODO`) or `DTSTART` (`VEVENT`) -> due date
+//! - `PRIORITY` 1-2 urgent, 3-4 high, 5 medium, 6-9 low
+//! - `STATUS` `COMPLETED` -> done, `IN-PROCESS` -> in progress
+//! - `RRULE` -> recurrence (FREQ, INTERVAL, COUNT and UNTIL only)
+//! - `VALARM` `TRIGGER`s relative to and before the due date -> reminders
+//!
+//! Times with `Z` are UTC; local and `TZID` times are read as UTC too, and a
+//! plain date means the end of that day (UTC).
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::models::entities::recurrence::RecurrenceRule;
+use crate::models::entities::task::{AddTaskRequest, Task, TaskPriority, TaskStatus};
+
+/// Product identifier written into every calendar
+const PRODID: &str = "-//Chasqui//Tasks//EN";
+/// Longest content line, in octets, before folding
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Calendar component a task is written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IcsComponent {
+    /// `VEVENT` at the due date (shown by every calendar app)
+    #[default]
+    Event,
+    /// `VTODO` due at the due date (to-do aware clients)
+    Todo,
+}
+
+/// One `VTODO` / `VEVENT` of an imported calendar
+pub struct IcsEntry {
+    /// `SUMMARY`, if any, to report skipped entries
+    pub summary: Option<String>,
+    pub request: Result<AddTaskRequest, String>,
+}
+
+/// Writes `tasks` (those with a due date) as an iCalendar document named `name`.
+pub fn write_calendar(name: &str, tasks: &[Task], component: IcsComponent) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    for task in tasks {
+        if let Some(due) = task.due_date {
+            write_task(&mut out, task, due, component);
+        }
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn write_task(out: &mut String, task: &Task, due: DateTime<Utc>, component: IcsComponent) {
+    let kind = match component {
+        IcsComponent::Event => "VEVENT",
+        IcsComponent::Todo => "VTODO",
+    };
+    push_line(out, &format!("BEGIN:{}", kind));
+    push_line(out, &format!("UID:{}@chasqui", task.uuid));
+    push_line(out, &format!("DTSTAMP:{}", format_time(task.updated_at)));
+    push_line(out, &format!("CREATED:{}", format_time(task.created_at)));
+    push_line(
+        out,
+        &format!("LAST-MODIFIED:{}", format_time(task.updated_at)),
+    );
+    push_line(out, &format!("SUMMARY:{}", escape_text(&task.task_name)));
+    if let Some(description) = &task.description {
+        push_line(out, &format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    match component {
+        IcsComponent::Event => {
+            push_line(out, &format!("DTSTART:{}", format_time(due)));
+            push_line(out, &format!("DTEND:{}", format_time(due)));
+        }
+        IcsComponent::Todo => {
+            push_line(out, &format!("DUE:{}", format_time(due)));
+            let status = match task.status {
+                TaskStatus::Todo => "NEEDS-ACTION",
+                TaskStatus::InProgress => "IN-PROCESS",
+                TaskStatus::Done => "COMPLETED",
+            };
+            push_line(out, &format!("STATUS:{}", status));
+            if let Some(completed_at) = task.completed_at {
+                push_line(out, &format!("COMPLETED:{}", format_time(completed_at)));
+            }
+            if let Some(progress) = task.progress {
+                push_line(out, &format!("PERCENT-COMPLETE:{}", progress.percent));
+            }
+        }
+    }
+    push_line(out, &format!("PRIORITY:{}", ics_priority(task.priority)));
+    for minutes in &task.reminders {
+        push_line(out, "BEGIN:VALARM");
+        push_line(out, "ACTION:DISPLAY");
+        push_line(
+            out,
+            &format!("DESCRIPTION:{}", escape_text(&task.task_name)),
+        );
+        // A VTODO alarm is relative to DUE only when RELATED=END
+        let related = match component {
+            IcsComponent::Event => "",
+            IcsComponent::Todo => ";RELATED=END",
+        };
+        push_line(out, &format!("TRIGGER{}:-PT{}M", related, minutes));
+        push_line(out, "END:VALARM");
+    }
+    push_line(out, &format!("END:{}", kind));
+}
+
+/// Reads the `VTODO` and `VEVENT` components of an iCalendar document, in order.
+pub fn read_calendar(text: &str) -> Result<Vec<IcsEntry>, String> {
+    let lines = unfold(text);
+    if !lines
+        .iter()
+        .any(|l| l.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("not an iCalendar file (BEGIN:VCALENDAR missing)".to_string());
+    }
+
+    let mut entries = Vec::new();
+    let mut current: Option<(String, Vec<ContentLine>)> = None;
+    // Components nested in a task (VALARM) keep their lines apart
+    let mut nested: Vec<String> = Vec::new();
+    let mut alarm: Vec<ContentLine> = Vec::new();
+    for raw in &lines {
+        let Some(line) = ContentLine::parse(raw) else {
+            continue;
+        };
+        match (line.name.as_str(), &mut current) {
+            ("BEGIN", None) if is_task_component(&line.value) => {
+                current = Some((line.value.to_ascii_uppercase(), Vec::new()));
+            }
+            ("BEGIN", Some(_)) => {
+                nested.push(line.value.to_ascii_uppercase());
+                if line.value.eq_ignore_ascii_case("VALARM") {
+                    alarm.clear();
+                }
+            }
+            ("END", Some((kind, props))) if nested.is_empty() => {
+                if line.value.eq_ignore_ascii_case(kind) {
+                    entries.push(to_entry(kind, props));
+                    current = None;
+                }
+            }
+            ("END", Some((_, props))) => {
+                if nested.pop().as_deref() == Some("VALARM") {
+                    // Keep only the alarm trigger, tagged for `to_entry`
+                    if let Some(trigger) = alarm.iter().find(|l| l.name == "TRIGGER") {
+                        props.push(ContentLine {
+                            name: "X-ALARM-TRIGGER".to_string(),
+                            value: trigger.value.clone(),
+                        });
+                    }
+                }
+            }
+            (_, Some((_, props))) => {
+                if nested.is_empty() {
+                    props.push(line);
+                } else if nested.last().map(String::as_str) == Some("VALARM") {
+                    alarm.push(line);
+                }
+            }
+            (_, None) => {}
+        }
+    }
+    Ok(entries)
+}
+
+fn is_task_component(name: &str) -> bool {
+    name.eq_ignore_ascii_case("VTODO") || name.eq_ignore_ascii_case("VEVENT")
+}
+
+fn to_entry(kind: &str, props: &[ContentLine]) -> IcsEntry {
+    let prop = |name: &str| props.iter().find(|l| l.name == name);
+    let summary = prop("SUMMARY").map(|l| unescape_text(&l.value));
+    IcsEntry {
+        summary: summary.clone(),
+        request: to_request(kind, summary, props),
+    }
+}
+
+fn to_request(
+    kind: &str,
+    summary: Option<String>,
+    props: &[ContentLine],
+) -> Result<AddTaskRequest, String> {
+    let prop = |name: &str| props.iter().find(|l| l.name == name);
+    let task_name = summary
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or("SUMMARY missing")?;
+    let due_prop = if kind == "VTODO" { "DUE" } else { "DTSTART" };
+    let due_date = match prop(due_prop) {
+        Some(line) => Some(parse_time(&line.value)?),
+        None => None,
+    };
+    let status = match prop("STATUS").map(|l| l.value.to_ascii_uppercase()) {
+        Some(s) if s == "COMPLETED" => Some(TaskStatus::Done),
+        Some(s) if s == "IN-PROCESS" => Some(TaskStatus::InProgress),
+        Some(s) if s == "CANCELLED" => return Err("cancelled".to_string()),
+        _ => None,
+    };
+    let priority = prop("PRIORITY")
+        .and_then(|l| l.value.trim().parse::<u8>().ok())
+        .and_then(task_priority);
+    let recurrence = match prop("RRULE") {
+        Some(line) => Some(RecurrenceRule::from_rrule(&line.value)?),
+        None => None,
+    };
+    let reminders: Vec<u32> = props
+        .iter()
+        .filter(|l| l.name == "X-ALARM-TRIGGER")
+        .filter_map(|l| reminder_minutes(&l.value))
+        .collect();
+
+    Ok(AddTaskRequest {
+        task_name,
+        description: prop("DESCRIPTION")
+            .map(|l| unescape_text(&l.value))
+            .filter(|d| !d.trim().is_empty()),
+        status,
+        priority,
+        due_date,
+        recurrence,
+        reminders: (!reminders.is_empty()).then_some(reminders),
+        ..AddTaskRequest::default()
+    })
+}
+
+/// A property line: `NAME;PARAM=VALUE:value` (parameters are not needed)
+#[derive(Debug, Clone)]
+struct ContentLine {
+    name: String,
+    value: String,
+}
+
+impl ContentLine {
+    fn parse(line: &str) -> Option<ContentLine> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut quoted = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                quoted = !quoted;
+                None
+            }
+            ':' if !quoted => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let name = head.split(';').next().unwrap_or(head);
+        Some(ContentLine {
+            name: name.trim().to_ascii_uppercase(),
+            value: value.to_string(),
+        })
+    }
+}
+
+// Joins folded lines (a line break followed by a space or tab continues the line)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// Appends a content line, folded at 75 octets without splitting characters
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Reads a DATE-TIME (`20261102T090000Z`, local or UTC) or DATE (`20261102`) value.
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    let naive = value.trim_end_matches(['Z', 'z']);
+    if naive.len() == 8 {
+        let date = NaiveDate::parse_from_str(naive, "%Y%m%d")
+            .map_err(|_| format!("malformed date {}", value))?;
+        let end_of_day = date.and_hms_opt(23, 59, 59).ok_or("malformed date")?;
+        return Ok(Utc.from_utc_datetime(&end_of_day));
+    }
+    NaiveDateTime::parse_from_str(naive, "%Y%m%dT%H%M%S")
+        .map(|t| Utc.from_utc_datetime(&t))
+        .map_err(|_| format!("malformed date-time {}", value))
+}
+
+fn ics_priority(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Urgent => 1,
+        TaskPriority::High => 3,
+        TaskPriority::Medium => 5,
+        TaskPriority::Low => 9,
+    }
+}
+
+fn task_priority(priority: u8) -> Option<TaskPriority> {
+    match priority {
+        1..=2 => Some(TaskPriority::Urgent),
+        3..=4 => Some(TaskPriority::High),
+        5 => Some(TaskPriority::Medium),
+        6..=9 => Some(TaskPriority::Low),
+        _ => None,
+    }
+}
+
+// Minutes of a trigger before the due date (`-PT30M`, `-P1D`, `-P1DT2H`);
+// absolute triggers and triggers after the due date are ignored
+fn reminder_minutes(trigger: &str) -> Option<u32> {
+    let duration = trigger.trim().strip_prefix("-P")?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in duration.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                // Out of range amounts from the file are rejected, not a panic
+                let part = match (unit, in_time) {
+                    ('W', false) => Duration::try_weeks(n),
+                    ('D', false) => Duration::try_days(n),
+                    ('H', true) => Duration::try_hours(n),
+                    ('M', true) => Duration::try_minutes(n),
+                    ('S', true) => Duration::try_seconds(n),
+                    _ => return None,
+                }?;
+                total = total.checked_add(&part)?;
+            }
+        }
+    }
+    u32::try_from(total.num_minutes()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_written_tasks_read_back() {
+        let mut task = Task::new("t1".to_string(), "Pay rent, utilities; etc".to_string());
+        task.description = Some("Line one\nLine two".to_string());
+        task.due_date = Some(parse_time("20261102T090000Z").unwrap());
+        task.priority = TaskPriority::High;
+        task.reminders = vec![1440, 30];
+        let ics = write_calendar("Tasks", &[task.clone()], IcsComponent::Todo);
+        assert!(ics.contains("SUMMARY:Pay rent\\, utilities\\; etc\r\n"));
+        assert!(ics.contains("TRIGGER;RELATED=END:-PT1440M\r\n"));
+        assert!(ics.lines().all(|l| l.len() <= MAX_LINE_OCTETS));
+
+        let entries = read_calendar(&ics).unwrap();
+        assert_eq!(entries.len(), 1);
+        let req = entries[0].request.as_ref().unwrap();
+        assert_eq!(req.task_name, task.task_name);
+        assert_eq!(req.description, task.description);
+        assert_eq!(req.due_date, task.due_date);
+        assert_eq!(req.priority, Some(TaskPriority::High));
+        assert_eq!(req.reminders, Some(vec![1440, 30]));
+    }
+
+    #[test]
+    fn test_folded_lines_dates_and_rules_are_read() {
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Stand\n -up\nDTSTART;VALUE=DATE:20261102\nRRULE:FREQ=WEEKLY;COUNT=4\nBEGIN:VALARM\nTRIGGER:-P1DT2H\nEND:VALARM\nEND:VEVENT\nBEGIN:VTODO\nSUMMARY:Bad\nRRULE:FREQ=YEARLY\nEND:VTODO\nEND:VCALENDAR\n";
+        let entries = read_calendar(ics).unwrap();
+        assert_eq!(entries.len(), 2);
+        let req = entries[0].request.as_ref().unwrap();
+        assert_eq!(req.task_name, "Stand-up");
+        assert_eq!(req.due_date, Some(parse_time("20261102T235959Z").unwrap()));
+        assert_eq!(req.recurrence.as_ref().unwrap().count, Some(4));
+        assert_eq!(req.reminders, Some(vec![1560]));
+        assert!(entries[1].request.is_err());
+        assert!(read_calendar("hello").is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_triggers_are_ignored() {
+        assert_eq!(reminder_minutes("-P9999999999999W"), None);
+        assert_eq!(reminder_minutes("-P99999999999999999999D"), None);
+        assert_eq!(reminder_minutes("-P10000000000W10000000000W"), None);
+        assert_eq!(reminder_minutes("-PT30M"), Some(30));
+        let ics = "BEGIN:VCALENDAR\nBEGIN:VTODO\nSUMMARY:Far\nBEGIN:VALARM\nTRIGGER:-P9999999999999W\nEND:VALARM\nEND:VTODO\nEND:VCALENDAR\n";
+        let entries = read_calendar(ics).unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
@@ -0,0 +1,9 @@
+//! Calendar Infrastructure Module
+//!
+//! Task due dates in the iCalendar format, for calendar subscriptions
+//! (`GET /api/tasks/calendar.ics`) and `.ics` imports.
+//!
+//! # Module Structure
+//! - `ics`: RFC 5545 writer and reader for `VEVENT` / `VTODO` components
+
+pub mod ics;
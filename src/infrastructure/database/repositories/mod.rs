@@ -1,2 +1,14 @@
+pub mod surreal_account_deletion;
+pub mod surreal_account_merge;
+pub mod surreal_api_key;
+pub mod surreal_audit_log;
+pub mod surreal_board;
+pub mod surreal_calendar_feed;
 pub mod surreal_conversation;
+pub mod surreal_login_throttle;
 pub mod surreal_message;
+pub mod surreal_notification;
+pub mod surreal_oidc_state;
+pub mod surreal_one_time_token;
+pub mod surreal_role;
+pub mod surreal_task_activity;
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::account_deletion::AccountDeletionRepository;
+use crate::models::entities::user::{deleted_user_thing, DELETED_USER_ID, DELETED_USER_NAME};
+
+pub struct SurrealAccountDeletionRepository {
+    db: Database,
+}
+
+impl SurrealAccountDeletionRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AccountDeletionRepository for SurrealAccountDeletionRepository {
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Thing>, Error> {
+        let sql = "SELECT VALUE id FROM user WHERE deletion_scheduled_at != NONE AND deletion_scheduled_at <= $now";
+        let mut response = self.db.client.query(sql).bind(("now", now)).await?;
+        let due: Vec<Thing> = response.take(0)?;
+        Ok(due)
+    }
+
+    async fn purge(&self, user_id: Thing) -> Result<(), Error> {
+        let sql = "
+            BEGIN TRANSACTION;
+            UPDATE message SET sender_id = $deleted WHERE sender_id = $user;
+            UPDATE message
+                SET read_by = array::complement(read_by, [$user])
+                WHERE read_by CONTAINS $user;
+            UPDATE conversation
+                SET participants = array::complement(participants, [$user]),
+                    updated_at = $now
+                WHERE participants CONTAINS $user;
+            UPDATE task SET creator = $deleted_uuid, version = (version OR 0) + 1
+                WHERE creator = $uuid;
+            UPDATE task SET assignee = NONE, version = (version OR 0) + 1 WHERE assignee = $uuid;
+            UPDATE task
+                SET watchers = array::complement(watchers, [$uuid]),
+                    version = (version OR 0) + 1
+                WHERE watchers CONTAINS $uuid;
+            UPDATE task_comment SET author = $deleted_uuid, author_name = $deleted_name
+                WHERE author = $uuid;
+            UPDATE task_comment SET mentions = array::complement(mentions, [$uuid])
+                WHERE mentions CONTAINS $uuid;
+            UPDATE task_activity SET actor = $deleted_uuid, actor_name = $deleted_name
+                WHERE actor = $uuid;
+            UPDATE task_activity SET `from` = $deleted_uuid WHERE kind = 'reassigned' AND `from` = $uuid;
+            UPDATE task_activity SET `to` = $deleted_uuid WHERE kind = 'reassigned' AND `to` = $uuid;
+            UPDATE project SET owner = $deleted_uuid, updated_at = $now WHERE owner = $uuid;
+            UPDATE project
+                SET members = array::complement(members, [$uuid]),
+                    updated_at = $now
+                WHERE members CONTAINS $uuid;
+            DELETE notification WHERE user_id = $uuid;
+            DELETE type::thing('calendar_feed', $uuid);
+            DELETE api_key WHERE user_id = $user;
+            DELETE one_time_token WHERE user_id = $user;
+            DELETE $user;
+            COMMIT TRANSACTION;
+        ";
+        let uuid = user_id.id.to_raw();
+        self.db
+            .client
+            .query(sql)
+            .bind(("user", user_id))
+            .bind(("uuid", uuid))
+            .bind(("deleted", deleted_user_thing()))
+            .bind(("deleted_uuid", DELETED_USER_ID))
+            .bind(("deleted_name", DELETED_USER_NAME))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::account_merge::AccountMergeRepository;
+use crate::models::entities::user::User;
+
+pub struct SurrealAccountMergeRepository {
+    db: Database,
+}
+
+impl SurrealAccountMergeRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AccountMergeRepository for SurrealAccountMergeRepository {
+    async fn merge(&self, source: Thing, target: User) -> Result<(), Error> {
+        let target_id = target.id.clone().ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Merge target has no id".to_string(),
+            ))
+        })?;
+        let source_uuid = record_key(&source);
+        let target_uuid = record_key(&target_id);
+        let sql = "
+            BEGIN TRANSACTION;
+            UPDATE $target MERGE $credentials;
+            UPDATE conversation
+                SET participants = array::union(array::complement(participants, [$source]), [$target]),
+                    updated_at = $now
+                WHERE participants CONTAINS $source;
+            UPDATE message SET sender_id = $target WHERE sender_id = $source;
+            UPDATE message
+                SET read_by = array::union(array::complement(read_by, [$source]), [$target])
+                WHERE read_by CONTAINS $source;
+            UPDATE api_key SET revoked_at = $now WHERE user_id = $source AND revoked_at = NONE;
+            UPDATE task SET creator = $target_uuid, version = (version OR 0) + 1
+                WHERE creator = $source_uuid;
+            UPDATE task SET assignee = $target_uuid, version = (version OR 0) + 1
+                WHERE assignee = $source_uuid;
+            UPDATE task
+                SET watchers = array::union(array::complement(watchers, [$source_uuid]), [$target_uuid]),
+                    version = (version OR 0) + 1
+                WHERE watchers CONTAINS $source_uuid;
+            UPDATE task_comment SET author = $target_uuid WHERE author = $source_uuid;
+            UPDATE task_activity SET actor = $target_uuid WHERE actor = $source_uuid;
+            UPDATE project SET owner = $target_uuid, updated_at = $now WHERE owner = $source_uuid;
+            UPDATE project
+                SET members = array::union(array::complement(members, [$source_uuid]), [$target_uuid]),
+                    updated_at = $now
+                WHERE members CONTAINS $source_uuid;
+            UPDATE project SET members = array::complement(members, [owner]) WHERE members CONTAINS owner;
+            UPDATE notification SET user_id = $target_uuid WHERE user_id = $source_uuid;
+            LET $feed = (SELECT * FROM ONLY type::thing('calendar_feed', $source_uuid));
+            IF $feed != NONE AND (SELECT * FROM ONLY type::thing('calendar_feed', $target_uuid)) = NONE {
+                CREATE type::thing('calendar_feed', $target_uuid) CONTENT {
+                    user_id: $target_uuid,
+                    token_hash: $feed.token_hash,
+                    created_at: $feed.created_at
+                };
+            };
+            DELETE type::thing('calendar_feed', $source_uuid);
+            DELETE $source;
+            COMMIT TRANSACTION;
+        ";
+        self.db
+            .client
+            .query(sql)
+            .bind(("source", source))
+            .bind(("target", target_id))
+            .bind(("source_uuid", source_uuid))
+            .bind(("target_uuid", target_uuid))
+            .bind(("credentials", credentials_patch(&target)))
+            .bind(("now", Utc::now()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+// Tasks, projects, notifications and feeds hold bare user UUIDs
+fn record_key(thing: &Thing) -> String {
+    match &thing.id {
+        surrealdb::sql::Id::String(s) => s.clone(),
+        surrealdb::sql::Id::Uuid(u) => u.to_string(),
+        other => other
+            .to_string()
+            .trim_matches('⟨')
+            .trim_matches('⟩')
+            .to_string(),
+    }
+}
+
+// Only the fields a merge can change; absent values are left untouched
+fn credentials_patch(user: &User) -> Value {
+    let mut patch = Map::new();
+    patch.insert("wallets".to_string(), serde_json::json!(user.wallets));
+    patch.insert("identities".to_string(), serde_json::json!(user.identities));
+    patch.insert(
+        "email_verified".to_string(),
+        Value::Bool(user.email_verified),
+    );
+    if let Some(wallet) = &user.wallet {
+        patch.insert("wallet".to_string(), Value::String(wallet.clone()));
+    }
+    if let Some(password) = &user.password {
+        patch.insert("password".to_string(), Value::String(password.clone()));
+    }
+    if let Some(email) = &user.email {
+        patch.insert("email".to_string(), Value::String(email.clone()));
+    }
+    Value::Object(patch)
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::api_key::ApiKeyRepository;
+use crate::models::entities::api_key::ApiKey;
+
+pub struct SurrealApiKeyRepository {
+    db: Database,
+}
+
+impl SurrealApiKeyRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for SurrealApiKeyRepository {
+    async fn create(&self, key: ApiKey) -> Result<ApiKey, Error> {
+        let created: Option<ApiKey> = self.db.client.create("api_key").content(key).await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create API key".to_string(),
+            ))
+        })
+    }
+
+    async fn find(&self, key_id: Thing) -> Result<Option<ApiKey>, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT * FROM $key")
+            .bind(("key", key_id))
+            .await?;
+        let keys: Vec<ApiKey> = response.take(0)?;
+        Ok(keys.into_iter().next())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, Error> {
+        let sql = "SELECT * FROM api_key WHERE key_hash = $hash LIMIT 1";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("hash", key_hash.to_owned()))
+            .await?;
+        let keys: Vec<ApiKey> = response.take(0)?;
+        Ok(keys.into_iter().next())
+    }
+
+    async fn list_for_user(&self, user_id: Thing) -> Result<Vec<ApiKey>, Error> {
+        let sql = "SELECT * FROM api_key WHERE user_id = $user ORDER BY created_at DESC";
+        let mut response = self.db.client.query(sql).bind(("user", user_id)).await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn revoke(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), Error> {
+        self.db
+            .client
+            .query("UPDATE $key SET revoked_at = $at WHERE revoked_at = NONE")
+            .bind(("key", key_id))
+            .bind(("at", at))
+            .await?;
+        Ok(())
+    }
+
+    async fn touch(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), Error> {
+        self.db
+            .client
+            .query("UPDATE $key SET last_used_at = $at")
+            .bind(("key", key_id))
+            .bind(("at", at))
+            .await?;
+        Ok(())
+    }
+}
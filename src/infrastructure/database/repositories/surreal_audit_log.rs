@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::audit_log::AuditLogRepository;
+use crate::models::entities::audit_event::AuditEvent;
+
+pub struct SurrealAuditLogRepository {
+    db: Database,
+}
+
+impl SurrealAuditLogRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AuditLogRepository for SurrealAuditLogRepository {
+    async fn record(&self, event: AuditEvent) -> Result<AuditEvent, Error> {
+        let created: Option<AuditEvent> =
+            self.db.client.create("audit_event").content(event).await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to record audit event".to_string(),
+            ))
+        })
+    }
+
+    async fn find_by_subject(&self, subject: &str, limit: usize) -> Result<Vec<AuditEvent>, Error> {
+        let sql = "SELECT * FROM audit_event WHERE subject = $subject ORDER BY created_at DESC LIMIT $limit";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("subject", subject.to_owned()))
+            .bind(("limit", limit))
+            .await?;
+        Ok(response.take(0)?)
+    }
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::board::BoardRepository;
+use crate::models::entities::board::{Board, Project};
+
+pub struct SurrealBoardRepository {
+    db: Database,
+}
+
+impl SurrealBoardRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl BoardRepository for SurrealBoardRepository {
+    async fn create_project(&self, project: Project) -> Result<Project, Error> {
+        let created: Option<Project> = self
+            .db
+            .client
+            .create(("project", project.uuid.clone()))
+            .content(project)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create project".to_string(),
+            ))
+        })
+    }
+
+    async fn find_project(&self, uuid: &str) -> Result<Option<Project>, Error> {
+        self.db.client.select(("project", uuid)).await
+    }
+
+    async fn find_projects_by_user(&self, user_id: &str) -> Result<Vec<Project>, Error> {
+        let sql = "SELECT * FROM project WHERE owner = $user OR members CONTAINS $user ORDER BY created_at";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("user", user_id.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn add_project_member(&self, uuid: &str, user_id: &str) -> Result<(), Error> {
+        self.db
+            .client
+            .query("UPDATE $project SET members += $user, updated_at = time::now() WHERE members CONTAINSNOT $user")
+            .bind(("project", Thing::from(("project", uuid))))
+            .bind(("user", user_id.to_owned()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn create_board(&self, board: Board) -> Result<Board, Error> {
+        let created: Option<Board> = self
+            .db
+            .client
+            .create(("board", board.uuid.clone()))
+            .content(board)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create board".to_string(),
+            ))
+        })
+    }
+
+    async fn find_board(&self, uuid: &str) -> Result<Option<Board>, Error> {
+        self.db.client.select(("board", uuid)).await
+    }
+
+    async fn find_boards_by_project(&self, project_id: &str) -> Result<Vec<Board>, Error> {
+        let sql = "SELECT * FROM board WHERE project_id = $project ORDER BY created_at";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("project", project_id.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn save_board(
+        &self,
+        board: Board,
+        expected_version: u64,
+    ) -> Result<Option<Board>, Error> {
+        // Compare-and-set on the version: a concurrent change makes the WHERE miss
+        let mut response = self
+            .db
+            .client
+            .query("UPDATE $board CONTENT $content WHERE version = $expected RETURN AFTER")
+            .bind(("board", Thing::from(("board", board.uuid.as_str()))))
+            .bind(("content", board))
+            .bind(("expected", expected_version))
+            .await?;
+        let saved: Vec<Board> = response.take(0)?;
+        Ok(saved.into_iter().next())
+    }
+}
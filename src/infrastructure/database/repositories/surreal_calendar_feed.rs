@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::calendar_feed::CalendarFeedRepository;
+use crate::models::entities::calendar::CalendarFeed;
+
+pub struct SurrealCalendarFeedRepository {
+    db: Database,
+}
+
+impl SurrealCalendarFeedRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl CalendarFeedRepository for SurrealCalendarFeedRepository {
+    async fn upsert(&self, feed: CalendarFeed) -> Result<CalendarFeed, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("UPSERT $id CONTENT $feed RETURN AFTER")
+            .bind(("id", Thing::from(("calendar_feed", feed.user_id.as_str()))))
+            .bind(("feed", feed))
+            .await?;
+        let stored: Option<CalendarFeed> = response.take(0)?;
+        stored.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to store calendar feed".to_string(),
+            ))
+        })
+    }
+
+    async fn find_by_token_hash(&self, token_hash: &str) -> Result<Option<CalendarFeed>, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT * FROM calendar_feed WHERE token_hash = $hash LIMIT 1")
+            .bind(("hash", token_hash.to_owned()))
+            .await?;
+        let found: Vec<CalendarFeed> = response.take(0)?;
+        Ok(found.into_iter().next())
+    }
+
+    async fn delete(&self, user_id: &str) -> Result<(), Error> {
+        let _deleted: Option<CalendarFeed> =
+            self.db.client.delete(("calendar_feed", user_id)).await?;
+        Ok(())
+    }
+}
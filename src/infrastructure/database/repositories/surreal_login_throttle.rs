@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::login_throttle::LoginThrottleRepository;
+use crate::models::entities::login_throttle::LoginThrottle;
+
+pub struct SurrealLoginThrottleRepository {
+    db: Database,
+}
+
+impl SurrealLoginThrottleRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LoginThrottleRepository for SurrealLoginThrottleRepository {
+    async fn find(&self, key: &str) -> Result<Option<LoginThrottle>, Error> {
+        let sql = "SELECT * FROM type::thing('login_throttle', $key)";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("key", key.to_owned()))
+            .await?;
+        let found: Vec<LoginThrottle> = response.take(0)?;
+        Ok(found.into_iter().next())
+    }
+
+    async fn record_failure(&self, key: &str, window: Duration) -> Result<LoginThrottle, Error> {
+        // Single UPSERT so concurrent failures are all counted
+        let sql = "UPSERT type::thing('login_throttle', $key) SET key = $key, failures = IF last_failure_at > $window_start THEN (failures OR 0) + 1 ELSE 1 END, last_failure_at = $now RETURN AFTER";
+        let now = Utc::now();
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("key", key.to_owned()))
+            .bind(("window_start", now - window))
+            .bind(("now", now))
+            .await?;
+        let updated: Vec<LoginThrottle> = response.take(0)?;
+        updated.into_iter().next().ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to record login failure".to_string(),
+            ))
+        })
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), Error> {
+        let sql = "UPDATE type::thing('login_throttle', $key) SET locked_until = $until";
+        self.db
+            .client
+            .query(sql)
+            .bind(("key", key.to_owned()))
+            .bind(("until", until))
+            .await?;
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), Error> {
+        let sql = "DELETE type::thing('login_throttle', $key)";
+        self.db
+            .client
+            .query(sql)
+            .bind(("key", key.to_owned()))
+            .await?;
+        Ok(())
+    }
+}
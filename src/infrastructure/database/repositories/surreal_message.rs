@@ -49,6 +49,43 @@ impl MessageRepository for SurrealMessageRepository {
         Ok(messages)
     }
 
+    async fn find_by_sender(&self, sender_id: Thing) -> Result<Vec<Message>, Error> {
+        let sql = "SELECT * FROM message WHERE sender_id = $sender ORDER BY created_at ASC";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("sender", sender_id))
+            .await?;
+
+        let messages: Vec<Message> = response.take(0)?;
+        Ok(messages)
+    }
+
+    async fn find_by_id(&self, id: Thing) -> Result<Option<Message>, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT * FROM $id")
+            .bind(("id", id))
+            .await?;
+
+        let message: Option<Message> = response.take(0)?;
+        Ok(message)
+    }
+
+    async fn link_task(&self, message_id: Thing, task_id: String) -> Result<bool, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("UPDATE $id SET task_id = $task WHERE task_id = NONE RETURN AFTER")
+            .bind(("id", message_id))
+            .bind(("task", task_id))
+            .await?;
+        let linked: Option<Message> = response.take(0)?;
+        Ok(linked.is_some())
+    }
+
     async fn mark_as_read(&self, message_id: Thing, user_id: Thing) -> Result<(), Error> {
         let sql = "UPDATE $id SET read_by += $user";
         self.db
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::notification::NotificationRepository;
+use crate::models::entities::notification::Notification;
+
+pub struct SurrealNotificationRepository {
+    db: Database,
+}
+
+impl SurrealNotificationRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SurrealNotificationRepository {
+    async fn create(&self, notification: Notification) -> Result<Notification, Error> {
+        let created: Option<Notification> = self
+            .db
+            .client
+            .create(("notification", notification.uuid.clone()))
+            .content(notification)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create notification".to_string(),
+            ))
+        })
+    }
+
+    async fn find_for_user(
+        &self,
+        user_id: &str,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, Error> {
+        let sql = if unread_only {
+            "SELECT * FROM notification WHERE user_id = $user AND read_at = NONE ORDER BY created_at DESC"
+        } else {
+            "SELECT * FROM notification WHERE user_id = $user ORDER BY created_at DESC"
+        };
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("user", user_id.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn mark_read(
+        &self,
+        user_id: &str,
+        uuid: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error> {
+        // Keeps the first read time; another user's notification is not matched
+        let sql = "UPDATE $id SET read_at = read_at OR $at WHERE user_id = $user RETURN AFTER";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("id", Thing::from(("notification", uuid))))
+            .bind(("user", user_id.to_owned()))
+            .bind(("at", at))
+            .await?;
+        let updated: Vec<Notification> = response.take(0)?;
+        Ok(updated.into_iter().next())
+    }
+}
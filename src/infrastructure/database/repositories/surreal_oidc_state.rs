@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::oidc_state::OidcStateRepository;
+use crate::models::entities::oidc_state::OidcLoginState;
+
+pub struct SurrealOidcStateRepository {
+    db: Database,
+}
+
+impl SurrealOidcStateRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OidcStateRepository for SurrealOidcStateRepository {
+    async fn create(&self, state: OidcLoginState) -> Result<OidcLoginState, Error> {
+        let created: Option<OidcLoginState> =
+            self.db.client.create("oidc_state").content(state).await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create OIDC login state".to_string(),
+            ))
+        })
+    }
+
+    async fn consume(&self, state_hash: &str) -> Result<Option<OidcLoginState>, Error> {
+        // Single DELETE so a replayed callback finds nothing
+        let sql = "DELETE oidc_state WHERE state_hash = $hash AND expires_at > $now RETURN BEFORE";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("hash", state_hash.to_owned()))
+            .bind(("now", Utc::now()))
+            .await?;
+        let consumed: Vec<OidcLoginState> = response.take(0)?;
+        Ok(consumed.into_iter().next())
+    }
+
+    async fn purge_expired(&self) -> Result<(), Error> {
+        self.db
+            .client
+            .query("DELETE oidc_state WHERE expires_at <= $now")
+            .bind(("now", Utc::now()))
+            .await?;
+        Ok(())
+    }
+}
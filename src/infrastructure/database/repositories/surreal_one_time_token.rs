@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+
+pub struct SurrealOneTimeTokenRepository {
+    db: Database,
+}
+
+impl SurrealOneTimeTokenRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for SurrealOneTimeTokenRepository {
+    async fn create(&self, token: OneTimeToken) -> Result<OneTimeToken, Error> {
+        let created: Option<OneTimeToken> = self
+            .db
+            .client
+            .create("one_time_token")
+            .content(token)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create one-time token".to_string(),
+            ))
+        })
+    }
+
+    async fn consume(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &str,
+    ) -> Result<Option<OneTimeToken>, Error> {
+        // Single UPDATE so two concurrent requests cannot both consume the token
+        let sql = "UPDATE one_time_token SET used_at = $now WHERE token_hash = $hash AND purpose = $purpose AND used_at = NONE AND expires_at > $now RETURN AFTER";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("hash", token_hash.to_owned()))
+            .bind(("purpose", purpose))
+            .bind(("now", Utc::now()))
+            .await?;
+        let consumed: Vec<OneTimeToken> = response.take(0)?;
+        Ok(consumed.into_iter().next())
+    }
+
+    async fn find_latest_for_user(
+        &self,
+        user_id: Thing,
+        purpose: TokenPurpose,
+    ) -> Result<Option<OneTimeToken>, Error> {
+        let sql = "SELECT * FROM one_time_token WHERE user_id = $user AND purpose = $purpose ORDER BY created_at DESC LIMIT 1";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("user", user_id))
+            .bind(("purpose", purpose))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn invalidate_for_user(
+        &self,
+        user_id: Thing,
+        purpose: TokenPurpose,
+    ) -> Result<(), Error> {
+        let sql = "UPDATE one_time_token SET used_at = $now WHERE user_id = $user AND purpose = $purpose AND used_at = NONE";
+        self.db
+            .client
+            .query(sql)
+            .bind(("user", user_id))
+            .bind(("purpose", purpose))
+            .bind(("now", Utc::now()))
+            .await?;
+        Ok(())
+    }
+
+    async fn purge_expired(&self, purpose: TokenPurpose) -> Result<(), Error> {
+        let sql = "DELETE one_time_token WHERE purpose = $purpose AND expires_at < $now";
+        self.db
+            .client
+            .query(sql)
+            .bind(("purpose", purpose))
+            .bind(("now", Utc::now()))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use surrealdb::Error;
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::role::RoleRepository;
+use crate::models::entities::role::Role;
+use crate::models::entities::user::User;
+
+pub struct SurrealRoleRepository {
+    db: Database,
+}
+
+impl SurrealRoleRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RoleRepository for SurrealRoleRepository {
+    async fn create(&self, role: Role) -> Result<Role, Error> {
+        let created: Option<Role> = self.db.client.create("role").content(role).await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create role".to_string(),
+            ))
+        })
+    }
+
+    async fn find_all(&self) -> Result<Vec<Role>, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT * FROM role ORDER BY name ASC")
+            .await?;
+        let roles: Vec<Role> = response.take(0)?;
+        Ok(roles)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Role>, Error> {
+        let mut response = self
+            .db
+            .client
+            .query("SELECT * FROM role WHERE name = $name LIMIT 1")
+            .bind(("name", name.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn update(&self, role: Role) -> Result<Role, Error> {
+        let sql = "UPDATE role SET description = $description, permissions = $permissions WHERE name = $name RETURN AFTER";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("name", role.name.clone()))
+            .bind(("description", role.description.clone()))
+            .bind(("permissions", role.permissions.clone()))
+            .await?;
+        let updated: Option<Role> = response.take(0)?;
+
+        updated.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to update role".to_string(),
+            ))
+        })
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), Error> {
+        self.db
+            .client
+            .query("DELETE role WHERE name = $name")
+            .bind(("name", name.to_owned()))
+            .await?;
+        Ok(())
+    }
+
+    async fn sync_users(&self, role: &Role) -> Result<usize, Error> {
+        let sql = "UPDATE user SET roles = array::append(roles[WHERE name != $name], $role), token_version = (token_version OR 0) + 1 WHERE roles.name CONTAINS $name RETURN AFTER";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("name", role.name.clone()))
+            .bind(("role", role.clone()))
+            .await?;
+        let users: Vec<User> = response.take(0)?;
+        Ok(users.len())
+    }
+
+    async fn remove_from_users(&self, name: &str) -> Result<usize, Error> {
+        let sql = "UPDATE user SET roles = roles[WHERE name != $name], token_version = (token_version OR 0) + 1 WHERE roles.name CONTAINS $name RETURN AFTER";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("name", name.to_owned()))
+            .await?;
+        let users: Vec<User> = response.take(0)?;
+        Ok(users.len())
+    }
+}
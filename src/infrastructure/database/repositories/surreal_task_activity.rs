@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use surrealdb::{Error, Response};
+
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::repositories::task_activity::TaskActivityRepository;
+use crate::models::entities::task_activity::{TaskActivity, TaskComment};
+
+pub struct SurrealTaskActivityRepository {
+    db: Database,
+}
+
+impl SurrealTaskActivityRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TaskActivityRepository for SurrealTaskActivityRepository {
+    async fn add_comment(&self, comment: TaskComment) -> Result<TaskComment, Error> {
+        let created: Option<TaskComment> = self
+            .db
+            .client
+            .create(("task_comment", comment.uuid.clone()))
+            .content(comment)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to create task comment".to_string(),
+            ))
+        })
+    }
+
+    async fn add_activity(&self, activity: TaskActivity) -> Result<TaskActivity, Error> {
+        let created: Option<TaskActivity> = self
+            .db
+            .client
+            .create(("task_activity", activity.uuid.clone()))
+            .content(activity)
+            .await?;
+
+        created.ok_or_else(|| {
+            Error::Db(surrealdb::error::Db::Thrown(
+                "Failed to record task activity".to_string(),
+            ))
+        })
+    }
+
+    async fn find_comments(&self, task_id: &str) -> Result<Vec<TaskComment>, Error> {
+        let sql = "SELECT * FROM task_comment WHERE task_id = $task ORDER BY created_at";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("task", task_id.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn find_activity(&self, task_id: &str) -> Result<Vec<TaskActivity>, Error> {
+        let sql = "SELECT * FROM task_activity WHERE task_id = $task ORDER BY created_at";
+        let mut response = self
+            .db
+            .client
+            .query(sql)
+            .bind(("task", task_id.to_owned()))
+            .await?;
+        Ok(response.take(0)?)
+    }
+
+    async fn delete_for_task(&self, task_id: &str) -> Result<(), Error> {
+        self.db
+            .client
+            .query("DELETE task_comment WHERE task_id = $task; DELETE task_activity WHERE task_id = $task;")
+            .bind(("task", task_id.to_owned()))
+            .await
+            .and_then(Response::check)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! File mailer: writes each e-mail as `<timestamp>-<uuid>.json` into a directory.
+//!
+//! Useful for local development, where no SMTP server is available.
+
+use async_trait::async_trait;
+use log::debug;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use super::{MailError, Mailer, OutgoingEmail};
+
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| MailError::Delivery(e.to_string()))?;
+
+        let name = format!(
+            "{}-{}.json",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+            Uuid::new_v4()
+        );
+        let path = self.dir.join(name);
+        let json = serde_json::to_vec_pretty(&email)
+            .map_err(|e| MailError::InvalidMessage(e.to_string()))?;
+        tokio::fs::write(&path, json)
+            .await
+            .map_err(|e| MailError::Delivery(e.to_string()))?;
+
+        debug!("FileMailer: wrote {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+//! In-memory mailer used by tests to inspect what would have been sent.
+
+use async_trait::async_trait;
+use std::sync::Mutex;
+
+use super::{MailError, Mailer, OutgoingEmail};
+
+/// Stores every e-mail in a vector instead of delivering it.
+#[derive(Default)]
+pub struct InMemoryMailer {
+    sent: Mutex<Vec<OutgoingEmail>>,
+}
+
+impl InMemoryMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of all e-mails sent so far.
+    pub fn sent(&self) -> Vec<OutgoingEmail> {
+        self.sent.lock().expect("mailer lock poisoned").clone()
+    }
+
+    /// Returns the last e-mail sent to `to`, if any.
+    pub fn last_to(&self, to: &str) -> Option<OutgoingEmail> {
+        self.sent
+            .lock()
+            .expect("mailer lock poisoned")
+            .iter()
+            .rev()
+            .find(|e| e.to == to)
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError> {
+        self.sent.lock().expect("mailer lock poisoned").push(email);
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+//! Mail Infrastructure Module
+//!
+//! Outgoing e-mail goes through the `Mailer` trait so the delivery mechanism can
+//! be swapped without touching the services that send mail (password reset,
+//! e-mail verification).
+//!
+//! # Module Structure
+//! - `smtp`: SMTP delivery via `lettre` (production)
+//! - `file`: Writes each e-mail as a JSON file into a directory (local dev)
+//! - `memory`: Keeps e-mails in memory (tests)
+//!
+//! Env:
+//! - MAILER: `smtp` | `file` | `memory` (default `file`)
+//! - SMTP_HOST, SMTP_PORT (default 587), SMTP_USER, SMTP_PASS
+//! - MAIL_FROM (default `Chasqui <no-reply@localhost>`)
+//! - MAIL_OUTBOX_DIR (default `./outbox`, file mailer only)
+
+pub mod file;
+pub mod memory;
+pub mod smtp;
+
+use async_trait::async_trait;
+use derive_more::Display;
+use log::{info, warn};
+use serde::Serialize;
+use std::env;
+use std::sync::Arc;
+
+/// A plain-text e-mail ready to be delivered.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Errors raised while delivering an e-mail.
+#[derive(Debug, Display)]
+pub enum MailError {
+    /// The message could not be built (invalid address, header, ...).
+    #[display(fmt = "invalid message: {}", _0)]
+    InvalidMessage(String),
+    /// The transport failed to deliver the message.
+    #[display(fmt = "delivery failed: {}", _0)]
+    Delivery(String),
+}
+
+impl std::error::Error for MailError {}
+
+/// Delivers outgoing e-mail.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError>;
+}
+
+/// Builds the mailer selected by the `MAILER` environment variable.
+///
+/// Falls back to the file mailer when `MAILER` is unset or the SMTP settings
+/// are incomplete, so a missing configuration never drops e-mails silently.
+pub fn mailer_from_env() -> Arc<dyn Mailer> {
+    let kind = env::var("MAILER").unwrap_or_else(|_| "file".to_string());
+    let from = env::var("MAIL_FROM").unwrap_or_else(|_| "Chasqui <no-reply@localhost>".to_string());
+
+    match kind.as_str() {
+        "smtp" => match smtp::SmtpMailer::from_env(&from) {
+            Ok(mailer) => {
+                info!("Mailer: SMTP");
+                return Arc::new(mailer);
+            }
+            Err(e) => warn!("Mailer: SMTP misconfigured ({}), falling back to file", e),
+        },
+        "memory" => {
+            info!("Mailer: in-memory");
+            return Arc::new(memory::InMemoryMailer::new());
+        }
+        _ => {}
+    }
+
+    let dir = env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "./outbox".to_string());
+    info!("Mailer: file outbox at {}", dir);
+    Arc::new(file::FileMailer::new(dir))
+}
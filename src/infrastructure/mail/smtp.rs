@@ -0,0 +1,66 @@
+//! SMTP mailer backed by `lettre` (STARTTLS on the configured port).
+
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::debug;
+use std::env;
+
+use super::{MailError, Mailer, OutgoingEmail};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+}
+
+impl SmtpMailer {
+    /// Builds the transport from SMTP_HOST, SMTP_PORT, SMTP_USER and SMTP_PASS.
+    pub fn from_env(from: &str) -> Result<Self, MailError> {
+        let host = env::var("SMTP_HOST")
+            .map_err(|_| MailError::InvalidMessage("SMTP_HOST is not set".to_string()))?;
+        let port = env::var("SMTP_PORT")
+            .ok()
+            .and_then(|v| v.parse::<u16>().ok())
+            .unwrap_or(587);
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .map_err(|e| MailError::InvalidMessage(e.to_string()))?
+            .port(port);
+        if let (Ok(user), Ok(pass)) = (env::var("SMTP_USER"), env::var("SMTP_PASS")) {
+            builder = builder.credentials(Credentials::new(user, pass));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError> {
+        let message = Message::builder()
+            .from(
+                self.from
+                    .parse()
+                    .map_err(|e| MailError::InvalidMessage(format!("from: {}", e)))?,
+            )
+            .to(email
+                .to
+                .parse()
+                .map_err(|e| MailError::InvalidMessage(format!("to: {}", e)))?)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| MailError::InvalidMessage(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| MailError::Delivery(e.to_string()))?;
+        debug!("SmtpMailer: delivered to {}", email.to);
+        Ok(())
+    }
+}
@@ -6,7 +6,9 @@
 //! # Module Structure
 //! - `database`: Database connection and query implementations
 //! - `auth`: Authentication and authorization services
+//! - `calendar`: iCalendar feeds and imports of task due dates
 //! - `logging`: System-wide logging facilities
+//! - `mail`: Outgoing e-mail delivery (SMTP, file outbox, in-memory)
 //! - `websocket`: WebSocket infrastructure for real-time chat
 //!
 //! # Usage
@@ -22,6 +24,8 @@
 //! - Enable real-time communication
 
 pub mod auth;
+pub mod calendar;
 pub mod database;
 pub mod logging;
+pub mod mail;
 pub mod websocket;
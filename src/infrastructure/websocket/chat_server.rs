@@ -6,8 +6,20 @@
 //! - Room management (conversations)
 //! - Message broadcasting
 //! - User presence tracking
+//! - Task change events (`TaskCreated` / `TaskUpdated` / `TaskDeleted`),
+//!   delivered to every session of the users who can see the task
+//! - The `/task <title>` command: the message is saved as usual, then turned
+//!   into a task; the room receives the `system` announcement (`NewMessage`)
+//!   and `MessageTaskLinked` for the source message
+//! - Task timeline entries (`TaskTimeline`: new comments and recorded
+//!   changes), delivered to the sessions of the task's watchers
+//! - Board changes (`ColumnsChanged` / `CardAdded` / `CardMoved` / `CardRemoved`),
+//!   delivered to every session of the project members
+//! - Notifications for one user (`TaskReminder`), delivered to every session
+//!   of that user; the sender learns whether the user was connected
 
 use actix::prelude::*;
+use async_trait::async_trait;
 use log::{debug, error, info};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -15,7 +27,18 @@ use surrealdb::sql::Thing;
 
 use crate::application::services::conversation_service::ConversationService;
 use crate::application::services::message_service::MessageService;
-use crate::models::entities::message::{Message, MessageType};
+use crate::application::services::message_task_service::{
+    task_command, MessageTask, MessageTaskService,
+};
+use crate::infrastructure::database::surrealdb::Database;
+use crate::models::entities::board::BoardEvent;
+use crate::models::entities::message::{LinkedTask, Message, MessagePayload, MessageType};
+use crate::models::entities::task::{CreateTaskFromMessageRequest, TaskEvent};
+use crate::models::entities::task_activity::TimelineEvent;
+use crate::models::traits::user_data_trait::UserDataTrait;
+use crate::models::traits::board_event_sink::BoardEventSink;
+use crate::models::traits::notification_sink::NotificationSink;
+use crate::models::traits::task_event_sink::TaskEventSink;
 
 /// Chat server manages all WebSocket connections and rooms
 pub struct ChatServer {
@@ -35,6 +58,9 @@ pub struct ChatServer {
     message_service: Arc<MessageService>,
     #[allow(dead_code)]
     conversation_service: Arc<ConversationService>,
+
+    /// Handles the `/task` command (disabled when not set)
+    message_tasks: Option<(Arc<MessageTaskService>, Database)>,
 }
 
 impl ChatServer {
@@ -49,9 +75,16 @@ impl ChatServer {
             recipients: HashMap::new(),
             message_service,
             conversation_service,
+            message_tasks: None,
         }
     }
 
+    /// Enables the `/task` command
+    pub fn with_message_tasks(mut self, message_tasks: Arc<MessageTaskService>, db: Database) -> Self {
+        self.message_tasks = Some((message_tasks, db));
+        self
+    }
+
     /// Helper to send message to a specific session
     fn send_message_to_session(&self, session_id: usize, msg: &str) {
         if let Some(recipient) = self.recipients.get(&session_id) {
@@ -61,6 +94,49 @@ impl ChatServer {
         }
     }
 
+    /// Send a message to every session of the given users (UUIDs)
+    fn send_to_users(&self, user_ids: &HashSet<&str>, msg: &str) {
+        for (session_id, user_id) in &self.sessions {
+            if user_ids.contains(user_id.id.to_raw().as_str()) {
+                self.send_message_to_session(*session_id, msg);
+            }
+        }
+    }
+
+    /// Announce a task created from a message to the message's conversation
+    fn broadcast_message_task(&self, message_task: &MessageTask, sender_is_bot: bool) {
+        let announcement = &message_task.announcement;
+        // Rooms are keyed by `conversation:<uuid>` as sent by the clients
+        let conversation_id = format!(
+            "{}:{}",
+            announcement.conversation_id.tb,
+            announcement.conversation_id.id.to_raw()
+        );
+        let linked = LinkedTask::from(&message_task.task);
+        let new_message = serde_json::json!({
+            "type": "NewMessage",
+            "sender": {
+                "id": announcement.sender_id.to_string(),
+                "bot": sender_is_bot
+            },
+            "message": MessagePayload {
+                message: announcement.clone(),
+                task: Some(linked.clone()),
+            }
+        })
+        .to_string();
+        self.broadcast_to_room(&conversation_id, &new_message, None);
+        if let Some(source_message_id) = &message_task.task.source_message_id {
+            let linked_payload = serde_json::json!({
+                "type": "MessageTaskLinked",
+                "message_id": format!("message:{}", source_message_id),
+                "task": linked
+            })
+            .to_string();
+            self.broadcast_to_room(&conversation_id, &linked_payload, None);
+        }
+    }
+
     /// Broadcast message to all participants in a room (conversation)
     fn broadcast_to_room(&self, conversation_id: &str, msg: &str, _skip_session: Option<usize>) {
         if let Some(sessions) = self.rooms.get(conversation_id) {
@@ -118,6 +194,41 @@ pub struct SendMessage {
     pub conversation_id: String,
     pub message: String,
     pub sender_id: Thing,
+    /// Whether the sender is a bot account
+    pub sender_is_bot: bool,
+}
+
+/// Message to announce a task created from a chat message to its room
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct BroadcastMessageTask {
+    pub message_task: MessageTask,
+    /// Whether the task creator is a bot account
+    pub sender_is_bot: bool,
+}
+
+/// Message to deliver a task change to the sessions of its audience
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PublishTaskEvent(pub TaskEvent);
+
+/// Message to deliver a task timeline entry to the sessions of its watchers
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PublishTimelineEvent(pub TimelineEvent);
+
+/// Message to deliver a board change to the sessions of the project members
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PublishBoardEvent(pub BoardEvent);
+
+/// Message to deliver a notification to the sessions of one user; answers
+/// whether the user had any
+#[derive(Message)]
+#[rtype(result = "bool")]
+pub struct DeliverToUser {
+    pub user_id: String,
+    pub payload: String,
 }
 
 /// Message sent from server to client
@@ -180,7 +291,7 @@ impl Handler<JoinRoom> for ChatServer {
         let room = self
             .rooms
             .entry(msg.conversation_id.clone())
-            .or_insert_with(HashSet::new);
+            .or_default();
         room.insert(msg.session_id);
 
         debug!(
@@ -218,6 +329,7 @@ impl Handler<SendMessage> for ChatServer {
     fn handle(&mut self, msg: SendMessage, _ctx: &mut Context<Self>) -> Self::Result {
         let conversation_id = msg.conversation_id.clone();
         let session_id = msg.session_id;
+        let sender_is_bot = msg.sender_is_bot;
         let message_service = self.message_service.clone();
 
         // Parse conversation_id into a Thing
@@ -235,6 +347,19 @@ impl Handler<SendMessage> for ChatServer {
 
         let conv_thing = Thing::from((parts[0], parts[1]));
 
+        // `/task <title>`: checked before saving so a bare `/task` is not stored
+        let task_title = task_command(&msg.message).map(str::to_string);
+        if task_title.as_deref() == Some("") {
+            let error_payload = serde_json::json!({
+                "type": "Error",
+                "message": "Usage: /task <title>"
+            })
+            .to_string();
+            self.send_message_to_session(session_id, &error_payload);
+            return Box::pin(async {}.into_actor(self));
+        }
+        let message_tasks = task_title.and(self.message_tasks.clone());
+
         let chat_msg = Message {
             id: None,
             conversation_id: conv_thing.clone(),
@@ -243,23 +368,53 @@ impl Handler<SendMessage> for ChatServer {
             message_type: MessageType::Text,
             created_at: chrono::Utc::now(),
             read_by: vec![],
+            task_id: None,
         };
 
         // Use wrap_future to run async logic within the actor
         Box::pin(
-            async move { message_service.send_message(chat_msg).await }
+            async move {
+                let saved = message_service.send_message(chat_msg).await;
+                // Turn the saved command message into a task
+                let message_task = match (&saved, message_tasks) {
+                    (Ok(saved_msg), Some((service, db))) => {
+                        Some(create_task_from_command(&service, &db, saved_msg).await)
+                    }
+                    _ => None,
+                };
+                (saved, message_task)
+            }
                 .into_actor(self)
-                .map(move |result, act, _ctx| {
+                .map(move |(result, message_task), act, _ctx| {
                     match result {
                         Ok(saved_msg) => {
                             // Broadcast the saved message with its real ID and timestamp
                             let broadcast_payload = serde_json::json!({
                                 "type": "NewMessage",
+                                "sender": {
+                                    "id": saved_msg.sender_id.to_string(),
+                                    "bot": sender_is_bot
+                                },
                                 "message": saved_msg
                             })
                             .to_string();
 
                             act.broadcast_to_room(&conversation_id, &broadcast_payload, None);
+
+                            match message_task {
+                                Some(Ok(message_task)) => {
+                                    act.broadcast_message_task(&message_task, sender_is_bot)
+                                }
+                                Some(Err(e)) => {
+                                    let error_payload = serde_json::json!({
+                                        "type": "Error",
+                                        "message": e
+                                    })
+                                    .to_string();
+                                    act.send_message_to_session(session_id, &error_payload);
+                                }
+                                None => {}
+                            }
                         }
                         Err(e) => {
                             error!("Failed to save message: {:?}", e);
@@ -276,3 +431,139 @@ impl Handler<SendMessage> for ChatServer {
         )
     }
 }
+
+/// Handler for BroadcastMessageTask - announces the task to the message's room
+impl Handler<BroadcastMessageTask> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: BroadcastMessageTask, _ctx: &mut Context<Self>) -> Self::Result {
+        self.broadcast_message_task(&msg.message_task, msg.sender_is_bot);
+    }
+}
+
+/// Handler for PublishTaskEvent - sends the change to the task's audience
+impl Handler<PublishTaskEvent> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: PublishTaskEvent, _ctx: &mut Context<Self>) -> Self::Result {
+        let event = msg.0;
+        let audience: HashSet<&str> = event.audience.iter().map(String::as_str).collect();
+        debug!(
+            "{:?} for task {} to {} users",
+            event.kind,
+            event.task.uuid,
+            audience.len()
+        );
+        self.send_to_users(&audience, &event.payload());
+    }
+}
+
+// The task service publishes through the actor's mailbox
+impl TaskEventSink for Addr<ChatServer> {
+    fn publish(&self, event: TaskEvent) {
+        self.do_send(PublishTaskEvent(event));
+    }
+
+    fn publish_timeline(&self, event: TimelineEvent) {
+        self.do_send(PublishTimelineEvent(event));
+    }
+}
+
+/// Handler for PublishTimelineEvent - sends the entry to the task's watchers
+impl Handler<PublishTimelineEvent> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: PublishTimelineEvent, _ctx: &mut Context<Self>) -> Self::Result {
+        let event = msg.0;
+        let audience: HashSet<&str> = event.audience.iter().map(String::as_str).collect();
+        debug!(
+            "Timeline entry for task {} to {} users",
+            event.task_id,
+            audience.len()
+        );
+        self.send_to_users(&audience, &event.payload());
+    }
+}
+
+/// Handler for PublishBoardEvent - sends the change to the project members
+impl Handler<PublishBoardEvent> for ChatServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: PublishBoardEvent, _ctx: &mut Context<Self>) -> Self::Result {
+        let event = msg.0;
+        let audience: HashSet<&str> = event.audience.iter().map(String::as_str).collect();
+        debug!(
+            "{:?} on board {} to {} users",
+            event.kind,
+            event.board.uuid,
+            audience.len()
+        );
+        self.send_to_users(&audience, &event.payload());
+    }
+}
+
+// The board service publishes through the actor's mailbox
+impl BoardEventSink for Addr<ChatServer> {
+    fn publish(&self, event: BoardEvent) {
+        self.do_send(PublishBoardEvent(event));
+    }
+}
+
+/// Handler for DeliverToUser - sends the notification to the user's sessions
+impl Handler<DeliverToUser> for ChatServer {
+    type Result = bool;
+
+    fn handle(&mut self, msg: DeliverToUser, _ctx: &mut Context<Self>) -> Self::Result {
+        let online = self
+            .sessions
+            .values()
+            .any(|user_id| user_id.id.to_raw() == msg.user_id);
+        debug!("Notification for user {} (online: {})", msg.user_id, online);
+        if online {
+            self.send_to_users(&HashSet::from([msg.user_id.as_str()]), &msg.payload);
+        }
+        online
+    }
+}
+
+// Notifications wait for the answer, so offline users can be told later
+#[async_trait]
+impl NotificationSink for Addr<ChatServer> {
+    async fn deliver(&self, user_id: &str, payload: String) -> bool {
+        self.send(DeliverToUser {
+            user_id: user_id.to_string(),
+            payload,
+        })
+        .await
+        .unwrap_or_else(|e| {
+            error!("Could not deliver a notification to user {}: {}", user_id, e);
+            false
+        })
+    }
+}
+
+// Creates the task for a saved `/task <title>` message; errors are reported to the sender
+async fn create_task_from_command(
+    service: &MessageTaskService,
+    db: &Database,
+    saved_msg: &Message,
+) -> Result<MessageTask, String> {
+    let title = task_command(&saved_msg.content).unwrap_or_default().to_string();
+    let message_id = saved_msg
+        .id
+        .as_ref()
+        .map(|id| id.id.to_raw())
+        .ok_or_else(|| "Message has no ID".to_string())?;
+    let creator = db
+        .find_user_by_id(&saved_msg.sender_id.id.to_raw())
+        .await
+        .ok_or_else(|| "User not found".to_string())?;
+    let req = CreateTaskFromMessageRequest {
+        task_name: Some(title),
+        ..CreateTaskFromMessageRequest::default()
+    };
+    service
+        .create_from_message(db, db, &creator, &message_id, req)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -10,6 +10,8 @@ use std::time::{Duration, Instant};
 use surrealdb::sql::Thing;
 
 use super::chat_server::{ChatServer, Connect, Disconnect, JoinRoom, ServerMessage};
+use crate::models::entities::api_key::ApiKey;
+use crate::models::entities::role::Permission;
 
 /// How often heartbeat pings are sent
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
@@ -31,18 +33,61 @@ pub struct WsSession {
 
     /// Chat server address
     pub server: Addr<ChatServer>,
+
+    /// Whether this user may send messages (false until the e-mail is
+    /// verified when the verification policy covers chat)
+    pub can_send: bool,
+
+    /// Whether the user is a bot account (flagged on broadcast messages)
+    pub is_bot: bool,
+
+    /// API key used for the handshake; joins and sends are limited to its scopes
+    pub api_key: Option<ApiKey>,
 }
 
 impl WsSession {
-    pub fn new(user_id: Thing, server: Addr<ChatServer>) -> Self {
+    pub fn new(user_id: Thing, server: Addr<ChatServer>, can_send: bool) -> Self {
         WsSession {
             id: 0,
             user_id,
             hb: Instant::now(),
             server,
+            can_send,
+            is_bot: false,
+            api_key: None,
         }
     }
 
+    /// Marks the session as belonging to a bot account
+    pub fn as_bot(mut self, is_bot: bool) -> Self {
+        self.is_bot = is_bot;
+        self
+    }
+
+    /// Limits the session to the scopes of `api_key`
+    pub fn with_api_key(mut self, api_key: Option<ApiKey>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Whether the API key (if any) grants one of `permissions` on the conversation
+    fn scope_allows(&self, permissions: &[Permission], conversation_id: &str) -> bool {
+        self.api_key.as_ref().is_none_or(|key| {
+            permissions
+                .iter()
+                .any(|p| key.allows(*p, Some(conversation_id)))
+        })
+    }
+
+    fn send_error(ctx: &mut ws::WebsocketContext<Self>, message: &str) {
+        let error_payload = serde_json::json!({
+            "type": "Error",
+            "message": message
+        })
+        .to_string();
+        ctx.text(error_payload);
+    }
+
     /// Helper method that sends ping to client every HEARTBEAT_INTERVAL
     /// Also checks heartbeats from client
     fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
@@ -154,22 +199,38 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
                                     if let Some(conv_id) =
                                         json.get("conversation_id").and_then(|v| v.as_str())
                                     {
+                                        if !self.scope_allows(
+                                            &[Permission::ChannelRead, Permission::MessageCreate],
+                                            conv_id,
+                                        ) {
+                                            Self::send_error(ctx, "API key scope does not cover this conversation");
+                                            return;
+                                        }
                                         self.server.do_send(JoinRoom {
                                             session_id: self.id,
                                             conversation_id: conv_id.to_string(),
                                         });
                                     }
                                 }
+                                "message" if !self.can_send => {
+                                    Self::send_error(ctx, "Email not verified");
+                                }
                                 "message" => {
                                     if let (Some(conv_id), Some(content)) = (
                                         json.get("conversation_id").and_then(|v| v.as_str()),
                                         json.get("content").and_then(|v| v.as_str()),
                                     ) {
+                                        if !self.scope_allows(&[Permission::MessageCreate], conv_id)
+                                        {
+                                            Self::send_error(ctx, "API key lacks scope: message:create");
+                                            return;
+                                        }
                                         self.server.do_send(super::chat_server::SendMessage {
                                             session_id: self.id,
                                             conversation_id: conv_id.to_string(),
                                             message: content.to_string(),
                                             sender_id: self.user_id.clone(),
+                                            sender_is_bot: self.is_bot,
                                         });
                                     }
                                 }
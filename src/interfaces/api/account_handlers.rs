@@ -0,0 +1,193 @@
+//! Account Handlers Module
+//! Implements self-service account recovery and e-mail verification endpoints.
+//!
+//! Endpoints
+//! - POST /api/auth/password/forgot
+//!   Request JSON: { "email": "alice@example.com" }
+//!   202 Accepted JSON (always, to avoid account enumeration):
+//!   { "status": "accepted", "message": "If the account exists, a reset link was sent" }
+//!
+//! - POST /api/auth/password/reset
+//!   Request JSON: { "token": "<from e-mail>", "new_password": "N3w$ecret!" }
+//!   200 OK JSON: { "status": "success", "message": "Password updated" }
+//!   400 Bad Request: `InvalidOrExpiredToken` | `WeakPassword`
+//!
+//! - GET /api/auth/verify-email?token=<from e-mail>
+//!   200 OK JSON: { "status": "success", "message": "Email verified" }
+//!   400 Bad Request: `InvalidOrExpiredToken`
+//!
+//! - POST /api/auth/verify-email/resend
+//!   Request JSON: { "email": "alice@example.com" }
+//!   202 Accepted JSON (always; throttled per account)
+//!
+//! - POST /api/auth/lockouts/unlock  (Bearer JWT, `admin:all`)
+//!   Request JSON: { "account": "alice@example.com" } or { "ip": "203.0.113.7" }
+//!   204 No Content | 404 Not Found (nothing to unlock); recorded in the audit log
+//!
+//! A successful reset bumps the user's token version, so all previously issued
+//! JWTs stop being accepted.
+
+use actix_web::{web, HttpResponse, ResponseError};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::application::services::email_verification_service::EmailVerificationService;
+use crate::application::services::login_throttle_service::LoginThrottleService;
+use crate::application::services::password_reset_service::PasswordResetService;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::login_throttle::ThrottleKind;
+use crate::models::entities::role::Permission;
+
+/// Request payload carrying only an e-mail (forgot password, resend verification)
+#[derive(Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "invalid email"))]
+    pub email: String,
+}
+
+/// Request payload for completing a password reset
+#[derive(Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "token is required"))]
+    pub token: String,
+    #[validate(length(min = 1, message = "new_password is required"))]
+    pub new_password: String,
+}
+
+/// Query parameters of the verification link
+#[derive(Deserialize)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+}
+
+/// Request payload for lifting a login lockout (exactly one field)
+#[derive(Deserialize)]
+pub struct UnlockLoginRequest {
+    /// E-mail or username of the locked account (or the identifier typed, if unknown)
+    pub account: Option<String>,
+    /// Client address
+    pub ip: Option<String>,
+}
+
+/// Generic status response
+#[derive(Serialize)]
+struct StatusResponse {
+    status: String,
+    message: String,
+}
+
+/// POST /api/auth/password/forgot
+pub async fn forgot_password(
+    body: web::Json<ForgotPasswordRequest>,
+    resets: web::Data<PasswordResetService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(e) = body.validate() {
+        warn!("Forgot password rejected: {:?}", e);
+        return HttpResponse::BadRequest().body("Invalid email");
+    }
+
+    if let Err(e) = resets.request_reset(db.get_ref(), &body.email).await {
+        // Log only: the response must not reveal whether the account exists
+        warn!("Forgot password failed internally: {}", e);
+    }
+
+    HttpResponse::Accepted().json(StatusResponse {
+        status: "accepted".to_string(),
+        message: "If the account exists, a reset link was sent".to_string(),
+    })
+}
+
+/// POST /api/auth/password/reset
+pub async fn reset_password(
+    body: web::Json<ResetPasswordRequest>,
+    resets: web::Data<PasswordResetService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(e) = body.validate() {
+        warn!("Reset password rejected: {:?}", e);
+        return HttpResponse::BadRequest().body(e.to_string());
+    }
+
+    match resets
+        .reset_password(db.get_ref(), &body.token, &body.new_password)
+        .await
+    {
+        Ok(()) => {
+            info!("Reset password succeeded");
+            HttpResponse::Ok().json(StatusResponse {
+                status: "success".to_string(),
+                message: "Password updated".to_string(),
+            })
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+/// GET /api/auth/verify-email?token=
+pub async fn verify_email(
+    query: web::Query<VerifyEmailQuery>,
+    verification: web::Data<EmailVerificationService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    match verification.verify(db.get_ref(), &query.token).await {
+        Ok(_) => HttpResponse::Ok().json(StatusResponse {
+            status: "success".to_string(),
+            message: "Email verified".to_string(),
+        }),
+        Err(e) => {
+            warn!("Verify email rejected: {}", e);
+            e.error_response()
+        }
+    }
+}
+
+/// POST /api/auth/verify-email/resend
+pub async fn resend_verification(
+    body: web::Json<ForgotPasswordRequest>,
+    verification: web::Data<EmailVerificationService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(e) = body.validate() {
+        warn!("Resend verification rejected: {:?}", e);
+        return HttpResponse::BadRequest().body("Invalid email");
+    }
+
+    if let Err(e) = verification.resend(db.get_ref(), &body.email).await {
+        // Log only: the response must not reveal whether the account exists
+        warn!("Resend verification failed internally: {}", e);
+    }
+
+    HttpResponse::Accepted().json(StatusResponse {
+        status: "accepted".to_string(),
+        message: "If the account exists and is unverified, a new link was sent".to_string(),
+    })
+}
+
+/// POST /api/auth/lockouts/unlock
+pub async fn unlock_login(
+    auth: AuthenticatedUser,
+    body: web::Json<UnlockLoginRequest>,
+    throttle: web::Data<LoginThrottleService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    let (kind, value) = match (&body.account, &body.ip) {
+        (Some(account), None) if !account.trim().is_empty() => (
+            ThrottleKind::Account,
+            LoginThrottleService::resolve_account(db.get_ref(), account).await,
+        ),
+        (None, Some(ip)) if !ip.trim().is_empty() => (ThrottleKind::Ip, ip.clone()),
+        _ => return HttpResponse::BadRequest().body("exactly one of account or ip is required"),
+    };
+
+    match throttle.unlock(kind, &value, &auth.user.username).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound().finish(),
+        Err(e) => e.error_response(),
+    }
+}
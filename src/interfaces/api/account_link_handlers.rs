@@ -0,0 +1,272 @@
+//! Account Link Handlers Module
+//! Implements linking several wallets and external identities to one account,
+//! and merging duplicate accounts.
+//!
+//! Endpoints
+//! - GET    /api/me/credentials                         (Bearer JWT)
+//!   200 OK JSON: { "password": true,
+//!   "wallets": [{ "address": "0x..", "verified_at": "..", "linked_at": ".." }],
+//!   "identities": [{ "provider": "google", "subject": "..", "email": "..",
+//!   "linked_at": ".." }] }
+//!
+//! - POST   /api/me/wallets/challenge                   (Bearer JWT)
+//!   Request JSON: { "wallet": "0x..." }
+//!   200 OK JSON: { "wallet": "0x...", "nonce": "...", "message": "...",
+//!   "expires_in": 300 }; the wallet signs `message` with `personal_sign`.
+//!   400: `InvalidWallet`
+//!
+//! - POST   /api/me/wallets                             (Bearer JWT)
+//!   Request JSON: { "wallet": "0x...", "nonce": "...", "signature": "0x<65 bytes>" }
+//!   200 OK JSON: same shape as GET /api/me/credentials
+//!   400: `InvalidWallet` | `InvalidOrExpiredToken`, 409: `WalletAlreadyLinked`
+//!
+//! - DELETE /api/me/wallets/{address}                   (Bearer JWT)
+//!   200 OK JSON: same shape as GET /api/me/credentials
+//!   404: `CredentialNotFound`, 400: `LastLoginMethod`
+//!
+//! - POST   /api/me/identities/{provider}/authorize     (Bearer JWT)
+//!   200 OK JSON: { "authorization_url": "https://..." } and the HttpOnly state
+//!   cookie; the usual `/api/auth/oidc/{provider}/callback`, made by the same
+//!   browser, then links the identity to the caller.
+//!   409 on the callback: `AccountLinkConflict` (identity owned by another account)
+//!
+//! - DELETE /api/me/identities/{provider}/{subject}     (Bearer JWT)
+//!   200 OK JSON: same shape as GET /api/me/credentials
+//!   404: `CredentialNotFound`, 400: `LastLoginMethod`
+//!
+//! - POST   /api/me/merge                               (Bearer JWT)
+//!   Request JSON: { "source_token": "<JWT of the duplicate account>" }
+//!   200 OK JSON: same shape as GET /api/me/credentials; the duplicate is deleted.
+//!   401: `InvalidOrExpiredToken`, 400: `InvalidMerge`
+//!
+//! - POST   /api/users/{id}/merge                       (admin:all)
+//!   Request JSON: { "source_user_id": "<uuid>" }
+//!   204 No Content; 404: `UserNotFound`, 400: `InvalidMerge`
+
+use actix_web::{web, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+
+use crate::application::services::account_link_service::AccountLinkService;
+use crate::application::services::account_merge_service::AccountMergeService;
+use crate::application::services::oidc_service::OidcService;
+use crate::error::AuthError;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::interfaces::api::user_handlers::oidc_state_cookie;
+use crate::models::entities::identity::ExternalIdentity;
+use crate::models::entities::role::Permission;
+use crate::models::entities::user::User;
+use crate::models::entities::wallet::LinkedWallet;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Request payload for a wallet challenge
+#[derive(Deserialize)]
+pub struct WalletChallengeRequest {
+    pub wallet: String,
+}
+
+/// Request payload for linking a wallet
+#[derive(Deserialize)]
+pub struct LinkWalletRequest {
+    pub wallet: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+/// Request payload for a self-service merge
+#[derive(Deserialize)]
+pub struct SelfMergeRequest {
+    pub source_token: String,
+}
+
+/// Request payload for an admin merge
+#[derive(Deserialize)]
+pub struct AdminMergeRequest {
+    pub source_user_id: String,
+}
+
+/// The ways the caller can sign in
+#[derive(Serialize)]
+struct CredentialsResponse {
+    password: bool,
+    wallets: Vec<LinkedWallet>,
+    identities: Vec<ExternalIdentity>,
+}
+
+impl From<&User> for CredentialsResponse {
+    fn from(user: &User) -> Self {
+        // Accounts from the wallet login only have the primary address
+        let wallets = user
+            .wallet_addresses()
+            .into_iter()
+            .map(|address| {
+                user.wallets
+                    .iter()
+                    .find(|w| w.address == address)
+                    .cloned()
+                    .unwrap_or_else(|| LinkedWallet::unverified(&address))
+            })
+            .collect();
+        CredentialsResponse {
+            password: user.password.is_some(),
+            wallets,
+            identities: user.identities.clone(),
+        }
+    }
+}
+
+/// Response payload with the provider URL to send the browser to
+#[derive(Serialize)]
+struct AuthorizationUrlResponse {
+    authorization_url: String,
+}
+
+/// GET /api/me/credentials
+pub async fn get_credentials(auth: AuthenticatedUser) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    HttpResponse::Ok().json(CredentialsResponse::from(&auth.user))
+}
+
+/// POST /api/me/wallets/challenge
+pub async fn wallet_challenge(
+    auth: AuthenticatedUser,
+    body: web::Json<WalletChallengeRequest>,
+    links: web::Data<AccountLinkService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match links.wallet_challenge(&auth.user, &body.wallet).await {
+        Ok(challenge) => HttpResponse::Ok().json(challenge),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/me/wallets
+pub async fn link_wallet(
+    auth: AuthenticatedUser,
+    body: web::Json<LinkWalletRequest>,
+    links: web::Data<AccountLinkService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    credentials_result(
+        links
+            .link_wallet(
+                db.get_ref(),
+                &auth.user,
+                &body.wallet,
+                &body.nonce,
+                &body.signature,
+            )
+            .await,
+    )
+}
+
+/// DELETE /api/me/wallets/{address}
+pub async fn unlink_wallet(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    links: web::Data<AccountLinkService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    credentials_result(
+        links
+            .unlink_wallet(db.get_ref(), &auth.user, &path.into_inner())
+            .await,
+    )
+}
+
+/// POST /api/me/identities/{provider}/authorize
+pub async fn link_identity_authorize(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    oidc: web::Data<OidcService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match oidc.start_link(&path.into_inner(), &auth.user).await {
+        Ok(authorization) => HttpResponse::Ok()
+            .cookie(oidc_state_cookie(&authorization.state, &oidc))
+            .json(AuthorizationUrlResponse {
+                authorization_url: authorization.url,
+            }),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// DELETE /api/me/identities/{provider}/{subject}
+pub async fn unlink_identity(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    links: web::Data<AccountLinkService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    let (provider, subject) = path.into_inner();
+    credentials_result(
+        links
+            .unlink_identity(db.get_ref(), &auth.user, &provider, &subject)
+            .await,
+    )
+}
+
+/// POST /api/me/merge
+pub async fn merge_into_me(
+    auth: AuthenticatedUser,
+    body: web::Json<SelfMergeRequest>,
+    merges: web::Data<AccountMergeService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    let source = match merges
+        .source_from_token(db.get_ref(), &body.source_token)
+        .await
+    {
+        Ok(source) => source,
+        Err(e) => return e.error_response(),
+    };
+    credentials_result(merges.merge(&source, &auth.user, &auth.user).await)
+}
+
+/// POST /api/users/{id}/merge
+pub async fn merge_users(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<AdminMergeRequest>,
+    merges: web::Data<AccountMergeService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    let target = <Database as UserDataTrait>::find_user_by_id(&db, &path.into_inner()).await;
+    let source = <Database as UserDataTrait>::find_user_by_id(&db, &body.source_user_id).await;
+    let (source, target) = match (source, target) {
+        (Some(source), Some(target)) => (source, target),
+        _ => return AuthError::UserNotFound.error_response(),
+    };
+    match merges.merge(&source, &target, &auth.user).await {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+fn credentials_result(result: Result<User, AuthError>) -> HttpResponse {
+    match result {
+        Ok(user) => HttpResponse::Ok().json(CredentialsResponse::from(&user)),
+        Err(e) => e.error_response(),
+    }
+}
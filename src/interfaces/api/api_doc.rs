@@ -9,26 +9,188 @@ pub fn print_routes() {
 
     print_endpoint(
         "GET",
-        "/api/tasks",
-        "Retrieve all tasks",
+        "/api/tasks?status=&assignee=&due_before=&q=&sort=&cursor=&limit=",
+        "List visible tasks (auth; sort: created_at|updated_at|due_date|priority, - for desc; assignee=me)",
         None,
-        Some(r#"[{"uuid": "string", "task_name": "string"}]"#),
+        Some(r#"{"items": [{"uuid": "string", "task_name": "string", "status": "todo", "priority": "medium", "...": "..."}], "next_cursor": "string|null"}"#),
     );
 
     print_endpoint(
         "POST",
         "/api/tasks",
-        "Create a new task",
-        Some(r#"{"task_name": "string"}"#),
-        Some(r#"{"uuid": "string", "task_name": "string"}"#),
+        "Create a new task (auth)",
+        Some(
+            r#"{"task_name": "string", "description": "string", "status": "todo", "priority": "medium", "assignee": "<uuid>", "conversation_id": "<uuid>", "due_date": "RFC3339", "parent": "<task uuid>", "recurrence": {"freq": "daily|weekly|monthly", "interval": 1, "count": 10, "until": "RFC3339"}, "reminders": [1440, 30]}"#,
+        ),
+        Some(r#"{"uuid": "string", "task_name": "string", "description": "string", "status": "todo|in_progress|done", "priority": "low|medium|high|urgent", "assignee": "<uuid>", "creator": "<uuid>", "conversation_id": "<uuid>", "due_date": "RFC3339", "created_at": "RFC3339", "updated_at": "RFC3339", "completed_at": "RFC3339"}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/tasks/calendar.ics?token=...&type=event|todo",
+        "iCalendar feed of the feed token owner's tasks with a due date (no login; 401 InvalidFeedToken)",
+        None,
+        Some("BEGIN:VCALENDAR ... BEGIN:VEVENT UID:<uuid>@chasqui SUMMARY:... DTSTART:20261102T090000Z ... END:VCALENDAR"),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/calendar/token",
+        "Create the caller's calendar feed token, replacing the previous one (DELETE revokes it; no API keys)",
+        None,
+        Some(r#"{"token": "...", "url": "/api/tasks/calendar.ics?token=..."}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/import",
+        "Create tasks from an .ics upload (multipart/form-data, `file` field, at most 1 MiB)",
+        Some("file=@tasks.ics"),
+        Some(r#"{"created": [{"uuid": "...", "task_name": "...", "...": "..."}], "skipped": [{"index": 2, "summary": "...", "reason": "..."}]}"#),
     );
 
     print_endpoint(
         "PATCH",
         "/api/tasks/{uuid}",
-        "Update task status",
+        "Update a task (creator, assignee or task:update; null clears; ?force=true ignores open blockers; completing a recurring task creates the next occurrence)",
+        Some(r#"{"status": "done", "assignee": null, "due_date": "RFC3339", "parent": "<task uuid>", "recurrence": null, "reminders": [60]}"#),
+        Some(r#"{"uuid": "string", "task_name": "string", "description": "string", "status": "todo|in_progress|done", "priority": "low|medium|high|urgent", "assignee": "<uuid>", "creator": "<uuid>", "conversation_id": "<uuid>", "due_date": "RFC3339", "created_at": "RFC3339", "updated_at": "RFC3339", "completed_at": "RFC3339"}"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/tasks/{uuid}",
+        "Delete a task (creator or task:delete; 204 No Content)",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/tasks/{uuid}",
+        "Task with parent, subtasks and blocking relations",
+        None,
+        Some(r#"{"uuid": "...", "task_name": "...", "checklist": [{"id": "...", "text": "...", "done": false}], "progress": {"done": 1, "total": 4, "percent": 25}, "parent": {"uuid": "...", "task_name": "...", "status": "todo"}, "subtasks": [...], "depends_on": [...], "blocks": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/{uuid}/checklist",
+        "Add a checklist item (appended unless position is given)",
+        Some(r#"{"text": "string", "position": 0}"#),
+        Some(r#"{"uuid": "...", "checklist": [{"id": "...", "text": "...", "done": false}], "progress": {...}}"#),
+    );
+
+    print_endpoint(
+        "PATCH",
+        "/api/tasks/{uuid}/checklist/{item_id}",
+        "Edit, check or move a checklist item (DELETE removes it)",
+        Some(r#"{"text": "string", "done": true, "position": 2}"#),
+        Some(r#"{"uuid": "...", "checklist": [...], "progress": {...}}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/{uuid}/dependencies",
+        "Make the task wait for another (409 DependencyCycle; DELETE .../{blocker} removes it)",
+        Some(r#"{"depends_on": "<task uuid>"}"#),
+        Some(r#"{"uuid": "...", "depends_on": [{"uuid": "...", "task_name": "...", "status": "todo"}], "...": "..."}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/tasks/{uuid}/activity",
+        "Task timeline: comments and recorded changes (status, assignee, due date), oldest first",
+        None,
+        Some(r#"[{"type": "activity", "kind": "status_changed", "from": "todo", "to": "done", "actor_name": "..."}, {"type": "comment", "text": "..."}]"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/{uuid}/comments",
+        "Comment on a task; @username mentions users who can see it (they start watching)",
+        Some(r#"{"text": "@bob can you review?"}"#),
+        Some(r#"{"uuid": "...", "task_id": "...", "author": "...", "text": "...", "mentions": ["<user uuid>"]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/tasks/{uuid}/watch",
+        "Watch a task's timeline over the WebSocket (DELETE stops watching)",
+        None,
+        Some(r#"{"uuid": "...", "watchers": ["<user uuid>"], "...": "..."}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/notifications",
+        "Notifications kept while the caller was offline, newest first (?unread=true)",
+        None,
+        Some(r#"[{"uuid": "...", "kind": "task_reminder", "task_id": "...", "task_name": "...", "due_date": "RFC3339", "minutes_before": 30, "created_at": "RFC3339"}]"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/notifications/{id}/read",
+        "Mark one of the caller's notifications read",
         None,
-        Some(r#"{"uuid": "string", "task_name": "string"}"#),
+        Some(r#"{"uuid": "...", "kind": "task_reminder", "...": "...", "read_at": "RFC3339"}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/projects",
+        "Create a project owned by the caller (GET lists the caller's projects)",
+        Some(r#"{"name": "Launch", "members": ["<user uuid>"]}"#),
+        Some(r#"{"uuid": "...", "name": "Launch", "owner": "...", "members": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/projects/{id}/members",
+        "Add a project member (owner only)",
+        Some(r#"{"user_id": "<user uuid>"}"#),
+        Some(r#"{"uuid": "...", "members": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/projects/{id}/boards",
+        "Create a board; default columns To do / In progress / Done (GET lists boards)",
+        Some(r#"{"name": "Sprint 1", "columns": [{"name": "Review", "status": "in_progress"}]}"#),
+        Some(r#"{"uuid": "...", "columns": [{"id": "...", "name": "...", "status": "todo"}], "version": 0}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/boards/{id}",
+        "Board with its columns and their cards in order",
+        None,
+        Some(r#"{"uuid": "...", "version": 3, "columns": [{"id": "...", "status": "todo", "cards": [{"id": "...", "position": "i", "task": {...}}]}]}"#),
+    );
+
+    print_endpoint(
+        "PUT",
+        "/api/boards/{id}/columns",
+        "Replace the columns; `id` keeps a column (409 ColumnNotEmpty, VersionConflict)",
+        Some(r#"{"columns": [{"id": "...", "name": "To do", "status": "todo"}], "version": 3}"#),
+        Some(r#"{"uuid": "...", "columns": [...], "version": 4}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/boards/{id}/cards",
+        "Put a task on the board; the column status becomes the task status",
+        Some(r#"{"task_id": "<task uuid>", "column_id": "...", "after": "<card id>", "version": 4}"#),
+        Some(r#"{"board_id": "...", "version": 5, "card": {"id": "...", "task_id": "...", "column_id": "...", "position": "i"}}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/boards/{id}/cards/{card_id}/move",
+        "Move a card after / before another, optionally to another column (DELETE .../{card_id} removes it)",
+        Some(r#"{"column_id": "...", "before": "<card id>", "version": 5}"#),
+        Some(r#"{"board_id": "...", "version": 6, "card": {...}}"#),
     );
 
     print_endpoint(
@@ -41,38 +203,392 @@ pub fn print_routes() {
         Some(r#"{"create": "success", "message": "User created successfully"}"#),
     );
 
+    print_endpoint(
+        "POST",
+        "/api/auth/wallet/challenge",
+        "Get a message to sign for a wallet login (then POST /api/login with the signature)",
+        Some(r#"{"wallet": "0x..."}"#),
+        Some(r#"{"wallet": "0x...", "nonce": "...", "message": "Sign in to Chasqui with wallet 0x...", "expires_in": 300}"#),
+    );
+
     print_endpoint(
         "POST",
         "/api/login",
-        "Authenticate and get JWT token",
+        "Authenticate and get JWT token (429 + Retry-After when throttled)",
         Some(
-            r#"{"email": "...", "password": "..."} OR {"username": "...", "password": "..."} OR {"wallet": "0x..."}"#,
+            r#"{"email": "...", "password": "..."} OR {"username": "...", "password": "..."} OR {"wallet": "0x...", "nonce": "...", "signature": "0x..."}"#,
         ),
+        Some(r#"{"token": "<JWT_STRING>"} OR {"mfa_required": true, "mfa_token": "...", "expires_in": 300}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/password/forgot",
+        "E-mail a single-use password reset link",
+        Some(r#"{"email": "alice@example.com"}"#),
+        Some(r#"{"status": "accepted", "message": "If the account exists, a reset link was sent"}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/password/reset",
+        "Set a new password with the e-mailed token (revokes sessions)",
+        Some(r#"{"token": "<from e-mail>", "new_password": "..."}"#),
+        Some(r#"{"status": "success", "message": "Password updated"}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/auth/verify-email?token=<token>",
+        "Confirm the e-mail address from the verification link",
+        None,
+        Some(r#"{"status": "success", "message": "Email verified"}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/verify-email/resend",
+        "Re-send the verification link (throttled)",
+        Some(r#"{"email": "alice@example.com"}"#),
+        Some(r#"{"status": "accepted", "message": "..."}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/lockouts/unlock",
+        "Lift a login lockout (admin:all, audited)",
+        Some(r#"{"account": "alice@example.com"} OR {"ip": "203.0.113.7"}"#),
+        None,
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/me",
+        "Get your own account and profile",
+        None,
+        Some(r#"{"id": "uuid", "username": "alice", "email": "alice@example.com", "email_verified": true, "roles": ["user"], "display_name": "Alice", "locale": "es-PE", "timezone": "America/Lima"}"#),
+    );
+
+    print_endpoint(
+        "PATCH",
+        "/api/me",
+        "Update your profile (all fields optional; \"\" clears a field; no API keys)",
+        Some(r#"{"display_name": "Alice", "avatar_url": "https://...", "bio": "...", "locale": "es-PE", "timezone": "America/Lima"}"#),
+        Some(r#"{"id": "uuid", "username": "alice", "display_name": "Alice", ...}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/email",
+        "Change your e-mail (current password required; the new address must be verified)",
+        Some(r#"{"current_password": "...", "email": "new@example.com"}"#),
+        Some(r#"{"id": "uuid", "email": "new@example.com", "email_verified": false, ...}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/password",
+        "Change your password (current password required; older tokens are revoked)",
+        Some(r#"{"current_password": "...", "new_password": "N3w$ecret!"}"#),
+        Some(r#"{"token": "<new JWT>"}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/deactivate",
+        "Deactivate your account (signing in again reactivates it)",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/me",
+        "Delete your account after the grace period (signing in cancels it)",
+        None,
+        Some(r#"{"deletion_scheduled_at": "2026-11-18T10:00:00Z"}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/me/export",
+        "Download your data (ZIP with profile.json, conversations.json, messages.json)",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/me/credentials",
+        "List your password, linked wallets and external identities",
+        None,
+        Some(r#"{"password": true, "wallets": [{"address": "0x...", "verified_at": "...", "linked_at": "..."}], "identities": [{"provider": "google", "subject": "...", "linked_at": "..."}]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/wallets/challenge",
+        "Get a message to sign with the wallet you want to link",
+        Some(r#"{"wallet": "0x..."}"#),
+        Some(r#"{"wallet": "0x...", "nonce": "...", "message": "...", "expires_in": 300}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/wallets",
+        "Link a wallet with the personal_sign signature of the challenge",
+        Some(r#"{"wallet": "0x...", "nonce": "...", "signature": "0x..."}"#),
+        Some(r#"{"password": true, "wallets": [...], "identities": [...]}"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/me/wallets/{address}",
+        "Unlink a wallet (not the last way to sign in)",
+        None,
+        Some(r#"{"password": true, "wallets": [...], "identities": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/identities/{provider}/authorize",
+        "Start linking an external identity; the OIDC callback attaches it to you",
+        None,
+        Some(r#"{"authorization_url": "https://..."}"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/me/identities/{provider}/{subject}",
+        "Unlink an external identity (not the last way to sign in)",
+        None,
+        Some(r#"{"password": true, "wallets": [...], "identities": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/me/merge",
+        "Merge a duplicate account you can sign in to into yours",
+        Some(r#"{"source_token": "<JWT of the duplicate account>"}"#),
+        Some(r#"{"password": true, "wallets": [...], "identities": [...]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/users/{id}/merge",
+        "Merge another account into this one (admin:all)",
+        Some(r#"{"source_user_id": "uuid"}"#),
+        None,
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/users/{id}",
+        "Get another user's public profile",
+        None,
+        Some(r#"{"id": "uuid", "username": "alice", "display_name": "Alice", "avatar_url": "https://...", "bio": "...", "bot": false}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/bots",
+        "Create a bot account (admin:all); bots act only through API keys",
+        Some(r#"{"username": "ci-bot"}"#),
+        Some(r#"{"id": "uuid", "username": "ci-bot", "bot": true}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/api-keys",
+        "Create a scoped API key for yourself or (admin) a bot; the key is shown once",
+        Some(
+            r#"{"name": "deploys", "scopes": [{"permission": "message:create", "conversation_id": "conversation:uuid"}], "expires_in_days": 90, "user_id": "<bot uuid, optional>"}"#,
+        ),
+        Some(r#"{"key": "chq_<prefix>_<secret>", "api_key": {"id": "uuid", "prefix": "chq_1a2b3c4d", "scopes": [...]}}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/api-keys",
+        "List your API keys (query: user_id of a bot, admin only)",
+        None,
+        Some(r#"[{"id": "uuid", "name": "deploys", "prefix": "chq_1a2b3c4d", "last_used_at": "..."}]"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/api-keys/{id}",
+        "Revoke an API key (own keys, or any as admin)",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/mfa/totp/enroll",
+        "Start TOTP enrollment (Bearer JWT)",
+        None,
+        Some(r#"{"secret": "<base32>", "otpauth_uri": "otpauth://totp/..."}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/mfa/totp/confirm",
+        "Confirm TOTP with a first code; returns recovery codes once",
+        Some(r#"{"code": "123456"}"#),
+        Some(r#"{"recovery_codes": ["a1b2c-3d4e5", "..."]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/mfa/totp/disable",
+        "Disable TOTP with a current or recovery code (204)",
+        Some(r#"{"code": "123456"}"#),
+        None,
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/auth/mfa/verify",
+        "Exchange the login mfa_token and a code for a JWT",
+        Some(r#"{"mfa_token": "...", "code": "123456"}"#),
         Some(r#"{"token": "<JWT_STRING>"}"#),
     );
 
+    print_endpoint(
+        "GET",
+        "/api/auth/oidc/providers",
+        "List configured external login providers",
+        None,
+        Some(r#"["github", "google"]"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/auth/oidc/{provider}/authorize",
+        "Redirect (302) to the provider with PKCE",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/auth/oidc/{provider}/callback?code=..&state=..",
+        "Finish external login (find, link by verified e-mail or create)",
+        None,
+        Some(r#"{"token": "<JWT_STRING>"} OR {"mfa_required": true, "mfa_token": "..."}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/.well-known/jwks.json",
+        "Public keys (JWKS) that verify issued JWTs; empty with HS256",
+        None,
+        Some(r#"{"keys": [{"kty": "RSA", "kid": "...", "alg": "RS256", "n": "...", "e": "AQAB"}]}"#),
+    );
+
     print_endpoint(
         "GET",
         "/api/users",
-        "Retrieve all users",
+        "Search users (query: q prefix, wallet, limit, cursor); e-mails only for admins, blocked users excluded",
+        None,
+        Some(r#"{"items": [{"id": "uuid", "username": "alice", "display_name": "Alice", "wallet": null, "bot": false}], "next_cursor": "..."}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/users/{id}/block",
+        "Block an account and revoke its tokens (admin:all); DELETE unblocks",
+        None,
         None,
-        Some(r#"[{"username": "...", "email": "...", "wallet": "0x..."}]"#),
     );
 
     print_endpoint(
         "DELETE",
         "/api/users/wallets",
-        "Delete all users with wallets",
+        "Delete all wallet-only users; accounts with another login method are kept (admin:all)",
         None,
         Some(r#"{"create": "success", "message": "..."}"#),
     );
 
+    print_endpoint(
+        "GET",
+        "/api/permissions",
+        "List every permission string (admin:all)",
+        None,
+        Some(r#"["admin:all", "workspace:create", "message:delete", "..."]"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/roles",
+        "List roles (admin:all)",
+        None,
+        Some(r#"[{"id": "role:uuid", "name": "moderator", "description": "...", "permissions": ["message:delete"]}]"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/roles",
+        "Create a custom role (admin:all)",
+        Some(r#"{"name": "support", "description": "...", "permissions": ["message:delete"]}"#),
+        Some(r#"{"id": "role:uuid", "name": "support", "description": "...", "permissions": ["message:delete"]}"#),
+    );
+
+    print_endpoint(
+        "GET",
+        "/api/roles/{name}",
+        "Get a role (admin:all)",
+        None,
+        Some(r#"{"id": "role:uuid", "name": "support", "description": "...", "permissions": ["..."]}"#),
+    );
+
+    print_endpoint(
+        "PATCH",
+        "/api/roles/{name}",
+        "Update role description/permissions (admin:all)",
+        Some(r#"{"description": "...", "permissions": ["message:pin"]}"#),
+        Some(r#"{"id": "role:uuid", "name": "support", "description": "...", "permissions": ["message:pin"]}"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/roles/{name}",
+        "Delete a custom role and revoke it from users (admin:all)",
+        None,
+        None,
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/users/{id}/roles",
+        "Assign a role to a user (admin:all)",
+        Some(r#"{"role": "support"}"#),
+        Some(r#"{"user_id": "uuid", "roles": ["user", "support"], "permissions": ["..."]}"#),
+    );
+
+    print_endpoint(
+        "DELETE",
+        "/api/users/{id}/roles/{name}",
+        "Revoke a role from a user (admin:all)",
+        None,
+        Some(r#"{"user_id": "uuid", "roles": ["user"], "permissions": ["..."]}"#),
+    );
+
+    print_endpoint(
+        "POST",
+        "/api/ws/ticket",
+        "Issue a single-use WebSocket ticket (Bearer JWT, valid ~30s)",
+        None,
+        Some(r#"{"ticket": "<ticket>", "expires_in": 30}"#),
+    );
+
     print_endpoint(
         "GET",
         "/api/ws/chat",
-        "WebSocket chat connection (requires token in query)",
+        "WebSocket chat connection (requires a ticket in query)",
         None,
-        Some(r#"ws://localhost:8080/api/ws/chat?token=<JWT>"#),
+        Some(r#"ws://localhost:8080/api/ws/chat?ticket=<ticket>"#),
     );
 
     print_endpoint(
@@ -103,10 +619,18 @@ pub fn print_routes() {
         "Get message history (query: limit, offset)",
         None,
         Some(
-            r#"[{"id": "msg:uuid", "content": "...", "sender_id": "user:uuid", "conversation_id": "conversation:uuid", "created_at": "..."}]"#,
+            r#"[{"id": "msg:uuid", "content": "...", "sender_id": "user:uuid", "conversation_id": "conversation:uuid", "created_at": "...", "task_id": "<uuid>", "task": {"uuid": "...", "task_name": "...", "status": "todo"}}]"#,
         ),
     );
 
+    print_endpoint(
+        "POST",
+        "/api/messages/{id}/task",
+        "Create a task from a message (name defaults to its first line; 409 if already linked)",
+        Some(r#"{"task_name": "string", "description": "string", "priority": "high", "assignee": "<uuid>", "due_date": "RFC3339"} (all optional)"#),
+        Some(r#"{"task": {"uuid": "...", "source_message_id": "<uuid>", "conversation_id": "<uuid>", "...": "..."}, "message": {"message_type": "System", "content": "alice created task \"...\"", "task_id": "<uuid>", "...": "..."}}"#),
+    );
+
     print_endpoint(
         "POST",
         "/api/conversations/{id}/participants",
@@ -117,6 +641,8 @@ pub fn print_routes() {
 
     println!("\n💡 Tips:");
     println!("- Use Bearer token in 'Authorization' header for protected routes");
+    println!("- API keys ('chq_...') are accepted as Bearer tokens, limited to their scopes");
+    println!("- JWT claims carry persisted 'roles', compact 'perms' and 'ver'; role changes revoke older tokens");
     println!("- Conversation IDs format: 'conversation:uuid'");
     println!("- User IDs format: 'user:uuid'");
     println!("- Message IDs format: 'msg:uuid'");
@@ -126,8 +652,10 @@ pub fn print_routes() {
 /// Prints documentation for WebSocket messages and events.
 pub fn print_ws_docs() {
     println!("\n🌐 Chasqui Server - WebSocket Documentation\n");
-    println!("Connection URL: /api/ws/chat?token=<JWT>");
-    println!("Note: The 'token' query parameter is required for the initial handshake.\n");
+    println!("Connection URL: /api/ws/chat?ticket=<ticket>");
+    println!("Note: Get the single-use ticket from POST /api/ws/ticket right before connecting.");
+    println!("      '?token=<JWT>' is rejected unless WS_ALLOW_QUERY_TOKEN=true.");
+    println!("      Bots connect with 'Authorization: Bearer chq_...' and are limited to the key scopes.\n");
 
     println!("--- CLIENT -> SERVER MESSAGES ---");
     println!("Sent by the client to the server.\n");
@@ -144,13 +672,67 @@ pub fn print_ws_docs() {
         r#"{"type": "message", "conversation_id": "conversation:uuid", "content": "Hello!"}"#,
     );
 
+    print_ws_message(
+        "/task",
+        "A message starting with '/task <title>' also creates a task linked to it",
+        r#"{"type": "message", "conversation_id": "conversation:uuid", "content": "/task Fix the login page"}"#,
+    );
+
     println!("\n--- SERVER -> CLIENT MESSAGES ---");
     println!("Sent by the server to one or more clients.\n");
 
     print_ws_message(
         "NewMessage",
         "Broadcast when a new message is saved",
-        r#"{"type": "NewMessage", "message": {"id": "msg:uuid", "content": "...", "sender_id": "user:uuid", "created_at": "..."}}"#,
+        r#"{"type": "NewMessage", "sender": {"id": "user:uuid", "bot": false}, "message": {"id": "msg:uuid", "content": "...", "sender_id": "user:uuid", "created_at": "..."}}"#,
+    );
+
+    print_ws_message(
+        "MessageTaskLinked",
+        "Broadcast to the room when a message is turned into a task",
+        r#"{"type": "MessageTaskLinked", "message_id": "message:uuid", "task": {"uuid": "...", "task_name": "...", "status": "todo"}}"#,
+    );
+
+    print_ws_message(
+        "TaskCreated",
+        "Sent to the creator, assignee and conversation members of a new task",
+        r#"{"type": "TaskCreated", "task": {"uuid": "...", "task_name": "...", "status": "todo", "...": "..."}}"#,
+    );
+
+    print_ws_message(
+        "TaskUpdated",
+        "Sent to everyone who can (or could, before the change) see the task",
+        r#"{"type": "TaskUpdated", "task": {"uuid": "...", "status": "done", "...": "..."}}"#,
+    );
+
+    print_ws_message(
+        "TaskDeleted",
+        "Sent to everyone who could see the deleted task",
+        r#"{"type": "TaskDeleted", "task": {"uuid": "...", "...": "..."}}"#,
+    );
+
+    print_ws_message(
+        "TaskTimeline",
+        "Sent to the watchers of a task for each new comment or recorded change",
+        r#"{"type": "TaskTimeline", "task_id": "...", "entry": {"type": "comment", "author_name": "...", "text": "..."}}"#,
+    );
+
+    print_ws_message(
+        "TaskReminder",
+        "Sent to the assignee (or creator) of a task when a reminder before its due date is reached",
+        r#"{"type": "TaskReminder", "task": {"uuid": "...", "task_name": "...", "due_date": "RFC3339"}, "minutes_before": 30}"#,
+    );
+
+    print_ws_message(
+        "CardAdded",
+        "Sent to the project members when a card is added (CardMoved, CardRemoved alike)",
+        r#"{"type": "CardMoved", "board_id": "...", "version": 6, "card": {"id": "...", "column_id": "...", "position": "i"}}"#,
+    );
+
+    print_ws_message(
+        "ColumnsChanged",
+        "Sent to the project members when the columns of a board change",
+        r#"{"type": "ColumnsChanged", "board_id": "...", "version": 4, "columns": [{"id": "...", "name": "...", "status": "todo"}]}"#,
     );
 
     print_ws_message(
@@ -0,0 +1,256 @@
+//! API Key Handlers Module
+//! Implements bot accounts and personal API keys.
+//!
+//! Endpoints
+//! - POST   /api/bots                (admin:all)
+//!   Request JSON: { "username": "ci-bot" }
+//!   201 Created JSON: { "id": "<uuid>", "username": "ci-bot", "bot": true }
+//!   Bots start with the `user` role; assign a role holding the permissions their
+//!   keys need (e.g. `message:create`) with `POST /api/users/{id}/roles`.
+//!
+//! - POST   /api/api-keys            (Bearer JWT)
+//!   Request JSON: { "name": "deploys", "scopes": [{ "permission": "message:create",
+//!   "conversation_id": "conversation:<uuid>" }], "expires_in_days": 90,
+//!   "user_id": "<bot uuid>" }
+//!   `user_id` is optional (default: the caller) and admin-only; it must name a bot.
+//!   201 Created JSON: { "key": "chq_<prefix>_<secret>", "api_key": { ... } }
+//!   The key is shown only once.
+//!
+//! - GET    /api/api-keys[?user_id=<bot uuid>]  (Bearer JWT; `user_id` admin-only)
+//!   200 OK JSON: [{ "id": "<uuid>", "name": "...", "prefix": "chq_1a2b3c4d", ... }]
+//!
+//! - DELETE /api/api-keys/{id}       (Bearer JWT; own keys, or any key as admin)
+//!   204 No Content | 404: `ApiKeyNotFound`
+//!
+//! Keys are used as `Authorization: Bearer chq_...` on REST calls and on the
+//! `/api/ws/chat` handshake. They cannot manage keys, 2FA or WebSocket tickets.
+
+use actix_web::{web, HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::application::services::api_key_service::ApiKeyService;
+use crate::error::AuthError;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::api_key::{ApiKey, ApiKeyScope};
+use crate::models::entities::role::Permission;
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Request payload for creating a bot account
+#[derive(Deserialize, Validate)]
+pub struct CreateBotRequest {
+    #[validate(length(min = 3, max = 32, message = "username must be 3-32 characters"))]
+    pub username: String,
+}
+
+/// Request payload for creating an API key
+#[derive(Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 64, message = "name must be 1-64 characters"))]
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_in_days: Option<i64>,
+    /// Bot the key acts as (admin only); defaults to the caller
+    pub user_id: Option<String>,
+}
+
+/// Query for listing keys
+#[derive(Deserialize)]
+pub struct ListApiKeysQuery {
+    pub user_id: Option<String>,
+}
+
+/// Response payload describing a bot account
+#[derive(Serialize)]
+struct BotResponse {
+    id: String,
+    username: String,
+    bot: bool,
+}
+
+/// Public view of a key (never the digest)
+#[derive(Serialize)]
+struct ApiKeyResponse {
+    id: String,
+    user_id: String,
+    name: String,
+    prefix: String,
+    scopes: Vec<ApiKeyScope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_used_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revoked_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        ApiKeyResponse {
+            id: key.id.map(|id| id.id.to_raw()).unwrap_or_default(),
+            user_id: key.user_id.id.to_raw(),
+            name: key.name,
+            prefix: key.prefix,
+            scopes: key.scopes,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked_at: key.revoked_at,
+            created_at: key.created_at,
+        }
+    }
+}
+
+/// Response payload for a created key, with the one-time display of the secret
+#[derive(Serialize)]
+struct CreatedApiKeyResponse {
+    key: String,
+    api_key: ApiKeyResponse,
+}
+
+/// POST /api/bots
+pub async fn create_bot(
+    auth: AuthenticatedUser,
+    body: web::Json<CreateBotRequest>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return HttpResponse::BadRequest().body(e.to_string());
+    }
+    let username = body.username.trim().to_string();
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return HttpResponse::BadRequest()
+            .body("username may contain only letters, digits, '-' and '_'");
+    }
+    if <Database as UserDataTrait>::find_user_by_username(&db, &username)
+        .await
+        .is_some()
+    {
+        return HttpResponse::Conflict().body("username already taken");
+    }
+
+    match <Database as UserDataTrait>::add_user(&db, User::new_bot(username.clone())).await {
+        Some(bot) => {
+            info!("Bot {} created by {}", username, auth.user.username);
+            HttpResponse::Created().json(BotResponse {
+                id: bot.id_string().unwrap_or_default(),
+                username: bot.username,
+                bot: true,
+            })
+        }
+        None => {
+            error!("Failed to persist bot {}", username);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// POST /api/api-keys
+pub async fn create_api_key(
+    auth: AuthenticatedUser,
+    body: web::Json<CreateApiKeyRequest>,
+    api_keys: web::Data<ApiKeyService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return AuthError::InvalidApiKeyScope(e.to_string()).error_response();
+    }
+    let owner = match resolve_key_owner(&auth, &db, body.user_id.as_deref()).await {
+        Ok(owner) => owner,
+        Err(resp) => return resp,
+    };
+
+    let body = body.into_inner();
+    match api_keys
+        .create(
+            &owner,
+            &auth.user,
+            &body.name,
+            body.scopes,
+            body.expires_in_days,
+        )
+        .await
+    {
+        Ok((api_key, key)) => HttpResponse::Created().json(CreatedApiKeyResponse {
+            key,
+            api_key: api_key.into(),
+        }),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// GET /api/api-keys
+pub async fn list_api_keys(
+    auth: AuthenticatedUser,
+    query: web::Query<ListApiKeysQuery>,
+    api_keys: web::Data<ApiKeyService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    let owner = match resolve_key_owner(&auth, &db, query.user_id.as_deref()).await {
+        Ok(owner) => owner,
+        Err(resp) => return resp,
+    };
+    let owner_id = match owner.id {
+        Some(id) => id,
+        None => return AuthError::UserNotFound.error_response(),
+    };
+
+    match api_keys.list(owner_id).await {
+        Ok(keys) => HttpResponse::Ok().json(
+            keys.into_iter()
+                .map(ApiKeyResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// DELETE /api/api-keys/{id}
+pub async fn revoke_api_key(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    api_keys: web::Data<ApiKeyService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match api_keys.revoke(&path.into_inner(), &auth.user).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// The caller itself, or (admins only) the bot named by `user_id`.
+async fn resolve_key_owner(
+    auth: &AuthenticatedUser,
+    db: &Database,
+    user_id: Option<&str>,
+) -> Result<User, HttpResponse> {
+    let user_id = match user_id {
+        None => return Ok(auth.user.clone()),
+        Some(id) if Some(id) == auth.user.id_string().as_deref() => return Ok(auth.user.clone()),
+        Some(id) => id,
+    };
+    auth.require_permission(Permission::AdminAll)?;
+    match <Database as UserDataTrait>::find_user_by_id(db, user_id).await {
+        Some(user) if user.bot => Ok(user),
+        Some(_) => Err(HttpResponse::BadRequest().body("keys for other users must target a bot")),
+        None => Err(AuthError::UserNotFound.error_response()),
+    }
+}
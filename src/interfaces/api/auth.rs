@@ -0,0 +1,281 @@
+//! Authenticated request extractor.
+//!
+//! `AuthenticatedUser` validates the bearer token and loads the current `User`
+//! record, so handlers authorize against the persisted roles instead of trusting
+//! the claims alone.
+//!
+//! Checks:
+//! - Signature and expiration via `validate_token`.
+//! - The user still exists, is not blocked and is not deactivated.
+//! - `claims.ver` equals `user.token_version`; a role change bumps the version,
+//!   so tokens issued before a demotion stop being accepted.
+//!
+//! `check_account` holds the account checks so the WebSocket handshake, which
+//! cannot use the extractor, applies the same rules.
+//!
+//! A bearer value starting with `chq_` is an API key instead of a JWT: it is
+//! resolved through `ApiKeyService`, and the caller's permissions are the key
+//! scopes intersected with the user's current roles. The claims of such a
+//! caller are synthesized from the key (`perms` = scope permissions).
+//!
+//! Example:
+//! ```rust,ignore
+//! pub async fn handler(auth: AuthenticatedUser) -> HttpResponse {
+//!     if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+//!         return resp;
+//!     }
+//!     // ...
+//! }
+//! ```
+
+use actix_web::dev::Payload;
+use actix_web::error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized};
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use log::{debug, warn};
+use std::env;
+use std::net::SocketAddr;
+use surrealdb::sql::Thing;
+
+use crate::application::services::api_key_service::ApiKeyService;
+use crate::application::services::task_service::TaskAccess;
+use crate::infrastructure::auth::jwt::{validate_token, Claims};
+use crate::infrastructure::database::surrealdb::Database;
+use crate::models::entities::api_key::{is_api_key, ApiKey};
+use crate::models::entities::message::Message;
+use crate::models::entities::role::Permission;
+use crate::models::entities::task::Task;
+use crate::models::entities::user::User;
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// The authenticated caller of a request.
+pub struct AuthenticatedUser {
+    /// Current user record loaded from the database
+    pub user: User,
+    /// Validated claims of the presented token
+    pub claims: Claims,
+    /// The API key used instead of a JWT, if any
+    pub api_key: Option<ApiKey>,
+}
+
+impl AuthenticatedUser {
+    /// The caller of a request authenticated by `key`; the claims mirror the
+    /// key scopes.
+    pub fn from_api_key(user: User, key: ApiKey) -> Self {
+        let claims = Claims {
+            sub: key.user_id.id.to_raw(),
+            exp: key
+                .expires_at
+                .map(|exp| exp.timestamp() as usize)
+                .unwrap_or(usize::MAX),
+            iat: key.created_at.timestamp() as usize,
+            username: user.username.clone(),
+            roles: user.role_names(),
+            perms: key.permission_names(),
+            ver: user.token_version,
+        };
+        AuthenticatedUser {
+            user,
+            claims,
+            api_key: Some(key),
+        }
+    }
+
+    /// The user's SurrealDB Thing (`user:<uuid>`).
+    pub fn user_id(&self) -> Thing {
+        Thing::from(("user", self.claims.sub.as_str()))
+    }
+
+    /// Like `require_permission`, but answers a bool without logging a denial.
+    /// Used where a permission widens access instead of gating the endpoint.
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.user.has_permission(permission)
+            && self
+                .api_key
+                .as_ref()
+                .is_none_or(|key| key.allows(permission, None))
+    }
+
+    /// Access to other users' tasks granted by the caller's roles (and key scopes).
+    pub fn task_access(&self) -> TaskAccess {
+        TaskAccess {
+            read_all: self.has_permission(Permission::TaskRead),
+            update_all: self.has_permission(Permission::TaskUpdate),
+            delete_all: self.has_permission(Permission::TaskDelete),
+        }
+    }
+
+    /// Returns `Err(403 Forbidden)` unless the persisted roles grant `permission`
+    /// (and, for API-key callers, an unrestricted key scope does too).
+    pub fn require_permission(&self, permission: Permission) -> Result<(), HttpResponse> {
+        if !self.user.has_permission(permission) {
+            warn!(
+                "Permission {} denied for username={}",
+                permission, self.user.username
+            );
+            return Err(HttpResponse::Forbidden().body(format!("missing permission: {}", permission)));
+        }
+        self.require_scope(permission, None)
+    }
+
+    /// For API-key callers, returns `Err(403 Forbidden)` unless a key scope grants
+    /// `permission` (on `conversation_id` if given). Always `Ok` for JWT callers.
+    pub fn require_scope(
+        &self,
+        permission: Permission,
+        conversation_id: Option<&str>,
+    ) -> Result<(), HttpResponse> {
+        match &self.api_key {
+            Some(key) if !key.allows(permission, conversation_id) => {
+                warn!(
+                    "API key {} lacks scope {} (conversation={:?})",
+                    key.prefix, permission, conversation_id
+                );
+                Err(HttpResponse::Forbidden().body(format!("API key lacks scope: {}", permission)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `require_scope` on the conversation of `task`, if it has one.
+    pub fn require_task_scope(&self, permission: Permission, task: &Task) -> Result<(), HttpResponse> {
+        let conversation = task
+            .conversation_id
+            .as_ref()
+            .map(|id| format!("conversation:{}", id));
+        self.require_scope(permission, conversation.as_deref())
+    }
+
+    /// `require_scope` for turning `message` into a task: `message:create` and
+    /// `task:update`, both on the message's conversation.
+    pub fn require_message_task_scope(&self, message: &Message) -> Result<(), HttpResponse> {
+        let conversation = format!("conversation:{}", message.conversation_id.id.to_raw());
+        self.require_scope(Permission::MessageCreate, Some(&conversation))?;
+        self.require_scope(Permission::TaskUpdate, Some(&conversation))
+    }
+
+    /// Returns `Err(403 Forbidden)` for API-key callers. Used by account
+    /// management endpoints (2FA, API keys, ...) that need a human login.
+    pub fn require_user_session(&self) -> Result<(), HttpResponse> {
+        match &self.api_key {
+            Some(key) => {
+                warn!("API key {} used on a login-only endpoint", key.prefix);
+                Err(HttpResponse::Forbidden().body("API keys cannot be used for this endpoint"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Client address of the request, used for throttling.
+///
+/// The socket peer by default; with TRUST_PROXY_HEADERS=true (behind a reverse
+/// proxy) the first `Forwarded` / `X-Forwarded-For` entry instead.
+pub fn client_ip(req: &HttpRequest) -> String {
+    let trust_proxy = env::var("TRUST_PROXY_HEADERS")
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    if trust_proxy {
+        if let Some(addr) = req.connection_info().realip_remote_addr() {
+            return addr
+                .parse::<SocketAddr>()
+                .map(|a| a.ip().to_string())
+                .unwrap_or_else(|_| addr.to_string());
+        }
+    }
+    req.peer_addr()
+        .map(|a| a.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Checks that `user` may still act: not blocked, not deactivated and, for JWT
+/// callers (`token_version` given), the token was issued at the user's current
+/// `token_version`. Shared by the extractor and the WebSocket handshake.
+pub fn check_account(user: &User, token_version: Option<u32>) -> Result<(), actix_web::Error> {
+    if user.blocked {
+        warn!("Request from blocked account username={}", user.username);
+        return Err(ErrorForbidden("account blocked"));
+    }
+    if user.is_deactivated() {
+        warn!("Request from deactivated account username={}", user.username);
+        return Err(ErrorForbidden("account deactivated"));
+    }
+    match token_version {
+        Some(ver) if ver != user.token_version => {
+            warn!(
+                "Stale token for username={} (token ver={}, current ver={})",
+                user.username, ver, user.token_version
+            );
+            Err(ErrorUnauthorized("token revoked"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Extracts the bearer token from the Authorization header.
+pub fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let token = bearer_token(req);
+        let db = req.app_data::<web::Data<Database>>().cloned();
+        let api_keys = req.app_data::<web::Data<ApiKeyService>>().cloned();
+
+        Box::pin(async move {
+            let db = db.ok_or_else(|| ErrorInternalServerError("database not configured"))?;
+            let token = token.ok_or_else(|| ErrorUnauthorized("missing bearer token"))?;
+            if is_api_key(&token) {
+                let api_keys =
+                    api_keys.ok_or_else(|| ErrorInternalServerError("API keys not configured"))?;
+                return authenticate_api_key(&db, &api_keys, &token).await;
+            }
+
+            let claims = validate_token(&token).map_err(|e| {
+                debug!("Token rejected: {}", e);
+                ErrorUnauthorized("invalid token")
+            })?;
+
+            let user = <Database as UserDataTrait>::find_user_by_id(&db, &claims.sub)
+                .await
+                .ok_or_else(|| ErrorUnauthorized("unknown user"))?;
+
+            check_account(&user, Some(claims.ver))?;
+
+            Ok(AuthenticatedUser {
+                user,
+                claims,
+                api_key: None,
+            })
+        })
+    }
+}
+
+// Resolves an API key and its user; the claims mirror the key scopes
+async fn authenticate_api_key(
+    db: &Database,
+    api_keys: &ApiKeyService,
+    token: &str,
+) -> Result<AuthenticatedUser, actix_web::Error> {
+    let key = api_keys.authenticate(token).await.map_err(|e| {
+        debug!("API key rejected: {}", e);
+        ErrorUnauthorized("invalid API key")
+    })?;
+    let user_id = key.user_id.id.to_raw();
+    let user = <Database as UserDataTrait>::find_user_by_id(db, &user_id)
+        .await
+        .ok_or_else(|| ErrorUnauthorized("unknown user"))?;
+    check_account(&user, None).inspect_err(|_| {
+        warn!("API key {} of unavailable account used", key.prefix);
+    })?;
+
+    Ok(AuthenticatedUser::from_api_key(user, key))
+}
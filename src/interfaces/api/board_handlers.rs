@@ -0,0 +1,301 @@
+//! Board Handlers Module
+//! Implements HTTP request handlers for projects and Kanban boards.
+//!
+//! Every endpoint requires authentication (Bearer JWT or API key). Projects
+//! and their boards are visible to the project owner and members only; others
+//! get 404. Only the owner adds members.
+//!
+//! API keys need a scope for changes: `workspace:create` for projects,
+//! `workspace:manage_members` for members, `workspace:update` for boards and
+//! their columns, and `task:update` for cards.
+//!
+//! Board changes accept the `version` the client last saw: a stale version
+//! answers 409 `VersionConflict`, so the client can reload and retry. Without
+//! it the server applies the change to the latest board. Members are told of
+//! every change over the chat WebSocket.
+
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use crate::application::services::board_service::BoardService;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::role::Permission;
+use crate::models::entities::board::{
+    AddCardRequest, AddProjectMemberRequest, CardPlacement, CreateBoardRequest,
+    CreateProjectRequest, SetColumnsRequest,
+};
+use log::{info, warn};
+
+/// Lists the projects the caller owns or is a member of
+///
+/// # Returns
+/// - 200 OK with the projects
+pub async fn list_projects(
+    auth: AuthenticatedUser,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    match boards.list_projects(&auth.user).await {
+        Ok(projects) => HttpResponse::Ok().json(projects),
+        Err(e) => {
+            warn!("GET /projects: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Creates a project; the caller becomes its owner
+///
+/// # Returns
+/// - 200 OK with the created project
+/// - 400 Bad Request (`InvalidBoard`, `UserNotFound`) for a bad name or unknown members
+/// - 403 Forbidden if an API key lacks the `workspace:create` scope
+pub async fn create_project(
+    auth: AuthenticatedUser,
+    body: web::Json<CreateProjectRequest>,
+    db: web::Data<Database>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::WorkspaceCreate, None) {
+        return resp;
+    }
+    info!("POST /projects: create requested");
+    match boards.create_project(db.get_ref(), &auth.user, body.into_inner()).await {
+        Ok(project) => HttpResponse::Ok().json(project),
+        Err(e) => {
+            warn!("POST /projects: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Adds a member to a project (owner only)
+///
+/// # Returns
+/// - 200 OK with the project
+/// - 400 Bad Request (`UserNotFound`) if the user does not exist
+/// - 403 Forbidden (`NotAllowed`) if the caller is a member but not the owner
+/// - 403 Forbidden if an API key lacks the `workspace:manage_members` scope
+/// - 404 Not Found if the project doesn't exist or the caller is not a member
+pub async fn add_project_member(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<AddProjectMemberRequest>,
+    db: web::Data<Database>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::WorkspaceManageMembers, None) {
+        return resp;
+    }
+    let project_id = path.into_inner();
+    match boards
+        .add_member(db.get_ref(), &auth.user, &project_id, body.into_inner())
+        .await
+    {
+        Ok(project) => HttpResponse::Ok().json(project),
+        Err(e) => {
+            warn!("POST /projects/{project_id}/members: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Lists the boards of a project
+///
+/// # Returns
+/// - 200 OK with the boards
+/// - 404 Not Found if the project doesn't exist or the caller is not a member
+pub async fn list_boards(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    let project_id = path.into_inner();
+    match boards.list_boards(&auth.user, &project_id).await {
+        Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => {
+            warn!("GET /projects/{project_id}/boards: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Creates a board in a project; without `columns` it gets To do / In progress / Done
+///
+/// # Returns
+/// - 200 OK with the created board
+/// - 400 Bad Request (`InvalidBoard`) for a bad name or columns
+/// - 403 Forbidden if an API key lacks the `workspace:update` scope
+/// - 404 Not Found if the project doesn't exist or the caller is not a member
+pub async fn create_board(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<CreateBoardRequest>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::WorkspaceUpdate, None) {
+        return resp;
+    }
+    let project_id = path.into_inner();
+    info!("POST /projects/{project_id}/boards: create requested");
+    match boards.create_board(&auth.user, &project_id, body.into_inner()).await {
+        Ok(board) => HttpResponse::Ok().json(board),
+        Err(e) => {
+            warn!("POST /projects/{project_id}/boards: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Returns a board with its columns and their cards in order
+///
+/// # Returns
+/// - 200 OK with `{ uuid, project_id, name, version, columns: [{ id, name, status, cards: [{ id, task_id, column_id, position, task }] }] }`
+/// - 404 Not Found if the board doesn't exist or the caller is not a member of its project
+pub async fn get_board(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    let board_id = path.into_inner();
+    match boards.get_board(db.get_ref(), &auth.user, &board_id).await {
+        Ok(view) => HttpResponse::Ok().json(view),
+        Err(e) => {
+            warn!("GET /boards/{board_id}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Replaces the columns of a board; listed `id`s keep existing columns
+///
+/// # Returns
+/// - 200 OK with the board
+/// - 400 Bad Request (`InvalidBoard`, `ColumnNotFound`) for bad columns
+/// - 403 Forbidden if an API key lacks the `workspace:update` scope
+/// - 404 Not Found if the board doesn't exist or the caller is not a member of its project
+/// - 409 Conflict (`ColumnNotEmpty`, `VersionConflict`) when removing a column
+///   holding cards or for a stale `version`
+pub async fn set_columns(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<SetColumnsRequest>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::WorkspaceUpdate, None) {
+        return resp;
+    }
+    let board_id = path.into_inner();
+    match boards.set_columns(&auth.user, &board_id, body.into_inner()).await {
+        Ok(board) => HttpResponse::Ok().json(board),
+        Err(e) => {
+            warn!("PUT /boards/{board_id}/columns: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Puts a task the caller can see on a board
+///
+/// A column whose status differs from the task's changes the task status,
+/// under the usual task rules.
+///
+/// # Returns
+/// - 200 OK with `{ board_id, version, card }`
+/// - 400 Bad Request (`ColumnNotFound`) for an unknown column
+/// - 403 Forbidden (`NotAllowed`) if the status change is not allowed to the caller,
+///   or if an API key lacks the `task:update` scope
+/// - 404 Not Found if the board, task or neighbour card doesn't exist or is not visible
+/// - 409 Conflict (`TaskAlreadyOnBoard`, `VersionConflict`, `BlockedBy`)
+pub async fn add_card(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<AddCardRequest>,
+    db: web::Data<Database>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::TaskUpdate, None) {
+        return resp;
+    }
+    let board_id = path.into_inner();
+    match boards
+        .add_card(
+            db.get_ref(),
+            db.get_ref(),
+            &auth.user,
+            auth.task_access(),
+            &board_id,
+            body.into_inner(),
+        )
+        .await
+    {
+        Ok(update) => HttpResponse::Ok().json(update),
+        Err(e) => {
+            warn!("POST /boards/{board_id}/cards: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Moves a card to a column (default: its own) after / before another card
+///
+/// # Returns
+/// - 200 OK with `{ board_id, version, card }`
+/// - 400 Bad Request (`ColumnNotFound`) for an unknown column
+/// - 403 Forbidden (`NotAllowed`) if the status change is not allowed to the caller,
+///   or if an API key lacks the `task:update` scope
+/// - 404 Not Found if the board or a card doesn't exist or is not visible
+/// - 409 Conflict (`VersionConflict`, `BlockedBy`) for a stale version or open blockers
+pub async fn move_card(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    body: web::Json<CardPlacement>,
+    db: web::Data<Database>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::TaskUpdate, None) {
+        return resp;
+    }
+    let (board_id, card_id) = path.into_inner();
+    match boards
+        .move_card(
+            db.get_ref(),
+            db.get_ref(),
+            &auth.user,
+            auth.task_access(),
+            &board_id,
+            &card_id,
+            body.into_inner(),
+        )
+        .await
+    {
+        Ok(update) => HttpResponse::Ok().json(update),
+        Err(e) => {
+            warn!("POST /boards/{board_id}/cards/{card_id}/move: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Takes a card off a board; the task is kept
+///
+/// # Returns
+/// - 200 OK with `{ board_id, version, card }`
+/// - 403 Forbidden if an API key lacks the `task:update` scope
+/// - 404 Not Found if the board or card doesn't exist or is not visible
+pub async fn remove_card(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    boards: web::Data<BoardService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_scope(Permission::TaskUpdate, None) {
+        return resp;
+    }
+    let (board_id, card_id) = path.into_inner();
+    match boards.remove_card(&auth.user, &board_id, &card_id).await {
+        Ok(update) => HttpResponse::Ok().json(update),
+        Err(e) => {
+            warn!("DELETE /boards/{board_id}/cards/{card_id}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
@@ -0,0 +1,171 @@
+//! Calendar Handlers Module
+//! Implements HTTP request handlers for the task calendar feed and imports.
+//!
+//! `GET /api/tasks/calendar.ics?token=...` is meant for calendar apps, which
+//! cannot send a bearer token: the secret feed token in the URL identifies the
+//! user instead. The token is created (or replaced) and revoked by the signed-in
+//! user; API keys cannot manage it.
+//!
+//! `POST /api/tasks/import` takes a `multipart/form-data` upload whose `file`
+//! field holds an `.ics` file, and creates a task per `VTODO` / `VEVENT`. API
+//! keys need the `task:update` scope to import.
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use crate::application::services::calendar_service::CalendarService;
+use crate::error::TaskError;
+use crate::infrastructure::calendar::ics::IcsComponent;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::calendar::CalendarFeedQuery;
+use crate::models::entities::role::Permission;
+use futures::StreamExt;
+use log::{info, warn};
+
+/// Largest accepted `.ics` upload (1 MiB)
+const MAX_ICS_BYTES: usize = 1024 * 1024;
+
+/// Serves the iCalendar feed of the token's owner
+///
+/// # Query
+/// - `token`: The feed token
+/// - `type`: `event` (default, `VEVENT`s) or `todo` (`VTODO`s)
+///
+/// # Returns
+/// - 200 OK with a `text/calendar` document of the visible tasks with a due date
+/// - 400 Bad Request (`InvalidCalendar`) for an unknown `type`
+/// - 401 Unauthorized (`InvalidFeedToken`) for an unknown or revoked token
+pub async fn calendar_feed(
+    query: web::Query<CalendarFeedQuery>,
+    db: web::Data<Database>,
+    calendar: web::Data<CalendarService>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let component = match query.kind.as_deref() {
+        None | Some("event") => IcsComponent::Event,
+        Some("todo") => IcsComponent::Todo,
+        Some(other) => {
+            return TaskError::InvalidCalendar(format!("unknown type: {}", other))
+                .error_response()
+        },
+    };
+    match calendar
+        .feed(db.get_ref(), db.get_ref(), &query.token, component)
+        .await
+    {
+        Ok(ics) => HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .insert_header(("Content-Disposition", "inline; filename=\"tasks.ics\""))
+            .body(ics),
+        Err(e) => {
+            warn!("GET /tasks/calendar.ics: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Creates a calendar feed token for the caller, replacing the previous one
+///
+/// # Returns
+/// - 200 OK with `{ "token": "...", "url": "/api/tasks/calendar.ics?token=..." }`
+///   (the token is shown only once)
+/// - 403 Forbidden for API-key callers
+pub async fn create_calendar_token(
+    auth: AuthenticatedUser,
+    calendar: web::Data<CalendarService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match calendar.create_token(&auth.user).await {
+        Ok(token) => HttpResponse::Ok().json(token),
+        Err(e) => {
+            warn!("POST /tasks/calendar/token: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Revokes the caller's calendar feed token; subscribed apps stop receiving updates
+///
+/// # Returns
+/// - 204 No Content
+/// - 403 Forbidden for API-key callers
+pub async fn revoke_calendar_token(
+    auth: AuthenticatedUser,
+    calendar: web::Data<CalendarService>,
+) -> impl Responder {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match calendar.revoke_token(&auth.user).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            warn!("DELETE /tasks/calendar/token: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Creates tasks from an uploaded `.ics` file (`file` form field)
+///
+/// # Returns
+/// - 200 OK with `{ "created": [task, ...], "skipped": [{ "index", "summary", "reason" }] }`
+/// - 400 Bad Request (`InvalidCalendar`) if the upload is missing, too large
+///   or not an iCalendar file
+/// - 403 Forbidden if an API key lacks the `task:update` scope
+pub async fn import_calendar(
+    auth: AuthenticatedUser,
+    payload: Multipart,
+    db: web::Data<Database>,
+    calendar: web::Data<CalendarService>,
+) -> impl Responder {
+    info!("POST /tasks/import: import requested");
+    if let Err(resp) = auth.require_scope(Permission::TaskUpdate, None) {
+        return resp;
+    }
+    let text = match read_upload(payload).await {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("POST /tasks/import: failed -> {}", e);
+            return e.error_response();
+        },
+    };
+    match calendar
+        .import(db.get_ref(), db.get_ref(), &auth.user, &text)
+        .await
+    {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => {
+            warn!("POST /tasks/import: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+// Reads the `file` field of the upload as UTF-8, up to MAX_ICS_BYTES
+async fn read_upload(mut payload: Multipart) -> Result<String, TaskError> {
+    let malformed = |e: actix_multipart::MultipartError| {
+        TaskError::InvalidCalendar(format!("malformed upload: {}", e))
+    };
+    while let Some(field) = payload.next().await {
+        let mut field = field.map_err(malformed)?;
+        if field.name() != Some("file") {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(malformed)?;
+            if bytes.len() + chunk.len() > MAX_ICS_BYTES {
+                return Err(TaskError::InvalidCalendar(format!(
+                    "file larger than {} bytes",
+                    MAX_ICS_BYTES
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return String::from_utf8(bytes)
+            .map_err(|_| TaskError::InvalidCalendar("file is not UTF-8".to_string()));
+    }
+    Err(TaskError::InvalidCalendar("file field missing".to_string()))
+}
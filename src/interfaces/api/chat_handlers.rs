@@ -3,17 +3,27 @@
 //! Handles HTTP requests related to chat and upgrades connections to WebSocket.
 
 use actix::Addr;
-use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web::{web, Error, HttpRequest, HttpResponse, ResponseError};
 use actix_web_actors::ws;
+use log::warn;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
 use surrealdb::sql::Thing;
 
+use crate::application::services::api_key_service::ApiKeyService;
 use crate::application::services::conversation_service::ConversationService;
+use crate::application::services::email_verification_service::EmailVerificationService;
 use crate::application::services::message_service::MessageService;
+use crate::application::services::message_task_service::MessageTaskService;
+use crate::application::services::ws_ticket_service::WsTicketService;
 use crate::infrastructure::auth::jwt::validate_token;
+use crate::interfaces::api::auth::{bearer_token, check_account, AuthenticatedUser};
 use crate::infrastructure::websocket::chat_server::ChatServer;
 use crate::infrastructure::websocket::session::WsSession;
+use crate::models::entities::api_key::is_api_key;
 use crate::models::entities::conversation::ConversationType;
+use crate::models::entities::role::Permission;
 
 /// DTO for creating a new conversation
 #[derive(Debug, Deserialize)]
@@ -33,8 +43,12 @@ pub struct GetMessagesQuery {
     pub offset: Option<u32>,
 }
 
-/// Helper to extract user_id from Authorization header or Query param
-fn extract_user_id(req: &HttpRequest) -> Option<Thing> {
+/// Helper to extract the user id and token version from the Authorization header.
+///
+/// A JWT in the `token` query parameter is accepted only with
+/// WS_ALLOW_QUERY_TOKEN=true (compatibility for clients that predate
+/// `POST /api/ws/ticket`); query strings end up in proxy and access logs.
+fn extract_user_id(req: &HttpRequest) -> Option<(Thing, u32)> {
     // 1. Try Authorization header
     let token = req
         .headers()
@@ -43,50 +57,131 @@ fn extract_user_id(req: &HttpRequest) -> Option<Thing> {
         .and_then(|s| s.strip_prefix("Bearer "))
         .map(|s| s.to_string());
 
-    // 2. Try query parameter (fallback for WebSockets)
+    // 2. Try query parameter (legacy WebSocket clients, opt-in)
     let token = token.or_else(|| {
-        req.query_string()
-            .split('&')
-            .find(|s| s.starts_with("token="))
-            .and_then(|s| s.strip_prefix("token="))
-            .map(|s| s.to_string())
+        let token = query_param(req, "token")?;
+        if !query_token_allowed() {
+            warn!("Rejected JWT in query string; use POST /api/ws/ticket");
+            return None;
+        }
+        warn!("Deprecated JWT in query string accepted (WS_ALLOW_QUERY_TOKEN)");
+        Some(token)
     });
 
     token
         .and_then(|t| validate_token(&t).ok())
-        .map(|claims| Thing::from(("user", claims.sub.as_str())))
+        .map(|claims| (Thing::from(("user", claims.sub.as_str())), claims.ver))
+}
+
+fn query_token_allowed() -> bool {
+    env::var("WS_ALLOW_QUERY_TOKEN")
+        .map(|v| v == "true")
+        .unwrap_or(false)
+}
+
+fn query_param(req: &HttpRequest, name: &str) -> Option<String> {
+    web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .ok()
+        .and_then(|q| q.get(name).cloned())
+}
+
+/// POST /api/ws/ticket
+///
+/// Issues a single-use ticket for `GET /api/ws/chat?ticket=<ticket>`.
+pub async fn issue_ws_ticket(
+    auth: AuthenticatedUser,
+    tickets: web::Data<WsTicketService>,
+) -> HttpResponse {
+    // Tickets carry no scopes; API-key clients send the key in the handshake header
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    let user_id = match auth.user.id.clone() {
+        Some(id) => id,
+        None => return HttpResponse::Unauthorized().finish(),
+    };
+    match tickets.issue(user_id).await {
+        Ok(ticket) => HttpResponse::Ok().json(ticket),
+        Err(e) => e.error_response(),
+    }
 }
 
 /// Handlers for WebSocket connection
 ///
 /// Upgrades the HTTP connection to WebSocket and starts a WsSession actor.
-/// For testing, we are using a dummy user_id.
+/// The user is identified by a `ticket` from `POST /api/ws/ticket`, or by a
+/// bearer JWT or API key header for non-browser clients. API-key sessions are
+/// limited to the key scopes the user still holds.
+/// Blocked and deactivated accounts, and revoked JWTs, are refused as in
+/// `AuthenticatedUser`.
+/// If the e-mail verification policy covers chat, unverified users can connect
+/// and receive messages but not send them.
 pub async fn chat_ws(
     req: HttpRequest,
     stream: web::Payload,
     srv: web::Data<Addr<ChatServer>>,
+    db: web::Data<crate::infrastructure::database::surrealdb::Database>,
+    verification: web::Data<EmailVerificationService>,
+    tickets: web::Data<WsTicketService>,
+    api_keys: web::Data<ApiKeyService>,
 ) -> Result<HttpResponse, Error> {
-    let user_id = match extract_user_id(&req) {
-        Some(id) => id,
+    use crate::models::traits::user_data_trait::UserDataTrait;
+
+    let api_key_header = bearer_token(&req).filter(|t| is_api_key(t));
+    // Tickets and API keys carry no token version; only JWTs are checked against it
+    let (user_id, api_key, token_version) = if let Some(ticket) = query_param(&req, "ticket") {
+        match tickets.redeem(&ticket).await {
+            Ok(id) => (id, None, None),
+            Err(e) => return Ok(e.error_response()),
+        }
+    } else if let Some(presented) = api_key_header {
+        match api_keys.authenticate(&presented).await {
+            Ok(key) => (key.user_id.clone(), Some(key), None),
+            Err(e) => return Ok(e.error_response()),
+        }
+    } else {
+        match extract_user_id(&req) {
+            Some((id, ver)) => (id, None, Some(ver)),
+            None => return Ok(HttpResponse::Unauthorized().finish()),
+        }
+    };
+
+    let user = match db.find_user_by_id(&user_id.id.to_raw()).await {
+        Some(user) => user,
         None => return Ok(HttpResponse::Unauthorized().finish()),
     };
+    if let Err(e) = check_account(&user, token_version) {
+        return Ok(e.error_response());
+    }
+    let can_send = verification.policy().allows_chat(&user);
+    // Drop scopes the user no longer holds through its roles
+    let api_key = api_key.map(|mut key| {
+        key.scopes.retain(|s| user.has_permission(s.permission));
+        key
+    });
 
-    ws::start(WsSession::new(user_id, srv.get_ref().clone()), &req, stream)
+    ws::start(
+        WsSession::new(user_id, srv.get_ref().clone(), can_send)
+            .as_bot(user.bot)
+            .with_api_key(api_key),
+        &req,
+        stream,
+    )
 }
 
 /// POST /api/conversations
 pub async fn create_conversation(
-    req: HttpRequest,
+    auth: AuthenticatedUser,
     body: web::Json<CreateConversationRequest>,
     conversation_service: web::Data<ConversationService>,
     db: web::Data<crate::infrastructure::database::surrealdb::Database>,
 ) -> HttpResponse {
     use crate::models::traits::user_data_trait::UserDataTrait;
 
-    let creator_id = match extract_user_id(&req) {
-        Some(id) => id,
-        None => return HttpResponse::Unauthorized().finish(),
-    };
+    if let Err(resp) = auth.require_scope(Permission::ChannelCreate, None) {
+        return resp;
+    }
+    let creator_id = auth.user_id();
 
     let mut participant_ids = Vec::new();
 
@@ -94,7 +189,7 @@ pub async fn create_conversation(
     if let Some(wallet) = &body.target_wallet {
         participant_ids.push(wallet.to_lowercase());
     } else if let Some(ids) = &body.participant_ids {
-        participant_ids = ids.into_iter().map(|id| id.to_lowercase()).collect();
+        participant_ids = ids.iter().map(|id| id.to_lowercase()).collect();
     } else {
         return HttpResponse::BadRequest()
             .body("Either 'participant_ids' or 'target_wallet' must be provided");
@@ -160,13 +255,13 @@ pub async fn create_conversation(
 
 /// GET /api/conversations
 pub async fn get_conversations(
-    req: HttpRequest,
+    auth: AuthenticatedUser,
     conversation_service: web::Data<ConversationService>,
 ) -> HttpResponse {
-    let user_id = match extract_user_id(&req) {
-        Some(id) => id,
-        None => return HttpResponse::Unauthorized().finish(),
-    };
+    if let Err(resp) = auth.require_scope(Permission::ChannelRead, None) {
+        return resp;
+    }
+    let user_id = auth.user_id();
 
     match conversation_service.get_user_conversations(user_id).await {
         Ok(convs) => HttpResponse::Ok().json(convs),
@@ -175,21 +270,24 @@ pub async fn get_conversations(
 }
 
 /// GET /api/conversations/{id}/messages
+///
+/// Messages linked to a task carry its current name and status (`task`).
 pub async fn get_messages(
-    req: HttpRequest,
+    auth: AuthenticatedUser,
     path: web::Path<String>,
     query: web::Query<GetMessagesQuery>,
     message_service: web::Data<MessageService>,
+    message_tasks: web::Data<MessageTaskService>,
+    db: web::Data<crate::infrastructure::database::surrealdb::Database>,
 ) -> HttpResponse {
-    if extract_user_id(&req).is_none() {
-        return HttpResponse::Unauthorized().finish();
-    }
-
     let conv_id_str = path.into_inner();
     let parts: Vec<&str> = conv_id_str.split(':').collect();
     if parts.len() != 2 {
         return HttpResponse::BadRequest().body("Invalid conversation ID format");
     }
+    if let Err(resp) = auth.require_scope(Permission::ChannelRead, Some(&conv_id_str)) {
+        return resp;
+    }
 
     let conv_id = Thing::from((parts[0], parts[1]));
     let limit = query.limit.unwrap_or(50);
@@ -199,14 +297,14 @@ pub async fn get_messages(
         .get_conversation_history(conv_id, limit, offset)
         .await
     {
-        Ok(msgs) => HttpResponse::Ok().json(msgs),
+        Ok(msgs) => HttpResponse::Ok().json(message_tasks.with_tasks(db.get_ref(), msgs).await),
         Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
     }
 }
 
 /// POST /api/conversations/{id}/participants
 pub async fn add_participant(
-    req: HttpRequest,
+    auth: AuthenticatedUser,
     path: web::Path<String>,
     body: web::Json<serde_json::Value>,
     conversation_service: web::Data<ConversationService>,
@@ -214,15 +312,14 @@ pub async fn add_participant(
 ) -> HttpResponse {
     use crate::models::traits::user_data_trait::UserDataTrait;
 
-    if extract_user_id(&req).is_none() {
-        return HttpResponse::Unauthorized().finish();
-    }
-
     let conv_id_str = path.into_inner();
     let parts: Vec<&str> = conv_id_str.split(':').collect();
     if parts.len() != 2 {
         return HttpResponse::BadRequest().body("Invalid conversation ID format");
     }
+    if let Err(resp) = auth.require_scope(Permission::UserInvite, Some(&conv_id_str)) {
+        return resp;
+    }
     let conv_id = Thing::from((parts[0], parts[1]));
 
     let identifier = match body.get("identifier").and_then(|v| v.as_str()) {
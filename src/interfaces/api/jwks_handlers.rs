@@ -0,0 +1,28 @@
+//! JWKS Handlers Module
+//! Publishes the public keys that verify Chasqui tokens, so other services can
+//! validate them without sharing any secret.
+//!
+//! Endpoints
+//! - GET /.well-known/jwks.json
+//!   200 OK JSON: { "keys": [ { "kty": "RSA", "kid": "...", "alg": "RS256", "n": "...", "e": "AQAB" } ] }
+//!   With HS256 (shared secret) the set is empty.
+//!   500 Internal Server Error: keys are not configured
+
+use actix_web::HttpResponse;
+use log::error;
+
+use crate::infrastructure::auth::keys;
+
+/// GET /.well-known/jwks.json
+pub async fn jwks() -> HttpResponse {
+    match keys::current() {
+        Ok(store) => HttpResponse::Ok()
+            // Short cache so rotated keys are picked up quickly
+            .insert_header(("Cache-Control", "public, max-age=300"))
+            .json(store.jwks()),
+        Err(e) => {
+            error!("JWKS unavailable: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
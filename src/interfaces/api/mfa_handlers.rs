@@ -0,0 +1,135 @@
+//! MFA Handlers Module
+//! Implements TOTP two-factor enrollment and the second step of the password login.
+//!
+//! Endpoints
+//! - POST /api/auth/mfa/totp/enroll   (Bearer JWT)
+//!   200 OK JSON: { "secret": "<base32>", "otpauth_uri": "otpauth://totp/..." }
+//!   409 Conflict: `MfaAlreadyEnabled`
+//!
+//! - POST /api/auth/mfa/totp/confirm  (Bearer JWT)
+//!   Request JSON: { "code": "123456" }
+//!   200 OK JSON: { "recovery_codes": ["a1b2c-3d4e5", ...] }  (shown only once)
+//!   400 Bad Request: `MfaNotEnabled` (no pending enrollment) | 401: `InvalidMfaCode`
+//!
+//! - POST /api/auth/mfa/totp/disable  (Bearer JWT)
+//!   Request JSON: { "code": "123456" }  (TOTP or recovery code)
+//!   204 No Content | 401: `InvalidMfaCode`
+//!
+//! - POST /api/auth/mfa/verify
+//!   Request JSON: { "mfa_token": "<from /api/login>", "code": "123456" }
+//!   `code` may also be an unused recovery code.
+//!   200 OK JSON: { "token": "<JWT>" }
+//!   400 Bad Request: `InvalidOrExpiredToken` | 401: `InvalidMfaCode`
+//!
+//! With 2FA enabled, `POST /api/login` (password flow) answers
+//! `{ "mfa_required": true, "mfa_token": "...", "expires_in": 300 }` instead of a JWT.
+
+use actix_web::{web, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::application::services::mfa_service::MfaService;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+
+/// Request payload carrying a TOTP or recovery code
+#[derive(Deserialize, Validate)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 1, max = 32, message = "code is required"))]
+    pub code: String,
+}
+
+/// Request payload for the second login step
+#[derive(Deserialize, Validate)]
+pub struct MfaVerifyRequest {
+    #[validate(length(min = 1, message = "mfa_token is required"))]
+    pub mfa_token: String,
+    #[validate(length(min = 1, max = 32, message = "code is required"))]
+    pub code: String,
+}
+
+/// Response payload with the one-time display of the recovery codes
+#[derive(Serialize)]
+struct RecoveryCodesResponse {
+    recovery_codes: Vec<String>,
+}
+
+/// Response payload for a completed login
+#[derive(Serialize)]
+struct TokenResponse {
+    token: String,
+}
+
+/// POST /api/auth/mfa/totp/enroll
+pub async fn enroll_totp(
+    auth: AuthenticatedUser,
+    mfa: web::Data<MfaService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match mfa.start_enrollment(db.get_ref(), &auth.user).await {
+        Ok(enrollment) => HttpResponse::Ok().json(enrollment),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/auth/mfa/totp/confirm
+pub async fn confirm_totp(
+    auth: AuthenticatedUser,
+    body: web::Json<MfaCodeRequest>,
+    mfa: web::Data<MfaService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if body.validate().is_err() {
+        return HttpResponse::BadRequest().body("code is required");
+    }
+    match mfa
+        .confirm_enrollment(db.get_ref(), &auth.user, &body.code)
+        .await
+    {
+        Ok(recovery_codes) => HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes }),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/auth/mfa/totp/disable
+pub async fn disable_totp(
+    auth: AuthenticatedUser,
+    body: web::Json<MfaCodeRequest>,
+    mfa: web::Data<MfaService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if body.validate().is_err() {
+        return HttpResponse::BadRequest().body("code is required");
+    }
+    match mfa.disable(db.get_ref(), &auth.user, &body.code).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/auth/mfa/verify
+pub async fn verify_mfa(
+    body: web::Json<MfaVerifyRequest>,
+    mfa: web::Data<MfaService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(e) = body.validate() {
+        return HttpResponse::BadRequest().body(e.to_string());
+    }
+    match mfa
+        .complete_login(db.get_ref(), &body.mfa_token, &body.code)
+        .await
+    {
+        Ok(token) => HttpResponse::Ok().json(TokenResponse { token }),
+        Err(e) => e.error_response(),
+    }
+}
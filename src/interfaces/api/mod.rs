@@ -4,6 +4,18 @@
 //! for different resources in the application.
 //!
 //! # Module Structure
+//! - `account_handlers`: Account recovery handlers (password reset, e-mail verification)
+//! - `account_link_handlers`: Linked wallets / external identities and account merges
+//! - `api_key_handlers`: Bot accounts and scoped API key management
+//! - `auth`: Authenticated request extractor (token version and permission checks)
+//! - `board_handlers`: Projects and Kanban boards (columns and cards)
+//! - `calendar_handlers`: Task calendar feed (`.ics` subscription) and `.ics` imports
+//! - `jwks_handlers`: Public JWT verification keys (`/.well-known/jwks.json`)
+//! - `mfa_handlers`: TOTP two-factor enrollment and second login step
+//! - `notification_handlers`: Stored notifications (task reminders) of the caller
+//! - `privacy_handlers`: Account deactivation, deletion and personal data export
+//! - `profile_handlers`: Own profile (`/api/me`), public profiles and credential changes
+//! - `role_handlers`: RBAC administration handlers (roles and assignments)
 //! - `routes`: API route configuration and setup
 //! - `task_handlers`: Task-related request handlers
 //! - `user_handlers`: User-related request handlers
@@ -19,8 +31,20 @@
 //! - Implement resource handlers
 //! - Manage API routing logic
 
+pub mod account_handlers;
+pub mod account_link_handlers;
 pub mod api_doc;
+pub mod api_key_handlers;
+pub mod auth;
+pub mod board_handlers;
+pub mod calendar_handlers;
 pub mod chat_handlers;
+pub mod jwks_handlers;
+pub mod mfa_handlers;
+pub mod notification_handlers;
+pub mod privacy_handlers;
+pub mod profile_handlers;
+pub mod role_handlers;
 pub mod routes;
 pub mod task_handlers;
 pub mod user_handlers;
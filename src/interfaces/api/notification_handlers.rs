@@ -0,0 +1,54 @@
+//! Notification Handlers Module
+//! Implements HTTP request handlers for the caller's stored notifications.
+//!
+//! Notifications (task reminders) are pushed over the chat WebSocket; those
+//! sent while the user had no open session are kept and listed here until
+//! the user marks them read. Every endpoint requires authentication and only
+//! ever shows the caller's own notifications.
+
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use crate::application::services::notification_service::NotificationService;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::notification::NotificationQuery;
+use log::warn;
+
+/// Lists the caller's stored notifications, newest first
+///
+/// # Query
+/// - `unread=true` to skip the notifications already read
+///
+/// # Returns
+/// - 200 OK with the notifications
+pub async fn list_notifications(
+    auth: AuthenticatedUser,
+    query: web::Query<NotificationQuery>,
+    notifications: web::Data<NotificationService>,
+) -> impl Responder {
+    match notifications.list(&auth.user, query.unread).await {
+        Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => {
+            warn!("GET /notifications: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Marks one of the caller's notifications read
+///
+/// # Returns
+/// - 200 OK with the notification
+/// - 404 Not Found (`NotificationNotFound`) if the caller has no such notification
+pub async fn mark_notification_read(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    notifications: web::Data<NotificationService>,
+) -> impl Responder {
+    let uuid = path.into_inner();
+    match notifications.mark_read(&auth.user, &uuid).await {
+        Ok(notification) => HttpResponse::Ok().json(notification),
+        Err(e) => {
+            warn!("POST /notifications/{}/read: failed -> {}", uuid, e);
+            e.error_response()
+        },
+    }
+}
@@ -0,0 +1,88 @@
+//! Privacy Handlers Module
+//! Implements leaving the service and exporting one's own data.
+//!
+//! Endpoints
+//! - POST   /api/me/deactivate     (Bearer JWT)
+//!   204 No Content; every issued token is revoked and the account is hidden
+//!   from the user directory. Signing in again reactivates it.
+//!
+//! - DELETE /api/me                (Bearer JWT)
+//!   202 Accepted JSON: { "deletion_scheduled_at": "2026-11-18T10:00:00Z" }
+//!   The account is deactivated now and deleted when the grace period ends;
+//!   signing in before that cancels the deletion. Messages of deleted accounts
+//!   remain, attributed to "deleted user".
+//!
+//! - GET    /api/me/export         (Bearer JWT)
+//!   200 OK `application/zip` with `profile.json`, `conversations.json` and
+//!   `messages.json`.
+
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::application::services::account_deletion_service::AccountDeletionService;
+use crate::application::services::data_export_service::DataExportService;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+
+/// Response payload of a deletion request
+#[derive(Serialize)]
+struct DeletionScheduledResponse {
+    deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+/// POST /api/me/deactivate
+pub async fn deactivate_me(
+    auth: AuthenticatedUser,
+    deletions: web::Data<AccountDeletionService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match deletions.deactivate(db.get_ref(), &auth.user).await {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// DELETE /api/me
+pub async fn delete_me(
+    auth: AuthenticatedUser,
+    deletions: web::Data<AccountDeletionService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match deletions.schedule_deletion(db.get_ref(), &auth.user).await {
+        Ok(user) => HttpResponse::Accepted().json(DeletionScheduledResponse {
+            deletion_scheduled_at: user.deletion_scheduled_at,
+        }),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// GET /api/me/export
+pub async fn export_me(
+    auth: AuthenticatedUser,
+    exports: web::Data<DataExportService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    match exports.export_zip(&auth.user).await {
+        Ok(archive) => HttpResponse::Ok()
+            .content_type("application/zip")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "chasqui-export-{}.zip",
+                    auth.user.username
+                ))],
+            })
+            .body(archive),
+        Err(e) => e.error_response(),
+    }
+}
@@ -0,0 +1,362 @@
+//! Profile Handlers Module
+//! Implements the caller's own profile, public profiles and credential changes.
+//!
+//! Endpoints
+//! - GET   /api/me                 (Bearer JWT or API key)
+//!   200 OK JSON: { "id": "<uuid>", "username": "alice", "email": "...",
+//!   "email_verified": true, "wallet": null, "roles": ["user"], "bot": false,
+//!   "mfa_enabled": false, "display_name": "Alice", "avatar_url": "https://...",
+//!   "bio": "...", "locale": "es-PE", "timezone": "America/Lima" }
+//!
+//! - PATCH /api/me                 (Bearer JWT or API key)
+//!   Request JSON (every field optional; "" clears it):
+//!   { "display_name": "Alice", "avatar_url": "https://...", "bio": "...",
+//!   "locale": "es-PE", "timezone": "America/Lima" }
+//!   200 OK JSON: same shape as GET /api/me
+//!   400 Bad Request: `InvalidProfile`
+//!
+//! - GET   /api/users/{id}         (Bearer JWT or API key)
+//!   200 OK JSON: { "id": "<uuid>", "username": "alice", "display_name": "Alice",
+//!   "avatar_url": "https://...", "bio": "...", "bot": false }
+//!   `GET /api/users/deleted` answers the "deleted user" placeholder that
+//!   messages of deleted accounts point to.
+//!   404 Not Found: `UserNotFound` (also for deactivated accounts)
+//!
+//! - POST  /api/me/email           (Bearer JWT)
+//!   Request JSON: { "current_password": "...", "email": "new@example.com" }
+//!   200 OK JSON: same shape as GET /api/me; the new address starts unverified and
+//!   a verification link is sent to it.
+//!   400: `InvalidEmail` | `PasswordNotSet`, 403: `InvalidCurrentPassword`,
+//!   409: `EmailAlreadyInUse`, 429: `TooManyLoginAttempts`
+//!
+//! - POST  /api/me/password        (Bearer JWT)
+//!   Request JSON: { "current_password": "...", "new_password": "N3w$ecret!" }
+//!   200 OK JSON: { "token": "<new JWT>" }; every previously issued JWT is revoked.
+//!   400: `WeakPassword` | `PasswordNotSet`, 403: `InvalidCurrentPassword`,
+//!   429: `TooManyLoginAttempts`
+//!
+//! Wrong current passwords count towards the login lockout of the account.
+
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidateUrl, ValidationError};
+
+use crate::application::services::email_verification_service::EmailVerificationService;
+use crate::application::services::login_throttle_service::LoginThrottleService;
+use crate::application::services::profile_service::ProfileService;
+use crate::error::AuthError;
+use crate::infrastructure::auth::jwt::generate_token_for_user;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::{client_ip, AuthenticatedUser};
+use crate::models::entities::profile::{
+    is_valid_locale, is_valid_timezone, UserProfile, MAX_BIO_LENGTH, MAX_DISPLAY_NAME_LENGTH,
+};
+use crate::models::entities::user::{User, DELETED_USER_ID, DELETED_USER_NAME};
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Request payload for updating the caller's profile
+#[derive(Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(
+        max = "MAX_DISPLAY_NAME_LENGTH",
+        message = "display_name must be at most 64 characters"
+    ))]
+    pub display_name: Option<String>,
+    #[validate(custom(function = "validate_avatar_url"))]
+    pub avatar_url: Option<String>,
+    #[validate(length(max = "MAX_BIO_LENGTH", message = "bio must be at most 500 characters"))]
+    pub bio: Option<String>,
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+}
+
+impl From<UpdateProfileRequest> for UserProfile {
+    fn from(req: UpdateProfileRequest) -> Self {
+        UserProfile {
+            display_name: req.display_name,
+            avatar_url: req.avatar_url,
+            bio: req.bio,
+            locale: req.locale,
+            timezone: req.timezone,
+        }
+    }
+}
+
+/// Request payload for changing the e-mail address
+#[derive(Deserialize, Validate)]
+pub struct ChangeEmailRequest {
+    #[validate(length(min = 1, message = "current_password is required"))]
+    pub current_password: String,
+    #[validate(email(message = "invalid email"))]
+    pub email: String,
+}
+
+/// Request payload for changing the password
+#[derive(Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "current_password is required"))]
+    pub current_password: String,
+    #[validate(length(min = 1, message = "new_password is required"))]
+    pub new_password: String,
+}
+
+/// The caller's own account, including private fields
+#[derive(Serialize)]
+struct MeResponse {
+    id: String,
+    username: String,
+    email: Option<String>,
+    email_verified: bool,
+    wallet: Option<String>,
+    roles: Vec<String>,
+    bot: bool,
+    mfa_enabled: bool,
+    #[serde(flatten)]
+    profile: UserProfile,
+}
+
+impl From<&User> for MeResponse {
+    fn from(user: &User) -> Self {
+        MeResponse {
+            id: user
+                .id
+                .as_ref()
+                .map(|id| id.id.to_raw())
+                .unwrap_or_default(),
+            username: user.username.clone(),
+            email: user.email.clone(),
+            email_verified: user.email_verified,
+            wallet: user.wallet.clone(),
+            roles: user.role_names(),
+            bot: user.bot,
+            mfa_enabled: user.is_mfa_enabled(),
+            profile: user.profile.clone(),
+        }
+    }
+}
+
+/// Fields of another user visible to any authenticated caller
+#[derive(Serialize)]
+struct PublicProfileResponse {
+    id: String,
+    username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bio: Option<String>,
+    bot: bool,
+}
+
+impl From<User> for PublicProfileResponse {
+    fn from(user: User) -> Self {
+        PublicProfileResponse {
+            id: user.id.map(|id| id.id.to_raw()).unwrap_or_default(),
+            username: user.username,
+            display_name: user.profile.display_name,
+            avatar_url: user.profile.avatar_url,
+            bio: user.profile.bio,
+            bot: user.bot,
+        }
+    }
+}
+
+impl PublicProfileResponse {
+    /// Stand-in for the sender of messages whose account was deleted
+    fn deleted_user() -> Self {
+        PublicProfileResponse {
+            id: DELETED_USER_ID.to_string(),
+            username: DELETED_USER_NAME.to_string(),
+            display_name: None,
+            avatar_url: None,
+            bio: None,
+            bot: false,
+        }
+    }
+}
+
+/// Response payload carrying a freshly issued JWT
+#[derive(Serialize)]
+struct TokenResponse {
+    token: String,
+}
+
+/// GET /api/me
+pub async fn get_me(auth: AuthenticatedUser) -> HttpResponse {
+    HttpResponse::Ok().json(MeResponse::from(&auth.user))
+}
+
+/// PATCH /api/me
+///
+/// Account management: API keys cannot edit the profile.
+pub async fn update_me(
+    auth: AuthenticatedUser,
+    body: web::Json<UpdateProfileRequest>,
+    profiles: web::Data<ProfileService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return AuthError::InvalidProfile(e.to_string()).error_response();
+    }
+    match profiles
+        .update_profile(db.get_ref(), &auth.user, body.into_inner().into())
+        .await
+    {
+        Ok(user) => HttpResponse::Ok().json(MeResponse::from(&user)),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// GET /api/users/{id}
+pub async fn get_user_profile(
+    _auth: AuthenticatedUser,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let user_id = path.into_inner();
+    if user_id == DELETED_USER_ID {
+        return HttpResponse::Ok().json(PublicProfileResponse::deleted_user());
+    }
+    match <Database as UserDataTrait>::find_user_by_id(&db, &user_id).await {
+        Some(user) if !user.is_deactivated() => {
+            HttpResponse::Ok().json(PublicProfileResponse::from(user))
+        }
+        _ => AuthError::UserNotFound.error_response(),
+    }
+}
+
+/// POST /api/me/email
+pub async fn change_email(
+    req: HttpRequest,
+    auth: AuthenticatedUser,
+    body: web::Json<ChangeEmailRequest>,
+    profiles: web::Data<ProfileService>,
+    throttle: web::Data<LoginThrottleService>,
+    verification: web::Data<EmailVerificationService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if body.validate().is_err() {
+        return AuthError::InvalidEmail.error_response();
+    }
+    let ip = client_ip(&req);
+    if let Err(e) = throttle.check(&auth.user.username, &ip).await {
+        return e.error_response();
+    }
+
+    let user = match profiles
+        .change_email(
+            db.get_ref(),
+            &auth.user,
+            &body.current_password,
+            &body.email,
+        )
+        .await
+    {
+        Ok(user) => user,
+        Err(e) => return reject_credentials(&throttle, &auth.user, &ip, e).await,
+    };
+    if !user.email_verified {
+        if let Err(e) = verification.send_verification(&user).await {
+            // The change is stored; the user can request a new link
+            warn!("Could not send verification after e-mail change: {}", e);
+        }
+    }
+    HttpResponse::Ok().json(MeResponse::from(&user))
+}
+
+/// POST /api/me/password
+pub async fn change_password(
+    req: HttpRequest,
+    auth: AuthenticatedUser,
+    body: web::Json<ChangePasswordRequest>,
+    profiles: web::Data<ProfileService>,
+    throttle: web::Data<LoginThrottleService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_user_session() {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return HttpResponse::BadRequest().body(e.to_string());
+    }
+    let ip = client_ip(&req);
+    if let Err(e) = throttle.check(&auth.user.username, &ip).await {
+        return e.error_response();
+    }
+
+    let user = match profiles
+        .change_password(
+            db.get_ref(),
+            &auth.user,
+            &body.current_password,
+            &body.new_password,
+        )
+        .await
+    {
+        Ok(user) => user,
+        Err(e) => return reject_credentials(&throttle, &auth.user, &ip, e).await,
+    };
+    match generate_token_for_user(&user) {
+        Ok(token) => HttpResponse::Ok().json(TokenResponse { token }),
+        Err(e) => {
+            error!("Token generation failed: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Counts wrong current passwords like failed logins, then answers the error.
+async fn reject_credentials(
+    throttle: &LoginThrottleService,
+    user: &User,
+    ip: &str,
+    e: AuthError,
+) -> HttpResponse {
+    if matches!(e, AuthError::InvalidCurrentPassword) {
+        if let Err(e) = throttle.record_failure(&user.username, ip).await {
+            error!("Could not record password failure: {}", e);
+        }
+    }
+    e.error_response()
+}
+
+fn validate_avatar_url(url: &str) -> Result<(), ValidationError> {
+    let url = url.trim();
+    if url.is_empty()
+        || ((url.starts_with("https://") || url.starts_with("http://")) && url.validate_url())
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("avatar_url")
+            .with_message("avatar_url must be an http(s) URL".into()))
+    }
+}
+
+fn validate_locale(locale: &str) -> Result<(), ValidationError> {
+    let locale = locale.trim();
+    if locale.is_empty() || is_valid_locale(locale) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("locale").with_message("locale must be a BCP 47 tag".into()))
+    }
+}
+
+fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
+    let timezone = timezone.trim();
+    if timezone.is_empty() || is_valid_timezone(timezone) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("timezone")
+            .with_message("timezone must be an IANA time zone name".into()))
+    }
+}
@@ -0,0 +1,261 @@
+//! Role Handlers Module
+//! Implements the dynamic RBAC administration API.
+//!
+//! All endpoints require a valid token whose user currently holds `admin:all`.
+//!
+//! Endpoints
+//! - GET    /api/permissions              -> every permission string
+//! - GET    /api/roles                    -> list roles
+//! - POST   /api/roles                    -> create a custom role
+//!   { "name": "support", "description": "...", "permissions": ["message:delete"] }
+//! - GET    /api/roles/{name}             -> get one role
+//! - PATCH  /api/roles/{name}             -> update description and/or permissions
+//! - DELETE /api/roles/{name}             -> delete a custom role (revoked from users)
+//! - POST   /api/users/{id}/roles         -> assign { "role": "support" }
+//! - DELETE /api/users/{id}/roles/{name}  -> revoke
+//!
+//! Errors are `RoleError` JSON bodies (404 not found, 409 duplicate, 400 invalid,
+//! 403 built-in protected, 409 revoking `admin` from the last admin). Changing a
+//! user's roles bumps its `token_version`, so previously issued tokens stop being
+//! accepted.
+
+use actix_web::{web, HttpResponse, ResponseError};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::application::services::role_service::RoleService;
+use crate::error::RoleError;
+use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::role::{Permission, Role};
+use crate::models::traits::user_data_trait::UserDataTrait;
+
+/// Request payload for creating a role
+#[derive(Deserialize, Validate)]
+pub struct CreateRoleRequest {
+    /// Unique role name (`[a-z0-9_-]`, 2..=32 chars)
+    #[validate(length(min = 2, max = 32, message = "role name must be 2-32 characters"))]
+    pub name: String,
+    /// Human readable description
+    #[serde(default)]
+    #[validate(length(max = 200, message = "description too long"))]
+    pub description: String,
+    /// Permission strings, e.g. "message:delete"
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// Request payload for updating a role (partial)
+#[derive(Deserialize, Validate)]
+pub struct UpdateRoleRequest {
+    #[validate(length(max = 200, message = "description too long"))]
+    pub description: Option<String>,
+    pub permissions: Option<Vec<Permission>>,
+}
+
+/// Request payload for assigning a role to a user
+#[derive(Deserialize, Validate)]
+pub struct AssignRoleRequest {
+    #[validate(length(min = 1, message = "role is required"))]
+    pub role: String,
+}
+
+/// Response payload describing a user's roles after a change
+#[derive(Serialize)]
+struct UserRolesResponse {
+    user_id: String,
+    roles: Vec<String>,
+    permissions: Vec<String>,
+}
+
+/// GET /api/permissions
+pub async fn list_permissions(auth: AuthenticatedUser) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    let all: Vec<String> = Permission::ALL.iter().map(|p| p.to_string()).collect();
+    HttpResponse::Ok().json(all)
+}
+
+/// GET /api/roles
+pub async fn list_roles(auth: AuthenticatedUser, roles: web::Data<RoleService>) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    match roles.list_roles().await {
+        Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// GET /api/roles/{name}
+pub async fn get_role(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    roles: web::Data<RoleService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    match roles.get_role(&path.into_inner()).await {
+        Ok(role) => HttpResponse::Ok().json(role),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/roles
+pub async fn create_role(
+    auth: AuthenticatedUser,
+    body: web::Json<CreateRoleRequest>,
+    roles: web::Data<RoleService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        warn!("POST /roles: validation failed -> {:?}", e);
+        return RoleError::InvalidRole(e.to_string()).error_response();
+    }
+
+    let name = body.name.trim().to_lowercase();
+    match roles
+        .create_role(&name, &body.description, &body.permissions)
+        .await
+    {
+        Ok(role) => {
+            info!("POST /roles: {} created by {}", role.name, auth.user.username);
+            HttpResponse::Created().json(role)
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+/// PATCH /api/roles/{name}
+pub async fn update_role(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<UpdateRoleRequest>,
+    roles: web::Data<RoleService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return RoleError::InvalidRole(e.to_string()).error_response();
+    }
+
+    let body = body.into_inner();
+    match roles
+        .update_role(&path.into_inner(), body.description, body.permissions)
+        .await
+    {
+        Ok(role) => HttpResponse::Ok().json(role),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// DELETE /api/roles/{name}
+pub async fn delete_role(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    roles: web::Data<RoleService>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    match roles.delete_role(&path.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// POST /api/users/{id}/roles
+pub async fn assign_role(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: web::Json<AssignRoleRequest>,
+    roles: web::Data<RoleService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    if let Err(e) = body.validate() {
+        return RoleError::InvalidRole(e.to_string()).error_response();
+    }
+
+    let role = match roles.get_role(&body.role).await {
+        Ok(role) => role,
+        Err(e) => return e.error_response(),
+    };
+    let user_id = path.into_inner();
+    update_user_roles(&db, &user_id, |current| {
+        let mut next = current.to_vec();
+        if !next.iter().any(|r| r.name == role.name) {
+            next.push(role);
+        }
+        next
+    })
+    .await
+}
+
+/// DELETE /api/users/{id}/roles/{name}
+pub async fn revoke_role(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    let (user_id, role_name) = path.into_inner();
+    update_user_roles(&db, &user_id, |current| {
+        current
+            .iter()
+            .filter(|r| r.name != role_name)
+            .cloned()
+            .collect()
+    })
+    .await
+}
+
+/// Loads the user, applies `change` to its roles and persists the result when it
+/// differs (bumping the token version).
+async fn update_user_roles<F>(db: &Database, user_id: &str, change: F) -> HttpResponse
+where
+    F: FnOnce(&[Role]) -> Vec<Role>,
+{
+    let user = match <Database as UserDataTrait>::find_user_by_id(db, user_id).await {
+        Some(user) => user,
+        None => return RoleError::UserNotFound.error_response(),
+    };
+
+    let next = change(&user.roles);
+    if let Err(e) = RoleService::ensure_admin_remains(db, &user, &next).await {
+        return e.error_response();
+    }
+    let unchanged = next.len() == user.roles.len()
+        && next.iter().zip(&user.roles).all(|(a, b)| a.name == b.name);
+    let user = if unchanged {
+        user
+    } else {
+        match <Database as UserDataTrait>::set_user_roles(db, user_id, next).await {
+            Some(updated) => {
+                info!(
+                    "User {} roles changed to {:?}",
+                    updated.username,
+                    updated.role_names()
+                );
+                updated
+            }
+            None => return RoleError::DatabaseError.error_response(),
+        }
+    };
+
+    HttpResponse::Ok().json(UserRolesResponse {
+        user_id: user_id.to_string(),
+        roles: user.role_names(),
+        permissions: user.permission_names(),
+    })
+}
@@ -12,10 +12,47 @@ use actix_web::web;
 /// All routes are prefixed with '/api' and include:
 /// - GET    /tasks       -> Retrieve all tasks
 /// - POST   /tasks       -> Create a new task
-/// - PATCH  /tasks/{uuid}-> Update an existing task
+/// - GET    /tasks/calendar.ics?token= -> iCalendar feed of tasks with a due date (feed token, no login)
+/// - POST/DELETE /tasks/calendar/token -> Create or revoke the calendar feed token
+/// - POST   /tasks/import -> Create tasks from an uploaded .ics file (multipart)
+/// - PATCH  /tasks/{uuid}-> Partially update an existing task
+/// - DELETE /tasks/{uuid}-> Delete a task
+/// - GET    /tasks/{uuid}-> Task with parent, subtasks and blocking relations
+/// - POST   /tasks/{uuid}/checklist, PATCH/DELETE /tasks/{uuid}/checklist/{item_id} -> Checklist
+/// - POST   /tasks/{uuid}/dependencies, DELETE /tasks/{uuid}/dependencies/{blocker} -> Blockers
+/// - GET    /tasks/{uuid}/activity, POST /tasks/{uuid}/comments -> Timeline and comments
+/// - POST/DELETE /tasks/{uuid}/watch -> Watch or unwatch a task
+/// - GET    /notifications, POST /notifications/{id}/read -> Stored notifications (task reminders)
+/// - GET/POST /projects, POST /projects/{id}/members, GET/POST /projects/{id}/boards -> Projects
+/// - GET /boards/{id}, PUT /boards/{id}/columns -> Kanban board and its columns
+/// - POST /boards/{id}/cards, POST /boards/{id}/cards/{card_id}/move, DELETE /boards/{id}/cards/{card_id} -> Cards
 /// - POST   /register    -> Register a new user
 /// - POST   /login       -> Authenticate a user
+/// - POST   /auth/wallet/challenge -> Challenge to sign for a wallet login
+/// - POST   /auth/password/forgot|reset -> Password reset by e-mailed token
+/// - GET    /auth/verify-email, POST /auth/verify-email/resend -> E-mail verification
+/// - POST   /auth/lockouts/unlock -> Lift a login lockout (admin:all)
+/// - POST   /auth/mfa/totp/enroll|confirm|disable, /auth/mfa/verify -> TOTP two-factor auth
+/// - GET    /auth/oidc/providers, /auth/oidc/{provider}/authorize|callback -> External login
+/// - /roles, /permissions, /users/{id}/roles -> RBAC administration (admin:all)
+/// - GET    /users?q=&wallet=&cursor=&limit= -> User directory search (paginated)
+/// - POST/DELETE /users/{id}/block -> Block or unblock an account (admin:all)
+/// - GET/PATCH /me, POST /me/email|password, GET /users/{id} -> Own profile, credential changes, public profiles
+/// - DELETE /me, POST /me/deactivate, GET /me/export -> Scheduled deletion, deactivation, data export
+/// - GET /me/credentials, POST/DELETE /me/wallets, /me/identities -> Linked wallets and external identities
+/// - POST   /me/merge, /users/{id}/merge -> Merge a duplicate account (self-service or admin:all)
+/// - POST   /bots, /api-keys (GET, POST, DELETE /{id}) -> Bot accounts and scoped API keys
+/// - POST   /ws/ticket, GET /ws/chat -> WebSocket ticket and chat connection
+/// - POST   /messages/{id}/task -> Create a task from a chat message
+///
+/// Outside '/api':
+/// - GET    /.well-known/jwks.json -> Public keys for verifying issued JWTs
 pub fn config(cfg: &mut web::ServiceConfig) {
+    // GET endpoint publishing the JWT verification keys (standard location)
+    cfg.route(
+        "/.well-known/jwks.json",
+        web::get().to(crate::interfaces::api::jwks_handlers::jwks),
+    );
     cfg.service(
         // Create a scope for all API routes under /api prefix
         web::scope("/api")
@@ -29,11 +66,127 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                 "/tasks",
                 web::post().to(crate::interfaces::api::task_handlers::add_task),
             )
+            // Calendar feed and import; registered before `/tasks/{uuid}` so
+            // `calendar.ics` is not taken for a task UUID
+            .route(
+                "/tasks/calendar.ics",
+                web::get().to(crate::interfaces::api::calendar_handlers::calendar_feed),
+            )
+            .route(
+                "/tasks/calendar/token",
+                web::post().to(crate::interfaces::api::calendar_handlers::create_calendar_token),
+            )
+            .route(
+                "/tasks/calendar/token",
+                web::delete().to(crate::interfaces::api::calendar_handlers::revoke_calendar_token),
+            )
+            .route(
+                "/tasks/import",
+                web::post().to(crate::interfaces::api::calendar_handlers::import_calendar),
+            )
             // PATCH endpoint for updating an existing task
             .route(
                 "/tasks/{uuid}",
                 web::patch().to(crate::interfaces::api::task_handlers::update_task),
             )
+            // DELETE endpoint for removing a task
+            .route(
+                "/tasks/{uuid}",
+                web::delete().to(crate::interfaces::api::task_handlers::delete_task),
+            )
+            // Task structure: detail, checklist and blocking relations
+            .route(
+                "/tasks/{uuid}",
+                web::get().to(crate::interfaces::api::task_handlers::get_task_detail),
+            )
+            .route(
+                "/tasks/{uuid}/checklist",
+                web::post().to(crate::interfaces::api::task_handlers::add_checklist_item),
+            )
+            .route(
+                "/tasks/{uuid}/checklist/{item_id}",
+                web::patch().to(crate::interfaces::api::task_handlers::update_checklist_item),
+            )
+            .route(
+                "/tasks/{uuid}/checklist/{item_id}",
+                web::delete().to(crate::interfaces::api::task_handlers::remove_checklist_item),
+            )
+            .route(
+                "/tasks/{uuid}/dependencies",
+                web::post().to(crate::interfaces::api::task_handlers::add_dependency),
+            )
+            .route(
+                "/tasks/{uuid}/dependencies/{blocker}",
+                web::delete().to(crate::interfaces::api::task_handlers::remove_dependency),
+            )
+            // Task timeline: comments, recorded changes and watchers
+            .route(
+                "/tasks/{uuid}/activity",
+                web::get().to(crate::interfaces::api::task_handlers::get_task_activity),
+            )
+            .route(
+                "/tasks/{uuid}/comments",
+                web::post().to(crate::interfaces::api::task_handlers::add_comment),
+            )
+            .route(
+                "/tasks/{uuid}/watch",
+                web::post().to(crate::interfaces::api::task_handlers::watch_task),
+            )
+            .route(
+                "/tasks/{uuid}/watch",
+                web::delete().to(crate::interfaces::api::task_handlers::unwatch_task),
+            )
+            // Projects and Kanban boards
+            .route(
+                "/projects",
+                web::get().to(crate::interfaces::api::board_handlers::list_projects),
+            )
+            .route(
+                "/projects",
+                web::post().to(crate::interfaces::api::board_handlers::create_project),
+            )
+            .route(
+                "/projects/{id}/members",
+                web::post().to(crate::interfaces::api::board_handlers::add_project_member),
+            )
+            .route(
+                "/projects/{id}/boards",
+                web::get().to(crate::interfaces::api::board_handlers::list_boards),
+            )
+            .route(
+                "/projects/{id}/boards",
+                web::post().to(crate::interfaces::api::board_handlers::create_board),
+            )
+            .route(
+                "/boards/{id}",
+                web::get().to(crate::interfaces::api::board_handlers::get_board),
+            )
+            .route(
+                "/boards/{id}/columns",
+                web::put().to(crate::interfaces::api::board_handlers::set_columns),
+            )
+            .route(
+                "/boards/{id}/cards",
+                web::post().to(crate::interfaces::api::board_handlers::add_card),
+            )
+            .route(
+                "/boards/{id}/cards/{card_id}/move",
+                web::post().to(crate::interfaces::api::board_handlers::move_card),
+            )
+            .route(
+                "/boards/{id}/cards/{card_id}",
+                web::delete().to(crate::interfaces::api::board_handlers::remove_card),
+            )
+            // Notifications kept while the user was offline
+            .route(
+                "/notifications",
+                web::get().to(crate::interfaces::api::notification_handlers::list_notifications),
+            )
+            .route(
+                "/notifications/{id}/read",
+                web::post()
+                    .to(crate::interfaces::api::notification_handlers::mark_notification_read),
+            )
             // POST endpoint for user registration
             .route(
                 "/register",
@@ -44,16 +197,210 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                 "/login",
                 web::post().to(crate::interfaces::api::user_handlers::login),
             )
-            // GET endpoint to retrieve all users
+            // POST endpoint issuing a wallet login challenge
+            .route(
+                "/auth/wallet/challenge",
+                web::post().to(crate::interfaces::api::user_handlers::wallet_login_challenge),
+            )
+            // Password reset endpoints
+            .route(
+                "/auth/password/forgot",
+                web::post().to(crate::interfaces::api::account_handlers::forgot_password),
+            )
+            .route(
+                "/auth/password/reset",
+                web::post().to(crate::interfaces::api::account_handlers::reset_password),
+            )
+            // E-mail verification endpoints
+            .route(
+                "/auth/verify-email",
+                web::get().to(crate::interfaces::api::account_handlers::verify_email),
+            )
+            .route(
+                "/auth/verify-email/resend",
+                web::post().to(crate::interfaces::api::account_handlers::resend_verification),
+            )
+            // POST endpoint lifting a login lockout (admin)
+            .route(
+                "/auth/lockouts/unlock",
+                web::post().to(crate::interfaces::api::account_handlers::unlock_login),
+            )
+            // TOTP two-factor endpoints
+            .route(
+                "/auth/mfa/totp/enroll",
+                web::post().to(crate::interfaces::api::mfa_handlers::enroll_totp),
+            )
+            .route(
+                "/auth/mfa/totp/confirm",
+                web::post().to(crate::interfaces::api::mfa_handlers::confirm_totp),
+            )
+            .route(
+                "/auth/mfa/totp/disable",
+                web::post().to(crate::interfaces::api::mfa_handlers::disable_totp),
+            )
+            .route(
+                "/auth/mfa/verify",
+                web::post().to(crate::interfaces::api::mfa_handlers::verify_mfa),
+            )
+            // External (OAuth2 / OIDC) login endpoints
+            .route(
+                "/auth/oidc/providers",
+                web::get().to(crate::interfaces::api::user_handlers::oidc_providers),
+            )
+            .route(
+                "/auth/oidc/{provider}/authorize",
+                web::get().to(crate::interfaces::api::user_handlers::oidc_authorize),
+            )
+            .route(
+                "/auth/oidc/{provider}/callback",
+                web::get().to(crate::interfaces::api::user_handlers::oidc_callback),
+            )
+            // GET endpoint searching the user directory (paginated)
             .route(
                 "/users",
-                web::get().to(crate::interfaces::api::user_handlers::get_all_users),
+                web::get().to(crate::interfaces::api::user_handlers::search_users),
             )
-            // DELETE endpoint to remove users with wallets
+            // Account blocking (admin:all)
+            .route(
+                "/users/{id}/block",
+                web::post().to(crate::interfaces::api::user_handlers::block_user),
+            )
+            .route(
+                "/users/{id}/block",
+                web::delete().to(crate::interfaces::api::user_handlers::unblock_user),
+            )
+            // DELETE endpoint to remove wallet-only users
             .route(
                 "/users/wallets",
                 web::delete().to(crate::interfaces::api::user_handlers::delete_wallet_users),
             )
+            // RBAC administration endpoints
+            .route(
+                "/permissions",
+                web::get().to(crate::interfaces::api::role_handlers::list_permissions),
+            )
+            .route(
+                "/roles",
+                web::get().to(crate::interfaces::api::role_handlers::list_roles),
+            )
+            .route(
+                "/roles",
+                web::post().to(crate::interfaces::api::role_handlers::create_role),
+            )
+            .route(
+                "/roles/{name}",
+                web::get().to(crate::interfaces::api::role_handlers::get_role),
+            )
+            .route(
+                "/roles/{name}",
+                web::patch().to(crate::interfaces::api::role_handlers::update_role),
+            )
+            .route(
+                "/roles/{name}",
+                web::delete().to(crate::interfaces::api::role_handlers::delete_role),
+            )
+            .route(
+                "/users/{id}/roles",
+                web::post().to(crate::interfaces::api::role_handlers::assign_role),
+            )
+            .route(
+                "/users/{id}/roles/{name}",
+                web::delete().to(crate::interfaces::api::role_handlers::revoke_role),
+            )
+            // Caller's own profile
+            .route(
+                "/me",
+                web::get().to(crate::interfaces::api::profile_handlers::get_me),
+            )
+            .route(
+                "/me",
+                web::patch().to(crate::interfaces::api::profile_handlers::update_me),
+            )
+            // Leaving the service and exporting one's own data
+            .route(
+                "/me",
+                web::delete().to(crate::interfaces::api::privacy_handlers::delete_me),
+            )
+            .route(
+                "/me/deactivate",
+                web::post().to(crate::interfaces::api::privacy_handlers::deactivate_me),
+            )
+            .route(
+                "/me/export",
+                web::get().to(crate::interfaces::api::privacy_handlers::export_me),
+            )
+            // Credential changes (current password required)
+            .route(
+                "/me/email",
+                web::post().to(crate::interfaces::api::profile_handlers::change_email),
+            )
+            .route(
+                "/me/password",
+                web::post().to(crate::interfaces::api::profile_handlers::change_password),
+            )
+            // Linked wallets and external identities
+            .route(
+                "/me/credentials",
+                web::get().to(crate::interfaces::api::account_link_handlers::get_credentials),
+            )
+            .route(
+                "/me/wallets/challenge",
+                web::post().to(crate::interfaces::api::account_link_handlers::wallet_challenge),
+            )
+            .route(
+                "/me/wallets",
+                web::post().to(crate::interfaces::api::account_link_handlers::link_wallet),
+            )
+            .route(
+                "/me/wallets/{address}",
+                web::delete().to(crate::interfaces::api::account_link_handlers::unlink_wallet),
+            )
+            .route(
+                "/me/identities/{provider}/authorize",
+                web::post()
+                    .to(crate::interfaces::api::account_link_handlers::link_identity_authorize),
+            )
+            .route(
+                "/me/identities/{provider}/{subject}",
+                web::delete().to(crate::interfaces::api::account_link_handlers::unlink_identity),
+            )
+            // Account merges (self-service with the duplicate's token, or admin:all)
+            .route(
+                "/me/merge",
+                web::post().to(crate::interfaces::api::account_link_handlers::merge_into_me),
+            )
+            .route(
+                "/users/{id}/merge",
+                web::post().to(crate::interfaces::api::account_link_handlers::merge_users),
+            )
+            // GET endpoint returning another user's public profile
+            .route(
+                "/users/{id}",
+                web::get().to(crate::interfaces::api::profile_handlers::get_user_profile),
+            )
+            // POST endpoint creating a bot account (admin:all)
+            .route(
+                "/bots",
+                web::post().to(crate::interfaces::api::api_key_handlers::create_bot),
+            )
+            // Scoped API keys for integrations and bots
+            .route(
+                "/api-keys",
+                web::get().to(crate::interfaces::api::api_key_handlers::list_api_keys),
+            )
+            .route(
+                "/api-keys",
+                web::post().to(crate::interfaces::api::api_key_handlers::create_api_key),
+            )
+            .route(
+                "/api-keys/{id}",
+                web::delete().to(crate::interfaces::api::api_key_handlers::revoke_api_key),
+            )
+            // Single-use ticket for the chat WebSocket handshake
+            .route(
+                "/ws/ticket",
+                web::post().to(crate::interfaces::api::chat_handlers::issue_ws_ticket),
+            )
             // WebSocket endpoint for chat
             .route(
                 "/ws/chat",
@@ -72,6 +419,10 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                 "/conversations/{id}/messages",
                 web::get().to(crate::interfaces::api::chat_handlers::get_messages),
             )
+            .route(
+                "/messages/{id}/task",
+                web::post().to(crate::interfaces::api::task_handlers::create_task_from_message),
+            )
             .route(
                 "/conversations/{id}/participants",
                 web::post().to(crate::interfaces::api::chat_handlers::add_participant),
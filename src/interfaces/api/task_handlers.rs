@@ -1,100 +1,595 @@
 //! Task Handlers Module
 //! Implements HTTP request handlers for task-related operations.
+//!
+//! Every endpoint requires authentication (Bearer JWT or API key); the caller
+//! is recorded as the creator of the tasks they create. API keys need the
+//! `task:update` scope to create or change tasks and `task:delete` to delete
+//! them, on the task's conversation if it has one. Commenting and watching
+//! count as changes; reading the timeline needs `task:read`.
+//!
+//! Access
+//! - See: creator, assignee, participants of the task's conversation, `task:read`
+//! - Update: creator, assignee, `task:update`
+//! - Delete: creator, `task:delete`
+//!
+//! Tasks the caller cannot see answer 404; visible tasks the caller may not
+//! change answer 403 `NotAllowed`. Checklist items and blocking relations are
+//! edited by whoever may change the task. Whoever sees a task may comment on
+//! it, read its activity timeline and watch it.
+//!
+//! `POST /api/messages/{id}/task` turns a chat message into a task of its
+//! conversation; the room is told through the chat server.
 
-use actix_web::{web, HttpResponse, Responder};
-use crate::models::entities::task::{AddTaskRequest, UpdateTaskUrl, Task};
+use actix::Addr;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use crate::application::services::message_task_service::MessageTaskService;
+use crate::application::services::task_service::{TaskQuery, TaskService};
+use crate::error::TaskError;
+use crate::infrastructure::websocket::chat_server::{BroadcastMessageTask, ChatServer};
+use crate::models::entities::task::{
+    AddChecklistItemRequest, AddDependencyRequest, AddTaskRequest, ChecklistItemUrl,
+    CreateTaskFromMessageRequest, DependencyUrl, UpdateChecklistItemRequest, UpdateTaskRequest,
+    UpdateTaskUrl,
+};
 use crate::infrastructure::database::surrealdb::Database;
+use crate::interfaces::api::auth::AuthenticatedUser;
+use crate::models::entities::role::Permission;
+use crate::models::entities::task_activity::AddCommentRequest;
 use crate::models::traits::task_data_trait::TaskDataTrait;
-use crate::error::TaskError;
-use validator::Validate;
-use uuid::Uuid;
-use log::{info, debug, warn, error};
+use log::{info, warn};
+use serde::Deserialize;
+
+/// Query parameters of the task listing
+#[derive(Deserialize)]
+pub struct ListTasksQuery {
+    /// `todo`, `in_progress` or `done`
+    pub status: Option<String>,
+    /// Assignee UUID, or `me` for the caller
+    pub assignee: Option<String>,
+    /// RFC 3339 instant; only tasks due strictly before it
+    pub due_before: Option<String>,
+    /// Case-insensitive substring of the name or description
+    pub q: Option<String>,
+    /// `created_at` (default), `updated_at`, `due_date` or `priority`; `-` for descending
+    pub sort: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size
+    pub limit: Option<usize>,
+}
 
-/// Retrieves all tasks from the database
+/// Query parameters of a task update
+#[derive(Deserialize)]
+pub struct UpdateTaskQuery {
+    /// Move to `done` even while blocking tasks are open
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Lists the tasks the caller can see, one page at a time
+///
+/// The caller sees the tasks they created or are assigned to and those of the
+/// conversations they take part in; `task:read` shows every task.
 /// 
 /// # Returns
-/// - 200 OK with tasks array if found
-/// - 404 Not Found if no tasks exist
-pub async fn get_task(db: web::Data<Database>) -> impl Responder {
+/// - 200 OK with `{ "items": [...], "next_cursor": "..." | null }`; no match is
+///   an empty `items` array, not an error
+/// - 400 Bad Request (`InvalidQuery`) for malformed filters, sort or cursor
+pub async fn get_task(
+    auth: AuthenticatedUser,
+    query: web::Query<ListTasksQuery>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
     info!("GET /tasks: start");
-    // Query the database for all tasks using the TaskDataTrait implementation
-    let tasks = <Database as TaskDataTrait>::get_all_tasks(&db).await;  
-    match tasks {
-        Some(found_tasks) => {
-            info!("GET /tasks: ok count={}", found_tasks.len());
-            HttpResponse::Ok().json(found_tasks)
+    let query = query.into_inner();
+    let assignee = match query.assignee.as_deref().map(str::trim) {
+        Some("me") => auth.user.id_string(),
+        _ => query.assignee,
+    };
+    let task_query = TaskQuery {
+        status: query.status,
+        assignee,
+        due_before: query.due_before,
+        q: query.q,
+        sort: query.sort,
+        cursor: query.cursor,
+        limit: query.limit,
+    };
+    match tasks.list(db.get_ref(), &auth.user, auth.task_access(), task_query).await {
+        Ok(page) => {
+            info!("GET /tasks: ok count={}", page.items.len());
+            HttpResponse::Ok().json(page)
         },
-        None => {
-            warn!("GET /tasks: no tasks found");
-            HttpResponse::NotFound().json(TaskError::NoTasksFound)
+        Err(e) => {
+            warn!("GET /tasks: failed -> {}", e);
+            e.error_response()
         }
     }
 }
 
-/// Creates a new task in the system
+/// Creates a new task in the system; the caller becomes its creator
 /// 
 /// # Arguments
+/// * `auth` - Authenticated caller
 /// * `body` - JSON payload containing task details
 /// * `db` - Database connection
 ///
 /// # Returns
 /// - 200 OK with created task if successful
-/// - 400 Bad Request if validation fails
+/// - 400 Bad Request (`InvalidTask`, `AssigneeNotFound`) if validation fails
+/// - 403 Forbidden if an API key lacks the `task:update` scope
 /// - 500 Internal Server Error if creation fails
-pub async fn add_task(body: web::Json<AddTaskRequest>, db: web::Data<Database>) -> impl Responder {
+pub async fn add_task(
+    auth: AuthenticatedUser,
+    body: web::Json<AddTaskRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
     info!("POST /tasks: create requested");
-    // Validate incoming request data
-    if let Err(e) = body.validate() {
-        warn!("POST /tasks: validation failed -> {:?}", e);
-        return HttpResponse::BadRequest().json(TaskError::TaskCreationError);
+    let conversation = body
+        .conversation_id
+        .as_ref()
+        .map(|id| format!("conversation:{}", id));
+    if let Err(resp) = auth.require_scope(Permission::TaskUpdate, conversation.as_deref()) {
+        return resp;
     }
-
-    // Generate new UUID and prepare task data
-    let task_name = body.task_name.clone();
-    let new_uuid = Uuid::new_v4().to_string();
-    debug!("POST /tasks: creating uuid={} name={}", new_uuid, task_name);
-
-    // Attempt to add the new task to the database
-    let new_task = <Database as TaskDataTrait>::add_task(&db, Task::new(new_uuid.clone(), task_name)).await;
-
-    match new_task {
-        Some(created) => {
-            info!("POST /tasks: created uuid={}", new_uuid);
-            HttpResponse::Ok().json(created)
+    match tasks
+        .create(db.get_ref(), db.get_ref(), &auth.user, auth.task_access(), body.into_inner())
+        .await
+    {
+        Ok(created) => HttpResponse::Ok().json(created),
+        Err(e) => {
+            warn!("POST /tasks: failed -> {}", e);
+            e.error_response()
         },
-        None => {
-            error!("POST /tasks: DB create failed uuid={}", new_uuid);
-            HttpResponse::InternalServerError().json(TaskError::TaskCreationError)
+    }
+}
+
+/// Returns a task with its parent, subtasks and blocking relations
+///
+/// # Returns
+/// - 200 OK with the task plus `parent`, `subtasks`, `depends_on` and `blocks`
+///   (only the related tasks the caller can see)
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn get_task_detail(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    match tasks.detail(db.get_ref(), &auth.user, auth.task_access(), &uuid).await {
+        Ok(detail) => HttpResponse::Ok().json(detail),
+        Err(e) => {
+            warn!("GET /tasks/{uuid}: failed -> {}", e);
+            e.error_response()
         },
     }
 }
 
-/// Updates an existing task by UUID
+/// Applies a partial update to an existing task
+///
+/// Absent fields are kept; `null` clears `description`, `assignee`, `due_date`
+/// or `parent`. `?force=true` completes a task whose blockers are still open.
 /// 
 /// # Arguments
 /// * `update_task_url` - URL parameters containing task UUID
+/// * `query` - `force` flag
+/// * `body` - JSON payload with the fields to change
 /// * `db` - Database connection
 ///
 /// # Returns
 /// - 200 OK with updated task if successful
-/// - 404 Not Found if task doesn't exist
+/// - 400 Bad Request (`InvalidTask`, `AssigneeNotFound`) if validation fails
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if task (or the new parent) doesn't exist or is not visible
+/// - 409 Conflict (`DependencyCycle`, `BlockedBy`) for a cyclic parent or open blockers
 pub async fn update_task(
+    auth: AuthenticatedUser,
     update_task_url: web::Path<UpdateTaskUrl>,
-    db: web::Data<Database>
+    query: web::Query<UpdateTaskQuery>,
+    body: web::Json<UpdateTaskRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
 ) -> impl Responder {
     let uuid = update_task_url.into_inner().uuid;
-    info!("PUT /tasks/{uuid}: update requested");
-    // Attempt to update the task in the database
-    let update_result = <Database as TaskDataTrait>::update_task(&db, uuid.clone()).await;
-
-    match update_result {
-        Some(updated_task) => {
-            info!("PUT /tasks/{uuid}: updated");
-            HttpResponse::Ok().json(updated_task)
+    info!("PATCH /tasks/{uuid}: update requested");
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    let access = auth.task_access();
+    let mut patch = body.into_inner();
+    patch.force = query.force;
+    match tasks
+        .update(db.get_ref(), db.get_ref(), &auth.user, access, &uuid, patch)
+        .await
+    {
+        Ok(updated_task) => HttpResponse::Ok().json(updated_task),
+        Err(e) => {
+            warn!("PATCH /tasks/{uuid}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Deletes a task by UUID
+///
+/// # Returns
+/// - 204 No Content if deleted
+/// - 403 Forbidden (`NotAllowed`) if the caller may not delete the task, or if
+///   an API key lacks the `task:delete` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn delete_task(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    info!("DELETE /tasks/{uuid}: delete requested");
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskDelete).await {
+        return resp;
+    }
+    match tasks.delete(db.get_ref(), &auth.user, auth.task_access(), &uuid).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            warn!("DELETE /tasks/{uuid}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Makes a task wait for another one
+///
+/// # Returns
+/// - 200 OK with the task detail
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if either task doesn't exist or is not visible to the caller
+/// - 409 Conflict (`DependencyCycle`) if the blocker (indirectly) waits for the task
+pub async fn add_dependency(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    body: web::Json<AddDependencyRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    let blocker = body.into_inner().depends_on;
+    match tasks
+        .add_dependency(db.get_ref(), &auth.user, auth.task_access(), &uuid, &blocker)
+        .await
+    {
+        Ok(detail) => HttpResponse::Ok().json(detail),
+        Err(e) => {
+            warn!("POST /tasks/{uuid}/dependencies: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Removes a blocking relation
+///
+/// # Returns
+/// - 200 OK with the task detail
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn remove_dependency(
+    auth: AuthenticatedUser,
+    path: web::Path<DependencyUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let DependencyUrl { uuid, blocker } = path.into_inner();
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .remove_dependency(db.get_ref(), &auth.user, auth.task_access(), &uuid, &blocker)
+        .await
+    {
+        Ok(detail) => HttpResponse::Ok().json(detail),
+        Err(e) => {
+            warn!("DELETE /tasks/{uuid}/dependencies/{blocker}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Adds a checklist item (appended unless `position` is given)
+///
+/// # Returns
+/// - 200 OK with the updated task
+/// - 400 Bad Request (`InvalidTask`) if the text is empty or too long
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn add_checklist_item(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    body: web::Json<AddChecklistItemRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .add_checklist_item(db.get_ref(), &auth.user, auth.task_access(), &uuid, body.into_inner())
+        .await
+    {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => {
+            warn!("POST /tasks/{uuid}/checklist: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Edits, checks or moves a checklist item
+///
+/// # Returns
+/// - 200 OK with the updated task
+/// - 400 Bad Request (`InvalidTask`) if the text is empty or too long
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if the task or item doesn't exist
+pub async fn update_checklist_item(
+    auth: AuthenticatedUser,
+    path: web::Path<ChecklistItemUrl>,
+    body: web::Json<UpdateChecklistItemRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let ChecklistItemUrl { uuid, item_id } = path.into_inner();
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .update_checklist_item(
+            db.get_ref(),
+            &auth.user,
+            auth.task_access(),
+            &uuid,
+            &item_id,
+            body.into_inner(),
+        )
+        .await
+    {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => {
+            warn!("PATCH /tasks/{uuid}/checklist/{item_id}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Removes a checklist item
+///
+/// # Returns
+/// - 200 OK with the updated task
+/// - 403 Forbidden (`NotAllowed`) if the caller may not change the task, or if
+///   an API key lacks the `task:update` scope
+/// - 404 Not Found if the task or item doesn't exist
+pub async fn remove_checklist_item(
+    auth: AuthenticatedUser,
+    path: web::Path<ChecklistItemUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let ChecklistItemUrl { uuid, item_id } = path.into_inner();
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .remove_checklist_item(db.get_ref(), &auth.user, auth.task_access(), &uuid, &item_id)
+        .await
+    {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => {
+            warn!("DELETE /tasks/{uuid}/checklist/{item_id}: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Creates a task from a chat message and links the message to it
+///
+/// The body is optional; the task name defaults to the first line of the
+/// message. A `system` message announcing the task is posted in the conversation.
+///
+/// # Arguments
+/// * `path` - Message id (`message:<uuid>` or `<uuid>`)
+/// * `body` - Optional `CreateTaskFromMessageRequest`
+///
+/// # Returns
+/// - 200 OK with `{ "task": {...}, "message": {...} }` (the announcement)
+/// - 400 Bad Request (`InvalidTask`, `AssigneeNotFound`) if validation fails
+/// - 403 Forbidden if an API key lacks the `message:create` or `task:update`
+///   scope on the message's conversation
+/// - 404 Not Found if the message doesn't exist or the caller is not in its conversation
+/// - 409 Conflict (`MessageAlreadyLinked`) if the message already has a task
+pub async fn create_task_from_message(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    body: Option<web::Json<CreateTaskFromMessageRequest>>,
+    db: web::Data<Database>,
+    message_tasks: web::Data<MessageTaskService>,
+    chat_server: web::Data<Addr<ChatServer>>,
+) -> HttpResponse {
+    let raw_id = path.into_inner();
+    let message_id = raw_id.strip_prefix("message:").unwrap_or(&raw_id).to_string();
+    info!("POST /messages/{message_id}/task: create requested");
+    if auth.api_key.is_some() {
+        match message_tasks.find_message(&message_id).await {
+            Ok(Some(message)) => {
+                if let Err(resp) = auth.require_message_task_scope(&message) {
+                    return resp;
+                }
+            },
+            Ok(None) => return TaskError::MessageNotFound.error_response(),
+            Err(e) => return e.error_response(),
+        }
+    }
+    let req = body.map(web::Json::into_inner).unwrap_or_default();
+    match message_tasks
+        .create_from_message(db.get_ref(), db.get_ref(), &auth.user, &message_id, req)
+        .await
+    {
+        Ok(message_task) => {
+            let body = serde_json::json!({
+                "task": message_task.task,
+                "message": message_task.announcement,
+            });
+            chat_server.do_send(BroadcastMessageTask {
+                message_task,
+                sender_is_bot: auth.user.bot,
+            });
+            HttpResponse::Ok().json(body)
+        },
+        Err(e) => {
+            warn!("POST /messages/{message_id}/task: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+/// Returns the timeline of a task: comments and recorded changes, oldest first
+///
+/// # Returns
+/// - 200 OK with `[{ "type": "comment", ... } | { "type": "activity", "kind": "status_changed", "from": "todo", "to": "done", ... }]`
+/// - 403 Forbidden if an API key lacks the `task:read` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn get_task_activity(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskRead).await {
+        return resp;
+    }
+    match tasks.timeline(db.get_ref(), &auth.user, auth.task_access(), &uuid).await {
+        Ok(entries) => HttpResponse::Ok().json(entries),
+        Err(e) => {
+            warn!("GET /tasks/{uuid}/activity: failed -> {}", e);
+            e.error_response()
         },
-        None => {
-            warn!("PUT /tasks/{uuid}: not found");
-            HttpResponse::NotFound().json(TaskError::NoTaskFoundWithId)
+    }
+}
+
+/// Comments on a task; `@username` mentions users who can see it
+///
+/// The caller and the mentioned users start watching the task.
+///
+/// # Returns
+/// - 200 OK with the comment
+/// - 400 Bad Request (`InvalidTask`) for an empty or too long comment
+/// - 403 Forbidden if an API key lacks the `task:update` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn add_comment(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    body: web::Json<AddCommentRequest>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    let uuid = task_url.into_inner().uuid;
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .add_comment(
+            db.get_ref(),
+            db.get_ref(),
+            &auth.user,
+            auth.task_access(),
+            &uuid,
+            body.into_inner(),
+        )
+        .await
+    {
+        Ok(comment) => HttpResponse::Ok().json(comment),
+        Err(e) => {
+            warn!("POST /tasks/{uuid}/comments: failed -> {}", e);
+            e.error_response()
         },
     }
-}
\ No newline at end of file
+}
+
+/// Starts watching a task: its new comments and changes are pushed over the WebSocket
+///
+/// # Returns
+/// - 200 OK with the task
+/// - 403 Forbidden if an API key lacks the `task:update` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn watch_task(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    set_watching(auth, task_url.into_inner().uuid, db, tasks, true).await
+}
+
+/// Stops watching a task
+///
+/// # Returns
+/// - 200 OK with the task
+/// - 403 Forbidden if an API key lacks the `task:update` scope
+/// - 404 Not Found if task doesn't exist or is not visible to the caller
+pub async fn unwatch_task(
+    auth: AuthenticatedUser,
+    task_url: web::Path<UpdateTaskUrl>,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+) -> impl Responder {
+    set_watching(auth, task_url.into_inner().uuid, db, tasks, false).await
+}
+
+async fn set_watching(
+    auth: AuthenticatedUser,
+    uuid: String,
+    db: web::Data<Database>,
+    tasks: web::Data<TaskService>,
+    watching: bool,
+) -> HttpResponse {
+    if let Err(resp) = require_task_scope(&auth, &db, &uuid, Permission::TaskUpdate).await {
+        return resp;
+    }
+    match tasks
+        .set_watching(db.get_ref(), &auth.user, auth.task_access(), &uuid, watching)
+        .await
+    {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => {
+            warn!("/tasks/{uuid}/watch: failed -> {}", e);
+            e.error_response()
+        },
+    }
+}
+
+// For API-key callers, checks `permission` on the conversation of the task
+// `uuid`; unknown tasks are left to the service, which answers 404
+async fn require_task_scope(
+    auth: &AuthenticatedUser,
+    db: &Database,
+    uuid: &str,
+    permission: Permission,
+) -> Result<(), HttpResponse> {
+    if auth.api_key.is_none() {
+        return Ok(());
+    }
+    match db.find_task(uuid.to_string()).await {
+        Some(task) => auth.require_task_scope(permission, &task),
+        None => Ok(()),
+    }
+}
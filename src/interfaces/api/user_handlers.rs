@@ -4,35 +4,89 @@
 //! Endpoints
 //! - POST /api/register
 //!   Request JSON:
-//!     { "username": "Alice", "email": "alice@example.com", "password": "Super$ecret123" }
+//!   { "username": "Alice", "email": "alice@example.com", "password": "Super$ecret123" }
 //!   200 OK JSON:
-//!     { "create": "success", "message": "User created successfully" }
+//!   { "create": "success", "message": "User created successfully" }
 //!   400 Bad Request: "Username must contain only letters" | "Invalid email"
+//!   | `WeakPassword` (longitud PASSWORD_MIN/MAX_LENGTH o contraseña filtrada)
 //!   500 Internal Server Error: "internal error: ..." o vacío
+//!   La cuenta queda con `email_verified = false` y se envía un enlace de verificación.
 //!
 //! - POST /api/login
 //!   Request JSON (email o username; uno requerido):
-//!     { "email": "alice@example.com", "password": "Super$ecret123" }
-//!     { "username": "Alice", "password": "Super$ecret123" }
+//!   { "email": "alice@example.com", "password": "Super$ecret123" }
+//!   { "username": "Alice", "password": "Super$ecret123" }
 //!   400 Bad Request: "email or username is required"
 //!   401 Unauthorized: credenciales inválidas o fila legacy sin hash
+//!   403 Forbidden: `EmailNotVerified` si EMAIL_VERIFICATION_REQUIRED_FOR incluye `login`
+//!   429 Too Many Requests: `TooManyLoginAttempts` (+ Retry-After) tras fallos repetidos
+//!   por cuenta o IP (retardo progresivo y bloqueo temporal; ver `LoginThrottleService`).
+//!   Las cuentas inexistentes se tratan igual, para no revelar si existen.
 //!   200 OK JSON:
-//!     { "token": "<JWT>" }
+//!   { "token": "<JWT>" }
+//!   Con 2FA (TOTP) activo, el login por contraseña responde en su lugar:
+//!   { "mfa_required": true, "mfa_token": "<JWT corto>", "expires_in": 300 }
+//!   y el JWT real se obtiene en POST /api/auth/mfa/verify.
+//!   Login con wallet: POST /api/auth/wallet/challenge { "wallet": "0x.." } devuelve
+//!   `nonce` y `message`; luego { "wallet", "nonce", "signature" } (firma
+//!   `personal_sign` del mensaje). Aplica las mismas reglas (bloqueo, 2FA,
+//!   verificación de email) que el login por contraseña.
+//!
+//! - GET /api/auth/oidc/providers -> ["github", "google"]
+//! - GET /api/auth/oidc/{provider}/authorize -> 302 al proveedor (code + PKCE S256)
+//! - GET /api/auth/oidc/{provider}/callback?code=..&state=..
+//!   200 OK: misma respuesta que /api/login (token o desafío MFA)
+//!   La cuenta se encuentra por identidad vinculada, se vincula por email verificado
+//!   o se crea sin password. Un SPA puede usar su propia ruta como redirect_uri y
+//!   reenviar `code` y `state` a este endpoint.
+//!
+//! - GET /api/users?q=ali&wallet=0x..&limit=20&cursor=<next_cursor>  (Bearer JWT o API key)
+//!   200 OK JSON: { "items": [{ "id": "<uuid>", "username": "alice", "display_name": "Alice",
+//!   "wallet": null, "bot": false }], "next_cursor": "<opaque>" | null }
+//!   Búsqueda por prefijo (username o nombre visible) y wallet exacta, ordenada por
+//!   username. Excluye cuentas bloqueadas; el `email` solo se incluye para admins.
+//!   400 Bad Request: `InvalidSearch` (limit fuera de rango, cursor inválido)
+//!
+//! - POST /api/users/{id}/block, DELETE /api/users/{id}/block  (`admin:all`)
+//!   204 No Content | 404 Not Found: `UserNotFound`
+//!   Una cuenta bloqueada pierde sus tokens y recibe 403 `AccountBlocked` al iniciar sesión.
 //!
 //! JWT (HS256):
-//! - Claims: { sub: "<uuid>", exp: <epoch>, iat: <epoch>, username: "<name>", roles: ["user"] }
+//! - Claims: { sub: "<uuid>", exp: <epoch>, iat: <epoch>, username: "<name>",
+//!   roles: ["user"], perms: ["channel:read", ...], ver: <token_version> }
+//! - `roles`/`perms` se toman de los roles persistidos del usuario.
+//! - `ver` debe coincidir con `User::token_version`; cambiar roles invalida tokens previos.
 //! - SECRET_KEY requerido (env). Expiración configurable por JWT_EXP_SECONDS.
 //!
 //! Seguridad:
-//! - Password con bcrypt y coste configurable (BCRYPT_COST).
-
-use crate::infrastructure::auth::jwt::{generate_token, verify_password};
+//! - Password con Argon2id (parámetros ARGON2_*); los hashes bcrypt existentes se
+//!   verifican y se re-hashean a Argon2id tras un login correcto.
+//! - Política de contraseñas (longitud, lista de contraseñas filtradas) en el registro.
+
+use crate::application::services::account_link_service::AccountLinkService;
+use crate::application::services::email_verification_service::EmailVerificationService;
+use crate::application::services::login_throttle_service::LoginThrottleService;
+use crate::application::services::mfa_service::MfaService;
+use crate::application::services::account_deletion_service::reactivate_on_login;
+use crate::application::services::oidc_service::{OidcService, OIDC_STATE_COOKIE};
+use crate::application::services::password_policy::PasswordPolicy;
+use crate::application::services::user_directory_service::{DirectoryQuery, UserDirectoryService};
+use crate::error::AuthError;
+use crate::infrastructure::auth::jwt::generate_token_for_user;
+use crate::infrastructure::auth::password::{
+    hash_password, needs_rehash, verify_password, verify_password_dummy,
+};
+use crate::interfaces::api::auth::{client_ip, AuthenticatedUser};
 use crate::infrastructure::database::surrealdb::Database;
+use crate::models::entities::role::Permission;
 use crate::models::entities::user::User;
 use crate::models::traits::user_data_trait::UserDataTrait;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::cookie::time::Duration as CookieDuration;
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError};
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use validator::ValidateEmail;
 
 /// Request payload for user registration
 #[derive(Deserialize)]
@@ -59,9 +113,23 @@ pub struct LoginRequest {
     /// Password of the account (optional for wallet-only flow)
     #[serde(default)]
     password: Option<String>,
-    /// Wallet address (optional). If provided alone, a JWT will be returned for the wallet user.
+    /// Wallet address (optional). Signs in with the wallet instead of a password,
+    /// together with `nonce` and `signature` of a `POST /api/auth/wallet/challenge`.
     #[serde(default)]
     wallet: Option<String>,
+    /// Nonce of the wallet login challenge
+    #[serde(default)]
+    nonce: Option<String>,
+    /// `personal_sign` signature of the challenge message
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+/// Request payload for a wallet login challenge
+#[derive(Deserialize)]
+pub struct WalletLoginChallengeRequest {
+    /// Address to sign in with
+    wallet: String,
 }
 
 /// Response payload for successful authentication
@@ -71,6 +139,17 @@ struct AuthResponse {
     token: String,
 }
 
+/// Response payload when the password was correct but a second factor is required
+#[derive(Serialize)]
+struct MfaChallengeResponse {
+    /// Always true
+    mfa_required: bool,
+    /// Short-lived token to present at /api/auth/mfa/verify
+    mfa_token: String,
+    /// Lifetime of `mfa_token` in seconds
+    expires_in: usize,
+}
+
 /// Response payload for successful registration
 #[derive(Serialize)]
 struct RegistrationResponse {
@@ -80,12 +159,48 @@ struct RegistrationResponse {
     message: String,
 }
 
-/// Response payload for a safe user representation
+/// Query parameters of the user directory
+#[derive(Deserialize)]
+pub struct SearchUsersQuery {
+    /// Prefix of the username or display name
+    pub q: Option<String>,
+    /// Exact wallet address
+    pub wallet: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size
+    pub limit: Option<usize>,
+}
+
+/// Response payload for a safe user representation (directory entry)
 #[derive(Serialize)]
 pub struct SafeUserResponse {
+    pub id: String,
     pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// Only shown to admins
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
     pub wallet: Option<String>,
+    pub bot: bool,
+}
+
+impl SafeUserResponse {
+    /// Directory view of `user`; the e-mail is kept only if `show_email`.
+    fn from_user(user: User, show_email: bool) -> Self {
+        SafeUserResponse {
+            id: user.id.map(|id| id.id.to_raw()).unwrap_or_default(),
+            username: user.username,
+            display_name: user.profile.display_name,
+            avatar_url: user.profile.avatar_url,
+            email: user.email.filter(|_| show_email),
+            wallet: user.wallet,
+            bot: user.bot,
+        }
+    }
 }
 
 /// Handles user registration requests
@@ -93,6 +208,7 @@ pub struct SafeUserResponse {
 /// # Arguments
 /// * `user_data` - JSON payload containing registration details
 /// * `db` - Database connection
+/// * `verification` - Sends the e-mail verification link for traditional accounts
 ///
 /// # Returns
 /// - 200 OK if registration successful
@@ -101,6 +217,8 @@ pub struct SafeUserResponse {
 pub async fn register(
     user_data: web::Json<RegisterRequest>,
     db: web::Data<Database>,
+    verification: web::Data<EmailVerificationService>,
+    password_policy: web::Data<PasswordPolicy>,
 ) -> impl Responder {
     info!(
         "Register attempt: username={:?}, email={:?}, wallet={:?}",
@@ -142,12 +260,18 @@ pub async fn register(
         return HttpResponse::BadRequest().body("Username must contain only letters");
     }
 
-    // Basic email validation
-    if !email.contains('@') || email.trim().len() < 5 {
+    // Email validation (RFC 5322 syntax via `validator`)
+    if !email.trim().validate_email() {
         warn!("Register rejected: invalid email");
         return HttpResponse::BadRequest().body("Invalid email");
     }
 
+    // Password policy (length, breached-password list)
+    if let Err(e) = password_policy.check(&password) {
+        warn!("Register rejected: {}", e);
+        return e.error_response();
+    }
+
     // Create new user instance using traditional flow
     let user = match User::new(username.clone(), email, password) {
         Ok(user) => {
@@ -161,7 +285,15 @@ pub async fn register(
     };
 
     let log_id = user.username.clone();
-    persist_user_and_respond(&db, user, &log_id).await
+    let response = persist_user_and_respond(&db, user.clone(), &log_id).await;
+
+    // Send the verification link; registration succeeds even if mail fails
+    if response.status().is_success() {
+        if let Err(e) = verification.send_verification(&user).await {
+            warn!("Verification e-mail not sent for {}: {}", log_id, e);
+        }
+    }
+    response
 }
 
 /// Helper to persist user and return response to avoid duplication
@@ -170,7 +302,7 @@ async fn persist_user_and_respond(
     user: User,
     log_id: &str,
 ) -> HttpResponse {
-    match <Database as UserDataTrait>::add_user(&db, user).await {
+    match <Database as UserDataTrait>::add_user(db, user).await {
         Some(_) => {
             info!("Register success: id={}", log_id);
             HttpResponse::Ok().json(RegistrationResponse {
@@ -194,10 +326,20 @@ async fn persist_user_and_respond(
 /// # Returns
 /// - 200 OK with JWT token if authentication successful
 /// - 401 Unauthorized if credentials invalid
+/// - 403 Forbidden if the policy requires a verified e-mail and it is not
 /// - 500 Internal Server Error if token generation fails
-pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>) -> impl Responder {
+pub async fn login(
+    req: HttpRequest,
+    user_data: web::Json<LoginRequest>,
+    db: web::Data<Database>,
+    verification: web::Data<EmailVerificationService>,
+    mfa: web::Data<MfaService>,
+    throttle: web::Data<LoginThrottleService>,
+    links: web::Data<AccountLinkService>,
+) -> impl Responder {
     // Allow three flows:
-    // 1) Wallet-only flow: { "wallet": "0x..." } -> return JWT (create user if missing)
+    // 1) Wallet flow: { "wallet", "nonce", "signature" } of a signed challenge
+    //    -> same answer as the password flow (create user if missing)
     // 2) Traditional flow: email/username + password
     // 3) If neither present, reject
 
@@ -212,9 +354,19 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
         wallet.is_some()
     );
 
-    // Wallet-only flow: if wallet provided and no email/username/password, authenticate by wallet
+    // Wallet flow: the signed challenge proves control of the address
     if let Some(w) = wallet {
-        let w = w.to_lowercase();
+        let (nonce, signature) = match (&user_data.nonce, &user_data.signature) {
+            (Some(nonce), Some(signature)) => (nonce, signature),
+            _ => {
+                warn!("Wallet login rejected: nonce and signature are required");
+                return HttpResponse::BadRequest().body("nonce and signature are required");
+            }
+        };
+        let w = match links.verify_login(w, nonce, signature).await {
+            Ok(w) => w,
+            Err(e) => return e.error_response(),
+        };
         debug!("Wallet login attempt for: {}", w);
 
         // Try to find existing user by wallet
@@ -230,42 +382,14 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
                 return HttpResponse::InternalServerError().finish();
             }
         }
-
         let user = user.unwrap();
 
-        // Roles por defecto
-        let roles = vec!["user".to_string()];
-
-        // Extract user id
-        let user_id_str = match &user.id {
-            Some(thing) => match &thing.id {
-                surrealdb::sql::Id::String(s) => s.clone(),
-                surrealdb::sql::Id::Uuid(u) => u.to_string(),
-                _ => thing
-                    .id
-                    .to_string()
-                    .trim_matches('⟨')
-                    .trim_matches('⟩')
-                    .to_string(),
-            },
-            None => {
-                error!("User has no id Thing set");
-                return HttpResponse::InternalServerError().finish();
-            }
-        };
-
-        let token = match generate_token(&user_id_str, &user.username, &roles) {
-            Ok(token) => {
-                info!("Wallet login success for username={}", user.username);
-                token
-            }
-            Err(e) => {
-                error!("Token generation failed: {}", e);
-                return HttpResponse::InternalServerError().finish();
-            }
-        };
-
-        return HttpResponse::Ok().json(AuthResponse { token });
+        // A wallet linked to an e-mail account is subject to that account's rules
+        if !verification.policy().allows_login(&user) {
+            warn!("Login rejected: e-mail not verified for username={}", user.username);
+            return AuthError::EmailNotVerified.error_response();
+        }
+        return complete_login_response(&db, user, &mfa).await;
     }
 
     // Traditional flow requires email or username and a password
@@ -299,6 +423,16 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
         }
     }
 
+    // Brute-force protection: refuse before checking the password while the
+    // account or the client address is delayed or locked out. An existing account
+    // is throttled by its id, so its e-mail and username share one budget.
+    let identifier = email.or(username).unwrap_or_default();
+    let account = LoginThrottleService::account_subject(user.as_ref(), identifier);
+    let ip = client_ip(&req);
+    if let Err(e) = throttle.check(&account, &ip).await {
+        return e.error_response();
+    }
+
     let user = match user {
         Some(u) => {
             debug!("User found: {}", u.username);
@@ -306,7 +440,8 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
         }
         None => {
             warn!("User not found or legacy row filtered (no password)");
-            return HttpResponse::Unauthorized().finish();
+            verify_password_dummy(&password);
+            return reject_login(&throttle, &account, &ip).await;
         }
     };
 
@@ -318,7 +453,8 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
                 "User has no password hash (legacy row). username={}",
                 user.username
             );
-            return HttpResponse::Unauthorized().finish();
+            verify_password_dummy(&password);
+            return reject_login(&throttle, &account, &ip).await;
         }
     };
 
@@ -329,32 +465,95 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
             "Password verification failed for username={}",
             user.username
         );
-        return HttpResponse::Unauthorized().finish();
+        return reject_login(&throttle, &account, &ip).await;
+    }
+    if let Err(e) = throttle.record_success(&account).await {
+        warn!("Could not reset login failures: {}", e);
+    }
+
+    // Migración transparente: bcrypt (o parámetros Argon2 antiguos) -> Argon2id actual
+    if needs_rehash(stored_hash) {
+        upgrade_password_hash(&db, &user, stored_hash, &password).await;
+    }
+
+    // Enforce e-mail verification when configured
+    if !verification.policy().allows_login(&user) {
+        warn!("Login rejected: e-mail not verified for username={}", user.username);
+        return AuthError::EmailNotVerified.error_response();
     }
 
-    // Roles por defecto (hasta tener roles persistidos)
-    let roles = vec!["user".to_string()];
-
-    // Extraer ID puro desde Thing (evitando los brackets ⟨ ⟩ de SurrealDB)
-    let user_id_str = match &user.id {
-        Some(thing) => match &thing.id {
-            surrealdb::sql::Id::String(s) => s.clone(),
-            surrealdb::sql::Id::Uuid(u) => u.to_string(),
-            _ => thing
-                .id
-                .to_string()
-                .trim_matches('⟨')
-                .trim_matches('⟩')
-                .to_string(),
+    complete_login_response(&db, user, &mfa).await
+}
+
+/// Issues a challenge for signing in with a wallet
+///
+/// # Returns
+/// - 200 OK: `{ "wallet": "0x..", "nonce": "..", "message": "..", "expires_in": 300 }`;
+///   sign `message` with `personal_sign` and send it to `POST /api/login`
+/// - 400 Bad Request: `InvalidWallet`
+pub async fn wallet_login_challenge(
+    body: web::Json<WalletLoginChallengeRequest>,
+    links: web::Data<AccountLinkService>,
+) -> HttpResponse {
+    match links.login_challenge(&body.wallet).await {
+        Ok(challenge) => HttpResponse::Ok().json(challenge),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Re-hashes a verified password with the current Argon2id parameters. Failures
+/// are logged only: the login itself already succeeded.
+async fn upgrade_password_hash(db: &Database, user: &User, old_hash: &str, password: &str) {
+    let Some(user_id) = user.id_string() else {
+        return;
+    };
+    match hash_password(password) {
+        Ok(new_hash) => match db.upgrade_password_hash(&user_id, old_hash, &new_hash).await {
+            Some(_) => info!("Password hash upgraded to Argon2id for username={}", user.username),
+            None => warn!("Password hash not upgraded for username={}", user.username),
         },
-        None => {
-            error!("User has no id Thing set");
-            return HttpResponse::InternalServerError().finish();
-        }
+        Err(e) => error!("Password rehash failed for username={}: {}", user.username, e),
+    }
+}
+
+/// Counts the failed attempt and answers 401; identical for unknown accounts.
+async fn reject_login(throttle: &LoginThrottleService, identifier: &str, ip: &str) -> HttpResponse {
+    if let Err(e) = throttle.record_failure(identifier, ip).await {
+        error!("Could not record login failure: {}", e);
+    }
+    HttpResponse::Unauthorized().finish()
+}
+
+/// Final step of every credential check (password, OIDC): the MFA challenge when
+/// 2FA is enabled, otherwise the JWT. A deactivated account is reactivated once
+/// the sign-in is complete.
+async fn complete_login_response(db: &Database, user: User, mfa: &MfaService) -> HttpResponse {
+    if user.blocked {
+        warn!("Login rejected: account blocked for username={}", user.username);
+        return AuthError::AccountBlocked.error_response();
+    }
+    // Con 2FA activo se emite solo el mfa_token; el JWT real requiere el código TOTP
+    if user.is_mfa_enabled() {
+        return match mfa.issue_mfa_token(&user) {
+            Ok(mfa_token) => {
+                info!("Login requires 2FA for username={}", user.username);
+                HttpResponse::Ok().json(MfaChallengeResponse {
+                    mfa_required: true,
+                    mfa_token,
+                    expires_in: mfa.mfa_token_ttl_secs(),
+                })
+            }
+            Err(e) => e.error_response(),
+        };
+    }
+
+    let user = match reactivate_on_login(db, user).await {
+        Ok(user) => user,
+        Err(e) => return e.error_response(),
     };
 
-    // Generar token con claims extendidos
-    let token = match generate_token(&user_id_str, &user.username, &roles) {
+    // Generar token con los roles, permisos y versión persistidos
+    let token = match generate_token_for_user(&user) {
         Ok(token) => {
             info!("Login success for username={}", user.username);
             token
@@ -368,31 +567,203 @@ pub async fn login(user_data: web::Json<LoginRequest>, db: web::Data<Database>)
     HttpResponse::Ok().json(AuthResponse { token })
 }
 
-/// Handles request to get all users
+/// Query parameters of the provider callback
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    /// Set by the provider when the user denied access
+    error: Option<String>,
+}
+
+/// Lists the configured external login providers
 ///
 /// # Returns
-/// - 200 OK with a list of users (excluding sensitive data)
-pub async fn get_all_users(db: web::Data<Database>) -> impl Responder {
-    let users = <Database as UserDataTrait>::get_all_users(&db).await;
-
-    let safe_users: Vec<SafeUserResponse> = users
-        .into_iter()
-        .map(|u| SafeUserResponse {
-            username: u.username,
-            email: u.email,
-            wallet: u.wallet,
-        })
-        .collect();
+/// - 200 OK: `["github", "google"]`
+pub async fn oidc_providers(oidc: web::Data<OidcService>) -> impl Responder {
+    HttpResponse::Ok().json(oidc.provider_names())
+}
+
+/// Path prefix of the provider callbacks
+const OIDC_CALLBACK_PATH: &str = "/api/auth/oidc";
+
+/// Cookie binding an external login (or link) to the browser that started it;
+/// only sent back to the callback
+pub fn oidc_state_cookie(state: &str, oidc: &OidcService) -> Cookie<'static> {
+    Cookie::build(OIDC_STATE_COOKIE, state.to_string())
+        .path(OIDC_CALLBACK_PATH)
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(CookieDuration::seconds(oidc.state_ttl().num_seconds()))
+        .finish()
+}
+
+/// Starts an external login: redirects the browser to the provider
+///
+/// # Returns
+/// - 302 Found with `Location: <authorization URL>` and the state cookie
+/// - 404 Not Found: `UnknownProvider`
+/// - 502 Bad Gateway: provider discovery failed
+pub async fn oidc_authorize(path: web::Path<String>, oidc: web::Data<OidcService>) -> HttpResponse {
+    match oidc.start_login(&path.into_inner()).await {
+        Ok(authorization) => HttpResponse::Found()
+            .insert_header(("Location", authorization.url))
+            .cookie(oidc_state_cookie(&authorization.state, &oidc))
+            .finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Completes an external login from the provider redirect
+///
+/// # Returns
+/// - 200 OK: `{ "token": "<JWT>" }` or the MFA challenge (same as `/api/login`)
+/// - 400 Bad Request: missing code/state, denied access or `InvalidOrExpiredToken`
+///   (also when the state cookie of `oidc_authorize` is missing or differs)
+/// - 401 Unauthorized: `InvalidIdToken`
+/// - 403 Forbidden: `EmailNotVerified` (provider did not verify the e-mail)
+/// - 409 Conflict: `AccountLinkConflict`
+pub async fn oidc_callback(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<OidcCallbackQuery>,
+    oidc: web::Data<OidcService>,
+    db: web::Data<Database>,
+    mfa: web::Data<MfaService>,
+) -> HttpResponse {
+    let provider = path.into_inner();
+    if let Some(error) = &query.error {
+        warn!("OIDC callback ({}) returned error: {}", provider, error);
+        return HttpResponse::BadRequest().body(format!("provider error: {}", error));
+    }
+    let (code, state) = match (&query.code, &query.state) {
+        (Some(code), Some(state)) => (code, state),
+        _ => return HttpResponse::BadRequest().body("code and state are required"),
+    };
 
-    HttpResponse::Ok().json(safe_users)
+    let browser_state = req.cookie(OIDC_STATE_COOKIE);
+    let mut response = match oidc
+        .complete_login(
+            db.get_ref(),
+            &provider,
+            code,
+            state,
+            browser_state.as_ref().map(|c| c.value()),
+        )
+        .await
+    {
+        Ok(user) => complete_login_response(&db, user, &mfa).await,
+        Err(e) => e.error_response(),
+    };
+    // The state is single use either way
+    if let Err(e) = response.add_removal_cookie(&oidc_state_cookie("", &oidc)) {
+        warn!("Could not clear the OIDC state cookie: {}", e);
+    }
+    response
 }
 
-/// Handles request to delete all users with wallets
+/// Searches the user directory
+///
+/// Any authenticated caller may search; e-mail addresses are included only for
+/// callers holding `admin:all`. Blocked accounts are never listed.
+///
+/// # Returns
+/// - 200 OK: `{ "items": [SafeUserResponse], "next_cursor": "<opaque>" | null }`
+/// - 400 Bad Request: `InvalidSearch` (limit out of range, malformed cursor)
+/// - 401 Unauthorized if the token is missing, invalid or revoked
+pub async fn search_users(
+    auth: AuthenticatedUser,
+    query: web::Query<SearchUsersQuery>,
+    directory: web::Data<UserDirectoryService>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let search = DirectoryQuery {
+        q: query.q,
+        wallet: query.wallet,
+        cursor: query.cursor,
+        limit: query.limit,
+    };
+    let show_email = auth.require_permission(Permission::AdminAll).is_ok();
+
+    match directory.search(db.get_ref(), search).await {
+        Ok(page) => {
+            HttpResponse::Ok().json(page.map(|u| SafeUserResponse::from_user(u, show_email)))
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Blocks (`POST`) or unblocks (`DELETE`) an account
+///
+/// Requires the `admin:all` permission. Blocking revokes the user's tokens;
+/// a blocked account cannot log in and is hidden from the directory.
+///
+/// # Returns
+/// - 204 No Content on success
+/// - 400 Bad Request when an admin tries to block itself
+/// - 403 Forbidden if the caller is not an admin
+/// - 404 Not Found: `UserNotFound`
+pub async fn block_user(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    set_user_blocked(auth, path.into_inner(), db, true).await
+}
+
+/// DELETE /api/users/{id}/block (see `block_user`)
+pub async fn unblock_user(
+    auth: AuthenticatedUser,
+    path: web::Path<String>,
+    db: web::Data<Database>,
+) -> HttpResponse {
+    set_user_blocked(auth, path.into_inner(), db, false).await
+}
+
+async fn set_user_blocked(
+    auth: AuthenticatedUser,
+    user_id: String,
+    db: web::Data<Database>,
+    blocked: bool,
+) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
+    if blocked && auth.user.id_string().as_deref() == Some(user_id.as_str()) {
+        return HttpResponse::BadRequest().body("cannot block your own account");
+    }
+    match <Database as UserDataTrait>::set_blocked(&db, &user_id, blocked).await {
+        Some(user) => {
+            info!(
+                "User {} {} by {}",
+                user.username,
+                if blocked { "blocked" } else { "unblocked" },
+                auth.user.username
+            );
+            HttpResponse::NoContent().finish()
+        }
+        None => AuthError::UserNotFound.error_response(),
+    }
+}
+
+/// Handles request to delete all wallet-only users
+///
+/// Accounts that also have an e-mail, a password or an external identity are kept,
+/// even if they linked a wallet.
+///
+/// Requires the `admin:all` permission on the caller's current roles.
 ///
 /// # Returns
 /// - 200 OK on success
+/// - 401 Unauthorized if the token is missing, invalid or revoked
+/// - 403 Forbidden if the caller is not an admin
 /// - 500 Internal Server Error on failure
-pub async fn delete_wallet_users(db: web::Data<Database>) -> impl Responder {
+pub async fn delete_wallet_users(auth: AuthenticatedUser, db: web::Data<Database>) -> HttpResponse {
+    if let Err(resp) = auth.require_permission(Permission::AdminAll) {
+        return resp;
+    }
     info!("Attempting to delete all users with wallets");
     let success = <Database as UserDataTrait>::delete_wallet_users(&db).await;
 
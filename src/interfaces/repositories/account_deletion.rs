@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait AccountDeletionRepository: Send + Sync {
+    /// Ids of the accounts whose scheduled deletion is due at `now`.
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Thing>, Error>;
+
+    /// In one transaction: reassigns the messages, created tasks, task comments
+    /// and activity of `user_id` to the deleted user placeholder (replacing the
+    /// copied usernames), removes it from conversations, read receipts, task
+    /// assignments, watchers and projects, drops its API keys, one-time tokens,
+    /// notifications and calendar feed and deletes the account.
+    async fn purge(&self, user_id: Thing) -> Result<(), Error>;
+}
@@ -0,0 +1,13 @@
+use crate::models::entities::user::User;
+use async_trait::async_trait;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait AccountMergeRepository: Send + Sync {
+    /// In one transaction: stores the merged credentials of `target`, moves the
+    /// conversations, messages, tasks, comments, activity, project memberships,
+    /// notifications and calendar feed of `source` to `target`, revokes the API
+    /// keys of `source` and deletes `source`.
+    async fn merge(&self, source: Thing, target: User) -> Result<(), Error>;
+}
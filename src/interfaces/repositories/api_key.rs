@@ -0,0 +1,19 @@
+use crate::models::entities::api_key::ApiKey;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, key: ApiKey) -> Result<ApiKey, Error>;
+    async fn find(&self, key_id: Thing) -> Result<Option<ApiKey>, Error>;
+    /// Returns the key whose digest is `key_hash`, revoked or not.
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, Error>;
+    /// Returns every key acting as the user, newest first.
+    async fn list_for_user(&self, user_id: Thing) -> Result<Vec<ApiKey>, Error>;
+    /// Marks the key as revoked at `at` (keeps an earlier revocation).
+    async fn revoke(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), Error>;
+    /// Records a successful use of the key.
+    async fn touch(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), Error>;
+}
@@ -0,0 +1,10 @@
+use crate::models::entities::audit_event::AuditEvent;
+use async_trait::async_trait;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait AuditLogRepository: Send + Sync {
+    async fn record(&self, event: AuditEvent) -> Result<AuditEvent, Error>;
+    /// Returns the latest events for `subject`, newest first.
+    async fn find_by_subject(&self, subject: &str, limit: usize) -> Result<Vec<AuditEvent>, Error>;
+}
@@ -0,0 +1,20 @@
+use crate::models::entities::board::{Board, Project};
+use async_trait::async_trait;
+use surrealdb::Error;
+
+/// Projects (`project:<uuid>`) and boards (`board:<uuid>`, with their columns and cards)
+#[async_trait]
+pub trait BoardRepository: Send + Sync {
+    async fn create_project(&self, project: Project) -> Result<Project, Error>;
+    async fn find_project(&self, uuid: &str) -> Result<Option<Project>, Error>;
+    /// Projects the user owns or is a member of
+    async fn find_projects_by_user(&self, user_id: &str) -> Result<Vec<Project>, Error>;
+    async fn add_project_member(&self, uuid: &str, user_id: &str) -> Result<(), Error>;
+    async fn create_board(&self, board: Board) -> Result<Board, Error>;
+    async fn find_board(&self, uuid: &str) -> Result<Option<Board>, Error>;
+    async fn find_boards_by_project(&self, project_id: &str) -> Result<Vec<Board>, Error>;
+    /// Stores `board` only if the stored version is still `expected_version`;
+    /// `None` means another change won
+    async fn save_board(&self, board: Board, expected_version: u64)
+        -> Result<Option<Board>, Error>;
+}
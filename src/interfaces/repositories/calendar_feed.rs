@@ -0,0 +1,12 @@
+use crate::models::entities::calendar::CalendarFeed;
+use async_trait::async_trait;
+use surrealdb::Error;
+
+/// Calendar feed tokens (`calendar_feed:<user uuid>`), one per user
+#[async_trait]
+pub trait CalendarFeedRepository: Send + Sync {
+    /// Stores the feed of a user, replacing any previous one
+    async fn upsert(&self, feed: CalendarFeed) -> Result<CalendarFeed, Error>;
+    async fn find_by_token_hash(&self, token_hash: &str) -> Result<Option<CalendarFeed>, Error>;
+    async fn delete(&self, user_id: &str) -> Result<(), Error>;
+}
@@ -0,0 +1,16 @@
+use crate::models::entities::login_throttle::LoginThrottle;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::Error;
+
+#[async_trait]
+pub trait LoginThrottleRepository: Send + Sync {
+    async fn find(&self, key: &str) -> Result<Option<LoginThrottle>, Error>;
+    /// Atomically counts a failed login for `key` (creating the counter) and returns
+    /// it. The count restarts when the previous failure is older than `window`.
+    async fn record_failure(&self, key: &str, window: Duration) -> Result<LoginThrottle, Error>;
+    /// Locks `key` out until `until`.
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), Error>;
+    /// Deletes the counter (and any lockout) of `key`.
+    async fn clear(&self, key: &str) -> Result<(), Error>;
+}
@@ -12,6 +12,12 @@ pub trait MessageRepository: Send + Sync {
         limit: u32,
         offset: u32,
     ) -> Result<Vec<Message>, Error>;
+    /// Every message sent by `sender_id`, oldest first.
+    async fn find_by_sender(&self, sender_id: Thing) -> Result<Vec<Message>, Error>;
+    async fn find_by_id(&self, id: Thing) -> Result<Option<Message>, Error>;
+    /// Links the message to the task `task_id` (UUID) unless it is already
+    /// linked; `false` means it was (or the message does not exist).
+    async fn link_task(&self, message_id: Thing, task_id: String) -> Result<bool, Error>;
     async fn mark_as_read(&self, message_id: Thing, user_id: Thing) -> Result<(), Error>;
     async fn delete(&self, id: Thing) -> Result<(), Error>;
 }
@@ -18,5 +18,17 @@
 //! - Implement CRUD operations
 //! - Manage entity persistence
 //! - Handle data relationships
+pub mod account_deletion;
+pub mod account_merge;
+pub mod api_key;
+pub mod audit_log;
+pub mod board;
+pub mod calendar_feed;
 pub mod conversation;
+pub mod login_throttle;
 pub mod message;
+pub mod notification;
+pub mod oidc_state;
+pub mod one_time_token;
+pub mod role;
+pub mod task_activity;
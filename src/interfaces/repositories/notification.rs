@@ -0,0 +1,23 @@
+use crate::models::entities::notification::Notification;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::Error;
+
+/// Notifications kept for offline users (`notification:<uuid>`)
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+    async fn create(&self, notification: Notification) -> Result<Notification, Error>;
+    /// Notifications of a user, newest first
+    async fn find_for_user(
+        &self,
+        user_id: &str,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, Error>;
+    /// Marks a notification of `user_id` read; None if the user has no such notification
+    async fn mark_read(
+        &self,
+        user_id: &str,
+        uuid: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error>;
+}
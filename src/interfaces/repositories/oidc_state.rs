@@ -0,0 +1,13 @@
+use crate::models::entities::oidc_state::OidcLoginState;
+use async_trait::async_trait;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait OidcStateRepository: Send + Sync {
+    async fn create(&self, state: OidcLoginState) -> Result<OidcLoginState, Error>;
+    /// Atomically deletes the unexpired pending login with `state_hash` and returns
+    /// it, so a callback can only be completed once.
+    async fn consume(&self, state_hash: &str) -> Result<Option<OidcLoginState>, Error>;
+    /// Deletes expired pending logins.
+    async fn purge_expired(&self) -> Result<(), Error>;
+}
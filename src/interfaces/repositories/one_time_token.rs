@@ -0,0 +1,27 @@
+use crate::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+use async_trait::async_trait;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait OneTimeTokenRepository: Send + Sync {
+    async fn create(&self, token: OneTimeToken) -> Result<OneTimeToken, Error>;
+    /// Atomically marks the matching unused, unexpired token as used and returns
+    /// it. Returns `None` if no such token exists, so a token works only once.
+    async fn consume(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &str,
+    ) -> Result<Option<OneTimeToken>, Error>;
+    /// Returns the most recently issued token of `purpose` for the user.
+    async fn find_latest_for_user(
+        &self,
+        user_id: Thing,
+        purpose: TokenPurpose,
+    ) -> Result<Option<OneTimeToken>, Error>;
+    /// Marks every outstanding token of `purpose` for the user as used.
+    async fn invalidate_for_user(&self, user_id: Thing, purpose: TokenPurpose)
+        -> Result<(), Error>;
+    /// Deletes expired tokens of `purpose`.
+    async fn purge_expired(&self, purpose: TokenPurpose) -> Result<(), Error>;
+}
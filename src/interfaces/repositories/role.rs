@@ -0,0 +1,18 @@
+use crate::models::entities::role::Role;
+use async_trait::async_trait;
+use surrealdb::Error;
+
+#[async_trait]
+pub trait RoleRepository: Send + Sync {
+    async fn create(&self, role: Role) -> Result<Role, Error>;
+    async fn find_all(&self) -> Result<Vec<Role>, Error>;
+    async fn find_by_name(&self, name: &str) -> Result<Option<Role>, Error>;
+    async fn update(&self, role: Role) -> Result<Role, Error>;
+    async fn delete(&self, name: &str) -> Result<(), Error>;
+    /// Replaces the embedded copy of `role` on every user holding it and bumps
+    /// their token version. Returns the number of users updated.
+    async fn sync_users(&self, role: &Role) -> Result<usize, Error>;
+    /// Removes the role named `name` from every user holding it and bumps their
+    /// token version. Returns the number of users updated.
+    async fn remove_from_users(&self, name: &str) -> Result<usize, Error>;
+}
@@ -0,0 +1,16 @@
+use crate::models::entities::task_activity::{TaskActivity, TaskComment};
+use async_trait::async_trait;
+use surrealdb::Error;
+
+/// Task comments (`task_comment:<uuid>`) and recorded changes (`task_activity:<uuid>`)
+#[async_trait]
+pub trait TaskActivityRepository: Send + Sync {
+    async fn add_comment(&self, comment: TaskComment) -> Result<TaskComment, Error>;
+    async fn add_activity(&self, activity: TaskActivity) -> Result<TaskActivity, Error>;
+    /// Comments of a task, oldest first
+    async fn find_comments(&self, task_id: &str) -> Result<Vec<TaskComment>, Error>;
+    /// Recorded changes of a task, oldest first
+    async fn find_activity(&self, task_id: &str) -> Result<Vec<TaskActivity>, Error>;
+    /// Removes the comments and history of a deleted task
+    async fn delete_for_task(&self, task_id: &str) -> Result<(), Error>;
+}
@@ -1,18 +1,54 @@
 //! Application Entry Point
 //! Initializes and runs the HTTP server with all necessary middleware and configurations.
 
-use actix::Actor;
+use actix::{Actor, AsyncContext};
 use chasqui_server::infrastructure::database::surrealdb::Database;
 use chasqui_server::interfaces::api::routes;
 use actix_web::{middleware::Logger, web, App, HttpServer};
+use chrono::Utc;
 use env_logger::Env;
 use std::env;
 use std::sync::Arc;
 
+use chasqui_server::application::services::account_deletion_service::AccountDeletionService;
+use chasqui_server::application::services::account_link_service::AccountLinkService;
+use chasqui_server::application::services::account_merge_service::AccountMergeService;
+use chasqui_server::application::services::api_key_service::ApiKeyService;
+use chasqui_server::application::services::board_service::BoardService;
+use chasqui_server::application::services::calendar_service::CalendarService;
 use chasqui_server::application::services::conversation_service::ConversationService;
+use chasqui_server::application::services::data_export_service::DataExportService;
+use chasqui_server::application::services::email_verification_service::EmailVerificationService;
 use chasqui_server::application::services::message_service::MessageService;
+use chasqui_server::application::services::message_task_service::MessageTaskService;
+use chasqui_server::application::services::login_throttle_service::LoginThrottleService;
+use chasqui_server::application::services::mfa_service::MfaService;
+use chasqui_server::application::services::notification_service::NotificationService;
+use chasqui_server::application::services::oidc_service::OidcService;
+use chasqui_server::application::services::password_policy::PasswordPolicy;
+use chasqui_server::application::services::profile_service::ProfileService;
+use chasqui_server::application::services::task_reminder_service::TaskReminderService;
+use chasqui_server::application::services::task_service::TaskService;
+use chasqui_server::application::services::user_directory_service::UserDirectoryService;
+use chasqui_server::application::services::password_reset_service::PasswordResetService;
+use chasqui_server::application::services::role_service::RoleService;
+use chasqui_server::application::services::ws_ticket_service::WsTicketService;
+use chasqui_server::infrastructure::database::repositories::surreal_account_deletion::SurrealAccountDeletionRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_account_merge::SurrealAccountMergeRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_api_key::SurrealApiKeyRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_audit_log::SurrealAuditLogRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_board::SurrealBoardRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_calendar_feed::SurrealCalendarFeedRepository;
 use chasqui_server::infrastructure::database::repositories::surreal_conversation::SurrealConversationRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_login_throttle::SurrealLoginThrottleRepository;
 use chasqui_server::infrastructure::database::repositories::surreal_message::SurrealMessageRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_notification::SurrealNotificationRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_oidc_state::SurrealOidcStateRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_one_time_token::SurrealOneTimeTokenRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_role::SurrealRoleRepository;
+use chasqui_server::infrastructure::database::repositories::surreal_task_activity::SurrealTaskActivityRepository;
+use chasqui_server::infrastructure::auth::keys;
+use chasqui_server::infrastructure::mail::mailer_from_env;
 use chasqui_server::infrastructure::websocket::chat_server::ChatServer;
 
 /// Main application entry point
@@ -42,6 +78,9 @@ async fn main() -> std::io::Result<()> {
         return Ok(());
     }
 
+    // Load and cache the JWT signing/verification keys (fail fast on bad config)
+    keys::init_from_env().expect("Invalid JWT key configuration");
+
     // Initialize database connection
     // This creates a new SurrealDB instance with configured credentials
     let db = Database::init()
@@ -55,6 +94,21 @@ async fn main() -> std::io::Result<()> {
     // Initialize repositories
     let message_repo = Arc::new(SurrealMessageRepository::new(db.clone()));
     let conversation_repo = Arc::new(SurrealConversationRepository::new(db.clone()));
+    let role_repo = Arc::new(SurrealRoleRepository::new(db.clone()));
+    let one_time_token_repo = Arc::new(SurrealOneTimeTokenRepository::new(db.clone()));
+    let oidc_state_repo = Arc::new(SurrealOidcStateRepository::new(db.clone()));
+    let login_throttle_repo = Arc::new(SurrealLoginThrottleRepository::new(db.clone()));
+    let audit_log_repo = Arc::new(SurrealAuditLogRepository::new(db.clone()));
+    let api_key_repo = Arc::new(SurrealApiKeyRepository::new(db.clone()));
+    let account_merge_repo = Arc::new(SurrealAccountMergeRepository::new(db.clone()));
+    let account_deletion_repo = Arc::new(SurrealAccountDeletionRepository::new(db.clone()));
+    let board_repo = Arc::new(SurrealBoardRepository::new(db.clone()));
+    let task_activity_repo = Arc::new(SurrealTaskActivityRepository::new(db.clone()));
+    let notification_repo = Arc::new(SurrealNotificationRepository::new(db.clone()));
+    let calendar_feed_repo = Arc::new(SurrealCalendarFeedRepository::new(db.clone()));
+
+    // Initialize outgoing mail (MAILER=smtp|file|memory)
+    let mailer = mailer_from_env();
 
     // Initialize services
     let message_service = Arc::new(MessageService::new(
@@ -62,15 +116,132 @@ async fn main() -> std::io::Result<()> {
         conversation_repo.clone(),
     ));
     let conversation_service = Arc::new(ConversationService::new(conversation_repo.clone()));
+    let role_service = Arc::new(RoleService::new(role_repo.clone()));
+    let password_policy = Arc::new(PasswordPolicy::from_env());
+    let password_reset_service = Arc::new(PasswordResetService::from_env(
+        one_time_token_repo.clone(),
+        mailer.clone(),
+        password_policy.clone(),
+    ));
+    let email_verification_service = Arc::new(EmailVerificationService::from_env(
+        one_time_token_repo.clone(),
+        mailer.clone(),
+    ));
+    let mfa_service = Arc::new(MfaService::from_env());
+    let oidc_service = Arc::new(OidcService::from_env(oidc_state_repo.clone()));
+    let login_throttle_service = Arc::new(LoginThrottleService::from_env(
+        login_throttle_repo.clone(),
+        audit_log_repo.clone(),
+    ));
+    let ws_ticket_service = Arc::new(WsTicketService::from_env(one_time_token_repo.clone()));
+    let api_key_service = Arc::new(ApiKeyService::new(api_key_repo.clone()));
+    let profile_service = Arc::new(ProfileService::new(password_policy.clone()));
+    let user_directory_service = Arc::new(UserDirectoryService::from_env());
+    let account_link_service = Arc::new(AccountLinkService::from_env(one_time_token_repo.clone()));
+    let account_merge_service = Arc::new(AccountMergeService::new(
+        account_merge_repo.clone(),
+        audit_log_repo.clone(),
+    ));
+    let account_deletion_service = Arc::new(AccountDeletionService::from_env(
+        account_deletion_repo.clone(),
+        audit_log_repo.clone(),
+    ));
+    let data_export_service = Arc::new(DataExportService::new(
+        conversation_repo.clone(),
+        message_repo.clone(),
+    ));
+
+    // Check for --seed-roles argument: create missing built-in roles and exit
+    if std::env::args().any(|arg| arg == "--seed-roles") {
+        match role_service.seed_builtin_roles().await {
+            Ok(seeded) => println!("Seeded built-in roles: {:?}", seeded),
+            Err(e) => eprintln!("Failed to seed built-in roles: {}", e),
+        }
+        return Ok(());
+    }
+
+    // Periodically delete the accounts whose deletion grace period is over
+    let purge_service = account_deletion_service.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(purge_service.purge_interval());
+        loop {
+            interval.tick().await;
+            purge_service.purge_due().await;
+        }
+    });
 
-    // Initialize ChatServer actor for WebSockets with injected services
-    let chat_server =
-        ChatServer::new(message_service.clone(), conversation_service.clone()).start();
+    // Initialize ChatServer actor for WebSockets with injected services.
+    // Task changes are pushed to the chat WebSocket sessions, and the chat
+    // server turns `/task` messages into tasks, so both are built together.
+    let mut task_services = None;
+    let chat_server = ChatServer::create(|ctx| {
+        let task_service = Arc::new(
+            TaskService::from_env(conversation_repo.clone())
+                .with_events(Arc::new(ctx.address()))
+                .with_activity(task_activity_repo.clone()),
+        );
+        let message_task_service = Arc::new(MessageTaskService::new(
+            task_service.clone(),
+            message_repo.clone(),
+            conversation_repo.clone(),
+        ));
+        task_services = Some((task_service, message_task_service.clone()));
+        ChatServer::new(message_service.clone(), conversation_service.clone())
+            .with_message_tasks(message_task_service, db.clone())
+    });
+    let (task_service, message_task_service) =
+        task_services.expect("ChatServer::create runs its factory immediately");
+    let board_service = Arc::new(
+        BoardService::new(board_repo.clone(), task_service.clone())
+            .with_events(Arc::new(chat_server.clone())),
+    );
+    let calendar_service = Arc::new(CalendarService::from_env(
+        calendar_feed_repo.clone(),
+        task_service.clone(),
+    ));
+    let notification_service = Arc::new(NotificationService::new(
+        notification_repo.clone(),
+        Arc::new(chat_server.clone()),
+    ));
+    let task_reminder_service = Arc::new(TaskReminderService::from_env(
+        notification_service.clone(),
+    ));
     let chat_server_data = web::Data::new(chat_server);
 
+    // Periodically send the task reminders whose time has come
+    let reminder_service = task_reminder_service.clone();
+    let reminder_db = db.clone();
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(reminder_service.interval());
+        loop {
+            interval.tick().await;
+            reminder_service.run_due(&reminder_db, Utc::now()).await;
+        }
+    });
+
     // Prepare web::Data for services to fix extractor issues
     let message_service_data = web::Data::from(message_service.clone());
     let conversation_service_data = web::Data::from(conversation_service.clone());
+    let role_service_data = web::Data::from(role_service.clone());
+    let password_policy_data = web::Data::from(password_policy.clone());
+    let password_reset_service_data = web::Data::from(password_reset_service.clone());
+    let email_verification_service_data = web::Data::from(email_verification_service.clone());
+    let mfa_service_data = web::Data::from(mfa_service.clone());
+    let oidc_service_data = web::Data::from(oidc_service.clone());
+    let login_throttle_service_data = web::Data::from(login_throttle_service.clone());
+    let ws_ticket_service_data = web::Data::from(ws_ticket_service.clone());
+    let api_key_service_data = web::Data::from(api_key_service.clone());
+    let profile_service_data = web::Data::from(profile_service.clone());
+    let task_service_data = web::Data::from(task_service.clone());
+    let message_task_service_data = web::Data::from(message_task_service.clone());
+    let board_service_data = web::Data::from(board_service.clone());
+    let notification_service_data = web::Data::from(notification_service.clone());
+    let calendar_service_data = web::Data::from(calendar_service.clone());
+    let user_directory_service_data = web::Data::from(user_directory_service.clone());
+    let account_link_service_data = web::Data::from(account_link_service.clone());
+    let account_merge_service_data = web::Data::from(account_merge_service.clone());
+    let account_deletion_service_data = web::Data::from(account_deletion_service.clone());
+    let data_export_service_data = web::Data::from(data_export_service.clone());
 
     println!("Starting the HTTP server...");
     // Configure and launch HTTP server
@@ -124,6 +295,26 @@ async fn main() -> std::io::Result<()> {
             .app_data(chat_server_data.clone()) // Share chat server actor
             .app_data(message_service_data.clone()) // Share message service
             .app_data(conversation_service_data.clone()) // Share conversation service
+            .app_data(role_service_data.clone()) // Share role service
+            .app_data(password_reset_service_data.clone()) // Share password reset service
+            .app_data(email_verification_service_data.clone()) // Share e-mail verification service
+            .app_data(mfa_service_data.clone()) // Share TOTP two-factor service
+            .app_data(oidc_service_data.clone()) // Share external login service
+            .app_data(login_throttle_service_data.clone()) // Share login brute-force protection
+            .app_data(password_policy_data.clone()) // Share password policy (register)
+            .app_data(ws_ticket_service_data.clone()) // Share WebSocket ticket service
+            .app_data(api_key_service_data.clone()) // Share API key authentication
+            .app_data(profile_service_data.clone()) // Share profile and credential changes
+            .app_data(task_service_data.clone()) // Share task access and updates
+            .app_data(message_task_service_data.clone()) // Share message-to-task linking
+            .app_data(board_service_data.clone()) // Share projects and Kanban boards
+            .app_data(notification_service_data.clone()) // Share stored notifications
+            .app_data(calendar_service_data.clone()) // Share calendar feed and imports
+            .app_data(user_directory_service_data.clone()) // Share user directory search
+            .app_data(account_link_service_data.clone()) // Share wallet / identity linking
+            .app_data(account_merge_service_data.clone()) // Share account merges
+            .app_data(account_deletion_service_data.clone()) // Share deactivation and deletion
+            .app_data(data_export_service_data.clone()) // Share personal data export
             .configure(routes::config) // Setup API routes
     })
     .bind({
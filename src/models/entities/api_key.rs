@@ -0,0 +1,224 @@
+//! API Key Entity Module
+//!
+//! Long-lived credentials for integrations and bot accounts. A key is shown to
+//! its creator once, as `chq_<prefix>_<secret>`; only the SHA-256 digest of the
+//! whole string is stored. The public `prefix` (`chq_1a2b3c4d`) identifies the
+//! key in listings and logs without revealing it.
+//!
+//! A key grants only its `scopes`, and only while the owning user still holds
+//! the corresponding permission through its roles.
+//!
+//! # Fields
+//! - `id`: SurrealDB Thing with schema `api_key:<uuid-v4>`
+//! - `user_id`: The user (human or bot) the key acts as
+//! - `created_by`: The user who created the key
+//! - `name`: Label chosen by the creator
+//! - `prefix`: Public identifier, also the start of the secret key
+//! - `key_hash`: SHA-256 (hex) of the full key; the plaintext is never stored
+//! - `scopes`: Permissions granted, optionally restricted to one conversation
+//! - `expires_at`: Optional expiration instant
+//! - `last_used_at`: Updated on every authenticated request
+//! - `revoked_at`: Set when the key is revoked; a revoked key is rejected
+//! - `created_at`: Timestamp when the key was created
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::models::entities::role::Permission;
+
+/// Start of every API key, so keys are recognizable (and secret-scannable)
+pub const API_KEY_PREFIX: &str = "chq_";
+
+/// True if `token` has the shape of an API key rather than a JWT.
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
+
+/// One permission granted by a key
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ApiKeyScope {
+    /// Granted permission (`message:create`, ...)
+    pub permission: Permission,
+
+    /// Restricts the permission to one conversation (`conversation:<uuid>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+impl ApiKeyScope {
+    /// Check whether the scope grants `permission`, on `conversation_id` if given.
+    ///
+    /// A scope restricted to a conversation never grants the permission globally.
+    pub fn allows(&self, permission: Permission, conversation_id: Option<&str>) -> bool {
+        let permission_ok =
+            self.permission == permission || self.permission == Permission::AdminAll;
+        let conversation_ok = match &self.conversation_id {
+            None => true,
+            Some(scoped) => conversation_id == Some(scoped.as_str()),
+        };
+        permission_ok && conversation_ok
+    }
+}
+
+/// Represents an API key
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    /// Database identifier (SurrealDB Thing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    /// The user the key acts as
+    pub user_id: Thing,
+
+    /// The user who created the key
+    pub created_by: Thing,
+
+    /// Label chosen by the creator
+    pub name: String,
+
+    /// Public identifier (`chq_<8 hex>`)
+    pub prefix: String,
+
+    /// SHA-256 digest (hex) of the full key
+    pub key_hash: String,
+
+    /// Granted permissions
+    pub scopes: Vec<ApiKeyScope>,
+
+    /// Expiration instant, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Last successful use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+
+    /// When the key was revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    /// When the key was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    /// Creates a new active key
+    ///
+    /// # Arguments
+    /// * `user_id` - The user the key acts as
+    /// * `created_by` - The user creating the key
+    /// * `name` - Label of the key
+    /// * `prefix` - Public identifier
+    /// * `key_hash` - SHA-256 digest of the full key
+    /// * `scopes` - Granted permissions
+    /// * `ttl` - Lifetime, or `None` for a key that does not expire
+    pub fn new(
+        user_id: Thing,
+        created_by: Thing,
+        name: String,
+        prefix: String,
+        key_hash: String,
+        scopes: Vec<ApiKeyScope>,
+        ttl: Option<Duration>,
+    ) -> Self {
+        let uuid = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        ApiKey {
+            id: Some(Thing::from(("api_key", uuid.as_str()))),
+            user_id,
+            created_by,
+            name,
+            prefix,
+            key_hash,
+            scopes,
+            expires_at: ttl.map(|ttl| now + ttl),
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now,
+        }
+    }
+
+    /// Check whether the key can authenticate at `now`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|exp| now < exp)
+    }
+
+    /// Check whether any scope grants `permission`, on `conversation_id` if given
+    pub fn allows(&self, permission: Permission, conversation_id: Option<&str>) -> bool {
+        self.scopes
+            .iter()
+            .any(|s| s.allows(permission, conversation_id))
+    }
+
+    /// Sorted, de-duplicated names of the granted permissions
+    pub fn permission_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .scopes
+            .iter()
+            .map(|s| s.permission.to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Validate key
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty()
+            && self.name.len() <= 64
+            && self.prefix.starts_with(API_KEY_PREFIX)
+            && !self.key_hash.is_empty()
+            && !self.scopes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(scopes: Vec<ApiKeyScope>) -> ApiKey {
+        ApiKey::new(
+            Thing::from(("user", "bot")),
+            Thing::from(("user", "admin")),
+            "ci".to_string(),
+            "chq_1a2b3c4d".to_string(),
+            "hash".to_string(),
+            scopes,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_conversation_scope_is_not_global() {
+        let key = key(vec![ApiKeyScope {
+            permission: Permission::MessageCreate,
+            conversation_id: Some("conversation:abc".to_string()),
+        }]);
+        assert!(key.is_valid());
+        assert!(key.allows(Permission::MessageCreate, Some("conversation:abc")));
+        assert!(!key.allows(Permission::MessageCreate, Some("conversation:xyz")));
+        assert!(!key.allows(Permission::MessageCreate, None));
+        assert!(!key.allows(Permission::MessageDelete, Some("conversation:abc")));
+    }
+
+    #[test]
+    fn test_revoked_or_expired_key_is_inactive() {
+        let now = Utc::now();
+        let mut key = key(vec![ApiKeyScope {
+            permission: Permission::ChannelRead,
+            conversation_id: None,
+        }]);
+        assert!(key.is_active_at(now));
+        key.expires_at = Some(now - Duration::seconds(1));
+        assert!(!key.is_active_at(now));
+        key.expires_at = None;
+        key.revoked_at = Some(now);
+        assert!(!key.is_active_at(now));
+    }
+}
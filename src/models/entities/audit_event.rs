@@ -0,0 +1,113 @@
+//! Audit Event Entity Module
+//!
+//! Append-only record of security relevant events (account lockouts, merges,
+//! deletions, ...).
+//!
+//! # Fields
+//! - `id`: SurrealDB Thing with schema `audit_event:<uuid-v4>`
+//! - `action`: What happened
+//! - `subject`: What it happened to (e.g. a throttle key `account:alice@example.com`)
+//! - `ip`: Client address of the triggering request, if any
+//! - `detail`: Free text context (reason, acting admin, ...)
+//! - `created_at`: Timestamp of the event
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+/// Kind of audited event
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Too many failed logins; the key is locked out
+    LoginLocked,
+    /// A lockout ended (expired or lifted by an admin)
+    LoginUnlocked,
+    /// A duplicate account was merged into another one and deleted
+    AccountMerged,
+    /// The owner asked for the account to be deleted after the grace period
+    AccountDeletionScheduled,
+    /// An account was deleted at the end of its grace period
+    AccountDeleted,
+}
+
+/// Represents one audit log entry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEvent {
+    /// Database identifier (SurrealDB Thing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    /// What happened
+    pub action: AuditAction,
+
+    /// What it happened to
+    pub subject: String,
+
+    /// Client address of the triggering request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    /// Additional context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// When the event happened
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    /// Creates a new event timestamped now
+    ///
+    /// # Arguments
+    /// * `action` - What happened
+    /// * `subject` - What it happened to
+    /// * `ip` - Client address, if known
+    /// * `detail` - Additional context
+    pub fn new(
+        action: AuditAction,
+        subject: &str,
+        ip: Option<String>,
+        detail: Option<String>,
+    ) -> Self {
+        let uuid = Uuid::new_v4().to_string();
+
+        AuditEvent {
+            id: Some(Thing::from(("audit_event", uuid.as_str()))),
+            action,
+            subject: subject.to_string(),
+            ip,
+            detail,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Validate event
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.subject.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_event() {
+        let event = AuditEvent::new(
+            AuditAction::LoginLocked,
+            "account:alice",
+            Some("10.0.0.1".to_string()),
+            None,
+        );
+        assert!(event.is_valid());
+        assert_eq!(
+            serde_json::to_value(event.action).unwrap(),
+            serde_json::json!("login_locked")
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Board Entity Module
+//! Projects, Kanban boards, their columns and cards.
+//!
+//! # Project
+//! - `uuid`: Record id (`project:<uuid>`)
+//! - `name`
+//! - `owner`: UUID of the user who created it; manages the members
+//! - `members`: UUIDs of the other users working on it
+//!
+//! # Board
+//! - `uuid`: Record id (`board:<uuid>`)
+//! - `project_id`: UUID of the project
+//! - `name`
+//! - `columns`: Ordered columns, each mapped to a task status
+//! - `cards`: Tasks on the board, each in one column and ordered within it by
+//!   a fractional index key (`position`)
+//! - `version`: Incremented by every change; writes only succeed against the
+//!   version they were computed from
+//!
+//! Columns and cards live in the board record so every change is one atomic
+//! compare-and-set.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::entities::task::{Task, TaskStatus};
+
+/// Maximum length (characters) of project, board and column names
+pub const MAX_BOARD_NAME_LENGTH: u64 = 100;
+/// Maximum number of columns of a board
+pub const MAX_BOARD_COLUMNS: usize = 20;
+
+/// A group of boards shared by its owner and members
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Project {
+    pub uuid: String,
+    pub name: String,
+    /// UUID of the creator
+    pub owner: String,
+    /// UUIDs of the other members
+    #[serde(default)]
+    pub members: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Project {
+    pub fn new(name: String, owner: String) -> Project {
+        let now = Utc::now();
+        Project {
+            uuid: Uuid::new_v4().to_string(),
+            name,
+            owner,
+            members: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// True for the owner and the members
+    pub fn is_member(&self, user_id: &str) -> bool {
+        self.owner == user_id || self.members.iter().any(|m| m == user_id)
+    }
+
+    /// UUIDs of the owner and the members
+    pub fn audience(&self) -> Vec<String> {
+        let mut audience = self.members.clone();
+        audience.push(self.owner.clone());
+        audience.sort();
+        audience.dedup();
+        audience
+    }
+}
+
+/// A board column; moving a card into it sets the task status
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BoardColumn {
+    /// UUID of the column
+    pub id: String,
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+/// A task placed on a board
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BoardCard {
+    /// UUID of the card
+    pub id: String,
+    pub task_id: String,
+    pub column_id: String,
+    /// Fractional index key; cards sort by (position, id)
+    pub position: String,
+}
+
+/// A Kanban board of a project
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Board {
+    pub uuid: String,
+    pub project_id: String,
+    pub name: String,
+    pub columns: Vec<BoardColumn>,
+    #[serde(default)]
+    pub cards: Vec<BoardCard>,
+    #[serde(default)]
+    pub version: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Board {
+    /// Creates an empty board; without columns it gets To do / In progress / Done
+    pub fn new(project_id: String, name: String, columns: Vec<BoardColumn>) -> Board {
+        let columns = if columns.is_empty() {
+            vec![
+                BoardColumn::new("To do", TaskStatus::Todo),
+                BoardColumn::new("In progress", TaskStatus::InProgress),
+                BoardColumn::new("Done", TaskStatus::Done),
+            ]
+        } else {
+            columns
+        };
+        let now = Utc::now();
+        Board {
+            uuid: Uuid::new_v4().to_string(),
+            project_id,
+            name,
+            columns,
+            cards: Vec::new(),
+            version: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn column(&self, column_id: &str) -> Option<&BoardColumn> {
+        self.columns.iter().find(|c| c.id == column_id)
+    }
+
+    pub fn card(&self, card_id: &str) -> Option<&BoardCard> {
+        self.cards.iter().find(|c| c.id == card_id)
+    }
+
+    /// The cards shown in a column, in display order. A task whose status was
+    /// changed outside the board (`statuses`, by task UUID) has its card shown
+    /// in the first column of that status, if there is one
+    pub fn column_cards(
+        &self,
+        column_id: &str,
+        statuses: &HashMap<String, TaskStatus>,
+    ) -> Vec<&BoardCard> {
+        let mut cards: Vec<&BoardCard> = self
+            .cards
+            .iter()
+            .filter(|c| self.shown_column(c, statuses) == column_id)
+            .collect();
+        cards.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.id.cmp(&b.id)));
+        cards
+    }
+
+    /// The column `card` is shown in (see `column_cards`)
+    pub fn shown_column<'a>(
+        &'a self,
+        card: &'a BoardCard,
+        statuses: &HashMap<String, TaskStatus>,
+    ) -> &'a str {
+        let Some(status) = statuses.get(&card.task_id) else {
+            return &card.column_id;
+        };
+        if self.column(&card.column_id).is_some_and(|c| c.status == *status) {
+            return &card.column_id;
+        }
+        self.columns
+            .iter()
+            .find(|c| c.status == *status)
+            .map_or(&card.column_id, |c| &c.id)
+    }
+}
+
+impl BoardColumn {
+    pub fn new(name: &str, status: TaskStatus) -> BoardColumn {
+        BoardColumn {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            status,
+        }
+    }
+}
+
+/// Request payload for creating a project
+#[derive(Validate, Serialize, Deserialize, Debug)]
+pub struct CreateProjectRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_BOARD_NAME_LENGTH",
+        message = "name required (at most 100 characters)"
+    ))]
+    pub name: String,
+    /// UUIDs of existing users
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+/// Request payload for adding a project member
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddProjectMemberRequest {
+    pub user_id: String,
+}
+
+/// A column as sent by clients; `id` keeps an existing column
+#[derive(Validate, Serialize, Deserialize, Debug, Clone)]
+pub struct ColumnSpec {
+    pub id: Option<String>,
+    #[validate(length(
+        min = 1,
+        max = "MAX_BOARD_NAME_LENGTH",
+        message = "column name required (at most 100 characters)"
+    ))]
+    pub name: String,
+    pub status: TaskStatus,
+}
+
+/// Request payload for creating a board
+#[derive(Validate, Serialize, Deserialize, Debug)]
+pub struct CreateBoardRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_BOARD_NAME_LENGTH",
+        message = "name required (at most 100 characters)"
+    ))]
+    pub name: String,
+    /// Defaults to To do / In progress / Done
+    #[validate(nested)]
+    #[serde(default)]
+    pub columns: Vec<ColumnSpec>,
+}
+
+/// Replaces the columns of a board (their order is the array order)
+#[derive(Validate, Serialize, Deserialize, Debug)]
+pub struct SetColumnsRequest {
+    #[validate(nested)]
+    pub columns: Vec<ColumnSpec>,
+    /// Board version the change was made against; stale versions answer 409
+    pub version: Option<u64>,
+}
+
+/// Where a card goes: a column, and optionally the cards it goes between
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CardPlacement {
+    /// Defaults to the first column mapped to the task status (adding only)
+    pub column_id: Option<String>,
+    /// Card the moved card follows
+    pub after: Option<String>,
+    /// Card the moved card precedes (used when `after` is absent)
+    pub before: Option<String>,
+    /// Board version the change was made against; stale versions answer 409
+    pub version: Option<u64>,
+}
+
+/// Request payload for putting a task on a board
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddCardRequest {
+    pub task_id: String,
+    #[serde(flatten)]
+    pub placement: CardPlacement,
+}
+
+/// A card with its task
+#[derive(Serialize, Debug, Clone)]
+pub struct CardView {
+    #[serde(flatten)]
+    pub card: BoardCard,
+    pub task: Task,
+}
+
+/// A column with its cards in order
+#[derive(Serialize, Debug, Clone)]
+pub struct ColumnView {
+    #[serde(flatten)]
+    pub column: BoardColumn,
+    pub cards: Vec<CardView>,
+}
+
+/// A board as shown to clients
+#[derive(Serialize, Debug, Clone)]
+pub struct BoardView {
+    pub uuid: String,
+    pub project_id: String,
+    pub name: String,
+    pub version: u64,
+    pub columns: Vec<ColumnView>,
+}
+
+/// Kind of a real-time board change (WebSocket `type` field)
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum BoardEventKind {
+    ColumnsChanged,
+    CardAdded,
+    CardMoved,
+    CardRemoved,
+}
+
+/// A board change and the users to notify
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardEvent {
+    pub kind: BoardEventKind,
+    /// The board after the change
+    pub board: Board,
+    /// The added, moved or removed card
+    pub card: Option<BoardCard>,
+    /// UUIDs of the project members
+    pub audience: Vec<String>,
+}
+
+impl BoardEvent {
+    /// WebSocket payload: `{ "type": "CardMoved", "board_id": "...", "version": 7, "card": { ... } }`;
+    /// `ColumnsChanged` carries `columns` instead of `card`
+    pub fn payload(&self) -> String {
+        let mut payload = serde_json::json!({
+            "type": self.kind,
+            "board_id": self.board.uuid,
+            "version": self.board.version,
+        });
+        match &self.card {
+            Some(card) => payload["card"] = serde_json::json!(card),
+            None => payload["columns"] = serde_json::json!(self.board.columns),
+        }
+        payload.to_string()
+    }
+}
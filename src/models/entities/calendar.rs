@@ -0,0 +1,58 @@
+//! Calendar Entity Module
+//! Calendar subscriptions of task due dates and `.ics` imports.
+//!
+//! # CalendarFeed
+//! - `user_id`: UUID of the owner (record id `calendar_feed:<user uuid>`)
+//! - `token_hash`: SHA-256 (hex) of the feed token; the token itself is only
+//!   shown when it is created
+//! - `created_at`: When the token was created
+//!
+//! A user has at most one feed token; creating a new one replaces it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::entities::task::Task;
+
+/// The feed token of a user, as stored
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CalendarFeed {
+    pub user_id: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A new feed token and the URL to subscribe to
+#[derive(Serialize, Debug, Clone)]
+pub struct CalendarFeedToken {
+    pub token: String,
+    /// Path of the feed, relative to the server (`/api/tasks/calendar.ics?token=...`)
+    pub url: String,
+}
+
+/// Query parameters of `GET /api/tasks/calendar.ics`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CalendarFeedQuery {
+    #[serde(default)]
+    pub token: String,
+    /// `event` (default) writes `VEVENT`s, `todo` writes `VTODO`s
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// An entry of an imported calendar that did not become a task
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SkippedEntry {
+    /// Zero-based position among the calendar's `VTODO`s and `VEVENT`s
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub reason: String,
+}
+
+/// Outcome of a calendar import
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ImportReport {
+    pub created: Vec<Task>,
+    pub skipped: Vec<SkippedEntry>,
+}
@@ -0,0 +1,60 @@
+//! External Identity Entity Module
+//!
+//! An account at an external OAuth2 / OpenID Connect provider linked to a `User`
+//! (embedded in `User::identities`).
+//!
+//! # Fields
+//! - `provider`: Configured provider name (e.g. `google`, `github`)
+//! - `subject`: Stable user id at the provider (`sub` claim)
+//! - `email`: E-mail asserted by the provider when the identity was linked
+//! - `linked_at`: Timestamp when the identity was linked
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Represents a linked external identity
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExternalIdentity {
+    /// Configured provider name
+    pub provider: String,
+
+    /// Stable user id at the provider
+    pub subject: String,
+
+    /// E-mail asserted by the provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+
+    /// When the identity was linked
+    pub linked_at: DateTime<Utc>,
+}
+
+impl ExternalIdentity {
+    /// Creates a new identity linked now
+    ///
+    /// # Arguments
+    /// * `provider` - Configured provider name
+    /// * `subject` - User id at the provider
+    /// * `email` - E-mail asserted by the provider, if any
+    pub fn new(provider: &str, subject: &str, email: Option<String>) -> Self {
+        ExternalIdentity {
+            provider: provider.to_string(),
+            subject: subject.to_string(),
+            email,
+            linked_at: Utc::now(),
+        }
+    }
+
+    /// True if this is the identity `subject` at `provider`
+    pub fn matches(&self, provider: &str, subject: &str) -> bool {
+        self.provider == provider && self.subject == subject
+    }
+
+    /// Validate identity
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.provider.is_empty() && !self.subject.is_empty()
+    }
+}
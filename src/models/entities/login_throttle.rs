@@ -0,0 +1,218 @@
+//! Login Throttle Entity Module
+//!
+//! Failed password logins counted per account identifier and per client IP,
+//! used for progressive delays and temporary lockouts.
+//!
+//! The account key is the identifier typed by the client (e-mail or username),
+//! whether or not such an account exists, so throttling behaves the same for
+//! unknown accounts and does not reveal which ones exist.
+//!
+//! # Fields
+//! - `id`: SurrealDB Thing with schema `login_throttle:<key>`
+//! - `key`: `account:<identifier>` or `ip:<address>`
+//! - `failures`: Consecutive failures within the failure window
+//! - `last_failure_at`: Timestamp of the latest failure
+//! - `locked_until`: Set while the key is locked out
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// What a throttle key counts failures for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleKind {
+    /// The account (`user:<uuid>`), or the identifier typed for unknown accounts
+    Account,
+    /// The client address
+    Ip,
+}
+
+impl ThrottleKind {
+    /// Builds the key for a value (identifiers are trimmed and lowercased)
+    pub fn key(self, value: &str) -> String {
+        match self {
+            ThrottleKind::Account => format!("account:{}", value.trim().to_lowercase()),
+            ThrottleKind::Ip => format!("ip:{}", value.trim()),
+        }
+    }
+}
+
+/// Thresholds and delays of the login throttling
+#[derive(Debug, Clone)]
+pub struct ThrottlePolicy {
+    /// Failures per account identifier before a lockout
+    pub max_account_failures: u32,
+    /// Failures per IP before a lockout (higher: many users may share an address)
+    pub max_ip_failures: u32,
+    /// Lockout duration
+    pub lockout: Duration,
+    /// Delay after the first account failure; doubles with each further failure
+    pub base_delay: Duration,
+    /// Upper bound of the progressive delay
+    pub max_delay: Duration,
+    /// Failures older than this are forgotten
+    pub window: Duration,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        ThrottlePolicy {
+            max_account_failures: 5,
+            max_ip_failures: 50,
+            lockout: Duration::minutes(15),
+            base_delay: Duration::seconds(1),
+            max_delay: Duration::seconds(60),
+            window: Duration::minutes(15),
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// Failures that lock a key of `kind`
+    pub fn max_failures(&self, kind: ThrottleKind) -> u32 {
+        match kind {
+            ThrottleKind::Account => self.max_account_failures,
+            ThrottleKind::Ip => self.max_ip_failures,
+        }
+    }
+
+    /// Progressive delay after `failures` consecutive account failures
+    pub fn delay_after(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::zero();
+        }
+        let factor = 1i32 << (failures - 1).min(20);
+        (self.base_delay * factor).min(self.max_delay)
+    }
+}
+
+/// Failure counter of one throttle key
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginThrottle {
+    /// Database identifier (SurrealDB Thing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    /// `account:<identifier>` or `ip:<address>`
+    pub key: String,
+
+    /// Consecutive failures within the window
+    #[serde(default)]
+    pub failures: u32,
+
+    /// Latest failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
+
+    /// End of the current lockout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginThrottle {
+    /// Creates an empty counter for `key`
+    pub fn new(key: &str) -> Self {
+        LoginThrottle {
+            id: Some(Thing::from(("login_throttle", key))),
+            key: key.to_string(),
+            failures: 0,
+            last_failure_at: None,
+            locked_until: None,
+        }
+    }
+
+    /// Counts a failure at `now`, restarting the count outside the window
+    pub fn register_failure(&mut self, now: DateTime<Utc>, window: Duration) {
+        let recent = self.last_failure_at.is_some_and(|t| now - t < window);
+        self.failures = if recent { self.failures + 1 } else { 1 };
+        self.last_failure_at = Some(now);
+    }
+
+    /// True while a lockout is in effect
+    pub fn is_locked_at(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|t| now < t)
+    }
+
+    /// True if a lockout was set and has run out
+    pub fn lock_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|t| now >= t)
+    }
+
+    /// Instant before which a new attempt is refused, if any.
+    ///
+    /// # Arguments
+    /// * `policy` - Throttling thresholds
+    /// * `kind` - Progressive delays apply to account keys only
+    /// * `now` - Current time
+    pub fn blocked_until(
+        &self,
+        policy: &ThrottlePolicy,
+        kind: ThrottleKind,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if self.is_locked_at(now) {
+            return self.locked_until;
+        }
+        if kind != ThrottleKind::Account {
+            return None;
+        }
+        let last = self.last_failure_at.filter(|t| now - *t < policy.window)?;
+        let until = last + policy.delay_after(self.failures);
+        (until > now).then_some(until)
+    }
+
+    /// Validate throttle
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        self.key.starts_with("account:") || self.key.starts_with("ip:")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let policy = ThrottlePolicy::default();
+        assert_eq!(policy.delay_after(0), Duration::zero());
+        assert_eq!(policy.delay_after(1), Duration::seconds(1));
+        assert_eq!(policy.delay_after(3), Duration::seconds(4));
+        assert_eq!(policy.delay_after(30), Duration::seconds(60));
+    }
+
+    #[test]
+    fn test_failures_restart_outside_window() {
+        let policy = ThrottlePolicy::default();
+        let now = Utc::now();
+        let mut throttle = LoginThrottle::new(&ThrottleKind::Account.key(" Alice@Example.com"));
+        assert_eq!(throttle.key, "account:alice@example.com");
+        assert!(throttle.is_valid());
+
+        throttle.register_failure(now, policy.window);
+        throttle.register_failure(now, policy.window);
+        assert_eq!(throttle.failures, 2);
+        assert_eq!(
+            throttle.blocked_until(&policy, ThrottleKind::Account, now),
+            Some(now + Duration::seconds(2))
+        );
+        assert!(throttle
+            .blocked_until(&policy, ThrottleKind::Ip, now)
+            .is_none());
+
+        throttle.register_failure(now + Duration::minutes(20), policy.window);
+        assert_eq!(throttle.failures, 1);
+    }
+
+    #[test]
+    fn test_lockout_state() {
+        let now = Utc::now();
+        let mut throttle = LoginThrottle::new("ip:127.0.0.1");
+        throttle.locked_until = Some(now + Duration::minutes(1));
+        assert!(throttle.is_locked_at(now));
+        assert!(!throttle.lock_expired_at(now));
+        assert!(throttle.lock_expired_at(now + Duration::minutes(1)));
+    }
+}
@@ -7,15 +7,21 @@
 //! - `conversation_id`: Reference to the conversation
 //! - `sender_id`: Reference to the user who sent the message
 //! - `content`: Message text content
-//! - `message_type`: Type of message (Text, Image, File)
+//! - `message_type`: Type of message (Text, Image, File, System)
 //! - `created_at`: Timestamp when message was created
 //! - `read_by`: List of user IDs who have read the message
+//! - `task_id`: UUID of the task created from (or announced by) the message
+//!
+//! Clients receive messages as `MessagePayload`, which adds the current name and
+//! status of the linked task.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
 use uuid::Uuid;
 
+use crate::models::entities::task::{Task, TaskStatus};
+
 /// Type of message content
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -26,6 +32,8 @@ pub enum MessageType {
     Image,
     /// File attachment with URL
     File,
+    /// Posted by the server (e.g. "alice created task ...")
+    System,
 }
 
 /// Represents a chat message
@@ -55,6 +63,38 @@ pub struct Message {
     /// List of user IDs who have read this message
     #[serde(default)]
     pub read_by: Vec<Thing>,
+
+    /// UUID of the linked task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+/// Name and current status of the task linked to a message
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LinkedTask {
+    pub uuid: String,
+    pub task_name: String,
+    pub status: TaskStatus,
+}
+
+impl From<&Task> for LinkedTask {
+    fn from(task: &Task) -> Self {
+        LinkedTask {
+            uuid: task.uuid.clone(),
+            task_name: task.task_name.clone(),
+            status: task.status,
+        }
+    }
+}
+
+/// A message as sent to clients
+#[derive(Debug, Serialize, Clone)]
+pub struct MessagePayload {
+    #[serde(flatten)]
+    pub message: Message,
+    /// The linked task; absent if there is none or it was deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<LinkedTask>,
 }
 
 fn default_message_type() -> MessageType {
@@ -89,6 +129,7 @@ impl Message {
             message_type: message_type.unwrap_or(MessageType::Text),
             created_at: Utc::now(),
             read_by: Vec::new(),
+            task_id: None,
         }
     }
 
@@ -5,10 +5,26 @@
 //!
 //! # Module Structure
 //! - `task`: Task entity definitions and related types
+//! - `task_activity`: Task comments, recorded changes and the merged timeline
 //! - `user`: User entity definitions and related types
 //! - `role`: Role entity definitions and related types
 //! - `message`: Message entity for chat functionality
 //! - `conversation`: Conversation entity for chat functionality
+//! - `api_key`: Hashed, scoped API keys for integrations and bots
+//! - `audit_event`: Append-only security audit log entries
+//! - `calendar`: Calendar feed tokens and `.ics` import reports
+//! - `board`: Projects and Kanban boards with status-mapped columns and ordered cards
+//! - `fractional_index`: String keys that sort between any two others (card order)
+//! - `notification`: Notifications kept for offline users (task reminders)
+//! - `login_throttle`: Failed-login counters and lockouts per account and IP
+//! - `identity`: External OAuth2/OIDC identities linked to a user
+//! - `pagination`: Cursor-based page envelope for list endpoints
+//! - `oidc_state`: Pending authorization-code + PKCE logins
+//! - `recurrence`: RRULE-style repeat rules of recurring tasks
+//! - `profile`: Display name, avatar, bio, locale and timezone embedded in `User`
+//! - `one_time_token`: Single-use expiring tokens (password reset, ...)
+//! - `totp`: Two-factor authentication settings embedded in `User`
+//! - `wallet`: Additional wallets linked to a `User`
 //!
 //! # Usage
 //! ```rust,ignore
@@ -21,8 +37,24 @@
 //! - Provide data structures
 //! - Define entity validation rules
 
+pub mod api_key;
+pub mod audit_event;
+pub mod board;
+pub mod calendar;
 pub mod conversation;
+pub mod fractional_index;
+pub mod identity;
+pub mod login_throttle;
 pub mod message;
+pub mod notification;
+pub mod oidc_state;
+pub mod pagination;
+pub mod one_time_token;
+pub mod profile;
+pub mod recurrence;
 pub mod role;
 pub mod task;
+pub mod task_activity;
+pub mod totp;
 pub mod user;
+pub mod wallet;
@@ -0,0 +1,84 @@
+//! Notification Entity Module
+//! Notifications kept for users who were offline when they were sent.
+//!
+//! # Notification
+//! - `uuid`: Record id (`notification:<uuid>`)
+//! - `user_id`: UUID of the recipient
+//! - `kind`: `task_reminder`
+//! - `task_id` / `task_name` / `due_date`: The task the notification is about
+//! - `minutes_before`: For reminders, how long before the due date it was sent
+//! - `read_at`: When the recipient marked it read; unread while absent
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::entities::task::Task;
+
+/// What a notification is about
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    TaskReminder,
+}
+
+/// A notification for one user
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub uuid: String,
+    /// UUID of the recipient
+    pub user_id: String,
+    pub kind: NotificationKind,
+    pub task_id: String,
+    pub task_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutes_before: Option<u32>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    /// Reminder of `task`'s due date, sent `minutes_before` it
+    pub fn task_reminder(user_id: String, task: &Task, minutes_before: u32) -> Self {
+        Notification {
+            uuid: Uuid::new_v4().to_string(),
+            user_id,
+            kind: NotificationKind::TaskReminder,
+            task_id: task.uuid.clone(),
+            task_name: task.task_name.clone(),
+            due_date: task.due_date,
+            minutes_before: Some(minutes_before),
+            created_at: Utc::now(),
+            read_at: None,
+        }
+    }
+
+    /// WebSocket payload:
+    /// `{ "type": "TaskReminder", "task": { "uuid", "task_name", "due_date" }, "minutes_before": 30 }`
+    pub fn payload(&self) -> String {
+        let kind = match self.kind {
+            NotificationKind::TaskReminder => "TaskReminder",
+        };
+        serde_json::json!({
+            "type": kind,
+            "task": {
+                "uuid": self.task_id,
+                "task_name": self.task_name,
+                "due_date": self.due_date,
+            },
+            "minutes_before": self.minutes_before,
+        })
+        .to_string()
+    }
+}
+
+/// Query parameters of `GET /api/notifications`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct NotificationQuery {
+    /// Only notifications not marked read
+    #[serde(default)]
+    pub unread: bool,
+}
@@ -0,0 +1,112 @@
+//! OIDC Login State Entity Module
+//!
+//! Server-side half of an OAuth2 authorization-code + PKCE login in progress.
+//! Created when the user is redirected to the provider and consumed (deleted)
+//! by the callback.
+//!
+//! # Fields
+//! - `id`: SurrealDB Thing with schema `oidc_state:<uuid-v4>`
+//! - `state_hash`: SHA-256 (hex) of the `state` parameter sent to the provider
+//! - `provider`: Configured provider name the login was started for
+//! - `code_verifier`: PKCE verifier; never leaves the server
+//! - `nonce`: Expected `nonce` claim of the ID token
+//! - `link_user_id`: Signed-in user the identity is linked to (link mode only)
+//! - `expires_at`: After this instant the callback is rejected
+//! - `created_at`: Timestamp when the login was started
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+/// Represents a pending external login
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OidcLoginState {
+    /// Database identifier (SurrealDB Thing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    /// SHA-256 (hex) of the `state` parameter
+    pub state_hash: String,
+
+    /// Provider the login was started for
+    pub provider: String,
+
+    /// PKCE code verifier
+    pub code_verifier: String,
+
+    /// Expected ID token nonce
+    pub nonce: String,
+
+    /// Account to attach the identity to, when started from `/api/me/identities`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_user_id: Option<Thing>,
+
+    /// When the pending login expires
+    pub expires_at: DateTime<Utc>,
+
+    /// When the login was started
+    pub created_at: DateTime<Utc>,
+}
+
+impl OidcLoginState {
+    /// Creates a new pending login valid for `ttl`
+    ///
+    /// # Arguments
+    /// * `provider` - Configured provider name
+    /// * `state_hash` - SHA-256 (hex) of the `state` parameter
+    /// * `code_verifier` - PKCE verifier
+    /// * `nonce` - Expected ID token nonce
+    /// * `ttl` - How long the callback is accepted
+    pub fn new(
+        provider: &str,
+        state_hash: String,
+        code_verifier: String,
+        nonce: String,
+        ttl: Duration,
+    ) -> Self {
+        let uuid = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        OidcLoginState {
+            id: Some(Thing::from(("oidc_state", uuid.as_str()))),
+            state_hash,
+            provider: provider.to_string(),
+            code_verifier,
+            nonce,
+            link_user_id: None,
+            expires_at: now + ttl,
+            created_at: now,
+        }
+    }
+
+    /// Turns the pending login into a link request for `user_id`
+    pub fn with_link_user(mut self, user_id: Thing) -> Self {
+        self.link_user_id = Some(user_id);
+        self
+    }
+
+    /// True if the callback for this login is still accepted at `now`
+    pub fn is_usable_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_state_expires() {
+        let state = OidcLoginState::new(
+            "google",
+            "hash".to_string(),
+            "verifier".to_string(),
+            "nonce".to_string(),
+            Duration::minutes(10),
+        );
+        assert!(state.id.is_some());
+        assert!(state.is_usable_at(Utc::now()));
+        assert!(!state.is_usable_at(Utc::now() + Duration::minutes(11)));
+    }
+}
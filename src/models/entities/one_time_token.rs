@@ -0,0 +1,132 @@
+//! One-Time Token Entity Module
+//!
+//! Single-use, expiring secrets sent to a user out of band (e.g. by e-mail):
+//! password reset links, e-mail verification links, WebSocket tickets and
+//! wallet link and login challenges.
+//!
+//! # Fields
+//! - `id`: SurrealDB Thing with schema `one_time_token:<uuid-v4>`
+//! - `user_id`: Reference to the user the token was issued for
+//! - `purpose`: What the token can be used for
+//! - `token_hash`: SHA-256 (hex) of the secret; the plaintext is never stored
+//! - `expires_at`: After this instant the token is rejected
+//! - `used_at`: Set when the token is consumed; a used token is rejected
+//! - `created_at`: Timestamp when the token was issued
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+/// What a one-time token authorizes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenPurpose {
+    /// Set a new password without knowing the current one
+    PasswordReset,
+    /// Confirm ownership of the account's email address
+    EmailVerification,
+    /// Open a WebSocket connection without putting the JWT in the URL
+    WsTicket,
+    /// Prove ownership of a wallet being linked (signed challenge nonce)
+    WalletLink,
+    /// Prove ownership of a wallet to sign in with it (signed challenge nonce).
+    /// Issued for `wallet:<address>`, since the account may not exist yet.
+    WalletLogin,
+}
+
+/// Represents a single-use token
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OneTimeToken {
+    /// Database identifier (SurrealDB Thing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+
+    /// Reference to the user the token was issued for
+    pub user_id: Thing,
+
+    /// What the token can be used for
+    pub purpose: TokenPurpose,
+
+    /// SHA-256 digest (hex) of the secret
+    pub token_hash: String,
+
+    /// Expiration instant
+    pub expires_at: DateTime<Utc>,
+
+    /// When the token was consumed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub used_at: Option<DateTime<Utc>>,
+
+    /// When the token was issued
+    pub created_at: DateTime<Utc>,
+}
+
+impl OneTimeToken {
+    /// Creates a new unused token valid for `ttl`
+    ///
+    /// # Arguments
+    /// * `user_id` - The user the token is issued for
+    /// * `purpose` - What the token authorizes
+    /// * `token_hash` - SHA-256 digest of the secret handed to the user
+    /// * `ttl` - How long the token stays valid
+    pub fn new(user_id: Thing, purpose: TokenPurpose, token_hash: String, ttl: Duration) -> Self {
+        let uuid = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        OneTimeToken {
+            id: Some(Thing::from(("one_time_token", uuid.as_str()))),
+            user_id,
+            purpose,
+            token_hash,
+            expires_at: now + ttl,
+            used_at: None,
+            created_at: now,
+        }
+    }
+
+    /// Check whether the token can still be used at `now`
+    pub fn is_usable_at(&self, now: DateTime<Utc>) -> bool {
+        self.used_at.is_none() && now < self.expires_at
+    }
+
+    /// Validate token
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.token_hash.is_empty() && self.expires_at > self.created_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_token_is_usable_until_expiry() {
+        let token = OneTimeToken::new(
+            Thing::from(("user", "u1")),
+            TokenPurpose::PasswordReset,
+            "abc".to_string(),
+            Duration::minutes(30),
+        );
+
+        assert!(token.is_valid());
+        assert!(token.is_usable_at(Utc::now()));
+        assert!(!token.is_usable_at(Utc::now() + Duration::minutes(31)));
+    }
+
+    #[test]
+    fn test_used_token_is_not_usable() {
+        let mut token = OneTimeToken::new(
+            Thing::from(("user", "u1")),
+            TokenPurpose::PasswordReset,
+            "abc".to_string(),
+            Duration::minutes(30),
+        );
+        token.used_at = Some(Utc::now());
+
+        assert!(!token.is_usable_at(Utc::now()));
+    }
+}
@@ -0,0 +1,81 @@
+//! Pagination Module
+//!
+//! Cursor-based response envelope shared by list endpoints:
+//! `{ "items": [...], "next_cursor": "<opaque>" | null }`.
+//!
+//! A cursor is the base64url encoding of the sort key of the last returned
+//! item; clients pass it back unchanged as `?cursor=` to get the next page.
+//! Repositories fetch `limit + 1` rows so a next page can be detected without
+//! a count query.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+
+/// One page of results
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    /// Items of this page, in the endpoint's sort order
+    pub items: Vec<T>,
+    /// Cursor of the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from up to `limit + 1` rows; the extra row only signals
+    /// that more results exist. `sort_key` gives the cursor of an item.
+    pub fn from_overfetch(mut rows: Vec<T>, limit: usize, sort_key: impl Fn(&T) -> String) -> Self {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_more {
+            rows.last().map(|last| encode_cursor(&sort_key(last)))
+        } else {
+            None
+        };
+        Page {
+            items: rows,
+            next_cursor,
+        }
+    }
+
+    /// Converts the items, keeping the cursor.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Encodes a sort key as an opaque cursor.
+pub fn encode_cursor(key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(key)
+}
+
+/// Decodes a cursor produced by `encode_cursor`; `None` if it is malformed.
+pub fn decode_cursor(cursor: &str) -> Option<String> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overfetch_sets_cursor_only_when_more_rows_exist() {
+        let page = Page::from_overfetch(vec!["a", "b", "c"], 2, |s| s.to_string());
+        assert_eq!(page.items, vec!["a", "b"]);
+        assert_eq!(
+            page.next_cursor
+                .as_deref()
+                .and_then(decode_cursor)
+                .as_deref(),
+            Some("b")
+        );
+
+        let last = Page::from_overfetch(vec!["c"], 2, |s| s.to_string());
+        assert_eq!(last.next_cursor, None);
+        assert_eq!(decode_cursor("not base64!"), None);
+    }
+}
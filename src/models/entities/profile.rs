@@ -0,0 +1,131 @@
+//! User Profile Module
+//!
+//! Self-managed presentation fields embedded in `User::profile`. All fields are
+//! optional; rows created before profiles existed deserialize to the default.
+//!
+//! # Fields
+//! - `display_name`: Name shown instead of the username
+//! - `avatar_url`: http(s) URL of the avatar image
+//! - `bio`: Short free text
+//! - `locale`: BCP 47 language tag (`es`, `es-PE`, `pt-BR`)
+//! - `timezone`: IANA time zone name (`America/Lima`, `UTC`)
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum length (characters) of the display name
+pub const MAX_DISPLAY_NAME_LENGTH: u64 = 64;
+/// Maximum length (characters) of the bio
+pub const MAX_BIO_LENGTH: u64 = 500;
+
+/// Top-level areas of the IANA time zone database
+const TIMEZONE_AREAS: [&str; 10] = [
+    "Africa",
+    "America",
+    "Antarctica",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Europe",
+    "Indian",
+    "Pacific",
+    "Etc",
+];
+
+/// Profile fields of a user
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct UserProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+impl UserProfile {
+    /// Applies a partial update: `None` keeps a field, an empty (or blank)
+    /// string clears it, anything else replaces it (trimmed).
+    pub fn merge(&mut self, patch: UserProfile) {
+        fn apply(field: &mut Option<String>, value: Option<String>) {
+            if let Some(value) = value {
+                let value = value.trim();
+                *field = (!value.is_empty()).then(|| value.to_string());
+            }
+        }
+        apply(&mut self.display_name, patch.display_name);
+        apply(&mut self.avatar_url, patch.avatar_url);
+        apply(&mut self.bio, patch.bio);
+        apply(&mut self.locale, patch.locale);
+        apply(&mut self.timezone, patch.timezone);
+    }
+}
+
+/// True for a BCP 47 style tag: a 2-3 letter language, then `-`-separated
+/// subtags of 1-8 letters or digits (`en`, `es-419`, `zh-Hant-TW`).
+pub fn is_valid_locale(tag: &str) -> bool {
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or_default();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|p| (1..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// True for `UTC` or an IANA style `Area/Location[/Sub]` name with a known area.
+///
+/// The name is checked for shape only; there is no bundled time zone database.
+pub fn is_valid_timezone(name: &str) -> bool {
+    if name == "UTC" {
+        return true;
+    }
+    let parts: Vec<&str> = name.split('/').collect();
+    (2..=3).contains(&parts.len())
+        && TIMEZONE_AREAS.contains(&parts[0])
+        && parts[1..].iter().all(|p| {
+            !p.is_empty()
+                && p.len() <= 32
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_replaces_and_clears() {
+        let mut profile = UserProfile {
+            display_name: Some("Alice".to_string()),
+            bio: Some("hi".to_string()),
+            ..UserProfile::default()
+        };
+        profile.merge(UserProfile {
+            display_name: Some("  Alice L. ".to_string()),
+            bio: Some(" ".to_string()),
+            locale: Some("es-PE".to_string()),
+            ..UserProfile::default()
+        });
+        assert_eq!(profile.display_name.as_deref(), Some("Alice L."));
+        assert_eq!(profile.bio, None);
+        assert_eq!(profile.locale.as_deref(), Some("es-PE"));
+        assert_eq!(profile.timezone, None);
+    }
+
+    #[test]
+    fn test_locale_and_timezone_shapes() {
+        assert!(is_valid_locale("en"));
+        assert!(is_valid_locale("zh-Hant-TW"));
+        assert!(!is_valid_locale("english"));
+        assert!(!is_valid_locale("en_US"));
+
+        assert!(is_valid_timezone("UTC"));
+        assert!(is_valid_timezone("America/Argentina/Buenos_Aires"));
+        assert!(is_valid_timezone("Etc/GMT+5"));
+        assert!(!is_valid_timezone("Mars/Olympus"));
+        assert!(!is_valid_timezone("America/"));
+    }
+}
@@ -0,0 +1,228 @@
+//! Recurrence Entity Module
+//! Repeating tasks, described with the RRULE fields of iCalendar (RFC 5545).
+//!
+//! # RecurrenceRule
+//! - `freq`: `daily`, `weekly` or `monthly` (RRULE `FREQ`)
+//! - `interval`: Periods between occurrences, default 1 (`INTERVAL`)
+//! - `count`: Number of occurrences of the whole series (`COUNT`)
+//! - `until`: No occurrence is due after this instant (`UNTIL`)
+//!
+//! # TaskRecurrence
+//! Stored on every task of a series:
+//! - `rule`: The rule above
+//! - `anchor`: Due date of the first occurrence; later due dates are computed
+//!   from it, so month ends do not drift (Jan 31 -> Feb 28 -> Mar 31)
+//! - `index`: Zero-based position of the task in the series
+//! - `next_task`: UUID of the occurrence spawned when this one was completed
+
+use chrono::{DateTime, Duration, Months, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Maximum `interval` of a rule
+pub const MAX_RECURRENCE_INTERVAL: u32 = 1000;
+
+/// How often a task repeats
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceFreq {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// When a task repeats
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub freq: RecurrenceFreq,
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl RecurrenceRule {
+    /// Checks the bounds of `interval` and `count`
+    pub fn check(&self) -> Result<(), String> {
+        if self.interval == 0 || self.interval > MAX_RECURRENCE_INTERVAL {
+            return Err(format!(
+                "recurrence interval must be between 1 and {}",
+                MAX_RECURRENCE_INTERVAL
+            ));
+        }
+        if self.count == Some(0) {
+            return Err("recurrence count must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Due date of occurrence `index` (zero-based) of a series starting at `anchor`
+    pub fn nth(&self, anchor: DateTime<Utc>, index: u32) -> Option<DateTime<Utc>> {
+        let steps = self.interval.checked_mul(index)?;
+        match self.freq {
+            RecurrenceFreq::Daily => anchor.checked_add_signed(Duration::days(steps.into())),
+            RecurrenceFreq::Weekly => anchor.checked_add_signed(Duration::weeks(steps.into())),
+            RecurrenceFreq::Monthly => anchor.checked_add_months(Months::new(steps)),
+        }
+    }
+
+    /// Due date of the occurrence after `index`; None once the series has ended
+    pub fn after(&self, anchor: DateTime<Utc>, index: u32) -> Option<DateTime<Utc>> {
+        let next = index.checked_add(1)?;
+        if self.count.is_some_and(|count| next >= count) {
+            return None;
+        }
+        self.nth(anchor, next)
+            .filter(|due| self.until.is_none_or(|until| *due <= until))
+    }
+
+    /// The rule as an iCalendar RRULE value, e.g. `FREQ=WEEKLY;INTERVAL=2;COUNT=5`
+    pub fn to_rrule(&self) -> String {
+        let freq = match self.freq {
+            RecurrenceFreq::Daily => "DAILY",
+            RecurrenceFreq::Weekly => "WEEKLY",
+            RecurrenceFreq::Monthly => "MONTHLY",
+        };
+        let mut rrule = format!("FREQ={}", freq);
+        if self.interval != 1 {
+            rrule.push_str(&format!(";INTERVAL={}", self.interval));
+        }
+        if let Some(count) = self.count {
+            rrule.push_str(&format!(";COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            rrule.push_str(&format!(";UNTIL={}", until.format("%Y%m%dT%H%M%SZ")));
+        }
+        rrule
+    }
+
+    /// Reads an iCalendar RRULE value; only FREQ, INTERVAL, COUNT and UNTIL are
+    /// understood, other parts (BYDAY, ...) are rejected
+    pub fn from_rrule(rrule: &str) -> Result<RecurrenceRule, String> {
+        let mut freq = None;
+        let mut rule = RecurrenceRule {
+            freq: RecurrenceFreq::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+        };
+        for part in rrule.trim().split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("malformed RRULE part {}", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => RecurrenceFreq::Daily,
+                        "WEEKLY" => RecurrenceFreq::Weekly,
+                        "MONTHLY" => RecurrenceFreq::Monthly,
+                        other => return Err(format!("unsupported FREQ {}", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .map_err(|_| format!("malformed INTERVAL {}", value))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("malformed COUNT {}", value))?,
+                    )
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                other => return Err(format!("unsupported RRULE part {}", other)),
+            }
+        }
+        rule.freq = freq.ok_or("RRULE without FREQ")?;
+        rule.check()?;
+        Ok(rule)
+    }
+}
+
+// UNTIL is a UTC date-time (`20261231T235959Z`) or a date (`20261231`, end of day)
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim_end_matches('Z');
+    let parsed = if value.len() == 8 {
+        NaiveDateTime::parse_from_str(&format!("{}T235959", value), "%Y%m%dT%H%M%S")
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+    };
+    parsed
+        .map(|naive| Utc.from_utc_datetime(&naive))
+        .map_err(|_| format!("malformed UNTIL {}", value))
+}
+
+/// A task's place in its recurring series
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskRecurrence {
+    pub rule: RecurrenceRule,
+    /// Due date of the first occurrence
+    pub anchor: DateTime<Utc>,
+    /// Zero-based position in the series
+    #[serde(default)]
+    pub index: u32,
+    /// UUID of the next occurrence, once spawned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_task: Option<String>,
+}
+
+impl TaskRecurrence {
+    /// Starts a series whose first occurrence is due at `anchor`
+    pub fn new(rule: RecurrenceRule, anchor: DateTime<Utc>) -> Self {
+        TaskRecurrence {
+            rule,
+            anchor,
+            index: 0,
+            next_task: None,
+        }
+    }
+
+    /// The recurrence of the following occurrence and its due date; None once
+    /// the series has ended
+    pub fn following(&self) -> Option<(TaskRecurrence, DateTime<Utc>)> {
+        let due = self.rule.after(self.anchor, self.index)?;
+        let next = TaskRecurrence {
+            rule: self.rule.clone(),
+            anchor: self.anchor,
+            index: self.index + 1,
+            next_task: None,
+        };
+        Some((next, due))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_occurrences_follow_the_anchor_and_end_conditions() {
+        let monthly = RecurrenceRule::from_rrule("FREQ=MONTHLY;COUNT=3").unwrap();
+        let anchor = at("2026-01-31T09:00:00Z");
+        assert_eq!(monthly.after(anchor, 0), Some(at("2026-02-28T09:00:00Z")));
+        assert_eq!(monthly.after(anchor, 1), Some(at("2026-03-31T09:00:00Z")));
+        assert_eq!(monthly.after(anchor, 2), None);
+
+        let weekly = RecurrenceRule::from_rrule("FREQ=WEEKLY;INTERVAL=2;UNTIL=20260301").unwrap();
+        let anchor = at("2026-02-01T09:00:00Z");
+        assert_eq!(weekly.after(anchor, 0), Some(at("2026-02-15T09:00:00Z")));
+        assert_eq!(weekly.after(anchor, 1), Some(at("2026-03-01T09:00:00Z")));
+        assert_eq!(weekly.after(anchor, 2), None);
+        assert_eq!(weekly.to_rrule(), "FREQ=WEEKLY;INTERVAL=2;UNTIL=20260301T235959Z");
+
+        assert!(RecurrenceRule::from_rrule("FREQ=YEARLY").is_err());
+        assert!(RecurrenceRule::from_rrule("FREQ=DAILY;BYDAY=MO").is_err());
+        assert!(RecurrenceRule::from_rrule("FREQ=DAILY;INTERVAL=0").is_err());
+    }
+}
@@ -1,3 +1,14 @@
+//! Role Entity Module
+//!
+//! Roles are stored in the `role` table (`role:<uuid-v4>`, unique `name`) and
+//! embedded by value in `User::roles`. The built-in roles in [`roles`] are seeded
+//! with `cargo run -- --seed-roles`; any other role is custom and managed via
+//! `/api/roles`.
+//!
+//! Permisos:
+//! - Se exponen y aceptan como strings `recurso:accion` (ej: `message:delete`).
+//! - La deserialización acepta también el nombre de variante (`MessageDelete`)
+//!   para filas persistidas antes de este formato.
 
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
@@ -20,7 +31,7 @@ pub struct Role {
 }
 
 /// Permisos disponibles en el sistema
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Permission {
     // Permisos de administración
     AdminAll,  // Acceso total al sistema
@@ -49,6 +60,64 @@ pub enum Permission {
     UserInvite,
     UserKick,
     UserBan,
+
+    // Permisos de tareas (sobre tareas ajenas; las propias no los necesitan)
+    TaskRead,
+    TaskUpdate,
+    TaskDelete,
+}
+
+impl Permission {
+    /// Todos los permisos, en orden de declaración
+    pub const ALL: [Permission; 21] = [
+        Permission::AdminAll,
+        Permission::WorkspaceCreate,
+        Permission::WorkspaceRead,
+        Permission::WorkspaceUpdate,
+        Permission::WorkspaceDelete,
+        Permission::WorkspaceManageMembers,
+        Permission::ChannelCreate,
+        Permission::ChannelRead,
+        Permission::ChannelUpdate,
+        Permission::ChannelDelete,
+        Permission::ChannelSendMessages,
+        Permission::MessageCreate,
+        Permission::MessageUpdate,
+        Permission::MessageDelete,
+        Permission::MessagePin,
+        Permission::UserInvite,
+        Permission::UserKick,
+        Permission::UserBan,
+        Permission::TaskRead,
+        Permission::TaskUpdate,
+        Permission::TaskDelete,
+    ];
+}
+
+// Serialize as "recurso:accion"
+impl Serialize for Permission {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+// Deserialize "recurso:accion" or the legacy variant name ("AdminAll")
+impl<'de> Deserialize<'de> for Permission {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<Permission>().or_else(|err| {
+            Permission::ALL
+                .into_iter()
+                .find(|p| format!("{:?}", p) == s)
+                .ok_or_else(|| serde::de::Error::custom(err))
+        })
+    }
 }
 
 impl Role {
@@ -73,6 +142,24 @@ impl Role {
     pub fn has_permission(&self, permission: Permission) -> bool {
         self.permissions.contains(&permission) || self.permissions.contains(&Permission::AdminAll)
     }
+
+    /// Indica si es uno de los roles predefinidos (`admin`, `moderator`, `user`)
+    pub fn is_builtin(&self) -> bool {
+        roles::BUILTIN_NAMES.contains(&self.name.as_str())
+    }
+
+    /// Valida el rol: nombre en minúsculas `[a-z0-9_-]`, 2..=32 caracteres
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        let len = self.name.len();
+        (2..=32).contains(&len)
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    }
 }
 
 // Implementación de Display para Permission
@@ -81,8 +168,25 @@ impl fmt::Display for Permission {
         let permission_str = match self {
             Permission::AdminAll => "admin:all",
             Permission::WorkspaceCreate => "workspace:create",
-            // ... otros permisos
-            _ => "unknown",
+            Permission::WorkspaceRead => "workspace:read",
+            Permission::WorkspaceUpdate => "workspace:update",
+            Permission::WorkspaceDelete => "workspace:delete",
+            Permission::WorkspaceManageMembers => "workspace:manage_members",
+            Permission::ChannelCreate => "channel:create",
+            Permission::ChannelRead => "channel:read",
+            Permission::ChannelUpdate => "channel:update",
+            Permission::ChannelDelete => "channel:delete",
+            Permission::ChannelSendMessages => "channel:send_messages",
+            Permission::MessageCreate => "message:create",
+            Permission::MessageUpdate => "message:update",
+            Permission::MessageDelete => "message:delete",
+            Permission::MessagePin => "message:pin",
+            Permission::UserInvite => "user:invite",
+            Permission::UserKick => "user:kick",
+            Permission::UserBan => "user:ban",
+            Permission::TaskRead => "task:read",
+            Permission::TaskUpdate => "task:update",
+            Permission::TaskDelete => "task:delete",
         };
         write!(f, "{}", permission_str)
     }
@@ -96,7 +200,25 @@ impl FromStr for Permission {
         match s {
             "admin:all" => Ok(Permission::AdminAll),
             "workspace:create" => Ok(Permission::WorkspaceCreate),
-            // ... otros permisos
+            "workspace:read" => Ok(Permission::WorkspaceRead),
+            "workspace:update" => Ok(Permission::WorkspaceUpdate),
+            "workspace:delete" => Ok(Permission::WorkspaceDelete),
+            "workspace:manage_members" => Ok(Permission::WorkspaceManageMembers),
+            "channel:create" => Ok(Permission::ChannelCreate),
+            "channel:read" => Ok(Permission::ChannelRead),
+            "channel:update" => Ok(Permission::ChannelUpdate),
+            "channel:delete" => Ok(Permission::ChannelDelete),
+            "channel:send_messages" => Ok(Permission::ChannelSendMessages),
+            "message:create" => Ok(Permission::MessageCreate),
+            "message:update" => Ok(Permission::MessageUpdate),
+            "message:delete" => Ok(Permission::MessageDelete),
+            "message:pin" => Ok(Permission::MessagePin),
+            "user:invite" => Ok(Permission::UserInvite),
+            "user:kick" => Ok(Permission::UserKick),
+            "user:ban" => Ok(Permission::UserBan),
+            "task:read" => Ok(Permission::TaskRead),
+            "task:update" => Ok(Permission::TaskUpdate),
+            "task:delete" => Ok(Permission::TaskDelete),
             _ => Err(format!("Permiso no válido: {}", s)),
         }
     }
@@ -105,6 +227,14 @@ impl FromStr for Permission {
 // Tipos de roles predefinidos
 pub mod roles {
     use super::*;
+
+    /// Nombres de los roles predefinidos
+    pub const BUILTIN_NAMES: [&str; 3] = ["admin", "moderator", "user"];
+
+    /// Todos los roles predefinidos (usados por el comando `--seed-roles`)
+    pub fn builtin() -> Vec<Role> {
+        vec![admin(), moderator(), user()]
+    }
     
     /// Rol de administrador con todos los permisos
     pub fn admin() -> Role {
@@ -122,6 +252,8 @@ pub mod roles {
                 Permission::ChannelRead,
                 Permission::ChannelSendMessages,
                 Permission::MessageDelete,
+                Permission::TaskRead,
+                Permission::TaskUpdate,
             ])
     }
     
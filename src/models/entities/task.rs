@@ -1,15 +1,291 @@
 //! Task Entity Module
 //! Defines the core task-related data structures and their behavior.
+//!
+//! # Fields
+//! - `uuid`: Record id (`task:<uuid>`)
+//! - `task_name`: Short title
+//! - `description`: Optional longer text
+//! - `status`: `todo`, `in_progress` or `done`
+//! - `priority`: `low`, `medium`, `high` or `urgent`
+//! - `assignee`: UUID of the user the task is assigned to
+//! - `creator`: UUID of the user who created the task (its owner)
+//! - `conversation_id`: UUID of the conversation the task belongs to, if any;
+//!   its participants can see the task
+//! - `source_message_id`: UUID of the chat message the task was created from
+//! - `due_date`: When the task should be done
+//! - `created_at` / `updated_at`: Timestamps of creation and last change
+//! - `completed_at`: When the task last moved to `done` (cleared when reopened)
+//! - `checklist`: Ordered checklist items
+//! - `progress`: Checked items and finished subtasks out of all of them, kept
+//!   up to date as the checklist and the subtasks change
+//! - `recurrence`: The repeat rule and the task's place in its series;
+//!   completing the task spawns the next occurrence
+//! - `reminders`: Minutes before `due_date` at which the assignee (or, without
+//!   one, the creator) is reminded; `reminded` lists those already sent for
+//!   the current due date
+//! - `watchers`: UUIDs of the users notified of new comments and changes
+//!   (creator, assignees, commenters, mentioned users, and whoever watches it)
+//!
+//! The parent task and the blocking "depends on" relations are not fields: they
+//! are stored as graph edges (`task->subtask_of->task`, `task->depends_on->task`).
+//!
+//! Rows created before these fields existed deserialize with the defaults
+//! (`todo`, `medium`, no assignee / creator).
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use validator::Validate;
 
+use crate::models::entities::recurrence::{RecurrenceRule, TaskRecurrence};
+
+/// Maximum length (characters) of the task name
+pub const MAX_TASK_NAME_LENGTH: u64 = 200;
+/// Maximum length (characters) of the description
+pub const MAX_TASK_DESCRIPTION_LENGTH: u64 = 5000;
+/// Maximum length (characters) of a checklist item
+pub const MAX_CHECKLIST_ITEM_LENGTH: u64 = 500;
+/// Maximum number of reminders of a task
+pub const MAX_REMINDERS: usize = 10;
+/// Earliest reminder, in minutes before the due date (4 weeks)
+pub const MAX_REMINDER_MINUTES: u32 = 40_320;
+
+/// Workflow state of a task
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Not started
+    #[default]
+    Todo,
+    /// Being worked on
+    InProgress,
+    /// Finished
+    Done,
+}
+
+impl TaskStatus {
+    /// The status as written in requests and responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+/// Importance of a task
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+/// One entry of a task's checklist
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    /// UUID of the item
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Completion of a task's checklist and subtasks
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskProgress {
+    pub done: u32,
+    pub total: u32,
+    /// `done / total` as a whole percentage (rounded down)
+    pub percent: u8,
+}
+
+impl TaskProgress {
+    /// Counts checked items and `done` subtasks; None when there are neither
+    pub fn of(checklist: &[ChecklistItem], subtasks: &[Task]) -> Option<TaskProgress> {
+        let total = (checklist.len() + subtasks.len()) as u32;
+        if total == 0 {
+            return None;
+        }
+        let done = (checklist.iter().filter(|i| i.done).count()
+            + subtasks
+                .iter()
+                .filter(|t| t.status == TaskStatus::Done)
+                .count()) as u32;
+        Some(TaskProgress {
+            done,
+            total,
+            percent: (done * 100 / total) as u8,
+        })
+    }
+}
+
+/// Kind of a real-time task change (WebSocket `type` field)
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum TaskEventKind {
+    TaskCreated,
+    TaskUpdated,
+    TaskDeleted,
+}
+
+/// A task change and the users to notify
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskEvent {
+    pub kind: TaskEventKind,
+    /// The task after the change (before it, for deletions)
+    pub task: Task,
+    /// UUIDs of the users who can see the task
+    pub audience: Vec<String>,
+}
+
+impl TaskEvent {
+    /// WebSocket payload: `{ "type": "TaskUpdated", "task": { ... } }`
+    pub fn payload(&self) -> String {
+        serde_json::json!({
+            "type": self.kind,
+            "task": self.task,
+        })
+        .to_string()
+    }
+}
+
 /// Request payload for creating a new task
-#[derive(Validate, Serialize, Deserialize)]
+#[derive(Validate, Serialize, Deserialize, Default)]
 pub struct AddTaskRequest {
     /// Name of the task, must not be empty
-    #[validate(length(min=1, message="task name required"))]
+    #[validate(length(
+        min = 1,
+        max = "MAX_TASK_NAME_LENGTH",
+        message = "task name required (at most 200 characters)"
+    ))]
     pub task_name: String,
+    #[validate(length(
+        max = "MAX_TASK_DESCRIPTION_LENGTH",
+        message = "description must be at most 5000 characters"
+    ))]
+    pub description: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    /// UUID of an existing user
+    pub assignee: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
+    /// UUID of a conversation the creator takes part in
+    pub conversation_id: Option<String>,
+    /// UUID of a task the creator can see; the new task becomes its subtask
+    pub parent: Option<String>,
+    /// Repeat the task; requires `due_date`
+    pub recurrence: Option<RecurrenceRule>,
+    /// Minutes before `due_date` to remind the assignee
+    pub reminders: Option<Vec<u32>>,
+    /// Set by the server when the task is created from a chat message
+    #[serde(skip)]
+    pub source_message_id: Option<String>,
+}
+
+/// Request payload for turning a chat message into a task
+/// (`POST /api/messages/{id}/task`); every field is optional
+#[derive(Validate, Serialize, Deserialize, Default, Debug)]
+pub struct CreateTaskFromMessageRequest {
+    /// Defaults to the first line of the message
+    #[validate(length(
+        min = 1,
+        max = "MAX_TASK_NAME_LENGTH",
+        message = "task name required (at most 200 characters)"
+    ))]
+    pub task_name: Option<String>,
+    /// Defaults to the whole message when it has more than one line
+    pub description: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub assignee: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+/// Partial update of a task (`PATCH /api/tasks/{uuid}`)
+///
+/// Absent fields are kept. For `description`, `assignee`, `due_date`,
+/// `parent` and `recurrence` an explicit `null` clears the value.
+#[derive(Validate, Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateTaskRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_TASK_NAME_LENGTH",
+        message = "task name required (at most 200 characters)"
+    ))]
+    pub task_name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(custom(function = "validate_description"))]
+    pub description: Option<Option<String>>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub assignee: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub due_date: Option<Option<DateTime<Utc>>>,
+    /// UUID of the parent task; `null` makes the task top-level
+    #[serde(default, deserialize_with = "nullable")]
+    pub parent: Option<Option<String>>,
+    /// New repeat rule; the series restarts from the current due date
+    #[serde(default, deserialize_with = "nullable")]
+    pub recurrence: Option<Option<RecurrenceRule>>,
+    /// Minutes before `due_date` to remind the assignee (replaces the list)
+    pub reminders: Option<Vec<u32>>,
+    /// Set by the server (`?force=true`): allows `done` while blockers are open
+    #[serde(skip)]
+    pub force: bool,
+}
+
+impl UpdateTaskRequest {
+    /// True if the request changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.task_name.is_none()
+            && self.description.is_none()
+            && self.status.is_none()
+            && self.priority.is_none()
+            && self.assignee.is_none()
+            && self.due_date.is_none()
+            && self.parent.is_none()
+            && self.recurrence.is_none()
+            && self.reminders.is_none()
+    }
+}
+
+/// Request payload for adding a checklist item
+#[derive(Validate, Serialize, Deserialize, Default, Debug)]
+pub struct AddChecklistItemRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_CHECKLIST_ITEM_LENGTH",
+        message = "item text required (at most 500 characters)"
+    ))]
+    pub text: String,
+    /// Zero-based position; appended when absent or past the end
+    pub position: Option<usize>,
+}
+
+/// Partial update of a checklist item
+#[derive(Validate, Serialize, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateChecklistItemRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_CHECKLIST_ITEM_LENGTH",
+        message = "item text required (at most 500 characters)"
+    ))]
+    pub text: Option<String>,
+    pub done: Option<bool>,
+    /// New zero-based position (clamped to the end)
+    pub position: Option<usize>,
+}
+
+/// Request payload for adding a blocking relation
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddDependencyRequest {
+    /// UUID of the task that must be done first
+    pub depends_on: String,
 }
 
 /// URL parameters for task update operations
@@ -19,22 +295,274 @@ pub struct UpdateTaskUrl {
     pub uuid: String,
 }
 
+/// URL parameters for checklist item operations
+#[derive(Serialize, Deserialize)]
+pub struct ChecklistItemUrl {
+    pub uuid: String,
+    pub item_id: String,
+}
+
+/// URL parameters for removing a blocking relation
+#[derive(Serialize, Deserialize)]
+pub struct DependencyUrl {
+    pub uuid: String,
+    /// UUID of the blocking task
+    pub blocker: String,
+}
+
 /// Represents a Task entity in the system
-#[derive(Validate, Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Task {
     /// Unique identifier for the task
     pub uuid: String,
-    /// Name/description of the task
+    /// Name of the task
     pub task_name: String,
+    /// Longer description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Workflow state
+    #[serde(default)]
+    pub status: TaskStatus,
+    /// Importance
+    #[serde(default)]
+    pub priority: TaskPriority,
+    /// UUID of the assigned user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// UUID of the user who created the task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// UUID of the conversation the task belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    /// UUID of the chat message the task was created from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_message_id: Option<String>,
+    /// When the task is due
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<DateTime<Utc>>,
+    /// When the task was created
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// When the task was last changed
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+    /// When the task was completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Ordered checklist
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    /// Completion of the checklist and subtasks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<TaskProgress>,
+    /// Repeat rule and place in the series
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<TaskRecurrence>,
+    /// Minutes before the due date to remind the assignee
+    #[serde(default)]
+    pub reminders: Vec<u32>,
+    /// Reminders already sent for the current due date
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminded: Vec<u32>,
+    /// UUIDs of the users following the task's timeline
+    #[serde(default)]
+    pub watchers: Vec<String>,
+    /// Incremented by every change; writes only succeed against the version
+    /// they were computed from
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl Task {
     /// Creates a new Task instance
-    /// 
+    ///
     /// # Arguments
     /// * `uuid` - Unique identifier for the task
-    /// * `task_name` - Name/description of the task
+    /// * `task_name` - Name of the task
     pub fn new(uuid: String, task_name: String) -> Task {
-        Task { uuid, task_name }
+        let now = Utc::now();
+        Task {
+            uuid,
+            task_name,
+            description: None,
+            status: TaskStatus::Todo,
+            priority: TaskPriority::Medium,
+            assignee: None,
+            creator: None,
+            conversation_id: None,
+            source_message_id: None,
+            due_date: None,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            checklist: Vec::new(),
+            progress: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            reminded: Vec::new(),
+            watchers: Vec::new(),
+            version: 0,
+        }
+    }
+
+    /// Applies a validated partial update, keeping `completed_at` in step with
+    /// the status. Blank descriptions are stored as no description.
+    pub fn apply(&mut self, patch: UpdateTaskRequest) {
+        if let Some(task_name) = patch.task_name {
+            self.task_name = task_name.trim().to_string();
+        }
+        if let Some(description) = patch.description {
+            self.description = description
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty());
+        }
+        if let Some(priority) = patch.priority {
+            self.priority = priority;
+        }
+        if let Some(assignee) = patch.assignee {
+            self.assignee = assignee;
+        }
+        if let Some(due_date) = patch.due_date {
+            if self.due_date != due_date {
+                self.reminded.clear();
+            }
+            self.due_date = due_date;
+        }
+        if let Some(reminders) = patch.reminders {
+            self.reminders = normalize_reminders(reminders);
+            let reminders = &self.reminders;
+            self.reminded.retain(|m| reminders.contains(m));
+        }
+        if let Some(status) = patch.status {
+            self.set_status(status);
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// True if `user_id` created the task or is assigned to it
+    pub fn is_involved(&self, user_id: &str) -> bool {
+        self.creator.as_deref() == Some(user_id) || self.assignee.as_deref() == Some(user_id)
+    }
+
+    /// Reminders whose time has come at `now` but were not sent yet
+    pub fn due_reminders(&self, now: DateTime<Utc>) -> Vec<u32> {
+        let Some(due) = self.due_date else {
+            return Vec::new();
+        };
+        if self.status == TaskStatus::Done {
+            return Vec::new();
+        }
+        self.reminders
+            .iter()
+            .filter(|m| !self.reminded.contains(m))
+            .filter(|m| due - chrono::Duration::minutes(i64::from(**m)) <= now)
+            .copied()
+            .collect()
+    }
+
+    /// Who gets the reminders: the assignee, or the creator of unassigned tasks
+    pub fn reminder_recipient(&self) -> Option<&str> {
+        self.assignee.as_deref().or(self.creator.as_deref())
+    }
+
+    /// Adds a watcher; false if they already watch the task
+    pub fn watch(&mut self, user_id: &str) -> bool {
+        if self.watchers.iter().any(|w| w == user_id) {
+            return false;
+        }
+        self.watchers.push(user_id.to_string());
+        true
+    }
+
+    /// Changes the status; entering `done` stamps `completed_at`, leaving it clears it.
+    pub fn set_status(&mut self, status: TaskStatus) {
+        match (self.status, status) {
+            (TaskStatus::Done, TaskStatus::Done) => {}
+            (_, TaskStatus::Done) => self.completed_at = Some(Utc::now()),
+            _ => self.completed_at = None,
+        }
+        self.status = status;
+    }
+}
+
+/// Checks the number and range of reminder offsets
+pub fn check_reminders(reminders: &[u32]) -> Result<(), String> {
+    if reminders.len() > MAX_REMINDERS {
+        return Err(format!("at most {} reminders", MAX_REMINDERS));
+    }
+    if reminders.iter().any(|m| *m > MAX_REMINDER_MINUTES) {
+        return Err(format!(
+            "reminders must be at most {} minutes before the due date",
+            MAX_REMINDER_MINUTES
+        ));
+    }
+    Ok(())
+}
+
+/// Reminder offsets, earliest first and without repeats
+pub fn normalize_reminders(mut reminders: Vec<u32>) -> Vec<u32> {
+    reminders.sort_unstable_by(|a, b| b.cmp(a));
+    reminders.dedup();
+    reminders
+}
+
+// Distinguishes an absent field (None) from an explicit null (Some(None))
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+// Runs only when the patch sets a description (not for absent or null)
+fn validate_description(description: &str) -> Result<(), validator::ValidationError> {
+    if description.chars().count() as u64 > MAX_TASK_DESCRIPTION_LENGTH {
+        return Err(validator::ValidationError::new("description")
+            .with_message("description must be at most 5000 characters".into()));
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_counts_items_and_subtasks() {
+        let item = |done| ChecklistItem {
+            id: "i".to_string(),
+            text: "step".to_string(),
+            done,
+        };
+        let mut finished = Task::new("s1".to_string(), "Sub".to_string());
+        finished.set_status(TaskStatus::Done);
+        let open = Task::new("s2".to_string(), "Sub".to_string());
+
+        assert_eq!(TaskProgress::of(&[], &[]), None);
+        let progress = TaskProgress::of(&[item(true), item(false)], &[finished, open]).unwrap();
+        assert_eq!((progress.done, progress.total, progress.percent), (2, 4, 50));
+    }
+
+    #[test]
+    fn test_patch_distinguishes_absent_and_null() {
+        let patch: UpdateTaskRequest =
+            serde_json::from_str(r#"{"status": "done", "due_date": null}"#).unwrap();
+        assert_eq!(patch.due_date, Some(None));
+        assert_eq!(patch.assignee, None);
+        assert!(serde_json::from_str::<UpdateTaskRequest>(r#"{"uuid": "x"}"#).is_err());
+
+        let mut task = Task::new("t1".to_string(), "Write docs".to_string());
+        task.due_date = Some(Utc::now());
+        task.assignee = Some("u1".to_string());
+        task.apply(patch);
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.completed_at.is_some());
+        assert_eq!(task.due_date, None);
+        assert_eq!(task.assignee.as_deref(), Some("u1"));
+
+        task.set_status(TaskStatus::InProgress);
+        assert_eq!(task.completed_at, None);
+    }
+}
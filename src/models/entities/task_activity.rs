@@ -0,0 +1,199 @@
+//! Task Activity Entity Module
+//! Comments on tasks and the automatically recorded history of their changes.
+//!
+//! # TaskComment
+//! - `uuid`: Record id (`task_comment:<uuid>`)
+//! - `task_id`: UUID of the task
+//! - `author` / `author_name`: UUID and username of the writer
+//! - `text`: The comment; `@username` mentions a user
+//! - `mentions`: UUIDs of the mentioned users who can see the task
+//!
+//! # TaskActivity
+//! - `uuid`: Record id (`task_activity:<uuid>`)
+//! - `task_id`: UUID of the task
+//! - `actor` / `actor_name`: UUID and username of whoever made the change
+//! - `kind`: `created`, `status_changed`, `reassigned` or `due_date_changed`
+//! - `from` / `to`: The old and new value (status, assignee UUID, or RFC 3339
+//!   due date); absent for `created` and for cleared values
+//!
+//! Both are merged by time into the task timeline (`TimelineEntry`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::entities::user::User;
+
+/// Maximum length (characters) of a comment
+pub const MAX_COMMENT_LENGTH: u64 = 4000;
+
+/// A comment on a task
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskComment {
+    pub uuid: String,
+    pub task_id: String,
+    /// UUID of the writer
+    pub author: String,
+    pub author_name: String,
+    pub text: String,
+    /// UUIDs of the mentioned users
+    #[serde(default)]
+    pub mentions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskComment {
+    pub fn new(task_id: String, author: &User, text: String, mentions: Vec<String>) -> Self {
+        TaskComment {
+            uuid: Uuid::new_v4().to_string(),
+            task_id,
+            author: author.id_string().unwrap_or_default(),
+            author_name: author.username.clone(),
+            text,
+            mentions,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// What changed on a task
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Created,
+    StatusChanged,
+    Reassigned,
+    DueDateChanged,
+}
+
+/// A recorded change of a task
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskActivity {
+    pub uuid: String,
+    pub task_id: String,
+    /// UUID of whoever made the change
+    pub actor: String,
+    pub actor_name: String,
+    pub kind: ActivityKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskActivity {
+    pub fn new(
+        task_id: String,
+        actor: &User,
+        kind: ActivityKind,
+        from: Option<String>,
+        to: Option<String>,
+    ) -> Self {
+        TaskActivity {
+            uuid: Uuid::new_v4().to_string(),
+            task_id,
+            actor: actor.id_string().unwrap_or_default(),
+            actor_name: actor.username.clone(),
+            kind,
+            from,
+            to,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// One entry of a task timeline: `{ "type": "comment" | "activity", ... }`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Comment(TaskComment),
+    Activity(TaskActivity),
+}
+
+impl TimelineEntry {
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            TimelineEntry::Comment(c) => c.created_at,
+            TimelineEntry::Activity(a) => a.created_at,
+        }
+    }
+
+    pub fn uuid(&self) -> &str {
+        match self {
+            TimelineEntry::Comment(c) => &c.uuid,
+            TimelineEntry::Activity(a) => &a.uuid,
+        }
+    }
+}
+
+/// A new timeline entry and the watchers to notify
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    pub task_id: String,
+    pub entry: TimelineEntry,
+    /// UUIDs of the watchers who can see the task
+    pub audience: Vec<String>,
+}
+
+impl TimelineEvent {
+    /// WebSocket payload: `{ "type": "TaskTimeline", "task_id": "...", "entry": { "type": "comment", ... } }`
+    pub fn payload(&self) -> String {
+        serde_json::json!({
+            "type": "TaskTimeline",
+            "task_id": self.task_id,
+            "entry": self.entry,
+        })
+        .to_string()
+    }
+}
+
+/// Request payload for commenting on a task
+#[derive(Validate, Serialize, Deserialize, Debug)]
+pub struct AddCommentRequest {
+    #[validate(length(
+        min = 1,
+        max = "MAX_COMMENT_LENGTH",
+        message = "comment required (at most 4000 characters)"
+    ))]
+    pub text: String,
+}
+
+/// Usernames mentioned in `text` as `@username`, in order and without repeats
+pub fn mentions(text: &str) -> Vec<&str> {
+    let mut found: Vec<&str> = Vec::new();
+    for (i, _) in text.match_indices('@') {
+        // An `@` inside a word (e-mail address) is not a mention
+        if text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric())
+        {
+            continue;
+        }
+        let rest = &text[i + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+            .unwrap_or(rest.len());
+        let name = rest[..end].trim_end_matches('.');
+        if !name.is_empty() && !found.contains(&name) {
+            found.push(name);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mentions_are_parsed_from_words_starting_with_at() {
+        assert_eq!(
+            mentions("@alice can you check with @bob_2? Thanks @alice."),
+            vec!["alice", "bob_2"]
+        );
+        assert!(mentions("mail me at carol@example.com or @ anyone").is_empty());
+    }
+}
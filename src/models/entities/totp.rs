@@ -0,0 +1,147 @@
+//! TOTP Settings Entity Module
+//!
+//! Two-factor authentication state embedded in `User::totp`.
+//!
+//! # Fields
+//! - `secret`: Base32 shared secret (RFC 6238); the authenticator app holds a copy
+//! - `enabled`: false while enrollment is pending confirmation
+//! - `recovery_codes`: SHA-256 (hex) of the unused recovery codes; plaintexts are
+//!   shown only once, when 2FA is confirmed
+//! - `last_used_step`: Last accepted time step, so a code cannot be replayed
+//! - `failed_attempts`: Consecutive wrong codes during login
+//! - `enabled_at`: Timestamp when enrollment was confirmed
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Per-user TOTP configuration
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TotpSettings {
+    /// Base32 shared secret
+    pub secret: String,
+
+    /// Whether enrollment was confirmed with a valid code
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Hashes of the remaining recovery codes
+    #[serde(default)]
+    pub recovery_codes: Vec<String>,
+
+    /// Last accepted time step (replay protection)
+    #[serde(default)]
+    pub last_used_step: u64,
+
+    /// Consecutive failed verification attempts
+    #[serde(default)]
+    pub failed_attempts: u32,
+
+    /// When 2FA was enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_at: Option<DateTime<Utc>>,
+}
+
+impl TotpSettings {
+    /// Creates settings for a pending (unconfirmed) enrollment
+    ///
+    /// # Arguments
+    /// * `secret` - Base32 shared secret
+    pub fn pending(secret: String) -> Self {
+        TotpSettings {
+            secret,
+            enabled: false,
+            recovery_codes: Vec::new(),
+            last_used_step: 0,
+            failed_attempts: 0,
+            enabled_at: None,
+        }
+    }
+
+    /// Marks the enrollment as confirmed and stores the recovery code hashes
+    ///
+    /// # Arguments
+    /// * `step` - Time step of the code used to confirm
+    /// * `recovery_codes` - Hashes of the freshly generated recovery codes
+    pub fn enable(&mut self, step: u64, recovery_codes: Vec<String>) {
+        self.enabled = true;
+        self.enabled_at = Some(Utc::now());
+        self.last_used_step = step;
+        self.recovery_codes = recovery_codes;
+        self.failed_attempts = 0;
+    }
+
+    /// Accepts a code for `step` unless that step (or a later one) was already used
+    ///
+    /// # Returns
+    /// `true` if accepted, `false` if it is a replay
+    pub fn accept_step(&mut self, step: u64) -> bool {
+        if step <= self.last_used_step {
+            return false;
+        }
+        self.last_used_step = step;
+        self.failed_attempts = 0;
+        true
+    }
+
+    /// Removes a recovery code by its hash (each code works once)
+    ///
+    /// # Returns
+    /// `true` if the code existed and was consumed
+    pub fn take_recovery_code(&mut self, code_hash: &str) -> bool {
+        let original_len = self.recovery_codes.len();
+        self.recovery_codes.retain(|h| h != code_hash);
+        if self.recovery_codes.len() != original_len {
+            self.failed_attempts = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Validate settings
+    ///
+    /// # Returns
+    /// `true` if valid, `false` otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.secret.is_empty() && (!self.enabled || self.enabled_at.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_then_enable() {
+        let mut totp = TotpSettings::pending("JBSWY3DPEHPK3PXP".to_string());
+        assert!(!totp.enabled);
+        assert!(totp.is_valid());
+
+        totp.enable(100, vec!["h1".to_string(), "h2".to_string()]);
+        assert!(totp.enabled);
+        assert!(totp.enabled_at.is_some());
+        assert_eq!(totp.last_used_step, 100);
+        assert!(totp.is_valid());
+    }
+
+    #[test]
+    fn test_accept_step_rejects_replay() {
+        let mut totp = TotpSettings::pending("JBSWY3DPEHPK3PXP".to_string());
+        totp.enable(100, Vec::new());
+
+        assert!(!totp.accept_step(100));
+        assert!(!totp.accept_step(99));
+        assert!(totp.accept_step(101));
+        assert_eq!(totp.last_used_step, 101);
+    }
+
+    #[test]
+    fn test_recovery_codes_are_single_use() {
+        let mut totp = TotpSettings::pending("JBSWY3DPEHPK3PXP".to_string());
+        totp.enable(1, vec!["h1".to_string(), "h2".to_string()]);
+
+        assert!(totp.take_recovery_code("h1"));
+        assert!(!totp.take_recovery_code("h1"));
+        assert_eq!(totp.recovery_codes, vec!["h2".to_string()]);
+    }
+}
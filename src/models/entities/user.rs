@@ -2,18 +2,38 @@
 //!
 //! - `id`: SurrealDB Thing con esquema `user:<uuid-v4>` (se genera al crear).
 //! - `username`: único.
-//! - `password`: hash bcrypt; opcional para compatibilidad con filas legacy.
+//! - `password`: hash Argon2id en formato PHC (o bcrypt legacy, migrado al iniciar
+//!   sesión); opcional para compatibilidad con filas legacy.
 //! - `email`: opcional para compatibilidad con filas legacy.
+//! - `email_verified`: true una vez confirmado el enlace enviado por correo.
+//! - `token_version`: se incrementa al cambiar roles; los JWT emitidos con una
+//!   versión anterior dejan de ser aceptados.
+//! - `identities`: cuentas OAuth2/OIDC vinculadas (proveedor + `sub`).
+//! - `wallets`: wallets adicionales vinculadas (verificadas con firma EIP-191);
+//!   `wallet` sigue siendo la wallet principal.
+//! - `totp`: configuración 2FA (RFC 6238); ausente si nunca se inició el enrolamiento.
+//! - `profile`: nombre visible, avatar, bio, locale y zona horaria (editables por el usuario).
+//! - `bot`: cuenta de integración; sin contraseña, se autentica solo con API keys.
+//! - `blocked`: cuenta bloqueada por un administrador; no puede autenticarse ni
+//!   aparece en el directorio de usuarios.
+//! - `deactivated_at`: la cuenta fue desactivada por su dueño; se reactiva al
+//!   volver a iniciar sesión.
+//! - `deletion_scheduled_at`: eliminación definitiva solicitada; al llegar la fecha
+//!   la cuenta se borra y sus mensajes quedan a nombre de "deleted user".
 //!
 //! Seguridad:
-//! - El constructor `User::new` aplica hash bcrypt (coste configurable vía BCRYPT_COST).
+//! - El constructor `User::new` aplica hash Argon2id (parámetros vía ARGON2_*).
 //! - No exponer el hash en respuestas públicas (usar DTO si es necesario).
 //! - Validar formato de email y unicidad en la creación de usuarios.
 
-use crate::infrastructure::auth::jwt::hash_password;
+use crate::infrastructure::auth::password::{hash_password, PasswordHashError};
+use crate::models::entities::identity::ExternalIdentity;
+use crate::models::entities::profile::UserProfile;
 use crate::models::entities::role::roles;
 use crate::models::entities::role::{Permission, Role};
-use bcrypt::BcryptError;
+use crate::models::entities::totp::TotpSettings;
+use crate::models::entities::wallet::LinkedWallet;
+use chrono::{DateTime, Utc};
 use log::debug;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -34,27 +54,72 @@ pub struct User {
     /// Email address of the user (optional for compat with legacy rows)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    /// Whether the email address was confirmed through the verification link
+    #[serde(default)]
+    pub email_verified: bool,
     /// Wallet address of the user (optional)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub wallet: Option<String>,
     /// Roles del usuario
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub roles: Vec<Role>,
+    /// Versión de los tokens emitidos; debe coincidir con el claim `ver` del JWT
+    #[serde(default)]
+    pub token_version: u32,
+    /// Configuración TOTP (2FA); `enabled` indica si el login exige un segundo factor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<TotpSettings>,
+    /// Identidades externas (OAuth2/OIDC) vinculadas a la cuenta
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identities: Vec<ExternalIdentity>,
+    /// Wallets vinculadas a la cuenta (además de la principal `wallet`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wallets: Vec<LinkedWallet>,
+    /// Cuenta bot (integraciones); sus mensajes se marcan como enviados por un bot
+    #[serde(default)]
+    pub bot: bool,
+    /// Perfil público y preferencias (nombre visible, avatar, locale, ...)
+    #[serde(default)]
+    pub profile: UserProfile,
+    /// Cuenta bloqueada por un administrador
+    #[serde(default)]
+    pub blocked: bool,
+    /// Momento en que el dueño desactivó la cuenta
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deactivated_at: Option<DateTime<Utc>>,
+    /// Fecha de eliminación definitiva (fin del período de gracia)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+/// Record id that authored messages point to once their sender is deleted
+pub const DELETED_USER_ID: &str = "deleted";
+
+/// Name shown for messages of deleted accounts
+pub const DELETED_USER_NAME: &str = "deleted user";
+
+/// `user:deleted`, the placeholder sender of anonymized messages
+pub fn deleted_user_thing() -> Thing {
+    Thing::from(("user", DELETED_USER_ID))
 }
 
 impl User {
     /// Creates a new User with:
     /// - id = Thing("user", <uuid-v4>)
-    /// - password = bcrypt(password)
+    /// - password = argon2id(password)
     /// - email stored
     ///
-    /// Returns hashing errors from bcrypt on failure.
-    pub fn new(username: String, email: String, password: String) -> Result<Self, BcryptError> {
+    /// Returns hashing errors on failure.
+    pub fn new(
+        username: String,
+        email: String,
+        password: String,
+    ) -> Result<Self, PasswordHashError> {
         debug!(
             "User::new creating user with username={} email={}",
             username, email
         );
-        // Hash the provided password (propagate bcrypt error)
+        // Hash the provided password (propagate hashing error)
         let hashed_password = hash_password(&password)?;
         let uuid = Uuid::new_v4().to_string();
 
@@ -63,8 +128,18 @@ impl User {
             username,
             password: Some(hashed_password),
             email: Some(email),
+            email_verified: false,
             wallet: None,
             roles: Vec::new(),
+            token_version: 0,
+            totp: None,
+            identities: Vec::new(),
+            wallets: Vec::new(),
+            bot: false,
+            profile: UserProfile::default(),
+            blocked: false,
+            deactivated_at: None,
+            deletion_scheduled_at: None,
         };
 
         user.add_role(roles::user());
@@ -91,8 +166,58 @@ impl User {
             username,
             password: None,
             email: None,
+            email_verified: false,
             wallet: Some(wallet),
             roles: Vec::new(),
+            token_version: 0,
+            totp: None,
+            identities: Vec::new(),
+            wallets: Vec::new(),
+            bot: false,
+            profile: UserProfile::default(),
+            blocked: false,
+            deactivated_at: None,
+            deletion_scheduled_at: None,
+        };
+
+        user.add_role(roles::user());
+
+        user
+    }
+
+    /// Creates a new User from an external (OAuth2/OIDC) login.
+    ///
+    /// There is no local password; the e-mail counts as verified only if the
+    /// provider asserted it.
+    pub fn new_from_identity(
+        username: String,
+        email: Option<String>,
+        email_verified: bool,
+        identity: ExternalIdentity,
+    ) -> Self {
+        debug!(
+            "User::new_from_identity creating user with username={} provider={}",
+            username, identity.provider
+        );
+        let uuid = Uuid::new_v4().to_string();
+
+        let mut user = User {
+            id: Some(Thing::from(("user", uuid.as_str()))),
+            username,
+            password: None,
+            email_verified: email_verified && email.is_some(),
+            email,
+            wallet: None,
+            roles: Vec::new(),
+            token_version: 0,
+            totp: None,
+            identities: vec![identity],
+            wallets: Vec::new(),
+            bot: false,
+            profile: UserProfile::default(),
+            blocked: false,
+            deactivated_at: None,
+            deletion_scheduled_at: None,
         };
 
         user.add_role(roles::user());
@@ -100,8 +225,121 @@ impl User {
         user
     }
 
+    /// Creates a bot account for integrations.
+    ///
+    /// Bots have no password, e-mail or wallet, so they cannot log in; they act
+    /// through API keys created by an admin.
+    pub fn new_bot(username: String) -> Self {
+        debug!("User::new_bot creating bot with username={}", username);
+        let uuid = Uuid::new_v4().to_string();
+
+        let mut user = User {
+            id: Some(Thing::from(("user", uuid.as_str()))),
+            username,
+            password: None,
+            email: None,
+            email_verified: false,
+            wallet: None,
+            roles: Vec::new(),
+            token_version: 0,
+            totp: None,
+            identities: Vec::new(),
+            wallets: Vec::new(),
+            bot: true,
+            profile: UserProfile::default(),
+            blocked: false,
+            deactivated_at: None,
+            deletion_scheduled_at: None,
+        };
+
+        user.add_role(roles::user());
+
+        user
+    }
+
+    /// True if the external identity `subject` at `provider` is linked.
+    pub fn has_identity(&self, provider: &str, subject: &str) -> bool {
+        self.identities.iter().any(|i| i.matches(provider, subject))
+    }
+
+    /// Every wallet address of the account (primary first), without duplicates.
+    pub fn wallet_addresses(&self) -> Vec<String> {
+        let mut addresses: Vec<String> = self.wallet.iter().cloned().collect();
+        for linked in &self.wallets {
+            if !addresses.contains(&linked.address) {
+                addresses.push(linked.address.clone());
+            }
+        }
+        addresses
+    }
+
+    /// True if `address` (lower-case) is the primary or a linked wallet.
+    pub fn has_wallet(&self, address: &str) -> bool {
+        self.wallet.as_deref() == Some(address) || self.wallets.iter().any(|w| w.address == address)
+    }
+
+    /// True for accounts that exist only through a wallet: no e-mail, password or
+    /// external identity. Accounts that linked a wallet later are not.
+    pub fn is_wallet_only(&self) -> bool {
+        !self.wallet_addresses().is_empty()
+            && self.email.is_none()
+            && self.password.is_none()
+            && self.identities.is_empty()
+    }
+
+    /// Number of ways to sign in: password, each wallet and each external identity.
+    /// Detaching a credential must leave at least one.
+    pub fn login_method_count(&self) -> usize {
+        usize::from(self.password.is_some()) + self.wallet_addresses().len() + self.identities.len()
+    }
+
+    /// True while the owner keeps the account deactivated (or pending deletion).
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivated_at.is_some()
+    }
+
+    /// Extracts the pure UUID from the SurrealDB Thing (without the `⟨ ⟩` brackets).
+    ///
+    /// Returns None when the user has no id set.
+    pub fn id_string(&self) -> Option<String> {
+        self.id.as_ref().map(|thing| match &thing.id {
+            surrealdb::sql::Id::String(s) => s.clone(),
+            surrealdb::sql::Id::Uuid(u) => u.to_string(),
+            _ => thing
+                .id
+                .to_string()
+                .trim_matches('⟨')
+                .trim_matches('⟩')
+                .to_string(),
+        })
+    }
+
     //--------- Roles Methods ---------
 
+    /// Names of the persisted roles, in the order they were assigned.
+    pub fn role_names(&self) -> Vec<String> {
+        self.roles.iter().map(|r| r.name.clone()).collect()
+    }
+
+    /// Compact, sorted permission set granted by all roles.
+    ///
+    /// `admin:all` implies every other permission, so when present it is the
+    /// only entry returned.
+    pub fn permission_names(&self) -> Vec<String> {
+        if self.has_permission(Permission::AdminAll) {
+            return vec![Permission::AdminAll.to_string()];
+        }
+        let mut perms: Vec<String> = self
+            .roles
+            .iter()
+            .flat_map(|r| r.permissions.iter().map(|p| p.to_string()))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        perms.sort();
+        perms
+    }
+
     pub fn has_role(&self, role_name: &str) -> bool {
         self.roles.iter().any(|r| r.name == role_name)
     }
@@ -158,4 +396,14 @@ impl User {
     pub fn is_standard_user(&self) -> bool {
         self.has_role("user") && !self.is_admin() && !self.is_moderator()
     }
+
+    /// True when the account has no email to verify (wallet users) or it was verified.
+    pub fn is_email_verified_or_exempt(&self) -> bool {
+        self.email.is_none() || self.email_verified
+    }
+
+    /// True when TOTP two-factor authentication is confirmed for this account.
+    pub fn is_mfa_enabled(&self) -> bool {
+        self.totp.as_ref().is_some_and(|t| t.enabled)
+    }
 }
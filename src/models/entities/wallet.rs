@@ -0,0 +1,55 @@
+//! Linked Wallet Entity Module
+//!
+//! A wallet address attached to a `User` (embedded in `User::wallets`). Wallets
+//! linked through `POST /api/me/wallets` carry a `verified_at` timestamp: the
+//! user signed a server challenge with the wallet. The legacy single
+//! `User::wallet` field of accounts created by the wallet login stays as the
+//! account's primary address.
+//!
+//! # Fields
+//! - `address`: Lower-case `0x` address
+//! - `verified_at`: When ownership was proven by a signature (None if unverified)
+//! - `linked_at`: Timestamp when the wallet was attached
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Represents a wallet attached to an account
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LinkedWallet {
+    /// Lower-case address
+    pub address: String,
+
+    /// When ownership was proven by a signed challenge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime<Utc>>,
+
+    /// When the wallet was attached
+    pub linked_at: DateTime<Utc>,
+}
+
+impl LinkedWallet {
+    /// A wallet whose ownership was just proven
+    pub fn verified(address: &str) -> Self {
+        let now = Utc::now();
+        LinkedWallet {
+            address: address.to_lowercase(),
+            verified_at: Some(now),
+            linked_at: now,
+        }
+    }
+
+    /// A wallet carried over without proof (e.g. from a merged wallet-login account)
+    pub fn unverified(address: &str) -> Self {
+        LinkedWallet {
+            address: address.to_lowercase(),
+            verified_at: None,
+            linked_at: Utc::now(),
+        }
+    }
+
+    /// True if ownership was proven by a signature
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+}
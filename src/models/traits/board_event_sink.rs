@@ -0,0 +1,11 @@
+//! Board Event Sink Module
+//! Defines where board changes are published for real-time delivery.
+
+use crate::models::entities::board::BoardEvent;
+
+/// Receives board changes; the chat server forwards them to the WebSocket
+/// sessions of the project members.
+pub trait BoardEventSink: Send + Sync {
+    /// Publishes an event without waiting for its delivery
+    fn publish(&self, event: BoardEvent);
+}
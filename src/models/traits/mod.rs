@@ -4,7 +4,10 @@
 //! for the domain models and entities.
 //!
 //! # Module Structure
+//! - `board_event_sink`: Publishing board changes to connected clients
+//! - `notification_sink`: Delivering notifications to a user's connected clients
 //! - `task_data_trait`: Trait definitions for task-related behaviors
+//! - `task_event_sink`: Publishing task changes to connected clients
 //! - `user_data_trait`: Trait definitions for user-related behaviors
 //!
 //! # Usage
@@ -18,5 +21,8 @@
 //! - Enable polymorphic operations
 //! - Support dependency inversion
 
+pub mod board_event_sink;
+pub mod notification_sink;
 pub mod task_data_trait;
+pub mod task_event_sink;
 pub mod user_data_trait;
@@ -0,0 +1,12 @@
+//! Notification Sink Module
+//! Defines where notifications for a single user are delivered in real time.
+
+use async_trait::async_trait;
+
+/// Delivers a payload to the connected clients of one user; the chat server
+/// forwards it to the user's WebSocket sessions.
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    /// Sends `payload` to every session of `user_id`; false if the user has none
+    async fn deliver(&self, user_id: &str, payload: String) -> bool;
+}
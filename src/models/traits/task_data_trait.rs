@@ -1,11 +1,146 @@
 //! Task Data Trait Module
 //! Defines the interface for task-related database operations.
+//!
+//! Parent and blocking relations are graph edges:
+//! - `task:<child> ->subtask_of-> task:<parent>` (at most one per child)
+//! - `task:<task> ->depends_on-> task:<blocker>`
 
-use crate::models::entities::task::Task;
+use crate::models::entities::task::{Task, TaskPriority, TaskStatus};
 use crate::infrastructure::database::surrealdb::Database;
-use surrealdb::Error;
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use surrealdb::sql::Thing;
+use surrealdb::{Error, Response};
 use async_trait::async_trait;
 use log::{info, debug, warn, error}; // añadido
+use serde::Deserialize;
+
+/// Field a task listing is ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    /// Tasks without a due date come last in both directions
+    DueDate,
+    Priority,
+}
+
+/// Order of a task listing: `created_at`, `updated_at`, `due_date` or
+/// `priority`, descending with a leading `-` (e.g. `-priority`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaskSort {
+    pub field: TaskSortField,
+    pub descending: bool,
+}
+
+impl TaskSort {
+    /// SurrealQL expression of the numeric sort key of a row
+    pub fn sql_key(&self) -> &'static str {
+        match (self.field, self.descending) {
+            (TaskSortField::CreatedAt, _) => {
+                "(IF created_at THEN time::nano(<datetime>created_at) ELSE 0 END)"
+            }
+            (TaskSortField::UpdatedAt, _) => {
+                "(IF updated_at THEN time::nano(<datetime>updated_at) ELSE 0 END)"
+            }
+            (TaskSortField::DueDate, false) => {
+                "(IF due_date THEN time::nano(<datetime>due_date) ELSE 9223372036854775807 END)"
+            }
+            (TaskSortField::DueDate, true) => {
+                "(IF due_date THEN time::nano(<datetime>due_date) ELSE -9223372036854775808 END)"
+            }
+            (TaskSortField::Priority, _) => {
+                "(IF priority = 'low' THEN 0 ELSE IF priority = 'high' THEN 2 \
+                 ELSE IF priority = 'urgent' THEN 3 ELSE 1 END)"
+            }
+        }
+    }
+
+    /// The same key computed from a loaded task, used to build cursors
+    pub fn key(&self, task: &Task) -> i64 {
+        let nanos = |at: DateTime<Utc>| at.timestamp_nanos_opt().unwrap_or(0);
+        match self.field {
+            TaskSortField::CreatedAt => nanos(task.created_at),
+            TaskSortField::UpdatedAt => nanos(task.updated_at),
+            TaskSortField::DueDate => match task.due_date {
+                Some(due) => nanos(due),
+                None if self.descending => i64::MIN,
+                None => i64::MAX,
+            },
+            TaskSortField::Priority => match task.priority {
+                TaskPriority::Low => 0,
+                TaskPriority::Medium => 1,
+                TaskPriority::High => 2,
+                TaskPriority::Urgent => 3,
+            },
+        }
+    }
+}
+
+impl FromStr for TaskSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let field = match name {
+            "created_at" => TaskSortField::CreatedAt,
+            "updated_at" => TaskSortField::UpdatedAt,
+            "due_date" => TaskSortField::DueDate,
+            "priority" => TaskSortField::Priority,
+            _ => return Err(format!("unknown sort: {}", s)),
+        };
+        Ok(TaskSort { field, descending })
+    }
+}
+
+/// Parameters of a task search
+#[derive(Debug, Clone, Default)]
+pub struct TaskSearch {
+    /// Only tasks created by / assigned to this user UUID or belonging to one
+    /// of these conversation UUIDs; `None` searches every task
+    pub visible_to: Option<(String, Vec<String>)>,
+    pub status: Option<TaskStatus>,
+    /// Assignee UUID
+    pub assignee: Option<String>,
+    /// Only tasks due strictly before this instant
+    pub due_before: Option<DateTime<Utc>>,
+    /// Only tasks due at or after this instant
+    pub due_after: Option<DateTime<Utc>>,
+    /// Case-insensitive substring of the name or description
+    pub text: Option<String>,
+    pub sort: TaskSort,
+    /// Only tasks after this (sort key, UUID) position (cursor)
+    pub after: Option<(i64, String)>,
+    /// Maximum number of tasks returned
+    pub limit: usize,
+}
+
+/// Graph neighbours of a task (UUIDs)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TaskLinks {
+    #[serde(default, deserialize_with = "first")]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub subtasks: Vec<String>,
+    /// Tasks that must be done before this one
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Tasks waiting for this one
+    #[serde(default)]
+    pub blocks: Vec<String>,
+}
+
+// Graph traversals yield arrays, even for the single parent
+fn first<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Vec::<String>::deserialize(deserializer)?.into_iter().next())
+}
 
 /// Defines the interface for task-related database operations
 #[async_trait]
@@ -15,7 +150,16 @@ pub trait TaskDataTrait {
     /// # Returns
     /// * `Option<Vec<Task>>` - Some(tasks) if found, None if error or no tasks
     async fn get_all_tasks(&self) -> Option<Vec<Task>>;
-    
+
+    /// Searches tasks with filters, ordered by `search.sort` and then by UUID
+    ///
+    /// # Arguments
+    /// * `search` - Filters, visibility, sort order and keyset cursor
+    ///
+    /// # Returns
+    /// * `Option<Vec<Task>>` - Some(tasks) (possibly empty), None if error
+    async fn search_tasks(&self, search: &TaskSearch) -> Option<Vec<Task>>;
+
     /// Adds a new task to the database
     /// 
     /// # Arguments
@@ -25,14 +169,74 @@ pub trait TaskDataTrait {
     /// * `Option<Task>` - Some(task) if created, None if error
     async fn add_task(&self, new_task: Task) -> Option<Task>;
     
-    /// Updates an existing task in the database
-    /// 
+    /// Retrieves one task by UUID
+    ///
+    /// # Returns
+    /// * `Option<Task>` - Some(task) if found, None if not found or error
+    async fn find_task(&self, uuid: String) -> Option<Task>;
+
+    /// Retrieves the tasks with the given UUIDs (missing ones are skipped)
+    ///
+    /// # Returns
+    /// * `Option<Vec<Task>>` - Some(tasks) (possibly empty), None if error
+    async fn find_tasks(&self, uuids: Vec<String>) -> Option<Vec<Task>>;
+
+    /// Replaces the stored fields of an existing task, but only if its stored
+    /// version is still `expected_version`
+    ///
     /// # Arguments
     /// * `uuid` - Unique identifier of the task to update
+    /// * `task` - The full updated task
+    /// * `expected_version` - Version the update was computed from
+    ///
+    /// # Returns
+    /// * `Option<Task>` - Some(task) if updated, None if not found, changed
+    ///   meanwhile or error
+    async fn update_task(&self, uuid: String, task: Task, expected_version: u64) -> Option<Task>;
+
+    /// Deletes a task
     ///
     /// # Returns
-    /// * `Option<Task>` - Some(task) if updated, None if not found or error
-    async fn update_task(&self, uuid: String) -> Option<Task>;
+    /// * `bool` - true if a task was deleted
+    async fn delete_task(&self, uuid: String) -> bool;
+
+    /// Retrieves the parent, subtasks and blocking relations of a task
+    ///
+    /// # Returns
+    /// * `Option<TaskLinks>` - Some(links) (empty for unknown tasks), None if error
+    async fn task_links(&self, uuid: String) -> Option<TaskLinks>;
+
+    /// Replaces the parent of a task (`None` makes it top-level)
+    ///
+    /// # Returns
+    /// * `bool` - true if stored
+    async fn set_parent(&self, uuid: String, parent: Option<String>) -> bool;
+
+    /// Records that `uuid` cannot be done before `blocker` (idempotent)
+    ///
+    /// # Returns
+    /// * `bool` - true if stored
+    async fn add_dependency(&self, uuid: String, blocker: String) -> bool;
+
+    /// Removes the blocking relation between `uuid` and `blocker`, if any
+    ///
+    /// # Returns
+    /// * `bool` - true unless the database failed
+    async fn remove_dependency(&self, uuid: String, blocker: String) -> bool;
+
+    /// Retrieves the open tasks with reminders not sent yet that are due
+    /// before `horizon`
+    ///
+    /// # Returns
+    /// * `Option<Vec<Task>>` - Some(tasks) (possibly empty), None if error
+    async fn find_tasks_with_reminders(&self, horizon: DateTime<Utc>) -> Option<Vec<Task>>;
+
+    /// Stores which reminders of a task were sent, as a change of its version,
+    /// if the task is still at `expected_version`
+    ///
+    /// # Returns
+    /// * `bool` - true if stored, false if the task changed meanwhile or on error
+    async fn set_reminded(&self, uuid: String, reminded: Vec<u32>, expected_version: u64) -> bool;
 }
 
 // Implementation of TaskDataTrait for the Database struct
@@ -54,6 +258,94 @@ impl TaskDataTrait for Database {
         }
     }
 
+    // Search tasks; every value is bound as a parameter
+    async fn search_tasks(&self, search: &TaskSearch) -> Option<Vec<Task>> {
+        debug!("Tasks: search {:?}", search);
+        let key = search.sort.sql_key();
+        let mut conditions: Vec<String> = Vec::new();
+        if search.visible_to.is_some() {
+            conditions.push(
+                "(creator = $user OR assignee = $user OR conversation_id INSIDE $conversations)"
+                    .to_string(),
+            );
+        }
+        if search.status.is_some() {
+            conditions.push("(status OR 'todo') = $status".to_string());
+        }
+        if search.assignee.is_some() {
+            conditions.push("assignee = $assignee".to_string());
+        }
+        if search.due_before.is_some() {
+            conditions.push("(due_date != NONE AND <datetime>due_date < <datetime>$due_before)".to_string());
+        }
+        if search.due_after.is_some() {
+            conditions.push("(due_date != NONE AND <datetime>due_date >= <datetime>$due_after)".to_string());
+        }
+        if search.text.is_some() {
+            conditions.push(
+                "(string::contains(string::lowercase(task_name), $text) \
+                 OR string::contains(string::lowercase(description OR ''), $text))"
+                    .to_string(),
+            );
+        }
+        if search.after.is_some() {
+            let op = if search.sort.descending { "<" } else { ">" };
+            conditions.push(format!(
+                "({key} {op} $after_key OR ({key} = $after_key AND uuid > $after_uuid))"
+            ));
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let direction = if search.sort.descending { "DESC" } else { "ASC" };
+        let sql = format!(
+            "SELECT *, {key} AS sort_key FROM task {filter} \
+             ORDER BY sort_key {direction}, uuid ASC LIMIT $limit"
+        );
+
+        let (user, conversations) = match &search.visible_to {
+            Some((user, conversations)) => (Some(user.clone()), conversations.clone()),
+            None => (None, Vec::new()),
+        };
+        let (after_key, after_uuid) = match &search.after {
+            Some((key, uuid)) => (Some(*key), Some(uuid.clone())),
+            None => (None, None),
+        };
+        let result = self
+            .client
+            .query(sql)
+            .bind(("user", user))
+            .bind(("conversations", conversations))
+            .bind(("status", search.status))
+            .bind(("assignee", search.assignee.clone()))
+            .bind(("due_before", search.due_before))
+            .bind(("due_after", search.due_after))
+            .bind(("text", search.text.as_ref().map(|t| t.to_lowercase())))
+            .bind(("after_key", after_key))
+            .bind(("after_uuid", after_uuid))
+            .bind(("limit", search.limit as i64))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Vec<Task>>(0) {
+                Ok(tasks) => {
+                    debug!("Tasks: search found {}", tasks.len());
+                    Some(tasks)
+                },
+                Err(e) => {
+                    error!("Tasks: search decode error -> {:?}", e);
+                    None
+                },
+            },
+            Err(e) => {
+                error!("Tasks: search DB error -> {:?}", e);
+                None
+            },
+        }
+    }
+
     // Add a new task to the database
     async fn add_task(&self, new_task: Task) -> Option<Task> {
         // Guardar datos antes de mover new_task
@@ -83,49 +375,254 @@ impl TaskDataTrait for Database {
         }
     }
 
-    // Update an existing task in the database
-    async fn update_task(&self, uuid: String) -> Option<Task> {
-        debug!("Tasks: update check uuid={}", uuid);
-        let find_task: Result<Option<Task>, Error> = self.client.select(("task", &uuid)).await;
-
-        match find_task {
-            Ok(found) => {
-                match found {
-                    Some(_found_task) => {
-                        debug!("Tasks: updating uuid={}", uuid);
-                        let updated_task: Result<Option<Task>, Error> = self
-                            .client
-                            .update(("task", &uuid))
-                            .merge(Task {
-                                uuid: uuid.clone(),
-                                task_name: String::from("Completed")
-                            })
-                            .await;
-                        match updated_task {
-                            Ok(updated) => {
-                                if updated.is_some() {
-                                    info!("Tasks: update success uuid={}", uuid);
-                                } else {
-                                    warn!("Tasks: update returned None uuid={}", uuid);
-                                }
-                                updated
-                            },
-                            Err(e) => {
-                                error!("Tasks: update DB error uuid={} -> {:?}", uuid, e);
-                                None
-                            },
-                        }
-                    },
-                    None => {
-                        warn!("Tasks: not found uuid={}", uuid);
-                        None
-                    },
+    // Retrieve one task by UUID
+    async fn find_task(&self, uuid: String) -> Option<Task> {
+        debug!("Tasks: find uuid={}", uuid);
+        let found: Result<Option<Task>, Error> = self.client.select(("task", &uuid)).await;
+        match found {
+            Ok(task) => task,
+            Err(e) => {
+                error!("Tasks: find DB error uuid={} -> {:?}", uuid, e);
+                None
+            },
+        }
+    }
+
+    // Retrieve several tasks by UUID
+    async fn find_tasks(&self, uuids: Vec<String>) -> Option<Vec<Task>> {
+        if uuids.is_empty() {
+            return Some(Vec::new());
+        }
+        let result = self
+            .client
+            .query("SELECT * FROM task WHERE uuid INSIDE $uuids")
+            .bind(("uuids", uuids))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Vec<Task>>(0) {
+                Ok(tasks) => Some(tasks),
+                Err(e) => {
+                    error!("Tasks: find many decode error -> {:?}", e);
+                    None
+                },
+            },
+            Err(e) => {
+                error!("Tasks: find many DB error -> {:?}", e);
+                None
+            },
+        }
+    }
+
+    // Update an existing task in the database; UPDATE on a missing record
+    // returns nothing, so unknown UUIDs are not created. Tasks stored before
+    // versioning count as version 0
+    async fn update_task(&self, uuid: String, task: Task, expected_version: u64) -> Option<Task> {
+        debug!("Tasks: updating uuid={} from version {}", uuid, expected_version);
+        let result = self
+            .client
+            .query("UPDATE $id CONTENT $task WHERE (version OR 0) = $expected RETURN AFTER")
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .bind(("task", task))
+            .bind(("expected", expected_version))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<Task>>(0) {
+                Ok(updated) => {
+                    if updated.is_some() {
+                        info!("Tasks: update success uuid={}", uuid);
+                    } else {
+                        warn!("Tasks: not found or changed meanwhile uuid={}", uuid);
+                    }
+                    updated
+                },
+                Err(e) => {
+                    error!("Tasks: update decode error uuid={} -> {:?}", uuid, e);
+                    None
+                },
+            },
+            Err(e) => {
+                error!("Tasks: update DB error uuid={} -> {:?}", uuid, e);
+                None
+            },
+        }
+    }
+
+    // Delete a task from the database
+    async fn delete_task(&self, uuid: String) -> bool {
+        let deleted: Result<Option<Task>, Error> = self.client.delete(("task", &uuid)).await;
+        match deleted {
+            Ok(Some(_)) => {
+                info!("Tasks: delete success uuid={}", uuid);
+                // Sus subtareas quedan como tareas de primer nivel
+                let edges = self
+                    .client
+                    .query("DELETE subtask_of WHERE in = $id OR out = $id; DELETE depends_on WHERE in = $id OR out = $id;")
+                    .bind(("id", Thing::from(("task", uuid.as_str()))))
+                    .await
+                    .and_then(Response::check);
+                if let Err(e) = edges {
+                    error!("Tasks: delete edges DB error uuid={} -> {:?}", uuid, e);
                 }
+                true
+            },
+            Ok(None) => {
+                warn!("Tasks: delete not found uuid={}", uuid);
+                false
             },
             Err(e) => {
-                error!("Tasks: find DB error uuid={} -> {:?}", uuid, e);
+                error!("Tasks: delete DB error uuid={} -> {:?}", uuid, e);
+                false
+            },
+        }
+    }
+
+    // Vecinos en el grafo: padre, subtareas y dependencias
+    async fn task_links(&self, uuid: String) -> Option<TaskLinks> {
+        let result = self
+            .client
+            .query(
+                "SELECT ->subtask_of->task.uuid AS parent, <-subtask_of<-task.uuid AS subtasks, \
+                 ->depends_on->task.uuid AS depends_on, <-depends_on<-task.uuid AS blocks FROM $id",
+            )
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Vec<TaskLinks>>(0) {
+                Ok(rows) => Some(rows.into_iter().next().unwrap_or_default()),
+                Err(e) => {
+                    error!("Tasks: links decode error uuid={} -> {:?}", uuid, e);
+                    None
+                },
+            },
+            Err(e) => {
+                error!("Tasks: links DB error uuid={} -> {:?}", uuid, e);
+                None
+            },
+        }
+    }
+
+    // Replace the subtask_of edge of a task
+    async fn set_parent(&self, uuid: String, parent: Option<String>) -> bool {
+        let query = match parent {
+            Some(_) => "BEGIN TRANSACTION; DELETE subtask_of WHERE in = $id; RELATE $id->subtask_of->$parent; COMMIT TRANSACTION;",
+            None => "DELETE subtask_of WHERE in = $id;",
+        };
+        let result = self
+            .client
+            .query(query)
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .bind(("parent", parent.as_deref().map(|p| Thing::from(("task", p)))))
+            .await
+            .and_then(Response::check);
+        match result {
+            Ok(_) => {
+                info!("Tasks: parent of uuid={} set to {:?}", uuid, parent);
+                true
+            },
+            Err(e) => {
+                error!("Tasks: set parent DB error uuid={} -> {:?}", uuid, e);
+                false
+            },
+        }
+    }
+
+    // Add a depends_on edge, replacing a duplicate
+    async fn add_dependency(&self, uuid: String, blocker: String) -> bool {
+        let result = self
+            .client
+            .query("BEGIN TRANSACTION; DELETE depends_on WHERE in = $id AND out = $blocker; RELATE $id->depends_on->$blocker; COMMIT TRANSACTION;")
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .bind(("blocker", Thing::from(("task", blocker.as_str()))))
+            .await
+            .and_then(Response::check);
+        match result {
+            Ok(_) => {
+                info!("Tasks: uuid={} now depends on {}", uuid, blocker);
+                true
+            },
+            Err(e) => {
+                error!("Tasks: add dependency DB error uuid={} -> {:?}", uuid, e);
+                false
+            },
+        }
+    }
+
+    // Remove a depends_on edge
+    async fn remove_dependency(&self, uuid: String, blocker: String) -> bool {
+        let result = self
+            .client
+            .query("DELETE depends_on WHERE in = $id AND out = $blocker")
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .bind(("blocker", Thing::from(("task", blocker.as_str()))))
+            .await
+            .and_then(Response::check);
+        match result {
+            Ok(_) => {
+                info!("Tasks: uuid={} no longer depends on {}", uuid, blocker);
+                true
+            },
+            Err(e) => {
+                error!("Tasks: remove dependency DB error uuid={} -> {:?}", uuid, e);
+                false
+            },
+        }
+    }
+
+    // Open tasks with unsent reminders, due before `horizon`; once all are
+    // sent a task is not read again until a new due date re-arms them
+    async fn find_tasks_with_reminders(&self, horizon: DateTime<Utc>) -> Option<Vec<Task>> {
+        let result = self
+            .client
+            .query("SELECT * FROM task WHERE status != 'done' AND due_date != NONE AND due_date <= $horizon AND array::len(array::complement(reminders OR [], reminded OR [])) > 0")
+            .bind(("horizon", horizon))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Vec<Task>>(0) {
+                Ok(tasks) => Some(tasks),
+                Err(e) => {
+                    error!("Tasks: reminders decode error -> {:?}", e);
+                    None
+                },
+            },
+            Err(e) => {
+                error!("Tasks: reminders DB error -> {:?}", e);
                 None
             },
         }
     }
+
+    // Store the sent reminders without touching the rest of the task; the
+    // version bump makes a concurrent full update recompute instead of
+    // writing back the reminders it read, and the version check keeps a
+    // stale read from undoing a change (a new due date resets `reminded`)
+    async fn set_reminded(&self, uuid: String, reminded: Vec<u32>, expected_version: u64) -> bool {
+        let result = self
+            .client
+            .query("UPDATE $id SET reminded = $reminded, version = (version OR 0) + 1 WHERE (version OR 0) = $expected RETURN AFTER")
+            .bind(("id", Thing::from(("task", uuid.as_str()))))
+            .bind(("reminded", reminded))
+            .bind(("expected", expected_version))
+            .await;
+        match result {
+            Ok(mut response) => match response.take::<Option<Task>>(0) {
+                Ok(Some(_)) => true,
+                Ok(None) => {
+                    warn!("Tasks: not found or changed meanwhile uuid={}", uuid);
+                    false
+                },
+                Err(e) => {
+                    error!("Tasks: set reminded decode error uuid={} -> {:?}", uuid, e);
+                    false
+                },
+            },
+            Err(e) => {
+                error!("Tasks: set reminded DB error uuid={} -> {:?}", uuid, e);
+                false
+            },
+        }
+    }
 }
@@ -0,0 +1,14 @@
+//! Task Event Sink Module
+//! Defines where task changes are published for real-time delivery.
+
+use crate::models::entities::task::TaskEvent;
+use crate::models::entities::task_activity::TimelineEvent;
+
+/// Receives task changes; the chat server forwards them to the WebSocket
+/// sessions of the event's audience.
+pub trait TaskEventSink: Send + Sync {
+    /// Publishes an event without waiting for its delivery
+    fn publish(&self, event: TaskEvent);
+    /// Publishes a new comment or recorded change to the task's watchers
+    fn publish_timeline(&self, event: TimelineEvent);
+}
@@ -5,6 +5,23 @@
 //! - add_user: inserta en tabla `user`, retorna Option<User>.
 //! - find_user_by_username/email: consultas parametrizadas con `LIMIT 1`
 //!   y filtro `AND password != NONE` para evitar filas legacy sin hash.
+//! - set_user_roles: reemplaza los roles e incrementa `token_version` en la misma
+//!   sentencia, invalidando los JWT emitidos anteriormente.
+//! - find_user_by_identity / link_identity: identidades OAuth2/OIDC embebidas en `identities`.
+//! - find_user_by_verified_email: sin filtro de password (cuentas creadas vía OIDC no tienen).
+//! - set_totp: reemplaza (o elimina con NONE) la configuración 2FA del usuario.
+//! - revoke_tokens: solo incrementa `token_version` (p. ej. tras demasiados códigos 2FA fallidos).
+//! - update_profile: reemplaza el perfil embebido (`profile`) completo.
+//! - update_email: cambia el e-mail y lo marca como no verificado.
+//! - search_users: directorio paginado por cursor (orden por `username`), prefijo
+//!   sobre username/nombre visible y wallet exacta; excluye cuentas bloqueadas
+//!   y desactivadas.
+//! - set_blocked: bloquea/desbloquea una cuenta; al bloquear incrementa `token_version`.
+//! - find_user_by_wallet: busca en la wallet principal y en las wallets vinculadas.
+//! - set_wallets / set_identities: reemplazan las credenciales vinculadas (desvincular).
+//! - deactivate / reactivate: desactivación por el dueño (con eliminación programada
+//!   opcional); desactivar incrementa `token_version`. Las cuentas desactivadas no
+//!   aparecen en el directorio.
 //!
 //! Notas:
 //! - Retorna None ante errores de DB o deserialización.
@@ -12,10 +29,30 @@
 //! - Las funciones son asíncronas y retornan resultados envueltos en Option.
 
 use crate::infrastructure::database::surrealdb::Database;
+use crate::models::entities::identity::ExternalIdentity;
+use crate::models::entities::profile::UserProfile;
+use crate::models::entities::role::Role;
+use crate::models::entities::totp::TotpSettings;
 use crate::models::entities::user::User;
+use crate::models::entities::wallet::LinkedWallet;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
 use log::{debug, error, info, warn}; // añadido
 
+/// Filters of a user directory search
+#[derive(Debug, Clone, Default)]
+pub struct UserSearch {
+    /// Case-insensitive prefix of the username or display name
+    pub prefix: Option<String>,
+    /// Exact (lower-case) wallet address
+    pub wallet: Option<String>,
+    /// Only users whose username sorts after this one (cursor)
+    pub after: Option<String>,
+    /// Maximum number of users returned
+    pub limit: usize,
+}
+
 /// Defines the interface for user-related database operations
 #[async_trait(?Send)]
 pub trait UserDataTrait {
@@ -46,7 +83,7 @@ pub trait UserDataTrait {
     /// * `Option<User>` - Some(user) if found, None if not found or error
     async fn find_user_by_email(&self, email: &str) -> Option<User>;
 
-    /// Finds a user by wallet address. Returns a single user (LIMIT 1).
+    /// Finds a user by wallet address (primary or linked). Returns a single user (LIMIT 1).
     ///
     /// # Arguments
     /// * `wallet` - The wallet address to search for
@@ -55,13 +92,246 @@ pub trait UserDataTrait {
     /// * `Option<User>` - Some(user) if found, None if not found or error
     async fn find_user_by_wallet(&self, wallet: &str) -> Option<User>;
 
-    /// Retrieves all users from the database.
+    /// Finds a user by its pure UUID (the `sub` claim of the JWT).
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if found, None if not found or error
+    async fn find_user_by_id(&self, user_id: &str) -> Option<User>;
+
+    /// Finds the user linked to the external identity `subject` at `provider`.
+    ///
+    /// # Arguments
+    /// * `provider` - Configured provider name
+    /// * `subject` - User id at the provider
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if found, None if not found or error
+    async fn find_user_by_identity(&self, provider: &str, subject: &str) -> Option<User>;
+
+    /// Finds a user whose e-mail is verified, with or without a local password.
+    ///
+    /// # Arguments
+    /// * `email` - The email to search for
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if found, None if not found or error
+    async fn find_user_by_verified_email(&self, email: &str) -> Option<User>;
+
+    /// Appends an external identity to the user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `identity` - The identity to link
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn link_identity(&self, user_id: &str, identity: ExternalIdentity) -> Option<User>;
+
+    /// Replaces the external identities of the user (used to unlink one).
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `identities` - The complete new list
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn set_identities(&self, user_id: &str, identities: Vec<ExternalIdentity>)
+        -> Option<User>;
+
+    /// Replaces the primary wallet and the linked wallets of the user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `wallet` - The new primary wallet, or None
+    /// * `wallets` - The complete new list of linked wallets
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn set_wallets(
+        &self,
+        user_id: &str,
+        wallet: Option<String>,
+        wallets: Vec<LinkedWallet>,
+    ) -> Option<User>;
+
+    /// Replaces the roles of a user and increments its `token_version`.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `roles` - The complete new list of roles
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) with the updated roles, None if not found or error
+    async fn set_user_roles(&self, user_id: &str, roles: Vec<Role>) -> Option<User>;
+
+    /// Counts the users holding a role.
+    ///
+    /// # Arguments
+    /// * `role_name` - The name of the role
+    ///
+    /// # Returns
+    /// * `Option<usize>` - Some(count), None on error
+    async fn count_users_with_role(&self, role_name: &str) -> Option<usize>;
+
+    /// Replaces the password hash of a user and increments its `token_version`,
+    /// revoking every token issued before the change.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `password_hash` - The new, already hashed password
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn update_password(&self, user_id: &str, password_hash: &str) -> Option<User>;
+
+    /// Replaces the password hash with a re-hash of the same password (algorithm or
+    /// parameter upgrade). Unlike `update_password` the `token_version` is kept,
+    /// and nothing changes if the stored hash is no longer `old_hash`.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `old_hash` - The hash the password was just verified against
+    /// * `new_hash` - The new hash of the same password
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found, changed meanwhile or error
+    async fn upgrade_password_hash(
+        &self,
+        user_id: &str,
+        old_hash: &str,
+        new_hash: &str,
+    ) -> Option<User>;
+
+    /// Sets `email_verified = true` on a user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn mark_email_verified(&self, user_id: &str) -> Option<User>;
+
+    /// Replaces the user's TOTP settings; `None` removes them (2FA disabled).
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `totp` - The new settings, or None to clear them
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn set_totp(&self, user_id: &str, totp: Option<TotpSettings>) -> Option<User>;
+
+    /// Atomically increments `totp.failed_attempts`, provided 2FA is enabled and
+    /// the user's `token_version` is still `token_version`.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `token_version` - The version the pending `mfa_token` was issued for
+    ///
+    /// # Returns
+    /// * `Option<u32>` - Some(attempts counted so far), None if not found, stale or error
+    async fn increment_totp_failures(&self, user_id: &str, token_version: u32) -> Option<u32>;
+
+    /// Resets `totp.failed_attempts` and increments `token_version` in one update,
+    /// locking out the pending `mfa_token`s.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn lock_out_totp(&self, user_id: &str) -> Option<User>;
+
+    /// Increments `token_version`, revoking every token issued so far.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn revoke_tokens(&self, user_id: &str) -> Option<User>;
+
+    /// Replaces the profile of a user.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `profile` - The complete new profile
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn update_profile(&self, user_id: &str, profile: UserProfile) -> Option<User>;
+
+    /// Replaces the e-mail address of a user and sets `email_verified = false`.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `email` - The new address
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn update_email(&self, user_id: &str, email: &str) -> Option<User>;
+
+    /// Searches the user directory, ordered by username. Blocked and
+    /// deactivated users are never returned.
+    ///
+    /// # Arguments
+    /// * `search` - Filters, cursor and page size
+    ///
+    /// # Returns
+    /// * `Vec<User>` - Up to `search.limit` users, or an empty list on error
+    async fn search_users(&self, search: &UserSearch) -> Vec<User>;
+
+    /// Finds the active user whose username equals `username`, ignoring case.
+    /// Unlike `find_user_by_username`, accounts without a password (bots,
+    /// wallet users) are found too; blocked and deactivated users are not.
+    ///
+    /// # Arguments
+    /// * `username` - The username to look up
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if found, None if not found or error
+    async fn find_active_user_by_username(&self, username: &str) -> Option<User>;
+
+    /// Blocks or unblocks an account. Blocking also increments `token_version`,
+    /// so the tokens already issued to the user stop being accepted.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `blocked` - The new state
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn set_blocked(&self, user_id: &str, blocked: bool) -> Option<User>;
+
+    /// Deactivates the account now, optionally scheduling its deletion, and
+    /// increments `token_version` so the user is signed out everywhere.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
+    /// * `deletion_scheduled_at` - When the account is to be deleted, or None
+    ///
+    /// # Returns
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn deactivate(
+        &self,
+        user_id: &str,
+        deletion_scheduled_at: Option<DateTime<Utc>>,
+    ) -> Option<User>;
+
+    /// Reactivates a deactivated account and cancels a scheduled deletion.
+    ///
+    /// # Arguments
+    /// * `user_id` - The UUID of the user (without the `user:` prefix)
     ///
     /// # Returns
-    /// * `Vec<User>` - A list of all users, or an empty list on error
-    async fn get_all_users(&self) -> Vec<User>;
+    /// * `Option<User>` - Some(user) if updated, None if not found or error
+    async fn reactivate(&self, user_id: &str) -> Option<User>;
 
-    /// Deletes all users that have a wallet associated.
+    /// Deletes all wallet-only users (see `User::is_wallet_only`); accounts that
+    /// linked a wallet to an e-mail, password or external identity are kept.
     ///
     /// # Returns
     /// * `bool` - true on success, false on error
@@ -168,7 +438,7 @@ impl UserDataTrait for Database {
         debug!("DB find_user_by_wallet: {}", wallet);
         let result = self
             .client
-            .query("SELECT * FROM user WHERE wallet = $wallet LIMIT 1")
+            .query("SELECT * FROM user WHERE wallet = $wallet OR (wallets OR []).address CONTAINS $wallet LIMIT 1")
             .bind(("wallet", wallet.to_owned()))
             .await;
 
@@ -195,33 +465,753 @@ impl UserDataTrait for Database {
         }
     }
 
-    // Retrieve all users
-    async fn get_all_users(&self) -> Vec<User> {
-        debug!("DB get_all_users");
-        let result = self.client.query("SELECT * FROM user").await;
+    // Find a user by its pure UUID
+    async fn find_user_by_id(&self, user_id: &str) -> Option<User> {
+        debug!("DB find_user_by_id: {}", user_id);
+        let result = self
+            .client
+            .query("SELECT * FROM $id")
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
 
         match result {
-            Ok(mut response) => match response.take::<Vec<User>>(0) {
-                Ok(users) => {
-                    debug!("DB get_all_users: found {} users", users.len());
-                    users
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        debug!("DB find_user_by_id: found");
+                    } else {
+                        debug!("DB find_user_by_id: not found");
+                    }
+                    user_opt
                 }
                 Err(e) => {
-                    error!("DB get_all_users deserialization error: {:?}", e);
-                    Vec::new()
+                    error!("DB find_user_by_id deserialization error: {:?}", e);
+                    None
                 }
             },
             Err(e) => {
-                error!("DB get_all_users query error: {:?}", e);
-                Vec::new()
+                error!("DB find_user_by_id query error: {:?}", e);
+                None
             }
         }
     }
 
-    // Delete all users with wallets
-    async fn delete_wallet_users(&self) -> bool {
-        debug!("DB delete_wallet_users");
-        let result = self.client.query("DELETE user WHERE wallet != NONE").await;
+    // Find the user linked to an external identity
+    async fn find_user_by_identity(&self, provider: &str, subject: &str) -> Option<User> {
+        debug!("DB find_user_by_identity: {}", provider);
+        let result = self
+            .client
+            .query("SELECT * FROM user WHERE array::len((identities OR [])[WHERE provider = $provider AND subject = $subject]) > 0 LIMIT 1")
+            .bind(("provider", provider.to_owned()))
+            .bind(("subject", subject.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        debug!("DB find_user_by_identity: found");
+                    } else {
+                        debug!("DB find_user_by_identity: not found");
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB find_user_by_identity deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB find_user_by_identity query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Find a user by verified email (password optional)
+    async fn find_user_by_verified_email(&self, email: &str) -> Option<User> {
+        debug!("DB find_user_by_verified_email: {}", email);
+        let result = self
+            .client
+            .query("SELECT * FROM user WHERE email = $email AND email_verified = true LIMIT 1")
+            .bind(("email", email.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        debug!("DB find_user_by_verified_email: found");
+                    } else {
+                        debug!("DB find_user_by_verified_email: not found");
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB find_user_by_verified_email deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB find_user_by_verified_email query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Append an external identity to a user
+    async fn link_identity(&self, user_id: &str, identity: ExternalIdentity) -> Option<User> {
+        debug!("DB link_identity: {} -> {}", identity.provider, user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET identities = array::append(identities OR [], $identity) RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("identity", identity))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB link_identity: linked identity to {}", user_id);
+                    } else {
+                        warn!("DB link_identity: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB link_identity deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB link_identity query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Replace the external identities of a user
+    async fn set_identities(
+        &self,
+        user_id: &str,
+        identities: Vec<ExternalIdentity>,
+    ) -> Option<User> {
+        debug!("DB set_identities: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET identities = $identities RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("identities", identities))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB set_identities: updated {}", user_id);
+                    } else {
+                        warn!("DB set_identities: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB set_identities deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB set_identities query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Replace the primary and linked wallets of a user
+    async fn set_wallets(
+        &self,
+        user_id: &str,
+        wallet: Option<String>,
+        wallets: Vec<LinkedWallet>,
+    ) -> Option<User> {
+        debug!("DB set_wallets: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET wallet = $wallet, wallets = $wallets RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("wallet", wallet))
+            .bind(("wallets", wallets))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB set_wallets: updated {}", user_id);
+                    } else {
+                        warn!("DB set_wallets: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB set_wallets deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB set_wallets query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Replace roles and bump the token version atomically
+    async fn set_user_roles(&self, user_id: &str, roles: Vec<Role>) -> Option<User> {
+        debug!("DB set_user_roles: {} ({} roles)", user_id, roles.len());
+        let result = self
+            .client
+            .query("UPDATE $id SET roles = $roles, token_version = (token_version OR 0) + 1 RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("roles", roles))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB set_user_roles: updated {}", user_id);
+                    } else {
+                        warn!("DB set_user_roles: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB set_user_roles deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB set_user_roles query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Count the users whose embedded roles include the role
+    async fn count_users_with_role(&self, role_name: &str) -> Option<usize> {
+        debug!("DB count_users_with_role: {}", role_name);
+        let result = self
+            .client
+            .query("RETURN count(SELECT VALUE id FROM user WHERE $role INSIDE roles.name)")
+            .bind(("role", role_name.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<usize>>(0) {
+                Ok(count) => Some(count.unwrap_or(0)),
+                Err(e) => {
+                    error!("DB count_users_with_role deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB count_users_with_role query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Replace the password hash and revoke existing tokens
+    async fn update_password(&self, user_id: &str, password_hash: &str) -> Option<User> {
+        debug!("DB update_password: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET password = $password, token_version = (token_version OR 0) + 1 RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("password", password_hash.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB update_password: updated {}", user_id);
+                    } else {
+                        warn!("DB update_password: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB update_password deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB update_password query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Swap the password hash for an upgraded one, only if it did not change meanwhile
+    async fn upgrade_password_hash(
+        &self,
+        user_id: &str,
+        old_hash: &str,
+        new_hash: &str,
+    ) -> Option<User> {
+        debug!("DB upgrade_password_hash: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET password = $new_hash WHERE password = $old_hash RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("old_hash", old_hash.to_owned()))
+            .bind(("new_hash", new_hash.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB upgrade_password_hash: updated {}", user_id);
+                    } else {
+                        warn!("DB upgrade_password_hash: user not found or hash changed {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB upgrade_password_hash deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB upgrade_password_hash query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Mark the email address of a user as verified
+    async fn mark_email_verified(&self, user_id: &str) -> Option<User> {
+        debug!("DB mark_email_verified: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET email_verified = true RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB mark_email_verified: verified {}", user_id);
+                    } else {
+                        warn!("DB mark_email_verified: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB mark_email_verified deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB mark_email_verified query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Replace or clear the 2FA settings of a user
+    async fn set_totp(&self, user_id: &str, totp: Option<TotpSettings>) -> Option<User> {
+        debug!("DB set_totp: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET totp = $totp RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("totp", totp))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB set_totp: updated 2FA settings of {}", user_id);
+                    } else {
+                        warn!("DB set_totp: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB set_totp deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB set_totp query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Count a 2FA attempt in the database, so parallel requests cannot lose updates
+    async fn increment_totp_failures(&self, user_id: &str, token_version: u32) -> Option<u32> {
+        debug!("DB increment_totp_failures: {}", user_id);
+        let result = self
+            .client
+            .query(
+                "UPDATE $id SET totp.failed_attempts = (totp.failed_attempts OR 0) + 1 \
+                 WHERE totp.enabled = true AND token_version = $ver \
+                 RETURN VALUE totp.failed_attempts",
+            )
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("ver", token_version))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<u32>>(0) {
+                Ok(attempts) => {
+                    if attempts.is_none() {
+                        warn!("DB increment_totp_failures: no 2FA or stale token for {}", user_id);
+                    }
+                    attempts
+                }
+                Err(e) => {
+                    error!("DB increment_totp_failures deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB increment_totp_failures query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Reset the 2FA failure counter and revoke issued tokens together
+    async fn lock_out_totp(&self, user_id: &str) -> Option<User> {
+        debug!("DB lock_out_totp: {}", user_id);
+        let result = self
+            .client
+            .query(
+                "UPDATE $id SET totp.failed_attempts = 0, \
+                 token_version = (token_version OR 0) + 1 RETURN AFTER",
+            )
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB lock_out_totp: tokens of {} revoked", user_id);
+                    } else {
+                        warn!("DB lock_out_totp: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB lock_out_totp deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB lock_out_totp query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Bump the token version, revoking issued tokens
+    async fn revoke_tokens(&self, user_id: &str) -> Option<User> {
+        debug!("DB revoke_tokens: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET token_version = (token_version OR 0) + 1 RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB revoke_tokens: revoked tokens of {}", user_id);
+                    } else {
+                        warn!("DB revoke_tokens: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB revoke_tokens deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB revoke_tokens query error: {:?}", e);
+                None
+            }
+        }
+    }
+    // Replace the embedded profile of a user
+    async fn update_profile(&self, user_id: &str, profile: UserProfile) -> Option<User> {
+        debug!("DB update_profile: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET profile = $profile RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("profile", profile))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB update_profile: updated {}", user_id);
+                    } else {
+                        warn!("DB update_profile: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB update_profile deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB update_profile query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Change the e-mail address; the new one must be verified again
+    async fn update_email(&self, user_id: &str, email: &str) -> Option<User> {
+        debug!("DB update_email: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET email = $email, email_verified = false RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("email", email.to_owned()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB update_email: updated {}", user_id);
+                    } else {
+                        warn!("DB update_email: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB update_email deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB update_email query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Search the user directory (one page, ordered by username)
+    async fn search_users(&self, search: &UserSearch) -> Vec<User> {
+        debug!("DB search_users: {:?}", search);
+        let mut conditions = vec!["blocked != true", "deactivated_at = NONE"];
+        if search.prefix.is_some() {
+            conditions.push(
+                "(string::starts_with(string::lowercase(username), $prefix) \
+                 OR string::starts_with(string::lowercase(profile.display_name OR ''), $prefix))",
+            );
+        }
+        if search.wallet.is_some() {
+            conditions.push("(wallet = $wallet OR (wallets OR []).address CONTAINS $wallet)");
+        }
+        if search.after.is_some() {
+            conditions.push("username > $after");
+        }
+        let sql = format!(
+            "SELECT * FROM user WHERE {} ORDER BY username ASC LIMIT $limit",
+            conditions.join(" AND ")
+        );
+        let result = self
+            .client
+            .query(sql)
+            .bind(("prefix", search.prefix.as_ref().map(|p| p.to_lowercase())))
+            .bind(("wallet", search.wallet.as_ref().map(|w| w.to_lowercase())))
+            .bind(("after", search.after.clone()))
+            .bind(("limit", search.limit as i64))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Vec<User>>(0) {
+                Ok(users) => {
+                    debug!("DB search_users: found {} users", users.len());
+                    users
+                }
+                Err(e) => {
+                    error!("DB search_users deserialization error: {:?}", e);
+                    Vec::new()
+                }
+            },
+            Err(e) => {
+                error!("DB search_users query error: {:?}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    // Find an active user by username, ignoring case
+    async fn find_active_user_by_username(&self, username: &str) -> Option<User> {
+        debug!("DB find_active_user_by_username: {}", username);
+        let result = self
+            .client
+            .query(
+                "SELECT * FROM user WHERE string::lowercase(username) = $username \
+                 AND blocked != true AND deactivated_at = NONE LIMIT 1",
+            )
+            .bind(("username", username.to_lowercase()))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        debug!("DB find_active_user_by_username: found");
+                    } else {
+                        debug!("DB find_active_user_by_username: not found");
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB find_active_user_by_username deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB find_active_user_by_username query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Block or unblock an account; blocking revokes issued tokens
+    async fn set_blocked(&self, user_id: &str, blocked: bool) -> Option<User> {
+        debug!("DB set_blocked: {} -> {}", user_id, blocked);
+        let sql = if blocked {
+            "UPDATE $id SET blocked = true, token_version = (token_version OR 0) + 1 RETURN AFTER"
+        } else {
+            "UPDATE $id SET blocked = false RETURN AFTER"
+        };
+        let result = self
+            .client
+            .query(sql)
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB set_blocked: {} blocked={}", user_id, blocked);
+                    } else {
+                        warn!("DB set_blocked: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB set_blocked deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB set_blocked query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Deactivate (and possibly schedule the deletion of) an account
+    async fn deactivate(
+        &self,
+        user_id: &str,
+        deletion_scheduled_at: Option<DateTime<Utc>>,
+    ) -> Option<User> {
+        debug!("DB deactivate: {}", user_id);
+        let sql = "UPDATE $id SET deactivated_at = $now, deletion_scheduled_at = $deletion, token_version = (token_version OR 0) + 1 RETURN AFTER";
+        let result = self
+            .client
+            .query(sql)
+            .bind(("id", Thing::from(("user", user_id))))
+            .bind(("now", Utc::now()))
+            .bind(("deletion", deletion_scheduled_at))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB deactivate: deactivated {}", user_id);
+                    } else {
+                        warn!("DB deactivate: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB deactivate deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB deactivate query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Clear the deactivation and any scheduled deletion
+    async fn reactivate(&self, user_id: &str) -> Option<User> {
+        debug!("DB reactivate: {}", user_id);
+        let result = self
+            .client
+            .query("UPDATE $id SET deactivated_at = NONE, deletion_scheduled_at = NONE RETURN AFTER")
+            .bind(("id", Thing::from(("user", user_id))))
+            .await;
+
+        match result {
+            Ok(mut response) => match response.take::<Option<User>>(0) {
+                Ok(user_opt) => {
+                    if user_opt.is_some() {
+                        info!("DB reactivate: reactivated {}", user_id);
+                    } else {
+                        warn!("DB reactivate: user not found {}", user_id);
+                    }
+                    user_opt
+                }
+                Err(e) => {
+                    error!("DB reactivate deserialization error: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("DB reactivate query error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    // Delete the users that only have a wallet to sign in with
+    async fn delete_wallet_users(&self) -> bool {
+        debug!("DB delete_wallet_users");
+        let result = self
+            .client
+            .query(
+                "DELETE user WHERE (wallet != NONE OR array::len(wallets OR []) > 0) \
+                 AND email = NONE AND password = NONE AND array::len(identities OR []) = 0",
+            )
+            .await;
 
         match result {
             Ok(_) => {
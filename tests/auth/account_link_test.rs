@@ -0,0 +1,263 @@
+//! Account Link Tests Module
+//! Exercises wallet linking by signed challenge, unlinking, and account merges
+//! (including the tasks they move) against in-memory fakes.
+
+use chasqui_server::application::services::account_link_service::{
+    challenge_message, AccountLinkService,
+};
+use chasqui_server::application::services::account_merge_service::AccountMergeService;
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::wallet::{address_of, personal_message_hash};
+use chasqui_server::models::entities::audit_event::AuditAction;
+use chasqui_server::models::entities::task::AddTaskRequest;
+use chasqui_server::models::entities::user::User;
+use chrono::Duration;
+use k256::ecdsa::SigningKey;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeAccountMerges, FakeAuditLog, FakeConversations, FakeOneTimeTokens, FakeUsers};
+
+fn user(username: &str) -> User {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    User::new(
+        username.to_string(),
+        format!("{}@example.com", username),
+        "$ecret123!".to_string(),
+    )
+    .expect("user")
+}
+
+fn wallet_key(byte: u8) -> (SigningKey, String) {
+    let mut secret = [0u8; 32];
+    secret[31] = byte;
+    let key = SigningKey::from_slice(&secret).unwrap();
+    let address = address_of(key.verifying_key());
+    (key, address)
+}
+
+fn sign(key: &SigningKey, message: &str) -> String {
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(&personal_message_hash(message))
+        .unwrap();
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(recovery_id.to_byte() + 27);
+    format!(
+        "0x{}",
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+fn link_service() -> AccountLinkService {
+    AccountLinkService::new(Arc::new(FakeOneTimeTokens::default()), Duration::minutes(5))
+}
+
+#[actix_rt::test]
+async fn wallet_link_requires_signed_single_use_challenge() {
+    let alice = user("alice");
+    let users = FakeUsers::with(vec![alice.clone()]);
+    let service = link_service();
+    let (key, address) = wallet_key(1);
+
+    let challenge = service.wallet_challenge(&alice, &address).await.unwrap();
+    assert_eq!(
+        challenge.message,
+        challenge_message(&alice, &address, &challenge.nonce)
+    );
+
+    // Signed by another wallet: rejected, and the challenge is spent
+    let (other_key, _) = wallet_key(2);
+    assert!(matches!(
+        service
+            .link_wallet(
+                &users,
+                &alice,
+                &address,
+                &challenge.nonce,
+                &sign(&other_key, &challenge.message),
+            )
+            .await,
+        Err(AuthError::InvalidWallet(_))
+    ));
+
+    let challenge = service.wallet_challenge(&alice, &address).await.unwrap();
+    let signature = sign(&key, &challenge.message);
+    let linked = service
+        .link_wallet(&users, &alice, &address, &challenge.nonce, &signature)
+        .await
+        .unwrap();
+    assert!(linked.has_wallet(&address));
+    assert!(linked.wallets[0].is_verified());
+    assert_eq!(linked.wallet.as_deref(), Some(address.as_str()));
+    // Linking a wallet does not make an e-mail account a wallet-only one
+    assert!(!linked.is_wallet_only());
+    assert!(User::new_from_wallet(address.clone()).is_wallet_only());
+
+    assert!(matches!(
+        service
+            .link_wallet(&users, &alice, &address, &challenge.nonce, &signature)
+            .await,
+        Err(AuthError::InvalidOrExpiredToken)
+    ));
+}
+
+#[actix_rt::test]
+async fn wallet_owned_by_another_account_is_refused() {
+    let (key, address) = wallet_key(3);
+    let owner = User::new_from_wallet(address.clone());
+    let alice = user("alice");
+    let users = FakeUsers::with(vec![owner, alice.clone()]);
+    let service = link_service();
+
+    let challenge = service.wallet_challenge(&alice, &address).await.unwrap();
+    let result = service
+        .link_wallet(
+            &users,
+            &alice,
+            &address,
+            &challenge.nonce,
+            &sign(&key, &challenge.message),
+        )
+        .await;
+    assert!(matches!(result, Err(AuthError::WalletAlreadyLinked)));
+}
+
+#[actix_rt::test]
+async fn last_login_method_cannot_be_unlinked() {
+    let (_, address) = wallet_key(4);
+    let wallet_only = User::new_from_wallet(address.clone());
+    let users = FakeUsers::with(vec![wallet_only.clone()]);
+    let service = link_service();
+
+    assert!(matches!(
+        service.unlink_wallet(&users, &wallet_only, &address).await,
+        Err(AuthError::LastLoginMethod)
+    ));
+    let (_, unknown) = wallet_key(5);
+    assert!(matches!(
+        service.unlink_wallet(&users, &wallet_only, &unknown).await,
+        Err(AuthError::CredentialNotFound)
+    ));
+    assert!(matches!(
+        service
+            .unlink_identity(&users, &wallet_only, "google", "sub-1")
+            .await,
+        Err(AuthError::CredentialNotFound)
+    ));
+}
+
+#[actix_rt::test]
+async fn merge_carries_credentials_into_target_and_is_audited() {
+    let (_, address) = wallet_key(6);
+    let source = User::new_from_wallet(address.clone());
+    let alice = user("alice");
+    let merges = Arc::new(FakeAccountMerges::default());
+    let audit = Arc::new(FakeAuditLog::default());
+    let service = AccountMergeService::new(merges.clone(), audit.clone());
+
+    assert!(matches!(
+        service.merge(&alice, &alice, &alice).await,
+        Err(AuthError::InvalidMerge(_))
+    ));
+
+    let merged = service.merge(&source, &alice, &alice).await.unwrap();
+    assert!(merged.has_wallet(&address));
+    assert_eq!(merged.wallet.as_deref(), Some(address.as_str()));
+    assert_eq!(merged.password, alice.password);
+    assert_eq!(merged.roles, alice.roles);
+
+    let recorded = merges.merges.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(Some(&recorded[0].0), source.id.as_ref());
+    assert!(recorded[0].1.has_wallet(&address));
+
+    let events = audit.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, AuditAction::AccountMerged);
+}
+
+#[actix_rt::test]
+async fn merge_moves_the_tasks_of_the_source_to_the_target() {
+    let (_, address) = wallet_key(7);
+    let source = User::new_from_wallet(address);
+    let alice = user("alice");
+    let merges = Arc::new(FakeAccountMerges::default());
+    let service = AccountMergeService::new(merges.clone(), Arc::new(FakeAuditLog::default()));
+    let tasks = TaskService::new(Arc::new(FakeConversations::default()), 20, 100);
+    let users = FakeUsers::with(vec![source.clone(), alice.clone()]);
+
+    let task = tasks
+        .create(
+            &*merges.tasks,
+            &users,
+            &source,
+            TaskAccess::default(),
+            AddTaskRequest {
+                task_name: "Ship it".to_string(),
+                assignee: source.id_string(),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(tasks
+        .get(&*merges.tasks, &alice, TaskAccess::default(), &task.uuid)
+        .await
+        .is_err());
+
+    let merged = service.merge(&source, &alice, &alice).await.unwrap();
+    let moved = tasks
+        .get(&*merges.tasks, &merged, TaskAccess::default(), &task.uuid)
+        .await
+        .unwrap();
+    assert_eq!(moved.creator, alice.id_string());
+    assert_eq!(moved.assignee, alice.id_string());
+    assert!(moved.version > task.version);
+}
+
+#[actix_rt::test]
+async fn wallet_login_requires_signed_single_use_challenge() {
+    let alice = user("alice");
+    let service = link_service();
+    let (key, address) = wallet_key(3);
+
+    let challenge = service.login_challenge(&address).await.unwrap();
+    assert_eq!(challenge.wallet, address);
+
+    // A link challenge signature cannot be replayed to sign in
+    let link_signature = sign(&key, &challenge_message(&alice, &address, &challenge.nonce));
+    assert!(matches!(
+        service
+            .verify_login(&address, &challenge.nonce, &link_signature)
+            .await,
+        Err(AuthError::InvalidWallet(_))
+    ));
+
+    // The failed attempt spent the challenge
+    let signature = sign(&key, &challenge.message);
+    assert!(matches!(
+        service
+            .verify_login(&address, &challenge.nonce, &signature)
+            .await,
+        Err(AuthError::InvalidOrExpiredToken)
+    ));
+
+    let challenge = service.login_challenge(&address).await.unwrap();
+    let signature = sign(&key, &challenge.message);
+    assert_eq!(
+        service
+            .verify_login(
+                &address.to_uppercase().replace("0X", "0x"),
+                &challenge.nonce,
+                &signature
+            )
+            .await
+            .unwrap(),
+        address
+    );
+}
@@ -0,0 +1,252 @@
+//! API Key Tests Module
+//! Tests creating, authenticating and revoking scoped API keys using in-memory
+//! repositories, and the scope checks applied to task endpoints and to tasks
+//! created from messages.
+
+use actix_web::http::StatusCode;
+use chasqui_server::application::services::api_key_service::ApiKeyService;
+use chasqui_server::error::AuthError;
+use chasqui_server::interfaces::api::auth::AuthenticatedUser;
+use chasqui_server::models::entities::api_key::{is_api_key, ApiKeyScope};
+use chasqui_server::models::entities::message::Message;
+use chasqui_server::models::entities::role::{roles, Permission, Role};
+use chasqui_server::models::entities::task::Task;
+use chasqui_server::models::entities::user::User;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeApiKeys;
+
+const CONVERSATION: &str = "conversation:general";
+
+fn admin() -> User {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let mut admin = User::new(
+        "root".to_string(),
+        "root@example.com".to_string(),
+        "Adm1n$ecret".to_string(),
+    )
+    .expect("user");
+    admin.add_role(roles::admin());
+    admin
+}
+
+/// Bot allowed to post messages through a custom role
+fn bot() -> User {
+    let mut bot = User::new_bot("deploy-bot".to_string());
+    bot.add_role(
+        Role::new("integration", "Posts messages").with_permissions(&[Permission::MessageCreate]),
+    );
+    bot
+}
+
+fn post_scope() -> Vec<ApiKeyScope> {
+    vec![ApiKeyScope {
+        permission: Permission::MessageCreate,
+        conversation_id: Some(CONVERSATION.to_string()),
+    }]
+}
+
+fn setup() -> (ApiKeyService, Arc<FakeApiKeys>) {
+    let keys = Arc::new(FakeApiKeys::default());
+    (ApiKeyService::new(keys.clone()), keys)
+}
+
+#[actix_rt::test]
+async fn created_key_is_prefixed_hashed_and_authenticates() {
+    let (service, keys) = setup();
+    let (bot, admin) = (bot(), admin());
+
+    let (created, plaintext) = service
+        .create(&bot, &admin, "deploys", post_scope(), Some(30))
+        .await
+        .expect("created");
+
+    assert!(is_api_key(&plaintext));
+    assert!(plaintext.starts_with(&created.prefix));
+    assert_ne!(keys.keys.lock().unwrap()[0].key_hash, plaintext);
+    assert_eq!(created.user_id, bot.id.clone().unwrap());
+
+    let key = service.authenticate(&plaintext).await.expect("valid key");
+    assert!(key.allows(Permission::MessageCreate, Some(CONVERSATION)));
+    assert!(!key.allows(Permission::MessageCreate, Some("conversation:other")));
+    assert!(keys.keys.lock().unwrap()[0].last_used_at.is_some());
+
+    assert!(matches!(
+        service.authenticate("chq_00000000_wrong").await,
+        Err(AuthError::InvalidApiKey)
+    ));
+}
+
+#[actix_rt::test]
+async fn scopes_must_be_held_by_the_key_user() {
+    let (service, _) = setup();
+    let admin = admin();
+    let plain_bot = User::new_bot("reader-bot".to_string());
+
+    // The `user` role does not grant message:create
+    let err = service
+        .create(&plain_bot, &admin, "deploys", post_scope(), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::InvalidApiKeyScope(_)));
+
+    let err = service
+        .create(&bot(), &admin, "deploys", Vec::new(), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::InvalidApiKeyScope(_)));
+
+    let bad_conversation = vec![ApiKeyScope {
+        permission: Permission::MessageCreate,
+        conversation_id: Some("general".to_string()),
+    }];
+    let err = service
+        .create(&bot(), &admin, "deploys", bad_conversation, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AuthError::InvalidApiKeyScope(_)));
+}
+
+#[actix_rt::test]
+async fn revoked_key_is_rejected_and_only_owner_or_admin_can_revoke() {
+    let (service, _) = setup();
+    let (bot, admin) = (bot(), admin());
+    let (created, plaintext) = service
+        .create(&bot, &admin, "deploys", post_scope(), None)
+        .await
+        .unwrap();
+    let key_id = created.id.unwrap().id.to_raw();
+
+    let stranger = User::new_bot("other-bot".to_string());
+    assert!(matches!(
+        service.revoke(&key_id, &stranger).await,
+        Err(AuthError::ApiKeyNotFound)
+    ));
+    service.authenticate(&plaintext).await.expect("still valid");
+
+    service
+        .revoke(&key_id, &admin)
+        .await
+        .expect("admin revokes");
+    assert!(matches!(
+        service.authenticate(&plaintext).await,
+        Err(AuthError::InvalidApiKey)
+    ));
+    assert!(service.list(bot.id.unwrap()).await.unwrap()[0]
+        .revoked_at
+        .is_some());
+}
+
+#[actix_rt::test]
+async fn task_changes_need_a_task_scope_on_the_task_conversation() {
+    let (service, _) = setup();
+    let admin = admin();
+    let mut bot = User::new_bot("planner-bot".to_string());
+    bot.add_role(
+        Role::new("planner", "Manages tasks")
+            .with_permissions(&[Permission::TaskRead, Permission::TaskUpdate]),
+    );
+    let mut task = Task::new("t1".to_string(), "Ship it".to_string());
+    task.creator = bot.id_string();
+    task.conversation_id = Some("general".to_string());
+    let status = |r: Result<(), actix_web::HttpResponse>| r.map_err(|resp| resp.status());
+
+    // A read-only key can see the bot's own task but not change or delete it
+    let read_only = vec![ApiKeyScope {
+        permission: Permission::TaskRead,
+        conversation_id: None,
+    }];
+    let (key, _) = service
+        .create(&bot, &admin, "reader", read_only, None)
+        .await
+        .unwrap();
+    let auth = AuthenticatedUser::from_api_key(bot.clone(), key);
+    assert!(auth.require_task_scope(Permission::TaskRead, &task).is_ok());
+    for permission in [Permission::TaskUpdate, Permission::TaskDelete] {
+        assert_eq!(
+            status(auth.require_task_scope(permission, &task)),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    // A key limited to another conversation cannot change tasks of this one
+    let elsewhere = vec![ApiKeyScope {
+        permission: Permission::TaskUpdate,
+        conversation_id: Some("conversation:other".to_string()),
+    }];
+    let (key, _) = service
+        .create(&bot, &admin, "elsewhere", elsewhere, None)
+        .await
+        .unwrap();
+    let auth = AuthenticatedUser::from_api_key(bot, key);
+    assert_eq!(
+        status(auth.require_task_scope(Permission::TaskUpdate, &task)),
+        Err(StatusCode::FORBIDDEN)
+    );
+    task.conversation_id = Some("other".to_string());
+    assert!(auth
+        .require_task_scope(Permission::TaskUpdate, &task)
+        .is_ok());
+}
+
+#[actix_rt::test]
+async fn tasks_from_messages_need_both_scopes_on_the_message_conversation() {
+    let (service, _) = setup();
+    let admin = admin();
+    let mut bot = bot();
+    bot.add_role(Role::new("planner", "Manages tasks").with_permissions(&[Permission::TaskUpdate]));
+    let message = Message::new(
+        Thing::from(("conversation", "general")),
+        bot.id.clone().unwrap(),
+        "Deploy failed".to_string(),
+        None,
+    );
+    let scoped = |permissions: &[Permission], conversation: &str| {
+        permissions
+            .iter()
+            .map(|p| ApiKeyScope {
+                permission: *p,
+                conversation_id: Some(conversation.to_string()),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let both = scoped(
+        &[Permission::MessageCreate, Permission::TaskUpdate],
+        CONVERSATION,
+    );
+    let (key, _) = service
+        .create(&bot, &admin, "both", both, None)
+        .await
+        .unwrap();
+    let auth = AuthenticatedUser::from_api_key(bot.clone(), key);
+    assert!(auth.require_message_task_scope(&message).is_ok());
+
+    let message_only = scoped(&[Permission::MessageCreate], CONVERSATION);
+    let (key, _) = service
+        .create(&bot, &admin, "poster", message_only, None)
+        .await
+        .unwrap();
+    let auth = AuthenticatedUser::from_api_key(bot.clone(), key);
+    let denied = auth
+        .require_message_task_scope(&message)
+        .map_err(|r| r.status());
+    assert_eq!(denied, Err(StatusCode::FORBIDDEN));
+
+    let elsewhere = scoped(
+        &[Permission::MessageCreate, Permission::TaskUpdate],
+        "conversation:other",
+    );
+    let (key, _) = service
+        .create(&bot, &admin, "elsewhere", elsewhere, None)
+        .await
+        .unwrap();
+    let auth = AuthenticatedUser::from_api_key(bot, key);
+    let denied = auth
+        .require_message_task_scope(&message)
+        .map_err(|r| r.status());
+    assert_eq!(denied, Err(StatusCode::FORBIDDEN));
+}
@@ -0,0 +1,90 @@
+//! E-mail Verification Tests Module
+//! Exercises verification links, resend throttling and the enforcement policy.
+
+use chasqui_server::application::services::email_verification_service::{
+    EmailVerificationPolicy, EmailVerificationService,
+};
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::mail::memory::InMemoryMailer;
+use chasqui_server::models::entities::user::User;
+use chrono::Duration;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeOneTimeTokens, FakeUsers};
+
+fn setup(cooldown: Duration) -> (EmailVerificationService, Arc<InMemoryMailer>, FakeUsers, User) {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let user = User::new(
+        "bob".to_string(),
+        "bob@example.com".to_string(),
+        "$ecret123".to_string(),
+    )
+    .expect("user");
+    let mailer = Arc::new(InMemoryMailer::new());
+    let service = EmailVerificationService::new(
+        Arc::new(FakeOneTimeTokens::default()),
+        mailer.clone(),
+        EmailVerificationPolicy::parse("login"),
+        Duration::hours(24),
+        cooldown,
+        "https://api.example".to_string(),
+    );
+    (service, mailer, FakeUsers::with(vec![user.clone()]), user)
+}
+
+fn token_from_mail(body: &str) -> String {
+    let start = body.find("token=").expect("link in body") + "token=".len();
+    body[start..].split_whitespace().next().unwrap().to_string()
+}
+
+#[actix_rt::test]
+async fn verification_link_marks_email_verified() {
+    let (service, mailer, users, user) = setup(Duration::seconds(60));
+    assert!(!service.policy().allows_login(&user));
+
+    service.send_verification(&user).await.expect("mail sent");
+    let mail = mailer.last_to("bob@example.com").expect("mail");
+    assert!(mail
+        .body
+        .contains("https://api.example/api/auth/verify-email?token="));
+
+    let verified = service
+        .verify(&users, &token_from_mail(&mail.body))
+        .await
+        .expect("verify");
+    assert!(verified.email_verified);
+    assert!(service.policy().allows_login(&verified));
+
+    let again = service.verify(&users, &token_from_mail(&mail.body)).await;
+    assert!(matches!(again, Err(AuthError::InvalidOrExpiredToken)));
+}
+
+#[actix_rt::test]
+async fn resend_is_throttled() {
+    let (service, mailer, users, user) = setup(Duration::seconds(60));
+
+    service.send_verification(&user).await.unwrap();
+    service.resend(&users, "bob@example.com").await.unwrap();
+    assert_eq!(mailer.sent().len(), 1, "resend within cooldown is skipped");
+
+    let (service, mailer, users, user) = setup(Duration::zero());
+    service.send_verification(&user).await.unwrap();
+    service.resend(&users, "bob@example.com").await.unwrap();
+    assert_eq!(mailer.sent().len(), 2);
+}
+
+#[test]
+fn policy_parsing_and_wallet_exemption() {
+    let policy = EmailVerificationPolicy::parse("login, chat");
+    assert!(policy.require_for_login && policy.require_for_chat);
+    assert_eq!(
+        EmailVerificationPolicy::parse(""),
+        EmailVerificationPolicy::default()
+    );
+
+    let wallet_user = User::new_from_wallet("0xabc".to_string());
+    assert!(policy.allows_login(&wallet_user));
+    assert!(policy.allows_chat(&wallet_user));
+}
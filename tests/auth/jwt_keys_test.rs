@@ -0,0 +1,181 @@
+//! JWT Key Tests Module
+//! Tests asymmetric signing (RS256/EdDSA), `kid` based key rotation and the
+//! public JWKS endpoint.
+
+use actix_web::{test as actix_test, App};
+use chasqui_server::infrastructure::auth::jwt::{generate_token, validate_token};
+use chasqui_server::infrastructure::auth::keys::KeyStore;
+use chasqui_server::interfaces::api::routes;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde_json::{json, Value};
+
+const RSA_PRIVATE: &str = include_str!("../fixtures/jwt/rsa_private.pem");
+const RSA_PREVIOUS_PRIVATE: &str = include_str!("../fixtures/jwt/rsa_previous_private.pem");
+const RSA_PUBLIC: &str = include_str!("../fixtures/jwt/rsa_public.pem");
+const ED25519_PRIVATE: &str = include_str!("../fixtures/jwt/ed25519_private.pem");
+const ED25519_PUBLIC: &str = include_str!("../fixtures/jwt/ed25519_public.pem");
+
+/// Configures the process-wide keys: RS256 signing key "current" plus the
+/// public key of the rotated-out key "previous". Every test sets the same values.
+fn setup() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt");
+    std::env::set_var("SECRET_KEY", "testing_secret_key");
+    std::env::set_var("JWT_ALGORITHM", "RS256");
+    std::env::set_var("JWT_PRIVATE_KEY_FILE", format!("{}/rsa_private.pem", dir));
+    std::env::set_var("JWT_KEY_ID", "current");
+    std::env::set_var(
+        "JWT_VERIFICATION_KEY_FILES",
+        format!("previous={}/rsa_previous_public.pem", dir),
+    );
+}
+
+fn claims(exp_offset: i64) -> Value {
+    let now = chrono::Utc::now().timestamp();
+    json!({
+        "sub": "8a3f0c1e-0000-4000-8000-000000000001",
+        "exp": now + exp_offset,
+        "iat": now,
+        "username": "rotated",
+        "roles": ["user"],
+        "perms": [],
+        "ver": 0,
+    })
+}
+
+fn sign_rs256(kid: &str, pem: &str) -> String {
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    encode(
+        &header,
+        &claims(600),
+        &EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn signs_rs256_with_kid_and_verifies_with_published_key() {
+    setup();
+    let token = generate_token("user-1", "alice", &["user".to_string()]).unwrap();
+
+    let header = decode_header(&token).unwrap();
+    assert_eq!(header.alg, Algorithm::RS256);
+    assert_eq!(header.kid.as_deref(), Some("current"));
+    assert_eq!(validate_token(&token).unwrap().username, "alice");
+
+    // Another service only needs the public key
+    let decoding = DecodingKey::from_rsa_pem(RSA_PUBLIC.as_bytes()).unwrap();
+    let data = decode::<Value>(&token, &decoding, &Validation::new(Algorithm::RS256)).unwrap();
+    assert_eq!(data.claims["sub"], "user-1");
+}
+
+#[test]
+fn accepts_rotated_keys_and_rejects_unknown_or_hmac_tokens() {
+    setup();
+    let previous = sign_rs256("previous", RSA_PREVIOUS_PRIVATE);
+    assert_eq!(validate_token(&previous).unwrap().username, "rotated");
+
+    // Right key, wrong kid
+    assert!(validate_token(&sign_rs256("retired", RSA_PRIVATE)).is_err());
+    assert!(validate_token(&sign_rs256("current", RSA_PREVIOUS_PRIVATE)).is_err());
+
+    // Shared-secret tokens are no longer accepted (no JWT_ACCEPT_LEGACY_HS256)
+    let hs256 = encode(
+        &Header::default(),
+        &claims(600),
+        &EncodingKey::from_secret(b"testing_secret_key"),
+    )
+    .unwrap();
+    assert!(validate_token(&hs256).is_err());
+
+    // Algorithm confusion: HMAC keyed with the public PEM under the RSA kid
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some("current".to_string());
+    let forged = encode(
+        &header,
+        &claims(600),
+        &EncodingKey::from_secret(RSA_PUBLIC.as_bytes()),
+    )
+    .unwrap();
+    assert!(validate_token(&forged).is_err());
+
+    // Expired
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("current".to_string());
+    let expired = encode(
+        &header,
+        &claims(-600),
+        &EncodingKey::from_rsa_pem(RSA_PRIVATE.as_bytes()).unwrap(),
+    )
+    .unwrap();
+    assert!(validate_token(&expired).is_err());
+}
+
+#[actix_rt::test]
+async fn jwks_endpoint_publishes_all_verification_keys() {
+    setup();
+    let app = actix_test::init_service(App::new().configure(routes::config)).await;
+    let req = actix_test::TestRequest::get()
+        .uri("/.well-known/jwks.json")
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert!(resp.headers().contains_key("cache-control"));
+    let body: Value = actix_test::read_body_json(resp).await;
+
+    let kids: Vec<&str> = body["keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|k| k["kid"].as_str().unwrap())
+        .collect();
+    assert_eq!(kids, vec!["current", "previous"]);
+    assert!(body["keys"][0].get("d").is_none(), "private parts leaked");
+
+    // Verify a token the way an external consumer would
+    let set: JwkSet = serde_json::from_value(body).unwrap();
+    let token = generate_token("user-2", "bob", &[]).unwrap();
+    let kid = decode_header(&token).unwrap().kid.unwrap();
+    let jwk = set.find(&kid).expect("kid published");
+    let decoding = DecodingKey::from_jwk(jwk).unwrap();
+    let data = decode::<Value>(&token, &decoding, &Validation::new(Algorithm::RS256)).unwrap();
+    assert_eq!(data.claims["username"], "bob");
+}
+
+#[test]
+fn ed25519_store_uses_thumbprint_kid_and_okp_jwk() {
+    let store =
+        KeyStore::from_pems(ED25519_PRIVATE, None, &[(None, ED25519_PUBLIC.to_string())]).unwrap();
+    let signing = store.signing();
+    assert_eq!(signing.algorithm, Algorithm::EdDSA);
+    let kid = signing.kid.clone().unwrap();
+
+    // The matching public key has the same thumbprint, so it is not duplicated
+    let jwks = store.jwks();
+    let keys = jwks["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0]["kty"], "OKP");
+    assert_eq!(keys[0]["crv"], "Ed25519");
+    assert_eq!(keys[0]["kid"], kid.as_str());
+
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(kid.clone());
+    let token = encode(&header, &claims(600), &signing.key).unwrap();
+    let key = store
+        .verification_key(Some(&kid), Algorithm::EdDSA)
+        .unwrap();
+    assert!(decode::<Value>(&token, &key.key, &Validation::new(Algorithm::EdDSA)).is_ok());
+    assert!(store.verification_key(None, Algorithm::HS256).is_none());
+}
+
+#[test]
+fn hmac_store_publishes_nothing() {
+    let store = KeyStore::hmac("secret").with_legacy_hmac("secret");
+    assert_eq!(store.jwks(), json!({ "keys": [] }));
+    assert!(store.signing().kid.is_none());
+    assert!(store.verification_key(None, Algorithm::HS256).is_some());
+    assert!(KeyStore::from_pems("not a pem", None, &[]).is_err());
+}
@@ -1,7 +1,12 @@
 //! JWT Authentication Tests Module
 //! Tests password hashing and JWT token generation/validation.
 
-use actix_crud::infrastructure::auth::jwt::{generate_token, hash_password, verify_password};
+use chasqui_server::infrastructure::auth::jwt::{
+    generate_token, generate_token_for_user, validate_token,
+};
+use chasqui_server::infrastructure::auth::password::{hash_password, verify_password};
+use chasqui_server::models::entities::role::{roles, Permission};
+use chasqui_server::models::entities::user::User;
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use uuid::Uuid;
 
@@ -53,3 +58,30 @@ fn generate_and_decode_token() {
     assert!(data.claims.exp > now);
     assert!(data.claims.iat <= now);
 }
+
+/// Test that tokens issued for a persisted user carry its real roles, permissions and version
+#[test]
+fn token_for_user_carries_persisted_roles() {
+    std::env::set_var("SECRET_KEY", "testing_secret_key");
+
+    let mut user = User::new_from_wallet("0xabc123def456".to_string());
+    user.add_role(roles::moderator());
+    user.token_version = 3;
+
+    let token = generate_token_for_user(&user).expect("token generation should succeed");
+    let claims = validate_token(&token).expect("token should validate");
+
+    assert_eq!(claims.sub, user.id_string().unwrap());
+    assert_eq!(claims.roles, vec!["user".to_string(), "moderator".to_string()]);
+    assert!(claims.perms.contains(&"message:delete".to_string()));
+    assert!(claims.has_permission(Permission::ChannelRead));
+    assert!(!claims.has_permission(Permission::AdminAll));
+    assert_eq!(claims.ver, 3);
+
+    // admin:all collapses the permission set to a single entry
+    user.add_role(roles::admin());
+    let token = generate_token_for_user(&user).expect("token generation should succeed");
+    let claims = validate_token(&token).expect("token should validate");
+    assert_eq!(claims.perms, vec!["admin:all".to_string()]);
+    assert!(claims.has_permission(Permission::UserBan));
+}
@@ -0,0 +1,205 @@
+//! Login Throttling Tests Module
+//! Tests progressive delays, account and IP lockouts, unlocks and their audit
+//! events using in-memory repositories.
+
+use chasqui_server::application::services::login_throttle_service::LoginThrottleService;
+use chasqui_server::error::AuthError;
+use chasqui_server::models::entities::audit_event::AuditAction;
+use chasqui_server::models::entities::login_throttle::{ThrottleKind, ThrottlePolicy};
+use chrono::Duration;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeAuditLog, FakeLoginThrottles};
+
+const IP: &str = "203.0.113.7";
+
+/// Policy without progressive delay, so only lockouts are observed.
+fn lockout_policy() -> ThrottlePolicy {
+    ThrottlePolicy {
+        max_account_failures: 3,
+        max_ip_failures: 5,
+        base_delay: Duration::zero(),
+        ..ThrottlePolicy::default()
+    }
+}
+
+fn setup(policy: ThrottlePolicy) -> (LoginThrottleService, Arc<FakeAuditLog>) {
+    let audit = Arc::new(FakeAuditLog::default());
+    let service = LoginThrottleService::new(
+        Arc::new(FakeLoginThrottles::default()),
+        audit.clone(),
+        policy,
+    );
+    (service, audit)
+}
+
+fn actions(audit: &FakeAuditLog) -> Vec<(AuditAction, String)> {
+    audit
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|e| (e.action, e.subject.clone()))
+        .collect()
+}
+
+#[actix_rt::test]
+async fn locks_account_after_max_failures_and_audits_it() {
+    let (service, audit) = setup(lockout_policy());
+
+    for _ in 0..3 {
+        service
+            .check("Alice@Example.com", IP)
+            .await
+            .expect("allowed");
+        service
+            .record_failure("alice@example.com", IP)
+            .await
+            .unwrap();
+    }
+
+    // Same key regardless of case; the lockout lasts the configured 15 minutes
+    match service.check("alice@example.com", "198.51.100.1").await {
+        Err(AuthError::TooManyLoginAttempts(secs)) => assert!(secs > 800 && secs <= 900),
+        other => panic!("expected lockout, got {:?}", other),
+    }
+    assert_eq!(
+        actions(&audit),
+        vec![(
+            AuditAction::LoginLocked,
+            "account:alice@example.com".to_string()
+        )]
+    );
+    assert_eq!(audit.events.lock().unwrap()[0].ip.as_deref(), Some(IP));
+
+    // Other accounts from another address are unaffected
+    assert!(service.check("bob", "198.51.100.1").await.is_ok());
+}
+
+#[actix_rt::test]
+async fn progressive_delay_applies_per_account() {
+    let (service, _) = setup(ThrottlePolicy {
+        base_delay: Duration::seconds(30),
+        ..ThrottlePolicy::default()
+    });
+
+    service.record_failure("carol", IP).await.unwrap();
+    match service.check("carol", IP).await {
+        Err(AuthError::TooManyLoginAttempts(secs)) => assert!(secs > 25 && secs <= 30),
+        other => panic!("expected delay, got {:?}", other),
+    }
+    service.record_failure("carol", IP).await.unwrap();
+    match service.check("carol", "198.51.100.1").await {
+        Err(AuthError::TooManyLoginAttempts(secs)) => assert!(secs > 55 && secs <= 60),
+        other => panic!("expected doubled delay, got {:?}", other),
+    }
+
+    // The address itself is not delayed for other accounts
+    assert!(service.check("dave", IP).await.is_ok());
+
+    // Success clears the account counter
+    service.record_success("carol").await.unwrap();
+    assert!(service.check("carol", IP).await.is_ok());
+}
+
+#[actix_rt::test]
+async fn locks_address_spraying_many_accounts() {
+    let (service, audit) = setup(lockout_policy());
+
+    for i in 0..5 {
+        let account = format!("user{}", i);
+        service.check(&account, IP).await.expect("allowed");
+        service.record_failure(&account, IP).await.unwrap();
+        // A valid login from the same address does not reset its counter
+        service.record_success("mallory").await.unwrap();
+    }
+
+    let blocked = service.check("someone-else", IP).await;
+    assert!(matches!(blocked, Err(AuthError::TooManyLoginAttempts(_))));
+    assert!(service.check("someone-else", "198.51.100.1").await.is_ok());
+    assert!(actions(&audit).contains(&(AuditAction::LoginLocked, format!("ip:{}", IP))));
+}
+
+#[actix_rt::test]
+async fn expired_lockout_is_cleared_and_audited() {
+    let (service, audit) = setup(ThrottlePolicy {
+        lockout: Duration::zero(),
+        ..lockout_policy()
+    });
+
+    for _ in 0..3 {
+        service.record_failure("erin", IP).await.unwrap();
+    }
+    assert!(service.check("erin", IP).await.is_ok());
+    assert_eq!(
+        actions(&audit),
+        vec![
+            (AuditAction::LoginLocked, "account:erin".to_string()),
+            (AuditAction::LoginUnlocked, "account:erin".to_string()),
+        ]
+    );
+
+    // The counter restarted: one more failure does not lock again
+    service.record_failure("erin", IP).await.unwrap();
+    assert_eq!(audit.events.lock().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn admin_unlock_lifts_lockout_and_is_audited() {
+    let (service, audit) = setup(lockout_policy());
+    for _ in 0..3 {
+        service.record_failure("frank", IP).await.unwrap();
+    }
+    assert!(service.check("frank", "198.51.100.1").await.is_err());
+
+    assert!(service
+        .unlock(ThrottleKind::Account, "Frank", "admin")
+        .await
+        .unwrap());
+    assert!(service.check("frank", "198.51.100.1").await.is_ok());
+    assert!(!service
+        .unlock(ThrottleKind::Account, "frank", "admin")
+        .await
+        .unwrap());
+
+    let events = audit.events.lock().unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.action, AuditAction::LoginUnlocked);
+    assert_eq!(last.detail.as_deref(), Some("lifted by admin"));
+}
+
+#[actix_rt::test]
+async fn email_and_username_share_the_account_budget() {
+    use chasqui_server::models::entities::user::User;
+
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let (service, _) = setup(lockout_policy());
+    let alice = User::new(
+        "alice".to_string(),
+        "alice@example.com".to_string(),
+        "$ecret123".to_string(),
+    )
+    .expect("user");
+    let users = fakes::FakeUsers::with(vec![alice.clone()]);
+
+    let by_email = LoginThrottleService::resolve_account(&users, "alice@example.com").await;
+    let by_name = LoginThrottleService::resolve_account(&users, "alice").await;
+    assert_eq!(by_email, format!("user:{}", alice.id_string().unwrap()));
+    assert_eq!(by_email, by_name);
+    assert_eq!(
+        LoginThrottleService::resolve_account(&users, "nobody").await,
+        "nobody"
+    );
+
+    // Alternating identifiers does not earn a second budget
+    for account in [&by_email, &by_name, &by_email] {
+        service.check(account, IP).await.expect("allowed");
+        service.record_failure(account, IP).await.unwrap();
+    }
+    assert!(matches!(
+        service.check(&by_name, "198.51.100.1").await,
+        Err(AuthError::TooManyLoginAttempts(_))
+    ));
+}
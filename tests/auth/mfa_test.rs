@@ -0,0 +1,176 @@
+//! TOTP Two-Factor Tests Module
+//! Exercises enrollment, the two-step login, recovery codes and lockout.
+
+use chasqui_server::application::services::mfa_service::{MfaService, RECOVERY_CODE_COUNT};
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::jwt::{validate_mfa_token, validate_token};
+use chasqui_server::infrastructure::auth::totp::{code_at_step, step_at};
+use chasqui_server::models::entities::user::User;
+use chrono::Utc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeUsers;
+
+fn setup() -> (MfaService, FakeUsers, String) {
+    std::env::set_var("SECRET_KEY", "test_secret_key");
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let user = User::new(
+        "carol".to_string(),
+        "carol@example.com".to_string(),
+        "$ecret123".to_string(),
+    )
+    .expect("user");
+    let user_id = user.id_string().unwrap();
+    (
+        MfaService::new("Chasqui".to_string(), 300, 3),
+        FakeUsers::with(vec![user]),
+        user_id,
+    )
+}
+
+fn code(secret: &str, offset: u64) -> String {
+    code_at_step(secret, step_at(Utc::now().timestamp() as u64) + offset).unwrap()
+}
+
+/// Enrolls and confirms 2FA; returns the secret and the recovery codes.
+async fn enable(service: &MfaService, users: &FakeUsers, user_id: &str) -> (String, Vec<String>) {
+    let enrollment = service
+        .start_enrollment(users, &users.get(user_id).unwrap())
+        .await
+        .expect("enroll");
+    assert!(enrollment
+        .otpauth_uri
+        .starts_with("otpauth://totp/Chasqui:carol@example.com?secret="));
+    let user = users.get(user_id).unwrap();
+    assert!(!user.is_mfa_enabled());
+
+    let recovery = service
+        .confirm_enrollment(users, &user, &code(&enrollment.secret, 0))
+        .await
+        .expect("confirm");
+    assert_eq!(recovery.len(), RECOVERY_CODE_COUNT);
+    (enrollment.secret, recovery)
+}
+
+#[actix_rt::test]
+async fn confirmation_requires_valid_code() {
+    let (service, users, user_id) = setup();
+    service
+        .start_enrollment(&users, &users.get(&user_id).unwrap())
+        .await
+        .expect("enroll");
+
+    let result = service
+        .confirm_enrollment(&users, &users.get(&user_id).unwrap(), "000000x")
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidMfaCode)));
+    assert!(!users.get(&user_id).unwrap().is_mfa_enabled());
+}
+
+#[actix_rt::test]
+async fn two_step_login_with_totp_code() {
+    let (service, users, user_id) = setup();
+    let (secret, recovery) = enable(&service, &users, &user_id).await;
+    let user = users.get(&user_id).unwrap();
+    assert!(user.is_mfa_enabled());
+    let stored = &user.totp.as_ref().unwrap().recovery_codes;
+    assert!(!stored.contains(&recovery[0]), "only hashes are stored");
+
+    let mfa_token = service.issue_mfa_token(&user).expect("mfa token");
+    assert!(validate_token(&mfa_token).is_err(), "not an access token");
+    assert_eq!(validate_mfa_token(&mfa_token).unwrap().sub, user_id);
+
+    // The confirmation code cannot be replayed
+    let replay = service
+        .complete_login(&users, &mfa_token, &code(&secret, 0))
+        .await;
+    assert!(matches!(replay, Err(AuthError::InvalidMfaCode)));
+
+    let token = service
+        .complete_login(&users, &mfa_token, &code(&secret, 1))
+        .await
+        .expect("login");
+    assert_eq!(validate_token(&token).unwrap().sub, user_id);
+}
+
+#[actix_rt::test]
+async fn recovery_codes_are_single_use() {
+    let (service, users, user_id) = setup();
+    let (_, recovery) = enable(&service, &users, &user_id).await;
+    let mfa_token = service
+        .issue_mfa_token(&users.get(&user_id).unwrap())
+        .unwrap();
+
+    let upper = recovery[0].to_uppercase();
+    service
+        .complete_login(&users, &mfa_token, &upper)
+        .await
+        .expect("recovery login");
+    let again = service
+        .complete_login(&users, &mfa_token, &recovery[0])
+        .await;
+    assert!(matches!(again, Err(AuthError::InvalidMfaCode)));
+
+    service
+        .disable(&users, &users.get(&user_id).unwrap(), &recovery[1])
+        .await
+        .expect("disable");
+    assert!(users.get(&user_id).unwrap().totp.is_none());
+}
+
+#[actix_rt::test]
+async fn repeated_failures_revoke_the_mfa_token() {
+    let (service, users, user_id) = setup();
+    let (secret, _) = enable(&service, &users, &user_id).await;
+    let mfa_token = service
+        .issue_mfa_token(&users.get(&user_id).unwrap())
+        .unwrap();
+
+    for _ in 0..3 {
+        let result = service
+            .complete_login(&users, &mfa_token, "wrong-code")
+            .await;
+        assert!(matches!(result, Err(AuthError::InvalidMfaCode)));
+    }
+    assert_eq!(users.get(&user_id).unwrap().token_version, 1);
+
+    let result = service
+        .complete_login(&users, &mfa_token, &code(&secret, 1))
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidOrExpiredToken)));
+}
+
+#[actix_rt::test]
+async fn attempts_over_the_limit_are_refused_before_checking_the_code() {
+    let (service, users, user_id) = setup();
+    let (secret, _) = enable(&service, &users, &user_id).await;
+    let mfa_token = service
+        .issue_mfa_token(&users.get(&user_id).unwrap())
+        .unwrap();
+
+    let result = service
+        .complete_login(&users, &mfa_token, "wrong-code")
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidMfaCode)));
+    let totp = users.get(&user_id).unwrap().totp.unwrap();
+    assert_eq!(totp.failed_attempts, 1);
+
+    // Parallel guesses have used up the budget: even a correct code is refused
+    let mut exhausted = totp.clone();
+    exhausted.failed_attempts = 3;
+    users.modify(&user_id, |u| u.totp = Some(exhausted));
+    let result = service
+        .complete_login(&users, &mfa_token, &code(&secret, 1))
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidMfaCode)));
+
+    // A correct code within the budget resets the counter
+    users.modify(&user_id, |u| u.totp = Some(totp));
+    service
+        .complete_login(&users, &mfa_token, &code(&secret, 1))
+        .await
+        .expect("login");
+    let totp = users.get(&user_id).unwrap().totp.unwrap();
+    assert_eq!(totp.failed_attempts, 0);
+}
@@ -0,0 +1,310 @@
+//! External Login Tests Module
+//! Runs the authorization-code + PKCE flow against a local mock OIDC issuer
+//! (discovery, JWKS and token endpoint served by an in-process actix server),
+//! including callbacks that do not come from the browser that started the login.
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use chasqui_server::application::services::oidc_service::OidcService;
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::oidc::{
+    pkce_challenge, OidcProviderConfig, ProviderKind,
+};
+use chasqui_server::models::entities::user::User;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeOidcStates, FakeUsers};
+
+const CLIENT_ID: &str = "chasqui-test";
+const ISSUER_KEY: &[u8] = include_bytes!("../fixtures/oidc/issuer_rsa.pem");
+const ISSUER_JWKS: &str = include_str!("../fixtures/oidc/issuer_jwks.json");
+
+/// What the mock "user" approved at the authorization endpoint
+#[derive(Clone)]
+struct Grant {
+    code_challenge: String,
+    nonce: String,
+    subject: String,
+    email: String,
+    email_verified: bool,
+}
+
+#[derive(Default)]
+struct MockIssuer {
+    base: String,
+    grants: Mutex<HashMap<String, Grant>>,
+    audience_override: Mutex<Option<String>>,
+}
+
+async fn discovery(issuer: web::Data<MockIssuer>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "issuer": issuer.base,
+        "authorization_endpoint": format!("{}/authorize", issuer.base),
+        "token_endpoint": format!("{}/token", issuer.base),
+        "jwks_uri": format!("{}/jwks", issuer.base),
+    }))
+}
+
+async fn jwks() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(ISSUER_JWKS)
+}
+
+async fn token(
+    issuer: web::Data<MockIssuer>,
+    form: web::Form<HashMap<String, String>>,
+) -> HttpResponse {
+    let grant = match issuer.grants.lock().unwrap().remove(&form["code"]) {
+        Some(grant) => grant,
+        None => return HttpResponse::BadRequest().json(json!({"error": "invalid_grant"})),
+    };
+    if pkce_challenge(&form["code_verifier"]) != grant.code_challenge {
+        return HttpResponse::BadRequest()
+            .json(json!({"error": "invalid_grant", "error_description": "PKCE"}));
+    }
+
+    let aud = issuer
+        .audience_override
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| CLIENT_ID.to_string());
+    let now = Utc::now().timestamp();
+    let claims = json!({
+        "iss": issuer.base,
+        "aud": aud,
+        "sub": grant.subject,
+        "email": grant.email,
+        "email_verified": grant.email_verified,
+        "nonce": grant.nonce,
+        "iat": now,
+        "exp": now + 300,
+    });
+    let mut header = Header::new(jsonwebtoken::Algorithm::RS256);
+    header.kid = Some("mock-1".to_string());
+    let id_token = encode(
+        &header,
+        &claims,
+        &EncodingKey::from_rsa_pem(ISSUER_KEY).unwrap(),
+    )
+    .unwrap();
+    HttpResponse::Ok()
+        .json(json!({"access_token": "opaque", "token_type": "Bearer", "id_token": id_token}))
+}
+
+/// Starts the mock issuer and a service configured with provider `mock`.
+fn setup() -> (Arc<MockIssuer>, OidcService) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let issuer = Arc::new(MockIssuer {
+        base: base.clone(),
+        ..Default::default()
+    });
+
+    let data = web::Data::from(issuer.clone());
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route(
+                "/.well-known/openid-configuration",
+                web::get().to(discovery),
+            )
+            .route("/jwks", web::get().to(jwks))
+            .route("/token", web::post().to(token))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_rt::spawn(server);
+
+    let config = OidcProviderConfig {
+        name: "mock".to_string(),
+        kind: ProviderKind::Oidc,
+        issuer: base,
+        client_id: CLIENT_ID.to_string(),
+        client_secret: Some("secret".to_string()),
+        redirect_uri: "http://localhost:8080/api/auth/oidc/mock/callback".to_string(),
+        scopes: "openid email profile".to_string(),
+        authorization_endpoint: None,
+        token_endpoint: None,
+        api_url: None,
+    };
+    let service = OidcService::new(
+        vec![config],
+        Arc::new(FakeOidcStates::default()),
+        Duration::minutes(10),
+    );
+    (issuer, service)
+}
+
+/// Starts a login and lets the mock issuer approve it; returns `(code, state)`.
+async fn authorize(
+    service: &OidcService,
+    issuer: &MockIssuer,
+    subject: &str,
+    email: &str,
+    email_verified: bool,
+) -> (String, String) {
+    let authorization = service
+        .start_login("mock")
+        .await
+        .expect("authorization url");
+    let url = reqwest::Url::parse(&authorization.url).unwrap();
+    assert!(url
+        .as_str()
+        .starts_with(&format!("{}/authorize?", issuer.base)));
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(params["client_id"], CLIENT_ID);
+
+    assert_eq!(params["state"], authorization.state);
+    let code = format!("code-{}", params["state"]);
+    issuer.grants.lock().unwrap().insert(
+        code.clone(),
+        Grant {
+            code_challenge: params["code_challenge"].clone(),
+            nonce: params["nonce"].clone(),
+            subject: subject.to_string(),
+            email: email.to_string(),
+            email_verified,
+        },
+    );
+    (code, params["state"].clone())
+}
+
+#[actix_rt::test]
+async fn creates_user_then_logs_in_by_identity() {
+    let (issuer, service) = setup();
+    let users = FakeUsers::default();
+
+    let (code, state) = authorize(&service, &issuer, "sub-1", "dave@example.com", true).await;
+    let created = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await
+        .expect("login");
+    assert!(created.password.is_none());
+    assert!(created.email_verified);
+    assert!(created.has_identity("mock", "sub-1"));
+
+    // The state is single use
+    let replay = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await;
+    assert!(matches!(replay, Err(AuthError::InvalidOrExpiredToken)));
+
+    let (code, state) = authorize(&service, &issuer, "sub-1", "dave@example.com", true).await;
+    let again = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await
+        .expect("second login");
+    assert_eq!(again.id, created.id);
+    assert_eq!(users.users.lock().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn links_existing_account_by_verified_email() {
+    let (issuer, service) = setup();
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let mut existing = User::new(
+        "erin".to_string(),
+        "erin@example.com".to_string(),
+        "$ecret123".to_string(),
+    )
+    .unwrap();
+    existing.email_verified = true;
+    let users = FakeUsers::with(vec![existing.clone()]);
+
+    let (code, state) = authorize(&service, &issuer, "sub-2", "erin@example.com", true).await;
+    let linked = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await
+        .expect("login");
+    assert_eq!(linked.id, existing.id);
+    assert!(linked.has_identity("mock", "sub-2"));
+}
+
+#[actix_rt::test]
+async fn refuses_to_link_unverified_local_account() {
+    let (issuer, service) = setup();
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let existing = User::new(
+        "frank".to_string(),
+        "frank@example.com".to_string(),
+        "$ecret123".to_string(),
+    )
+    .unwrap();
+    let users = FakeUsers::with(vec![existing]);
+
+    let (code, state) = authorize(&service, &issuer, "sub-3", "frank@example.com", true).await;
+    let result = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await;
+    assert!(matches!(result, Err(AuthError::AccountLinkConflict)));
+
+    let (code, state) = authorize(&service, &issuer, "sub-4", "gina@example.com", false).await;
+    let result = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await;
+    assert!(matches!(result, Err(AuthError::EmailNotVerified)));
+}
+
+#[actix_rt::test]
+async fn rejects_bad_id_token_and_pkce_mismatch() {
+    let (issuer, service) = setup();
+    let users = FakeUsers::default();
+
+    *issuer.audience_override.lock().unwrap() = Some("someone-else".to_string());
+    let (code, state) = authorize(&service, &issuer, "sub-5", "hal@example.com", true).await;
+    let result = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidIdToken)));
+    *issuer.audience_override.lock().unwrap() = None;
+
+    let (code, state) = authorize(&service, &issuer, "sub-5", "hal@example.com", true).await;
+    issuer
+        .grants
+        .lock()
+        .unwrap()
+        .get_mut(&code)
+        .unwrap()
+        .code_challenge = "tampered".to_string();
+    let result = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await;
+    assert!(matches!(result, Err(AuthError::IdentityProviderError(_))));
+
+    let unknown = service.start_login("nope").await;
+    assert!(matches!(unknown, Err(AuthError::UnknownProvider)));
+}
+
+#[actix_rt::test]
+async fn rejects_a_callback_from_another_browser() {
+    let (issuer, service) = setup();
+    let users = FakeUsers::default();
+
+    let (code, state) = authorize(&service, &issuer, "sub-6", "ivy@example.com", true).await;
+    let (_, other_state) = authorize(&service, &issuer, "sub-7", "jo@example.com", true).await;
+    for browser_state in [None, Some(other_state.as_str())] {
+        let result = service
+            .complete_login(&users, "mock", &code, &state, browser_state)
+            .await;
+        assert!(matches!(result, Err(AuthError::InvalidOrExpiredToken)));
+    }
+    assert!(users.users.lock().unwrap().is_empty());
+
+    // The forged callbacks did not spend the state of the real browser
+    let user = service
+        .complete_login(&users, "mock", &code, &state, Some(&state))
+        .await
+        .expect("login");
+    assert!(user.has_identity("mock", "sub-6"));
+}
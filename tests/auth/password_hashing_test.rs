@@ -0,0 +1,93 @@
+//! Password Hashing Tests Module
+//! Tests Argon2id hashing, the transparent upgrade of legacy bcrypt hashes and
+//! the password policy used by registration and reset.
+
+use chasqui_server::application::services::password_policy::PasswordPolicy;
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::password::{
+    hash_password, needs_rehash, verify_password,
+};
+use chasqui_server::models::entities::user::User;
+use chasqui_server::models::traits::user_data_trait::UserDataTrait;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeUsers;
+
+#[test]
+fn new_hashes_are_argon2id_phc_strings() {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let hash = hash_password("Super$ecret123").unwrap();
+    assert!(hash.starts_with("$argon2id$v=19$m=1024,t=2,p=1$"));
+    assert!(verify_password("Super$ecret123", &hash));
+    assert!(!verify_password("wrong", &hash));
+    assert!(!needs_rehash(&hash));
+
+    // Salted: the same password hashes differently
+    assert_ne!(hash, hash_password("Super$ecret123").unwrap());
+}
+
+#[actix_rt::test]
+async fn legacy_bcrypt_hash_is_upgraded_without_revoking_sessions() {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let mut user = User::new(
+        "legacy".to_string(),
+        "legacy@example.com".to_string(),
+        "ignored1".to_string(),
+    )
+    .unwrap();
+    let bcrypt_hash = bcrypt::hash("Old$ecret1", 4).unwrap();
+    user.password = Some(bcrypt_hash.clone());
+    let user_id = user.id_string().unwrap();
+    let users = FakeUsers::with(vec![user]);
+
+    assert!(verify_password("Old$ecret1", &bcrypt_hash));
+    assert!(needs_rehash(&bcrypt_hash));
+
+    let upgraded = hash_password("Old$ecret1").unwrap();
+    let updated = users
+        .upgrade_password_hash(&user_id, &bcrypt_hash, &upgraded)
+        .await
+        .expect("upgraded");
+    let stored = updated.password.as_deref().unwrap();
+    assert!(stored.starts_with("$argon2id$"));
+    assert!(verify_password("Old$ecret1", stored));
+    assert_eq!(updated.token_version, 0);
+
+    // A concurrent change wins: the stale hash no longer matches
+    assert!(users
+        .upgrade_password_hash(&user_id, &bcrypt_hash, &upgraded)
+        .await
+        .is_none());
+}
+
+#[test]
+fn policy_enforces_length_limits() {
+    let policy = PasswordPolicy::new(10, 20);
+    assert!(matches!(
+        policy.check("short"),
+        Err(AuthError::WeakPassword(_))
+    ));
+    assert!(matches!(
+        policy.check(&"x".repeat(21)),
+        Err(AuthError::WeakPassword(_))
+    ));
+    assert!(policy.check("long enough ✓").is_ok());
+}
+
+#[test]
+fn policy_rejects_breached_passwords() {
+    // Plaintext entry plus the SHA-1 of "password1" in HIBP `HASH:count` format
+    let list = "letmein123\n\nE38AD214943DAAD1D64C102FAEC29DE4AFE9DA3D:2413945\n";
+    let policy = PasswordPolicy::default().with_breached_list(list);
+
+    assert!(matches!(
+        policy.check("letmein123"),
+        Err(AuthError::WeakPassword(_))
+    ));
+    assert!(matches!(
+        policy.check("password1"),
+        Err(AuthError::WeakPassword(_))
+    ));
+    assert!(policy.check("c0rrect-horse-battery").is_ok());
+}
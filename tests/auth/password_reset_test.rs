@@ -0,0 +1,127 @@
+//! Password Reset Tests Module
+//! Exercises the forgot/reset flow against in-memory fakes and mailer.
+
+use chasqui_server::application::services::password_policy::PasswordPolicy;
+use chasqui_server::application::services::password_reset_service::PasswordResetService;
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::password::verify_password;
+use chasqui_server::infrastructure::mail::memory::InMemoryMailer;
+use chasqui_server::infrastructure::mail::{MailError, Mailer, OutgoingEmail};
+use chasqui_server::models::entities::user::User;
+use chrono::Duration;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeOneTimeTokens, FakeUsers};
+
+fn setup() -> (PasswordResetService, Arc<InMemoryMailer>, FakeUsers, String) {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    let user = User::new(
+        "alice".to_string(),
+        "alice@example.com".to_string(),
+        "Old$ecret123".to_string(),
+    )
+    .expect("user");
+    let user_id = user.id_string().unwrap();
+    let mailer = Arc::new(InMemoryMailer::new());
+    let service = PasswordResetService::new(
+        Arc::new(FakeOneTimeTokens::default()),
+        mailer.clone(),
+        Arc::new(PasswordPolicy::default()),
+        Duration::minutes(30),
+        "https://app.example".to_string(),
+    );
+    (service, mailer, FakeUsers::with(vec![user]), user_id)
+}
+
+/// Extracts the token from the reset link in the e-mail body
+fn token_from_mail(body: &str) -> String {
+    let start = body.find("token=").expect("link in body") + "token=".len();
+    body[start..].split_whitespace().next().unwrap().to_string()
+}
+
+#[actix_rt::test]
+async fn reset_flow_changes_password_and_revokes_sessions() {
+    let (service, mailer, users, user_id) = setup();
+
+    service
+        .request_reset(&users, "alice@example.com")
+        .await
+        .expect("request should succeed");
+    let mail = mailer.last_to("alice@example.com").expect("mail sent");
+    assert!(mail.body.contains("https://app.example/reset-password?token="));
+    let token = token_from_mail(&mail.body);
+
+    service
+        .reset_password(&users, &token, "N3w$ecret!")
+        .await
+        .expect("reset should succeed");
+
+    let updated = users.get(&user_id).unwrap();
+    assert!(verify_password("N3w$ecret!", updated.password.as_deref().unwrap()));
+    assert_eq!(updated.token_version, 1);
+
+    // Single use
+    let again = service.reset_password(&users, &token, "An0ther$ecret").await;
+    assert!(matches!(again, Err(AuthError::InvalidOrExpiredToken)));
+}
+
+/// Mailer whose transport is always down
+struct DownMailer;
+
+#[async_trait::async_trait]
+impl Mailer for DownMailer {
+    async fn send(&self, _email: OutgoingEmail) -> Result<(), MailError> {
+        Err(MailError::Delivery("connection refused".to_string()))
+    }
+}
+
+#[actix_rt::test]
+async fn mail_failure_answers_like_an_unknown_email() {
+    let (_, _, users, _) = setup();
+    let service = PasswordResetService::new(
+        Arc::new(FakeOneTimeTokens::default()),
+        Arc::new(DownMailer),
+        Arc::new(PasswordPolicy::default()),
+        Duration::minutes(30),
+        "https://app.example".to_string(),
+    );
+
+    assert!(service.request_reset(&users, "alice@example.com").await.is_ok());
+    assert!(service.request_reset(&users, "nobody@example.com").await.is_ok());
+}
+
+#[actix_rt::test]
+async fn unknown_email_sends_nothing() {
+    let (service, mailer, users, _) = setup();
+
+    service
+        .request_reset(&users, "nobody@example.com")
+        .await
+        .expect("unknown email is not an error");
+    assert!(mailer.sent().is_empty());
+}
+
+#[actix_rt::test]
+async fn new_request_invalidates_previous_token() {
+    let (service, mailer, users, _) = setup();
+
+    service.request_reset(&users, "alice@example.com").await.unwrap();
+    let first = token_from_mail(&mailer.last_to("alice@example.com").unwrap().body);
+    service.request_reset(&users, "alice@example.com").await.unwrap();
+
+    let result = service.reset_password(&users, &first, "N3w$ecret!").await;
+    assert!(matches!(result, Err(AuthError::InvalidOrExpiredToken)));
+}
+
+#[actix_rt::test]
+async fn weak_password_is_rejected() {
+    let (service, mailer, users, _) = setup();
+
+    service.request_reset(&users, "alice@example.com").await.unwrap();
+    let token = token_from_mail(&mailer.last_to("alice@example.com").unwrap().body);
+
+    let result = service.reset_password(&users, &token, "short").await;
+    assert!(matches!(result, Err(AuthError::WeakPassword(_))));
+}
@@ -0,0 +1,132 @@
+//! Profile Tests Module
+//! Exercises profile updates and the password / e-mail change flows against
+//! in-memory fakes.
+
+use chasqui_server::application::services::password_policy::PasswordPolicy;
+use chasqui_server::application::services::profile_service::ProfileService;
+use chasqui_server::error::AuthError;
+use chasqui_server::infrastructure::auth::password::verify_password;
+use chasqui_server::models::entities::profile::UserProfile;
+use chasqui_server::models::entities::user::User;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeUsers;
+
+fn user(username: &str, email: &str) -> User {
+    std::env::set_var("ARGON2_MEMORY_KIB", "1024");
+    User::new(
+        username.to_string(),
+        email.to_string(),
+        "Old$ecret123".to_string(),
+    )
+    .expect("user")
+}
+
+fn setup() -> (ProfileService, FakeUsers, User) {
+    let alice = user("alice", "alice@example.com");
+    let bob = user("bob", "bob@example.com");
+    let users = FakeUsers::with(vec![alice.clone(), bob]);
+    (
+        ProfileService::new(Arc::new(PasswordPolicy::default())),
+        users,
+        alice,
+    )
+}
+
+#[actix_rt::test]
+async fn profile_patch_merges_and_clears_fields() {
+    let (service, users, alice) = setup();
+
+    let updated = service
+        .update_profile(
+            &users,
+            &alice,
+            UserProfile {
+                display_name: Some("Alice".to_string()),
+                timezone: Some("America/Lima".to_string()),
+                ..UserProfile::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.profile.display_name.as_deref(), Some("Alice"));
+
+    let cleared = service
+        .update_profile(
+            &users,
+            &updated,
+            UserProfile {
+                display_name: Some(String::new()),
+                ..UserProfile::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(cleared.profile.display_name, None);
+    assert_eq!(cleared.profile.timezone.as_deref(), Some("America/Lima"));
+
+    let stored = users.get(&alice.id_string().unwrap()).unwrap();
+    assert_eq!(stored.profile, cleared.profile);
+}
+
+#[actix_rt::test]
+async fn password_change_requires_current_password_and_revokes_tokens() {
+    let (service, users, alice) = setup();
+
+    assert!(matches!(
+        service
+            .change_password(&users, &alice, "wrong", "N3w$ecret!456")
+            .await,
+        Err(AuthError::InvalidCurrentPassword)
+    ));
+    assert!(matches!(
+        service
+            .change_password(&users, &alice, "Old$ecret123", "short")
+            .await,
+        Err(AuthError::WeakPassword(_))
+    ));
+
+    let updated = service
+        .change_password(&users, &alice, "Old$ecret123", "N3w$ecret!456")
+        .await
+        .unwrap();
+    assert!(updated.token_version > alice.token_version);
+    assert!(verify_password(
+        "N3w$ecret!456",
+        updated.password.as_deref().unwrap()
+    ));
+}
+
+#[actix_rt::test]
+async fn email_change_checks_password_format_and_uniqueness() {
+    let (service, users, mut alice) = setup();
+    alice.email_verified = true;
+
+    assert!(matches!(
+        service
+            .change_email(&users, &alice, "wrong", "new@example.com")
+            .await,
+        Err(AuthError::InvalidCurrentPassword)
+    ));
+    assert!(matches!(
+        service
+            .change_email(&users, &alice, "Old$ecret123", "not-an-email")
+            .await,
+        Err(AuthError::InvalidEmail)
+    ));
+    assert!(matches!(
+        service
+            .change_email(&users, &alice, "Old$ecret123", "bob@example.com")
+            .await,
+        Err(AuthError::EmailAlreadyInUse)
+    ));
+
+    let updated = service
+        .change_email(&users, &alice, "Old$ecret123", "alice@new.example")
+        .await
+        .unwrap();
+    assert_eq!(updated.email.as_deref(), Some("alice@new.example"));
+    assert!(!updated.email_verified);
+}
@@ -0,0 +1,94 @@
+//! WebSocket Ticket Tests Module
+//! Tests issuing and redeeming single-use WebSocket tickets using in-memory
+//! repositories.
+
+use chasqui_server::application::services::ws_ticket_service::WsTicketService;
+use chasqui_server::error::AuthError;
+use chasqui_server::models::entities::one_time_token::TokenPurpose;
+use chrono::Duration;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeOneTimeTokens;
+
+fn setup(ttl: Duration) -> (WsTicketService, Arc<FakeOneTimeTokens>) {
+    let tokens = Arc::new(FakeOneTimeTokens::default());
+    (WsTicketService::new(tokens.clone(), ttl), tokens)
+}
+
+#[actix_rt::test]
+async fn ticket_is_bound_to_user_and_single_use() {
+    let (service, tokens) = setup(Duration::seconds(30));
+    let alice = Thing::from(("user", "alice"));
+
+    let issued = service.issue(alice.clone()).await.unwrap();
+    assert_eq!(issued.expires_in, 30);
+
+    // Only the digest is stored
+    let stored = tokens.tokens.lock().unwrap()[0].clone();
+    assert_eq!(stored.purpose, TokenPurpose::WsTicket);
+    assert_ne!(stored.token_hash, issued.ticket);
+
+    assert_eq!(service.redeem(&issued.ticket).await.unwrap(), alice);
+    assert!(matches!(
+        service.redeem(&issued.ticket).await,
+        Err(AuthError::InvalidOrExpiredToken)
+    ));
+}
+
+#[actix_rt::test]
+async fn expired_or_unknown_ticket_is_rejected() {
+    let (service, _) = setup(Duration::seconds(-1));
+    let issued = service.issue(Thing::from(("user", "bob"))).await.unwrap();
+
+    assert!(matches!(
+        service.redeem(&issued.ticket).await,
+        Err(AuthError::InvalidOrExpiredToken)
+    ));
+    assert!(matches!(
+        service.redeem("not-a-ticket").await,
+        Err(AuthError::InvalidOrExpiredToken)
+    ));
+}
+
+#[actix_rt::test]
+async fn issuing_purges_expired_tickets() {
+    let (expired, tokens) = setup(Duration::seconds(-1));
+    expired.issue(Thing::from(("user", "bob"))).await.unwrap();
+    assert_eq!(tokens.tokens.lock().unwrap().len(), 1);
+
+    let service = WsTicketService::new(tokens.clone(), Duration::seconds(30));
+    service.issue(Thing::from(("user", "bob"))).await.unwrap();
+    assert_eq!(tokens.tokens.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn handshake_refuses_unavailable_accounts_and_revoked_tokens() {
+    use actix_web::http::StatusCode;
+    use chasqui_server::interfaces::api::auth::check_account;
+    use chasqui_server::models::entities::user::User;
+
+    let mut user = User::new_bot("alice".to_string());
+    user.token_version = 2;
+    assert!(check_account(&user, None).is_ok());
+    assert!(check_account(&user, Some(2)).is_ok());
+
+    let stale = check_account(&user, Some(1)).unwrap_err();
+    assert_eq!(
+        stale.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Tickets and API keys carry no version, but the account state still applies
+    user.blocked = true;
+    let blocked = check_account(&user, None).unwrap_err();
+    assert_eq!(
+        blocked.as_response_error().status_code(),
+        StatusCode::FORBIDDEN
+    );
+    user.blocked = false;
+    user.deactivated_at = Some(chrono::Utc::now());
+    assert!(check_account(&user, None).is_err());
+}
@@ -0,0 +1,512 @@
+//! Board Service Tests Module
+//! Exercises projects and Kanban boards against in-memory fakes: card ordering,
+//! status-mapped columns that follow task changes, version conflicts and
+//! member-only visibility.
+
+use chasqui_server::application::services::board_service::BoardService;
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::{BoardError, TaskError};
+use chasqui_server::models::entities::board::{
+    AddCardRequest, AddProjectMemberRequest, Board, BoardEventKind, CardPlacement, ColumnSpec,
+    CreateBoardRequest, CreateProjectRequest, SetColumnsRequest,
+};
+use chasqui_server::models::entities::task::{AddTaskRequest, Task, TaskStatus};
+use chasqui_server::models::entities::user::User;
+use chasqui_server::models::traits::task_data_trait::TaskDataTrait;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeBoardEvents, FakeBoards, FakeConversations, FakeTasks, FakeUsers};
+
+const OWN: TaskAccess = TaskAccess {
+    read_all: false,
+    update_all: false,
+    delete_all: false,
+};
+
+struct Setup {
+    service: BoardService,
+    tasks_service: Arc<TaskService>,
+    boards: Arc<FakeBoards>,
+    events: Arc<FakeBoardEvents>,
+    tasks: FakeTasks,
+    users: FakeUsers,
+    alice: User,
+    bob: User,
+    carol: User,
+    board: Board,
+}
+
+// alice owns a project with bob as member and one default board; carol is an outsider
+async fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let carol = User::new_bot("carol".to_string());
+    let users = FakeUsers::with(vec![alice.clone(), bob.clone(), carol.clone()]);
+    let tasks_service = Arc::new(TaskService::new(
+        Arc::new(FakeConversations::default()),
+        20,
+        100,
+    ));
+    let boards = Arc::new(FakeBoards::default());
+    let events = Arc::new(FakeBoardEvents::default());
+    let service =
+        BoardService::new(boards.clone(), tasks_service.clone()).with_events(events.clone());
+
+    let project = service
+        .create_project(
+            &users,
+            &alice,
+            CreateProjectRequest {
+                name: "Launch".to_string(),
+                members: vec![bob.id_string().unwrap()],
+            },
+        )
+        .await
+        .unwrap();
+    let board = service
+        .create_board(
+            &alice,
+            &project.uuid,
+            CreateBoardRequest {
+                name: "Sprint 1".to_string(),
+                columns: Vec::new(),
+            },
+        )
+        .await
+        .unwrap();
+    Setup {
+        service,
+        tasks_service,
+        boards,
+        events,
+        tasks: FakeTasks::default(),
+        users,
+        alice,
+        bob,
+        carol,
+        board,
+    }
+}
+
+impl Setup {
+    async fn task(&self, name: &str) -> Task {
+        self.tasks_service
+            .create(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                AddTaskRequest {
+                    task_name: name.to_string(),
+                    ..AddTaskRequest::default()
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn add(&self, task: &Task) -> String {
+        self.service
+            .add_card(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                &self.board.uuid,
+                AddCardRequest {
+                    task_id: task.uuid.clone(),
+                    placement: CardPlacement::default(),
+                },
+            )
+            .await
+            .unwrap()
+            .card
+            .id
+    }
+
+    async fn move_card(&self, card_id: &str, placement: CardPlacement) -> Result<u64, BoardError> {
+        self.service
+            .move_card(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                &self.board.uuid,
+                card_id,
+                placement,
+            )
+            .await
+            .map(|update| update.version)
+    }
+
+    // Task names per column, in display order
+    async fn names(&self) -> Vec<Vec<String>> {
+        let view = self
+            .service
+            .get_board(&self.tasks, &self.alice, &self.board.uuid)
+            .await
+            .unwrap();
+        view.columns
+            .iter()
+            .map(|c| {
+                c.cards
+                    .iter()
+                    .map(|card| card.task.task_name.clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn column(&self, index: usize) -> String {
+        self.board.columns[index].id.clone()
+    }
+}
+
+#[actix_rt::test]
+async fn cards_keep_their_order_across_moves() {
+    let s = setup().await;
+    let statuses: Vec<TaskStatus> = s.board.columns.iter().map(|c| c.status).collect();
+    assert_eq!(
+        statuses,
+        vec![TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done]
+    );
+
+    let a = s.add(&s.task("A").await).await;
+    let b = s.add(&s.task("B").await).await;
+    let c = s.add(&s.task("C").await).await;
+    assert_eq!(s.names().await[0], vec!["A", "B", "C"]);
+
+    s.move_card(
+        &c,
+        CardPlacement {
+            before: Some(a.clone()),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+    s.move_card(
+        &a,
+        CardPlacement {
+            after: Some(c.clone()),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(s.names().await[0], vec!["C", "A", "B"]);
+
+    // A neighbour from another column is not found in the target column
+    let wrong = s
+        .move_card(
+            &b,
+            CardPlacement {
+                column_id: Some(s.column(1)),
+                after: Some(a.clone()),
+                ..CardPlacement::default()
+            },
+        )
+        .await;
+    assert!(matches!(wrong, Err(BoardError::CardNotFound)));
+
+    let kinds: Vec<BoardEventKind> = s
+        .events
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|e| e.kind)
+        .collect();
+    assert_eq!(kinds.len(), 5);
+    assert_eq!(kinds[4], BoardEventKind::CardMoved);
+    let audience = &s.events.events.lock().unwrap()[0].audience;
+    assert!(audience.contains(&s.bob.id_string().unwrap()));
+}
+
+#[actix_rt::test]
+async fn moving_across_columns_sets_the_task_status() {
+    let s = setup().await;
+    let release = s.task("Release").await;
+    let tests = s.task("Tests").await;
+    let release_card = s.add(&release).await;
+
+    s.move_card(
+        &release_card,
+        CardPlacement {
+            column_id: Some(s.column(1)),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(s.names().await[1], vec!["Release"]);
+    let stored = |uuid: &str| {
+        s.tasks
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.uuid == uuid)
+            .unwrap()
+            .status
+    };
+    assert_eq!(stored(&release.uuid), TaskStatus::InProgress);
+
+    // Task rules still apply: an open blocker keeps the card out of "Done"
+    s.tasks_service
+        .add_dependency(&s.tasks, &s.alice, OWN, &release.uuid, &tests.uuid)
+        .await
+        .unwrap();
+    let blocked = s
+        .move_card(
+            &release_card,
+            CardPlacement {
+                column_id: Some(s.column(2)),
+                ..CardPlacement::default()
+            },
+        )
+        .await;
+    assert!(matches!(
+        blocked,
+        Err(BoardError::Task(TaskError::BlockedBy(_)))
+    ));
+    assert_eq!(s.names().await[1], vec!["Release"]);
+
+    // ...and a card added straight into "Done" is taken off again
+    let deploy = s.task("Deploy").await;
+    s.tasks_service
+        .add_dependency(&s.tasks, &s.alice, OWN, &deploy.uuid, &tests.uuid)
+        .await
+        .unwrap();
+    let blocked = s
+        .service
+        .add_card(
+            &s.tasks,
+            &s.users,
+            &s.alice,
+            OWN,
+            &s.board.uuid,
+            AddCardRequest {
+                task_id: deploy.uuid.clone(),
+                placement: CardPlacement {
+                    column_id: Some(s.column(2)),
+                    ..CardPlacement::default()
+                },
+            },
+        )
+        .await;
+    assert!(matches!(
+        blocked,
+        Err(BoardError::Task(TaskError::BlockedBy(_)))
+    ));
+    assert!(!s.names().await.concat().contains(&"Deploy".to_string()));
+
+    // A task added to a board lands in the column of its status
+    let done = s.task("Done already").await;
+    s.tasks_service
+        .update(
+            &s.tasks,
+            &s.users,
+            &s.alice,
+            OWN,
+            &done.uuid,
+            serde_json::from_str(r#"{"status": "done"}"#).unwrap(),
+        )
+        .await
+        .unwrap();
+    s.add(&done).await;
+    assert_eq!(s.names().await[2], vec!["Done already"]);
+}
+
+#[actix_rt::test]
+async fn cards_follow_task_status_changes_made_outside_the_board() {
+    let s = setup().await;
+    let review = s.task("Review").await;
+    let review_card = s.add(&review).await;
+    let wip_card = s.add(&s.task("WIP").await).await;
+    s.move_card(
+        &wip_card,
+        CardPlacement {
+            column_id: Some(s.column(1)),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    s.tasks_service
+        .update(
+            &s.tasks,
+            &s.users,
+            &s.alice,
+            OWN,
+            &review.uuid,
+            serde_json::from_str(r#"{"status": "in_progress"}"#).unwrap(),
+        )
+        .await
+        .unwrap();
+    let mut names = s.names().await;
+    assert!(names[0].is_empty());
+    names[1].sort();
+    assert_eq!(names[1], vec!["Review", "WIP"]);
+
+    // Neighbours are those of the column the card is shown in
+    s.move_card(
+        &review_card,
+        CardPlacement {
+            after: Some(wip_card.clone()),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(s.names().await[1], vec!["WIP", "Review"]);
+
+    // Moving the card back to the column it is stored in still sets the status
+    s.move_card(
+        &review_card,
+        CardPlacement {
+            column_id: Some(s.column(0)),
+            ..CardPlacement::default()
+        },
+    )
+    .await
+    .unwrap();
+    let stored = s.tasks.find_task(review.uuid.clone()).await.unwrap();
+    assert_eq!(stored.status, TaskStatus::Todo);
+    assert_eq!(s.names().await[0], vec!["Review"]);
+}
+
+#[actix_rt::test]
+async fn stale_versions_conflict_and_races_are_retried() {
+    let s = setup().await;
+    let a = s.add(&s.task("A").await).await;
+    let b = s.add(&s.task("B").await).await;
+    let version = s.boards.boards.lock().unwrap()[0].version;
+    assert_eq!(version, 2);
+
+    let stale = s
+        .move_card(
+            &a,
+            CardPlacement {
+                after: Some(b.clone()),
+                version: Some(version - 1),
+                ..CardPlacement::default()
+            },
+        )
+        .await;
+    assert!(matches!(stale, Err(BoardError::VersionConflict)));
+
+    // Without a client version a lost race is recomputed on the newer board
+    *s.boards.interleaved.lock().unwrap() = 1;
+    let version = s
+        .move_card(
+            &a,
+            CardPlacement {
+                after: Some(b.clone()),
+                ..CardPlacement::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(version, 4);
+    assert_eq!(s.names().await[0], vec!["B", "A"]);
+
+    // ... but not forever
+    *s.boards.interleaved.lock().unwrap() = 10;
+    let lost = s.move_card(&a, CardPlacement::default()).await;
+    assert!(matches!(lost, Err(BoardError::VersionConflict)));
+}
+
+#[actix_rt::test]
+async fn boards_are_hidden_from_non_members() {
+    let s = setup().await;
+    let hidden = s.service.get_board(&s.tasks, &s.carol, &s.board.uuid).await;
+    assert!(matches!(hidden, Err(BoardError::BoardNotFound)));
+    assert!(s.service.list_projects(&s.carol).await.unwrap().is_empty());
+    assert_eq!(s.service.list_projects(&s.bob).await.unwrap().len(), 1);
+
+    // Members see the board; only the owner adds members
+    s.service
+        .get_board(&s.tasks, &s.bob, &s.board.uuid)
+        .await
+        .unwrap();
+    let project_id = s.board.project_id.clone();
+    let invite = |user: &User| AddProjectMemberRequest {
+        user_id: user.id_string().unwrap(),
+    };
+    let by_member = s
+        .service
+        .add_member(&s.users, &s.bob, &project_id, invite(&s.carol))
+        .await;
+    assert!(matches!(by_member, Err(BoardError::NotAllowed)));
+    let by_outsider = s
+        .service
+        .add_member(&s.users, &s.carol, &project_id, invite(&s.carol))
+        .await;
+    assert!(matches!(by_outsider, Err(BoardError::ProjectNotFound)));
+    s.service
+        .add_member(&s.users, &s.alice, &project_id, invite(&s.carol))
+        .await
+        .unwrap();
+    s.service
+        .get_board(&s.tasks, &s.carol, &s.board.uuid)
+        .await
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn columns_holding_cards_cannot_be_removed() {
+    let s = setup().await;
+    s.add(&s.task("A").await).await;
+    let keep = |index: usize| ColumnSpec {
+        id: Some(s.column(index)),
+        name: s.board.columns[index].name.clone(),
+        status: s.board.columns[index].status,
+    };
+    let set = |columns: Vec<ColumnSpec>| SetColumnsRequest {
+        columns,
+        version: None,
+    };
+
+    let removed = s
+        .service
+        .set_columns(&s.alice, &s.board.uuid, set(vec![keep(1), keep(2)]))
+        .await;
+    assert!(matches!(removed, Err(BoardError::ColumnNotEmpty)));
+
+    let review = ColumnSpec {
+        id: None,
+        name: "Review".to_string(),
+        status: TaskStatus::InProgress,
+    };
+    let board = s
+        .service
+        .set_columns(&s.alice, &s.board.uuid, set(vec![keep(0), review, keep(2)]))
+        .await
+        .unwrap();
+    let names: Vec<&str> = board.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["To do", "Review", "Done"]);
+    assert_eq!(board.columns[0].id, s.column(0));
+
+    let unknown = s
+        .service
+        .set_columns(
+            &s.alice,
+            &s.board.uuid,
+            set(vec![ColumnSpec {
+                id: Some("nope".to_string()),
+                name: "X".to_string(),
+                status: TaskStatus::Todo,
+            }]),
+        )
+        .await;
+    assert!(matches!(unknown, Err(BoardError::ColumnNotFound)));
+    let empty = s
+        .service
+        .set_columns(&s.alice, &s.board.uuid, set(Vec::new()))
+        .await;
+    assert!(matches!(empty, Err(BoardError::InvalidBoard(_))));
+}
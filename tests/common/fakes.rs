@@ -0,0 +1,1383 @@
+//! In-memory fakes of the data-access traits, for service tests that must not
+//! need a running SurrealDB.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use chasqui_server::interfaces::repositories::account_deletion::AccountDeletionRepository;
+use chasqui_server::interfaces::repositories::account_merge::AccountMergeRepository;
+use chasqui_server::interfaces::repositories::api_key::ApiKeyRepository;
+use chasqui_server::interfaces::repositories::audit_log::AuditLogRepository;
+use chasqui_server::interfaces::repositories::board::BoardRepository;
+use chasqui_server::interfaces::repositories::calendar_feed::CalendarFeedRepository;
+use chasqui_server::interfaces::repositories::conversation::ConversationRepository;
+use chasqui_server::interfaces::repositories::login_throttle::LoginThrottleRepository;
+use chasqui_server::interfaces::repositories::message::MessageRepository;
+use chasqui_server::interfaces::repositories::notification::NotificationRepository;
+use chasqui_server::interfaces::repositories::oidc_state::OidcStateRepository;
+use chasqui_server::interfaces::repositories::one_time_token::OneTimeTokenRepository;
+use chasqui_server::interfaces::repositories::task_activity::TaskActivityRepository;
+use chasqui_server::models::entities::api_key::ApiKey;
+use chasqui_server::models::entities::audit_event::AuditEvent;
+use chasqui_server::models::entities::board::{Board, BoardEvent, Project};
+use chasqui_server::models::entities::calendar::CalendarFeed;
+use chasqui_server::models::entities::conversation::Conversation;
+use chasqui_server::models::entities::identity::ExternalIdentity;
+use chasqui_server::models::entities::login_throttle::LoginThrottle;
+use chasqui_server::models::entities::message::Message;
+use chasqui_server::models::entities::notification::Notification;
+use chasqui_server::models::entities::oidc_state::OidcLoginState;
+use chasqui_server::models::entities::one_time_token::{OneTimeToken, TokenPurpose};
+use chasqui_server::models::entities::profile::UserProfile;
+use chasqui_server::models::entities::role::Role;
+use chasqui_server::models::entities::task::{Task, TaskEvent, TaskStatus};
+use chasqui_server::models::entities::task_activity::{
+    ActivityKind, TaskActivity, TaskComment, TimelineEvent,
+};
+use chasqui_server::models::entities::totp::TotpSettings;
+use chasqui_server::models::entities::user::{
+    deleted_user_thing, User, DELETED_USER_ID, DELETED_USER_NAME,
+};
+use chasqui_server::models::entities::wallet::LinkedWallet;
+use chasqui_server::models::traits::board_event_sink::BoardEventSink;
+use chasqui_server::models::traits::notification_sink::NotificationSink;
+use chasqui_server::models::traits::task_data_trait::{TaskDataTrait, TaskLinks, TaskSearch};
+use chasqui_server::models::traits::task_event_sink::TaskEventSink;
+use chasqui_server::models::traits::user_data_trait::{UserDataTrait, UserSearch};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use surrealdb::sql::Thing;
+
+/// `UserDataTrait` backed by a vector.
+#[derive(Default)]
+pub struct FakeUsers {
+    pub users: Mutex<Vec<User>>,
+}
+
+impl FakeUsers {
+    pub fn with(users: Vec<User>) -> Self {
+        Self {
+            users: Mutex::new(users),
+        }
+    }
+
+    pub fn get(&self, user_id: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.id_string().as_deref() == Some(user_id))
+            .cloned()
+    }
+
+    /// Applies `f` to the stored user and returns the updated copy
+    pub fn modify<F: FnOnce(&mut User)>(&self, user_id: &str, f: F) -> Option<User> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|u| u.id_string().as_deref() == Some(user_id))?;
+        f(user);
+        Some(user.clone())
+    }
+}
+
+#[async_trait(?Send)]
+impl UserDataTrait for FakeUsers {
+    async fn add_user(&self, new_user: User) -> Option<User> {
+        self.users.lock().unwrap().push(new_user.clone());
+        Some(new_user)
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.username == username && u.password.is_some())
+            .cloned()
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.email.as_deref() == Some(email) && u.password.is_some())
+            .cloned()
+    }
+
+    async fn find_user_by_wallet(&self, wallet: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.has_wallet(wallet))
+            .cloned()
+    }
+
+    async fn find_user_by_id(&self, user_id: &str) -> Option<User> {
+        self.get(user_id)
+    }
+
+    async fn find_user_by_identity(&self, provider: &str, subject: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.has_identity(provider, subject))
+            .cloned()
+    }
+
+    async fn find_user_by_verified_email(&self, email: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.email.as_deref() == Some(email) && u.email_verified)
+            .cloned()
+    }
+
+    async fn link_identity(&self, user_id: &str, identity: ExternalIdentity) -> Option<User> {
+        self.modify(user_id, |u| u.identities.push(identity))
+    }
+
+    async fn set_user_roles(&self, user_id: &str, roles: Vec<Role>) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.roles = roles;
+            u.token_version += 1;
+        })
+    }
+
+    async fn count_users_with_role(&self, role_name: &str) -> Option<usize> {
+        Some(
+            self.users
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|u| u.roles.iter().any(|r| r.name == role_name))
+                .count(),
+        )
+    }
+
+    async fn update_password(&self, user_id: &str, password_hash: &str) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.password = Some(password_hash.to_string());
+            u.token_version += 1;
+        })
+    }
+
+    async fn upgrade_password_hash(
+        &self,
+        user_id: &str,
+        old_hash: &str,
+        new_hash: &str,
+    ) -> Option<User> {
+        if self.get(user_id)?.password.as_deref() != Some(old_hash) {
+            return None;
+        }
+        self.modify(user_id, |u| u.password = Some(new_hash.to_string()))
+    }
+
+    async fn mark_email_verified(&self, user_id: &str) -> Option<User> {
+        self.modify(user_id, |u| u.email_verified = true)
+    }
+
+    async fn set_totp(&self, user_id: &str, totp: Option<TotpSettings>) -> Option<User> {
+        self.modify(user_id, |u| u.totp = totp)
+    }
+
+    async fn increment_totp_failures(&self, user_id: &str, token_version: u32) -> Option<u32> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|u| u.id_string().as_deref() == Some(user_id))
+            .filter(|u| u.token_version == token_version)?;
+        let totp = user.totp.as_mut().filter(|t| t.enabled)?;
+        totp.failed_attempts += 1;
+        Some(totp.failed_attempts)
+    }
+
+    async fn lock_out_totp(&self, user_id: &str) -> Option<User> {
+        self.modify(user_id, |u| {
+            if let Some(totp) = u.totp.as_mut() {
+                totp.failed_attempts = 0;
+            }
+            u.token_version += 1;
+        })
+    }
+
+    async fn revoke_tokens(&self, user_id: &str) -> Option<User> {
+        self.modify(user_id, |u| u.token_version += 1)
+    }
+
+    async fn update_profile(&self, user_id: &str, profile: UserProfile) -> Option<User> {
+        self.modify(user_id, |u| u.profile = profile)
+    }
+
+    async fn update_email(&self, user_id: &str, email: &str) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.email = Some(email.to_string());
+            u.email_verified = false;
+        })
+    }
+
+    async fn set_identities(
+        &self,
+        user_id: &str,
+        identities: Vec<ExternalIdentity>,
+    ) -> Option<User> {
+        self.modify(user_id, |u| u.identities = identities)
+    }
+
+    async fn set_wallets(
+        &self,
+        user_id: &str,
+        wallet: Option<String>,
+        wallets: Vec<LinkedWallet>,
+    ) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.wallet = wallet;
+            u.wallets = wallets;
+        })
+    }
+
+    async fn search_users(&self, search: &UserSearch) -> Vec<User> {
+        let prefix = search.prefix.as_ref().map(|p| p.to_lowercase());
+        let wallet = search.wallet.as_ref().map(|w| w.to_lowercase());
+        let mut users: Vec<User> = self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| !u.blocked && !u.is_deactivated())
+            .filter(|u| {
+                prefix.as_ref().is_none_or(|p| {
+                    u.username.to_lowercase().starts_with(p)
+                        || u.profile
+                            .display_name
+                            .as_ref()
+                            .is_some_and(|d| d.to_lowercase().starts_with(p))
+                })
+            })
+            .filter(|u| wallet.as_deref().is_none_or(|w| u.has_wallet(w)))
+            .filter(|u| {
+                search
+                    .after
+                    .as_ref()
+                    .is_none_or(|after| u.username.as_str() > after.as_str())
+            })
+            .cloned()
+            .collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        users.truncate(search.limit);
+        users
+    }
+
+    async fn find_active_user_by_username(&self, username: &str) -> Option<User> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| {
+                u.username.to_lowercase() == username.to_lowercase()
+                    && !u.blocked
+                    && !u.is_deactivated()
+            })
+            .cloned()
+    }
+
+    async fn set_blocked(&self, user_id: &str, blocked: bool) -> Option<User> {
+        self.modify(user_id, |u| {
+            if blocked {
+                u.token_version += 1;
+            }
+            u.blocked = blocked;
+        })
+    }
+
+    async fn deactivate(
+        &self,
+        user_id: &str,
+        deletion_scheduled_at: Option<DateTime<Utc>>,
+    ) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.deactivated_at = Some(Utc::now());
+            u.deletion_scheduled_at = deletion_scheduled_at;
+            u.token_version += 1;
+        })
+    }
+
+    async fn reactivate(&self, user_id: &str) -> Option<User> {
+        self.modify(user_id, |u| {
+            u.deactivated_at = None;
+            u.deletion_scheduled_at = None;
+        })
+    }
+
+    async fn delete_wallet_users(&self) -> bool {
+        self.users.lock().unwrap().retain(|u| !u.is_wallet_only());
+        true
+    }
+}
+
+/// `OneTimeTokenRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeOneTimeTokens {
+    pub tokens: Mutex<Vec<OneTimeToken>>,
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for FakeOneTimeTokens {
+    async fn create(&self, token: OneTimeToken) -> Result<OneTimeToken, surrealdb::Error> {
+        self.tokens.lock().unwrap().push(token.clone());
+        Ok(token)
+    }
+
+    async fn consume(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &str,
+    ) -> Result<Option<OneTimeToken>, surrealdb::Error> {
+        let now = Utc::now();
+        let mut tokens = self.tokens.lock().unwrap();
+        Ok(tokens
+            .iter_mut()
+            .find(|t| t.purpose == purpose && t.token_hash == token_hash && t.is_usable_at(now))
+            .map(|t| {
+                t.used_at = Some(now);
+                t.clone()
+            }))
+    }
+
+    async fn find_latest_for_user(
+        &self,
+        user_id: Thing,
+        purpose: TokenPurpose,
+    ) -> Result<Option<OneTimeToken>, surrealdb::Error> {
+        Ok(self
+            .tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.user_id == user_id && t.purpose == purpose)
+            .max_by_key(|t| t.created_at)
+            .cloned())
+    }
+
+    async fn invalidate_for_user(
+        &self,
+        user_id: Thing,
+        purpose: TokenPurpose,
+    ) -> Result<(), surrealdb::Error> {
+        let now = Utc::now();
+        for t in self.tokens.lock().unwrap().iter_mut() {
+            if t.user_id == user_id && t.purpose == purpose && t.used_at.is_none() {
+                t.used_at = Some(now);
+            }
+        }
+        Ok(())
+    }
+
+    async fn purge_expired(&self, purpose: TokenPurpose) -> Result<(), surrealdb::Error> {
+        let now = Utc::now();
+        self.tokens
+            .lock()
+            .unwrap()
+            .retain(|t| t.purpose != purpose || t.expires_at >= now);
+        Ok(())
+    }
+}
+
+/// `OidcStateRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeOidcStates {
+    pub states: Mutex<Vec<OidcLoginState>>,
+}
+
+#[async_trait]
+impl OidcStateRepository for FakeOidcStates {
+    async fn create(&self, state: OidcLoginState) -> Result<OidcLoginState, surrealdb::Error> {
+        self.states.lock().unwrap().push(state.clone());
+        Ok(state)
+    }
+
+    async fn consume(&self, state_hash: &str) -> Result<Option<OidcLoginState>, surrealdb::Error> {
+        let now = Utc::now();
+        let mut states = self.states.lock().unwrap();
+        let pos = states
+            .iter()
+            .position(|s| s.state_hash == state_hash && s.is_usable_at(now));
+        Ok(pos.map(|i| states.remove(i)))
+    }
+
+    async fn purge_expired(&self) -> Result<(), surrealdb::Error> {
+        let now = Utc::now();
+        self.states.lock().unwrap().retain(|s| s.is_usable_at(now));
+        Ok(())
+    }
+}
+
+/// `LoginThrottleRepository` backed by a map keyed on the throttle key.
+#[derive(Default)]
+pub struct FakeLoginThrottles {
+    pub throttles: Mutex<HashMap<String, LoginThrottle>>,
+}
+
+#[async_trait]
+impl LoginThrottleRepository for FakeLoginThrottles {
+    async fn find(&self, key: &str) -> Result<Option<LoginThrottle>, surrealdb::Error> {
+        Ok(self.throttles.lock().unwrap().get(key).cloned())
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        window: Duration,
+    ) -> Result<LoginThrottle, surrealdb::Error> {
+        let mut throttles = self.throttles.lock().unwrap();
+        let throttle = throttles
+            .entry(key.to_string())
+            .or_insert_with(|| LoginThrottle::new(key));
+        throttle.register_failure(Utc::now(), window);
+        Ok(throttle.clone())
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), surrealdb::Error> {
+        if let Some(throttle) = self.throttles.lock().unwrap().get_mut(key) {
+            throttle.locked_until = Some(until);
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), surrealdb::Error> {
+        self.throttles.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// `AuditLogRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeAuditLog {
+    pub events: Mutex<Vec<AuditEvent>>,
+}
+
+#[async_trait]
+impl AuditLogRepository for FakeAuditLog {
+    async fn record(&self, event: AuditEvent) -> Result<AuditEvent, surrealdb::Error> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(event)
+    }
+
+    async fn find_by_subject(
+        &self,
+        subject: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>, surrealdb::Error> {
+        let mut events: Vec<AuditEvent> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.subject == subject)
+            .cloned()
+            .collect();
+        events.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        events.truncate(limit);
+        Ok(events)
+    }
+}
+
+/// `ApiKeyRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeApiKeys {
+    pub keys: Mutex<Vec<ApiKey>>,
+}
+
+impl FakeApiKeys {
+    fn modify<F: FnOnce(&mut ApiKey)>(&self, key_id: &Thing, f: F) {
+        if let Some(key) = self
+            .keys
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|k| k.id.as_ref() == Some(key_id))
+        {
+            f(key);
+        }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for FakeApiKeys {
+    async fn create(&self, key: ApiKey) -> Result<ApiKey, surrealdb::Error> {
+        self.keys.lock().unwrap().push(key.clone());
+        Ok(key)
+    }
+
+    async fn find(&self, key_id: Thing) -> Result<Option<ApiKey>, surrealdb::Error> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|k| k.id.as_ref() == Some(&key_id))
+            .cloned())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, surrealdb::Error> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|k| k.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn list_for_user(&self, user_id: Thing) -> Result<Vec<ApiKey>, surrealdb::Error> {
+        let mut keys: Vec<ApiKey> = self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|k| k.user_id == user_id)
+            .cloned()
+            .collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.created_at));
+        Ok(keys)
+    }
+
+    async fn revoke(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), surrealdb::Error> {
+        self.modify(&key_id, |k| {
+            k.revoked_at.get_or_insert(at);
+        });
+        Ok(())
+    }
+
+    async fn touch(&self, key_id: Thing, at: DateTime<Utc>) -> Result<(), surrealdb::Error> {
+        self.modify(&key_id, |k| k.last_used_at = Some(at));
+        Ok(())
+    }
+}
+
+/// `AccountMergeRepository` that records each merge and moves the tasks of
+/// the source like the SQL transaction.
+#[derive(Default)]
+pub struct FakeAccountMerges {
+    pub merges: Mutex<Vec<(Thing, User)>>,
+    pub tasks: Arc<FakeTasks>,
+}
+
+#[async_trait]
+impl AccountMergeRepository for FakeAccountMerges {
+    async fn merge(&self, source: Thing, target: User) -> Result<(), surrealdb::Error> {
+        let source_uuid = source.id.to_raw();
+        let target_uuid = target.id_string().unwrap_or_default();
+        for task in self.tasks.tasks.lock().unwrap().iter_mut() {
+            let mut moved = false;
+            if task.creator.as_deref() == Some(source_uuid.as_str()) {
+                task.creator = Some(target_uuid.clone());
+                moved = true;
+            }
+            if task.assignee.as_deref() == Some(source_uuid.as_str()) {
+                task.assignee = Some(target_uuid.clone());
+                moved = true;
+            }
+            if task.watchers.contains(&source_uuid) {
+                task.watchers
+                    .retain(|w| *w != source_uuid && *w != target_uuid);
+                task.watchers.push(target_uuid.clone());
+                moved = true;
+            }
+            if moved {
+                task.version += 1;
+            }
+        }
+        self.merges.lock().unwrap().push((source, target));
+        Ok(())
+    }
+}
+
+/// `AccountDeletionRepository` over shared fakes, mirroring the SQL purge.
+pub struct FakeAccountDeletions {
+    pub users: Arc<FakeUsers>,
+    pub messages: Arc<FakeMessages>,
+    pub tasks: Arc<FakeTasks>,
+    pub activity: Arc<FakeTaskActivity>,
+    pub boards: Arc<FakeBoards>,
+    pub notifications: Arc<FakeNotifications>,
+    pub feeds: Arc<FakeCalendarFeeds>,
+}
+
+impl FakeAccountDeletions {
+    pub fn new(users: Arc<FakeUsers>, messages: Arc<FakeMessages>) -> Self {
+        FakeAccountDeletions {
+            users,
+            messages,
+            tasks: Arc::default(),
+            activity: Arc::default(),
+            boards: Arc::default(),
+            notifications: Arc::default(),
+            feeds: Arc::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl AccountDeletionRepository for FakeAccountDeletions {
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Thing>, surrealdb::Error> {
+        Ok(self
+            .users
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| u.deletion_scheduled_at.is_some_and(|at| at <= now))
+            .filter_map(|u| u.id.clone())
+            .collect())
+    }
+
+    async fn purge(&self, user_id: Thing) -> Result<(), surrealdb::Error> {
+        let uuid = user_id.id.to_raw();
+        for message in self.messages.messages.lock().unwrap().iter_mut() {
+            if message.sender_id == user_id {
+                message.sender_id = deleted_user_thing();
+            }
+            message.read_by.retain(|id| *id != user_id);
+        }
+        for task in self.tasks.tasks.lock().unwrap().iter_mut() {
+            if task.creator.as_deref() == Some(uuid.as_str()) {
+                task.creator = Some(DELETED_USER_ID.to_string());
+            }
+            if task.assignee.as_deref() == Some(uuid.as_str()) {
+                task.assignee = None;
+            }
+            task.watchers.retain(|w| *w != uuid);
+        }
+        for comment in self.activity.comments.lock().unwrap().iter_mut() {
+            if comment.author == uuid {
+                comment.author = DELETED_USER_ID.to_string();
+                comment.author_name = DELETED_USER_NAME.to_string();
+            }
+            comment.mentions.retain(|m| *m != uuid);
+        }
+        for entry in self.activity.activity.lock().unwrap().iter_mut() {
+            if entry.actor == uuid {
+                entry.actor = DELETED_USER_ID.to_string();
+                entry.actor_name = DELETED_USER_NAME.to_string();
+            }
+            if entry.kind == ActivityKind::Reassigned {
+                for id in [&mut entry.from, &mut entry.to].into_iter().flatten() {
+                    if *id == uuid {
+                        *id = DELETED_USER_ID.to_string();
+                    }
+                }
+            }
+        }
+        for project in self.boards.projects.lock().unwrap().iter_mut() {
+            if project.owner == uuid {
+                project.owner = DELETED_USER_ID.to_string();
+            }
+            project.members.retain(|m| *m != uuid);
+        }
+        self.notifications
+            .notifications
+            .lock()
+            .unwrap()
+            .retain(|n| n.user_id != uuid);
+        self.feeds
+            .feeds
+            .lock()
+            .unwrap()
+            .retain(|f| f.user_id != uuid);
+        self.users
+            .users
+            .lock()
+            .unwrap()
+            .retain(|u| u.id.as_ref() != Some(&user_id));
+        Ok(())
+    }
+}
+
+/// `ConversationRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeConversations {
+    pub conversations: Mutex<Vec<Conversation>>,
+}
+
+#[async_trait]
+impl ConversationRepository for FakeConversations {
+    async fn create(&self, conversation: Conversation) -> Result<Conversation, surrealdb::Error> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .push(conversation.clone());
+        Ok(conversation)
+    }
+
+    async fn find_by_user(&self, user_id: Thing) -> Result<Vec<Conversation>, surrealdb::Error> {
+        Ok(self
+            .conversations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.participants.contains(&user_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_id(&self, id: Thing) -> Result<Option<Conversation>, surrealdb::Error> {
+        Ok(self
+            .conversations
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|c| c.id.as_ref() == Some(&id))
+            .cloned())
+    }
+
+    async fn add_participant(
+        &self,
+        conversation_id: Thing,
+        user_id: Thing,
+    ) -> Result<(), surrealdb::Error> {
+        if let Some(c) = self
+            .conversations
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|c| c.id.as_ref() == Some(&conversation_id))
+        {
+            c.add_participant(user_id);
+        }
+        Ok(())
+    }
+
+    async fn remove_participant(
+        &self,
+        conversation_id: Thing,
+        user_id: Thing,
+    ) -> Result<(), surrealdb::Error> {
+        if let Some(c) = self
+            .conversations
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|c| c.id.as_ref() == Some(&conversation_id))
+        {
+            c.remove_participant(&user_id);
+        }
+        Ok(())
+    }
+}
+
+/// `MessageRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeMessages {
+    pub messages: Mutex<Vec<Message>>,
+}
+
+#[async_trait]
+impl MessageRepository for FakeMessages {
+    async fn create(&self, message: Message) -> Result<Message, surrealdb::Error> {
+        self.messages.lock().unwrap().push(message.clone());
+        Ok(message)
+    }
+
+    async fn find_by_conversation(
+        &self,
+        conversation_id: Thing,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Message>, surrealdb::Error> {
+        let mut messages: Vec<Message> = self
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|m| m.conversation_id == conversation_id)
+            .cloned()
+            .collect();
+        messages.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+        Ok(messages
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn find_by_sender(&self, sender_id: Thing) -> Result<Vec<Message>, surrealdb::Error> {
+        let mut messages: Vec<Message> = self
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|m| m.sender_id == sender_id)
+            .cloned()
+            .collect();
+        messages.sort_by_key(|m| m.created_at);
+        Ok(messages)
+    }
+
+    async fn find_by_id(&self, id: Thing) -> Result<Option<Message>, surrealdb::Error> {
+        Ok(self
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|m| m.id.as_ref() == Some(&id))
+            .cloned())
+    }
+
+    async fn link_task(
+        &self,
+        message_id: Thing,
+        task_id: String,
+    ) -> Result<bool, surrealdb::Error> {
+        match self
+            .messages
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|m| m.id.as_ref() == Some(&message_id) && m.task_id.is_none())
+        {
+            Some(m) => {
+                m.task_id = Some(task_id);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn mark_as_read(
+        &self,
+        message_id: Thing,
+        user_id: Thing,
+    ) -> Result<(), surrealdb::Error> {
+        if let Some(m) = self
+            .messages
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|m| m.id.as_ref() == Some(&message_id))
+        {
+            m.read_by.push(user_id);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: Thing) -> Result<(), surrealdb::Error> {
+        self.messages
+            .lock()
+            .unwrap()
+            .retain(|m| m.id.as_ref() != Some(&id));
+        Ok(())
+    }
+}
+
+/// `TaskDataTrait` backed by a vector.
+#[derive(Default)]
+pub struct FakeTasks {
+    pub tasks: Mutex<Vec<Task>>,
+    /// (child, parent) edges
+    pub parents: Mutex<Vec<(String, String)>>,
+    /// (task, blocker) edges
+    pub dependencies: Mutex<Vec<(String, String)>>,
+    /// Makes `set_parent` fail like a database error
+    pub fail_set_parent: Mutex<bool>,
+    /// Returned by `find_tasks_with_reminders` instead of the stored tasks,
+    /// like a read that a concurrent change overtook
+    pub stale_reminder_reads: Mutex<Vec<Task>>,
+}
+
+#[async_trait]
+impl TaskDataTrait for FakeTasks {
+    async fn get_all_tasks(&self) -> Option<Vec<Task>> {
+        Some(self.tasks.lock().unwrap().clone())
+    }
+
+    async fn search_tasks(&self, search: &TaskSearch) -> Option<Vec<Task>> {
+        let text = search.text.as_ref().map(|t| t.to_lowercase());
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| match &search.visible_to {
+                Some((user_id, conversation_ids)) => {
+                    t.is_involved(user_id)
+                        || t.conversation_id
+                            .as_ref()
+                            .is_some_and(|c| conversation_ids.contains(c))
+                }
+                None => true,
+            })
+            .filter(|t| search.status.is_none_or(|s| t.status == s))
+            .filter(|t| search.assignee.is_none() || t.assignee == search.assignee)
+            .filter(|t| {
+                search
+                    .due_before
+                    .is_none_or(|before| t.due_date.is_some_and(|due| due < before))
+            })
+            .filter(|t| {
+                search
+                    .due_after
+                    .is_none_or(|after| t.due_date.is_some_and(|due| due >= after))
+            })
+            .filter(|t| {
+                text.as_ref().is_none_or(|q| {
+                    t.task_name.to_lowercase().contains(q)
+                        || t.description
+                            .as_ref()
+                            .is_some_and(|d| d.to_lowercase().contains(q))
+                })
+            })
+            .filter(|t| match &search.after {
+                Some((key, uuid)) => {
+                    let k = search.sort.key(t);
+                    (if search.sort.descending {
+                        k < *key
+                    } else {
+                        k > *key
+                    }) || (k == *key && t.uuid > *uuid)
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+        tasks.sort_by(|a, b| {
+            let (ka, kb) = (search.sort.key(a), search.sort.key(b));
+            let by_key = if search.sort.descending {
+                kb.cmp(&ka)
+            } else {
+                ka.cmp(&kb)
+            };
+            by_key.then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        tasks.truncate(search.limit);
+        Some(tasks)
+    }
+
+    async fn add_task(&self, new_task: Task) -> Option<Task> {
+        self.tasks.lock().unwrap().push(new_task.clone());
+        Some(new_task)
+    }
+
+    async fn find_task(&self, uuid: String) -> Option<Task> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.uuid == uuid)
+            .cloned()
+    }
+
+    async fn find_tasks(&self, uuids: Vec<String>) -> Option<Vec<Task>> {
+        Some(
+            self.tasks
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|t| uuids.contains(&t.uuid))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    async fn update_task(&self, uuid: String, task: Task, expected_version: u64) -> Option<Task> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let stored = tasks
+            .iter_mut()
+            .find(|t| t.uuid == uuid && t.version == expected_version)?;
+        *stored = task.clone();
+        Some(task)
+    }
+
+    async fn delete_task(&self, uuid: String) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        let before = tasks.len();
+        tasks.retain(|t| t.uuid != uuid);
+        for edges in [&self.parents, &self.dependencies] {
+            edges
+                .lock()
+                .unwrap()
+                .retain(|(a, b)| *a != uuid && *b != uuid);
+        }
+        tasks.len() != before
+    }
+
+    async fn task_links(&self, uuid: String) -> Option<TaskLinks> {
+        let parents = self.parents.lock().unwrap();
+        let dependencies = self.dependencies.lock().unwrap();
+        let from = |edges: &[(String, String)]| -> Vec<String> {
+            edges
+                .iter()
+                .filter(|(a, _)| *a == uuid)
+                .map(|(_, b)| b.clone())
+                .collect()
+        };
+        let to = |edges: &[(String, String)]| -> Vec<String> {
+            edges
+                .iter()
+                .filter(|(_, b)| *b == uuid)
+                .map(|(a, _)| a.clone())
+                .collect()
+        };
+        Some(TaskLinks {
+            parent: from(&parents).into_iter().next(),
+            subtasks: to(&parents),
+            depends_on: from(&dependencies),
+            blocks: to(&dependencies),
+        })
+    }
+
+    async fn set_parent(&self, uuid: String, parent: Option<String>) -> bool {
+        if *self.fail_set_parent.lock().unwrap() {
+            return false;
+        }
+        let mut parents = self.parents.lock().unwrap();
+        parents.retain(|(child, _)| *child != uuid);
+        parents.extend(parent.map(|p| (uuid, p)));
+        true
+    }
+
+    async fn add_dependency(&self, uuid: String, blocker: String) -> bool {
+        let mut dependencies = self.dependencies.lock().unwrap();
+        let edge = (uuid, blocker);
+        if !dependencies.contains(&edge) {
+            dependencies.push(edge);
+        }
+        true
+    }
+
+    async fn remove_dependency(&self, uuid: String, blocker: String) -> bool {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .retain(|edge| *edge != (uuid.clone(), blocker.clone()));
+        true
+    }
+
+    async fn find_tasks_with_reminders(&self, horizon: DateTime<Utc>) -> Option<Vec<Task>> {
+        let stale = self.stale_reminder_reads.lock().unwrap();
+        if !stale.is_empty() {
+            return Some(stale.clone());
+        }
+        Some(
+            self.tasks
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|t| t.status != TaskStatus::Done)
+                .filter(|t| t.reminders.iter().any(|m| !t.reminded.contains(m)))
+                .filter(|t| t.due_date.is_some_and(|due| due <= horizon))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    async fn set_reminded(&self, uuid: String, reminded: Vec<u32>, expected_version: u64) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        match tasks
+            .iter_mut()
+            .find(|t| t.uuid == uuid && t.version == expected_version)
+        {
+            Some(task) => {
+                task.reminded = reminded;
+                task.version += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// `TaskEventSink` that records the published events.
+#[derive(Default)]
+pub struct FakeTaskEvents {
+    pub events: Mutex<Vec<TaskEvent>>,
+    pub timeline: Mutex<Vec<TimelineEvent>>,
+}
+
+impl TaskEventSink for FakeTaskEvents {
+    fn publish(&self, event: TaskEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn publish_timeline(&self, event: TimelineEvent) {
+        self.timeline.lock().unwrap().push(event);
+    }
+}
+
+/// `TaskActivityRepository` backed by vectors.
+#[derive(Default)]
+pub struct FakeTaskActivity {
+    pub comments: Mutex<Vec<TaskComment>>,
+    pub activity: Mutex<Vec<TaskActivity>>,
+}
+
+#[async_trait]
+impl TaskActivityRepository for FakeTaskActivity {
+    async fn add_comment(&self, comment: TaskComment) -> Result<TaskComment, surrealdb::Error> {
+        self.comments.lock().unwrap().push(comment.clone());
+        Ok(comment)
+    }
+
+    async fn add_activity(&self, activity: TaskActivity) -> Result<TaskActivity, surrealdb::Error> {
+        self.activity.lock().unwrap().push(activity.clone());
+        Ok(activity)
+    }
+
+    async fn find_comments(&self, task_id: &str) -> Result<Vec<TaskComment>, surrealdb::Error> {
+        Ok(self
+            .comments
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_activity(&self, task_id: &str) -> Result<Vec<TaskActivity>, surrealdb::Error> {
+        Ok(self
+            .activity
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|a| a.task_id == task_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_for_task(&self, task_id: &str) -> Result<(), surrealdb::Error> {
+        self.comments
+            .lock()
+            .unwrap()
+            .retain(|c| c.task_id != task_id);
+        self.activity
+            .lock()
+            .unwrap()
+            .retain(|a| a.task_id != task_id);
+        Ok(())
+    }
+}
+
+/// `BoardRepository` backed by vectors; `save_board` is a compare-and-set on
+/// the version. `interleaved` makes the next saves lose to a simulated
+/// concurrent change, which bumps the stored version.
+#[derive(Default)]
+pub struct FakeBoards {
+    pub projects: Mutex<Vec<Project>>,
+    pub boards: Mutex<Vec<Board>>,
+    pub interleaved: Mutex<usize>,
+}
+
+#[async_trait]
+impl BoardRepository for FakeBoards {
+    async fn create_project(&self, project: Project) -> Result<Project, surrealdb::Error> {
+        self.projects.lock().unwrap().push(project.clone());
+        Ok(project)
+    }
+
+    async fn find_project(&self, uuid: &str) -> Result<Option<Project>, surrealdb::Error> {
+        Ok(self
+            .projects
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| p.uuid == uuid)
+            .cloned())
+    }
+
+    async fn find_projects_by_user(&self, user_id: &str) -> Result<Vec<Project>, surrealdb::Error> {
+        Ok(self
+            .projects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.is_member(user_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn add_project_member(&self, uuid: &str, user_id: &str) -> Result<(), surrealdb::Error> {
+        if let Some(project) = self
+            .projects
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|p| p.uuid == uuid)
+        {
+            project.members.push(user_id.to_string());
+        }
+        Ok(())
+    }
+
+    async fn create_board(&self, board: Board) -> Result<Board, surrealdb::Error> {
+        self.boards.lock().unwrap().push(board.clone());
+        Ok(board)
+    }
+
+    async fn find_board(&self, uuid: &str) -> Result<Option<Board>, surrealdb::Error> {
+        Ok(self
+            .boards
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|b| b.uuid == uuid)
+            .cloned())
+    }
+
+    async fn find_boards_by_project(
+        &self,
+        project_id: &str,
+    ) -> Result<Vec<Board>, surrealdb::Error> {
+        Ok(self
+            .boards
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|b| b.project_id == project_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn save_board(
+        &self,
+        board: Board,
+        expected_version: u64,
+    ) -> Result<Option<Board>, surrealdb::Error> {
+        let mut boards = self.boards.lock().unwrap();
+        let Some(stored) = boards.iter_mut().find(|b| b.uuid == board.uuid) else {
+            return Ok(None);
+        };
+        let mut interleaved = self.interleaved.lock().unwrap();
+        if *interleaved > 0 {
+            *interleaved -= 1;
+            stored.version += 1;
+        }
+        if stored.version != expected_version {
+            return Ok(None);
+        }
+        *stored = board.clone();
+        Ok(Some(board))
+    }
+}
+
+/// `BoardEventSink` that records the published events.
+#[derive(Default)]
+pub struct FakeBoardEvents {
+    pub events: Mutex<Vec<BoardEvent>>,
+}
+
+impl BoardEventSink for FakeBoardEvents {
+    fn publish(&self, event: BoardEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+/// `NotificationRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeNotifications {
+    pub notifications: Mutex<Vec<Notification>>,
+}
+
+#[async_trait]
+impl NotificationRepository for FakeNotifications {
+    async fn create(&self, notification: Notification) -> Result<Notification, surrealdb::Error> {
+        self.notifications
+            .lock()
+            .unwrap()
+            .push(notification.clone());
+        Ok(notification)
+    }
+
+    async fn find_for_user(
+        &self,
+        user_id: &str,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, surrealdb::Error> {
+        Ok(self
+            .notifications
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|n| n.user_id == user_id && !(unread_only && n.read_at.is_some()))
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_read(
+        &self,
+        user_id: &str,
+        uuid: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Notification>, surrealdb::Error> {
+        let mut notifications = self.notifications.lock().unwrap();
+        Ok(notifications
+            .iter_mut()
+            .find(|n| n.uuid == uuid && n.user_id == user_id)
+            .map(|n| {
+                n.read_at.get_or_insert(at);
+                n.clone()
+            }))
+    }
+}
+
+/// `NotificationSink` where the users in `online` are connected; records
+/// what it delivered as (user UUID, payload).
+#[derive(Default)]
+pub struct FakeNotificationSink {
+    pub online: Mutex<Vec<String>>,
+    pub delivered: Mutex<Vec<(String, String)>>,
+}
+
+#[async_trait]
+impl NotificationSink for FakeNotificationSink {
+    async fn deliver(&self, user_id: &str, payload: String) -> bool {
+        if !self.online.lock().unwrap().iter().any(|u| u == user_id) {
+            return false;
+        }
+        self.delivered
+            .lock()
+            .unwrap()
+            .push((user_id.to_string(), payload));
+        true
+    }
+}
+
+/// `CalendarFeedRepository` backed by a vector.
+#[derive(Default)]
+pub struct FakeCalendarFeeds {
+    pub feeds: Mutex<Vec<CalendarFeed>>,
+}
+
+#[async_trait]
+impl CalendarFeedRepository for FakeCalendarFeeds {
+    async fn upsert(&self, feed: CalendarFeed) -> Result<CalendarFeed, surrealdb::Error> {
+        let mut feeds = self.feeds.lock().unwrap();
+        feeds.retain(|f| f.user_id != feed.user_id);
+        feeds.push(feed.clone());
+        Ok(feed)
+    }
+
+    async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<CalendarFeed>, surrealdb::Error> {
+        Ok(self
+            .feeds
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|f| f.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn delete(&self, user_id: &str) -> Result<(), surrealdb::Error> {
+        self.feeds.lock().unwrap().retain(|f| f.user_id != user_id);
+        Ok(())
+    }
+}
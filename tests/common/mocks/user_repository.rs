@@ -1,5 +1,8 @@
-use actix_crud::models::entities::role::Role;
-use actix_crud::models::entities::user::User;
+//! Mock repository shared by several test targets; not every target uses every method.
+#![allow(dead_code)]
+
+use chasqui_server::models::entities::role::Role;
+use chasqui_server::models::entities::user::User;
 use async_trait::async_trait;
 use mockall::automock;
 use std::fmt;
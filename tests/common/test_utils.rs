@@ -1,6 +1,6 @@
 //! Test utilities and helpers
 use super::mocks::user_repository::MockUserRepository;
-use actix_crud::models::entities::user::User;
+use chasqui_server::models::entities::user::User;
 
 
 /// Create a test user with default values
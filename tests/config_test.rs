@@ -1,7 +1,7 @@
 //! Configuration Tests Module
 //! Tests the application configuration loading functionality.
 
-use actix_crud::config::app_config::AppConfig;
+use chasqui_server::config::app_config::AppConfig;
 
 /// Test that configuration values are correctly read from environment variables
 #[test]
@@ -1,4 +1,4 @@
-use actix_crud::infrastructure::database::surrealdb::Database;
+use chasqui_server::infrastructure::database::surrealdb::Database;
 use std::env;
 use surrealdb::engine::any;
 use surrealdb::opt::auth::Root;
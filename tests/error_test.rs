@@ -1,7 +1,7 @@
 //! Error Handling Tests Module
 //! Validates error types and their HTTP response characteristics.
 
-use actix_crud::error::task_error::TaskError;
+use chasqui_server::error::task_error::TaskError;
 use actix_web::http::{header::CONTENT_TYPE, StatusCode};
 use actix_web::ResponseError;
 
@@ -15,6 +15,25 @@ fn task_error_status_codes() {
         StatusCode::INTERNAL_SERVER_ERROR
     );
     assert_eq!(TaskError::NoTaskFoundWithId.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        TaskError::InvalidTask("task_name".to_string()).status_code(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(TaskError::AssigneeNotFound.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(TaskError::NotAllowed.status_code(), StatusCode::FORBIDDEN);
+    assert_eq!(TaskError::MessageNotFound.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(TaskError::MessageAlreadyLinked.status_code(), StatusCode::CONFLICT);
+    assert_eq!(TaskError::DependencyCycle.status_code(), StatusCode::CONFLICT);
+    assert_eq!(TaskError::NotificationNotFound.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        TaskError::InvalidCalendar("x".to_string()).status_code(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(TaskError::InvalidFeedToken.status_code(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        TaskError::BlockedBy(vec!["t1".to_string()]).status_code(),
+        StatusCode::CONFLICT
+    );
 }
 
 /// Test that error responses are properly formatted as JSON
@@ -31,3 +50,26 @@ fn task_error_response_is_json() {
         .expect("content-type header present");
     assert_eq!(ct.to_str().unwrap(), "application/json");
 }
+
+/// Test that throttled logins answer 429 with a Retry-After header
+#[test]
+fn auth_error_too_many_attempts_sets_retry_after() {
+    use chasqui_server::error::AuthError;
+
+    let resp = AuthError::TooManyLoginAttempts(42).error_response();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(resp.headers().get("retry-after").unwrap(), "42");
+}
+
+/// Test that board errors map to their status codes and pass task errors through
+#[test]
+fn board_error_status_codes() {
+    use chasqui_server::error::BoardError;
+
+    assert_eq!(BoardError::BoardNotFound.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(BoardError::ColumnNotEmpty.status_code(), StatusCode::CONFLICT);
+    assert_eq!(BoardError::VersionConflict.status_code(), StatusCode::CONFLICT);
+
+    let resp = BoardError::Task(TaskError::NotAllowed).error_response();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,296 @@
+//! Message Task Service Tests Module
+//! Exercises turning chat messages into linked tasks against in-memory fakes.
+
+use chasqui_server::application::services::message_task_service::MessageTaskService;
+use chasqui_server::application::services::task_service::TaskService;
+use chasqui_server::error::TaskError;
+use chasqui_server::interfaces::repositories::message::MessageRepository;
+use chasqui_server::models::entities::conversation::Conversation;
+use chasqui_server::models::entities::message::{Message, MessageType};
+use chasqui_server::models::entities::task::{CreateTaskFromMessageRequest, TaskStatus};
+use chasqui_server::models::entities::user::User;
+use chasqui_server::models::traits::task_data_trait::TaskDataTrait;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeConversations, FakeMessages, FakeTasks, FakeUsers};
+
+struct Setup {
+    service: MessageTaskService,
+    tasks: Arc<TaskService>,
+    messages: Arc<FakeMessages>,
+    conversations: Arc<FakeConversations>,
+    alice: User,
+    mallory: User,
+    message: Message,
+}
+
+// alice and bob share a conversation holding one two-line message from bob
+fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let mallory = User::new_bot("mallory".to_string());
+    let conversation = Conversation::new_direct(alice.id.clone().unwrap(), bob.id.clone().unwrap());
+    let message = Message::new(
+        conversation.id.clone().unwrap(),
+        bob.id.clone().unwrap(),
+        "Fix the login page\nIt crashes on empty passwords".to_string(),
+        None,
+    );
+
+    let conversations = Arc::new(FakeConversations::default());
+    conversations
+        .conversations
+        .lock()
+        .unwrap()
+        .push(conversation);
+    let messages = Arc::new(FakeMessages::default());
+    messages.messages.lock().unwrap().push(message.clone());
+    let tasks = Arc::new(TaskService::new(conversations.clone(), 20, 100));
+    Setup {
+        service: MessageTaskService::new(tasks.clone(), messages.clone(), conversations.clone()),
+        tasks,
+        messages,
+        conversations,
+        alice,
+        mallory,
+        message,
+    }
+}
+
+fn uuid_of(message: &Message) -> String {
+    message.id.as_ref().unwrap().id.to_raw()
+}
+
+#[actix_rt::test]
+async fn creates_linked_task_and_announces_it() {
+    let s = setup();
+    let users = FakeUsers::with(vec![s.alice.clone()]);
+    let tasks = FakeTasks::default();
+    let message_id = uuid_of(&s.message);
+
+    let created = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            &message_id,
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await
+        .unwrap();
+
+    let task = &created.task;
+    assert_eq!(task.task_name, "Fix the login page");
+    assert_eq!(
+        task.description.as_deref(),
+        Some("Fix the login page\nIt crashes on empty passwords")
+    );
+    assert_eq!(task.source_message_id.as_deref(), Some(message_id.as_str()));
+    assert_eq!(
+        task.conversation_id,
+        Some(s.message.conversation_id.id.to_raw())
+    );
+    assert_eq!(task.creator, s.alice.id_string());
+
+    let announcement = &created.announcement;
+    assert_eq!(announcement.message_type, MessageType::System);
+    assert_eq!(announcement.task_id.as_ref(), Some(&task.uuid));
+    assert_eq!(
+        announcement.content,
+        "alice created task \"Fix the login page\""
+    );
+
+    let stored = s.messages.messages.lock().unwrap().clone();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0].task_id.as_ref(), Some(&task.uuid));
+
+    // A message is linked to one task at most
+    let again = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            &message_id,
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await;
+    assert!(matches!(again, Err(TaskError::MessageAlreadyLinked)));
+}
+
+// Reads messages as they were before any task was linked, like a request
+// that loaded the message just before a concurrent one linked it
+struct StaleMessages(Arc<FakeMessages>);
+
+#[async_trait::async_trait]
+impl MessageRepository for StaleMessages {
+    async fn create(&self, message: Message) -> Result<Message, surrealdb::Error> {
+        self.0.create(message).await
+    }
+
+    async fn find_by_conversation(
+        &self,
+        conversation_id: Thing,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Message>, surrealdb::Error> {
+        self.0
+            .find_by_conversation(conversation_id, limit, offset)
+            .await
+    }
+
+    async fn find_by_sender(&self, sender_id: Thing) -> Result<Vec<Message>, surrealdb::Error> {
+        self.0.find_by_sender(sender_id).await
+    }
+
+    async fn find_by_id(&self, id: Thing) -> Result<Option<Message>, surrealdb::Error> {
+        Ok(self
+            .0
+            .find_by_id(id)
+            .await?
+            .map(|m| Message { task_id: None, ..m }))
+    }
+
+    async fn link_task(
+        &self,
+        message_id: Thing,
+        task_id: String,
+    ) -> Result<bool, surrealdb::Error> {
+        self.0.link_task(message_id, task_id).await
+    }
+
+    async fn mark_as_read(
+        &self,
+        message_id: Thing,
+        user_id: Thing,
+    ) -> Result<(), surrealdb::Error> {
+        self.0.mark_as_read(message_id, user_id).await
+    }
+
+    async fn delete(&self, id: Thing) -> Result<(), surrealdb::Error> {
+        self.0.delete(id).await
+    }
+}
+
+#[actix_rt::test]
+async fn a_request_that_loses_the_link_race_drops_its_task() {
+    let s = setup();
+    let users = FakeUsers::with(vec![s.alice.clone()]);
+    let tasks = FakeTasks::default();
+    let message_id = uuid_of(&s.message);
+    let racing = MessageTaskService::new(
+        s.tasks.clone(),
+        Arc::new(StaleMessages(s.messages.clone())),
+        s.conversations.clone(),
+    );
+
+    let first = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            &message_id,
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await
+        .unwrap();
+    let second = racing
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            &message_id,
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await;
+    assert!(matches!(second, Err(TaskError::MessageAlreadyLinked)));
+
+    let stored = tasks.tasks.lock().unwrap().clone();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].uuid, first.task.uuid);
+    let messages = s.messages.messages.lock().unwrap().clone();
+    assert_eq!(messages[0].task_id.as_ref(), Some(&first.task.uuid));
+    // Only the winner announced its task
+    assert_eq!(messages.len(), 2);
+}
+
+#[actix_rt::test]
+async fn outsiders_and_unknown_messages_are_not_found() {
+    let s = setup();
+    let users = FakeUsers::with(vec![s.mallory.clone()]);
+    let tasks = FakeTasks::default();
+
+    let outsider = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.mallory,
+            &uuid_of(&s.message),
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await;
+    assert!(matches!(outsider, Err(TaskError::MessageNotFound)));
+
+    let unknown = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            "00000000-0000-0000-0000-000000000000",
+            CreateTaskFromMessageRequest::default(),
+        )
+        .await;
+    assert!(matches!(unknown, Err(TaskError::MessageNotFound)));
+    assert!(tasks.tasks.lock().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn history_shows_current_status_of_linked_tasks() {
+    let s = setup();
+    let users = FakeUsers::with(vec![s.alice.clone()]);
+    let tasks = FakeTasks::default();
+    let created = s
+        .service
+        .create_from_message(
+            &tasks,
+            &users,
+            &s.alice,
+            &uuid_of(&s.message),
+            CreateTaskFromMessageRequest {
+                task_name: Some("Login crash".to_string()),
+                ..CreateTaskFromMessageRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(created.task.description, None);
+
+    let mut task = created.task.clone();
+    task.set_status(TaskStatus::InProgress);
+    let version = task.version;
+    task.version += 1;
+    tasks
+        .update_task(task.uuid.clone(), task, version)
+        .await
+        .unwrap();
+
+    let history = s.messages.messages.lock().unwrap().clone();
+    let payloads = s.service.with_tasks(&tasks, history).await;
+    assert_eq!(payloads.len(), 2);
+    for payload in &payloads {
+        let linked = payload.task.as_ref().unwrap();
+        assert_eq!(linked.task_name, "Login crash");
+        assert_eq!(linked.status, TaskStatus::InProgress);
+    }
+
+    let json = serde_json::to_value(&payloads[0]).unwrap();
+    assert_eq!(json["task"]["status"], "in_progress");
+    assert_eq!(json["content"], s.message.content.as_str());
+}
@@ -0,0 +1,228 @@
+//! Task Activity Tests Module
+//! Exercises task comments, the recorded change history, the merged timeline
+//! and watcher notifications against in-memory fakes.
+
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::TaskError;
+use chasqui_server::models::entities::task::{AddTaskRequest, Task};
+use chasqui_server::models::entities::task_activity::{
+    ActivityKind, AddCommentRequest, TaskComment, TimelineEntry,
+};
+use chasqui_server::models::entities::user::User;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeConversations, FakeTaskActivity, FakeTaskEvents, FakeTasks, FakeUsers};
+
+const OWN: TaskAccess = TaskAccess {
+    read_all: false,
+    update_all: false,
+    delete_all: false,
+};
+
+struct Setup {
+    service: TaskService,
+    tasks: FakeTasks,
+    users: FakeUsers,
+    activity: Arc<FakeTaskActivity>,
+    events: Arc<FakeTaskEvents>,
+    alice: User,
+    bob: User,
+    carol: User,
+}
+
+fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let carol = User::new_bot("carol".to_string());
+    let activity = Arc::new(FakeTaskActivity::default());
+    let events = Arc::new(FakeTaskEvents::default());
+    Setup {
+        service: TaskService::new(Arc::new(FakeConversations::default()), 20, 100)
+            .with_events(events.clone())
+            .with_activity(activity.clone()),
+        tasks: FakeTasks::default(),
+        users: FakeUsers::with(vec![alice.clone(), bob.clone(), carol.clone()]),
+        activity,
+        events,
+        alice,
+        bob,
+        carol,
+    }
+}
+
+impl Setup {
+    async fn task(&self) -> Task {
+        self.service
+            .create(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                AddTaskRequest {
+                    task_name: "Ship it".to_string(),
+                    ..AddTaskRequest::default()
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn comment(
+        &self,
+        user: &User,
+        task: &Task,
+        text: &str,
+    ) -> Result<TaskComment, TaskError> {
+        self.service
+            .add_comment(
+                &self.tasks,
+                &self.users,
+                user,
+                OWN,
+                &task.uuid,
+                AddCommentRequest {
+                    text: text.to_string(),
+                },
+            )
+            .await
+    }
+
+    // Audience of the last timeline event
+    fn notified(&self) -> Vec<String> {
+        self.events
+            .timeline
+            .lock()
+            .unwrap()
+            .last()
+            .unwrap()
+            .audience
+            .clone()
+    }
+}
+
+#[actix_rt::test]
+async fn changes_and_comments_form_one_timeline() {
+    let s = setup();
+    let task = s.task().await;
+    let patch = format!(
+        r#"{{"status": "in_progress", "assignee": "{}", "due_date": "2026-11-01T12:00:00Z"}}"#,
+        s.bob.id_string().unwrap()
+    );
+    s.service
+        .update(
+            &s.tasks,
+            &s.users,
+            &s.alice,
+            OWN,
+            &task.uuid,
+            serde_json::from_str(&patch).unwrap(),
+        )
+        .await
+        .unwrap();
+    s.comment(&s.bob, &task, "On it").await.unwrap();
+
+    let timeline = s
+        .service
+        .timeline(&s.tasks, &s.alice, OWN, &task.uuid)
+        .await
+        .unwrap();
+    let kinds: Vec<Option<ActivityKind>> = timeline
+        .iter()
+        .map(|entry| match entry {
+            TimelineEntry::Activity(a) => Some(a.kind),
+            TimelineEntry::Comment(_) => None,
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Some(ActivityKind::Created),
+            Some(ActivityKind::StatusChanged),
+            Some(ActivityKind::Reassigned),
+            Some(ActivityKind::DueDateChanged),
+            None,
+        ]
+    );
+    match &timeline[1] {
+        TimelineEntry::Activity(a) => {
+            assert_eq!(
+                (a.from.as_deref(), a.to.as_deref()),
+                (Some("todo"), Some("in_progress"))
+            )
+        }
+        other => panic!("expected a status change, got {:?}", other),
+    }
+
+    // Outsiders see neither the task nor its timeline
+    let hidden = s
+        .service
+        .timeline(&s.tasks, &s.carol, OWN, &task.uuid)
+        .await;
+    assert!(matches!(hidden, Err(TaskError::NoTaskFoundWithId)));
+    let comment = s.comment(&s.carol, &task, "Hi").await;
+    assert!(matches!(comment, Err(TaskError::NoTaskFoundWithId)));
+
+    s.service
+        .delete(&s.tasks, &s.alice, OWN, &task.uuid)
+        .await
+        .unwrap();
+    assert!(s.activity.activity.lock().unwrap().is_empty());
+    assert!(s.activity.comments.lock().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn mentions_and_watchers_decide_who_is_notified() {
+    let s = setup();
+    let alice_id = s.alice.id_string().unwrap();
+    let bob_id = s.bob.id_string().unwrap();
+    let mut task = s.task().await;
+    assert_eq!(task.watchers, vec![alice_id.clone()]);
+
+    // carol cannot see the task, so mentioning her does nothing
+    let comment = s
+        .comment(&s.alice, &task, "@Bob and @carol, thoughts?")
+        .await
+        .unwrap();
+    assert!(comment.mentions.is_empty());
+    assert_eq!(s.notified(), vec![alice_id.clone()]);
+
+    // Once assigned, bob watches the task and can be mentioned
+    let patch = format!(r#"{{"assignee": "{}"}}"#, bob_id);
+    task = s
+        .service
+        .update(
+            &s.tasks,
+            &s.users,
+            &s.alice,
+            OWN,
+            &task.uuid,
+            serde_json::from_str(&patch).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(task.watchers, vec![alice_id.clone(), bob_id.clone()]);
+    assert_eq!(s.notified(), vec![alice_id.clone(), bob_id.clone()]);
+    // Many users sharing the prefix (and sorting first) do not hide bob
+    s.users
+        .users
+        .lock()
+        .unwrap()
+        .extend((0..12).map(|i| User::new_bot(format!("Bob{}", i))));
+    let comment = s
+        .comment(&s.alice, &task, "@bob please review")
+        .await
+        .unwrap();
+    assert_eq!(comment.mentions, vec![bob_id.clone()]);
+
+    s.service
+        .set_watching(&s.tasks, &s.bob, OWN, &task.uuid, false)
+        .await
+        .unwrap();
+    s.comment(&s.alice, &task, "Anyone?").await.unwrap();
+    assert_eq!(s.notified(), vec![alice_id.clone()]);
+
+    let blank = s.comment(&s.alice, &task, "   ").await;
+    assert!(matches!(blank, Err(TaskError::InvalidTask(_))));
+}
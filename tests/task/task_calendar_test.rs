@@ -0,0 +1,221 @@
+//! Task Calendar Tests Module
+//! Exercises the iCalendar feed and `.ics` imports against in-memory fakes:
+//! feed tokens being rotated and revoked, which tasks a feed shows, and
+//! imports that skip entries they cannot turn into tasks.
+
+use chasqui_server::application::services::calendar_service::{
+    CalendarService, FEED_LOOKBACK_DAYS,
+};
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::TaskError;
+use chasqui_server::infrastructure::calendar::ics::IcsComponent;
+use chasqui_server::models::entities::task::{AddTaskRequest, Task};
+use chasqui_server::models::entities::user::User;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeCalendarFeeds, FakeConversations, FakeTasks, FakeUsers};
+
+struct Setup {
+    service: Arc<TaskService>,
+    calendar: CalendarService,
+    tasks: FakeTasks,
+    users: FakeUsers,
+    alice: User,
+    bob: User,
+}
+
+fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let service = Arc::new(TaskService::new(
+        Arc::new(FakeConversations::default()),
+        20,
+        100,
+    ));
+    Setup {
+        calendar: CalendarService::new(
+            Arc::new(FakeCalendarFeeds::default()),
+            service.clone(),
+            500,
+            3,
+        ),
+        service,
+        tasks: FakeTasks::default(),
+        users: FakeUsers::with(vec![alice.clone(), bob.clone()]),
+        alice,
+        bob,
+    }
+}
+
+fn at(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+}
+
+impl Setup {
+    async fn create(&self, user: &User, req: AddTaskRequest) -> Task {
+        self.service
+            .create(&self.tasks, &self.users, user, TaskAccess::default(), req)
+            .await
+            .unwrap()
+    }
+
+    async fn feed(&self, token: &str, component: IcsComponent) -> Result<String, TaskError> {
+        self.calendar
+            .feed(&self.tasks, &self.users, token, component)
+            .await
+    }
+}
+
+#[actix_rt::test]
+async fn feed_lists_own_dated_tasks_until_the_token_is_rotated_or_revoked() {
+    let s = setup();
+    let dated = s
+        .create(
+            &s.alice,
+            AddTaskRequest {
+                task_name: "Ship release".to_string(),
+                due_date: Some(at("2099-11-02T09:00:00Z")),
+                reminders: Some(vec![60]),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await;
+    s.create(
+        &s.alice,
+        AddTaskRequest {
+            task_name: "Someday".to_string(),
+            ..AddTaskRequest::default()
+        },
+    )
+    .await;
+    s.create(
+        &s.bob,
+        AddTaskRequest {
+            task_name: "Private errand".to_string(),
+            due_date: Some(at("2099-11-03T09:00:00Z")),
+            ..AddTaskRequest::default()
+        },
+    )
+    .await;
+
+    let first = s.calendar.create_token(&s.alice).await.unwrap();
+    assert!(first.url.ends_with(&first.token));
+    let events = s.feed(&first.token, IcsComponent::Event).await.unwrap();
+    assert!(events.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(events.contains(&format!("UID:{}@chasqui", dated.uuid)));
+    assert!(events.contains("SUMMARY:Ship release"));
+    assert!(events.contains("BEGIN:VALARM"));
+    assert!(!events.contains("Someday"));
+    assert!(!events.contains("Private errand"));
+    let todos = s.feed(&first.token, IcsComponent::Todo).await.unwrap();
+    assert!(todos.contains("BEGIN:VTODO"));
+    assert!(todos.contains("DUE:20991102T090000Z"));
+
+    // A new token replaces the old one
+    let second = s.calendar.create_token(&s.alice).await.unwrap();
+    assert_ne!(first.token, second.token);
+    let stale = s.feed(&first.token, IcsComponent::Event).await;
+    assert!(matches!(stale, Err(TaskError::InvalidFeedToken)));
+    assert!(s.feed(&second.token, IcsComponent::Event).await.is_ok());
+
+    s.calendar.revoke_token(&s.alice).await.unwrap();
+    let revoked = s.feed(&second.token, IcsComponent::Event).await;
+    assert!(matches!(revoked, Err(TaskError::InvalidFeedToken)));
+    let empty = s.feed("", IcsComponent::Event).await;
+    assert!(matches!(empty, Err(TaskError::InvalidFeedToken)));
+}
+
+#[actix_rt::test]
+async fn feed_over_the_limit_keeps_the_nearest_due_dates() {
+    let s = setup();
+    let calendar = CalendarService::new(
+        Arc::new(FakeCalendarFeeds::default()),
+        s.service.clone(),
+        2,
+        3,
+    );
+    let now = Utc::now();
+    for (name, days) in [
+        ("Long overdue", -FEED_LOOKBACK_DAYS - 1),
+        ("Next year", 365),
+        ("Tomorrow", 1),
+        ("Yesterday", -1),
+    ] {
+        s.create(
+            &s.alice,
+            AddTaskRequest {
+                task_name: name.to_string(),
+                due_date: Some(now + Duration::days(days)),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await;
+    }
+
+    let token = calendar.create_token(&s.alice).await.unwrap().token;
+    let feed = calendar
+        .feed(&s.tasks, &s.users, &token, IcsComponent::Event)
+        .await
+        .unwrap();
+    assert!(feed.contains("SUMMARY:Yesterday"));
+    assert!(feed.contains("SUMMARY:Tomorrow"));
+    assert!(!feed.contains("Next year"));
+    assert!(!feed.contains("Long overdue"));
+}
+
+#[actix_rt::test]
+async fn import_creates_tasks_and_reports_skipped_entries() {
+    let s = setup();
+    let ics = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VTODO\r\n\
+SUMMARY:Pay invoices\r\n\
+DUE;VALUE=DATE:20261105\r\n\
+RRULE:FREQ=MONTHLY;COUNT=2\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT1H\r\n\
+END:VALARM\r\n\
+END:VTODO\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Cancelled sync\r\n\
+DTSTART:20261106T100000Z\r\n\
+STATUS:CANCELLED\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20261107T100000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    let report = s
+        .calendar
+        .import(&s.tasks, &s.users, &s.alice, ics)
+        .await
+        .unwrap();
+    assert_eq!(report.created.len(), 1);
+    let task = &report.created[0];
+    assert_eq!(task.task_name, "Pay invoices");
+    assert_eq!(task.reminders, vec![60]);
+    assert!(task.recurrence.is_some());
+    assert_eq!(report.skipped.len(), 2);
+    assert_eq!(report.skipped[0].index, 1);
+    assert_eq!(report.skipped[1].index, 2);
+    assert_eq!(s.tasks.tasks.lock().unwrap().len(), 1);
+
+    let not_ics = s
+        .calendar
+        .import(&s.tasks, &s.users, &s.alice, "hello")
+        .await;
+    assert!(matches!(not_ics, Err(TaskError::InvalidCalendar(_))));
+
+    // More entries than CALENDAR_MAX_IMPORT are refused as a whole
+    let many = format!(
+        "BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n",
+        "BEGIN:VTODO\r\nSUMMARY:x\r\nEND:VTODO\r\n".repeat(4)
+    );
+    let too_many = s.calendar.import(&s.tasks, &s.users, &s.alice, &many).await;
+    assert!(matches!(too_many, Err(TaskError::InvalidCalendar(_))));
+    assert_eq!(s.tasks.tasks.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,295 @@
+//! Task Recurrence Tests Module
+//! Exercises recurring tasks and due-date reminders against in-memory fakes:
+//! spawning the next occurrence, the end of a series, and reminders delivered
+//! live or kept for offline users.
+
+use chasqui_server::application::services::notification_service::NotificationService;
+use chasqui_server::application::services::task_reminder_service::TaskReminderService;
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::TaskError;
+use chasqui_server::models::entities::recurrence::RecurrenceRule;
+use chasqui_server::models::entities::task::{AddTaskRequest, Task, TaskStatus};
+use chasqui_server::models::entities::user::User;
+use chasqui_server::models::traits::task_data_trait::TaskDataTrait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeConversations, FakeNotificationSink, FakeNotifications, FakeTasks, FakeUsers};
+
+const OWN: TaskAccess = TaskAccess {
+    read_all: false,
+    update_all: false,
+    delete_all: false,
+};
+
+struct Setup {
+    service: TaskService,
+    tasks: FakeTasks,
+    users: FakeUsers,
+    alice: User,
+    bob: User,
+}
+
+fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    Setup {
+        service: TaskService::new(Arc::new(FakeConversations::default()), 20, 100),
+        tasks: FakeTasks::default(),
+        users: FakeUsers::with(vec![alice.clone(), bob.clone()]),
+        alice,
+        bob,
+    }
+}
+
+fn at(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+}
+
+impl Setup {
+    async fn create(&self, req: AddTaskRequest) -> Result<Task, TaskError> {
+        self.service
+            .create(&self.tasks, &self.users, &self.alice, OWN, req)
+            .await
+    }
+
+    async fn patch(&self, task: &Task, patch: &str) -> Task {
+        self.service
+            .update(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                &task.uuid,
+                serde_json::from_str(patch).unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    fn stored(&self) -> Vec<Task> {
+        self.tasks.tasks.lock().unwrap().clone()
+    }
+}
+
+#[actix_rt::test]
+async fn completing_an_occurrence_spawns_the_next_until_the_series_ends() {
+    let s = setup();
+    let rule = RecurrenceRule::from_rrule("FREQ=WEEKLY;COUNT=3").unwrap();
+    let first = s
+        .create(AddTaskRequest {
+            task_name: "Weekly report".to_string(),
+            due_date: Some(at("2026-11-02T09:00:00Z")),
+            recurrence: Some(rule.clone()),
+            reminders: Some(vec![30, 1440, 30]),
+            ..AddTaskRequest::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(first.reminders, vec![1440, 30]);
+
+    let done = s.patch(&first, r#"{"status": "done"}"#).await;
+    let tasks = s.stored();
+    assert_eq!(tasks.len(), 2);
+    let second = tasks.iter().find(|t| t.uuid != first.uuid).unwrap().clone();
+    assert_eq!(
+        done.recurrence.as_ref().unwrap().next_task.as_deref(),
+        Some(second.uuid.as_str())
+    );
+    assert_eq!(second.status, TaskStatus::Todo);
+    assert_eq!(second.due_date, Some(at("2026-11-09T09:00:00Z")));
+    assert_eq!(second.reminders, vec![1440, 30]);
+    assert_eq!(second.recurrence.as_ref().unwrap().index, 1);
+
+    // Reopening and completing again does not spawn a second copy
+    s.patch(&done, r#"{"status": "todo"}"#).await;
+    s.patch(&done, r#"{"status": "done"}"#).await;
+    assert_eq!(s.stored().len(), 2);
+
+    // The third occurrence is the last one (COUNT=3)
+    s.patch(&second, r#"{"status": "done"}"#).await;
+    let third = s
+        .stored()
+        .into_iter()
+        .find(|t| t.recurrence.as_ref().is_some_and(|r| r.index == 2))
+        .unwrap();
+    assert_eq!(third.due_date, Some(at("2026-11-16T09:00:00Z")));
+    let last = s.patch(&third, r#"{"status": "done"}"#).await;
+    assert_eq!(s.stored().len(), 3);
+    assert!(last.recurrence.unwrap().next_task.is_none());
+
+    // A series needs a due date
+    let undated = s
+        .create(AddTaskRequest {
+            task_name: "Someday".to_string(),
+            recurrence: Some(rule),
+            ..AddTaskRequest::default()
+        })
+        .await;
+    assert!(matches!(undated, Err(TaskError::InvalidTask(_))));
+    let too_many = s
+        .create(AddTaskRequest {
+            task_name: "Nag".to_string(),
+            due_date: Some(at("2026-11-02T09:00:00Z")),
+            reminders: Some((1..=11).collect()),
+            ..AddTaskRequest::default()
+        })
+        .await;
+    assert!(matches!(too_many, Err(TaskError::InvalidTask(_))));
+}
+
+#[actix_rt::test]
+async fn reminders_are_sent_once_per_due_date_live_or_kept_for_offline_users() {
+    let s = setup();
+    let repo = Arc::new(FakeNotifications::default());
+    let sink = Arc::new(FakeNotificationSink::default());
+    let notifications = Arc::new(NotificationService::new(repo.clone(), sink.clone()));
+    let reminders =
+        TaskReminderService::new(notifications.clone(), std::time::Duration::from_secs(60));
+    let bob_id = s.bob.id_string().unwrap();
+    let task = s
+        .create(AddTaskRequest {
+            task_name: "Renew certificate".to_string(),
+            assignee: Some(bob_id.clone()),
+            due_date: Some(at("2026-11-02T09:00:00Z")),
+            reminders: Some(vec![1440, 60]),
+            ..AddTaskRequest::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        reminders
+            .run_due(&s.tasks, at("2026-10-31T09:00:00Z"))
+            .await,
+        0
+    );
+
+    // Both reminders are reached while bob is offline: one notification is kept
+    let now = at("2026-11-02T08:30:00Z");
+    assert_eq!(reminders.run_due(&s.tasks, now).await, 1);
+    assert_eq!(reminders.run_due(&s.tasks, now).await, 0);
+    let kept = notifications.list(&s.bob, true).await.unwrap();
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].minutes_before, Some(60));
+    assert_eq!(kept[0].task_id, task.uuid);
+
+    // Only bob can mark the notification read
+    let foreign = notifications.mark_read(&s.alice, &kept[0].uuid).await;
+    assert!(matches!(foreign, Err(TaskError::NotificationNotFound)));
+    notifications
+        .mark_read(&s.bob, &kept[0].uuid)
+        .await
+        .unwrap();
+    assert!(notifications.list(&s.bob, true).await.unwrap().is_empty());
+
+    // Moving the due date re-arms the reminders; bob is online now
+    s.patch(&task, r#"{"due_date": "2026-11-09T09:00:00Z"}"#)
+        .await;
+    sink.online.lock().unwrap().push(bob_id.clone());
+    assert_eq!(
+        reminders
+            .run_due(&s.tasks, at("2026-11-08T09:00:00Z"))
+            .await,
+        1
+    );
+    let delivered = sink.delivered.lock().unwrap().clone();
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].0, bob_id);
+    let payload: serde_json::Value = serde_json::from_str(&delivered[0].1).unwrap();
+    assert_eq!(payload["type"], "TaskReminder");
+    assert_eq!(payload["minutes_before"], 1440);
+    assert_eq!(notifications.list(&s.bob, false).await.unwrap().len(), 1);
+
+    // Done tasks are not reminded
+    s.patch(&task, r#"{"status": "done"}"#).await;
+    assert_eq!(
+        reminders
+            .run_due(&s.tasks, at("2026-11-09T08:30:00Z"))
+            .await,
+        0
+    );
+}
+
+#[actix_rt::test]
+async fn writes_computed_before_a_reminder_was_sent_do_not_re_arm_it() {
+    let s = setup();
+    let repo = Arc::new(FakeNotifications::default());
+    let sink = Arc::new(FakeNotificationSink::default());
+    let notifications = Arc::new(NotificationService::new(repo, sink));
+    let reminders = TaskReminderService::new(notifications, std::time::Duration::from_secs(60));
+    let task = s
+        .create(AddTaskRequest {
+            task_name: "Renew certificate".to_string(),
+            assignee: Some(s.bob.id_string().unwrap()),
+            due_date: Some(at("2026-11-02T09:00:00Z")),
+            reminders: Some(vec![60]),
+            ..AddTaskRequest::default()
+        })
+        .await
+        .unwrap();
+
+    let now = at("2026-11-02T08:30:00Z");
+    assert_eq!(reminders.run_due(&s.tasks, now).await, 1);
+    assert_eq!(s.stored()[0].reminded, vec![60]);
+
+    // A copy read before the reminder went out is refused...
+    let mut stale = task.clone();
+    stale.task_name = "Renew certificates".to_string();
+    stale.version += 1;
+    assert!(s
+        .tasks
+        .update_task(task.uuid.clone(), stale, task.version)
+        .await
+        .is_none());
+
+    // ...while updates through the service are applied on the newer task
+    let patched = s.patch(&task, r#"{"description": "Both domains"}"#).await;
+    assert_eq!(patched.reminded, vec![60]);
+    assert_eq!(patched.version, s.stored()[0].version);
+    assert_eq!(reminders.run_due(&s.tasks, now).await, 0);
+}
+
+#[actix_rt::test]
+async fn a_reminder_read_before_the_due_date_moved_does_not_disarm_the_new_one() {
+    let s = setup();
+    let repo = Arc::new(FakeNotifications::default());
+    let sink = Arc::new(FakeNotificationSink::default());
+    let notifications = Arc::new(NotificationService::new(repo, sink));
+    let reminders = TaskReminderService::new(notifications, std::time::Duration::from_secs(60));
+    let task = s
+        .create(AddTaskRequest {
+            task_name: "Renew certificate".to_string(),
+            assignee: Some(s.bob.id_string().unwrap()),
+            due_date: Some(at("2026-11-02T09:00:00Z")),
+            reminders: Some(vec![60]),
+            ..AddTaskRequest::default()
+        })
+        .await
+        .unwrap();
+
+    // The scheduler read the task, then the due date moved
+    s.tasks
+        .stale_reminder_reads
+        .lock()
+        .unwrap()
+        .push(task.clone());
+    s.patch(&task, r#"{"due_date": "2026-11-09T09:00:00Z"}"#)
+        .await;
+    let now = at("2026-11-02T08:30:00Z");
+    assert_eq!(reminders.run_due(&s.tasks, now).await, 0);
+    assert!(s.stored()[0].reminded.is_empty());
+
+    s.tasks.stale_reminder_reads.lock().unwrap().clear();
+    let later = at("2026-11-09T08:30:00Z");
+    assert_eq!(reminders.run_due(&s.tasks, later).await, 1);
+    // Tasks whose reminders were all sent are not read again
+    assert!(s
+        .tasks
+        .find_tasks_with_reminders(later)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,572 @@
+//! Task Service Tests Module
+//! Exercises task creation, partial updates, deletion and the ownership rules
+//! against in-memory fakes.
+
+use chasqui_server::application::services::task_service::{TaskAccess, TaskQuery, TaskService};
+use chasqui_server::error::TaskError;
+use chasqui_server::models::entities::conversation::Conversation;
+use chasqui_server::models::entities::pagination::Page;
+use chasqui_server::models::entities::task::{
+    AddTaskRequest, Task, TaskEventKind, TaskPriority, TaskStatus, UpdateTaskRequest,
+};
+use chasqui_server::models::entities::user::User;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeConversations, FakeTaskEvents, FakeTasks, FakeUsers};
+
+const OWN: TaskAccess = TaskAccess {
+    read_all: false,
+    update_all: false,
+    delete_all: false,
+};
+
+fn patch(json: &str) -> UpdateTaskRequest {
+    serde_json::from_str(json).unwrap()
+}
+
+fn service() -> TaskService {
+    TaskService::new(Arc::new(FakeConversations::default()), 20, 100)
+}
+
+fn names(tasks: Vec<Task>) -> Vec<String> {
+    tasks.into_iter().map(|t| t.task_name).collect()
+}
+
+#[actix_rt::test]
+async fn create_records_creator_and_checks_assignee() {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let users = FakeUsers::with(vec![alice.clone(), bob.clone()]);
+    let tasks = FakeTasks::default();
+    let service = service();
+
+    let due = Utc::now() + Duration::days(2);
+    let task = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "  Ship release  ".to_string(),
+                priority: Some(TaskPriority::High),
+                assignee: bob.id_string(),
+                due_date: Some(due),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(task.task_name, "Ship release");
+    assert_eq!(task.status, TaskStatus::Todo);
+    assert_eq!(task.creator, alice.id_string());
+    assert_eq!(task.assignee, bob.id_string());
+    assert_eq!(task.due_date, Some(due));
+
+    let unknown = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Orphan".to_string(),
+                assignee: Some("nobody".to_string()),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await;
+    assert!(matches!(unknown, Err(TaskError::AssigneeNotFound)));
+
+    let blank = service
+        .create(&tasks, &users, &alice, OWN, AddTaskRequest::default())
+        .await;
+    assert!(matches!(blank, Err(TaskError::InvalidTask(_))));
+    assert_eq!(tasks.tasks.lock().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn patch_changes_only_given_fields() {
+    let alice = User::new_bot("alice".to_string());
+    let users = FakeUsers::with(vec![alice.clone()]);
+    let tasks = FakeTasks::default();
+    let service = service();
+    let task = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Review PR".to_string(),
+                description: Some("Look at the parser".to_string()),
+                assignee: alice.id_string(),
+                due_date: Some(Utc::now()),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let done = service
+        .update(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            &task.uuid,
+            patch(r#"{"status": "done", "due_date": null}"#),
+        )
+        .await
+        .unwrap();
+    assert_eq!(done.task_name, "Review PR");
+    assert_eq!(done.description.as_deref(), Some("Look at the parser"));
+    assert_eq!(done.assignee, alice.id_string());
+    assert_eq!(done.due_date, None);
+    assert_eq!(done.status, TaskStatus::Done);
+    assert!(done.completed_at.is_some());
+    assert_eq!(tasks.tasks.lock().unwrap()[0], done);
+
+    let reopened = service
+        .update(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            &task.uuid,
+            patch(r#"{"status": "in_progress", "assignee": null}"#),
+        )
+        .await
+        .unwrap();
+    assert_eq!(reopened.completed_at, None);
+    assert_eq!(reopened.assignee, None);
+
+    let invalid = service
+        .update(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            &task.uuid,
+            patch(r#"{"task_name": ""}"#),
+        )
+        .await;
+    assert!(matches!(invalid, Err(TaskError::InvalidTask(_))));
+    let missing = service
+        .update(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            "missing",
+            patch(r#"{"priority": "low"}"#),
+        )
+        .await;
+    assert!(matches!(missing, Err(TaskError::NoTaskFoundWithId)));
+}
+
+#[actix_rt::test]
+async fn delete_removes_task_once() {
+    let alice = User::new_bot("alice".to_string());
+    let users = FakeUsers::with(vec![alice.clone()]);
+    let tasks = FakeTasks::default();
+    let service = service();
+    let task = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Temporary".to_string(),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    service
+        .delete(&tasks, &alice, OWN, &task.uuid)
+        .await
+        .unwrap();
+    assert!(tasks.tasks.lock().unwrap().is_empty());
+    assert!(matches!(
+        service.delete(&tasks, &alice, OWN, &task.uuid).await,
+        Err(TaskError::NoTaskFoundWithId)
+    ));
+}
+
+#[actix_rt::test]
+async fn only_involved_users_see_and_change_tasks() {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let carol = User::new_bot("carol".to_string());
+    let users = FakeUsers::with(vec![alice.clone(), bob.clone(), carol.clone()]);
+    let conversation =
+        Conversation::new_direct(alice.id.clone().unwrap(), carol.id.clone().unwrap());
+    let conversation_id = conversation.id.clone().unwrap().id.to_raw();
+    let conversations = Arc::new(FakeConversations::default());
+    conversations
+        .conversations
+        .lock()
+        .unwrap()
+        .push(conversation);
+    let tasks = FakeTasks::default();
+    let service = TaskService::new(conversations, 20, 100);
+
+    let private = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Private".to_string(),
+                assignee: bob.id_string(),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    let shared = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Shared".to_string(),
+                conversation_id: Some(conversation_id.clone()),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    // Bob does not take part in the conversation
+    let outsider = service
+        .create(
+            &tasks,
+            &users,
+            &bob,
+            OWN,
+            AddTaskRequest {
+                task_name: "Sneaky".to_string(),
+                conversation_id: Some(conversation_id),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await;
+    assert!(matches!(outsider, Err(TaskError::ConversationNotFound)));
+
+    assert_eq!(
+        names(
+            service
+                .list(&tasks, &alice, OWN, TaskQuery::default())
+                .await
+                .unwrap()
+                .items
+        ),
+        vec!["Private", "Shared"]
+    );
+    assert_eq!(
+        names(
+            service
+                .list(&tasks, &bob, OWN, TaskQuery::default())
+                .await
+                .unwrap()
+                .items
+        ),
+        vec!["Private"]
+    );
+    assert_eq!(
+        names(
+            service
+                .list(&tasks, &carol, OWN, TaskQuery::default())
+                .await
+                .unwrap()
+                .items
+        ),
+        vec!["Shared"]
+    );
+
+    // The assignee may edit but not delete; a participant may only look
+    let edited = service
+        .update(
+            &tasks,
+            &users,
+            &bob,
+            OWN,
+            &private.uuid,
+            patch(r#"{"status": "in_progress"}"#),
+        )
+        .await
+        .unwrap();
+    assert_eq!(edited.status, TaskStatus::InProgress);
+    assert!(matches!(
+        service.delete(&tasks, &bob, OWN, &private.uuid).await,
+        Err(TaskError::NotAllowed)
+    ));
+    assert!(matches!(
+        service
+            .update(
+                &tasks,
+                &users,
+                &carol,
+                OWN,
+                &shared.uuid,
+                patch(r#"{"priority": "low"}"#)
+            )
+            .await,
+        Err(TaskError::NotAllowed)
+    ));
+    assert!(matches!(
+        service
+            .update(
+                &tasks,
+                &users,
+                &carol,
+                OWN,
+                &private.uuid,
+                patch(r#"{"priority": "low"}"#)
+            )
+            .await,
+        Err(TaskError::NoTaskFoundWithId)
+    ));
+
+    // task:update / task:delete reach every task
+    let moderator = TaskAccess {
+        read_all: true,
+        update_all: true,
+        delete_all: true,
+    };
+    assert_eq!(
+        service
+            .list(&tasks, &carol, moderator, TaskQuery::default())
+            .await
+            .unwrap()
+            .items
+            .len(),
+        2
+    );
+    service
+        .update(
+            &tasks,
+            &users,
+            &carol,
+            moderator,
+            &private.uuid,
+            patch(r#"{"priority": "low"}"#),
+        )
+        .await
+        .unwrap();
+    service
+        .delete(&tasks, &carol, moderator, &private.uuid)
+        .await
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn listing_filters_sorts_and_pages() {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let users = FakeUsers::with(vec![alice.clone(), bob.clone()]);
+    let tasks = FakeTasks::default();
+    let service = TaskService::new(Arc::new(FakeConversations::default()), 2, 100);
+    let now = Utc::now();
+    for (name, priority, due_in, assignee) in [
+        (
+            "Fix login bug",
+            TaskPriority::Urgent,
+            Some(1),
+            bob.id_string(),
+        ),
+        ("Write release notes", TaskPriority::Low, Some(5), None),
+        (
+            "Review login flow",
+            TaskPriority::High,
+            None,
+            bob.id_string(),
+        ),
+        ("Plan sprint", TaskPriority::Medium, Some(2), None),
+    ] {
+        service
+            .create(
+                &tasks,
+                &users,
+                &alice,
+                OWN,
+                AddTaskRequest {
+                    task_name: name.to_string(),
+                    priority: Some(priority),
+                    due_date: due_in.map(|days| now + Duration::days(days)),
+                    assignee,
+                    ..AddTaskRequest::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+    let list = |query: TaskQuery| service.list(&tasks, &alice, OWN, query);
+
+    // Pages of two by priority, most urgent first, following the cursor
+    let first: Page<Task> = list(TaskQuery {
+        sort: Some("-priority".to_string()),
+        ..TaskQuery::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        names(first.items),
+        vec!["Fix login bug", "Review login flow"]
+    );
+    let second = list(TaskQuery {
+        sort: Some("-priority".to_string()),
+        cursor: first.next_cursor,
+        ..TaskQuery::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        names(second.items),
+        vec!["Plan sprint", "Write release notes"]
+    );
+    assert_eq!(second.next_cursor, None);
+
+    // Filters combine; tasks without a due date sort last
+    let due_soon = list(TaskQuery {
+        due_before: Some((now + Duration::days(3)).to_rfc3339()),
+        sort: Some("due_date".to_string()),
+        ..TaskQuery::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(names(due_soon.items), vec!["Fix login bug", "Plan sprint"]);
+    let login = list(TaskQuery {
+        q: Some("LOGIN".to_string()),
+        assignee: bob.id_string(),
+        limit: Some(10),
+        ..TaskQuery::default()
+    })
+    .await
+    .unwrap();
+    assert_eq!(login.items.len(), 2);
+
+    // No match is an empty page, not an error
+    let none = list(TaskQuery {
+        status: Some("done".to_string()),
+        ..TaskQuery::default()
+    })
+    .await
+    .unwrap();
+    assert!(none.items.is_empty());
+    assert_eq!(none.next_cursor, None);
+
+    for bad in [
+        TaskQuery {
+            status: Some("archived".to_string()),
+            ..TaskQuery::default()
+        },
+        TaskQuery {
+            sort: Some("name".to_string()),
+            ..TaskQuery::default()
+        },
+        TaskQuery {
+            cursor: Some("garbage".to_string()),
+            ..TaskQuery::default()
+        },
+        TaskQuery {
+            limit: Some(0),
+            ..TaskQuery::default()
+        },
+    ] {
+        assert!(matches!(list(bad).await, Err(TaskError::InvalidQuery(_))));
+    }
+}
+
+#[actix_rt::test]
+async fn changes_are_published_to_everyone_who_can_see_the_task() {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let carol = User::new_bot("carol".to_string());
+    let dave = User::new_bot("dave".to_string());
+    let users = FakeUsers::with(vec![
+        alice.clone(),
+        bob.clone(),
+        carol.clone(),
+        dave.clone(),
+    ]);
+    let conversation =
+        Conversation::new_direct(alice.id.clone().unwrap(), carol.id.clone().unwrap());
+    let conversation_id = conversation.id.clone().unwrap().id.to_raw();
+    let conversations = Arc::new(FakeConversations::default());
+    conversations
+        .conversations
+        .lock()
+        .unwrap()
+        .push(conversation);
+    let events = Arc::new(FakeTaskEvents::default());
+    let tasks = FakeTasks::default();
+    let service = TaskService::new(conversations, 20, 100).with_events(events.clone());
+    let audience = |users: &[&User]| {
+        let mut ids: Vec<String> = users.iter().filter_map(|u| u.id_string()).collect();
+        ids.sort();
+        ids
+    };
+
+    let task = service
+        .create(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            AddTaskRequest {
+                task_name: "Shared".to_string(),
+                assignee: bob.id_string(),
+                conversation_id: Some(conversation_id),
+                ..AddTaskRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    service
+        .update(
+            &tasks,
+            &users,
+            &alice,
+            OWN,
+            &task.uuid,
+            patch(&format!(
+                r#"{{"assignee": "{}"}}"#,
+                dave.id_string().unwrap()
+            )),
+        )
+        .await
+        .unwrap();
+    service
+        .delete(&tasks, &alice, OWN, &task.uuid)
+        .await
+        .unwrap();
+
+    let published = events.events.lock().unwrap();
+    let kinds: Vec<TaskEventKind> = published.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TaskEventKind::TaskCreated,
+            TaskEventKind::TaskUpdated,
+            TaskEventKind::TaskDeleted
+        ]
+    );
+    assert_eq!(published[0].audience, audience(&[&alice, &bob, &carol]));
+    // Bob was unassigned by the update and still hears about it
+    assert_eq!(
+        published[1].audience,
+        audience(&[&alice, &bob, &carol, &dave])
+    );
+    assert_eq!(published[1].task.assignee, dave.id_string());
+    assert_eq!(published[2].audience, audience(&[&alice, &carol, &dave]));
+    assert!(published[2].payload().contains(r#""type":"TaskDeleted""#));
+}
@@ -0,0 +1,298 @@
+//! Task Structure Tests Module
+//! Exercises subtasks, checklists and blocking relations: cycle rejection,
+//! progress rollup and the `done` gate, against in-memory fakes.
+
+use chasqui_server::application::services::task_service::{TaskAccess, TaskService};
+use chasqui_server::error::TaskError;
+use chasqui_server::models::entities::task::{
+    AddChecklistItemRequest, AddTaskRequest, Task, TaskStatus, UpdateChecklistItemRequest,
+    UpdateTaskRequest,
+};
+use chasqui_server::models::entities::user::User;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeConversations, FakeTasks, FakeUsers};
+
+const OWN: TaskAccess = TaskAccess {
+    read_all: false,
+    update_all: false,
+    delete_all: false,
+};
+
+fn patch(json: &str) -> UpdateTaskRequest {
+    serde_json::from_str(json).unwrap()
+}
+
+struct Setup {
+    service: TaskService,
+    tasks: FakeTasks,
+    users: FakeUsers,
+    alice: User,
+}
+
+fn setup() -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    Setup {
+        service: TaskService::new(Arc::new(FakeConversations::default()), 20, 100),
+        tasks: FakeTasks::default(),
+        users: FakeUsers::with(vec![alice.clone()]),
+        alice,
+    }
+}
+
+impl Setup {
+    async fn task(&self, name: &str, parent: Option<&Task>) -> Task {
+        self.service
+            .create(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                AddTaskRequest {
+                    task_name: name.to_string(),
+                    parent: parent.map(|p| p.uuid.clone()),
+                    ..AddTaskRequest::default()
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn update(&self, task: &Task, json: &str) -> Result<Task, TaskError> {
+        self.service
+            .update(
+                &self.tasks,
+                &self.users,
+                &self.alice,
+                OWN,
+                &task.uuid,
+                patch(json),
+            )
+            .await
+    }
+
+    async fn stored(&self, task: &Task) -> Task {
+        self.tasks
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.uuid == task.uuid)
+            .cloned()
+            .unwrap()
+    }
+}
+
+#[actix_rt::test]
+async fn subtasks_roll_progress_up_to_the_parent() {
+    let s = setup();
+    let epic = s.task("Epic", None).await;
+    let first = s.task("First", Some(&epic)).await;
+    let second = s.task("Second", Some(&epic)).await;
+
+    let progress = s.stored(&epic).await.progress.unwrap();
+    assert_eq!((progress.done, progress.total), (0, 2));
+
+    s.update(&first, r#"{"status": "done"}"#).await.unwrap();
+    assert_eq!(s.stored(&epic).await.progress.unwrap().percent, 50);
+
+    // Moving a subtask away updates the former parent
+    s.update(&second, r#"{"parent": null}"#).await.unwrap();
+    let progress = s.stored(&epic).await.progress.unwrap();
+    assert_eq!((progress.done, progress.total), (1, 1));
+
+    let detail = s
+        .service
+        .detail(&s.tasks, &s.alice, OWN, &epic.uuid)
+        .await
+        .unwrap();
+    assert_eq!(detail.subtasks.len(), 1);
+    assert_eq!(detail.subtasks[0].uuid, first.uuid);
+
+    s.service
+        .delete(&s.tasks, &s.alice, OWN, &first.uuid)
+        .await
+        .unwrap();
+    assert_eq!(s.stored(&epic).await.progress, None);
+}
+
+#[actix_rt::test]
+async fn parent_and_dependency_cycles_are_rejected() {
+    let s = setup();
+    let root = s.task("Root", None).await;
+    let child = s.task("Child", Some(&root)).await;
+    let grandchild = s.task("Grandchild", Some(&child)).await;
+
+    let parent_cycle = s
+        .update(&root, &format!(r#"{{"parent": "{}"}}"#, grandchild.uuid))
+        .await;
+    assert!(matches!(parent_cycle, Err(TaskError::DependencyCycle)));
+    let own_parent = s
+        .update(&root, &format!(r#"{{"parent": "{}"}}"#, root.uuid))
+        .await;
+    assert!(matches!(own_parent, Err(TaskError::DependencyCycle)));
+
+    let a = s.task("A", None).await;
+    let b = s.task("B", None).await;
+    let c = s.task("C", None).await;
+    let depend = |task: &Task, blocker: &Task| {
+        let (task, blocker) = (task.uuid.clone(), blocker.uuid.clone());
+        let s = &s;
+        async move {
+            s.service
+                .add_dependency(&s.tasks, &s.alice, OWN, &task, &blocker)
+                .await
+        }
+    };
+    depend(&a, &b).await.unwrap();
+    let detail = depend(&b, &c).await.unwrap();
+    assert_eq!(detail.blocks[0].uuid, a.uuid);
+    assert!(matches!(
+        depend(&c, &a).await,
+        Err(TaskError::DependencyCycle)
+    ));
+    assert!(matches!(
+        depend(&a, &a).await,
+        Err(TaskError::DependencyCycle)
+    ));
+    assert_eq!(s.tasks.dependencies.lock().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn open_blockers_prevent_done_unless_forced() {
+    let s = setup();
+    let release = s.task("Release", None).await;
+    let tests = s.task("Tests", None).await;
+    s.service
+        .add_dependency(&s.tasks, &s.alice, OWN, &release.uuid, &tests.uuid)
+        .await
+        .unwrap();
+
+    match s.update(&release, r#"{"status": "done"}"#).await {
+        Err(TaskError::BlockedBy(open)) => assert_eq!(open, vec![tests.uuid.clone()]),
+        other => panic!("expected BlockedBy, got {:?}", other),
+    }
+
+    let mut forced = patch(r#"{"status": "done"}"#);
+    forced.force = true;
+    let done = s
+        .service
+        .update(&s.tasks, &s.users, &s.alice, OWN, &release.uuid, forced)
+        .await
+        .unwrap();
+    assert_eq!(done.status, TaskStatus::Done);
+
+    // Once the blocker is done the gate opens
+    s.update(&release, r#"{"status": "todo"}"#).await.unwrap();
+    s.update(&tests, r#"{"status": "done"}"#).await.unwrap();
+    s.update(&release, r#"{"status": "done"}"#).await.unwrap();
+}
+
+#[actix_rt::test]
+async fn checklist_items_are_ordered_and_counted() {
+    let s = setup();
+    let task = s.task("Onboarding", None).await;
+    let add = |text: &str, position: Option<usize>| AddChecklistItemRequest {
+        text: text.to_string(),
+        position,
+    };
+
+    s.service
+        .add_checklist_item(&s.tasks, &s.alice, OWN, &task.uuid, add("Laptop", None))
+        .await
+        .unwrap();
+    s.service
+        .add_checklist_item(&s.tasks, &s.alice, OWN, &task.uuid, add("Badge", None))
+        .await
+        .unwrap();
+    let updated = s
+        .service
+        .add_checklist_item(
+            &s.tasks,
+            &s.alice,
+            OWN,
+            &task.uuid,
+            add("Contract", Some(0)),
+        )
+        .await
+        .unwrap();
+    let texts: Vec<&str> = updated.checklist.iter().map(|i| i.text.as_str()).collect();
+    assert_eq!(texts, vec!["Contract", "Laptop", "Badge"]);
+
+    let badge = updated.checklist[2].id.clone();
+    let updated = s
+        .service
+        .update_checklist_item(
+            &s.tasks,
+            &s.alice,
+            OWN,
+            &task.uuid,
+            &badge,
+            UpdateChecklistItemRequest {
+                done: Some(true),
+                position: Some(0),
+                ..UpdateChecklistItemRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.checklist[0].text, "Badge");
+    assert!(updated.checklist[0].done);
+    let progress = updated.progress.unwrap();
+    assert_eq!(
+        (progress.done, progress.total, progress.percent),
+        (1, 3, 33)
+    );
+
+    let missing = s
+        .service
+        .remove_checklist_item(&s.tasks, &s.alice, OWN, &task.uuid, "nope")
+        .await;
+    assert!(matches!(missing, Err(TaskError::ChecklistItemNotFound)));
+    let updated = s
+        .service
+        .remove_checklist_item(&s.tasks, &s.alice, OWN, &task.uuid, &badge)
+        .await
+        .unwrap();
+    assert_eq!(updated.checklist.len(), 2);
+    assert_eq!(updated.progress.unwrap().done, 0);
+}
+
+#[actix_rt::test]
+async fn subtasks_need_a_visible_parent_and_are_dropped_if_it_cannot_be_set() {
+    let s = setup();
+    let bob = User::new_bot("bob".to_string());
+    let root = s.task("Root", None).await;
+    let under_root = |name: &str| AddTaskRequest {
+        task_name: name.to_string(),
+        parent: Some(root.uuid.clone()),
+        ..AddTaskRequest::default()
+    };
+
+    // bob cannot see alice's task, unless task:read lets him see all tasks
+    let hidden = s
+        .service
+        .create(&s.tasks, &s.users, &bob, OWN, under_root("Peek"))
+        .await;
+    assert!(matches!(hidden, Err(TaskError::NoTaskFoundWithId)));
+    let reader = TaskAccess {
+        read_all: true,
+        ..OWN
+    };
+    s.service
+        .create(&s.tasks, &s.users, &bob, reader, under_root("Review"))
+        .await
+        .unwrap();
+    assert_eq!(s.tasks.tasks.lock().unwrap().len(), 2);
+
+    // A task whose parent cannot be stored is not left behind at the top level
+    *s.tasks.fail_set_parent.lock().unwrap() = true;
+    let failed = s
+        .service
+        .create(&s.tasks, &s.users, &s.alice, OWN, under_root("Orphan"))
+        .await;
+    assert!(matches!(failed, Err(TaskError::DatabaseError)));
+    assert_eq!(s.tasks.tasks.lock().unwrap().len(), 2);
+}
@@ -1,6 +1,6 @@
 use crate::common::mocks::user_repository::UserRepository;
 use crate::common::test_utils::{create_mock_user_repository, create_test_user};
-use actix_crud::models::entities::user::User;
+use chasqui_server::models::entities::user::User;
 
 #[tokio::test]
 async fn test_create_user() {
@@ -0,0 +1,240 @@
+//! Account Deletion Tests Module
+//! Exercises deactivation, scheduled deletion with its grace period, message
+//! and task anonymization and the personal data export against in-memory fakes.
+
+use chasqui_server::application::services::account_deletion_service::{
+    reactivate_on_login, AccountDeletionService,
+};
+use chasqui_server::application::services::data_export_service::DataExportService;
+use chasqui_server::models::entities::audit_event::AuditAction;
+use chasqui_server::models::entities::board::Project;
+use chasqui_server::models::entities::calendar::CalendarFeed;
+use chasqui_server::models::entities::conversation::Conversation;
+use chasqui_server::models::entities::message::Message;
+use chasqui_server::models::entities::notification::Notification;
+use chasqui_server::models::entities::task::Task;
+use chasqui_server::models::entities::task_activity::{ActivityKind, TaskActivity, TaskComment};
+use chasqui_server::models::entities::user::{
+    deleted_user_thing, User, DELETED_USER_ID, DELETED_USER_NAME,
+};
+use chasqui_server::models::traits::user_data_trait::{UserDataTrait, UserSearch};
+use chrono::{Duration, Utc};
+use std::io::Read;
+use std::sync::Arc;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::{FakeAccountDeletions, FakeAuditLog, FakeConversations, FakeMessages, FakeUsers};
+
+struct Setup {
+    service: AccountDeletionService,
+    users: Arc<FakeUsers>,
+    messages: Arc<FakeMessages>,
+    deletions: Arc<FakeAccountDeletions>,
+    audit: Arc<FakeAuditLog>,
+    alice: User,
+    bob: User,
+}
+
+fn setup(grace: Duration) -> Setup {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let users = Arc::new(FakeUsers::with(vec![alice.clone(), bob.clone()]));
+    let messages = Arc::new(FakeMessages::default());
+    let audit = Arc::new(FakeAuditLog::default());
+    let deletions = Arc::new(FakeAccountDeletions::new(users.clone(), messages.clone()));
+    let service = AccountDeletionService::new(
+        deletions.clone(),
+        audit.clone(),
+        grace,
+        std::time::Duration::from_secs(60),
+    );
+    Setup {
+        service,
+        users,
+        messages,
+        deletions,
+        audit,
+        alice,
+        bob,
+    }
+}
+
+fn directory() -> UserSearch {
+    UserSearch {
+        limit: 10,
+        ..UserSearch::default()
+    }
+}
+
+#[actix_rt::test]
+async fn deletion_is_cancelled_by_signing_in_during_grace_period() {
+    let s = setup(Duration::days(30));
+
+    let scheduled = s
+        .service
+        .schedule_deletion(s.users.as_ref(), &s.alice)
+        .await
+        .unwrap();
+    assert!(scheduled.is_deactivated());
+    assert!(scheduled.deletion_scheduled_at.unwrap() > Utc::now() + Duration::days(29));
+    assert_eq!(scheduled.token_version, s.alice.token_version + 1);
+    assert_eq!(s.users.search_users(&directory()).await.len(), 1);
+
+    // Not due yet: nothing is deleted
+    assert_eq!(s.service.purge_due().await, 0);
+
+    let back = reactivate_on_login(s.users.as_ref(), scheduled)
+        .await
+        .unwrap();
+    assert!(!back.is_deactivated());
+    assert_eq!(back.deletion_scheduled_at, None);
+    assert_eq!(s.users.search_users(&directory()).await.len(), 2);
+}
+
+#[actix_rt::test]
+async fn due_accounts_are_deleted_and_their_messages_anonymized() {
+    let s = setup(Duration::zero());
+    let alice_id = s.alice.id.clone().unwrap();
+    let bob_id = s.bob.id.clone().unwrap();
+    let conversation = Conversation::new_direct(alice_id.clone(), bob_id.clone());
+    let mut message = Message::new(
+        conversation.id.clone().unwrap(),
+        alice_id.clone(),
+        "hola".to_string(),
+        None,
+    );
+    message.read_by.push(alice_id.clone());
+    s.messages.messages.lock().unwrap().push(message);
+
+    s.service
+        .schedule_deletion(s.users.as_ref(), &s.alice)
+        .await
+        .unwrap();
+    assert_eq!(s.service.purge_due().await, 1);
+
+    assert!(s.users.get(&s.alice.id_string().unwrap()).is_none());
+    assert!(s.users.get(&s.bob.id_string().unwrap()).is_some());
+    let messages = s.messages.messages.lock().unwrap();
+    assert_eq!(messages[0].sender_id, deleted_user_thing());
+    assert!(messages[0].read_by.is_empty());
+    assert_eq!(messages[0].content, "hola");
+
+    let actions: Vec<AuditAction> = s
+        .audit
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|e| e.action)
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            AuditAction::AccountDeletionScheduled,
+            AuditAction::AccountDeleted
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn purge_strips_the_deleted_user_from_tasks_projects_and_feeds() {
+    let s = setup(Duration::zero());
+    let alice_uuid = s.alice.id_string().unwrap();
+    let bob_uuid = s.bob.id_string().unwrap();
+    let d = &s.deletions;
+
+    let mut task = Task::new("task-1".to_string(), "Ship it".to_string());
+    task.creator = Some(alice_uuid.clone());
+    task.assignee = Some(alice_uuid.clone());
+    task.watchers = vec![alice_uuid.clone(), bob_uuid.clone()];
+    d.tasks.tasks.lock().unwrap().push(task.clone());
+    d.activity.comments.lock().unwrap().push(TaskComment::new(
+        task.uuid.clone(),
+        &s.alice,
+        "done soon".to_string(),
+        vec![],
+    ));
+    d.activity.activity.lock().unwrap().push(TaskActivity::new(
+        task.uuid.clone(),
+        &s.alice,
+        ActivityKind::Created,
+        None,
+        None,
+    ));
+    let mut project = Project::new("Launch".to_string(), bob_uuid.clone());
+    project.members.push(alice_uuid.clone());
+    d.boards.projects.lock().unwrap().push(project);
+    d.notifications
+        .notifications
+        .lock()
+        .unwrap()
+        .push(Notification::task_reminder(alice_uuid.clone(), &task, 15));
+    d.feeds.feeds.lock().unwrap().push(CalendarFeed {
+        user_id: alice_uuid.clone(),
+        token_hash: "hash".to_string(),
+        created_at: Utc::now(),
+    });
+
+    s.service
+        .schedule_deletion(s.users.as_ref(), &s.alice)
+        .await
+        .unwrap();
+    assert_eq!(s.service.purge_due().await, 1);
+
+    let tasks = d.tasks.tasks.lock().unwrap();
+    assert_eq!(tasks[0].creator.as_deref(), Some(DELETED_USER_ID));
+    assert_eq!(tasks[0].assignee, None);
+    assert_eq!(tasks[0].watchers, vec![bob_uuid]);
+    let comments = d.activity.comments.lock().unwrap();
+    assert_eq!(comments[0].author, DELETED_USER_ID);
+    assert_eq!(comments[0].author_name, DELETED_USER_NAME);
+    let activity = d.activity.activity.lock().unwrap();
+    assert_eq!(activity[0].actor, DELETED_USER_ID);
+    assert_eq!(activity[0].actor_name, DELETED_USER_NAME);
+    assert!(d.boards.projects.lock().unwrap()[0].members.is_empty());
+    assert!(d.notifications.notifications.lock().unwrap().is_empty());
+    assert!(d.feeds.feeds.lock().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn export_archive_holds_profile_conversations_and_own_messages() {
+    let alice = User::new_bot("alice".to_string());
+    let bob = User::new_bot("bob".to_string());
+    let alice_id = alice.id.clone().unwrap();
+    let bob_id = bob.id.clone().unwrap();
+    let conversation = Conversation::new_direct(alice_id.clone(), bob_id.clone());
+    let conversation_id = conversation.id.clone().unwrap();
+
+    let conversations = Arc::new(FakeConversations::default());
+    conversations
+        .conversations
+        .lock()
+        .unwrap()
+        .push(conversation);
+    let messages = Arc::new(FakeMessages::default());
+    messages.messages.lock().unwrap().extend([
+        Message::new(conversation_id.clone(), alice_id, "mine".to_string(), None),
+        Message::new(conversation_id, bob_id, "theirs".to_string(), None),
+    ]);
+    let exports = DataExportService::new(conversations, messages);
+
+    let archive = exports.export_zip(&alice).await.unwrap();
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+    let mut read_json = |name: &str| -> serde_json::Value {
+        let mut contents = String::new();
+        zip.by_name(name)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        serde_json::from_str(&contents).unwrap()
+    };
+
+    let profile = read_json("profile.json");
+    assert_eq!(profile["username"], "alice");
+    assert!(profile.get("password").is_none());
+    assert_eq!(read_json("conversations.json").as_array().unwrap().len(), 1);
+    let sent = read_json("messages.json");
+    assert_eq!(sent.as_array().unwrap().len(), 1);
+    assert_eq!(sent[0]["content"], "mine");
+}
@@ -1,2 +1 @@
-
-pub mod role_persistence_test;
\ No newline at end of file
+// `role_persistence_test`, `role_test`, `user_directory_test` and `account_deletion_test` run as their own [[test]] targets (see Cargo.toml).
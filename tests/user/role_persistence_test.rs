@@ -1,7 +1,7 @@
-use actix_crud::models::entities::user::User;
+use chasqui_server::models::entities::user::User;
 use uuid::Uuid;
 use mockall::predicate::*;
-use actix_crud::models::entities::role::roles;
+use chasqui_server::models::entities::role::roles;
 #[path = "../common/mocks/user_repository.rs"]
 mod user_repository;
 use user_repository::{UserRepository, MockUserRepository};
@@ -1,5 +1,6 @@
-use actix_crud::models::entities::role::Role;
-use actix_crud::models::entities::role::Permission;
+use chasqui_server::models::entities::role::Role;
+use chasqui_server::models::entities::role::Permission;
+use chasqui_server::models::entities::role::roles;
 
 #[test]
 fn test_role_creation() {
@@ -16,4 +17,71 @@ fn test_has_permission() {
     
     assert!(role.has_permission(Permission::AdminAll));
     assert!(role.has_permission(Permission::UserBan));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_permission_string_round_trip() {
+    let perms = [
+        Permission::AdminAll,
+        Permission::WorkspaceManageMembers,
+        Permission::ChannelSendMessages,
+        Permission::MessagePin,
+        Permission::UserBan,
+    ];
+    for perm in perms {
+        let s = perm.to_string();
+        assert_ne!(s, "unknown");
+        assert_eq!(s.parse::<Permission>(), Ok(perm));
+    }
+    assert!("bogus:perm".parse::<Permission>().is_err());
+}
+
+#[test]
+fn test_permission_serde_accepts_legacy_variant_names() {
+    let json = serde_json::to_string(&Permission::MessageDelete).unwrap();
+    assert_eq!(json, "\"message:delete\"");
+
+    let legacy: Permission = serde_json::from_str("\"MessageDelete\"").unwrap();
+    assert_eq!(legacy, Permission::MessageDelete);
+    assert!(serde_json::from_str::<Permission>("\"Nope\"").is_err());
+}
+
+#[test]
+fn test_builtin_roles_and_validation() {
+    let builtin = roles::builtin();
+    assert_eq!(builtin.len(), 3);
+    assert!(builtin.iter().all(|r| r.is_builtin() && r.is_valid()));
+
+    assert!(Role::new("support-team", "Support").is_valid());
+    assert!(!Role::new("Support Team", "Support").is_valid());
+    assert!(!Role::new("x", "Too short").is_valid());
+    assert!(!Role::new("support", "Support").is_builtin());
+}
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+
+#[actix_rt::test]
+async fn test_last_admin_keeps_admin_role() {
+    use chasqui_server::application::services::role_service::RoleService;
+    use chasqui_server::error::RoleError;
+    use chasqui_server::models::entities::user::User;
+
+    let mut alice = User::new_bot("alice".to_string());
+    alice.add_role(roles::admin());
+    let mut bob = User::new_bot("bob".to_string());
+    bob.add_role(roles::moderator());
+    let users = fakes::FakeUsers::with(vec![alice.clone(), bob.clone()]);
+
+    // Revoking admin from the only admin is refused, other changes are not
+    let demoted = RoleService::ensure_admin_remains(&users, &alice, &[roles::user()]).await;
+    assert!(matches!(demoted, Err(RoleError::LastAdmin)));
+    let extended = [roles::admin(), roles::moderator()];
+    assert!(RoleService::ensure_admin_remains(&users, &alice, &extended).await.is_ok());
+    assert!(RoleService::ensure_admin_remains(&users, &bob, &[]).await.is_ok());
+
+    // With a second admin the first one can step down
+    bob.add_role(roles::admin());
+    let users = fakes::FakeUsers::with(vec![alice.clone(), bob]);
+    assert!(RoleService::ensure_admin_remains(&users, &alice, &[]).await.is_ok());
+}
@@ -0,0 +1,109 @@
+//! User Directory Tests Module
+//! Exercises directory search, cursor paging and blocked-user filtering
+//! against in-memory fakes.
+
+use chasqui_server::application::services::user_directory_service::{
+    DirectoryQuery, UserDirectoryService,
+};
+use chasqui_server::error::AuthError;
+use chasqui_server::models::entities::user::User;
+use chasqui_server::models::traits::user_data_trait::UserDataTrait;
+
+#[path = "../common/fakes.rs"]
+mod fakes;
+use fakes::FakeUsers;
+
+fn setup() -> (UserDirectoryService, FakeUsers) {
+    let mut users: Vec<User> = ["alice", "alfred", "albert", "bob"]
+        .iter()
+        .map(|name| User::new_bot(name.to_string()))
+        .collect();
+    users[3].profile.display_name = Some("Alan B.".to_string());
+    users.push(User::new_from_wallet("0xABCDEF1234567890".to_string()));
+    (UserDirectoryService::new(2, 10), FakeUsers::with(users))
+}
+
+fn names(users: &[User]) -> Vec<&str> {
+    users.iter().map(|u| u.username.as_str()).collect()
+}
+
+#[actix_rt::test]
+async fn prefix_search_pages_with_cursor() {
+    let (directory, users) = setup();
+    let query = DirectoryQuery {
+        q: Some("AL".to_string()),
+        ..DirectoryQuery::default()
+    };
+
+    let first = directory.search(&users, query.clone()).await.unwrap();
+    assert_eq!(names(&first.items), vec!["albert", "alfred"]);
+    let cursor = first.next_cursor.expect("more results");
+
+    let second = directory
+        .search(
+            &users,
+            DirectoryQuery {
+                cursor: Some(cursor),
+                ..query
+            },
+        )
+        .await
+        .unwrap();
+    // "bob" matches through its display name
+    assert_eq!(names(&second.items), vec!["alice", "bob"]);
+    assert_eq!(second.next_cursor, None);
+}
+
+#[actix_rt::test]
+async fn wallet_matches_exactly_and_blocked_users_are_hidden() {
+    let (directory, users) = setup();
+    let page = directory
+        .search(
+            &users,
+            DirectoryQuery {
+                wallet: Some("0xabcdef1234567890".to_string()),
+                ..DirectoryQuery::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+
+    let alice = users.users.lock().unwrap()[0].id_string().unwrap();
+    assert!(users.set_blocked(&alice, true).await.unwrap().blocked);
+    let page = directory
+        .search(
+            &users,
+            DirectoryQuery {
+                q: Some("alice".to_string()),
+                ..DirectoryQuery::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+}
+
+#[actix_rt::test]
+async fn invalid_limit_and_cursor_are_rejected() {
+    let (directory, users) = setup();
+    for query in [
+        DirectoryQuery {
+            limit: Some(0),
+            ..DirectoryQuery::default()
+        },
+        DirectoryQuery {
+            limit: Some(11),
+            ..DirectoryQuery::default()
+        },
+        DirectoryQuery {
+            cursor: Some("%%%".to_string()),
+            ..DirectoryQuery::default()
+        },
+    ] {
+        assert!(matches!(
+            directory.search(&users, query).await,
+            Err(AuthError::InvalidSearch(_))
+        ));
+    }
+}